@@ -14,6 +14,9 @@ pub enum DumpFileError {
 
 impl From<DumpFileError> for std::io::Error {
     fn from(err: DumpFileError) -> Self {
-        std::io::Error::new(ErrorKind::Other, format!("{:?}", err))
+        match err {
+            DumpFileError::ReadError(err) => err,
+            err => std::io::Error::new(ErrorKind::Other, format!("{:?}", err)),
+        }
     }
 }
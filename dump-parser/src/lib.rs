@@ -4,8 +4,10 @@ use crate::errors::DumpFileError;
 
 pub mod errors;
 pub mod mongodb;
+pub mod mssql;
 pub mod mysql;
 pub mod postgres;
+pub mod sqlite;
 pub mod utils;
 
 #[derive(Debug, PartialOrd, PartialEq, Ord, Eq)]
@@ -1,5 +1,5 @@
 use bson::Document;
-use crc::crc64;
+use crc::crc64::{self, ECMA_TABLE};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::{BufReader, Error, ErrorKind, Read};
@@ -248,11 +248,173 @@ impl Archive {
     }
 }
 
+/// Stream a mongodump archive one document at a time, instead of loading the whole archive in memory.
+///
+/// `alter_doc` gets the prefix (`<db_name>.<collection_name>`) and the documents of the collections, it
+/// returns the document to write instead, or `None` to remove it. `write` gets the original bytes of each
+/// part of the archive and the bytes to write instead. The CRC of the collections are updated.
+pub fn stream_archive<R, A, W>(
+    mut reader: BufReader<R>,
+    mut alter_doc: A,
+    mut write: W,
+) -> Result<(), Error>
+where
+    R: Read,
+    A: FnMut(&str, Document) -> Option<Document>,
+    W: FnMut(&[u8], &[u8]),
+{
+    let mut buf: [u8; 4] = [0; 4];
+
+    // read magic bytes
+    reader.read_exact(&mut buf)?;
+    if buf != MAGIC_BYTES {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "Stream or file does not appear to be a mongodump archive",
+        ));
+    }
+    write(&MAGIC_BYTES, &MAGIC_BYTES);
+
+    // read namespace header
+    let header = read_raw_doc(&mut reader)?
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "Missing archive header"))?;
+    let _: Header = bson::from_slice(&header)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, format!("{}", e)))?;
+    write(&header, &header);
+
+    // read metadata headers, up to the seperator
+    let mut num_blocks = 0;
+    while let Some(metadata_doc) = read_raw_doc(&mut reader)? {
+        write(&metadata_doc, &metadata_doc);
+        num_blocks += 1;
+    }
+    write(&SEPERATOR_BYTES, &SEPERATOR_BYTES);
+
+    // crc64 checksums of the written docs, the blocks of the collections can be interleaved
+    let mut crc64_checksums: HashMap<Prefix, u64> = HashMap::new();
+    let mut num_eofs = 0;
+
+    // when we've seen as much EOFs as there are blocks, we're done.
+    while num_eofs < num_blocks {
+        let namespace_bytes = read_raw_doc(&mut reader)?
+            .ok_or_else(|| Error::new(ErrorKind::Other, "Error reading block header"))?;
+        let mut namespace_doc: Namespace = bson::from_slice(&namespace_bytes).map_err(|err| {
+            Error::new(
+                ErrorKind::Other,
+                format!("Error reading block header: {}", err),
+            )
+        })?;
+        let prefix = format!("{}.{}", namespace_doc.db, namespace_doc.collection);
+
+        if namespace_doc.eof {
+            // the footer of the collection holds the checksum of all its docs
+            num_eofs += 1;
+            namespace_doc.crc = crc64_checksums.get(&prefix).copied().unwrap_or(0) as i64;
+
+            let mut footer = vec![];
+            bson::to_document(&namespace_doc)
+                .unwrap()
+                .to_writer(&mut footer)
+                .map_err(|err| {
+                    Error::new(
+                        ErrorKind::Other,
+                        format!("Error writing block header: {}", err),
+                    )
+                })?;
+            write(&namespace_bytes, &footer);
+        } else {
+            write(&namespace_bytes, &namespace_bytes);
+        }
+
+        // read block data
+        while let Some(doc_bytes) = read_raw_doc(&mut reader)? {
+            let doc = Document::from_reader(doc_bytes.as_slice()).map_err(|err| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Error reading prefixed doc: {}", err),
+                )
+            })?;
+
+            let new_doc = match alter_doc(prefix.as_str(), doc) {
+                Some(new_doc) => new_doc,
+                None => {
+                    write(&doc_bytes, &[]);
+                    continue;
+                }
+            };
+
+            let mut new_doc_bytes = Vec::with_capacity(doc_bytes.len());
+            new_doc.to_writer(&mut new_doc_bytes).map_err(|err| {
+                Error::new(
+                    ErrorKind::Other,
+                    format!("Error writing prefixed doc: {}", err),
+                )
+            })?;
+
+            let crc64_checksum = crc64_checksums.entry(prefix.clone()).or_insert(0);
+            *crc64_checksum = crc64::update(*crc64_checksum, &ECMA_TABLE, &new_doc_bytes);
+
+            write(&doc_bytes, &new_doc_bytes);
+        }
+        write(&SEPERATOR_BYTES, &SEPERATOR_BYTES);
+    }
+
+    Ok(())
+}
+
+/// bytes of the next BSON document, `None` if the next bytes are a seperator
+fn read_raw_doc<R: Read>(reader: &mut R) -> Result<Option<Vec<u8>>, Error> {
+    let mut buf: [u8; 4] = [0; 4];
+    reader.read_exact(&mut buf)?;
+    if buf == SEPERATOR_BYTES {
+        return Ok(None);
+    }
+
+    // the length of a document includes its length and its trailing null byte
+    let len = i32::from_le_bytes(buf);
+    if len < 5 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Invalid BSON document length: {}", len),
+        ));
+    }
+
+    let mut doc = vec![0; len as usize];
+    doc[..4].copy_from_slice(&buf);
+    reader.read_exact(&mut doc[4..])?;
+
+    Ok(Some(doc))
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{mongodb::Archive, utils::decode_hex};
+    use crate::{
+        mongodb::{stream_archive, Archive},
+        utils::decode_hex,
+    };
+    use crc::crc64;
     use std::{fmt::Write, io::BufReader};
 
+    // a single collection "Users" in db "test2" with a single document: {name: "John", age: 42}
+    const DUMP_STR: &str = "6de299816600000010636f6e63757272656e745f636f6c6c656374696f6e7300040000000276657273696f6e0004000000302e3100027365727665725f76657273696f6e0006000000352e302e360002746f6f6c5f76657273696f6e00080000003130302e352e32000003010000026462000600000074657374320002636f6c6c656374696f6e0006000000557365727300026d6574616461746100ad0000007b22696e6465786573223a5b7b2276223a7b22246e756d626572496e74223a2232227d2c226b6579223a7b225f6964223a7b22246e756d626572496e74223a2231227d7d2c226e616d65223a225f69645f227d5d2c2275756964223a223732306531616132326231373435643739663139373530626162323933303837222c22636f6c6c656374696f6e4e616d65223a225573657273222c2274797065223a22636f6c6c656374696f6e227d001073697a6500000000000274797065000b000000636f6c6c656374696f6e0000ffffffff3c000000026462000600000074657374320002636f6c6c656374696f6e000600000055736572730008454f46000012435243000000000000000000002e000000075f696400623f23928e7f1feed4d5e3e1026e616d6500050000004a6f686e0010616765002a00000000ffffffff3c000000026462000600000074657374320002636f6c6c656374696f6e000600000055736572730008454f4600011243524300ff2a87dec3c86e6e00ffffffff";
+
+    fn stream(alter_doc: fn(&str, bson::Document) -> Option<bson::Document>) -> (Vec<u8>, Vec<u8>) {
+        let hexdump = decode_hex(DUMP_STR).unwrap();
+        let mut original = vec![];
+        let mut altered = vec![];
+        stream_archive(
+            BufReader::new(hexdump.as_slice()),
+            alter_doc,
+            |original_bytes, bytes| {
+                original.extend_from_slice(original_bytes);
+                altered.extend_from_slice(bytes);
+            },
+        )
+        .unwrap();
+
+        (original, altered)
+    }
+
     #[test]
     fn mongo_archive_parsing() {
         // archive should contain a single collection "Users" in db "test2" with a single document: {name: "John", age: 42}
@@ -287,4 +449,53 @@ mod tests {
         }
         assert_eq!(out.as_str(), dump_str);
     }
+
+    #[test]
+    fn mongo_archive_streaming() {
+        let hexdump = decode_hex(DUMP_STR).unwrap();
+
+        // the archive is written as is when its docs are not altered
+        let (original, altered) = stream(|_, doc| Some(doc));
+        assert_eq!(original, hexdump);
+        assert_eq!(altered, hexdump);
+
+        let (original, altered) = stream(|prefix, mut doc| {
+            assert_eq!(prefix, "test2.Users");
+            let _ = doc.insert("name", "Jane");
+            Some(doc)
+        });
+        assert_eq!(original, hexdump);
+        let archive = Archive::from_reader(BufReader::new(altered.as_slice())).unwrap();
+        let doc = archive.prefixed_collections.get("test2.Users").unwrap()[0].clone();
+        assert_eq!(doc.get_str("name").unwrap(), "Jane");
+        assert_eq!(doc.get_i32("age").unwrap(), 42);
+
+        // the checksum of the collection is updated in its footer
+        let mut doc_bytes = vec![];
+        doc.to_writer(&mut doc_bytes).unwrap();
+        let footer = archive.namespace_docs.iter().find(|ns| ns.eof).unwrap();
+        assert_eq!(footer.crc, crc64::checksum_ecma(&doc_bytes) as i64);
+
+        let (_, altered) = stream(|_, _| None);
+        let archive = Archive::from_reader(BufReader::new(altered.as_slice())).unwrap();
+        assert!(archive
+            .prefixed_collections
+            .get("test2.Users")
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            archive.namespace_docs.iter().find(|ns| ns.eof).unwrap().crc,
+            0
+        );
+
+        // truncated archive
+        let truncated = &hexdump[..hexdump.len() - 20];
+        assert!(stream_archive(BufReader::new(truncated), |_, doc| Some(doc), |_, _| {}).is_err());
+        assert!(stream_archive(
+            BufReader::new(&b"not an archive"[..]),
+            |_, doc| Some(doc),
+            |_, _| {}
+        )
+        .is_err());
+    }
 }
@@ -0,0 +1,330 @@
+// SQL Server dumps are made of plain T-SQL statements that can be tokenized with the PostgreSQL tokenizer.
+// Only the bracket quoted identifiers (which can contain any char) are handled here.
+pub use crate::postgres::{
+    match_keyword_at_position, trim_pre_whitespaces, Keyword, Token, Tokenizer, Whitespace, Word,
+};
+
+/// Value of a column inside an `INSERT INTO ... VALUES (...)` statement
+#[derive(Debug, PartialEq, Clone)]
+pub enum Value {
+    /// Integer, decimal or float number, with its sign and exponent
+    Number(String),
+    /// Text value: i.e.: N'text' or 'text'
+    String(String),
+    /// Hexadecimal binary value, without its 0x prefix: i.e.: 0xDEADBEEF
+    Binary(String),
+    Null,
+}
+
+/// Schema and table name of a `CREATE TABLE` or an `INSERT INTO` query
+#[derive(Debug, PartialEq, Clone)]
+pub struct TableName {
+    pub schema: Option<String>,
+    pub value: String,
+}
+
+impl TableName {
+    /// table name with its schema, both quoted with brackets
+    pub fn quoted(&self) -> String {
+        match &self.schema {
+            Some(schema) => format!(
+                "{}.{}",
+                quote_identifier(schema),
+                quote_identifier(&self.value)
+            ),
+            None => quote_identifier(&self.value),
+        }
+    }
+}
+
+/// quote an identifier with brackets, the closing bracket is escaped by doubling it
+pub fn quote_identifier(identifier: &str) -> String {
+    format!("[{}]", identifier.replace(']', "]]"))
+}
+
+pub fn get_tokens_from_query_str(query: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut segment_start_idx = 0;
+    let mut is_single_quoted_string = false;
+    let mut chars = query.char_indices().peekable();
+
+    while let Some((idx, ch)) = chars.next() {
+        match ch {
+            '\'' => is_single_quoted_string = !is_single_quoted_string,
+            '[' if !is_single_quoted_string => {
+                tokens.append(&mut tokenize(&query[segment_start_idx..idx]));
+
+                let mut identifier = String::new();
+                segment_start_idx = query.len();
+
+                while let Some((idx, ch)) = chars.next() {
+                    if ch == ']' {
+                        if let Some((_, ']')) = chars.peek() {
+                            // escaped closing bracket
+                            let _ = chars.next();
+                        } else {
+                            segment_start_idx = idx + 1;
+                            break;
+                        }
+                    }
+
+                    identifier.push(ch);
+                }
+
+                tokens.push(Token::Word(Word {
+                    value: identifier,
+                    quote_style: Some('['),
+                    keyword: Keyword::NoKeyword,
+                }));
+            }
+            _ => {}
+        }
+    }
+
+    if segment_start_idx < query.len() {
+        tokens.append(&mut tokenize(&query[segment_start_idx..]));
+    }
+
+    trim_pre_whitespaces(tokens)
+}
+
+fn tokenize(query: &str) -> Vec<Token> {
+    let mut tokenizer = Tokenizer::new(query);
+
+    match tokenizer.tokenize() {
+        Ok(tokens) => tokens,
+        Err(err) => {
+            println!("failing query: '{}'", query);
+            panic!("{:?}", err)
+        }
+    }
+}
+
+pub fn is_create_table_query(tokens: &Vec<Token>) -> bool {
+    match_keyword_at_position(Keyword::Create, tokens, 0)
+        && match_keyword_at_position(Keyword::Table, tokens, 2)
+}
+
+pub fn is_insert_into_query(tokens: &Vec<Token>) -> bool {
+    match_keyword_at_position(Keyword::Insert, tokens, 0)
+        && match_keyword_at_position(Keyword::Into, tokens, 2)
+}
+
+/// return the table name of a `CREATE TABLE [<schema>.]<table> (...)` query
+pub fn get_table_name_from_create_table_query(tokens: &Vec<Token>) -> Option<TableName> {
+    if !is_create_table_query(tokens) {
+        return None;
+    }
+
+    get_table_name_at_position(tokens, 4)
+}
+
+/// return the table name of an `INSERT INTO [<schema>.]<table> ...` query
+pub fn get_table_name_from_insert_into_query(tokens: &Vec<Token>) -> Option<TableName> {
+    if !is_insert_into_query(tokens) {
+        return None;
+    }
+
+    get_table_name_at_position(tokens, 4)
+}
+
+fn get_table_name_at_position(tokens: &Vec<Token>, pos: usize) -> Option<TableName> {
+    match (tokens.get(pos), tokens.get(pos + 1), tokens.get(pos + 2)) {
+        (Some(Token::Word(schema)), Some(Token::Period), Some(Token::Word(table))) => {
+            Some(TableName {
+                schema: Some(schema.value.clone()),
+                value: table.value.clone(),
+            })
+        }
+        (Some(Token::Word(table)), _, _) => Some(TableName {
+            schema: None,
+            value: table.value.clone(),
+        }),
+        _ => None,
+    }
+}
+
+/// return the column names of an `INSERT INTO <table> (<columns>) VALUES (...)` query
+pub fn get_column_names_from_insert_into_query(tokens: &Vec<Token>) -> Vec<String> {
+    if !is_insert_into_query(tokens) {
+        return Vec::new();
+    }
+
+    tokens
+        .iter()
+        .skip_while(|token| **token != Token::LParen)
+        .skip(1)
+        .take_while(|token| **token != Token::RParen)
+        .filter_map(|token| match token {
+            Token::Word(word) => Some(word.value.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// return the values of an `INSERT INTO ... VALUES (...)` query,
+/// or None if a value is an expression that can't be decoded
+pub fn get_column_values_from_insert_into_query(tokens: &Vec<Token>) -> Option<Vec<Value>> {
+    if !is_insert_into_query(tokens) {
+        return None;
+    }
+
+    let values_idx = tokens.iter().position(|token| match token {
+        Token::Word(word) => word.quote_style.is_none() && word.value.to_uppercase() == "VALUES",
+        _ => false,
+    })?;
+
+    let values_tokens = tokens
+        .iter()
+        .skip(values_idx + 1)
+        .skip_while(|token| matches!(token, Token::Whitespace(_)))
+        .collect::<Vec<_>>();
+
+    if values_tokens.first() != Some(&&Token::LParen) {
+        return None;
+    }
+
+    let mut values = vec![];
+    let mut value_tokens = vec![];
+
+    for token in values_tokens.into_iter().skip(1) {
+        match token {
+            Token::Comma | Token::RParen => {
+                values.push(parse_value(&value_tokens)?);
+                value_tokens.clear();
+
+                if *token == Token::RParen {
+                    return Some(values);
+                }
+            }
+            Token::Whitespace(_) => {}
+            // nested expressions are not supported
+            Token::LParen => return None,
+            token => value_tokens.push(token),
+        }
+    }
+
+    None
+}
+
+fn parse_value(tokens: &[&Token]) -> Option<Value> {
+    let (sign, tokens) = match tokens {
+        [Token::Minus, tokens @ ..] => ("-", tokens),
+        [Token::Plus, tokens @ ..] => ("", tokens),
+        tokens => ("", tokens),
+    };
+
+    match tokens {
+        [Token::Number(value, _)] => Some(Value::Number(format!("{}{}", sign, value))),
+        // float with an exponent: i.e.: 1.5E+10, the tokenizer splits it
+        [Token::Number(value, _), Token::Word(exponent), exponent_sign, Token::Number(exponent_value, _)]
+            if exponent.value.to_uppercase() == "E"
+                && matches!(exponent_sign, Token::Plus | Token::Minus) =>
+        {
+            Some(Value::Number(format!(
+                "{}{}E{}{}",
+                sign,
+                value,
+                if **exponent_sign == Token::Minus {
+                    "-"
+                } else {
+                    "+"
+                },
+                exponent_value
+            )))
+        }
+        [Token::Number(value, _), Token::Word(exponent)]
+            if exponent.value.to_uppercase().starts_with('E')
+                && exponent.value[1..].chars().all(|ch| ch.is_ascii_digit()) =>
+        {
+            Some(Value::Number(format!(
+                "{}{}{}",
+                sign, value, exponent.value
+            )))
+        }
+        _ if !sign.is_empty() => None,
+        [Token::SingleQuotedString(value)] | [Token::NationalStringLiteral(value)] => {
+            Some(Value::String(value.clone()))
+        }
+        [Token::HexStringLiteral(value)] => Some(Value::Binary(value.clone())),
+        [Token::Word(word)] if word.keyword == Keyword::Null => Some(Value::Null),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mssql::{
+        get_column_names_from_insert_into_query, get_column_values_from_insert_into_query,
+        get_table_name_from_create_table_query, get_table_name_from_insert_into_query,
+        get_tokens_from_query_str, TableName, Value,
+    };
+
+    #[test]
+    fn create_table_query() {
+        let q = "CREATE TABLE [dbo].[user accounts] ([id] int IDENTITY(1,1) NOT NULL, [name] nvarchar(255) NULL);";
+        let tokens = get_tokens_from_query_str(q);
+
+        let table_name = get_table_name_from_create_table_query(&tokens).unwrap();
+        assert_eq!(
+            table_name,
+            TableName {
+                schema: Some("dbo".to_string()),
+                value: "user accounts".to_string(),
+            }
+        );
+        assert_eq!(table_name.quoted(), "[dbo].[user accounts]");
+    }
+
+    #[test]
+    fn insert_into_query() {
+        let q = "INSERT INTO [dbo].[users] ([id], [first]]name], [height], [ratio], [avatar], [note], [bio]) \
+VALUES (1, N'it''s [not] an identifier', -1.78, 1.5E+10, 0xCAFE, NULL, 'plain');";
+        let tokens = get_tokens_from_query_str(q);
+
+        assert_eq!(
+            get_table_name_from_insert_into_query(&tokens),
+            Some(TableName {
+                schema: Some("dbo".to_string()),
+                value: "users".to_string(),
+            })
+        );
+
+        assert_eq!(
+            get_column_names_from_insert_into_query(&tokens),
+            vec![
+                "id",
+                "first]name",
+                "height",
+                "ratio",
+                "avatar",
+                "note",
+                "bio"
+            ]
+        );
+
+        assert_eq!(
+            get_column_values_from_insert_into_query(&tokens),
+            Some(vec![
+                Value::Number("1".to_string()),
+                Value::String("it's [not] an identifier".to_string()),
+                Value::Number("-1.78".to_string()),
+                Value::Number("1.5E+10".to_string()),
+                Value::Binary("CAFE".to_string()),
+                Value::Null,
+                Value::String("plain".to_string()),
+            ])
+        );
+
+        let q = "INSERT INTO users (id, created_at) VALUES (1, GETDATE());";
+        let tokens = get_tokens_from_query_str(q);
+        assert_eq!(
+            get_table_name_from_insert_into_query(&tokens),
+            Some(TableName {
+                schema: None,
+                value: "users".to_string(),
+            })
+        );
+        assert_eq!(get_column_values_from_insert_into_query(&tokens), None);
+    }
+}
@@ -11,7 +11,7 @@ use crate::mysql::Keyword::{
 pub enum Token {
     /// An end-of-file marker, not a real token
     EOF,
-    /// An unsigned numeric literal
+    /// An unsigned numeric literal, true if it is negative (only in the values of the rows, see [get_rows_from_insert_into_query])
     Number(String, bool),
     /// TABLE instruction
     Word(Word),
@@ -23,8 +23,12 @@ pub enum Token {
     SingleQuotedString(String),
     /// "National" string literal: i.e: N'string'
     NationalStringLiteral(String),
-    /// Hexadecimal string literal: i.e.: X'deadbeef'
+    /// Hexadecimal string literal: i.e.: X'deadbeef' or 0xdeadbeef
     HexStringLiteral(String),
+    /// Binary string literal: i.e.: _binary 'string'
+    BinaryStringLiteral(String),
+    /// Bit value literal: i.e.: b'0101'
+    BitStringLiteral(String),
     /// Comma
     Comma,
     /// Double equals sign `==`
@@ -298,11 +302,32 @@ impl<'a> Tokenizer<'a> {
                         }
                     }
                 }
+                b @ 'b' | b @ 'B' => {
+                    chars.next(); // consume, to check the next char
+                    match chars.peek() {
+                        Some('\'') => {
+                            // b'...' - a bit value literal
+                            let s = self.tokenize_single_quoted_string(chars)?;
+                            Ok(Some(Token::BitStringLiteral(s)))
+                        }
+                        _ => {
+                            // regular identifier starting with a "B"
+                            let s = self.tokenize_word(b, chars);
+                            Ok(Some(Token::make_word(&s, None)))
+                        }
+                    }
+                }
                 // identifier or keyword
                 ch if is_identifier_start(ch) => {
                     chars.next(); // consume the first char
                     let s = self.tokenize_word(ch, chars);
 
+                    if s.eq_ignore_ascii_case("_binary") {
+                        if let Some(token) = self.tokenize_binary_string(chars)? {
+                            return Ok(Some(token));
+                        }
+                    }
+
                     if s.chars().all(|x| ('0'..='9').contains(&x) || x == '.') {
                         let mut s = peeking_take_while(&mut s.chars().peekable(), |ch| {
                             matches!(ch, '0'..='9' | '.')
@@ -323,7 +348,7 @@ impl<'a> Tokenizer<'a> {
                 '0'..='9' | '.' => {
                     let mut s = peeking_take_while(chars, |ch| matches!(ch, '0'..='9'));
 
-                    // match binary literal that starts with 0x
+                    // match hexadecimal literal that starts with 0x
                     if s == "0" && chars.peek() == Some(&'x') {
                         chars.next();
                         let s2 = peeking_take_while(
@@ -345,13 +370,27 @@ impl<'a> Tokenizer<'a> {
                         return Ok(Some(Token::Period));
                     }
 
-                    let long = if chars.peek() == Some(&'L') {
-                        chars.next();
-                        true
-                    } else {
-                        false
-                    };
-                    Ok(Some(Token::Number(s, long)))
+                    // match an exponent, e.g. 1.5e-5
+                    if let Some('e') | Some('E') = chars.peek() {
+                        let mut lookahead = chars.clone();
+                        let mut exponent = lookahead.next().unwrap().to_string();
+                        if let Some(&sign) = lookahead.peek() {
+                            if sign == '+' || sign == '-' {
+                                exponent.push(sign);
+                                lookahead.next();
+                            }
+                        }
+
+                        let exponent_value =
+                            peeking_take_while(&mut lookahead, |ch| matches!(ch, '0'..='9'));
+                        if !exponent_value.is_empty() {
+                            s += &exponent;
+                            s += &exponent_value;
+                            *chars = lookahead;
+                        }
+                    }
+
+                    Ok(Some(Token::Number(s, false)))
                 }
                 // punctuation
                 '(' => self.consume_and_return(chars, Token::LParen),
@@ -497,39 +536,60 @@ impl<'a> Tokenizer<'a> {
         s
     }
 
-    /// Read a single quoted string, starting with the opening quote.
+    /// Read the string following a `_binary` introducer, or None if it is not followed by a string.
+    /// `_binary 0x...` and `_binary X'...'` are hexadecimal literals.
+    fn tokenize_binary_string(
+        &self,
+        chars: &mut Peekable<Chars<'_>>,
+    ) -> Result<Option<Token>, TokenizerError> {
+        let mut lookahead = chars.clone();
+        let _ = peeking_take_while(&mut lookahead, |ch| ch == ' ');
+
+        let token = match lookahead.peek() {
+            Some('\'') => {
+                let s = self.tokenize_single_quoted_string(&mut lookahead)?;
+                Token::BinaryStringLiteral(s)
+            }
+            Some('0') | Some('x') | Some('X') => match self.next_token(&mut lookahead)? {
+                Some(token @ Token::HexStringLiteral(_)) => token,
+                _ => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+
+        *chars = lookahead;
+        Ok(Some(token))
+    }
+
+    /// Read a quoted string or identifier, starting with the opening quote ' or `.
+    /// A doubled quote is an escaped quote. In a string, the backslash escape sequences are processed.
     fn tokenize_single_quoted_string(
         &self,
         chars: &mut Peekable<Chars<'_>>,
     ) -> Result<String, TokenizerError> {
         let mut s = String::new();
-        chars.next(); // consume the opening quote
+        let quote = match chars.next() {
+            Some(quote) => quote, // consume the opening quote
+            None => return self.tokenizer_error("Unterminated string literal"),
+        };
 
-        // slash escaping is specific to some dialect
-        let mut is_escaped = false;
-        while let Some(&ch) = chars.peek() {
+        while let Some(ch) = chars.next() {
             match ch {
-                '\'' | '`' => {
-                    chars.next(); // consume
-
-                    if let Some(next_char) = chars.peek() {
-                        if ch != '`' && *next_char != ')' && *next_char != ',' {
-                            is_escaped = true;
-                            s.push(ch);
-                        } else {
-                            return Ok(s);
-                        }
+                ch if ch == quote => match chars.peek() {
+                    Some(&next_char) if next_char == quote => {
+                        chars.next(); // consume the escaped quote
+                        s.push(quote);
                     }
-
-                    if !is_escaped {
-                        chars.next(); // consume
-                        s.push(ch);
-                    }
-                }
-                _ => {
-                    chars.next(); // consume
-                    s.push(ch);
-                }
+                    None | Some(' ' | '\t' | '\r' | '\n' | ',' | ')' | ';') => return Ok(s),
+                    // not followed by a separator: a quote not escaped in the string, i.e. 'People's Republic'
+                    Some(_) if quote == '\'' => s.push(ch),
+                    Some(_) => return Ok(s),
+                },
+                '\\' if quote == '\'' => match chars.next() {
+                    Some(escaped_char) => unescape_char(escaped_char, &mut s),
+                    None => break,
+                },
+                _ => s.push(ch),
             }
         }
 
@@ -574,6 +634,42 @@ impl<'a> Tokenizer<'a> {
     }
 }
 
+/// push the char escaped by a backslash, see https://dev.mysql.com/doc/refman/8.0/en/string-literals.html
+fn unescape_char(escaped_char: char, s: &mut String) {
+    match escaped_char {
+        '0' => s.push('\0'),
+        'b' => s.push('\u{8}'),
+        'n' => s.push('\n'),
+        'r' => s.push('\r'),
+        't' => s.push('\t'),
+        'Z' => s.push('\u{1a}'),
+        // kept escaped, they are wildcards in the LIKE patterns only
+        '%' | '_' => {
+            s.push('\\');
+            s.push(escaped_char);
+        }
+        other => s.push(other),
+    }
+}
+
+/// Escape a string value to write it between single quotes
+pub fn escape_string(value: &str) -> String {
+    let mut s = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '\0' => s.push_str("\\0"),
+            '\n' => s.push_str("\\n"),
+            '\r' => s.push_str("\\r"),
+            '\u{1a}' => s.push_str("\\Z"),
+            '\'' => s.push_str("\\'"),
+            '\\' => s.push_str("\\\\"),
+            other => s.push(other),
+        }
+    }
+
+    s
+}
+
 fn is_identifier_start(ch: char) -> bool {
     // See https://www.postgresql.org/docs/14/sql-syntax-lexical.html#SQL-SYNTAX-IDENTIFIERS
     // We don't yet support identifiers beginning with "letters with
@@ -650,8 +746,12 @@ pub fn get_column_names_from_insert_into_query(tokens: &Vec<Token>) -> Vec<&str>
         return Vec::new();
     }
 
+    // the column names are optional, they are before the VALUES keyword
+    let values_idx = get_values_keyword_position(tokens).unwrap_or(tokens.len());
+
     tokens
         .iter()
+        .take(values_idx)
         .skip_while(|token| match **token {
             Token::LParen => false,
             _ => true,
@@ -668,7 +768,77 @@ pub fn get_column_names_from_insert_into_query(tokens: &Vec<Token>) -> Vec<&str>
         .collect::<Vec<_>>()
 }
 
-pub fn get_column_values_from_insert_into_query(tokens: &Vec<Token>) -> Vec<&Token> {
+/// return the column names declared in a `CREATE TABLE` query, the keys and constraints are ignored
+pub fn get_column_names_from_create_table_query(tokens: &Vec<Token>) -> Vec<&str> {
+    if !match_keyword_at_position(Keyword::Create, &tokens, 0)
+        || !match_keyword_at_position(Keyword::Table, &tokens, 2)
+    {
+        return Vec::new();
+    }
+
+    let column_definitions = match tokens.iter().position(|token| *token == Token::LParen) {
+        Some(idx) => &tokens[idx + 1..],
+        None => return Vec::new(),
+    };
+
+    let mut column_names = vec![];
+    let mut depth = 0;
+    let mut is_start_of_definition = true;
+
+    for token in column_definitions {
+        match token {
+            Token::LParen => depth += 1,
+            Token::RParen if depth == 0 => break,
+            Token::RParen => depth -= 1,
+            Token::Comma if depth == 0 => is_start_of_definition = true,
+            Token::Whitespace(_) => {}
+            // `name` int NOT NULL
+            Token::SingleQuotedString(name) if is_start_of_definition && depth == 0 => {
+                is_start_of_definition = false;
+                column_names.push(name.as_str());
+            }
+            // name int NOT NULL, but not PRIMARY KEY (...), KEY ..., CONSTRAINT ...
+            Token::Word(word) if is_start_of_definition && depth == 0 => {
+                is_start_of_definition = false;
+
+                let is_key_definition = [
+                    "PRIMARY",
+                    "KEY",
+                    "INDEX",
+                    "UNIQUE",
+                    "FULLTEXT",
+                    "SPATIAL",
+                    "CONSTRAINT",
+                    "FOREIGN",
+                    "CHECK",
+                ]
+                .iter()
+                .any(|keyword| word.value.eq_ignore_ascii_case(keyword));
+
+                if !is_key_definition {
+                    column_names.push(word.value.as_str());
+                }
+            }
+            _ => is_start_of_definition = false,
+        }
+    }
+
+    column_names
+}
+
+/// values of the first row of the query, see [get_rows_from_insert_into_query]
+pub fn get_column_values_from_insert_into_query(tokens: &Vec<Token>) -> Vec<Token> {
+    get_rows_from_insert_into_query(tokens)
+        .into_iter()
+        .next()
+        .unwrap_or_default()
+}
+
+/// values of all the rows of the query, an extended INSERT having many rows:
+/// `INSERT INTO `table` VALUES (1,'a'),(-2,_binary 'b');`
+/// A value is a single token, a negative number being a `Token::Number(value, true)`.
+/// The expressions are not supported, only their first token is kept.
+pub fn get_rows_from_insert_into_query(tokens: &Vec<Token>) -> Vec<Vec<Token>> {
     if !match_keyword_at_position(Keyword::Insert, &tokens, 0)
         || !match_keyword_at_position(Keyword::Into, &tokens, 2)
     {
@@ -676,25 +846,87 @@ pub fn get_column_values_from_insert_into_query(tokens: &Vec<Token>) -> Vec<&Tok
         return Vec::new();
     }
 
-    tokens
-        .iter()
-        .skip_while(|token| match **token {
-            Token::RParen => false,
-            _ => true,
-        })
-        .skip_while(|token| match **token {
-            Token::LParen => false,
-            _ => true,
-        })
-        .take_while(|token| match **token {
-            Token::RParen => false,
-            _ => true,
-        })
-        .filter_map(|token| match token {
-            Token::Comma | Token::Whitespace(_) | Token::LParen | Token::RParen => None,
-            token => Some(token), // column value
-        })
-        .collect::<Vec<_>>()
+    let values_idx = match get_values_keyword_position(tokens) {
+        Some(values_idx) => values_idx,
+        None => return Vec::new(),
+    };
+
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut value = vec![];
+    let mut depth = 0usize;
+
+    for token in tokens.iter().skip(values_idx + 1) {
+        match token {
+            Token::LParen => {
+                if depth > 0 {
+                    value.push(token);
+                }
+                depth += 1;
+            }
+            Token::RParen if depth == 1 => {
+                // `VALUES ()` is a row without values
+                if !value.is_empty() || !row.is_empty() {
+                    row.push(to_value_token(&value));
+                }
+                value.clear();
+                rows.push(row);
+                row = vec![];
+                depth = 0;
+            }
+            Token::RParen if depth > 1 => {
+                value.push(token);
+                depth -= 1;
+            }
+            Token::Comma if depth == 1 => {
+                row.push(to_value_token(&value));
+                value.clear();
+            }
+            Token::Whitespace(_) => {}
+            token if depth > 0 => value.push(token),
+            // the separators of the rows, and the end of the query
+            _ => {}
+        }
+    }
+
+    rows
+}
+
+fn get_values_keyword_position(tokens: &[Token]) -> Option<usize> {
+    tokens.iter().position(|token| match token {
+        Token::Word(word) => {
+            word.quote_style.is_none()
+                && (word.value.eq_ignore_ascii_case("VALUES")
+                    || word.value.eq_ignore_ascii_case("VALUE"))
+        }
+        _ => false,
+    })
+}
+
+fn to_value_token(value: &[&Token]) -> Token {
+    match value {
+        [Token::Minus, Token::Number(number, _)] => Token::Number(number.clone(), true),
+        [Token::Plus, Token::Number(number, _)] => Token::Number(number.clone(), false),
+        [token, ..] => (*token).clone(),
+        [] => Token::EOF,
+    }
+}
+
+/// SQL literal of a value token returned by [get_rows_from_insert_into_query]
+pub fn to_sql_value(token: &Token) -> String {
+    match token {
+        Token::Number(value, false) => value.clone(),
+        Token::Number(value, true) => format!("-{}", value),
+        Token::SingleQuotedString(value) => format!("'{}'", escape_string(value)),
+        Token::NationalStringLiteral(value) => format!("N'{}'", escape_string(value)),
+        Token::BinaryStringLiteral(value) => format!("_binary '{}'", escape_string(value)),
+        Token::HexStringLiteral(value) if value.is_empty() => "X''".to_string(),
+        Token::HexStringLiteral(value) => format!("0x{}", value),
+        Token::BitStringLiteral(value) => format!("b'{}'", value),
+        Token::Word(word) => word.value.clone(),
+        Token::Char(ch) => ch.to_string(),
+        _ => "NULL".to_string(),
+    }
 }
 
 pub fn get_tokens_from_query_str(query: &str) -> Vec<Token> {
@@ -725,11 +957,19 @@ pub fn trim_pre_whitespaces(tokens: Vec<Token>) -> Vec<Token> {
 
 #[cfg(test)]
 mod tests {
+    use quickcheck::{Arbitrary, Gen, QuickCheck};
+
     use crate::mysql::{
+        escape_string, get_column_names_from_create_table_query,
         get_column_names_from_insert_into_query, get_column_values_from_insert_into_query,
-        get_tokens_from_query_str, match_keyword_at_position, trim_pre_whitespaces, Token,
-        Tokenizer, Whitespace, get_single_quoted_string_value_at_position,
+        get_rows_from_insert_into_query, get_single_quoted_string_value_at_position,
+        get_tokens_from_query_str, match_keyword_at_position, to_sql_value, trim_pre_whitespaces,
+        Token, Tokenizer, Whitespace,
     };
+    use crate::utils::{list_mysql_queries_from_dump_reader, ListQueryResult};
+    use crate::DumpFileError;
+    use crate::DumpFileError::ReadError;
+    use std::io::{BufReader, ErrorKind};
 
     #[test]
     fn test_tokenize_single_quoted_string() {
@@ -963,15 +1203,15 @@ VALUES (1,'Stanford','Stiedemann','alaina.moore@example.net','EUR',1,'2022-04-13
         assert_eq!(
             column_values,
             vec![
-                &Token::Number("1".to_string(), false),
-                &Token::SingleQuotedString("Stanford".to_string()),
-                &Token::SingleQuotedString("People\'sRepublic".to_string()),
-                &Token::SingleQuotedString("alaina.moore@example.net".to_string()),
-                &Token::SingleQuotedString("EUR".to_string()),
-                &Token::Number("1".to_string(), false),
-                &Token::make_keyword("NULL"),
-                &Token::SingleQuotedString("2022-04-13 20:29:23".to_string()),
-                &Token::SingleQuotedString("2022-04-13 20:29:23".to_string()),
+                Token::Number("1".to_string(), false),
+                Token::SingleQuotedString("Stanford".to_string()),
+                Token::SingleQuotedString("People\'sRepublic".to_string()),
+                Token::SingleQuotedString("alaina.moore@example.net".to_string()),
+                Token::SingleQuotedString("EUR".to_string()),
+                Token::Number("1".to_string(), false),
+                Token::make_keyword("NULL"),
+                Token::SingleQuotedString("2022-04-13 20:29:23".to_string()),
+                Token::SingleQuotedString("2022-04-13 20:29:23".to_string()),
             ]
         );
     }
@@ -1012,8 +1252,8 @@ VALUES ('Romaric', true);
         assert_eq!(
             column_values,
             vec![
-                &Token::SingleQuotedString("Romaric".to_string()),
-                &Token::make_word("true", None),
+                Token::SingleQuotedString("Romaric".to_string()),
+                Token::make_word("true", None),
             ]
         );
     }
@@ -1030,7 +1270,308 @@ VALUES ('Romaric', true);
         assert_eq!(tokens_result.is_ok(), true);
 
         let tokens = trim_pre_whitespaces(tokens_result.unwrap());
-        assert_eq!("customers", get_single_quoted_string_value_at_position(&tokens, 4).unwrap());
+        assert_eq!(
+            "customers",
+            get_single_quoted_string_value_at_position(&tokens, 4).unwrap()
+        );
         assert!(get_single_quoted_string_value_at_position(&tokens, 0).is_none());
     }
+
+    #[test]
+    fn tokenize_escaped_strings() {
+        let q = r"INSERT INTO `users` VALUES ('O\'Reilly','it''s','C:\\path\\','\0\b\n\r\t\Z\%\_\x','',```id```);";
+        let tokens = get_tokens_from_query_str(q);
+
+        assert_eq!(
+            get_column_values_from_insert_into_query(&tokens),
+            vec![
+                Token::SingleQuotedString("O'Reilly".to_string()),
+                Token::SingleQuotedString("it's".to_string()),
+                Token::SingleQuotedString("C:\\path\\".to_string()),
+                Token::SingleQuotedString("\0\u{8}\n\r\t\u{1a}\\%\\_x".to_string()),
+                Token::SingleQuotedString("".to_string()),
+                Token::SingleQuotedString("`id`".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenize_binary_and_number_literals() {
+        let q = r"INSERT INTO `files` VALUES (_binary '\0\'a',_BINARY 0x0aFF,X'dead',0xbeef,b'0101',-1.5e-5,2E10,+3,-4,.5);";
+        let tokens = get_tokens_from_query_str(q);
+
+        assert_eq!(
+            get_column_values_from_insert_into_query(&tokens),
+            vec![
+                Token::BinaryStringLiteral("\0'a".to_string()),
+                Token::HexStringLiteral("0aFF".to_string()),
+                Token::HexStringLiteral("dead".to_string()),
+                Token::HexStringLiteral("beef".to_string()),
+                Token::BitStringLiteral("0101".to_string()),
+                Token::Number("1.5e-5".to_string(), true),
+                Token::Number("2E10".to_string(), false),
+                Token::Number("3".to_string(), false),
+                Token::Number("4".to_string(), true),
+                Token::Number(".5".to_string(), false),
+            ]
+        );
+
+        // `_binary` is a regular word when it does not introduce a string
+        let tokens = get_tokens_from_query_str("SELECT _binary FROM t;");
+        assert_eq!(tokens[2], Token::make_word("_binary", None));
+    }
+
+    #[test]
+    fn test_get_column_names_from_create_table_query() {
+        let q = r"
+CREATE TABLE `city` (
+  `ID` int NOT NULL AUTO_INCREMENT,
+  `Name` char(35) NOT NULL DEFAULT '',
+  Population decimal(10,2) NOT NULL DEFAULT '0',
+  PRIMARY KEY (`ID`),
+  KEY `CountryCode` (`CountryCode`),
+  CONSTRAINT `city_ibfk_1` FOREIGN KEY (`CountryCode`) REFERENCES `country` (`Code`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;";
+        let tokens = get_tokens_from_query_str(q);
+
+        assert_eq!(
+            get_column_names_from_create_table_query(&tokens),
+            vec!["ID", "Name", "Population"]
+        );
+
+        let tokens = get_tokens_from_query_str("INSERT INTO `city` VALUES (1,'Kabul',1780000);");
+        assert!(get_column_names_from_create_table_query(&tokens).is_empty());
+    }
+
+    #[test]
+    fn test_get_rows_from_extended_insert_into_query() {
+        let q = "INSERT INTO `users` VALUES (1,'a,b)',NULL),(2,'(c)',_binary 'd'),\n(3,'', -5);";
+        let tokens = get_tokens_from_query_str(q);
+
+        assert_eq!(
+            get_rows_from_insert_into_query(&tokens),
+            vec![
+                vec![
+                    Token::Number("1".to_string(), false),
+                    Token::SingleQuotedString("a,b)".to_string()),
+                    Token::make_keyword("NULL"),
+                ],
+                vec![
+                    Token::Number("2".to_string(), false),
+                    Token::SingleQuotedString("(c)".to_string()),
+                    Token::BinaryStringLiteral("d".to_string()),
+                ],
+                vec![
+                    Token::Number("3".to_string(), false),
+                    Token::SingleQuotedString("".to_string()),
+                    Token::Number("5".to_string(), true),
+                ],
+            ]
+        );
+        assert!(get_column_names_from_insert_into_query(&tokens).is_empty());
+
+        let q = "INSERT INTO `users` (`id`, `name`) VALUES (1,'a'),(2,'b');";
+        let tokens = get_tokens_from_query_str(q);
+        assert_eq!(
+            get_column_names_from_insert_into_query(&tokens),
+            vec!["id", "name"]
+        );
+        assert_eq!(get_rows_from_insert_into_query(&tokens).len(), 2);
+
+        let tokens = get_tokens_from_query_str("CREATE TABLE `users` (`id` int);");
+        assert!(get_rows_from_insert_into_query(&tokens).is_empty());
+    }
+
+    fn digits(g: &mut Gen) -> u32 {
+        u32::arbitrary(g) % 1_000_000
+    }
+
+    /// a value of a row, as returned by [get_rows_from_insert_into_query]
+    #[derive(Clone, Debug)]
+    struct Value(Token);
+
+    impl Arbitrary for Value {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let token = match u8::arbitrary(g) % 9 {
+                0 => Token::Number(digits(g).to_string(), bool::arbitrary(g)),
+                1 => Token::Number(format!("{}.{}", digits(g), digits(g)), bool::arbitrary(g)),
+                2 => Token::Number(
+                    format!("{}.{}e-{}", digits(g), digits(g), u8::arbitrary(g) % 30 + 1),
+                    bool::arbitrary(g),
+                ),
+                3 => Token::make_keyword("NULL"),
+                4 => Token::HexStringLiteral(format!("{:x}", digits(g))),
+                5 => Token::BinaryStringLiteral(String::arbitrary(g)),
+                6 => Token::BitStringLiteral(format!("{:b}", digits(g))),
+                _ => Token::SingleQuotedString(String::arbitrary(g)),
+            };
+
+            Value(token)
+        }
+    }
+
+    /// the rows of an extended INSERT, with the same number of columns
+    #[derive(Clone, Debug)]
+    struct Rows(Vec<Vec<Token>>);
+
+    impl Arbitrary for Rows {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let columns = usize::arbitrary(g) % 5 + 1;
+            let rows = usize::arbitrary(g) % 5 + 1;
+
+            Rows(
+                (0..rows)
+                    .map(|_| (0..columns).map(|_| Value::arbitrary(g).0).collect())
+                    .collect(),
+            )
+        }
+    }
+
+    fn to_extended_insert_into_query(rows: &[Vec<Token>]) -> String {
+        format!(
+            "INSERT INTO `table` VALUES {};",
+            rows.iter()
+                .map(|row| {
+                    let values = row.iter().map(to_sql_value).collect::<Vec<_>>();
+                    format!("({})", values.join(","))
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        )
+    }
+
+    /// escape the bytes of a binary string like mysqldump does
+    fn escape_bytes(bytes: &[u8]) -> Vec<u8> {
+        bytes
+            .iter()
+            .flat_map(|byte| match byte {
+                b'\0' => b"\\0".to_vec(),
+                b'\n' => b"\\n".to_vec(),
+                b'\r' => b"\\r".to_vec(),
+                b'\x1a' => b"\\Z".to_vec(),
+                b'\\' | b'\'' | b'"' => vec![b'\\', *byte],
+                byte => vec![*byte],
+            })
+            .collect()
+    }
+
+    fn list_queries(dump: &[u8]) -> Result<Vec<String>, DumpFileError> {
+        let mut queries = vec![];
+        list_mysql_queries_from_dump_reader(BufReader::new(dump), |query| {
+            if query != "\n" {
+                queries.push(query.to_string());
+            }
+            ListQueryResult::Continue
+        })?;
+
+        Ok(queries)
+    }
+
+    #[test]
+    fn extended_insert_into_queries_round_trip() {
+        fn property(rows: Rows) -> bool {
+            let query = to_extended_insert_into_query(&rows.0);
+            let tokens = get_tokens_from_query_str(query.as_str());
+            let parsed_rows = get_rows_from_insert_into_query(&tokens);
+
+            // the serialization is stable
+            parsed_rows == rows.0 && to_extended_insert_into_query(&parsed_rows) == query
+        }
+
+        QuickCheck::new()
+            .tests(500)
+            .quickcheck(property as fn(Rows) -> bool);
+    }
+
+    #[test]
+    fn extended_insert_into_queries_in_dump() {
+        fn property(rows: Vec<Rows>) -> bool {
+            let queries = rows
+                .iter()
+                .map(|rows| to_extended_insert_into_query(&rows.0))
+                .collect::<Vec<_>>();
+
+            matches!(list_queries(queries.join("\n").as_bytes()), Ok(listed_queries) if listed_queries == queries)
+        }
+
+        QuickCheck::new()
+            .tests(200)
+            .quickcheck(property as fn(Vec<Rows>) -> bool);
+    }
+
+    #[test]
+    fn hexadecimal_binary_values_round_trip() {
+        fn property(bytes: Vec<u8>) -> bool {
+            let hex = bytes
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>();
+            let query = format!(
+                "INSERT INTO `t` VALUES (_binary {});",
+                to_sql_value(&Token::HexStringLiteral(hex))
+            );
+
+            let listed_queries = match list_queries(query.as_bytes()) {
+                Ok(listed_queries) => listed_queries,
+                Err(_) => return false,
+            };
+
+            let tokens = get_tokens_from_query_str(listed_queries[0].as_str());
+            let parsed_bytes = match get_rows_from_insert_into_query(&tokens).as_slice() {
+                [row] => match row.as_slice() {
+                    [Token::HexStringLiteral(hex)] => (0..hex.len())
+                        .step_by(2)
+                        .map(|idx| u8::from_str_radix(&hex[idx..idx + 2], 16).unwrap())
+                        .collect::<Vec<_>>(),
+                    _ => return false,
+                },
+                _ => return false,
+            };
+
+            parsed_bytes == bytes
+        }
+
+        QuickCheck::new()
+            .tests(500)
+            .quickcheck(property as fn(Vec<u8>) -> bool);
+    }
+
+    #[test]
+    fn raw_binary_values() {
+        // the binary values not dumped with --hex-blob are read if they are valid UTF-8,
+        // the other ones are a read error
+        fn property(bytes: Vec<u8>) -> bool {
+            let mut query = b"INSERT INTO `t` VALUES (1,_binary '".to_vec();
+            query.extend(escape_bytes(&bytes));
+            query.extend(b"');");
+
+            match (list_queries(&query), String::from_utf8(bytes)) {
+                (Ok(listed_queries), Ok(value)) => {
+                    let tokens = get_tokens_from_query_str(listed_queries[0].as_str());
+                    listed_queries.len() == 1
+                        && listed_queries[0].as_bytes() == query.as_slice()
+                        && get_rows_from_insert_into_query(&tokens)
+                            == vec![vec![
+                                Token::Number("1".to_string(), false),
+                                Token::BinaryStringLiteral(value),
+                            ]]
+                }
+                (Err(ReadError(err)), Err(_)) => err.kind() == ErrorKind::InvalidData,
+                _ => false,
+            }
+        }
+
+        QuickCheck::new()
+            .tests(500)
+            .quickcheck(property as fn(Vec<u8>) -> bool);
+
+        let query = b"INSERT INTO `t` VALUES (1,_binary '\xff\xfe\x80');";
+        assert!(matches!(list_queries(query), Err(ReadError(_))));
+    }
+
+    #[test]
+    fn test_escape_string() {
+        assert_eq!(escape_string("O'Reilly"), "O\\'Reilly");
+        assert_eq!(escape_string("a\\b\n\0\u{1a}\"`"), "a\\\\b\\n\\0\\Z\"`");
+    }
 }
@@ -25,6 +25,8 @@ pub enum Token {
     NationalStringLiteral(String),
     /// Hexadecimal string literal: i.e.: X'deadbeef'
     HexStringLiteral(String),
+    /// Escape string constant, with its backslash escapes decoded: i.e. E'it\'s'
+    EscapedStringLiteral(String),
     /// Comma
     Comma,
     /// Double equals sign `==`
@@ -291,6 +293,21 @@ impl<'a> Tokenizer<'a> {
                         }
                     }
                 }
+                e @ 'E' | e @ 'e' => {
+                    chars.next(); // consume, to check the next char
+                    match chars.peek() {
+                        Some('\'') => {
+                            // E'...' - an escape string constant
+                            let s = self.tokenize_escaped_single_quoted_string(chars)?;
+                            Ok(Some(Token::EscapedStringLiteral(s)))
+                        }
+                        _ => {
+                            // regular identifier starting with an "E"
+                            let s = self.tokenize_word(e, chars);
+                            Ok(Some(Token::make_word(&s, None)))
+                        }
+                    }
+                }
                 // The spec only allows an uppercase 'X' to introduce a hex
                 // string, but PostgreSQL, at least, allows a lowercase 'x' too.
                 x @ 'x' | x @ 'X' => {
@@ -556,6 +573,57 @@ impl<'a> Tokenizer<'a> {
         self.tokenizer_error("Unterminated string literal")
     }
 
+    /// Read an escape string constant, i.e. E'...', and decode its backslash escapes
+    fn tokenize_escaped_single_quoted_string(
+        &self,
+        chars: &mut Peekable<Chars<'_>>,
+    ) -> Result<String, TokenizerError> {
+        // the octal and hexadecimal escapes are bytes, e.g. E'\303\251' is a UTF-8 char
+        let mut bytes = vec![];
+        let mut buf = [0; 4];
+        chars.next(); // consume the opening quote
+
+        while let Some(ch) = chars.next() {
+            let ch = match ch {
+                '\'' if chars.peek() == Some(&'\'') => {
+                    chars.next();
+                    '\''
+                }
+                '\'' => return Ok(String::from_utf8_lossy(&bytes).to_string()),
+                '\\' => match chars.next() {
+                    Some('b') => '\u{8}',
+                    Some('f') => '\u{c}',
+                    Some('n') => '\n',
+                    Some('r') => '\r',
+                    Some('t') => '\t',
+                    Some(digit @ '0'..='7') => {
+                        let value = take_digits(chars, 8, 2, digit.to_digit(8).unwrap());
+                        bytes.push(value as u8);
+                        continue;
+                    }
+                    Some('x') if matches!(chars.peek(), Some(ch) if ch.is_ascii_hexdigit()) => {
+                        bytes.push(take_digits(chars, 16, 2, 0) as u8);
+                        continue;
+                    }
+                    Some(escape @ ('u' | 'U')) if matches!(chars.peek(), Some(ch) if ch.is_ascii_hexdigit()) =>
+                    {
+                        let max_digits = if escape == 'u' { 4 } else { 8 };
+                        let value = take_digits(chars, 16, max_digits, 0);
+                        char::from_u32(value).unwrap_or(char::REPLACEMENT_CHARACTER)
+                    }
+                    // any other char is itself, e.g. E'\\' is a backslash
+                    Some(ch) => ch,
+                    None => break,
+                },
+                ch => ch,
+            };
+
+            bytes.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+        }
+
+        self.tokenizer_error("Unterminated string literal")
+    }
+
     fn tokenize_multiline_comment(
         &self,
         chars: &mut Peekable<Chars<'_>>,
@@ -630,6 +698,26 @@ fn peeking_take_while(
     s
 }
 
+/// the value of the next digits, up to `max_digits`, added to `value`
+fn take_digits(
+    chars: &mut Peekable<Chars<'_>>,
+    radix: u32,
+    max_digits: usize,
+    mut value: u32,
+) -> u32 {
+    for _ in 0..max_digits {
+        match chars.peek().and_then(|ch| ch.to_digit(radix)) {
+            Some(digit) => {
+                value = value.wrapping_mul(radix).wrapping_add(digit);
+                chars.next();
+            }
+            None => break,
+        }
+    }
+
+    value
+}
+
 fn parse_quoted_ident(chars: &mut Peekable<Chars<'_>>, quote_end: char) -> (String, Option<char>) {
     let mut last_char = None;
     let mut s = String::new();
@@ -650,6 +738,17 @@ fn parse_quoted_ident(chars: &mut Peekable<Chars<'_>>, quote_end: char) -> (Stri
     (s, last_char)
 }
 
+/// first words of the table constraints of a `CREATE TABLE ...` query
+const TABLE_CONSTRAINT_KEYWORDS: [&str; 7] = [
+    "CONSTRAINT",
+    "CHECK",
+    "UNIQUE",
+    "PRIMARY",
+    "FOREIGN",
+    "EXCLUDE",
+    "LIKE",
+];
+
 pub fn match_keyword_at_position(keyword: Keyword, tokens: &Vec<Token>, pos: usize) -> bool {
     if let Some(token) = tokens.get(pos) {
         return match token {
@@ -750,6 +849,190 @@ pub fn get_column_values_str_from_insert_into_query(tokens: &Vec<Token>) -> Vec<
         .collect::<Vec<_>>()
 }
 
+pub fn get_column_names_from_copy_query(tokens: &Vec<Token>) -> Vec<String> {
+    if !match_keyword_at_position(Keyword::Copy, &tokens, 0) {
+        // it means that the query is not a COPY.. one
+        return Vec::new();
+    }
+
+    tokens
+        .iter()
+        .skip_while(|token| match **token {
+            Token::LParen => false,
+            _ => true,
+        })
+        .take_while(|token| match **token {
+            Token::RParen => false,
+            _ => true,
+        })
+        .filter_map(|token| match token {
+            Token::Word(word) => Some(format!(
+                "{quote_style}{value}{quote_style}",
+                value = word.value.as_str(),
+                quote_style = match word.quote_style {
+                    Some(quote) => quote.to_string(),
+                    None => "".to_string(),
+                }
+            )),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+}
+
+/// names and types of the columns of a `CREATE TABLE ...` query, e.g. `("data", "bytea")`.
+/// The type is its first word (i.e. `character` for `character varying(255)`), the table constraints are skipped.
+pub fn get_column_types_from_create_table_query(tokens: &Vec<Token>) -> Vec<(String, String)> {
+    if !match_keyword_at_position(Keyword::Create, tokens, 0) {
+        // it means that the query is not a CREATE TABLE.. one
+        return Vec::new();
+    }
+
+    let mut column_types = vec![];
+    // the first two words of the current column definition: its name and its type
+    let mut words: Vec<&Word> = vec![];
+    let mut depth = 0;
+
+    for token in tokens {
+        match token {
+            Token::LParen => depth += 1,
+            Token::RParen | Token::Comma if depth == 1 => {
+                if let [name, column_type] = words.as_slice() {
+                    let is_constraint = name.quote_style.is_none()
+                        && TABLE_CONSTRAINT_KEYWORDS.contains(&name.value.to_uppercase().as_str());
+
+                    if !is_constraint {
+                        column_types.push((
+                            format!(
+                                "{quote_style}{value}{quote_style}",
+                                value = name.value.as_str(),
+                                quote_style = match name.quote_style {
+                                    Some(quote) => quote.to_string(),
+                                    None => "".to_string(),
+                                }
+                            ),
+                            column_type.value.to_lowercase(),
+                        ));
+                    }
+                }
+
+                words.clear();
+                if let Token::RParen = token {
+                    depth -= 1;
+                }
+            }
+            Token::RParen => depth -= 1,
+            Token::Word(word) if depth == 1 && words.len() < 2 => words.push(word),
+            _ => {}
+        }
+    }
+
+    column_types
+}
+
+/// values of a row of a `COPY ... FROM stdin;` query, in the text format: tab separated and backslash escaped.
+/// A `NULL` value (`\N`) is `None`.
+pub fn get_column_values_from_copy_row(row: &str) -> Vec<Option<String>> {
+    row.split('\t')
+        .map(|value| match value {
+            "\\N" => None,
+            value => Some(unescape_copy_value(value)),
+        })
+        .collect::<Vec<_>>()
+}
+
+fn unescape_copy_value(value: &str) -> String {
+    if !value.contains('\\') {
+        return value.to_string();
+    }
+
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+
+        match chars.next() {
+            Some('b') => bytes.push(0x08),
+            Some('f') => bytes.push(0x0C),
+            Some('n') => bytes.push(b'\n'),
+            Some('r') => bytes.push(b'\r'),
+            Some('t') => bytes.push(b'\t'),
+            Some('v') => bytes.push(0x0B),
+            Some('x') if chars.peek().is_some_and(|ch| ch.is_ascii_hexdigit()) => {
+                // \xH or \xHH
+                let mut byte = 0u8;
+                for _ in 0..2 {
+                    match chars.peek().and_then(|ch| ch.to_digit(16)) {
+                        Some(digit) => {
+                            byte = byte.wrapping_mul(16).wrapping_add(digit as u8);
+                            let _ = chars.next();
+                        }
+                        None => break,
+                    }
+                }
+                bytes.push(byte);
+            }
+            Some(ch @ '0'..='7') => {
+                // \N, \NN or \NNN in octal
+                let mut byte = ch.to_digit(8).unwrap() as u8;
+                for _ in 0..2 {
+                    match chars.peek().and_then(|ch| ch.to_digit(8)) {
+                        Some(digit) => {
+                            byte = byte.wrapping_mul(8).wrapping_add(digit as u8);
+                            let _ = chars.next();
+                        }
+                        None => break,
+                    }
+                }
+                bytes.push(byte);
+            }
+            // any other escaped char is taken as is, e.g. \\
+            Some(ch) => {
+                let mut buf = [0; 4];
+                bytes.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+            }
+            None => bytes.push(b'\\'),
+        }
+    }
+
+    String::from_utf8_lossy(bytes.as_slice()).to_string()
+}
+
+/// row of a `COPY ... FROM stdin;` query, in the text format, from its values. A `None` value is `NULL`.
+pub fn to_copy_row(values: &[Option<String>]) -> String {
+    values
+        .iter()
+        .map(|value| match value {
+            Some(value) => escape_copy_value(value.as_str()),
+            None => "\\N".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\t")
+}
+
+fn escape_copy_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for ch in value.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            '\x08' => escaped.push_str("\\b"),
+            '\x0C' => escaped.push_str("\\f"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\x0B' => escaped.push_str("\\v"),
+            ch => escaped.push(ch),
+        }
+    }
+
+    escaped
+}
+
 pub fn get_tokens_from_query_str(query: &str) -> Vec<Token> {
     // query by query
     let mut tokenizer = Tokenizer::new(query);
@@ -779,7 +1062,9 @@ pub fn trim_pre_whitespaces(tokens: Vec<Token>) -> Vec<Token> {
 #[cfg(test)]
 mod tests {
     use crate::postgres::{
-        get_column_names_from_insert_into_query, get_column_values_from_insert_into_query,
+        get_column_names_from_copy_query, get_column_names_from_insert_into_query,
+        get_column_types_from_create_table_query, get_column_values_from_copy_row,
+        get_column_values_from_insert_into_query, get_tokens_from_query_str, to_copy_row,
         trim_pre_whitespaces, Token, Tokenizer, Whitespace,
     };
 
@@ -978,4 +1263,87 @@ VALUES ('Romaric', true);
             ]
         );
     }
+
+    #[test]
+    fn test_get_column_names_from_copy_query() {
+        let tokens = get_tokens_from_query_str(
+            r#"COPY public.customers (customer_id, "upperCaseColumnName") FROM stdin;"#,
+        );
+
+        assert_eq!(
+            get_column_names_from_copy_query(&tokens),
+            vec!["customer_id", r#""upperCaseColumnName""#]
+        );
+    }
+
+    #[test]
+    fn test_get_column_values_from_copy_row() {
+        let values = get_column_values_from_copy_row(
+            "1\tO'Reilly\t\\N\tline 1\\nline 2\\ttab \\\\ \\x41\\101\t",
+        );
+
+        assert_eq!(
+            values,
+            vec![
+                Some("1".to_string()),
+                Some("O'Reilly".to_string()),
+                None,
+                Some("line 1\nline 2\ttab \\ AA".to_string()),
+                Some("".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_copy_row() {
+        let values = vec![
+            Some("1".to_string()),
+            None,
+            Some("line 1\nline 2\ttab \\ \\N".to_string()),
+        ];
+
+        let row = to_copy_row(&values);
+        assert_eq!(row, "1\t\\N\tline 1\\nline 2\\ttab \\\\ \\\\N");
+        assert_eq!(get_column_values_from_copy_row(row.as_str()), values);
+    }
+
+    #[test]
+    fn test_escaped_string_literals() {
+        let q = r"INSERT INTO public.files (name, data) VALUES (E'it\'s\tan E''string \\ \101\x42\u00e9', e'\303\251');";
+        let tokens = get_tokens_from_query_str(q);
+
+        assert_eq!(
+            get_column_values_from_insert_into_query(&tokens),
+            vec![
+                &Token::EscapedStringLiteral("it's\tan E'string \\ ABé".to_string()),
+                &Token::EscapedStringLiteral("é".to_string()),
+            ]
+        );
+
+        // a word starting with an E is not an escape string
+        let tokens = get_tokens_from_query_str("SELECT email FROM users;");
+        assert!(tokens.contains(&Token::make_word("email", None)));
+    }
+
+    #[test]
+    fn test_get_column_types_from_create_table_query() {
+        let q = r#"CREATE TABLE public.files (
+    id integer NOT NULL,
+    "Name" character varying(255) DEFAULT 'a, b'::character varying,
+    price numeric(10, 2),
+    data bytea,
+    CONSTRAINT files_price_check CHECK ((price > (0)::numeric))
+);"#;
+        let tokens = get_tokens_from_query_str(q);
+
+        assert_eq!(
+            get_column_types_from_create_table_query(&tokens),
+            vec![
+                ("id".to_string(), "integer".to_string()),
+                ("\"Name\"".to_string(), "character".to_string()),
+                ("price".to_string(), "numeric".to_string()),
+                ("data".to_string(), "bytea".to_string()),
+            ]
+        );
+    }
 }
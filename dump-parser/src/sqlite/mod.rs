@@ -0,0 +1,444 @@
+// SQLite dumps (`sqlite3 <database> .dump`) are close enough to PostgreSQL dumps to be tokenized with the
+// PostgreSQL tokenizer. Only the double quoted identifiers (which can contain any char) are handled here.
+pub use crate::postgres::{
+    match_keyword_at_position, trim_pre_whitespaces, Keyword, Token, Tokenizer, Whitespace, Word,
+};
+
+/// Value of a column inside an `INSERT INTO ... VALUES(...)` statement
+#[derive(Debug, PartialEq, Clone)]
+pub enum Value {
+    /// Integer or real number, with its sign
+    Number(String),
+    /// Text value, with the escaped chars already decoded
+    String(String),
+    /// Hexadecimal blob value: i.e.: X'deadbeef'
+    Blob(String),
+    Null,
+}
+
+/// Table name and quote style of a `CREATE TABLE` or an `INSERT INTO` query
+#[derive(Debug, PartialEq, Clone)]
+pub struct TableName {
+    pub value: String,
+    pub quote_style: Option<char>,
+}
+
+impl TableName {
+    /// table name with escaping
+    pub fn quoted(&self) -> String {
+        match self.quote_style {
+            Some(quote) => format!(
+                "{quote}{value}{quote}",
+                quote = quote,
+                value = self
+                    .value
+                    .replace(quote, format!("{}{}", quote, quote).as_str())
+            ),
+            None => self.value.clone(),
+        }
+    }
+}
+
+pub fn get_tokens_from_query_str(query: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut segment_start_idx = 0;
+    let mut is_single_quoted_string = false;
+    let mut chars = query.char_indices().peekable();
+
+    while let Some((idx, ch)) = chars.next() {
+        match ch {
+            '\'' => is_single_quoted_string = !is_single_quoted_string,
+            '"' if !is_single_quoted_string => {
+                tokens.append(&mut tokenize(&query[segment_start_idx..idx]));
+
+                let mut identifier = String::new();
+                segment_start_idx = query.len();
+
+                while let Some((idx, ch)) = chars.next() {
+                    if ch == '"' {
+                        if let Some((_, '"')) = chars.peek() {
+                            // escaped double quote
+                            let _ = chars.next();
+                        } else {
+                            segment_start_idx = idx + 1;
+                            break;
+                        }
+                    }
+
+                    identifier.push(ch);
+                }
+
+                tokens.push(Token::Word(Word {
+                    value: identifier,
+                    quote_style: Some('"'),
+                    keyword: Keyword::NoKeyword,
+                }));
+            }
+            _ => {}
+        }
+    }
+
+    if segment_start_idx < query.len() {
+        tokens.append(&mut tokenize(&query[segment_start_idx..]));
+    }
+
+    trim_pre_whitespaces(tokens)
+}
+
+fn tokenize(query: &str) -> Vec<Token> {
+    let mut tokenizer = Tokenizer::new(query);
+
+    match tokenizer.tokenize() {
+        Ok(tokens) => tokens,
+        Err(err) => {
+            println!("failing query: '{}'", query);
+            panic!("{:?}", err)
+        }
+    }
+}
+
+pub fn is_create_table_query(tokens: &Vec<Token>) -> bool {
+    match_keyword_at_position(Keyword::Create, tokens, 0)
+        && match_keyword_at_position(Keyword::Table, tokens, 2)
+}
+
+pub fn is_insert_into_query(tokens: &Vec<Token>) -> bool {
+    match_keyword_at_position(Keyword::Insert, tokens, 0)
+        && match_keyword_at_position(Keyword::Into, tokens, 2)
+}
+
+/// return the table name of a `CREATE TABLE [IF NOT EXISTS] <table>(...)` query
+pub fn get_table_name_from_create_table_query(tokens: &Vec<Token>) -> Option<TableName> {
+    if !is_create_table_query(tokens) {
+        return None;
+    }
+
+    // the table name is the last word before the column definitions
+    tokens
+        .iter()
+        .take_while(|token| **token != Token::LParen)
+        .filter_map(|token| match token {
+            Token::Word(word) => Some(word),
+            _ => None,
+        })
+        .last()
+        .filter(|word| word.keyword != Keyword::Table)
+        .map(to_table_name)
+}
+
+/// return the table name of an `INSERT INTO <table> ...` query
+pub fn get_table_name_from_insert_into_query(tokens: &Vec<Token>) -> Option<TableName> {
+    if !is_insert_into_query(tokens) {
+        return None;
+    }
+
+    match tokens.get(4) {
+        Some(Token::Word(word)) => Some(to_table_name(word)),
+        _ => None,
+    }
+}
+
+/// return the column names declared in a `CREATE TABLE` query, table constraints are ignored
+pub fn get_column_names_from_create_table_query(tokens: &Vec<Token>) -> Vec<String> {
+    if !is_create_table_query(tokens) {
+        return Vec::new();
+    }
+
+    let column_definitions = match tokens.iter().position(|token| *token == Token::LParen) {
+        Some(idx) => &tokens[idx + 1..],
+        None => return Vec::new(),
+    };
+
+    let mut column_names = vec![];
+    let mut depth = 0;
+    let mut is_start_of_definition = true;
+
+    for token in column_definitions {
+        match token {
+            Token::LParen => depth += 1,
+            Token::RParen if depth == 0 => break,
+            Token::RParen => depth -= 1,
+            Token::Comma if depth == 0 => is_start_of_definition = true,
+            Token::Whitespace(_) => {}
+            Token::Word(word) if is_start_of_definition && depth == 0 => {
+                is_start_of_definition = false;
+
+                if !is_table_constraint(word) {
+                    column_names.push(word.value.clone());
+                }
+            }
+            _ => is_start_of_definition = false,
+        }
+    }
+
+    column_names
+}
+
+/// return the column names of an `INSERT INTO <table>(<columns>) VALUES(...)` query,
+/// or None if the query does not list its columns (which is what `sqlite3 .dump` does)
+pub fn get_column_names_from_insert_into_query(tokens: &Vec<Token>) -> Option<Vec<String>> {
+    if !is_insert_into_query(tokens) {
+        return None;
+    }
+
+    let mut column_names = vec![];
+    let mut has_column_list = false;
+
+    for token in tokens.iter().skip(5) {
+        match token {
+            Token::Word(word) if !has_column_list && is_values_word(word) => return None,
+            Token::LParen if !has_column_list => has_column_list = true,
+            Token::RParen => break,
+            Token::Word(word) if has_column_list => column_names.push(word.value.clone()),
+            _ => {}
+        }
+    }
+
+    Some(column_names)
+}
+
+/// return the values of an `INSERT INTO ... VALUES(...)` query,
+/// or None if a value is an expression that can't be decoded
+pub fn get_column_values_from_insert_into_query(tokens: &Vec<Token>) -> Option<Vec<Value>> {
+    if !is_insert_into_query(tokens) {
+        return None;
+    }
+
+    let values_idx = tokens.iter().position(|token| match token {
+        Token::Word(word) => is_values_word(word),
+        _ => false,
+    })?;
+
+    let values_tokens = tokens
+        .iter()
+        .skip(values_idx + 1)
+        .skip_while(|token| matches!(token, Token::Whitespace(_)))
+        .collect::<Vec<_>>();
+
+    if values_tokens.first() != Some(&&Token::LParen) {
+        return None;
+    }
+
+    let end_idx = find_closing_paren(&values_tokens, 0)?;
+
+    split_arguments(&values_tokens[1..end_idx])
+        .into_iter()
+        .map(|value_tokens| parse_value(&value_tokens))
+        .collect()
+}
+
+fn to_table_name(word: &Word) -> TableName {
+    TableName {
+        value: word.value.clone(),
+        quote_style: word.quote_style,
+    }
+}
+
+fn is_values_word(word: &Word) -> bool {
+    word.quote_style.is_none() && word.value.to_uppercase() == "VALUES"
+}
+
+fn is_table_constraint(word: &Word) -> bool {
+    word.quote_style.is_none()
+        && matches!(
+            word.value.to_uppercase().as_str(),
+            "CONSTRAINT" | "PRIMARY" | "UNIQUE" | "CHECK" | "FOREIGN"
+        )
+}
+
+/// index of the parenthesis closing the one at `open_idx`
+fn find_closing_paren(tokens: &[&Token], open_idx: usize) -> Option<usize> {
+    let mut depth = 0;
+
+    for (idx, token) in tokens.iter().enumerate().skip(open_idx) {
+        match token {
+            Token::LParen => depth += 1,
+            Token::RParen => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(idx);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// split comma separated arguments, commas inside parenthesis are ignored
+fn split_arguments<'a>(tokens: &[&'a Token]) -> Vec<Vec<&'a Token>> {
+    let mut arguments = vec![vec![]];
+    let mut depth = 0;
+
+    for token in tokens {
+        match token {
+            Token::Comma if depth == 0 => arguments.push(vec![]),
+            Token::Whitespace(_) if depth == 0 => {}
+            token => {
+                match token {
+                    Token::LParen => depth += 1,
+                    Token::RParen => depth -= 1,
+                    _ => {}
+                }
+
+                arguments.last_mut().unwrap().push(*token);
+            }
+        }
+    }
+
+    arguments
+}
+
+fn parse_value(tokens: &[&Token]) -> Option<Value> {
+    match tokens {
+        [Token::Number(value, _)] | [Token::Plus, Token::Number(value, _)] => {
+            Some(Value::Number(value.clone()))
+        }
+        [Token::Minus, Token::Number(value, _)] => Some(Value::Number(format!("-{}", value))),
+        [Token::SingleQuotedString(value)] => Some(Value::String(value.clone())),
+        [Token::HexStringLiteral(value)] => Some(Value::Blob(value.clone())),
+        [Token::Word(word)] if word.keyword == Keyword::Null => Some(Value::Null),
+        [Token::Word(word), Token::LParen, .., Token::RParen]
+            if word.keyword == Keyword::Replace =>
+        {
+            parse_replace_function(&tokens[2..tokens.len() - 1])
+        }
+        _ => None,
+    }
+}
+
+/// `sqlite3 .dump` escapes new lines this way: replace('line1\nline2','\n',char(10))
+fn parse_replace_function(arguments: &[&Token]) -> Option<Value> {
+    let arguments = split_arguments(arguments);
+
+    let (value, from, to) = match arguments.as_slice() {
+        [value, from, to] => (
+            parse_value(value)?,
+            parse_value(from)?,
+            parse_char_function(to)?,
+        ),
+        _ => return None,
+    };
+
+    match (value, from) {
+        (Value::String(value), Value::String(from)) => Some(Value::String(
+            value.replace(from.as_str(), to.to_string().as_str()),
+        )),
+        _ => None,
+    }
+}
+
+fn parse_char_function(tokens: &[&Token]) -> Option<char> {
+    match tokens {
+        [Token::Word(word), Token::LParen, Token::Number(code, _), Token::RParen]
+            if word.value.to_lowercase() == "char" =>
+        {
+            code.parse::<u32>().ok().and_then(char::from_u32)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sqlite::{
+        get_column_names_from_create_table_query, get_column_names_from_insert_into_query,
+        get_column_values_from_insert_into_query, get_table_name_from_create_table_query,
+        get_table_name_from_insert_into_query, get_tokens_from_query_str, TableName, Value,
+    };
+
+    #[test]
+    fn create_table_query() {
+        let q = r#"CREATE TABLE IF NOT EXISTS "user accounts"(
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL DEFAULT 'unknown, really',
+    balance DECIMAL(10, 2),
+    "last name" TEXT,
+    created_at DATETIME DEFAULT (datetime('now')),
+    UNIQUE (name, "last name"),
+    CONSTRAINT balance_positive CHECK (balance >= 0)
+);"#;
+
+        let tokens = get_tokens_from_query_str(q);
+
+        assert_eq!(
+            get_table_name_from_create_table_query(&tokens),
+            Some(TableName {
+                value: "user accounts".to_string(),
+                quote_style: Some('"'),
+            })
+        );
+
+        assert_eq!(
+            get_column_names_from_create_table_query(&tokens),
+            vec!["id", "name", "balance", "last name", "created_at"]
+        );
+
+        let tokens = get_tokens_from_query_str("CREATE TABLE sqlite_sequence(name,seq);");
+        assert_eq!(
+            get_column_names_from_create_table_query(&tokens),
+            vec!["name", "seq"]
+        );
+    }
+
+    #[test]
+    fn insert_into_query() {
+        let q = "INSERT INTO users VALUES(1,'Romaric',-12.5,NULL,X'DEADBEEF','it''s');";
+        let tokens = get_tokens_from_query_str(q);
+
+        assert_eq!(
+            get_table_name_from_insert_into_query(&tokens),
+            Some(TableName {
+                value: "users".to_string(),
+                quote_style: None,
+            })
+        );
+
+        assert_eq!(get_column_names_from_insert_into_query(&tokens), None);
+
+        assert_eq!(
+            get_column_values_from_insert_into_query(&tokens),
+            Some(vec![
+                Value::Number("1".to_string()),
+                Value::String("Romaric".to_string()),
+                Value::Number("-12.5".to_string()),
+                Value::Null,
+                Value::Blob("DEADBEEF".to_string()),
+                Value::String("it's".to_string()),
+            ])
+        );
+
+        let q = r#"INSERT INTO "users"(id, "first name") VALUES (1, 'Romaric');"#;
+        let tokens = get_tokens_from_query_str(q);
+        assert_eq!(
+            get_column_names_from_insert_into_query(&tokens),
+            Some(vec!["id".to_string(), "first name".to_string()])
+        );
+        assert_eq!(
+            get_column_values_from_insert_into_query(&tokens),
+            Some(vec![
+                Value::Number("1".to_string()),
+                Value::String("Romaric".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn insert_into_query_with_escaped_new_lines() {
+        let q = r"INSERT INTO notes VALUES(1,replace(replace('line1\r\nline2','\r',char(13)),'\n',char(10)));";
+        let tokens = get_tokens_from_query_str(q);
+
+        assert_eq!(
+            get_column_values_from_insert_into_query(&tokens),
+            Some(vec![
+                Value::Number("1".to_string()),
+                Value::String("line1\r\nline2".to_string()),
+            ])
+        );
+
+        let q = "INSERT INTO notes VALUES(1,upper('abc'));";
+        let tokens = get_tokens_from_query_str(q);
+        assert_eq!(get_column_values_from_insert_into_query(&tokens), None);
+    }
+}
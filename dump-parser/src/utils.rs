@@ -1,10 +1,12 @@
 use crate::DumpFileError;
 use crate::DumpFileError::ReadError;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Read};
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read};
 use std::str;
 
 const COMMENT_CHARS: &str = "--";
+/// line ending the rows of a `COPY ... FROM stdin;` query
+pub const END_OF_COPY_DATA: &str = "\\.";
 
 pub enum ListQueryResult {
     Continue,
@@ -29,9 +31,46 @@ where
     list_sql_queries_from_dump_reader(reader, query)
 }
 
-/// read dump and callback query function with each valid query inside the dump
+/// read dump and callback query function with each valid query inside the dump.
+/// The rows following a `COPY ... FROM stdin;` query are not SQL: they are given line by line
+/// (without the line break), up to the `\.` line ending them.
 pub fn list_sql_queries_from_dump_reader<R, F>(
+    dump_reader: BufReader<R>,
+    query: F,
+) -> Result<(), DumpFileError>
+where
+    R: Read,
+    F: FnMut(&str) -> ListQueryResult,
+{
+    list_queries_from_dump_reader(dump_reader, false, query)
+}
+
+/// read MySQL dump and callback query function with each valid query inside the dump.
+/// In the MySQL strings, a `\` char escapes the next char, e.g. `'O\'Reilly'`.
+pub fn list_mysql_queries_from_dump_reader<R, F>(
+    dump_reader: BufReader<R>,
+    query: F,
+) -> Result<(), DumpFileError>
+where
+    R: Read,
+    F: FnMut(&str) -> ListQueryResult,
+{
+    list_queries_from_dump_reader(dump_reader, true, query)
+}
+
+/// the bytes of a line or of a query as a string, an invalid UTF-8 sequence is a read error
+fn utf8_str(bytes: &[u8]) -> Result<&str, DumpFileError> {
+    str::from_utf8(bytes).map_err(|err| {
+        ReadError(Error::new(
+            ErrorKind::InvalidData,
+            format!("the dump is not valid UTF-8: {}", err),
+        ))
+    })
+}
+
+fn list_queries_from_dump_reader<R, F>(
     mut dump_reader: BufReader<R>,
+    backslash_escapes: bool,
     mut query: F,
 ) -> Result<(), DumpFileError>
 where
@@ -41,6 +80,7 @@ where
     let mut count_empty_lines = 0;
     let mut buf_bytes: Vec<u8> = Vec::new();
     let mut line_buf_bytes: Vec<u8> = Vec::new();
+    let mut is_copy_data = false;
 
     loop {
         let bytes = dump_reader.read_until(b'\n', &mut line_buf_bytes);
@@ -49,19 +89,33 @@ where
             Err(err) => return Err(ReadError(err)),
         };
 
-        let last_real_char_idx = if buf_bytes.len() > 1 {
-            buf_bytes.len() - 2
-        } else if buf_bytes.len() == 1 {
-            1
-        } else {
-            0
-        };
+        if is_copy_data {
+            if total_bytes == 0 {
+                // EOF
+                break;
+            }
+
+            let line = utf8_str(line_buf_bytes.as_slice())?;
+            let line = line.trim_end_matches(['\n', '\r']);
+            if line == END_OF_COPY_DATA {
+                is_copy_data = false;
+            }
+
+            let query_res = query(line);
+            line_buf_bytes.clear();
+
+            match query_res {
+                ListQueryResult::Continue => continue,
+                ListQueryResult::Break => break,
+            }
+        }
 
         // check end of line is a ';' char - it would mean it's the end of the query
-        let is_last_line_buf_bytes_by_end_of_query = match line_buf_bytes.get(last_real_char_idx) {
-            Some(byte) => *byte == b';',
-            None => false,
-        };
+        let is_last_line_buf_bytes_by_end_of_query = line_buf_bytes
+            .iter()
+            .rev()
+            .find(|byte| **byte != b'\n' && **byte != b'\r')
+            == Some(&b';');
 
         let mut query_res = ListQueryResult::Continue;
 
@@ -71,12 +125,15 @@ where
             let mut buf_bytes_to_keep: Vec<u8> = Vec::new();
 
             if buf_bytes.len() > 1 {
-                let query_str = str::from_utf8(buf_bytes.as_slice()).unwrap(); // FIXME remove unwrap
+                let query_str = utf8_str(buf_bytes.as_slice())?;
 
-                for statement in list_statements(query_str) {
+                for statement in list_dialect_statements(query_str, backslash_escapes) {
                     match statement {
                         Statement::NewLine => {
-                            query("\n");
+                            // the rows of a COPY query follow it, an empty line would be a row
+                            if !is_copy_data {
+                                query("\n");
+                            }
                         }
                         Statement::CommentLine(comment_statement) => {
                             query(comment_statement.statement);
@@ -84,6 +141,7 @@ where
                         Statement::Query(sql_statement) => {
                             if sql_statement.valid {
                                 query(sql_statement.statement);
+                                is_copy_data = is_copy_from_stdin_query(sql_statement.statement);
                             } else {
                                 // the query is not complete, so keep it for the next iteration
                                 buf_bytes_to_keep
@@ -96,6 +154,9 @@ where
 
             let _ = buf_bytes.clear();
             buf_bytes.extend_from_slice(buf_bytes_to_keep.as_slice());
+        }
+
+        if total_bytes <= 1 {
             count_empty_lines += 1;
         } else {
             count_empty_lines = 0;
@@ -117,6 +178,18 @@ where
     Ok(())
 }
 
+/// true if the query is a `COPY ... FROM stdin;` one, followed by the rows to copy
+fn is_copy_from_stdin_query(query: &str) -> bool {
+    let query = query.trim();
+
+    match query.get(..5) {
+        Some(start) if start.eq_ignore_ascii_case("COPY ") => {
+            query.to_ascii_lowercase().ends_with("from stdin;")
+        }
+        _ => false,
+    }
+}
+
 /// Decodes a hex string to a byte `Vec`.
 /// #### example:
 ///
@@ -151,43 +224,45 @@ struct QueryStatement<'a> {
     statement: &'a str,
 }
 
+#[cfg(test)]
+fn list_statements(query: &str) -> Vec<Statement> {
+    list_dialect_statements(query, false)
+}
+
 /// Lightweight function to parse and validate the SQL statement AST.
 /// This function can be executed thousands of time per second.
 /// It must be fast enough. That's why it does not validate the grammar,
-/// but just the structure of a SQL query and return the list of SQL statements with their index
-fn list_statements(query: &str) -> Vec<Statement> {
+/// but just the structure of a SQL query and return the list of SQL statements with their index.
+/// `backslash_escapes` is true if a `\` char escapes the next char in the strings (MySQL).
+fn list_dialect_statements(query: &str, backslash_escapes: bool) -> Vec<Statement> {
     let mut sql_statements = vec![];
     let mut stack = vec![];
 
-    let is_next_char_comment = if query.find("--").is_some() {
-        // it means there is comments in this query string
-        let x: Box<dyn Fn(usize) -> bool> = if query.len() == query.chars().count() {
-            Box::new(|next_idx: usize| {
-                query.len() > next_idx && &query[next_idx..next_idx + 1] == "-"
-            })
-        } else {
-            // very low performance ... chars().nth(idx) is O(n)
-            Box::new(|next_idx: usize| {
-                query.len() > next_idx && query.chars().nth(next_idx) == Some('-')
-            })
-        };
-
-        x
-    // check if query contains multiple bytes utf-8 chars
-    } else {
-        let x: Box<dyn Fn(usize) -> bool> = Box::new(|_: usize| false);
-        x
-    };
+    let bytes = query.as_bytes();
+    // the indexes are byte indexes, a `-` byte is never a part of a multiple bytes UTF-8 char
+    let is_next_char_comment = |next_idx: usize| bytes.get(next_idx) == Some(&b'-');
 
     let mut is_statement_complete = true;
     let mut is_comment_line = false;
     let mut is_partial_comment_line = false;
     let mut start_index = 0usize;
     let mut previous_chars_are_whitespaces = true;
+    let mut is_escaped_char = false;
+    // PostgreSQL escape string constants (i.e. E'it\'s') are escaped with a `\` as well
+    let mut is_escape_string = false;
     for (idx, byte_char) in query.bytes().enumerate() {
         let next_idx = idx + 1;
 
+        if is_escaped_char {
+            // the escaped char does not end the string
+            is_escaped_char = false;
+            continue;
+        }
+
         match byte_char {
+            b'\\' if (backslash_escapes || is_escape_string) && stack.get(0) == Some(&b'\'') => {
+                is_escaped_char = true;
+            }
             char if is_comment_line && char == b'\n' => {
                 sql_statements.push(Statement::CommentLine(CommentStatement {
                     start_index,
@@ -210,6 +285,15 @@ fn list_statements(query: &str) -> Vec<Statement> {
                         let _ = stack.remove(0);
                     }
                 } else {
+                    // a '' in an escape string is a quote of the same string
+                    is_escape_string = match idx.checked_sub(1).map(|idx| bytes[idx]) {
+                        Some(b'\'') => is_escape_string,
+                        Some(b'E' | b'e') => match idx.checked_sub(2).map(|idx| bytes[idx]) {
+                            Some(byte) => !(byte.is_ascii_alphanumeric() || byte == b'_'),
+                            None => true,
+                        },
+                        _ => false,
+                    };
                     stack.insert(0, byte_char);
                 }
                 is_statement_complete = false;
@@ -246,7 +330,10 @@ fn list_statements(query: &str) -> Vec<Statement> {
                 previous_chars_are_whitespaces = false;
             }
             // use grapheme instead of code points or bytes?
-            b'-' if !is_statement_complete && is_next_char_comment(next_idx) => {
+            b'-' if !is_statement_complete
+                && stack.get(0) != Some(&b'\'')
+                && is_next_char_comment(next_idx) =>
+            {
                 // comment
                 is_partial_comment_line = true;
                 previous_chars_are_whitespaces = false;
@@ -315,9 +402,11 @@ fn list_statements(query: &str) -> Vec<Statement> {
 #[cfg(test)]
 mod tests {
     use crate::utils::{
-        list_sql_queries_from_dump_reader, list_statements, ListQueryResult, Statement,
+        list_mysql_queries_from_dump_reader, list_sql_queries_from_dump_reader, list_statements,
+        ListQueryResult, Statement,
     };
-    use std::io::BufReader;
+    use crate::DumpFileError::ReadError;
+    use std::io::{BufReader, ErrorKind};
 
     #[test]
     fn check_list_sql_queries_from_dump_reader() {
@@ -339,6 +428,131 @@ Etiam augue augue, bibendum et molestie non, finibus non nulla. Etiam quis rhonc
         assert!(queries.len() > 0);
     }
 
+    #[test]
+    fn check_list_sql_queries_with_invalid_utf8_copy_rows() {
+        let r = b"COPY public.users (id, name) FROM stdin;\n1\tcaf\xe9\n\\.\n";
+        let reader = BufReader::new(&r[..]);
+
+        let mut queries = vec![];
+
+        let result = list_sql_queries_from_dump_reader(reader, |query| {
+            queries.push(query.to_string());
+            ListQueryResult::Continue
+        });
+
+        assert!(matches!(result, Err(ReadError(err)) if err.kind() == ErrorKind::InvalidData));
+        assert_eq!(queries, vec!["COPY public.users (id, name) FROM stdin;"]);
+    }
+
+    #[test]
+    fn check_list_sql_queries_with_copy_rows() {
+        let r = r#"COPY public.users (id, name, bio) FROM stdin;
+1	O'Reilly	ends with a semicolon;
+2	\N	(unbalanced
+\.
+
+INSERT INTO public.users (id, name, bio) VALUES (3, 'Doe', NULL);
+"#
+        .as_bytes();
+        let reader = BufReader::new(r);
+
+        let mut queries = vec![];
+
+        let _ = list_sql_queries_from_dump_reader(reader, |query| {
+            queries.push(query.to_string());
+            ListQueryResult::Continue
+        });
+
+        assert_eq!(
+            queries,
+            vec![
+                "COPY public.users (id, name, bio) FROM stdin;",
+                "1\tO'Reilly\tends with a semicolon;",
+                "2\t\\N\t(unbalanced",
+                "\\.",
+                "INSERT INTO public.users (id, name, bio) VALUES (3, 'Doe', NULL);",
+                "\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn check_list_mysql_queries_with_escaped_quotes() {
+        let r = r#"INSERT INTO `users` VALUES (1,'O\'Reilly; -- not a comment','C:\\'),(2,'\\\'(',_binary '\0\'');
+INSERT INTO `users` VALUES (3,'it''s',NULL);
+"#
+        .as_bytes();
+        let reader = BufReader::new(r);
+
+        let mut queries = vec![];
+
+        let _ = list_mysql_queries_from_dump_reader(reader, |query| {
+            queries.push(query.to_string());
+            ListQueryResult::Continue
+        });
+
+        assert_eq!(
+            queries,
+            vec![
+                r#"INSERT INTO `users` VALUES (1,'O\'Reilly; -- not a comment','C:\\'),(2,'\\\'(',_binary '\0\'');"#,
+                "\n",
+                "INSERT INTO `users` VALUES (3,'it''s',NULL);",
+                "\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn check_list_mysql_queries_with_multiple_bytes_chars_and_negative_numbers() {
+        // the `-` of a negative number after multiple bytes chars is not a comment
+        let r = "INSERT INTO `users` VALUES ('Zoë--, Zoë',-1,-2);\nINSERT INTO `users` VALUES ('日本',-3);\n"
+            .as_bytes();
+        let reader = BufReader::new(r);
+
+        let mut queries = vec![];
+
+        let _ = list_mysql_queries_from_dump_reader(reader, |query| {
+            queries.push(query.to_string());
+            ListQueryResult::Continue
+        });
+
+        assert_eq!(
+            queries,
+            vec![
+                "INSERT INTO `users` VALUES ('Zoë--, Zoë',-1,-2);",
+                "\n",
+                "INSERT INTO `users` VALUES ('日本',-3);",
+                "\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn check_list_sql_queries_with_escape_strings() {
+        let r = r#"INSERT INTO public.users (id, bio) VALUES (1, E'it\'s; -- not a comment\\');
+INSERT INTO public.users (id, bio) VALUES (2, e'\''''), (3, 'C:\');
+"#
+        .as_bytes();
+        let reader = BufReader::new(r);
+
+        let mut queries = vec![];
+
+        let _ = list_sql_queries_from_dump_reader(reader, |query| {
+            queries.push(query.to_string());
+            ListQueryResult::Continue
+        });
+
+        assert_eq!(
+            queries,
+            vec![
+                r#"INSERT INTO public.users (id, bio) VALUES (1, E'it\'s; -- not a comment\\');"#,
+                "\n",
+                r#"INSERT INTO public.users (id, bio) VALUES (2, e'\''''), (3, 'C:\');"#,
+                "\n",
+            ]
+        );
+    }
+
     #[test]
     fn check_list_sql_statements_with_multiple_lines() {
         let s = list_statements(
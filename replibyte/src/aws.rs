@@ -0,0 +1,57 @@
+use aws_config::profile::retry_config::ProfileFileRetryConfigProvider;
+use aws_config::profile::{ProfileFileCredentialsProvider, ProfileFileRegionProvider};
+use aws_types::region::Region;
+use aws_types::{Credentials, SdkConfig};
+
+use crate::config::AwsCredentials;
+use crate::errors::ReplibyteError;
+use crate::runtime::block_on;
+
+/// load the AWS configuration from the environment, the given profile, region and credentials take precedence
+pub fn sdk_config(
+    region: Option<String>,
+    profile: Option<String>,
+    credentials: Option<AwsCredentials>,
+) -> Result<SdkConfig, ReplibyteError> {
+    let mut config_loader = aws_config::from_env();
+
+    if let Some(profile) = profile {
+        config_loader = config_loader
+            .region(
+                ProfileFileRegionProvider::builder()
+                    .profile_name(profile.as_str())
+                    .build(),
+            )
+            .credentials_provider(
+                ProfileFileCredentialsProvider::builder()
+                    .profile_name(profile.as_str())
+                    .build(),
+            )
+            .retry_config(
+                block_on(
+                    ProfileFileRetryConfigProvider::builder()
+                        .profile_name(profile.as_str())
+                        .build()
+                        .retry_config_builder(),
+                )
+                .map_err(|err| ReplibyteError::Config(err.to_string()))?
+                .build(),
+            )
+    }
+
+    if let Some(region) = region {
+        config_loader = config_loader.region(Region::new(region))
+    }
+
+    if let Some(credentials) = credentials {
+        config_loader = config_loader.credentials_provider(Credentials::new(
+            credentials.access_key_id,
+            credentials.secret_access_key,
+            credentials.session_token,
+            None,
+            "replibyte-config",
+        ))
+    }
+
+    Ok(block_on(config_loader.load()))
+}
@@ -10,11 +10,29 @@ pub struct CLI {
     /// Replibyte configuration file
     #[clap(short, long, parse(from_os_str), value_name = "configuration file")]
     pub config: PathBuf,
+    /// profile of the configuration file merged over its other settings, see `profiles`
+    #[clap(short, long, value_name = "name")]
+    pub profile: Option<String>,
     #[clap(subcommand)]
     pub sub_commands: SubCommand,
     /// disable telemetry
     #[clap(short, long)]
     pub no_telemetry: bool,
+    /// do not show the progress of the dumps and the restores (e.g. in a CI job)
+    #[clap(short, long)]
+    pub quiet: bool,
+    /// format of the logs, `json` to ingest them in a log management tool
+    #[clap(long, value_name = "[text | json]", possible_values = &["text", "json"], default_value = "text")]
+    pub log_format: String,
+    /// log levels, with the format of RUST_LOG (e.g. `info` or `replibyte::event=info`)
+    #[clap(long, value_name = "levels")]
+    pub log_level: Option<String>,
+    /// prompt for the database passwords missing from the connection uris, `~/.pgpass` and `~/.my.cnf`
+    #[clap(long)]
+    pub ask_password: bool,
+    /// maximum bandwidth of the transfers to and from the datastores (e.g. `50MB/s`), see `bandwidth.max`
+    #[clap(long, value_name = "bandwidth")]
+    pub max_bandwidth: Option<String>,
 }
 
 /// sub commands
@@ -26,13 +44,62 @@ pub enum SubCommand {
     /// all transformers command
     #[clap(subcommand)]
     Transformer(TransformerCommand),
+    /// scan the source for personal data and suggest the transformers to use
+    Scan(ScanArgs),
+    /// create the dumps on the schedule of the `daemon` object of the configuration file
+    Daemon(DaemonArgs),
+    /// all local development environment commands
+    #[clap(subcommand)]
+    Dev(DevCommand),
+    /// check the configuration file, the required binaries, and the connections to the databases and the datastores
+    Check(CheckArgs),
+    /// all seed commands
+    #[clap(subcommand)]
+    Seed(SeedCommand),
+}
+
+impl SubCommand {
+    /// name of the command, e.g. `dump-create`
+    pub fn name(&self) -> &'static str {
+        match self {
+            SubCommand::Dump(cmd) => match cmd {
+                DumpCommand::List(_) => "dump-list",
+                DumpCommand::Create(_) => "dump-create",
+                DumpCommand::Delete(_) => "dump-delete",
+                DumpCommand::Rewrap(_) => "dump-rewrap",
+                DumpCommand::Inspect(_) => "dump-inspect",
+                DumpCommand::Verify(_) => "dump-verify",
+                DumpCommand::Diff(_) => "dump-diff",
+                DumpCommand::Download(_) => "dump-download",
+                DumpCommand::Prune(_) => "dump-prune",
+                DumpCommand::Import(_) => "dump-import",
+                DumpCommand::Restore(restore_cmd) => match restore_cmd {
+                    RestoreCommand::Local(_) => "dump-restore-local",
+                    RestoreCommand::Remote(_) => "dump-restore-remote",
+                    RestoreCommand::K8s(_) => "dump-restore-k8s",
+                },
+            },
+            SubCommand::Transformer(cmd) => match cmd {
+                TransformerCommand::List => "transformer-list",
+            },
+            SubCommand::Scan(_) => "scan",
+            SubCommand::Daemon(_) => "daemon",
+            SubCommand::Dev(cmd) => match cmd {
+                DevCommand::Up(_) => "dev-up",
+            },
+            SubCommand::Check(_) => "check",
+            SubCommand::Seed(cmd) => match cmd {
+                SeedCommand::Generate(_) => "seed-generate",
+            },
+        }
+    }
 }
 
 /// all dump commands
 #[derive(Subcommand, Debug)]
 pub enum DumpCommand {
     /// list available dumps
-    List,
+    List(DumpListArgs),
     /// launch dump -- use `-h` to show all the options
     Create(DumpCreateArgs),
     /// all restore commands
@@ -40,6 +107,34 @@ pub enum DumpCommand {
     Restore(RestoreCommand),
     /// delete a dump from the defined datastore
     Delete(DumpDeleteArgs),
+    /// re-encrypt the dumps with the newest encryption key
+    Rewrap(DumpRewrapArgs),
+    /// show the tables, the subset and the transformers of a dump without downloading it
+    Inspect(DumpInspectArgs),
+    /// read a dump and compare its parts with their checksums, to detect a corrupted dump before restoring it
+    Verify(DumpVerifyArgs),
+    /// compare the tables, the rows and the schema of two dumps
+    Diff(DumpDiffArgs),
+    /// download a dump to a local file, decrypted and decompressed
+    Download(DumpDownloadArgs),
+    /// delete the dumps not kept by the retention policy of the datastore
+    Prune(DumpPruneArgs),
+    /// import a dump file made by another tool (e.g. `pg_dump`) into the datastore
+    Import(DumpImportArgs),
+}
+
+/// all local development environment commands
+#[derive(Subcommand, Debug)]
+pub enum DevCommand {
+    /// restore a dump in a Docker container matching the source and write its connection string in a `.env` file
+    Up(DevUpArgs),
+}
+
+/// all seed commands
+#[derive(Subcommand, Debug)]
+pub enum SeedCommand {
+    /// generate fake rows from the schema of the source (or of a file) and store them as a dump, without reading any row
+    Generate(SeedGenerateArgs),
 }
 
 /// all transformer commands
@@ -49,6 +144,14 @@ pub enum TransformerCommand {
     List,
 }
 
+/// scan the source for personal data
+#[derive(Args, Debug)]
+pub struct ScanArgs {
+    /// number of values sampled per column
+    #[clap(long, default_value = "100")]
+    pub sample_size: usize,
+}
+
 /// all restore commands
 #[derive(Subcommand, Debug)]
 pub enum RestoreCommand {
@@ -56,6 +159,8 @@ pub enum RestoreCommand {
     Local(RestoreLocalArgs),
     /// Restore dump inside the configured destination
     Remote(RestoreArgs),
+    /// Restore dump inside the configured destination from a Kubernetes Job, where the destination is reachable
+    K8s(RestoreK8sArgs),
 }
 
 /// all restore commands
@@ -64,9 +169,65 @@ pub struct RestoreArgs {
     /// restore dump -- set `latest` or `<dump name>` - use `dump list` command to list all dumps available
     #[clap(short, long, value_name = "[latest | dump name]")]
     pub value: String,
-    /// stream output on stdout
+    /// write the restored dump to a file instead, `-` or no file to stream it on stdout
+    #[clap(
+        short,
+        long,
+        value_name = "FILE",
+        min_values = 0,
+        default_missing_value = "-"
+    )]
+    pub output: Option<PathBuf>,
+    /// number of tables restored concurrently (PostgreSQL, MySQL, MariaDB and SQL Server destinations)
+    #[clap(long, default_value = "1")]
+    pub concurrency: usize,
+    /// record the restored chunks and tables in a local checkpoint file, to `--resume` the restore if it is interrupted
+    #[clap(long)]
+    pub checkpoint: bool,
+    /// resume an interrupted restore, the chunks and tables already restored are skipped
+    #[clap(long)]
+    pub resume: bool,
+    /// only restore this table -- can be repeated (e.g. `--table users --table public.orders`)
+    #[clap(long = "table", value_name = "table name")]
+    pub tables: Vec<String>,
+    /// only restore the schema (e.g. tables, indexes, constraints), without the data
+    #[clap(long, conflicts_with = "data-only")]
+    pub schema_only: bool,
+    /// only restore the data into the existing schema, the destination database is not wiped
+    #[clap(long)]
+    pub data_only: bool,
+    /// apply the transformers of the source to the dump while it is restored, e.g. to a dump created without them
+    #[clap(long)]
+    pub transform: bool,
+}
+
+/// restore dump from a Kubernetes Job
+#[derive(Args, Debug)]
+pub struct RestoreK8sArgs {
+    /// restore dump -- set `latest` or `<dump name>` - use `dump list` command to list all dumps available
+    #[clap(short, long, value_name = "[latest | dump name]")]
+    pub value: String,
+    /// kubectl context of the cluster, the current context otherwise
+    #[clap(long)]
+    pub context: Option<String>,
+    /// namespace of the Job, the namespace of the context otherwise
     #[clap(short, long)]
-    pub output: bool,
+    pub namespace: Option<String>,
+    /// Docker image of Replibyte run by the Job, `ghcr.io/qovery/replibyte` with the version of this binary otherwise
+    #[clap(long)]
+    pub image: Option<String>,
+    /// Secret whose keys are set as environment variables of the Job, e.g. the variables of the configuration file -- can be repeated
+    #[clap(long = "env-from-secret", value_name = "secret name")]
+    pub env_from_secrets: Vec<String>,
+    /// seconds to wait for the pod of the Job to run (e.g. to pull the image)
+    #[clap(long, default_value = "300")]
+    pub timeout: u64,
+    /// keep the Job and the Secret holding the configuration once the restore is finished
+    #[clap(long)]
+    pub keep: bool,
+    /// apply the transformers of the source to the dump while it is restored, e.g. to a dump created without them
+    #[clap(long)]
+    pub transform: bool,
 }
 
 /// restore dump in a local Docker container
@@ -75,27 +236,45 @@ pub struct RestoreLocalArgs {
     /// restore dump -- set `latest` or `<dump name>` - use `dump list` command to list all dumps available
     #[clap(short, long, value_name = "[latest | dump name]")]
     pub value: String,
-    /// stream output on stdout
-    #[clap(short, long)]
-    pub output: bool,
+    /// write the restored dump to a file instead, `-` or no file to stream it on stdout
+    #[clap(
+        short,
+        long,
+        value_name = "FILE",
+        min_values = 0,
+        default_missing_value = "-"
+    )]
+    pub output: Option<PathBuf>,
     /// Docker image tag for the container to spawn
     #[clap(short, long)]
     pub tag: Option<String>,
     /// Docker container port to map on the host
     #[clap(short, long)]
     pub port: Option<u16>,
-    /// Remove the Docker container on Ctrl-c
-    #[clap(short, long)]
+    /// Remove the Docker container on Ctrl-c, or if the restore fails
+    #[clap(short, long, visible_alias = "rm-on-exit")]
     pub remove: bool,
     /// Docker image type
     #[clap(short, long, value_name = "[postgresql | mysql | mongodb]")]
     pub image: Option<String>,
+    /// Docker container name
+    #[clap(long)]
+    pub name: Option<String>,
+    /// environment variable of the Docker container -- can be repeated (e.g. `--env TZ=UTC`)
+    #[clap(long = "env", value_name = "KEY=VALUE")]
+    pub env: Vec<String>,
+    /// volume mounted in the Docker container -- can be repeated (e.g. `--volume /tmp/data:/data`)
+    #[clap(long = "volume", value_name = "HOST_PATH:CONTAINER_PATH")]
+    pub volumes: Vec<String>,
+    /// apply the transformers of the source to the dump while it is restored, e.g. to a dump created without them
+    #[clap(long)]
+    pub transform: bool,
 }
 
 /// all dump run commands
 #[derive(Args, Debug)]
 pub struct DumpCreateArgs {
-    #[clap(name = "source_type", short, long, value_name = "[postgresql | mysql | mongodb]", possible_values = &["postgresql", "mysql", "mongodb"], requires = "input")]
+    #[clap(name = "source_type", short, long, value_name = "[postgresql | mysql | mariadb | mongodb | sqlite | mssql]", possible_values = &["postgresql", "mysql", "mariadb", "mongodb", "sqlite", "mssql"], requires = "input")]
     /// database source type to import
     pub source_type: Option<String>,
     /// import dump from stdin
@@ -107,6 +286,232 @@ pub struct DumpCreateArgs {
     /// dump name
     #[clap(short, long)]
     pub name: Option<String>,
+    /// only dump the rows changed since the latest dump (PostgreSQL only), see `source.incremental`
+    #[clap(long, conflicts_with = "source_type")]
+    pub incremental: bool,
+    /// fail and delete the dump if columns without transformer seem to contain personal data
+    #[clap(long)]
+    pub check_coverage: bool,
+    /// print the original and transformed rows instead of writing the dump to the datastore
+    #[clap(long)]
+    pub dry_run: bool,
+    /// number of rows printed per table with `--dry-run`
+    #[clap(long, default_value = "10", requires = "dry-run")]
+    pub sample: usize,
+    /// read the source at the maximum bandwidth too, see `bandwidth.source`
+    #[clap(long)]
+    pub throttle_source: bool,
+}
+
+/// import a dump file into the datastore
+#[derive(Args, Debug)]
+pub struct DumpImportArgs {
+    /// dump file, compressed with gzip or zstd or not -- `-` to read it from stdin
+    #[clap(parse(from_os_str), value_name = "FILE")]
+    pub file: PathBuf,
+    /// database type of the dump
+    #[clap(short, long, value_name = "[postgresql | mysql | mariadb | mongodb | sqlite | mssql]", possible_values = &["postgres", "postgresql", "mysql", "mariadb", "mongodb", "sqlite", "mssql"])]
+    pub source_type: String,
+    /// dump name
+    #[clap(short, long)]
+    pub name: Option<String>,
+    /// apply the transformers of the source to the dump, it is imported as it is otherwise
+    #[clap(long)]
+    pub transform: bool,
+}
+
+impl DumpImportArgs {
+    /// arguments of the dump created from the file
+    pub fn dump_create_args(&self) -> DumpCreateArgs {
+        let source_type = match self.source_type.as_str() {
+            "postgres" => "postgresql",
+            source_type => source_type,
+        };
+
+        DumpCreateArgs {
+            source_type: Some(source_type.to_string()),
+            input: true,
+            file: Some(self.file.clone()),
+            name: self.name.clone(),
+            incremental: false,
+            check_coverage: false,
+            dry_run: false,
+            sample: 10,
+            throttle_source: false,
+        }
+    }
+}
+
+/// generate fake rows from a schema
+#[derive(Args, Debug)]
+pub struct SeedGenerateArgs {
+    /// file with the queries creating the tables (e.g. `pg_dump --schema-only`), the schema of the source is read otherwise
+    #[clap(long, parse(from_os_str), value_name = "FILE")]
+    pub schema: Option<PathBuf>,
+    /// database type of the schema file, the type of the source by default
+    #[clap(short, long, value_name = "[postgresql | mysql | mariadb]", possible_values = &["postgres", "postgresql", "mysql", "mariadb"])]
+    pub source_type: Option<String>,
+    /// rows generated per table
+    #[clap(short, long, default_value = "100")]
+    pub rows: usize,
+    /// seed of the random values, the same seed generates the same rows
+    #[clap(long)]
+    pub seed: Option<u64>,
+    /// dump name
+    #[clap(short, long)]
+    pub name: Option<String>,
+    /// write the dump to a file instead of the datastore -- `-` to write it to stdout
+    #[clap(short, long, parse(from_os_str), value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct CheckArgs {
+    /// only check the configuration file and the binaries, without connecting to the databases and the datastores
+    #[clap(long)]
+    pub offline: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct DaemonArgs {
+    /// only dump the rows changed since the latest dump (PostgreSQL only), see `source.incremental`
+    #[clap(long)]
+    pub incremental: bool,
+    /// fail and delete the dump if columns without transformer seem to contain personal data
+    #[clap(long)]
+    pub check_coverage: bool,
+}
+
+impl DaemonArgs {
+    /// arguments of the scheduled dumps
+    pub fn dump_create_args(&self) -> DumpCreateArgs {
+        DumpCreateArgs {
+            source_type: None,
+            input: false,
+            file: None,
+            name: None,
+            incremental: self.incremental,
+            check_coverage: self.check_coverage,
+            dry_run: false,
+            sample: 10,
+            throttle_source: false,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct DevUpArgs {
+    /// dump to restore -- set `latest` or `<dump name>`
+    #[clap(
+        short,
+        long,
+        value_name = "[latest | dump name]",
+        default_value = "latest"
+    )]
+    pub value: String,
+    /// Docker image tag for the container to spawn
+    #[clap(short, long)]
+    pub tag: Option<String>,
+    /// Docker container port to map on the host
+    #[clap(short, long)]
+    pub port: Option<u16>,
+    /// Docker container name
+    #[clap(long, default_value = "replibyte-dev")]
+    pub name: String,
+    /// environment variable of the Docker container -- can be repeated (e.g. `--env TZ=UTC`)
+    #[clap(long = "env", value_name = "KEY=VALUE")]
+    pub env: Vec<String>,
+    /// volume mounted in the Docker container -- can be repeated (e.g. `--volume /tmp/data:/data`)
+    #[clap(long = "volume", value_name = "HOST_PATH:CONTAINER_PATH")]
+    pub volumes: Vec<String>,
+    /// file to write the connection string to, the other variables of the file are kept
+    #[clap(long, parse(from_os_str), value_name = "FILE", default_value = ".env")]
+    pub env_file: PathBuf,
+    /// variable of the env file set to the connection string
+    #[clap(long, value_name = "NAME", default_value = "DATABASE_URL")]
+    pub env_var: String,
+}
+
+impl DevUpArgs {
+    /// arguments of the restore in the container, which keeps running after the restore
+    pub fn restore_local_args(&self, image: &str) -> RestoreLocalArgs {
+        RestoreLocalArgs {
+            value: self.value.clone(),
+            output: None,
+            tag: self.tag.clone(),
+            port: self.port,
+            remove: false,
+            image: Some(image.to_string()),
+            name: Some(self.name.clone()),
+            env: self.env.clone(),
+            volumes: self.volumes.clone(),
+            transform: false,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct DumpRewrapArgs {
+    /// Name of the dump to re-encrypt, the dumps encrypted with an older key are re-encrypted otherwise
+    pub dump: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct DumpInspectArgs {
+    /// Name of the dump to inspect, `latest` for the latest dump
+    pub dump: String,
+    /// format of the output, `json` to read it from a script
+    #[clap(short, long, value_name = "[table | json]", possible_values = &["table", "json"], default_value = "table")]
+    pub output: String,
+}
+
+#[derive(Args, Debug)]
+pub struct DumpVerifyArgs {
+    /// Name of the dump to verify, `latest` for the latest dump. The dumps it is based on are verified too
+    pub dump: String,
+    /// only check the index file, the dump is not downloaded
+    #[clap(long)]
+    pub metadata_only: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct DumpDiffArgs {
+    /// Name of the dump to compare from, `latest` for the latest dump
+    pub from: String,
+    /// Name of the dump to compare to, `latest` for the latest dump
+    pub to: String,
+    /// format of the output, `json` to read it from a script
+    #[clap(short, long, value_name = "[table | json]", possible_values = &["table", "json"], default_value = "table")]
+    pub output: String,
+}
+
+#[derive(Args, Debug)]
+pub struct DumpDownloadArgs {
+    /// Name of the dump to download, `latest` for the latest dump. An incremental dump is downloaded after the dumps it is based on
+    pub dump: String,
+    /// file to write the dump to, compressed with gzip if it ends with `.gz`. `-` writes the dump on stdout
+    #[clap(short, long, value_name = "FILE")]
+    pub output: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct DumpListArgs {
+    /// format of the list, `json` to read it from a script
+    #[clap(short, long, value_name = "[table | json]", possible_values = &["table", "json"], default_value = "table")]
+    pub output: String,
+    /// only list the N latest dumps
+    #[clap(long)]
+    pub limit: Option<usize>,
+    /// only list the dumps created in the last days. Example: `7d` for the dumps of the last 7 days
+    #[clap(long)]
+    pub since: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct DumpPruneArgs {
+    /// list the dumps to delete without deleting them
+    #[clap(long)]
+    pub dry_run: bool,
 }
 
 #[derive(Args, Debug)]
@@ -121,4 +526,7 @@ pub struct DumpDeleteArgs {
     /// Keep only the last N dumps
     #[clap(long, group = "delete-mode")]
     pub keep_last: Option<usize>,
+    /// delete without asking for a confirmation
+    #[clap(long)]
+    pub force: bool,
 }
@@ -0,0 +1,157 @@
+use std::fs::{read_to_string, write};
+use std::io::ErrorKind;
+
+use crate::cli::DevUpArgs;
+use crate::commands::dump::{docker_restore, read_options};
+use crate::config::Config;
+use crate::datastore::{Datastore, ReadOptions};
+use crate::errors::ReplibyteError;
+use crate::tasks::Progress;
+
+/// restore a dump in a Docker container matching its source and write the connection string in the env file
+pub fn up<F>(
+    args: &DevUpArgs,
+    mut datastore: Box<dyn Datastore>,
+    config: Config,
+    progress_callback: F,
+) -> Result<(), ReplibyteError>
+where
+    F: Fn(&Progress),
+{
+    if let Some(encryption_keys) = config.encryption_keys()? {
+        datastore.set_encryption_keys(encryption_keys);
+    }
+
+    let mut index_file = datastore.index_file()?;
+    let dump = index_file.find_dump(&read_options(args.value.as_str()))?;
+    let dump_name = dump.directory_name.clone();
+
+    // the type of the dumps created with older versions of Replibyte is unknown
+    let source_type = match &dump.source_type {
+        Some(source_type) => source_type.clone(),
+        None => match &config.source {
+            Some(source) => source.connection_uri()?.database_type().to_string(),
+            None => {
+                return Err(ReplibyteError::Config(format!(
+                    "the type of the dump '{}' is unknown, add a <source> object in the configuration file",
+                    dump_name
+                )));
+            }
+        },
+    };
+
+    let image = image(source_type.as_str())?;
+    let restore_args = args.restore_local_args(image);
+
+    let database = docker_restore(
+        image,
+        &restore_args,
+        datastore,
+        ReadOptions::Dump {
+            name: dump_name.clone(),
+        },
+        None,
+        progress_callback,
+    )?;
+
+    let env_file = match read_to_string(&args.env_file) {
+        Ok(env_file) => env_file,
+        Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(ReplibyteError::from(err)),
+    };
+
+    write(
+        &args.env_file,
+        set_env_var(
+            env_file.as_str(),
+            args.env_var.as_str(),
+            database.connection_string.as_str(),
+        ),
+    )?;
+
+    println!(
+        "{} restored in the {} container {}",
+        dump_name,
+        database.engine,
+        database.container.display_name(&database.options)
+    );
+    println!(
+        "{}={} written to {}",
+        args.env_var,
+        database.connection_string,
+        args.env_file.display()
+    );
+
+    Ok(())
+}
+
+/// Docker image of `dump restore local` matching the type of the source
+fn image(source_type: &str) -> Result<&'static str, ReplibyteError> {
+    match source_type {
+        "postgresql" => Ok("postgresql"),
+        "mysql" | "mariadb" => Ok("mysql"),
+        "mongodb" => Ok("mongodb"),
+        source_type => Err(ReplibyteError::Config(format!(
+            "no Docker image for the {} dumps, only PostgreSQL, MySQL, MariaDB and MongoDB are supported",
+            source_type
+        ))),
+    }
+}
+
+/// set the variable in the content of an env file, the other lines are kept
+fn set_env_var(env_file: &str, name: &str, value: &str) -> String {
+    let line = format!("{}={}", name, value);
+    let prefix = format!("{}=", name);
+    let mut is_set = false;
+
+    let mut lines = env_file
+        .lines()
+        .map(|current_line| {
+            if current_line.trim_start().starts_with(prefix.as_str()) {
+                is_set = true;
+                line.clone()
+            } else {
+                current_line.to_string()
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if !is_set {
+        lines.push(line);
+    }
+
+    format!("{}\n", lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::dev::{image, set_env_var};
+
+    #[test]
+    fn source_images() {
+        assert_eq!(image("postgresql").unwrap(), "postgresql");
+        assert_eq!(image("mariadb").unwrap(), "mysql");
+        assert_eq!(image("mongodb").unwrap(), "mongodb");
+        assert!(image("sqlite").is_err());
+    }
+
+    #[test]
+    fn env_file() {
+        assert_eq!(
+            set_env_var("", "DATABASE_URL", "postgres://localhost:5432/db"),
+            "DATABASE_URL=postgres://localhost:5432/db\n"
+        );
+        assert_eq!(
+            set_env_var(
+                "# local settings\nDEBUG=true\nDATABASE_URL=postgres://old\n",
+                "DATABASE_URL",
+                "postgres://localhost:5432/db"
+            ),
+            "# local settings\nDEBUG=true\nDATABASE_URL=postgres://localhost:5432/db\n"
+        );
+        assert_eq!(
+            set_env_var("DEBUG=true", "DATABASE_URL", "mysql://localhost:3306/db"),
+            "DEBUG=true\nDATABASE_URL=mysql://localhost:3306/db\n"
+        );
+    }
+}
@@ -1,2 +1,6 @@
+pub mod check;
+pub mod dev;
 pub mod dump;
+pub mod scan;
+pub mod seed;
 pub mod transformer;
@@ -0,0 +1,729 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::io::{Error, ErrorKind};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::rc::Rc;
+
+use fake::locales::{Data, EN, FR_FR};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::ScanArgs;
+use crate::config::{
+    ColumnConfig, Config, ConnectionUri, OnlyTablesConfig, RowFilterConfig, SkipConfig,
+    TransformerConfig, TransformerTypeConfig,
+};
+use crate::errors::ReplibyteError;
+use crate::proxy::{database_proxy, proxy_connection_uri};
+use crate::source::mariadb::MariaDB;
+use crate::source::mongodb::MongoDB;
+use crate::source::mssql::MSSQL;
+use crate::source::mysql::Mysql;
+use crate::source::postgres::Postgres;
+use crate::source::sqlite::SQLite;
+use crate::source::{replica, Source, SourceOptions};
+use crate::ssh::tunnel_connection_uri;
+use crate::transformer::credit_card::luhn_check_digit;
+use crate::transformer::faker::{FakerKind, FakerTransformerOptions};
+use crate::transformer::Transformer;
+use crate::types::Column;
+
+/// the database of the transformers of the sources without database names in their dumps
+pub const SQLITE_DATABASE_NAME: &str = "main";
+
+lazy_static! {
+    static ref EMAIL: Regex =
+        Regex::new(r"^[A-Za-z0-9._%+'-]+@[A-Za-z0-9-]+(\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}$").unwrap();
+    static ref SSN: Regex = Regex::new(r"^\d{3}-\d{2}-\d{4}$").unwrap();
+    static ref IBAN: Regex = Regex::new(r"^[A-Z]{2}\d{2}( ?[A-Z0-9]){11,30}$").unwrap();
+    static ref MAC: Regex = Regex::new(
+        r"^[0-9A-Fa-f]{2}([:-][0-9A-Fa-f]{2}){5}$|^[0-9A-Fa-f]{4}(\.[0-9A-Fa-f]{4}){2}$"
+    )
+    .unwrap();
+    static ref PHONE_NUMBER: Regex = Regex::new(r"^\+?[0-9(][0-9 ().-]*[0-9]$").unwrap();
+    static ref DATE: Regex = Regex::new(r"^\d{4}[-/.]\d{1,2}[-/.]\d{1,2}$").unwrap();
+    static ref DECIMAL: Regex = Regex::new(r"^\d+\.\d+$").unwrap();
+}
+
+/// Scan the source for personal data, and print the transformers to add to the configuration
+pub fn run(args: &ScanArgs, config: Config) -> Result<(), ReplibyteError> {
+    let source = match config.source {
+        Some(source) => source,
+        None => {
+            return Err(ReplibyteError::Config(
+                "missing <source> object in the configuration file".to_string(),
+            ));
+        }
+    };
+
+    let (connection_uri, tls) = replica::source_connection(&source)?;
+    let proxy = database_proxy(source.proxy()?, source.ssh.as_ref(), &connection_uri)?;
+
+    // the tunnels are closed when they go out of scope
+    let (connection_uri, _ssh_tunnel) =
+        tunnel_connection_uri(source.ssh.as_ref(), proxy.as_ref(), connection_uri)?;
+
+    let (connection_uri, _proxy_tunnel) = match &source.ssh {
+        // the SSH connection goes through the proxy instead
+        Some(_) => (connection_uri, None),
+        None => proxy_connection_uri(proxy.as_ref(), connection_uri)?,
+    };
+
+    let samples = Rc::new(RefCell::new(BTreeMap::new()));
+
+    // the columns with a transformer are already safe, the sampler only gets the other ones
+    let mut transformers = source.transformers();
+    transformers.push(Box::new(SampleTransformer::new(
+        default_database_name(&connection_uri),
+        args.sample_size,
+        samples.clone(),
+    )));
+
+    let skip_config = &source.skip_config();
+    let empty_config = vec![];
+    let only_tables_config = source.only_tables.as_ref().unwrap_or(&empty_config);
+    let filters_config = &source.filters();
+
+    match connection_uri {
+        ConnectionUri::Postgres(host, port, username, password, database) => {
+            let postgres = Postgres::new(
+                host.as_str(),
+                port,
+                database.as_str(),
+                username.as_str(),
+                password.as_str(),
+                source.dialect.unwrap_or_default(),
+                source.schemas.as_deref().unwrap_or(&[]),
+                source.dump_format.unwrap_or_default(),
+                source.consistency.unwrap_or_default(),
+                &tls,
+            );
+
+            sample(
+                postgres,
+                &transformers,
+                skip_config,
+                only_tables_config,
+                filters_config,
+                source.encoding.as_deref(),
+            )?
+        }
+        ConnectionUri::Mysql(host, port, username, password, database) => {
+            let mysql = Mysql::new(
+                host.as_str(),
+                port,
+                database.as_str(),
+                username.as_str(),
+                password.as_str(),
+                source.consistency.unwrap_or_default(),
+                &tls,
+            );
+
+            sample(
+                mysql,
+                &transformers,
+                skip_config,
+                only_tables_config,
+                filters_config,
+                source.encoding.as_deref(),
+            )?
+        }
+        ConnectionUri::MariaDB(host, port, username, password, database) => {
+            let mariadb = MariaDB::new(
+                host.as_str(),
+                port,
+                database.as_str(),
+                username.as_str(),
+                password.as_str(),
+                source.consistency.unwrap_or_default(),
+            );
+
+            sample(
+                mariadb,
+                &transformers,
+                skip_config,
+                only_tables_config,
+                filters_config,
+                source.encoding.as_deref(),
+            )?
+        }
+        ConnectionUri::MongoDB(uri, database) => {
+            let mongodb = MongoDB::new(uri.as_str(), database.as_str());
+
+            sample(
+                mongodb,
+                &transformers,
+                skip_config,
+                only_tables_config,
+                filters_config,
+                source.encoding.as_deref(),
+            )?
+        }
+        ConnectionUri::MSSQL(host, port, username, password, database) => {
+            let mssql = MSSQL::new(
+                host.as_str(),
+                port,
+                database.as_str(),
+                username.as_str(),
+                password.as_str(),
+            );
+
+            sample(
+                mssql,
+                &transformers,
+                skip_config,
+                only_tables_config,
+                filters_config,
+                source.encoding.as_deref(),
+            )?
+        }
+        ConnectionUri::SQLite(path) => {
+            let sqlite = SQLite::new(path.as_str());
+
+            sample(
+                sqlite,
+                &transformers,
+                skip_config,
+                only_tables_config,
+                filters_config,
+                source.encoding.as_deref(),
+            )?
+        }
+    }
+
+    let report = ScanReport {
+        transformers: suggested_transformers(&samples.borrow()),
+    };
+
+    if report.transformers.is_empty() {
+        eprintln!("no personal data found");
+        return Ok(());
+    }
+
+    let yaml =
+        serde_yaml::to_string(&report).map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+    println!("{}", yaml.trim_start_matches("---\n"));
+
+    Ok(())
+}
+
+fn sample<S: Source>(
+    mut source: S,
+    transformers: &Vec<Box<dyn Transformer>>,
+    skip_config: &Vec<SkipConfig>,
+    only_tables_config: &Vec<OnlyTablesConfig>,
+    filters_config: &Vec<RowFilterConfig>,
+    encoding: Option<&str>,
+) -> Result<(), ReplibyteError> {
+    let _ = source.init()?;
+
+    let options = SourceOptions {
+        transformers,
+        skip_config,
+        database_subset: &None,
+        only_tables: only_tables_config,
+        filters: filters_config,
+        encoding,
+        skip_large_objects: false,
+        skip_bytea: false,
+    };
+
+    // the values are sampled by the transformer, the queries are not kept
+    source.read(options, |_, _| {})
+}
+
+/// the database of the columns of a source, when the names of its columns do not include it
+pub fn default_database_name(connection_uri: &ConnectionUri) -> Option<String> {
+    match connection_uri {
+        ConnectionUri::Mysql(_, _, _, _, database)
+        | ConnectionUri::MariaDB(_, _, _, _, database) => Some(database.to_string()),
+        ConnectionUri::SQLite(_) => Some(SQLITE_DATABASE_NAME.to_string()),
+        ConnectionUri::Postgres(..) | ConnectionUri::MongoDB(..) | ConnectionUri::MSSQL(..) => None,
+    }
+}
+
+/// sampled values by database, table and column name
+pub type Samples = BTreeMap<(String, String, String), Vec<String>>;
+
+#[derive(Serialize, Deserialize)]
+struct ScanReport {
+    transformers: Vec<TransformerConfig>,
+}
+
+/// the columns with personal data, found in their sampled values or in their names
+pub fn personal_data_columns(samples: &Samples) -> Vec<(&String, &String, &String, PersonalData)> {
+    samples
+        .iter()
+        .filter_map(|((database_name, table_name, column_name), values)| {
+            detect(column_name, values)
+                .map(|personal_data| (database_name, table_name, column_name, personal_data))
+        })
+        .collect()
+}
+
+/// the transformers of the columns with personal data, grouped by table
+fn suggested_transformers(samples: &Samples) -> Vec<TransformerConfig> {
+    let mut transformers: Vec<TransformerConfig> = vec![];
+
+    for (database_name, table_name, column_name, personal_data) in personal_data_columns(samples) {
+        let column = ColumnConfig {
+            name: column_name.to_string(),
+            transformer: personal_data.transformer(),
+            when: None,
+        };
+
+        match transformers.last_mut() {
+            Some(transformer)
+                if transformer.database == *database_name && transformer.table == *table_name =>
+            {
+                transformer.columns.push(column)
+            }
+            _ => transformers.push(TransformerConfig {
+                database: database_name.to_string(),
+                table: table_name.to_string(),
+                columns: vec![column],
+                groups: None,
+            }),
+        }
+    }
+
+    transformers
+}
+
+/// Transformer sampling the first string values of every column, without changing them
+pub struct SampleTransformer {
+    /// database of the columns when the names of the columns do not include it
+    default_database_name: Option<String>,
+    sample_size: usize,
+    samples: Rc<RefCell<Samples>>,
+}
+
+impl SampleTransformer {
+    pub fn new(
+        default_database_name: Option<String>,
+        sample_size: usize,
+        samples: Rc<RefCell<Samples>>,
+    ) -> Self {
+        SampleTransformer {
+            default_database_name,
+            sample_size,
+            samples,
+        }
+    }
+}
+
+impl Transformer for SampleTransformer {
+    fn id(&self) -> &str {
+        "sample"
+    }
+
+    fn description(&self) -> &str {
+        "Sample the values of the columns to scan them."
+    }
+
+    fn database_name(&self) -> &str {
+        "*"
+    }
+
+    fn table_name(&self) -> &str {
+        "*"
+    }
+
+    fn column_name(&self) -> &str {
+        "*"
+    }
+
+    fn transform(&self, column: Column) -> Column {
+        column
+    }
+
+    fn transform_column(&self, name: &str, column: Column, _row: &[Column]) -> Column {
+        let (database_name, table_and_column_name) = match &self.default_database_name {
+            Some(database_name) => (database_name.as_str(), name),
+            None => match name.split_once('.') {
+                Some(names) => names,
+                None => return column,
+            },
+        };
+
+        let (table_name, column_name) = match table_and_column_name.split_once('.') {
+            Some(names) => names,
+            None => return column,
+        };
+
+        let mut samples = self.samples.borrow_mut();
+        let values = samples
+            .entry((
+                database_name.to_string(),
+                table_name.to_string(),
+                column_name.to_string(),
+            ))
+            .or_default();
+
+        match &column {
+            Column::StringValue(_, value)
+                if !value.trim().is_empty() && values.len() < self.sample_size =>
+            {
+                values.push(value.trim().to_string())
+            }
+            _ => {}
+        }
+
+        column
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PersonalData {
+    Email,
+    CreditCard,
+    Iban,
+    Ssn,
+    Ipv4,
+    Ipv6,
+    Mac,
+    PhoneNumber,
+    Name,
+    FirstName,
+    LastName,
+}
+
+/// the kinds of personal data, the first one matching the values of a column is used
+const PERSONAL_DATA: [PersonalData; 11] = [
+    PersonalData::Email,
+    PersonalData::CreditCard,
+    PersonalData::Iban,
+    PersonalData::Ssn,
+    PersonalData::Ipv4,
+    PersonalData::Ipv6,
+    PersonalData::Mac,
+    PersonalData::PhoneNumber,
+    PersonalData::Name,
+    PersonalData::FirstName,
+    PersonalData::LastName,
+];
+
+impl PersonalData {
+    pub fn name(&self) -> &str {
+        match self {
+            PersonalData::Email => "email",
+            PersonalData::CreditCard => "credit card number",
+            PersonalData::Iban => "IBAN",
+            PersonalData::Ssn => "social security number",
+            PersonalData::Ipv4 => "IPv4 address",
+            PersonalData::Ipv6 => "IPv6 address",
+            PersonalData::Mac => "MAC address",
+            PersonalData::PhoneNumber => "phone number",
+            PersonalData::Name => "name",
+            PersonalData::FirstName => "first name",
+            PersonalData::LastName => "last name",
+        }
+    }
+
+    fn matches(&self, value: &str) -> bool {
+        match self {
+            PersonalData::Email => EMAIL.is_match(value),
+            PersonalData::CreditCard => is_credit_card_number(value),
+            PersonalData::Iban => IBAN.is_match(value) && is_iban_valid(value),
+            PersonalData::Ssn => SSN.is_match(value),
+            PersonalData::Ipv4 => value.parse::<Ipv4Addr>().is_ok(),
+            PersonalData::Ipv6 => value.parse::<Ipv6Addr>().is_ok(),
+            PersonalData::Mac => MAC.is_match(value),
+            PersonalData::PhoneNumber => is_phone_number(value),
+            PersonalData::Name => match value.split_once(' ') {
+                Some((first_name, last_name)) => {
+                    is_first_name(first_name) && is_last_name(last_name)
+                }
+                None => false,
+            },
+            PersonalData::FirstName => is_first_name(value),
+            PersonalData::LastName => is_last_name(value),
+        }
+    }
+
+    /// share of the sampled values which must match, the dictionaries miss many names
+    fn min_match_ratio(&self) -> f64 {
+        match self {
+            PersonalData::Name | PersonalData::FirstName | PersonalData::LastName => 0.5,
+            _ => 0.8,
+        }
+    }
+
+    /// the usual names of the columns, lowercase and without separators
+    fn column_names(&self) -> &[&str] {
+        match self {
+            PersonalData::Email => &["email", "emailaddress", "mail"],
+            PersonalData::CreditCard => &["creditcard", "creditcardnumber", "cardnumber"],
+            PersonalData::Iban => &["iban"],
+            PersonalData::Ssn => &["ssn", "socialsecuritynumber"],
+            PersonalData::Ipv4 | PersonalData::Ipv6 => &[],
+            PersonalData::Mac => &["mac", "macaddress"],
+            PersonalData::PhoneNumber => &["phone", "phonenumber", "mobile", "telephone"],
+            PersonalData::Name => &["fullname"],
+            PersonalData::FirstName => &["firstname", "givenname"],
+            PersonalData::LastName => &["lastname", "surname", "familyname"],
+        }
+    }
+
+    fn transformer(&self) -> TransformerTypeConfig {
+        match self {
+            PersonalData::Email => TransformerTypeConfig::Email,
+            PersonalData::CreditCard => TransformerTypeConfig::CreditCard,
+            PersonalData::Iban => TransformerTypeConfig::Iban,
+            PersonalData::Ssn => TransformerTypeConfig::Random,
+            PersonalData::Ipv4 => TransformerTypeConfig::Ipv4(None),
+            PersonalData::Ipv6 => TransformerTypeConfig::Ipv6(None),
+            PersonalData::Mac => TransformerTypeConfig::Mac(None),
+            PersonalData::PhoneNumber => TransformerTypeConfig::PhoneNumber,
+            PersonalData::Name => faker(FakerKind::Name),
+            PersonalData::FirstName => TransformerTypeConfig::FirstName,
+            PersonalData::LastName => faker(FakerKind::LastName),
+        }
+    }
+}
+
+fn faker(kind: FakerKind) -> TransformerTypeConfig {
+    TransformerTypeConfig::Faker(FakerTransformerOptions { kind, seed: None })
+}
+
+/// the personal data of a column, from its sampled values or from its name
+fn detect(column_name: &str, values: &[String]) -> Option<PersonalData> {
+    if !values.is_empty() {
+        let personal_data = PERSONAL_DATA.iter().find(|personal_data| {
+            let matching_values = values
+                .iter()
+                .filter(|value| personal_data.matches(value))
+                .count();
+
+            matching_values as f64 / values.len() as f64 >= personal_data.min_match_ratio()
+        });
+
+        if personal_data.is_some() {
+            return personal_data.copied();
+        }
+    }
+
+    let column_name = column_name
+        .to_ascii_lowercase()
+        .replace(|c: char| !c.is_ascii_alphanumeric(), "");
+
+    PERSONAL_DATA
+        .iter()
+        .find(|personal_data| personal_data.column_names().contains(&column_name.as_str()))
+        .copied()
+}
+
+fn digits(value: &str) -> Vec<u32> {
+    value.chars().filter_map(|c| c.to_digit(10)).collect()
+}
+
+fn is_credit_card_number(value: &str) -> bool {
+    if value
+        .chars()
+        .any(|c| !(c.is_ascii_digit() || c == ' ' || c == '-'))
+    {
+        return false;
+    }
+
+    let digits = digits(value);
+    if !(13..=19).contains(&digits.len()) || !(2..=6).contains(&digits[0]) {
+        return false;
+    }
+
+    let (check_digit, digits) = digits.split_last().unwrap();
+    luhn_check_digit(digits) == *check_digit
+}
+
+/// the IBAN checksum: the number made of the rearranged IBAN modulo 97 is 1
+fn is_iban_valid(value: &str) -> bool {
+    let value = value.replace(' ', "");
+    let (country_and_check_digits, account) = value.split_at(4);
+
+    let remainder = format!("{}{}", account, country_and_check_digits)
+        .chars()
+        // unwrap is safe: the IBAN is alphanumeric
+        .map(|c| c.to_digit(36).unwrap())
+        .fold(0, |remainder, digit| match digit {
+            0..=9 => (remainder * 10 + digit) % 97,
+            _ => (remainder * 100 + digit) % 97,
+        });
+
+    remainder == 1
+}
+
+fn is_phone_number(value: &str) -> bool {
+    if !PHONE_NUMBER.is_match(value) || DATE.is_match(value) || DECIMAL.is_match(value) {
+        return false;
+    }
+
+    let digits = digits(value).len();
+    let has_separators = value.starts_with('+') || value.contains(|c: char| !c.is_ascii_digit());
+
+    // the numbers without separators could be any identifier
+    (7..=15).contains(&digits) && (has_separators || digits >= 10)
+}
+
+fn is_first_name(value: &str) -> bool {
+    [EN::NAME_FIRST_NAME, FR_FR::NAME_FIRST_NAME]
+        .iter()
+        .any(|names| names.iter().any(|name| name.eq_ignore_ascii_case(value)))
+}
+
+fn is_last_name(value: &str) -> bool {
+    [EN::NAME_LAST_NAME, FR_FR::NAME_LAST_NAME]
+        .iter()
+        .any(|names| names.iter().any(|name| name.eq_ignore_ascii_case(value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+    use std::rc::Rc;
+
+    use crate::config::TransformerTypeConfig;
+    use crate::transformer::selector::TransformerSelector;
+    use crate::transformer::Transformer;
+    use crate::types::Column;
+
+    use super::{detect, suggested_transformers, PersonalData, SampleTransformer, ScanReport};
+
+    #[test]
+    fn detect_personal_data() {
+        for (column_name, values, personal_data) in [
+            (
+                "contact",
+                vec!["john@doe.com", "jane.doe+test@company.co.uk"],
+                Some(PersonalData::Email),
+            ),
+            (
+                "card",
+                vec!["4242 4242 4242 4242", "5555-5555-5555-4444"],
+                Some(PersonalData::CreditCard),
+            ),
+            (
+                "account",
+                vec!["FR1420041010050500013M02606", "GB82 WEST 1234 5698 7654 32"],
+                Some(PersonalData::Iban),
+            ),
+            (
+                "social",
+                vec!["123-45-6789", "987-65-4321"],
+                Some(PersonalData::Ssn),
+            ),
+            (
+                "last_ip",
+                vec!["192.168.1.42", "10.0.0.1"],
+                Some(PersonalData::Ipv4),
+            ),
+            (
+                "last_ip",
+                vec!["2001:db8::1", "fe80::1"],
+                Some(PersonalData::Ipv6),
+            ),
+            (
+                "device",
+                vec!["00:1A:2B:3C:4D:5E", "001a.2b3c.4d5e"],
+                Some(PersonalData::Mac),
+            ),
+            (
+                "contact",
+                vec!["+33 6 12 34 56 78", "(555) 123-4567"],
+                Some(PersonalData::PhoneNumber),
+            ),
+            (
+                "owner",
+                vec!["John Smith", "Mary Johnson", "Unknown"],
+                Some(PersonalData::Name),
+            ),
+            (
+                "given",
+                vec!["John", "Mary", "Xyzzy"],
+                Some(PersonalData::FirstName),
+            ),
+            (
+                "family",
+                vec!["Smith", "Schmeler", "Xyzzy"],
+                Some(PersonalData::LastName),
+            ),
+            ("created_at", vec!["2022-01-01", "2022-12-31"], None),
+            ("price", vec!["1234567.89"], None),
+            ("code", vec!["1234567", "42"], None),
+            ("description", vec!["hello world", "john@doe.com"], None),
+            // without values, or values not matching, the name of the column is used
+            ("Email_Address", vec![], Some(PersonalData::Email)),
+            (
+                "phone-number",
+                vec!["unknown"],
+                Some(PersonalData::PhoneNumber),
+            ),
+            ("email_verified", vec![], None),
+        ] {
+            let values = values
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                detect(column_name, &values),
+                personal_data,
+                "{}",
+                column_name
+            );
+        }
+    }
+
+    #[test]
+    fn sample_and_suggest_transformers() {
+        let samples = Rc::new(RefCell::new(BTreeMap::new()));
+        let transformers: Vec<Box<dyn Transformer>> =
+            vec![Box::new(SampleTransformer::new(None, 2, samples.clone()))];
+        let selector = TransformerSelector::new(&transformers, true);
+
+        for (name, value) in [
+            ("public.users.email", "john@doe.com"),
+            ("public.users.email", "jane@doe.com"),
+            ("public.users.email", "not sampled"),
+            ("public.users.bio", "hello"),
+            ("public.orders.billing_phone", "+33 6 12 34 56 78"),
+        ] {
+            let column_name = name.rsplit('.').next().unwrap();
+            let column = Column::StringValue(column_name.to_string(), value.to_string());
+            let transformer = selector.get(name).unwrap();
+
+            // the values are not changed
+            let transformed_column = transformer.transform_column(name, column, &[]);
+            assert_eq!(transformed_column.string_value().unwrap(), value);
+        }
+
+        let samples = samples.borrow();
+        assert_eq!(
+            samples[&(
+                "public".to_string(),
+                "users".to_string(),
+                "email".to_string()
+            )],
+            vec!["john@doe.com", "jane@doe.com"]
+        );
+
+        let transformers = suggested_transformers(&samples);
+        assert_eq!(transformers.len(), 2);
+        assert_eq!(transformers[0].table, "orders");
+        assert_eq!(transformers[0].columns[0].name, "billing_phone");
+        assert_eq!(
+            transformers[0].columns[0].transformer,
+            TransformerTypeConfig::PhoneNumber
+        );
+        assert_eq!(transformers[1].table, "users");
+        assert_eq!(transformers[1].columns.len(), 1);
+        assert_eq!(
+            transformers[1].columns[0].transformer,
+            TransformerTypeConfig::Email
+        );
+
+        // the suggestion can be pasted into the configuration
+        let yaml = serde_yaml::to_string(&ScanReport { transformers }).unwrap();
+        let config = serde_yaml::from_str::<ScanReport>(yaml.as_str()).unwrap();
+        assert_eq!(config.transformers.len(), 2);
+    }
+}
@@ -0,0 +1,258 @@
+use std::fs::File;
+use std::io::{stdout, BufWriter, Error, ErrorKind, Write};
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+
+use crate::cli::{DumpImportArgs, SeedGenerateArgs};
+use crate::commands::dump;
+use crate::config::{Config, ConnectionUri, SourceConfig};
+use crate::datastore::Datastore;
+use crate::errors::ReplibyteError;
+use crate::proxy::{database_proxy, proxy_connection_uri};
+use crate::seed::Schema;
+use crate::source::dump_file::DumpType;
+use crate::source::mariadb::MariaDB;
+use crate::source::mysql::Mysql;
+use crate::source::postgres::Postgres;
+use crate::source::{replica, Source, SourceOptions};
+use crate::ssh::tunnel_connection_uri;
+use crate::tasks::diff::DumpSummary;
+use crate::tasks::transform::ChunksReader;
+use crate::tasks::Progress;
+use crate::types::Bytes;
+
+/// Generate the fake rows and store them as a dump, restored like any other dump
+pub fn generate<F>(
+    args: &SeedGenerateArgs,
+    datastore: Box<dyn Datastore>,
+    config: Config,
+    progress_callback: F,
+) -> Result<(), ReplibyteError>
+where
+    F: Fn(&Progress),
+{
+    let (schema, dump_type) = read_schema(args, config.source.as_ref())?;
+
+    let named_temp_file = tempfile::NamedTempFile::new()?;
+    write_dump(
+        args,
+        &schema,
+        &mut BufWriter::new(named_temp_file.as_file()),
+    )?;
+
+    let import_args = DumpImportArgs {
+        file: named_temp_file.path().to_path_buf(),
+        source_type: dump_type.database_type().to_string(),
+        name: args.name.clone(),
+        // the generated rows are fake already
+        transform: false,
+    };
+
+    dump::import(&import_args, datastore, config, progress_callback)
+}
+
+/// Generate the fake rows and write them to the `--output` file, or to stdout
+pub fn write(args: &SeedGenerateArgs, config: &Config) -> Result<(), ReplibyteError> {
+    let (schema, _) = read_schema(args, config.source.as_ref())?;
+
+    match args.output.as_deref() {
+        Some(path) if path.as_os_str() == "-" => {
+            write_dump(args, &schema, &mut BufWriter::new(stdout().lock()))?
+        }
+        Some(path) => write_dump(args, &schema, &mut BufWriter::new(File::create(path)?))?,
+        None => unreachable!("the dump is stored in the datastore without --output"),
+    }
+
+    Ok(())
+}
+
+/// the schema queries followed by the generated rows
+fn write_dump<W: Write>(
+    args: &SeedGenerateArgs,
+    (schema, queries): &(Schema, Vec<String>),
+    writer: &mut W,
+) -> Result<(), ReplibyteError> {
+    for query in queries {
+        writeln!(writer, "{}", query)?;
+    }
+
+    let rows = schema.write_rows(args.rows, args.seed, writer)?;
+    writer.flush()?;
+
+    eprintln!("{} rows generated in {} tables", rows, schema.tables.len());
+
+    Ok(())
+}
+
+/// the parsed schema and its queries, from the `--schema` file or from the source
+fn read_schema(
+    args: &SeedGenerateArgs,
+    source: Option<&SourceConfig>,
+) -> Result<((Schema, Vec<String>), DumpType), ReplibyteError> {
+    let (summary, dump_type) = match (&args.schema, source) {
+        (Some(path), source) => {
+            let dump_type = match (&args.source_type, source) {
+                (Some(source_type), _) => DumpType::new(source_type)?,
+                (None, Some(source)) => {
+                    DumpType::new(replica::source_connection(source)?.0.database_type())?
+                }
+                (None, None) => {
+                    return Err(ReplibyteError::Config(
+                        "missing --source-type, the type of the schema file cannot be deduced without a <source> object"
+                            .to_string(),
+                    ))
+                }
+            };
+
+            (read_schema_file(path, dump_type)?, dump_type)
+        }
+        (None, Some(source)) => read_source_schema(source)?,
+        (None, None) => {
+            return Err(ReplibyteError::Config(
+                "missing <source> object in the configuration file, or --schema to read the schema from a file"
+                    .to_string(),
+            ))
+        }
+    };
+
+    let schema = Schema::parse(&summary.schema, dump_type)?;
+    if schema.tables.is_empty() {
+        return Err(ReplibyteError::Config(
+            "no CREATE TABLE query found in the schema".to_string(),
+        ));
+    }
+
+    Ok(((schema, summary.schema), dump_type))
+}
+
+fn read_schema_file(path: &Path, dump_type: DumpType) -> Result<DumpSummary, ReplibyteError> {
+    let file = File::open(path).map_err(|err| {
+        Error::new(
+            err.kind(),
+            format!("cannot open the schema file '{}': {}", path.display(), err),
+        )
+    })?;
+
+    DumpSummary::from_reader(file, Some(dump_type))
+}
+
+/// read the dump of the source and keep its schema, its rows are dropped while it is read
+fn read_source_schema(source: &SourceConfig) -> Result<(DumpSummary, DumpType), ReplibyteError> {
+    let (connection_uri, tls) = replica::source_connection(source)?;
+    let proxy = database_proxy(source.proxy()?, source.ssh.as_ref(), &connection_uri)?;
+
+    // the tunnels are closed when they go out of scope
+    let (connection_uri, _ssh_tunnel) =
+        tunnel_connection_uri(source.ssh.as_ref(), proxy.as_ref(), connection_uri)?;
+
+    let (connection_uri, _proxy_tunnel) = match &source.ssh {
+        // the SSH connection goes through the proxy instead
+        Some(_) => (connection_uri, None),
+        None => proxy_connection_uri(proxy.as_ref(), connection_uri)?,
+    };
+
+    let dump_type = DumpType::new(connection_uri.database_type())?;
+
+    let summary = match connection_uri {
+        ConnectionUri::Postgres(host, port, username, password, database) => {
+            let postgres = Postgres::new(
+                host.as_str(),
+                port,
+                database.as_str(),
+                username.as_str(),
+                password.as_str(),
+                source.dialect.unwrap_or_default(),
+                source.schemas.as_deref().unwrap_or(&[]),
+                source.dump_format.unwrap_or_default(),
+                source.consistency.unwrap_or_default(),
+                &tls,
+            );
+
+            summary(postgres, source, dump_type)?
+        }
+        ConnectionUri::Mysql(host, port, username, password, database) => {
+            let mysql = Mysql::new(
+                host.as_str(),
+                port,
+                database.as_str(),
+                username.as_str(),
+                password.as_str(),
+                source.consistency.unwrap_or_default(),
+                &tls,
+            );
+
+            summary(mysql, source, dump_type)?
+        }
+        ConnectionUri::MariaDB(host, port, username, password, database) => {
+            let mariadb = MariaDB::new(
+                host.as_str(),
+                port,
+                database.as_str(),
+                username.as_str(),
+                password.as_str(),
+                source.consistency.unwrap_or_default(),
+            );
+
+            summary(mariadb, source, dump_type)?
+        }
+        ConnectionUri::MongoDB(..) | ConnectionUri::MSSQL(..) | ConnectionUri::SQLite(..) => {
+            return Err(ReplibyteError::Config(format!(
+                "the rows of a {} source cannot be generated, only PostgreSQL, MySQL and MariaDB are supported",
+                dump_type.database_type()
+            )));
+        }
+    };
+
+    Ok((summary, dump_type))
+}
+
+fn summary<S: Source>(
+    mut source: S,
+    source_config: &SourceConfig,
+    dump_type: DumpType,
+) -> Result<DumpSummary, ReplibyteError> {
+    let _ = source.init()?;
+
+    let skip_config = source_config.skip_config();
+    let only_tables = source_config.only_tables.clone().unwrap_or_default();
+
+    let options = SourceOptions {
+        // the rows are dropped, they are not transformed
+        transformers: &vec![],
+        skip_config: &skip_config,
+        database_subset: &None,
+        only_tables: &only_tables,
+        filters: &vec![],
+        encoding: source_config.encoding.as_deref(),
+        skip_large_objects: true,
+        skip_bytea: true,
+    };
+
+    // bound to 1 to avoid eating too much memory if the source has rows
+    let (tx, rx) = mpsc::sync_channel::<Bytes>(1);
+
+    thread::scope(|scope| {
+        let join_handle = scope.spawn(move || {
+            DumpSummary::from_reader(ChunksReader::new(rx.into_iter()), Some(dump_type))
+        });
+
+        let read_result = source.read(options, |_, query| {
+            let mut data = query.data().to_vec();
+            data.push(b'\n');
+            let _ = tx.send(data);
+        });
+
+        // the summary ends once the sender is dropped
+        drop(tx);
+
+        let summary = join_handle.join().unwrap_or_else(|_| {
+            Err(ReplibyteError::from(Error::new(
+                ErrorKind::Other,
+                "the read of the schema panicked",
+            )))
+        });
+
+        read_result.and(summary)
+    })
+}
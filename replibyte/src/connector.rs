@@ -1,5 +1,5 @@
-use std::io::Error;
+use crate::errors::ReplibyteError;
 
 pub trait Connector {
-    fn init(&mut self) -> Result<(), Error>;
+    fn init(&mut self) -> Result<(), ReplibyteError>;
 }
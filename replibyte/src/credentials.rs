@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use log::warn;
+
+use crate::config::ConnectionUri;
+use crate::errors::ReplibyteError;
+
+static ASK_PASSWORD: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    /// the passwords already typed, the connection uris are decoded several times by a command
+    static ref PASSWORDS: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
+}
+
+/// prompt for the passwords which are neither in the connection uris nor in the credential files
+pub fn ask_password(ask_password: bool) {
+    ASK_PASSWORD.store(ask_password, Ordering::Relaxed);
+}
+
+/// fill the password missing from the connection uri with the one of the credential file of the
+/// database clients (`~/.pgpass` or `~/.my.cnf`), or with the one typed by the user
+pub fn with_password(connection_uri: ConnectionUri) -> Result<ConnectionUri, ReplibyteError> {
+    let (username, host, port, database) = match &connection_uri {
+        ConnectionUri::Postgres(host, port, username, password, database)
+        | ConnectionUri::Mysql(host, port, username, password, database)
+        | ConnectionUri::MariaDB(host, port, username, password, database)
+        | ConnectionUri::MSSQL(host, port, username, password, database)
+            if password.is_empty() =>
+        {
+            (username, host, *port, database)
+        }
+        _ => return Ok(connection_uri),
+    };
+
+    let password = match &connection_uri {
+        ConnectionUri::Postgres(..) => pgpass_file()
+            .and_then(|pgpass_file| pgpass_password(&pgpass_file, host, port, database, username)),
+        ConnectionUri::Mysql(..) => {
+            mycnf_file().and_then(|mycnf_file| mycnf_password(&mycnf_file, username, &["client"]))
+        }
+        ConnectionUri::MariaDB(..) => mycnf_file().and_then(|mycnf_file| {
+            mycnf_password(&mycnf_file, username, &["client", "client-mariadb"])
+        }),
+        _ => None,
+    };
+
+    let password = match password {
+        Some(password) => password,
+        None if ASK_PASSWORD.load(Ordering::Relaxed) => {
+            prompt_password(format!("{}@{}:{}/{}", username, host, port, database))?
+        }
+        None => return Ok(connection_uri),
+    };
+
+    connection_uri.with_password(password.as_str())
+}
+
+fn prompt_password(connection: String) -> Result<String, ReplibyteError> {
+    if let Some(password) = PASSWORDS.lock().unwrap().get(&connection) {
+        return Ok(password.clone());
+    }
+
+    let password =
+        rpassword::prompt_password(format!("Password for {}: ", connection)).map_err(|err| {
+            ReplibyteError::Config(format!(
+                "cannot ask the password of {}: {}",
+                connection, err
+            ))
+        })?;
+
+    let _ = PASSWORDS
+        .lock()
+        .unwrap()
+        .insert(connection, password.clone());
+
+    Ok(password)
+}
+
+fn home_file(name: &str) -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(name))
+}
+
+/// `PGPASSFILE`, or `~/.pgpass` as `psql` and `pg_dump`
+fn pgpass_file() -> Option<PathBuf> {
+    std::env::var_os("PGPASSFILE")
+        .map(PathBuf::from)
+        .or_else(|| home_file(".pgpass"))
+        .filter(|path| path.is_file())
+}
+
+fn mycnf_file() -> Option<PathBuf> {
+    home_file(".my.cnf").filter(|path| path.is_file())
+}
+
+/// password of the first `hostname:port:database:username:password` line matching the connection,
+/// `*` matches any value and `localhost` also matches the unix sockets
+fn pgpass_password(
+    path: &Path,
+    host: &str,
+    port: u16,
+    database: &str,
+    username: &str,
+) -> Option<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        // libpq ignores the files readable by the other users as well
+        let mode = fs::metadata(path).ok()?.permissions().mode();
+        if mode & 0o077 != 0 {
+            warn!(
+                "password file '{}' has group or world access; permissions should be u=rw (0600) or less",
+                path.display()
+            );
+            return None;
+        }
+    }
+
+    let content = fs::read_to_string(path).ok()?;
+    let port = port.to_string();
+
+    content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .map(pgpass_fields)
+        .find(|fields| {
+            fields.len() == 5
+                && (fields[0] == "*"
+                    || fields[0] == host
+                    || (fields[0] == "localhost" && host.starts_with('/')))
+                && (fields[1] == "*" || fields[1] == port)
+                && (fields[2] == "*" || fields[2] == database)
+                && (fields[3] == "*" || fields[3] == username)
+        })
+        .map(|fields| fields[4].clone())
+}
+
+/// fields separated by `:`, with `\:` and `\\` for the colons and the backslashes of the values
+fn pgpass_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    fields.last_mut().unwrap().push(c);
+                }
+            }
+            // the password is the rest of the line
+            ':' if fields.len() < 5 => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+
+    fields
+}
+
+/// `password` of the sections of the option file read by the MySQL clients, the last one wins.
+/// The password is ignored if the file sets another `user`.
+fn mycnf_password(path: &Path, username: &str, sections: &[&str]) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+
+    let mut section = String::new();
+    let mut user = None;
+    let mut password = None;
+
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            section = name.trim().to_lowercase();
+            continue;
+        }
+
+        if !sections.contains(&section.as_str()) {
+            continue;
+        }
+
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim().replace('_', "-"), unquote(value.trim())),
+            None => continue,
+        };
+
+        match key.as_str() {
+            "user" => user = Some(value),
+            "password" => password = Some(value),
+            _ => {}
+        }
+    }
+
+    match user {
+        Some(user) if user != username => None,
+        _ => password,
+    }
+}
+
+fn unquote(value: &str) -> String {
+    for quote in ['"', '\''] {
+        if let Some(value) = value
+            .strip_prefix(quote)
+            .and_then(|value| value.strip_suffix(quote))
+        {
+            return value.to_string();
+        }
+    }
+
+    value.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use crate::credentials::{mycnf_password, pgpass_fields, pgpass_password};
+
+    #[test]
+    fn pgpass_passwords() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(".pgpass");
+        fs::write(
+            &path,
+            "# replibyte\n\
+            prod-db:5432:app:replibyte:pr0d\n\
+            *:*:*:postgres:p\\:ss\\\\word\n\
+            localhost:5432:*:root:l0cal\n",
+        )
+        .unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        }
+
+        let password =
+            |host, port, database, username| pgpass_password(&path, host, port, database, username);
+        assert_eq!(
+            password("prod-db", 5432, "app", "replibyte"),
+            Some("pr0d".to_string())
+        );
+        assert_eq!(password("prod-db", 5433, "app", "replibyte"), None);
+        assert_eq!(
+            password("staging-db", 5433, "other", "postgres"),
+            Some("p:ss\\word".to_string())
+        );
+        assert_eq!(
+            password("/var/run/postgresql", 5432, "app", "root"),
+            Some("l0cal".to_string())
+        );
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+            assert_eq!(password("prod-db", 5432, "app", "replibyte"), None);
+        }
+
+        assert_eq!(
+            pgpass_fields("a:b:c:d:e:f"),
+            vec!["a", "b", "c", "d", "e:f"]
+        );
+    }
+
+    #[test]
+    fn mycnf_passwords() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(".my.cnf");
+        fs::write(
+            &path,
+            "[mysqld]\npassword = server\n\n[client]\nuser = root\npassword = \"s3cret\"\n\n[client-mariadb]\npassword=mariadb\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            mycnf_password(&path, "root", &["client"]),
+            Some("s3cret".to_string())
+        );
+        assert_eq!(
+            mycnf_password(&path, "root", &["client", "client-mariadb"]),
+            Some("mariadb".to_string())
+        );
+        assert_eq!(mycnf_password(&path, "replibyte", &["client"]), None);
+    }
+}
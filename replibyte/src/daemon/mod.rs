@@ -0,0 +1,190 @@
+use std::io::{BufRead, BufReader, Error, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use chrono::{DateTime, Utc};
+use log::{error, info, warn};
+use serde::Serialize;
+
+use crate::cli::{DaemonArgs, DumpCommand, SubCommand};
+use crate::config::Config;
+use crate::daemon::schedule::Schedule;
+use crate::errors::ReplibyteError;
+use crate::metrics::{self, Metrics};
+
+pub mod schedule;
+
+/// State of the daemon, served on the `/health` endpoint
+#[derive(Debug, Default, Clone, Serialize)]
+struct Health {
+    /// a dump is being created
+    running: bool,
+    next_run: Option<DateTime<Utc>>,
+    last_success: Option<DateTime<Utc>>,
+    last_failure: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+}
+
+/// create the dumps on the schedule of the `daemon` config, one at a time
+pub fn run(
+    args: &DaemonArgs,
+    config: Config,
+    metrics: &Arc<Metrics>,
+) -> Result<(), ReplibyteError> {
+    let daemon_config = match &config.daemon {
+        Some(daemon_config) => daemon_config.clone(),
+        None => {
+            return Err(ReplibyteError::Config(
+                "missing <daemon> object in the configuration file".to_string(),
+            ));
+        }
+    };
+
+    let schedule_expression = daemon_config.schedule()?;
+    let schedule = Schedule::parse(schedule_expression.as_str())?;
+
+    let health = Arc::new(Mutex::new(Health::default()));
+    if let Some(listen) = daemon_config.listen()? {
+        serve(health.clone(), listen.as_str())?;
+    }
+
+    let sub_commands = SubCommand::Dump(DumpCommand::Create(args.dump_create_args()));
+    let mut after = Utc::now();
+
+    loop {
+        let next_run = match schedule.next_after(after) {
+            Some(next_run) => next_run,
+            None => {
+                return Err(ReplibyteError::Config(format!(
+                    "the schedule '{}' never runs",
+                    schedule_expression
+                )));
+            }
+        };
+
+        health.lock().unwrap().next_run = Some(next_run);
+        info!("next dump at {}", next_run);
+
+        if let Ok(duration) = (next_run - Utc::now()).to_std() {
+            thread::sleep(duration);
+        }
+
+        {
+            let mut health = health.lock().unwrap();
+            health.running = true;
+            health.next_run = None;
+        }
+
+        metrics.restart();
+        let result = crate::run(config.clone(), &sub_commands, true, metrics);
+        metrics.finish(result.is_ok());
+
+        if let Some(metrics_config) = &config.metrics {
+            if let Err(err) = metrics::push(metrics, metrics_config) {
+                error!("{}", err);
+            }
+        }
+
+        let now = Utc::now();
+
+        {
+            let mut health = health.lock().unwrap();
+            health.running = false;
+
+            match &result {
+                Ok(_) => health.last_success = Some(now),
+                Err(err) => {
+                    error!("the dump scheduled at {} failed: {}", next_run, err);
+                    health.last_failure = Some(now);
+                    health.last_error = Some(err.to_string());
+                }
+            }
+        }
+
+        // the runs scheduled while the dump was running are skipped instead of overlapping
+        if let Some(missed_run) = schedule.next_after(next_run) {
+            if missed_run < now {
+                warn!(
+                    "the dump scheduled at {} took longer than the schedule, the runs scheduled until {} are skipped",
+                    next_run, now
+                );
+            }
+        }
+
+        after = now.max(next_run);
+    }
+}
+
+/// serve the state of the daemon on `http://<listen>/health`
+fn serve(health: Arc<Mutex<Health>>, listen: &str) -> Result<(), ReplibyteError> {
+    let listener = TcpListener::bind(listen).map_err(|err| {
+        ReplibyteError::Config(format!(
+            "unable to serve the health endpoint on '{}': {}",
+            listen, err
+        ))
+    })?;
+
+    let _ = thread::spawn(move || {
+        for stream in listener.incoming() {
+            let result = stream.and_then(|stream| respond(stream, &health));
+
+            if let Err(err) = result {
+                error!("health endpoint error: {}", err);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn respond(mut stream: TcpStream, health: &Mutex<Health>) -> Result<(), Error> {
+    let mut request_line = String::new();
+    let _ = BufReader::new(&stream).read_line(&mut request_line)?;
+
+    let response = match request_line.split_whitespace().nth(1) {
+        Some("/health") => {
+            let body = serde_json::to_string(&*health.lock().unwrap())?;
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        }
+        _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+    };
+
+    stream.write_all(response.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::sync::{Arc, Mutex};
+
+    use crate::daemon::{serve, Health};
+
+    #[test]
+    fn serve_health() {
+        let health = Health {
+            running: true,
+            last_error: Some("Can't connect to the source".to_string()),
+            ..Default::default()
+        };
+
+        serve(Arc::new(Mutex::new(health)), "127.0.0.1:19899").unwrap();
+
+        let mut stream = TcpStream::connect("127.0.0.1:19899").unwrap();
+        stream
+            .write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+
+        let mut response = String::new();
+        let _ = stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with(
+            "{\"running\":true,\"next_run\":null,\"last_success\":null,\"last_failure\":null,\"last_error\":\"Can't connect to the source\"}"
+        ));
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Timelike, Utc};
+
+use crate::errors::ReplibyteError;
+
+/// the next run is looked for up to this number of years, e.g. `0 0 30 2 *` never runs
+const MAX_YEARS: i32 = 5;
+
+/// Cron schedule: `minute hour day-of-month month day-of-week`, e.g. `0 3 * * *` runs every day at 03:00 UTC
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    minutes: BTreeSet<u32>,
+    hours: BTreeSet<u32>,
+    days_of_month: BTreeSet<u32>,
+    months: BTreeSet<u32>,
+    /// 0 is Sunday
+    days_of_week: BTreeSet<u32>,
+    /// a day matches the day of month or the day of week if both are restricted, like cron does
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+impl Schedule {
+    /// parse a cron expression, or one of `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly`
+    pub fn parse(expression: &str) -> Result<Self, ReplibyteError> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            expression => expression,
+        };
+
+        let fields = expression.split_whitespace().collect::<Vec<_>>();
+        if fields.len() != 5 {
+            return Err(invalid_schedule(
+                expression,
+                "expected 5 fields: minute hour day-of-month month day-of-week",
+            ));
+        }
+
+        let days_of_week = parse_field(expression, fields[4], 0, 7)?
+            .into_iter()
+            // 7 is Sunday too
+            .map(|day| day % 7)
+            .collect();
+
+        Ok(Schedule {
+            minutes: parse_field(expression, fields[0], 0, 59)?,
+            hours: parse_field(expression, fields[1], 0, 23)?,
+            days_of_month: parse_field(expression, fields[2], 1, 31)?,
+            months: parse_field(expression, fields[3], 1, 12)?,
+            days_of_week,
+            any_day_of_month: fields[2] == "*",
+            any_day_of_week: fields[4] == "*",
+        })
+    }
+
+    /// the first time of the schedule strictly after `time`, `None` if there is none
+    pub fn next_after(&self, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let time = time.naive_utc();
+        let mut next =
+            time.date().and_hms_opt(time.hour(), time.minute(), 0)? + Duration::minutes(1);
+        let max_year = next.year() + MAX_YEARS;
+
+        while next.year() <= max_year {
+            if !self.months.contains(&next.month()) {
+                next = first_day_of_next_month(next.date())?.and_hms_opt(0, 0, 0)?;
+            } else if !self.matches_day(next.date()) {
+                next = next.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if !self.hours.contains(&next.hour()) {
+                next = next.date().and_hms_opt(next.hour(), 0, 0)? + Duration::hours(1);
+            } else if !self.minutes.contains(&next.minute()) {
+                next += Duration::minutes(1);
+            } else {
+                return Some(DateTime::<Utc>::from_naive_utc_and_offset(next, Utc));
+            }
+        }
+
+        None
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        let day_of_month = self.days_of_month.contains(&date.day());
+        let day_of_week = self
+            .days_of_week
+            .contains(&date.weekday().num_days_from_sunday());
+
+        match (self.any_day_of_month, self.any_day_of_week) {
+            (false, false) => day_of_month || day_of_week,
+            _ => day_of_month && day_of_week,
+        }
+    }
+}
+
+fn first_day_of_next_month(date: NaiveDate) -> Option<NaiveDate> {
+    match date.month() {
+        12 => NaiveDate::from_ymd_opt(date.year() + 1, 1, 1),
+        month => NaiveDate::from_ymd_opt(date.year(), month + 1, 1),
+    }
+}
+
+/// parse a field made of `*`, values, ranges (`1-5`) and steps (`*/15`, `0-30/10`) separated by commas
+fn parse_field(
+    expression: &str,
+    field: &str,
+    min: u32,
+    max: u32,
+) -> Result<BTreeSet<u32>, ReplibyteError> {
+    let mut values = BTreeSet::new();
+
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, parse_value(expression, step)?),
+            None => (item, 1),
+        };
+
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (
+                parse_value(expression, start)?,
+                parse_value(expression, end)?,
+            ),
+            // `5/10` is from 5 to the max, every 10
+            None if item.contains('/') => (parse_value(expression, range)?, max),
+            None => {
+                let value = parse_value(expression, range)?;
+                (value, value)
+            }
+        };
+
+        if step == 0 || start < min || end > max || start > end {
+            return Err(invalid_schedule(
+                expression,
+                format!("'{}' is not between {} and {}", item, min, max).as_str(),
+            ));
+        }
+
+        values.extend((start..=end).step_by(step as usize));
+    }
+
+    Ok(values)
+}
+
+fn parse_value(expression: &str, value: &str) -> Result<u32, ReplibyteError> {
+    value
+        .parse::<u32>()
+        .map_err(|_| invalid_schedule(expression, format!("'{}' is not a number", value).as_str()))
+}
+
+fn invalid_schedule(expression: &str, reason: &str) -> ReplibyteError {
+    ReplibyteError::Config(format!("invalid schedule '{}': {}", expression, reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, NaiveDateTime, Utc};
+
+    use crate::daemon::schedule::Schedule;
+
+    /// `2022-05-11T03:00:00Z` to a time in UTC
+    fn utc(time: &str) -> DateTime<Utc> {
+        DateTime::<Utc>::from_naive_utc_and_offset(
+            NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M:%SZ").unwrap(),
+            Utc,
+        )
+    }
+
+    #[test]
+    fn parse_schedule() {
+        assert!(Schedule::parse("0 3 * * *").is_ok());
+        assert!(Schedule::parse("*/15 0-6,22,23 1 1-12/2 1-5").is_ok());
+        assert_eq!(
+            Schedule::parse("@daily").unwrap(),
+            Schedule::parse("0 0 * * *").unwrap()
+        );
+        assert_eq!(
+            Schedule::parse("0 0 * * 7").unwrap(),
+            Schedule::parse("0 0 * * 0").unwrap()
+        );
+
+        assert!(Schedule::parse("0 3 * *").is_err());
+        assert!(Schedule::parse("60 3 * * *").is_err());
+        assert!(Schedule::parse("0 3 0 * *").is_err());
+        assert!(Schedule::parse("*/0 3 * * *").is_err());
+        assert!(Schedule::parse("0 three * * *").is_err());
+    }
+
+    #[test]
+    fn next_run() {
+        let schedule = Schedule::parse("0 3 * * *").unwrap();
+        assert_eq!(
+            schedule.next_after(utc("2022-05-11T02:59:30Z")),
+            Some(utc("2022-05-11T03:00:00Z"))
+        );
+        // strictly after
+        assert_eq!(
+            schedule.next_after(utc("2022-05-11T03:00:00Z")),
+            Some(utc("2022-05-12T03:00:00Z"))
+        );
+        assert_eq!(
+            schedule.next_after(utc("2022-12-31T12:00:00Z")),
+            Some(utc("2023-01-01T03:00:00Z"))
+        );
+
+        let schedule = Schedule::parse("*/20 9-17 * * 1-5").unwrap();
+        // 2022-05-13 is a Friday
+        assert_eq!(
+            schedule.next_after(utc("2022-05-13T17:45:00Z")),
+            Some(utc("2022-05-16T09:00:00Z"))
+        );
+        assert_eq!(
+            schedule.next_after(utc("2022-05-16T09:05:00Z")),
+            Some(utc("2022-05-16T09:20:00Z"))
+        );
+
+        // the 1st of the month or a Sunday
+        let schedule = Schedule::parse("0 0 1 * 0").unwrap();
+        assert_eq!(
+            schedule.next_after(utc("2022-05-11T00:00:00Z")),
+            Some(utc("2022-05-15T00:00:00Z"))
+        );
+
+        assert_eq!(
+            Schedule::parse("0 0 30 2 *")
+                .unwrap()
+                .next_after(utc("2022-05-11T00:00:00Z")),
+            None
+        );
+    }
+}
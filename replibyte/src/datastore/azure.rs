@@ -0,0 +1,802 @@
+use std::collections::BTreeMap;
+use std::io::{Error, ErrorKind};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use log::{error, info};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::blocking::{Client, Response};
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{Method, StatusCode};
+use ring::hmac;
+use serde_json::Value;
+use url::Url;
+
+use crate::connector::Connector;
+use crate::datastore::{
+    chunk_key, dump_part_number, Compression, Datastore, Dump, EncodedPart, EncryptionKeys,
+    IndexFile,
+};
+use crate::errors::ReplibyteError;
+use crate::types::Bytes;
+use crate::utils::epoch_millis;
+
+use super::INDEX_FILE_NAME;
+
+const API_VERSION: &str = "2021-08-06";
+const ENDPOINT_SUFFIX: &str = "core.windows.net";
+const STORAGE_RESOURCE: &str = "https://storage.azure.com/";
+/// Instance Metadata Service giving the access tokens of the managed identity
+const IMDS_TOKEN_URL: &str = "http://169.254.169.254/metadata/identity/oauth2/token";
+/// account and key of the storage emulator (Azurite), they are the same for everyone
+const DEVELOPMENT_STORAGE_ACCOUNT: &str = "devstoreaccount1";
+const DEVELOPMENT_STORAGE_KEY: &str =
+    "Eby8vdM02xNOcqFlqUwJPLlmEtlCDXJ1OUzFT50uSRZ6IFsuFq2UVErCz4I6tq/K1SZFPTOtr/KBHBeksoGMGw==";
+const DEVELOPMENT_STORAGE_ENDPOINT: &str = "http://127.0.0.1:10000/devstoreaccount1";
+/// access tokens are renewed a bit before they expire
+const ACCESS_TOKEN_EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+const TIMEOUT: Duration = Duration::from_secs(300);
+
+/// characters encoded in the blob paths, `/` is kept to not escape the virtual directories
+const BLOB_PATH_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'/')
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+#[derive(Debug, PartialEq)]
+enum Credentials {
+    SharedKey { account: String, key: Vec<u8> },
+    SharedAccessSignature(String),
+    ManagedIdentity { client_id: Option<String> },
+}
+
+struct AccessToken {
+    token: String,
+    expires_at: Instant,
+}
+
+pub struct AzureBlobStorage {
+    container: String,
+    root_key: String,
+    endpoint: String,
+    client: Client,
+    credentials: Credentials,
+    access_token: Mutex<Option<AccessToken>>,
+    compression: Option<Compression>,
+    encryption_keys: EncryptionKeys,
+}
+
+impl AzureBlobStorage {
+    /// the connection string is `AZURE_STORAGE_CONNECTION_STRING` by default,
+    /// the managed identity of the storage account is used if none is set
+    pub fn new<S: Into<String>>(
+        container: S,
+        connection_string: Option<String>,
+        account: Option<String>,
+        managed_identity_client_id: Option<String>,
+        proxy: Option<String>,
+    ) -> Result<Self, ReplibyteError> {
+        let connection_string = connection_string
+            .or_else(|| std::env::var("AZURE_STORAGE_CONNECTION_STRING").ok())
+            .filter(|connection_string| !connection_string.is_empty());
+
+        let (endpoint, credentials) = match (connection_string, account) {
+            (Some(connection_string), _) => parse_connection_string(connection_string.as_str())?,
+            (None, Some(account)) => (
+                format!("https://{}.blob.{}", account, ENDPOINT_SUFFIX),
+                Credentials::ManagedIdentity {
+                    client_id: managed_identity_client_id,
+                },
+            ),
+            (None, None) => {
+                return Err(ReplibyteError::Datastore(
+                    "a <connection_string> or an <account> is mandatory for the Azure datastore"
+                        .to_string(),
+                ));
+            }
+        };
+
+        let mut client_builder = Client::builder().timeout(TIMEOUT);
+        if let Some(proxy) = proxy {
+            let proxy = reqwest::Proxy::all(proxy.as_str())
+                .map_err(|err| ReplibyteError::Datastore(format!("invalid proxy: {}", err)))?;
+
+            client_builder = client_builder.proxy(proxy);
+        }
+
+        let client = client_builder
+            .build()
+            .map_err(|err| ReplibyteError::Datastore(err.to_string()))?;
+
+        Ok(AzureBlobStorage {
+            container: container.into(),
+            root_key: format!("dump-{}", epoch_millis()),
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            client,
+            credentials,
+            access_token: Mutex::new(None),
+            compression: Some(Compression::default()),
+            encryption_keys: EncryptionKeys::default(),
+        })
+    }
+
+    fn create_index_file(&self) -> Result<IndexFile, ReplibyteError> {
+        match self.index_file() {
+            Ok(index_file) => Ok(index_file),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                let index_file = IndexFile::new();
+                let _ = self.write_index_file(&index_file)?;
+                Ok(index_file)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// return a valid access token of the managed identity, a new one is requested when the current one is about to expire
+    fn access_token(&self, client_id: Option<&String>) -> Result<String, ReplibyteError> {
+        let mut access_token = self.access_token.lock().unwrap();
+
+        if let Some(access_token) = access_token.as_ref() {
+            if access_token.expires_at > Instant::now() + ACCESS_TOKEN_EXPIRY_MARGIN {
+                return Ok(access_token.token.clone());
+            }
+        }
+
+        let mut query = vec![
+            ("api-version", "2018-02-01"),
+            ("resource", STORAGE_RESOURCE),
+        ];
+
+        if let Some(client_id) = client_id {
+            query.push(("client_id", client_id.as_str()));
+        }
+
+        // the metadata service is never reached through a proxy
+        let client = Client::builder()
+            .no_proxy()
+            .timeout(TIMEOUT)
+            .build()
+            .map_err(|err| ReplibyteError::Datastore(err.to_string()))?;
+
+        let response = check_response(
+            client
+                .get(IMDS_TOKEN_URL)
+                .query(&query)
+                .header("Metadata", "true")
+                .send(),
+        )
+        .map_err(|err| {
+            ReplibyteError::Datastore(format!(
+                "failed to get an access token of the managed identity: {}",
+                err
+            ))
+        })?;
+
+        let response: Value = serde_json::from_slice(
+            response
+                .bytes()
+                .map_err(|err| ReplibyteError::Datastore(err.to_string()))?
+                .as_ref(),
+        )?;
+
+        let token = response["access_token"]
+            .as_str()
+            .ok_or_else(|| {
+                ReplibyteError::Datastore("missing access token in the response".to_string())
+            })?
+            .to_string();
+
+        // the lifetime of the token is given as a string
+        let expires_in = match &response["expires_in"] {
+            Value::String(expires_in) => expires_in.parse::<u64>().unwrap_or_default(),
+            expires_in => expires_in.as_u64().unwrap_or_default(),
+        };
+
+        *access_token = Some(AccessToken {
+            token: token.clone(),
+            expires_at: Instant::now() + Duration::from_secs(expires_in),
+        });
+
+        Ok(token)
+    }
+
+    /// send an authenticated request to the container (`blob` is None) or to one of its blobs
+    fn send(
+        &self,
+        method: Method,
+        blob: Option<&str>,
+        query: &[(&str, &str)],
+        body: Option<Bytes>,
+    ) -> Result<Response, ReplibyteError> {
+        let path = match blob {
+            Some(blob) => format!(
+                "{}/{}",
+                self.container,
+                utf8_percent_encode(blob, BLOB_PATH_ENCODE_SET)
+            ),
+            None => self.container.clone(),
+        };
+
+        let mut url = Url::parse(format!("{}/{}", self.endpoint, path).as_str())
+            .map_err(|err| ReplibyteError::Datastore(format!("invalid url: {}", err)))?;
+
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+
+        let mut ms_headers = vec![
+            (
+                "x-ms-date",
+                Utc::now().format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+            ),
+            ("x-ms-version", API_VERSION.to_string()),
+        ];
+
+        let content_type = match body {
+            Some(_) if blob.is_some() => {
+                ms_headers.push(("x-ms-blob-type", "BlockBlob".to_string()));
+                "application/octet-stream"
+            }
+            _ => "",
+        };
+
+        let content_length = body.as_ref().map(|body| body.len()).unwrap_or(0);
+
+        let authorization = match &self.credentials {
+            Credentials::SharedKey { account, key } => {
+                let string_to_sign = string_to_sign(
+                    &method,
+                    content_length,
+                    content_type,
+                    &ms_headers,
+                    account.as_str(),
+                    &url,
+                );
+
+                Some(format!(
+                    "SharedKey {}:{}",
+                    account,
+                    sign(key.as_slice(), string_to_sign.as_str())
+                ))
+            }
+            Credentials::SharedAccessSignature(sas) => {
+                // the signature is already url encoded
+                let query = match url.query() {
+                    Some(query) => format!("{}&{}", query, sas),
+                    None => sas.clone(),
+                };
+
+                url.set_query(Some(query.as_str()));
+                None
+            }
+            Credentials::ManagedIdentity { client_id } => {
+                Some(format!("Bearer {}", self.access_token(client_id.as_ref())?))
+            }
+        };
+
+        let mut request = self.client.request(method, url);
+
+        for (name, value) in ms_headers {
+            request = request.header(name, value);
+        }
+
+        if let Some(authorization) = authorization {
+            request = request.header(AUTHORIZATION, authorization);
+        }
+
+        if !content_type.is_empty() {
+            request = request.header(CONTENT_TYPE, content_type);
+        }
+
+        if let Some(body) = body {
+            request = request.body(body);
+        }
+
+        check_response(request.send())
+    }
+
+    fn get_blob(&self, key: &str) -> Result<Bytes, ReplibyteError> {
+        let response = self
+            .send(Method::GET, Some(key), &[], None)
+            .map_err(|err| {
+                Error::new(
+                    err.kind(),
+                    format!(
+                        "failed to download blob '{}/{}': {}",
+                        self.container, key, err
+                    ),
+                )
+            })?;
+
+        response
+            .bytes()
+            .map(|data| data.to_vec())
+            .map_err(|err| ReplibyteError::Datastore(err.to_string()))
+    }
+
+    fn create_blob(&self, key: &str, data: Bytes) -> Result<(), ReplibyteError> {
+        let _ = self
+            .send(Method::PUT, Some(key), &[], Some(data))
+            .map_err(|err| {
+                error!("{}", err);
+                Error::new(
+                    err.kind(),
+                    format!(
+                        "failed to upload blob '{}/{}': {}",
+                        self.container, key, err
+                    ),
+                )
+            })?;
+
+        Ok(())
+    }
+
+    fn delete_blob(&self, key: &str) -> Result<(), ReplibyteError> {
+        let _ = self
+            .send(Method::DELETE, Some(key), &[], None)
+            .map_err(|err| {
+                Error::new(
+                    err.kind(),
+                    format!(
+                        "failed to delete blob '{}/{}': {}",
+                        self.container, key, err
+                    ),
+                )
+            })?;
+
+        Ok(())
+    }
+
+    /// list the names of the blobs starting with the prefix
+    fn list_blobs(&self, prefix: &str) -> Result<Vec<String>, ReplibyteError> {
+        let mut names = vec![];
+        let mut marker: Option<String> = None;
+
+        loop {
+            let mut query = vec![
+                ("restype", "container"),
+                ("comp", "list"),
+                ("prefix", prefix),
+            ];
+            if let Some(marker) = &marker {
+                query.push(("marker", marker.as_str()));
+            }
+
+            let response = self
+                .send(Method::GET, None, query.as_slice(), None)
+                .map_err(|err| {
+                    Error::new(
+                        err.kind(),
+                        format!(
+                            "failed to list blobs from container '{}': {}",
+                            self.container, err
+                        ),
+                    )
+                })?;
+
+            let xml = response
+                .text()
+                .map_err(|err| ReplibyteError::Datastore(err.to_string()))?;
+
+            names.extend(xml_values(xml.as_str(), "Name"));
+
+            match xml_values(xml.as_str(), "NextMarker").pop() {
+                Some(next_marker) if !next_marker.is_empty() => marker = Some(next_marker),
+                _ => break,
+            }
+        }
+
+        Ok(names)
+    }
+}
+
+impl Connector for AzureBlobStorage {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        match self.send(Method::GET, None, &[("restype", "container")], None) {
+            Ok(_) => info!("container {} exists", self.container),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                let _ = self
+                    .send(Method::PUT, None, &[("restype", "container")], None)
+                    .map_err(|err| {
+                        Error::new(
+                            err.kind(),
+                            format!("failed to create container '{}': {}", self.container, err),
+                        )
+                    })?;
+
+                info!("container {} created", self.container);
+            }
+            Err(err) => return Err(err),
+        }
+
+        self.create_index_file().map(|_| ())
+    }
+}
+
+impl Datastore for AzureBlobStorage {
+    fn index_file(&self) -> Result<IndexFile, ReplibyteError> {
+        let blob = self.get_blob(INDEX_FILE_NAME)?;
+        let index_file: IndexFile = serde_json::from_slice(blob.as_slice())?;
+        Ok(index_file)
+    }
+
+    fn raw_index_file(&self) -> Result<Value, ReplibyteError> {
+        let blob = self.get_blob(INDEX_FILE_NAME)?;
+        let index_file = serde_json::from_slice(blob.as_slice())?;
+        Ok(index_file)
+    }
+
+    fn write_index_file(&self, index_file: &IndexFile) -> Result<(), ReplibyteError> {
+        let index_file_json = serde_json::to_vec(index_file)?;
+        self.create_blob(INDEX_FILE_NAME, index_file_json)
+    }
+
+    fn write_raw_index_file(&self, raw_index_file: &Value) -> Result<(), ReplibyteError> {
+        let index_file_json = serde_json::to_vec(raw_index_file)?;
+        self.create_blob(INDEX_FILE_NAME, index_file_json)
+    }
+
+    fn write_encoded(&self, file_part: u16, part: EncodedPart) -> Result<(), ReplibyteError> {
+        let EncodedPart { data, checksum } = part;
+
+        let data_size = data.len();
+        let key = format!("{}/{}.dump", self.root_key, file_part);
+
+        info!("upload blob '{}' part {} on", key.as_str(), file_part);
+
+        let _ = self.create_blob(key.as_str(), data)?;
+
+        // update index file
+        let mut index_file = self.index_file()?;
+
+        let mut new_dump = Dump {
+            directory_name: self.root_key.to_string(),
+            size: 0,
+            created_at: epoch_millis(),
+            compressed: self.compression().is_some(),
+            compression: self.compression().map(|compression| compression.algorithm),
+            encrypted: self.encryption_keys().current().is_some(),
+            encryption_key_id: self
+                .encryption_keys()
+                .current()
+                .and_then(|key| key.id.clone()),
+            encrypted_data_key: self
+                .encryption_keys()
+                .current()
+                .and_then(|key| key.encrypted_data_key.clone()),
+            chunks: None,
+            parts: None,
+            started_at: None,
+            base: None,
+            uncompressed_size: None,
+            source_type: None,
+            metadata: None,
+            checksums: None,
+        };
+
+        // find or create dump
+        let dump = index_file
+            .dumps
+            .iter_mut()
+            .find(|b| b.directory_name.as_str() == self.root_key)
+            .unwrap_or(&mut new_dump);
+
+        dump.checksums.get_or_insert_with(Vec::new).push(checksum);
+
+        if dump.size == 0 {
+            // it means it's a new dump.
+            // We need to add it into the index_file.dumps
+            new_dump.size = data_size;
+            index_file.dumps.push(new_dump);
+        } else {
+            // update total dump size
+            dump.size += data_size;
+        }
+
+        // save index file
+        self.write_index_file(&index_file)
+    }
+
+    fn read_encoded(
+        &self,
+        dump: &Dump,
+        data_callback: &mut dyn FnMut(Bytes) -> Result<(), ReplibyteError>,
+    ) -> Result<(), ReplibyteError> {
+        let mut keys = self.list_blobs(format!("{}/", dump.directory_name).as_str())?;
+        // the parts are read in order, `10.dump` comes after `9.dump`
+        keys.sort_by_key(|key| dump_part_number(key.as_str()));
+
+        for key in keys {
+            let _ = data_callback(self.get_blob(key.as_str())?)?;
+        }
+
+        Ok(())
+    }
+
+    fn compression(&self) -> Option<Compression> {
+        self.compression
+    }
+
+    fn set_compression(&mut self, compression: Option<Compression>) {
+        self.compression = compression;
+    }
+
+    fn encryption_keys(&self) -> &EncryptionKeys {
+        &self.encryption_keys
+    }
+
+    fn set_encryption_keys(&mut self, keys: EncryptionKeys) {
+        self.encryption_keys = keys;
+    }
+
+    fn set_dump_name(&mut self, name: String) {
+        self.root_key = name;
+    }
+
+    fn delete_dump_files(&self, name: &str) -> Result<(), ReplibyteError> {
+        for key in self.list_blobs(format!("{}/", name).as_str())? {
+            self.delete_blob(key.as_str())?;
+        }
+
+        Ok(())
+    }
+
+    fn write_chunk(&self, hash: &str, data: Bytes) -> Result<(), ReplibyteError> {
+        self.create_blob(chunk_key(hash).as_str(), data)
+    }
+
+    fn read_chunk(&self, hash: &str) -> Result<Bytes, ReplibyteError> {
+        self.get_blob(chunk_key(hash).as_str())
+    }
+
+    fn delete_chunk(&self, hash: &str) -> Result<(), ReplibyteError> {
+        self.delete_blob(chunk_key(hash).as_str())
+    }
+}
+
+fn check_response(response: reqwest::Result<Response>) -> Result<Response, ReplibyteError> {
+    let response = response.map_err(|err| ReplibyteError::Datastore(err.to_string()))?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let body = response.text().unwrap_or_default();
+    let kind = match status {
+        StatusCode::NOT_FOUND => ErrorKind::NotFound,
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ErrorKind::PermissionDenied,
+        StatusCode::CONFLICT => ErrorKind::AlreadyExists,
+        _ => ErrorKind::Other,
+    };
+
+    let message = xml_values(body.as_str(), "Message")
+        .pop()
+        .unwrap_or_else(|| body.trim().to_string());
+
+    Err(ReplibyteError::from(Error::new(
+        kind,
+        format!("{} {}", status, message),
+    )))
+}
+
+/// parse a `Key=Value;...` connection string into the blob endpoint and the credentials
+fn parse_connection_string(
+    connection_string: &str,
+) -> Result<(String, Credentials), ReplibyteError> {
+    let values = connection_string
+        .split(';')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .collect::<BTreeMap<_, _>>();
+
+    if values.get("UseDevelopmentStorage") == Some(&"true") {
+        return Ok((
+            DEVELOPMENT_STORAGE_ENDPOINT.to_string(),
+            Credentials::SharedKey {
+                account: DEVELOPMENT_STORAGE_ACCOUNT.to_string(),
+                key: base64::decode(DEVELOPMENT_STORAGE_KEY).unwrap(),
+            },
+        ));
+    }
+
+    let account = values.get("AccountName");
+
+    let endpoint = match (values.get("BlobEndpoint"), account) {
+        (Some(blob_endpoint), _) => blob_endpoint.to_string(),
+        (None, Some(account)) => format!(
+            "{}://{}.blob.{}",
+            values.get("DefaultEndpointsProtocol").unwrap_or(&"https"),
+            account,
+            values.get("EndpointSuffix").unwrap_or(&ENDPOINT_SUFFIX)
+        ),
+        (None, None) => {
+            return Err(ReplibyteError::Datastore(
+                "the connection string needs an AccountName or a BlobEndpoint".to_string(),
+            ));
+        }
+    };
+
+    let credentials = match (
+        account,
+        values.get("AccountKey"),
+        values.get("SharedAccessSignature"),
+    ) {
+        (Some(account), Some(key), _) => Credentials::SharedKey {
+            account: account.to_string(),
+            key: base64::decode(key).map_err(|_| {
+                ReplibyteError::Datastore("invalid AccountKey in the connection string".to_string())
+            })?,
+        },
+        (_, _, Some(sas)) => {
+            Credentials::SharedAccessSignature(sas.trim_start_matches('?').to_string())
+        }
+        _ => {
+            return Err(ReplibyteError::Datastore("the connection string needs an AccountName and an AccountKey, or a SharedAccessSignature".to_string()));
+        }
+    };
+
+    Ok((endpoint, credentials))
+}
+
+/// build the string signed with the account key (https://docs.microsoft.com/en-us/rest/api/storageservices/authorize-with-shared-key)
+fn string_to_sign(
+    method: &Method,
+    content_length: usize,
+    content_type: &str,
+    ms_headers: &[(&str, String)],
+    account: &str,
+    url: &Url,
+) -> String {
+    // an empty body has no length since the version 2015-02-21
+    let content_length = match content_length {
+        0 => String::new(),
+        content_length => content_length.to_string(),
+    };
+
+    let mut ms_headers = ms_headers.to_vec();
+    ms_headers.sort();
+
+    let canonicalized_headers = ms_headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value))
+        .collect::<String>();
+
+    let mut query: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (name, value) in url.query_pairs() {
+        query
+            .entry(name.to_lowercase())
+            .or_default()
+            .push(value.to_string());
+    }
+
+    let mut canonicalized_resource = format!("/{}{}", account, url.path());
+    for (name, mut values) in query {
+        values.sort();
+        canonicalized_resource.push_str(format!("\n{}:{}", name, values.join(",")).as_str());
+    }
+
+    format!(
+        "{}\n\n\n{}\n\n{}\n\n\n\n\n\n\n{}{}",
+        method, content_length, content_type, canonicalized_headers, canonicalized_resource
+    )
+}
+
+fn sign(key: &[u8], string_to_sign: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, key);
+    base64::encode(hmac::sign(&key, string_to_sign.as_bytes()).as_ref())
+}
+
+/// return the values of the `<tag>value</tag>` elements of a XML document
+fn xml_values(xml: &str, tag: &str) -> Vec<String> {
+    let start_tag = format!("<{}>", tag);
+    let end_tag = format!("</{}>", tag);
+
+    xml.split(start_tag.as_str())
+        .skip(1)
+        .filter_map(|element| element.split_once(end_tag.as_str()))
+        .map(|(value, _)| {
+            value
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&quot;", "\"")
+                .replace("&apos;", "'")
+                .replace("&amp;", "&")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::Method;
+    use url::Url;
+
+    use crate::datastore::azure::{
+        parse_connection_string, sign, string_to_sign, xml_values, Credentials,
+        DEVELOPMENT_STORAGE_KEY,
+    };
+
+    #[test]
+    fn connection_strings() {
+        let (endpoint, credentials) = parse_connection_string(
+            "DefaultEndpointsProtocol=https;AccountName=replibyte;AccountKey=c2VjcmV0;EndpointSuffix=core.windows.net",
+        )
+        .unwrap();
+        assert_eq!(endpoint, "https://replibyte.blob.core.windows.net");
+        assert_eq!(
+            credentials,
+            Credentials::SharedKey {
+                account: "replibyte".to_string(),
+                key: b"secret".to_vec(),
+            }
+        );
+
+        let (endpoint, credentials) = parse_connection_string(
+            "BlobEndpoint=https://replibyte.blob.core.windows.net/;SharedAccessSignature=?sv=2021-08-06&sig=abc%3D",
+        )
+        .unwrap();
+        assert_eq!(endpoint, "https://replibyte.blob.core.windows.net/");
+        assert_eq!(
+            credentials,
+            Credentials::SharedAccessSignature("sv=2021-08-06&sig=abc%3D".to_string())
+        );
+
+        let (endpoint, _) = parse_connection_string("UseDevelopmentStorage=true").unwrap();
+        assert_eq!(endpoint, "http://127.0.0.1:10000/devstoreaccount1");
+
+        assert!(parse_connection_string("AccountName=replibyte").is_err());
+        assert!(parse_connection_string("AccountKey=c2VjcmV0").is_err());
+    }
+
+    #[test]
+    fn shared_key_signature() {
+        let url = Url::parse(
+            "http://127.0.0.1:10000/devstoreaccount1/replibyte?restype=container&comp=list&prefix=dump-1%2F",
+        )
+        .unwrap();
+
+        let ms_headers = vec![
+            ("x-ms-version", "2021-08-06".to_string()),
+            ("x-ms-date", "Thu, 14 Apr 2022 00:00:00 GMT".to_string()),
+        ];
+
+        let string_to_sign =
+            string_to_sign(&Method::GET, 0, "", &ms_headers, "devstoreaccount1", &url);
+
+        assert_eq!(
+            string_to_sign,
+            "GET\n\n\n\n\n\n\n\n\n\n\n\nx-ms-date:Thu, 14 Apr 2022 00:00:00 GMT\nx-ms-version:2021-08-06\n/devstoreaccount1/devstoreaccount1/replibyte\ncomp:list\nprefix:dump-1/\nrestype:container"
+        );
+
+        assert_eq!(
+            sign(
+                base64::decode(DEVELOPMENT_STORAGE_KEY).unwrap().as_slice(),
+                string_to_sign.as_str()
+            ),
+            "wXU3HN/k2ed/rlFuBFGShRHF1HcZjtBus8KxAqqNQhM="
+        );
+    }
+
+    #[test]
+    fn list_blobs_response() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<EnumerationResults ServiceEndpoint="http://127.0.0.1:10000/devstoreaccount1" ContainerName="replibyte">
+  <Prefix>dump-1/</Prefix>
+  <Blobs>
+    <Blob><Name>dump-1/1.dump</Name><Properties /></Blob>
+    <Blob><Name>dump-1/a&amp;b.dump</Name><Properties /></Blob>
+  </Blobs>
+  <NextMarker>2!72!MDAwMDE</NextMarker>
+</EnumerationResults>"#;
+
+        assert_eq!(
+            xml_values(xml, "Name"),
+            vec!["dump-1/1.dump", "dump-1/a&b.dump"]
+        );
+        assert_eq!(xml_values(xml, "NextMarker"), vec!["2!72!MDAwMDE"]);
+        assert!(xml_values("<NextMarker />", "NextMarker").is_empty());
+    }
+}
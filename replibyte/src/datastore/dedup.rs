@@ -0,0 +1,757 @@
+use std::collections::HashSet;
+
+use fastcdc::v2020::FastCDC;
+use log::{debug, info};
+use serde_json::Value;
+
+use crate::connector::Connector;
+use crate::datastore::{
+    checksum, compress, decode, encrypt, read_concurrently, Compression, Datastore, Dump,
+    EncodedPart, Encoder, EncryptionKey, EncryptionKeys, IndexFile, ReadOptions,
+};
+use crate::errors::ReplibyteError;
+use crate::types::Bytes;
+use crate::utils::epoch_millis;
+
+/// the chunks are cut depending on their content, the data added or removed between two dumps
+/// only changes the chunks around it
+const MIN_CHUNK_SIZE: u32 = 256 * 1024;
+const AVG_CHUNK_SIZE: u32 = 1024 * 1024;
+const MAX_CHUNK_SIZE: u32 = 4 * 1024 * 1024;
+
+/// context of the key the chunk hashes are keyed with, derived from the encryption key
+const CHUNK_HASH_CONTEXT: &str = "replibyte 2022-07 dedup chunk hash";
+
+/// Dedup splits the dumps in chunks addressed by their BLAKE3 hash, and only writes the chunks
+/// which are not already referenced by a dump of the index file.
+/// The dumps written without dedup are read, deleted and rewrapped by the wrapped datastore.
+pub struct Dedup {
+    datastore: Box<dyn Datastore>,
+    enabled: bool,
+    dump_name: String,
+    compression: Option<Compression>,
+    encryption_keys: EncryptionKeys,
+}
+
+impl Dedup {
+    pub fn new(datastore: Box<dyn Datastore>, enabled: bool) -> Self {
+        let mut dedup = Dedup {
+            datastore,
+            enabled,
+            dump_name: String::new(),
+            compression: Some(Compression::default()),
+            encryption_keys: EncryptionKeys::default(),
+        };
+
+        // the dump has the same name in the wrapped datastore
+        dedup.set_dump_name(format!("dump-{}", epoch_millis()));
+        dedup
+    }
+
+    /// return the hash of a chunk, keyed with the encryption key so that the hashes do not reveal the content
+    /// of the encrypted chunks. The chunks compressed or encrypted differently have different hashes.
+    fn chunk_hash(&self, data: &[u8]) -> String {
+        let mut hasher = match self.encryption_keys.current() {
+            Some(key) => blake3::Hasher::new_keyed(&blake3::derive_key(
+                CHUNK_HASH_CONTEXT,
+                key.key.as_slice(),
+            )),
+            None => blake3::Hasher::new(),
+        };
+
+        let algorithm = match self.compression {
+            Some(compression) => compression.algorithm as u8 + 1,
+            None => 0,
+        };
+
+        let _ = hasher.update(&[algorithm]);
+        let _ = hasher.update(data);
+        hasher.finalize().to_hex().to_string()
+    }
+
+    /// write the chunks of the data which are not stored yet, return the hashes and the checksums
+    /// of all the chunks and the size of the chunks written
+    fn write_chunks(
+        &self,
+        data: &[u8],
+        stored_chunks: &mut HashSet<String>,
+    ) -> Result<(Vec<String>, Vec<String>, usize), ReplibyteError> {
+        let mut hashes = vec![];
+        let mut checksums = vec![];
+        let mut size = 0;
+
+        for chunk in FastCDC::new(data, MIN_CHUNK_SIZE, AVG_CHUNK_SIZE, MAX_CHUNK_SIZE) {
+            let chunk = &data[chunk.offset..chunk.offset + chunk.length];
+            let hash = self.chunk_hash(chunk);
+
+            if stored_chunks.insert(hash.clone()) {
+                // compress data?
+                let chunk = match self.compression {
+                    Some(compression) => compress(chunk.to_vec(), compression)?,
+                    None => chunk.to_vec(),
+                };
+
+                // encrypt data?
+                let chunk = match self.encryption_keys.current() {
+                    Some(key) => encrypt(chunk, key.key.as_slice())?,
+                    None => chunk,
+                };
+
+                debug!("writing chunk {}", hash);
+                size += chunk.len();
+                let _ = self.datastore.write_chunk(hash.as_str(), chunk)?;
+            }
+
+            hashes.push(hash);
+            checksums.push(checksum(chunk));
+        }
+
+        Ok((hashes, checksums, size))
+    }
+
+    /// delete the chunks which are not referenced anymore by the dumps of the index file
+    fn delete_unreferenced_chunks(
+        &self,
+        chunks: Vec<String>,
+        index_file: &IndexFile,
+    ) -> Result<(), ReplibyteError> {
+        let referenced_chunks = referenced_chunks(index_file);
+
+        for hash in chunks.into_iter().collect::<HashSet<_>>() {
+            if !referenced_chunks.contains(&hash) {
+                debug!("deleting chunk {}", hash);
+                let _ = self.datastore.delete_chunk(hash.as_str())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Connector for Dedup {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        self.datastore.init()
+    }
+}
+
+impl Datastore for Dedup {
+    fn index_file(&self) -> Result<IndexFile, ReplibyteError> {
+        self.datastore.index_file()
+    }
+
+    fn raw_index_file(&self) -> Result<Value, ReplibyteError> {
+        self.datastore.raw_index_file()
+    }
+
+    fn write_index_file(&self, index_file: &IndexFile) -> Result<(), ReplibyteError> {
+        self.datastore.write_index_file(index_file)
+    }
+
+    fn write_raw_index_file(&self, raw_index_file: &Value) -> Result<(), ReplibyteError> {
+        self.datastore.write_raw_index_file(raw_index_file)
+    }
+
+    fn encoder(&self) -> Encoder {
+        if !self.enabled {
+            return self.datastore.encoder();
+        }
+
+        // the chunks are checksummed, compressed and encrypted once the chunks already stored are known
+        Encoder::default()
+    }
+
+    fn write_encoded(&self, file_part: u16, part: EncodedPart) -> Result<(), ReplibyteError> {
+        if !self.enabled {
+            return self.datastore.write_encoded(file_part, part);
+        }
+
+        let data = part.data;
+
+        let mut index_file = self.index_file()?;
+        let mut stored_chunks = referenced_chunks(&index_file);
+        let (chunks, checksums, size) = self.write_chunks(data.as_slice(), &mut stored_chunks)?;
+
+        info!(
+            "dump '{}' part {}: {} chunks, {} bytes written",
+            self.dump_name,
+            file_part,
+            chunks.len(),
+            size
+        );
+
+        match index_file
+            .dumps
+            .iter_mut()
+            .find(|dump| dump.directory_name == self.dump_name)
+        {
+            Some(dump) => {
+                dump.size += size;
+                dump.parts.get_or_insert_with(Vec::new).push(chunks.len());
+                dump.chunks.get_or_insert_with(Vec::new).extend(chunks);
+                dump.checksums
+                    .get_or_insert_with(Vec::new)
+                    .extend(checksums);
+            }
+            None => index_file.dumps.push(Dump {
+                directory_name: self.dump_name.to_string(),
+                size,
+                created_at: epoch_millis(),
+                compressed: self.compression.is_some(),
+                compression: self.compression.map(|compression| compression.algorithm),
+                encrypted: self.encryption_keys.current().is_some(),
+                encryption_key_id: self
+                    .encryption_keys
+                    .current()
+                    .and_then(|key| key.id.clone()),
+                encrypted_data_key: self
+                    .encryption_keys
+                    .current()
+                    .and_then(|key| key.encrypted_data_key.clone()),
+                parts: Some(vec![chunks.len()]),
+                chunks: Some(chunks),
+                started_at: None,
+                base: None,
+                uncompressed_size: None,
+                source_type: None,
+                metadata: None,
+                checksums: Some(checksums),
+            }),
+        }
+
+        self.write_index_file(&index_file)
+    }
+
+    fn read_encoded(
+        &self,
+        dump: &Dump,
+        data_callback: &mut dyn FnMut(Bytes) -> Result<(), ReplibyteError>,
+    ) -> Result<(), ReplibyteError> {
+        if dump.chunks.is_some() {
+            return Err(ReplibyteError::Datastore(format!(
+                "the dump '{}' is deduplicated, it is not stored in parts",
+                dump.directory_name
+            )));
+        }
+
+        self.datastore.read_encoded(dump, data_callback)
+    }
+
+    fn read(
+        &self,
+        options: &ReadOptions,
+        data_callback: &mut dyn FnMut(Bytes),
+    ) -> Result<(), ReplibyteError> {
+        let dump = self.index_file()?.find_dump(options)?.clone();
+
+        let chunks = match &dump.chunks {
+            Some(chunks) => chunks.clone(),
+            None => return self.datastore.read(options, data_callback),
+        };
+
+        // the chunks are cut anywhere in the queries, the parts written end with a complete query
+        let parts = match &dump.parts {
+            Some(parts) => parts.clone(),
+            None => vec![chunks.len()],
+        };
+
+        if parts.iter().sum::<usize>() != chunks.len() {
+            return Err(ReplibyteError::Datastore(format!(
+                "the dump '{}' has {} chunks, its parts have {}",
+                dump.directory_name,
+                chunks.len(),
+                parts.iter().sum::<usize>()
+            )));
+        }
+
+        // number of chunks read once each part is complete
+        let mut part_ends = parts
+            .iter()
+            .scan(0, |chunks_read, part_chunks| {
+                *chunks_read += part_chunks;
+                Some(*chunks_read)
+            })
+            .peekable();
+        let mut part = vec![];
+        let mut chunks_read = 0;
+
+        // the next chunks are downloaded while the previous ones are decrypted and decompressed
+        let encryption_keys = self.encryption_keys.clone();
+        let _ = read_concurrently(
+            |encoded_chunk_callback| {
+                for (idx, hash) in chunks.iter().enumerate() {
+                    let data = self.datastore.read_chunk(hash.as_str())?;
+                    let _ = encoded_chunk_callback((idx, hash.clone(), data))?;
+                }
+
+                Ok(())
+            },
+            move |(idx, hash, data)| {
+                decode_chunk(data, &dump, idx, hash.as_str(), &encryption_keys)
+            },
+            &mut |data| {
+                // the parts ending before this chunk are complete
+                while part_ends.next_if_eq(&chunks_read).is_some() {
+                    data_callback(std::mem::take(&mut part));
+                }
+
+                part.extend(data);
+                chunks_read += 1;
+            },
+        )?;
+
+        for _ in part_ends {
+            data_callback(std::mem::take(&mut part));
+        }
+
+        Ok(())
+    }
+
+    /// read the dump and verify each of its chunks, return the number of verified chunks
+    fn verify(&self, name: &str) -> Result<usize, ReplibyteError> {
+        let options = ReadOptions::Dump {
+            name: name.to_string(),
+        };
+
+        let chunks = match &self.index_file()?.find_dump(&options)?.chunks {
+            Some(chunks) => chunks.len(),
+            None => return self.datastore.verify(name),
+        };
+
+        let _ = self.read(&options, &mut |_| {}).map_err(|err| {
+            ReplibyteError::Datastore(format!("the dump '{}' is corrupted: {}", name, err))
+        })?;
+
+        Ok(chunks)
+    }
+
+    fn compression(&self) -> Option<Compression> {
+        self.compression
+    }
+
+    fn set_compression(&mut self, compression: Option<Compression>) {
+        self.datastore.set_compression(compression);
+        self.compression = compression;
+    }
+
+    fn encryption_keys(&self) -> &EncryptionKeys {
+        &self.encryption_keys
+    }
+
+    fn set_encryption_keys(&mut self, keys: EncryptionKeys) {
+        self.datastore.set_encryption_keys(keys.clone());
+        self.encryption_keys = keys;
+    }
+
+    fn set_dump_name(&mut self, name: String) {
+        self.datastore.set_dump_name(name.clone());
+        self.dump_name = name;
+    }
+
+    fn delete_dump_files(&self, name: &str) -> Result<(), ReplibyteError> {
+        self.datastore.delete_dump_files(name)
+    }
+
+    fn delete_by_name(&self, name: String) -> Result<(), ReplibyteError> {
+        let mut index_file = self.index_file()?;
+
+        let chunks = match index_file
+            .dumps
+            .iter()
+            .find(|dump| dump.directory_name == name)
+            .and_then(|dump| dump.chunks.clone())
+        {
+            Some(chunks) => chunks,
+            None => return self.datastore.delete_by_name(name),
+        };
+
+        // the dump is removed from the index file first, its chunks are not referenced once deleted
+        index_file.dumps.retain(|dump| dump.directory_name != name);
+        let _ = self.write_index_file(&index_file)?;
+
+        info!(
+            "deleting the chunks of dump '{}' not used by other dumps",
+            name
+        );
+        self.delete_unreferenced_chunks(chunks, &index_file)
+    }
+
+    fn delete_dumps(&self, names: &[String]) -> Result<(), ReplibyteError> {
+        let mut index_file = self.index_file()?;
+
+        let (deleted_dumps, dumps) = index_file
+            .dumps
+            .into_iter()
+            .partition(|dump| names.contains(&dump.directory_name));
+        index_file.dumps = dumps;
+        let _ = self.write_index_file(&index_file)?;
+
+        let mut chunks = vec![];
+        for dump in deleted_dumps {
+            match dump.chunks {
+                Some(dump_chunks) => chunks.extend(dump_chunks),
+                None => self
+                    .datastore
+                    .delete_dump_files(dump.directory_name.as_str())?,
+            }
+        }
+
+        info!("deleting the chunks of the deleted dumps not used by other dumps");
+        self.delete_unreferenced_chunks(chunks, &index_file)
+    }
+
+    fn write_chunk(&self, hash: &str, data: Bytes) -> Result<(), ReplibyteError> {
+        self.datastore.write_chunk(hash, data)
+    }
+
+    fn read_chunk(&self, hash: &str) -> Result<Bytes, ReplibyteError> {
+        self.datastore.read_chunk(hash)
+    }
+
+    fn delete_chunk(&self, hash: &str) -> Result<(), ReplibyteError> {
+        self.datastore.delete_chunk(hash)
+    }
+
+    /// re-encrypt a deduplicated dump with the current encryption key, its chunks are written again
+    /// and the ones not used anymore are deleted once the index file references the new ones
+    fn rewrap(&mut self, name: &str) -> Result<(), ReplibyteError> {
+        let mut index_file = self.index_file()?;
+        let dump = index_file
+            .dumps
+            .iter_mut()
+            .find(|dump| dump.directory_name.as_str() == name)
+            .ok_or_else(|| {
+                ReplibyteError::Datastore(format!("Can't find dump with name '{}'", name))
+            })?;
+
+        let previous_chunks = match &dump.chunks {
+            Some(chunks) => chunks.clone(),
+            None => return self.datastore.rewrap(name),
+        };
+
+        let current = self.encryption_keys.current().cloned();
+
+        // the chunk hashes are keyed with the data key, which stays the same
+        if let (
+            Some(encrypted_data_key),
+            Some(EncryptionKey {
+                encrypted_data_key: Some(_),
+                id,
+                ..
+            }),
+        ) = (&dump.encrypted_data_key, &current)
+        {
+            dump.encrypted_data_key =
+                Some(self.encryption_keys.rewrap_data_key(encrypted_data_key)?);
+            dump.encryption_key_id = id.clone();
+            return self.write_index_file(&index_file);
+        }
+
+        self.compression = dump
+            .compression_algorithm()
+            .map(|algorithm| Compression::new(algorithm, None));
+
+        let options = ReadOptions::Dump {
+            name: name.to_string(),
+        };
+
+        let datastore: &Self = self;
+        let mut stored_chunks = referenced_chunks(&index_file);
+        let mut chunks = vec![];
+        let mut parts = vec![];
+        let mut checksums = vec![];
+        let mut size = 0;
+        let mut result = Ok(());
+
+        let _ = datastore.read(&options, &mut |data| {
+            if result.is_ok() {
+                result = datastore
+                    .write_chunks(data.as_slice(), &mut stored_chunks)
+                    .map(|(hashes, chunk_checksums, written_size)| {
+                        parts.push(hashes.len());
+                        chunks.extend(hashes);
+                        checksums.extend(chunk_checksums);
+                        size += written_size;
+                    });
+            }
+        })?;
+
+        let _ = result?;
+
+        let mut index_file = self.index_file()?;
+        if let Some(dump) = index_file
+            .dumps
+            .iter_mut()
+            .find(|dump| dump.directory_name.as_str() == name)
+        {
+            dump.size = size;
+            dump.encrypted = current.is_some();
+            dump.encryption_key_id = current.as_ref().and_then(|key| key.id.clone());
+            dump.encrypted_data_key = current.and_then(|key| key.encrypted_data_key);
+            dump.chunks = Some(chunks);
+            dump.parts = Some(parts);
+            dump.checksums = Some(checksums);
+        }
+
+        let _ = self.write_index_file(&index_file)?;
+        self.delete_unreferenced_chunks(previous_chunks, &index_file)
+    }
+}
+
+/// decrypt and decompress the chunk `idx` of a dump, and compare it with its checksum
+fn decode_chunk(
+    data: Bytes,
+    dump: &Dump,
+    idx: usize,
+    hash: &str,
+    encryption_keys: &EncryptionKeys,
+) -> Result<Bytes, ReplibyteError> {
+    let data = decode(data, dump, encryption_keys)?;
+
+    if let Some(checksums) = &dump.checksums {
+        if checksums.get(idx) != Some(&checksum(data.as_slice())) {
+            return Err(ReplibyteError::Datastore(format!(
+                "the chunk {} ({}) does not match its checksum",
+                idx + 1,
+                hash
+            )));
+        }
+    }
+
+    Ok(data)
+}
+
+/// return the hashes of the chunks referenced by the dumps of the index file
+fn referenced_chunks(index_file: &IndexFile) -> HashSet<String> {
+    index_file
+        .dumps
+        .iter()
+        .flat_map(|dump| dump.chunks.iter().flatten())
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{read as read_file, read_dir, write as write_file};
+    use std::path::Path;
+
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use tempfile::tempdir;
+
+    use crate::connector::Connector;
+    use crate::datastore::dedup::Dedup;
+    use crate::datastore::local_disk::LocalDisk;
+    use crate::datastore::{Datastore, EncryptionKey, EncryptionKeys, ReadOptions, CHUNKS_DIR};
+
+    fn dedup(dir: &Path, enabled: bool) -> Dedup {
+        let mut dedup = Dedup::new(
+            Box::new(LocalDisk::new(dir.to_str().unwrap().to_string())),
+            enabled,
+        );
+        let _ = dedup.init().unwrap();
+        dedup
+    }
+
+    fn read(datastore: &dyn Datastore, name: &str) -> Vec<u8> {
+        let mut data = vec![];
+        let options = ReadOptions::Dump {
+            name: name.to_string(),
+        };
+
+        datastore
+            .read(&options, &mut |chunk| data.extend(chunk))
+            .unwrap();
+
+        data
+    }
+
+    fn chunks_count(dir: &Path) -> usize {
+        match read_dir(dir.join(CHUNKS_DIR)) {
+            Ok(entries) => entries.count(),
+            Err(_) => 0,
+        }
+    }
+
+    /// data which does not compress, so that it is cut in several chunks
+    fn random_data(size: usize) -> Vec<u8> {
+        let mut rng = StdRng::seed_from_u64(42);
+        (0..size).map(|_| rng.gen::<u8>()).collect()
+    }
+
+    #[test]
+    fn write_the_missing_chunks_only() {
+        let dir = tempdir().unwrap();
+        let data = random_data(8 * 1024 * 1024);
+
+        let mut datastore = dedup(dir.path(), true);
+        datastore.set_dump_name("dump-1".to_string());
+        let _ = datastore.write(1, data.clone()).unwrap();
+        let chunks = chunks_count(dir.path());
+        assert!(chunks > 1);
+
+        // the second dump only adds the chunk of the data appended
+        let mut appended_data = data.clone();
+        appended_data.extend(b"INSERT INTO orders VALUES (1);");
+
+        datastore.set_dump_name("dump-2".to_string());
+        let _ = datastore.write(1, appended_data.clone()).unwrap();
+        assert_eq!(chunks_count(dir.path()), chunks + 1);
+
+        let index_file = datastore.index_file().unwrap();
+        assert_eq!(index_file.dumps[1].chunks.as_ref().unwrap().len(), chunks);
+        assert!(index_file.dumps[1].size < index_file.dumps[0].size);
+
+        assert_eq!(read(&datastore, "dump-1"), data);
+        assert_eq!(read(&datastore, "dump-2"), appended_data);
+
+        // the chunks shared with the second dump are kept
+        let _ = datastore.delete_by_name("dump-1".to_string()).unwrap();
+        assert_eq!(chunks_count(dir.path()), chunks);
+        assert_eq!(read(&datastore, "dump-2"), appended_data);
+
+        let _ = datastore.delete_by_name("dump-2".to_string()).unwrap();
+        assert_eq!(chunks_count(dir.path()), 0);
+        assert!(datastore.index_file().unwrap().dumps.is_empty());
+    }
+
+    #[test]
+    fn read_the_chunks_of_each_part_together() {
+        let dir = tempdir().unwrap();
+        // an empty part has no chunks
+        let parts = [
+            random_data(6 * 1024 * 1024),
+            vec![],
+            random_data(5 * 1024 * 1024),
+        ];
+
+        let mut datastore = dedup(dir.path(), true);
+        datastore.set_dump_name("dump-1".to_string());
+        for (idx, part) in parts.iter().enumerate() {
+            let _ = datastore.write(idx as u16 + 1, part.clone()).unwrap();
+        }
+
+        let index_file = datastore.index_file().unwrap();
+        let dump = &index_file.dumps[0];
+        assert!(dump.chunks.as_ref().unwrap().len() > 2);
+        assert_eq!(dump.parts.as_ref().unwrap().len(), 3);
+        assert_eq!(dump.parts.as_ref().unwrap()[1], 0);
+
+        // the parts end with a complete query, the chunks do not
+        let mut read_parts = vec![];
+        let options = ReadOptions::Dump {
+            name: "dump-1".to_string(),
+        };
+        let _ = datastore
+            .read(&options, &mut |data| read_parts.push(data))
+            .unwrap();
+
+        assert_eq!(read_parts, parts);
+    }
+
+    #[test]
+    fn verify_the_chunks() {
+        let dir = tempdir().unwrap();
+
+        let mut datastore = dedup(dir.path(), true);
+        datastore.set_dump_name("dump-1".to_string());
+        let _ = datastore.write(1, b"hello".to_vec()).unwrap();
+        let _ = datastore.write(2, b"world".to_vec()).unwrap();
+        assert_eq!(datastore.verify("dump-1").unwrap(), 2);
+
+        // the chunks are swapped, each one is still a valid compressed chunk
+        let chunks = datastore.index_file().unwrap().dumps[0]
+            .chunks
+            .clone()
+            .unwrap();
+        let path = |hash: &String| dir.path().join(CHUNKS_DIR).join(hash);
+        let (hello, world) = (
+            read_file(path(&chunks[0])).unwrap(),
+            read_file(path(&chunks[1])).unwrap(),
+        );
+        write_file(path(&chunks[0]), world).unwrap();
+        write_file(path(&chunks[1]), hello).unwrap();
+
+        let options = ReadOptions::Dump {
+            name: "dump-1".to_string(),
+        };
+        assert!(datastore.read(&options, &mut |_| {}).is_err());
+        assert!(datastore.verify("dump-1").is_err());
+    }
+
+    #[test]
+    fn read_and_delete_dumps_without_chunks() {
+        let dir = tempdir().unwrap();
+
+        let mut datastore = dedup(dir.path(), false);
+        datastore.set_dump_name("dump-1".to_string());
+        let _ = datastore.write(1, b"hello world".to_vec()).unwrap();
+
+        let mut datastore = dedup(dir.path(), true);
+        datastore.set_dump_name("dump-2".to_string());
+        let _ = datastore.write(1, b"hello world".to_vec()).unwrap();
+
+        let index_file = datastore.index_file().unwrap();
+        assert!(index_file.dumps[0].chunks.is_none());
+        assert_eq!(index_file.dumps[1].chunks.as_ref().unwrap().len(), 1);
+
+        assert_eq!(read(&datastore, "dump-1"), b"hello world");
+        assert_eq!(read(&datastore, "dump-2"), b"hello world");
+
+        let _ = datastore.delete_by_name("dump-1".to_string()).unwrap();
+        assert!(!dir.path().join("dump-1").exists());
+        assert_eq!(chunks_count(dir.path()), 1);
+    }
+
+    #[test]
+    fn delete_dumps_with_and_without_chunks() {
+        let dir = tempdir().unwrap();
+
+        let mut datastore = dedup(dir.path(), false);
+        datastore.set_dump_name("dump-1".to_string());
+        let _ = datastore.write(1, b"hello world".to_vec()).unwrap();
+
+        let mut datastore = dedup(dir.path(), true);
+        datastore.set_dump_name("dump-2".to_string());
+        let _ = datastore.write(1, b"hello world".to_vec()).unwrap();
+
+        let _ = datastore
+            .delete_dumps(&["dump-1".to_string(), "dump-2".to_string()])
+            .unwrap();
+        assert!(!dir.path().join("dump-1").exists());
+        assert_eq!(chunks_count(dir.path()), 0);
+        assert!(datastore.index_file().unwrap().dumps.is_empty());
+    }
+
+    #[test]
+    fn rewrap_the_chunks() {
+        let dir = tempdir().unwrap();
+        let key = |id: &str| EncryptionKey {
+            id: Some(id.to_string()),
+            key: format!("key {}", id).into_bytes(),
+            encrypted_data_key: None,
+        };
+
+        let mut datastore = dedup(dir.path(), true);
+        datastore.set_encryption_keys(EncryptionKeys::new(vec![key("1")]));
+        datastore.set_dump_name("dump-1".to_string());
+        let _ = datastore.write(1, b"hello world".to_vec()).unwrap();
+        let previous_chunks = datastore.index_file().unwrap().dumps[0].chunks.clone();
+
+        // the same data encrypted with another key has other chunks
+        let mut datastore = dedup(dir.path(), true);
+        datastore.set_encryption_keys(EncryptionKeys::new(vec![key("1"), key("2")]));
+        let _ = datastore.rewrap("dump-1").unwrap();
+
+        let index_file = datastore.index_file().unwrap();
+        let dump = &index_file.dumps[0];
+        assert_eq!(dump.encryption_key_id.as_deref(), Some("2"));
+        assert_ne!(dump.chunks, previous_chunks);
+        assert_eq!(chunks_count(dir.path()), 1);
+
+        let mut datastore = dedup(dir.path(), true);
+        datastore.set_encryption_keys(EncryptionKeys::new(vec![key("2")]));
+        assert_eq!(read(&datastore, "dump-1"), b"hello world");
+        assert_eq!(datastore.verify("dump-1").unwrap(), 1);
+    }
+}
@@ -0,0 +1,416 @@
+use std::io::{Error, ErrorKind};
+use std::time::Duration;
+
+use log::{error, info};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::header::CONTENT_TYPE;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::config::Endpoint;
+use crate::connector::Connector;
+use crate::datastore::{
+    chunk_key, dump_part_number, Compression, Datastore, Dump, EncodedPart, EncryptionKeys,
+    IndexFile,
+};
+use crate::errors::ReplibyteError;
+use crate::gcp::{check_response, Credentials};
+use crate::types::Bytes;
+use crate::utils::epoch_millis;
+
+use super::INDEX_FILE_NAME;
+
+const GOOGLE_CLOUD_STORAGE_API_URL: &str = "https://storage.googleapis.com";
+const STORAGE_SCOPE: &str = "https://www.googleapis.com/auth/devstorage.read_write";
+const TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Deserialize)]
+struct ObjectList {
+    #[serde(default)]
+    items: Vec<ObjectListItem>,
+    #[serde(rename = "nextPageToken")]
+    next_page_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ObjectListItem {
+    name: String,
+}
+
+pub struct GoogleCloudStorage {
+    bucket: String,
+    root_key: String,
+    endpoint: String,
+    client: Client,
+    credentials: Credentials,
+    compression: Option<Compression>,
+    encryption_keys: EncryptionKeys,
+}
+
+impl GoogleCloudStorage {
+    /// the service account key file is `GOOGLE_APPLICATION_CREDENTIALS` by default,
+    /// the workload identity is used if none is set
+    pub fn new<S: Into<String>>(
+        bucket: S,
+        service_account_key: Option<String>,
+        endpoint: Endpoint,
+        proxy: Option<String>,
+    ) -> Result<Self, ReplibyteError> {
+        let credentials = Credentials::new(service_account_key, STORAGE_SCOPE)?;
+
+        let mut client_builder = Client::builder().timeout(TIMEOUT);
+        if let Some(proxy) = proxy {
+            let proxy = reqwest::Proxy::all(proxy.as_str())
+                .map_err(|err| ReplibyteError::Datastore(format!("invalid proxy: {}", err)))?;
+
+            client_builder = client_builder.proxy(proxy);
+        }
+
+        let client = client_builder
+            .build()
+            .map_err(|err| ReplibyteError::Datastore(err.to_string()))?;
+
+        let endpoint = match endpoint {
+            Endpoint::Default => GOOGLE_CLOUD_STORAGE_API_URL.to_string(),
+            Endpoint::Custom(url) => url.trim_end_matches('/').to_string(),
+        };
+
+        Ok(GoogleCloudStorage {
+            bucket: bucket.into(),
+            root_key: format!("dump-{}", epoch_millis()),
+            endpoint,
+            client,
+            credentials,
+            compression: Some(Compression::default()),
+            encryption_keys: EncryptionKeys::default(),
+        })
+    }
+
+    fn create_index_file(&self) -> Result<IndexFile, ReplibyteError> {
+        match self.index_file() {
+            Ok(index_file) => Ok(index_file),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                let index_file = IndexFile::new();
+                let _ = self.write_index_file(&index_file)?;
+                Ok(index_file)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn send(&self, request: RequestBuilder) -> Result<Response, ReplibyteError> {
+        check_response(
+            request
+                .bearer_auth(self.credentials.access_token(&self.client)?)
+                .send(),
+        )
+    }
+
+    fn object_url(&self, key: &str) -> String {
+        format!(
+            "{}/storage/v1/b/{}/o/{}",
+            self.endpoint,
+            self.bucket,
+            utf8_percent_encode(key, NON_ALPHANUMERIC)
+        )
+    }
+
+    fn get_object(&self, key: &str) -> Result<Bytes, ReplibyteError> {
+        let response = self
+            .send(
+                self.client
+                    .get(self.object_url(key))
+                    .query(&[("alt", "media")]),
+            )
+            .map_err(|err| {
+                Error::new(
+                    err.kind(),
+                    format!(
+                        "failed to download object '{}/{}': {}",
+                        self.bucket, key, err
+                    ),
+                )
+            })?;
+
+        response
+            .bytes()
+            .map(|data| data.to_vec())
+            .map_err(|err| ReplibyteError::Datastore(err.to_string()))
+    }
+
+    fn create_object(&self, key: &str, data: Bytes) -> Result<(), ReplibyteError> {
+        let _ = self
+            .send(
+                self.client
+                    .post(format!(
+                        "{}/upload/storage/v1/b/{}/o",
+                        self.endpoint, self.bucket
+                    ))
+                    .query(&[("uploadType", "media"), ("name", key)])
+                    .header(CONTENT_TYPE, "application/octet-stream")
+                    .body(data),
+            )
+            .map_err(|err| {
+                error!("{}", err);
+                Error::new(
+                    err.kind(),
+                    format!("failed to upload object '{}/{}': {}", self.bucket, key, err),
+                )
+            })?;
+
+        Ok(())
+    }
+
+    fn delete_object(&self, key: &str) -> Result<(), ReplibyteError> {
+        let _ = self
+            .send(self.client.delete(self.object_url(key)))
+            .map_err(|err| {
+                Error::new(
+                    err.kind(),
+                    format!("failed to delete object '{}/{}': {}", self.bucket, key, err),
+                )
+            })?;
+
+        Ok(())
+    }
+
+    /// list the keys of the objects starting with the prefix
+    fn list_objects(&self, prefix: &str) -> Result<Vec<String>, ReplibyteError> {
+        let mut keys = vec![];
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut request = self
+                .client
+                .get(format!("{}/storage/v1/b/{}/o", self.endpoint, self.bucket))
+                .query(&[("prefix", prefix), ("fields", "items(name),nextPageToken")]);
+
+            if let Some(page_token) = &page_token {
+                request = request.query(&[("pageToken", page_token.as_str())]);
+            }
+
+            let response = self.send(request).map_err(|err| {
+                Error::new(
+                    err.kind(),
+                    format!(
+                        "failed to list objects from bucket '{}': {}",
+                        self.bucket, err
+                    ),
+                )
+            })?;
+
+            let object_list: ObjectList = serde_json::from_slice(
+                response
+                    .bytes()
+                    .map_err(|err| ReplibyteError::Datastore(err.to_string()))?
+                    .as_ref(),
+            )?;
+
+            keys.extend(object_list.items.into_iter().map(|item| item.name));
+
+            match object_list.next_page_token {
+                Some(next_page_token) => page_token = Some(next_page_token),
+                None => break,
+            }
+        }
+
+        Ok(keys)
+    }
+}
+
+impl Connector for GoogleCloudStorage {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        // the bucket is not created, it needs a project and a location which are not known here
+        let _ = self
+            .send(
+                self.client
+                    .get(format!("{}/storage/v1/b/{}", self.endpoint, self.bucket)),
+            )
+            .map_err(|err| match err.kind() {
+                ErrorKind::NotFound => ReplibyteError::from(Error::new(
+                    ErrorKind::NotFound,
+                    format!("bucket '{}' does not exist", self.bucket),
+                )),
+                _ => err,
+            })?;
+
+        info!("bucket {} exists", self.bucket);
+
+        self.create_index_file().map(|_| ())
+    }
+}
+
+impl Datastore for GoogleCloudStorage {
+    fn index_file(&self) -> Result<IndexFile, ReplibyteError> {
+        let object = self.get_object(INDEX_FILE_NAME)?;
+        let index_file: IndexFile = serde_json::from_slice(object.as_slice())?;
+        Ok(index_file)
+    }
+
+    fn raw_index_file(&self) -> Result<Value, ReplibyteError> {
+        let object = self.get_object(INDEX_FILE_NAME)?;
+        let index_file = serde_json::from_slice(object.as_slice())?;
+        Ok(index_file)
+    }
+
+    fn write_index_file(&self, index_file: &IndexFile) -> Result<(), ReplibyteError> {
+        let index_file_json = serde_json::to_vec(index_file)?;
+        self.create_object(INDEX_FILE_NAME, index_file_json)
+    }
+
+    fn write_raw_index_file(&self, raw_index_file: &Value) -> Result<(), ReplibyteError> {
+        let index_file_json = serde_json::to_vec(raw_index_file)?;
+        self.create_object(INDEX_FILE_NAME, index_file_json)
+    }
+
+    fn write_encoded(&self, file_part: u16, part: EncodedPart) -> Result<(), ReplibyteError> {
+        let EncodedPart { data, checksum } = part;
+
+        let data_size = data.len();
+        let key = format!("{}/{}.dump", self.root_key, file_part);
+
+        info!("upload object '{}' part {} on", key.as_str(), file_part);
+
+        let _ = self.create_object(key.as_str(), data)?;
+
+        // update index file
+        let mut index_file = self.index_file()?;
+
+        let mut new_dump = Dump {
+            directory_name: self.root_key.to_string(),
+            size: 0,
+            created_at: epoch_millis(),
+            compressed: self.compression().is_some(),
+            compression: self.compression().map(|compression| compression.algorithm),
+            encrypted: self.encryption_keys().current().is_some(),
+            encryption_key_id: self
+                .encryption_keys()
+                .current()
+                .and_then(|key| key.id.clone()),
+            encrypted_data_key: self
+                .encryption_keys()
+                .current()
+                .and_then(|key| key.encrypted_data_key.clone()),
+            chunks: None,
+            parts: None,
+            started_at: None,
+            base: None,
+            uncompressed_size: None,
+            source_type: None,
+            metadata: None,
+            checksums: None,
+        };
+
+        // find or create dump
+        let dump = index_file
+            .dumps
+            .iter_mut()
+            .find(|b| b.directory_name.as_str() == self.root_key)
+            .unwrap_or(&mut new_dump);
+
+        dump.checksums.get_or_insert_with(Vec::new).push(checksum);
+
+        if dump.size == 0 {
+            // it means it's a new dump.
+            // We need to add it into the index_file.dumps
+            new_dump.size = data_size;
+            index_file.dumps.push(new_dump);
+        } else {
+            // update total dump size
+            dump.size += data_size;
+        }
+
+        // save index file
+        self.write_index_file(&index_file)
+    }
+
+    fn read_encoded(
+        &self,
+        dump: &Dump,
+        data_callback: &mut dyn FnMut(Bytes) -> Result<(), ReplibyteError>,
+    ) -> Result<(), ReplibyteError> {
+        let mut keys = self.list_objects(format!("{}/", dump.directory_name).as_str())?;
+        // the parts are read in order, `10.dump` comes after `9.dump`
+        keys.sort_by_key(|key| dump_part_number(key.as_str()));
+
+        for key in keys {
+            let _ = data_callback(self.get_object(key.as_str())?)?;
+        }
+
+        Ok(())
+    }
+
+    fn compression(&self) -> Option<Compression> {
+        self.compression
+    }
+
+    fn set_compression(&mut self, compression: Option<Compression>) {
+        self.compression = compression;
+    }
+
+    fn encryption_keys(&self) -> &EncryptionKeys {
+        &self.encryption_keys
+    }
+
+    fn set_encryption_keys(&mut self, keys: EncryptionKeys) {
+        self.encryption_keys = keys;
+    }
+
+    fn set_dump_name(&mut self, name: String) {
+        self.root_key = name;
+    }
+
+    fn delete_dump_files(&self, name: &str) -> Result<(), ReplibyteError> {
+        for key in self.list_objects(format!("{}/", name).as_str())? {
+            self.delete_object(key.as_str())?;
+        }
+
+        Ok(())
+    }
+
+    fn write_chunk(&self, hash: &str, data: Bytes) -> Result<(), ReplibyteError> {
+        self.create_object(chunk_key(hash).as_str(), data)
+    }
+
+    fn read_chunk(&self, hash: &str) -> Result<Bytes, ReplibyteError> {
+        self.get_object(chunk_key(hash).as_str())
+    }
+
+    fn delete_chunk(&self, hash: &str) -> Result<(), ReplibyteError> {
+        self.delete_object(chunk_key(hash).as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Endpoint;
+    use crate::datastore::gcs::GoogleCloudStorage;
+
+    #[test]
+    fn object_urls() {
+        let gcs = GoogleCloudStorage::new(
+            "replibyte-test",
+            None,
+            Endpoint::Custom("http://localhost:4443/".to_string()),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            gcs.object_url("dump-1650000000000/1.dump"),
+            "http://localhost:4443/storage/v1/b/replibyte-test/o/dump%2D1650000000000%2F1%2Edump"
+        );
+    }
+
+    #[test]
+    fn missing_service_account_key() {
+        assert!(GoogleCloudStorage::new(
+            "replibyte-test",
+            Some("/does/not/exist.json".to_string()),
+            Endpoint::Default,
+            None,
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,540 @@
+use std::io::{Error, ErrorKind};
+use std::time::Duration;
+
+use log::{error, info};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::StatusCode;
+use serde_json::Value;
+use url::Url;
+
+use crate::connector::Connector;
+use crate::datastore::{
+    chunk_key, Compression, Datastore, Dump, EncodedPart, EncryptionKeys, IndexFile,
+};
+use crate::errors::ReplibyteError;
+use crate::types::Bytes;
+use crate::utils::epoch_millis;
+
+use super::INDEX_FILE_NAME;
+
+/// path of the dump parts relative to the url, `{dump}` is the name of the dump and `{part}` the part number
+const DEFAULT_DUMP_PART_PATH: &str = "{dump}/{part}.dump";
+const TIMEOUT: Duration = Duration::from_secs(300);
+
+/// characters encoded in the dump names, which are used in the urls
+const PATH_SEGMENT_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// Http stores the index file and the dump parts on any HTTP(S) service accepting PUT, GET and DELETE requests,
+/// the dump parts are numbered from 1 and read until the service answers 404.
+pub struct Http {
+    url: String,
+    index_file_path: String,
+    dump_part_path: String,
+    token: Option<String>,
+    root_key: String,
+    client: Client,
+    compression: Option<Compression>,
+    encryption_keys: EncryptionKeys,
+}
+
+impl Http {
+    pub fn new<S: Into<String>>(
+        url: S,
+        index_file_path: Option<String>,
+        dump_part_path: Option<String>,
+        token: Option<String>,
+        proxy: Option<String>,
+    ) -> Result<Self, ReplibyteError> {
+        let url = url.into();
+        let _ = Url::parse(url.as_str()).map_err(|err| {
+            ReplibyteError::Datastore(format!("invalid datastore url '{}': {}", url, err))
+        })?;
+
+        let dump_part_path = dump_part_path.unwrap_or_else(|| DEFAULT_DUMP_PART_PATH.to_string());
+        if !dump_part_path.contains("{dump}") || !dump_part_path.contains("{part}") {
+            return Err(ReplibyteError::Datastore(format!(
+                "invalid dump_part_path '{}': {{dump}} and {{part}} are required",
+                dump_part_path
+            )));
+        }
+
+        let mut client_builder = Client::builder().timeout(TIMEOUT);
+        if let Some(proxy) = proxy {
+            let proxy = reqwest::Proxy::all(proxy.as_str())
+                .map_err(|err| ReplibyteError::Datastore(format!("invalid proxy: {}", err)))?;
+
+            client_builder = client_builder.proxy(proxy);
+        }
+
+        let client = client_builder
+            .build()
+            .map_err(|err| ReplibyteError::Datastore(err.to_string()))?;
+
+        Ok(Http {
+            url: url.trim_end_matches('/').to_string(),
+            index_file_path: index_file_path.unwrap_or_else(|| INDEX_FILE_NAME.to_string()),
+            dump_part_path,
+            token,
+            root_key: format!("dump-{}", epoch_millis()),
+            client,
+            compression: Some(Compression::default()),
+            encryption_keys: EncryptionKeys::default(),
+        })
+    }
+
+    fn create_index_file(&self) -> Result<IndexFile, ReplibyteError> {
+        match self.index_file() {
+            Ok(index_file) => Ok(index_file),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                info!("creating a new index_file");
+                let index_file = IndexFile::new();
+                let _ = self.write_index_file(&index_file)?;
+                Ok(index_file)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn send(&self, request: RequestBuilder) -> Result<Response, ReplibyteError> {
+        let request = match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        };
+
+        check_response(request.send())
+    }
+
+    fn index_file_url(&self) -> String {
+        format!(
+            "{}/{}",
+            self.url,
+            self.index_file_path.trim_start_matches('/')
+        )
+    }
+
+    fn dump_part_url(&self, dump_name: &str, part: u16) -> String {
+        let path = self
+            .dump_part_path
+            .replace(
+                "{dump}",
+                utf8_percent_encode(dump_name, PATH_SEGMENT_ENCODE_SET)
+                    .to_string()
+                    .as_str(),
+            )
+            .replace("{part}", part.to_string().as_str());
+
+        format!("{}/{}", self.url, path.trim_start_matches('/'))
+    }
+
+    fn chunk_url(&self, hash: &str) -> String {
+        format!("{}/{}", self.url, chunk_key(hash))
+    }
+
+    fn get(&self, url: &str) -> Result<Bytes, ReplibyteError> {
+        let response = self
+            .send(self.client.get(url))
+            .map_err(|err| err.with_context(format!("failed to download '{}'", url).as_str()))?;
+
+        response
+            .bytes()
+            .map(|data| data.to_vec())
+            .map_err(|err| ReplibyteError::Datastore(err.to_string()))
+    }
+
+    fn put(&self, url: &str, content_type: &str, data: Bytes) -> Result<(), ReplibyteError> {
+        let _ = self
+            .send(
+                self.client
+                    .put(url)
+                    .header(CONTENT_TYPE, content_type)
+                    .body(data),
+            )
+            .map_err(|err| err.with_context(format!("failed to upload '{}'", url).as_str()))?;
+
+        Ok(())
+    }
+
+    fn delete(&self, url: &str) -> Result<(), ReplibyteError> {
+        let _ = self
+            .send(self.client.delete(url))
+            .map_err(|err| err.with_context(format!("failed to delete '{}'", url).as_str()))?;
+
+        Ok(())
+    }
+}
+
+impl Connector for Http {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        self.create_index_file().map(|_| ())
+    }
+}
+
+impl Datastore for Http {
+    fn index_file(&self) -> Result<IndexFile, ReplibyteError> {
+        let data = self.get(self.index_file_url().as_str())?;
+        let index_file: IndexFile = serde_json::from_slice(data.as_slice())?;
+        Ok(index_file)
+    }
+
+    fn raw_index_file(&self) -> Result<Value, ReplibyteError> {
+        let data = self.get(self.index_file_url().as_str())?;
+        let raw_index_file = serde_json::from_slice(data.as_slice())?;
+        Ok(raw_index_file)
+    }
+
+    fn write_index_file(&self, index_file: &IndexFile) -> Result<(), ReplibyteError> {
+        let index_file_json = serde_json::to_vec(index_file)?;
+        self.put(
+            self.index_file_url().as_str(),
+            "application/json",
+            index_file_json,
+        )
+    }
+
+    fn write_raw_index_file(&self, raw_index_file: &Value) -> Result<(), ReplibyteError> {
+        let index_file_json = serde_json::to_vec(raw_index_file)?;
+        self.put(
+            self.index_file_url().as_str(),
+            "application/json",
+            index_file_json,
+        )
+    }
+
+    fn write_encoded(&self, file_part: u16, part: EncodedPart) -> Result<(), ReplibyteError> {
+        let EncodedPart { data, checksum } = part;
+
+        let data_size = data.len();
+        let url = self.dump_part_url(self.root_key.as_str(), file_part);
+
+        info!("upload dump '{}' part {}", self.root_key, file_part);
+
+        let _ = self.put(url.as_str(), "application/octet-stream", data)?;
+
+        // update index file
+        let mut index_file = self.index_file()?;
+
+        let mut new_dump = Dump {
+            directory_name: self.root_key.to_string(),
+            size: 0,
+            created_at: epoch_millis(),
+            compressed: self.compression().is_some(),
+            compression: self.compression().map(|compression| compression.algorithm),
+            encrypted: self.encryption_keys().current().is_some(),
+            encryption_key_id: self
+                .encryption_keys()
+                .current()
+                .and_then(|key| key.id.clone()),
+            encrypted_data_key: self
+                .encryption_keys()
+                .current()
+                .and_then(|key| key.encrypted_data_key.clone()),
+            chunks: None,
+            parts: None,
+            started_at: None,
+            base: None,
+            uncompressed_size: None,
+            source_type: None,
+            metadata: None,
+            checksums: None,
+        };
+
+        // find or create dump
+        let dump = index_file
+            .dumps
+            .iter_mut()
+            .find(|b| b.directory_name.as_str() == self.root_key)
+            .unwrap_or(&mut new_dump);
+
+        dump.checksums.get_or_insert_with(Vec::new).push(checksum);
+
+        if dump.size == 0 {
+            // it means it's a new dump.
+            // We need to add it into the index_file.dumps
+            new_dump.size = data_size;
+            index_file.dumps.push(new_dump);
+        } else {
+            // update total dump size
+            dump.size += data_size;
+        }
+
+        // save index file
+        self.write_index_file(&index_file)
+    }
+
+    fn read_encoded(
+        &self,
+        dump: &Dump,
+        data_callback: &mut dyn FnMut(Bytes) -> Result<(), ReplibyteError>,
+    ) -> Result<(), ReplibyteError> {
+        // the parts are numbered from 1, the service answers 404 after the last one
+        for part in 1..=u16::MAX {
+            let data = match self.get(
+                self.dump_part_url(dump.directory_name.as_str(), part)
+                    .as_str(),
+            ) {
+                Ok(data) => data,
+                Err(err) if err.kind() == ErrorKind::NotFound && part > 1 => break,
+                Err(err) => return Err(err),
+            };
+
+            let _ = data_callback(data)?;
+        }
+
+        Ok(())
+    }
+
+    fn compression(&self) -> Option<Compression> {
+        self.compression
+    }
+
+    fn set_compression(&mut self, compression: Option<Compression>) {
+        self.compression = compression;
+    }
+
+    fn encryption_keys(&self) -> &EncryptionKeys {
+        &self.encryption_keys
+    }
+
+    fn set_encryption_keys(&mut self, keys: EncryptionKeys) {
+        self.encryption_keys = keys;
+    }
+
+    fn set_dump_name(&mut self, name: String) {
+        self.root_key = name;
+    }
+
+    fn delete_dump_files(&self, name: &str) -> Result<(), ReplibyteError> {
+        for part in 1..=u16::MAX {
+            match self.delete(self.dump_part_url(name, part).as_str()) {
+                Ok(_) => {}
+                Err(err) if err.kind() == ErrorKind::NotFound => break,
+                Err(err) => {
+                    error!("error while deleting the dump '{}' part {}", name, part);
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_chunk(&self, hash: &str, data: Bytes) -> Result<(), ReplibyteError> {
+        self.put(
+            self.chunk_url(hash).as_str(),
+            "application/octet-stream",
+            data,
+        )
+    }
+
+    fn read_chunk(&self, hash: &str) -> Result<Bytes, ReplibyteError> {
+        self.get(self.chunk_url(hash).as_str())
+    }
+
+    fn delete_chunk(&self, hash: &str) -> Result<(), ReplibyteError> {
+        self.delete(self.chunk_url(hash).as_str())
+    }
+}
+
+fn check_response(response: reqwest::Result<Response>) -> Result<Response, ReplibyteError> {
+    let response = response.map_err(|err| ReplibyteError::Datastore(err.to_string()))?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let body = response.text().unwrap_or_default();
+    let kind = match status {
+        StatusCode::NOT_FOUND => ErrorKind::NotFound,
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ErrorKind::PermissionDenied,
+        _ => ErrorKind::Other,
+    };
+
+    Err(ReplibyteError::from(Error::new(
+        kind,
+        format!("{} {}", status, body.trim()),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    use crate::connector::Connector;
+    use crate::datastore::http::Http;
+    use crate::datastore::{Datastore, ReadOptions};
+
+    /// files of the artifact server by path
+    type Files = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+    /// serve PUT, GET and DELETE requests from memory, the token must be sent by the client
+    fn artifact_server(token: &'static str) -> (String, Files) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/replibyte", listener.local_addr().unwrap());
+        let files = Arc::new(Mutex::new(HashMap::new()));
+        let server_files = files.clone();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut request_line = String::new();
+                let _ = reader.read_line(&mut request_line).unwrap();
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap_or_default().to_string();
+                let path = parts.next().unwrap_or_default().to_string();
+
+                let mut content_length = 0;
+                let mut authorized = false;
+                loop {
+                    let mut header = String::new();
+                    let _ = reader.read_line(&mut header).unwrap();
+                    let header = header.trim_end();
+                    if header.is_empty() {
+                        break;
+                    }
+
+                    let (name, value) = header.split_once(": ").unwrap();
+                    match name.to_lowercase().as_str() {
+                        "content-length" => content_length = value.parse().unwrap(),
+                        "authorization" => authorized = value == format!("Bearer {}", token),
+                        _ => {}
+                    }
+                }
+
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                let mut files = server_files.lock().unwrap();
+                let (status, body) = match (authorized, method.as_str()) {
+                    (false, _) => ("401 Unauthorized", vec![]),
+                    (true, "PUT") => {
+                        let _ = files.insert(path, body);
+                        ("201 Created", vec![])
+                    }
+                    (true, "GET") => match files.get(&path) {
+                        Some(body) => ("200 OK", body.clone()),
+                        None => ("404 Not Found", vec![]),
+                    },
+                    (true, "DELETE") => match files.remove(&path) {
+                        Some(_) => ("204 No Content", vec![]),
+                        None => ("404 Not Found", vec![]),
+                    },
+                    _ => ("405 Method Not Allowed", vec![]),
+                };
+
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    body.len()
+                );
+                let _ = stream.write_all(body.as_slice());
+            }
+        });
+
+        (url, files)
+    }
+
+    #[test]
+    fn dump_part_urls() {
+        let http = Http::new(
+            "https://artifacts.example.com/replibyte/",
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            http.index_file_url(),
+            "https://artifacts.example.com/replibyte/metadata.json"
+        );
+        assert_eq!(
+            http.dump_part_url("dump 1", 12),
+            "https://artifacts.example.com/replibyte/dump%201/12.dump"
+        );
+
+        let http = Http::new(
+            "https://artifacts.example.com",
+            Some("/replibyte/index.json".to_string()),
+            Some("/replibyte/{dump}-{part}.bin".to_string()),
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            http.index_file_url(),
+            "https://artifacts.example.com/replibyte/index.json"
+        );
+        assert_eq!(
+            http.dump_part_url("dump-1", 3),
+            "https://artifacts.example.com/replibyte/dump-1-3.bin"
+        );
+
+        assert!(Http::new(
+            "https://artifacts.example.com",
+            None,
+            Some("{dump}.dump".to_string()),
+            None,
+            None
+        )
+        .is_err());
+        assert!(Http::new("artifacts", None, None, None, None).is_err());
+    }
+
+    #[test]
+    fn write_read_and_delete() {
+        let (url, files) = artifact_server("secret");
+
+        let mut http =
+            Http::new(url.as_str(), None, None, Some("secret".to_string()), None).unwrap();
+        let _ = http.init().unwrap();
+        http.set_encryption_keys("encryption key".to_string().into());
+        http.set_dump_name("dump-1".to_string());
+
+        for part in 1..=3u16 {
+            let _ = http
+                .write(part, format!("part {}", part).into_bytes())
+                .unwrap();
+        }
+
+        assert!(files
+            .lock()
+            .unwrap()
+            .contains_key("/replibyte/dump-1/3.dump"));
+        assert_eq!(http.index_file().unwrap().dumps.len(), 1);
+
+        let mut parts = vec![];
+        let _ = http
+            .read(&ReadOptions::Latest, &mut |data| {
+                parts.push(String::from_utf8(data).unwrap());
+            })
+            .unwrap();
+
+        assert_eq!(parts, vec!["part 1", "part 2", "part 3"]);
+
+        let _ = http.delete_by_name("dump-1".to_string()).unwrap();
+        assert!(http.index_file().unwrap().dumps.is_empty());
+        assert_eq!(files.lock().unwrap().len(), 1);
+
+        // a wrong token is refused
+        let http = Http::new(url.as_str(), None, None, Some("wrong".to_string()), None).unwrap();
+        assert_eq!(
+            http.index_file().unwrap_err().kind(),
+            std::io::ErrorKind::PermissionDenied
+        );
+    }
+}
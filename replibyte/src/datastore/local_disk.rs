@@ -1,4 +1,4 @@
-use std::fs::{read, read_dir, remove_dir_all, write, DirBuilder, OpenOptions};
+use std::fs::{read, read_dir, remove_dir_all, remove_file, write, DirBuilder, OpenOptions};
 use std::io::{BufReader, Error, Read, Write};
 use std::path::Path;
 
@@ -6,29 +6,33 @@ use log::{debug, error, info};
 use serde_json::Value;
 
 use crate::connector::Connector;
+use crate::errors::ReplibyteError;
 use crate::types;
 use crate::utils::epoch_millis;
 
-use super::{compress, decompress, decrypt, encrypt, Datastore, Dump, IndexFile, INDEX_FILE_NAME};
+use super::{
+    chunk_key, dump_part_number, Compression, Datastore, Dump, EncodedPart, EncryptionKeys,
+    IndexFile, CHUNKS_DIR, INDEX_FILE_NAME,
+};
 
 pub struct LocalDisk {
     dir: String,
     dump_name: String,
-    enable_compression: bool,
-    encryption_key: Option<String>,
+    compression: Option<Compression>,
+    encryption_keys: EncryptionKeys,
 }
 
 impl LocalDisk {
     pub fn new<S: Into<String>>(dir: S) -> Self {
         Self {
             dir: dir.into(),
-            enable_compression: true,
-            encryption_key: None,
+            compression: Some(Compression::default()),
+            encryption_keys: EncryptionKeys::default(),
             dump_name: format!("dump-{}", epoch_millis()),
         }
     }
 
-    fn create_index_file(&self) -> Result<IndexFile, Error> {
+    fn create_index_file(&self) -> Result<IndexFile, ReplibyteError> {
         match self.index_file() {
             Ok(index_file) => Ok(index_file),
             Err(_) => {
@@ -42,14 +46,24 @@ impl LocalDisk {
 }
 
 impl Connector for LocalDisk {
-    fn init(&mut self) -> Result<(), Error> {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
         debug!("initializing datastore");
+
+        // create the datastore directory if needed
+        DirBuilder::new()
+            .recursive(true)
+            .create(&self.dir)
+            .map_err(|err| {
+                error!("error while creating the datastore directory: {}", self.dir);
+                err
+            })?;
+
         self.create_index_file().map(|_| ())
     }
 }
 
 impl Datastore for LocalDisk {
-    fn index_file(&self) -> Result<IndexFile, Error> {
+    fn index_file(&self) -> Result<IndexFile, ReplibyteError> {
         debug!("reading index_file at: {}", &self.dir);
 
         let file = OpenOptions::new()
@@ -59,12 +73,12 @@ impl Datastore for LocalDisk {
         let reader = BufReader::new(file);
 
         let index_file: IndexFile =
-            serde_json::from_reader(reader).map_err(|err| Error::from(err))?;
+            serde_json::from_reader(reader).map_err(|err| ReplibyteError::from(err))?;
 
         Ok(index_file)
     }
 
-    fn raw_index_file(&self) -> Result<Value, Error> {
+    fn raw_index_file(&self) -> Result<Value, ReplibyteError> {
         info!("reading raw index_file at: {}", &self.dir);
 
         let file = OpenOptions::new()
@@ -73,12 +87,13 @@ impl Datastore for LocalDisk {
 
         let reader = BufReader::new(file);
 
-        let raw_index_file = serde_json::from_reader(reader).map_err(|err| Error::from(err))?;
+        let raw_index_file =
+            serde_json::from_reader(reader).map_err(|err| ReplibyteError::from(err))?;
 
         Ok(raw_index_file)
     }
 
-    fn write_index_file(&self, index_file: &IndexFile) -> Result<(), Error> {
+    fn write_index_file(&self, index_file: &IndexFile) -> Result<(), ReplibyteError> {
         info!("writing index_file");
         let index_file_path = format!("{}/{}", self.dir, INDEX_FILE_NAME);
 
@@ -90,10 +105,10 @@ impl Datastore for LocalDisk {
             .open(&index_file_path)?;
 
         debug!("writing index_file at {}", index_file_path.as_str());
-        serde_json::to_writer(file, index_file).map_err(|err| Error::from(err))
+        serde_json::to_writer(file, index_file).map_err(|err| ReplibyteError::from(err))
     }
 
-    fn write_raw_index_file(&self, raw_index_file: &Value) -> Result<(), Error> {
+    fn write_raw_index_file(&self, raw_index_file: &Value) -> Result<(), ReplibyteError> {
         info!("writing raw index_file");
         let index_file_path = format!("{}/{}", self.dir, INDEX_FILE_NAME);
 
@@ -105,22 +120,11 @@ impl Datastore for LocalDisk {
             .open(&index_file_path)?;
 
         debug!("writing raw index_file at {}", index_file_path.as_str());
-        serde_json::to_writer(file, raw_index_file).map_err(|err| Error::from(err))
+        serde_json::to_writer(file, raw_index_file).map_err(|err| ReplibyteError::from(err))
     }
 
-    fn write(&self, file_part: u16, data: types::Bytes) -> Result<(), Error> {
-        // compress data?
-        let data = if self.compression_enabled() {
-            compress(data)?
-        } else {
-            data
-        };
-
-        // encrypt data?
-        let data = match self.encryption_key() {
-            Some(key) => encrypt(data, key.as_str())?,
-            None => data,
-        };
+    fn write_encoded(&self, file_part: u16, part: EncodedPart) -> Result<(), ReplibyteError> {
+        let EncodedPart { data, checksum } = part;
 
         let data_size = data.len();
         let dump_dir_path = format!("{}/{}", self.dir, self.dump_name);
@@ -148,8 +152,25 @@ impl Datastore for LocalDisk {
             directory_name: self.dump_name.to_string(),
             size: 0,
             created_at: epoch_millis(),
-            compressed: self.compression_enabled(),
-            encrypted: self.encryption_key().is_some(),
+            compressed: self.compression().is_some(),
+            compression: self.compression().map(|compression| compression.algorithm),
+            encrypted: self.encryption_keys().current().is_some(),
+            encryption_key_id: self
+                .encryption_keys()
+                .current()
+                .and_then(|key| key.id.clone()),
+            encrypted_data_key: self
+                .encryption_keys()
+                .current()
+                .and_then(|key| key.encrypted_data_key.clone()),
+            chunks: None,
+            parts: None,
+            started_at: None,
+            base: None,
+            uncompressed_size: None,
+            source_type: None,
+            metadata: None,
+            checksums: None,
         };
 
         // find or create Dump
@@ -159,6 +180,8 @@ impl Datastore for LocalDisk {
             .find(|b| b.directory_name.as_str() == self.dump_name)
             .unwrap_or(&mut new_dump);
 
+        dump.checksums.get_or_insert_with(Vec::new).push(checksum);
+
         if dump.size == 0 {
             // it means it's a new dump.
             // We need to add it into the index_file.dumps
@@ -173,80 +196,89 @@ impl Datastore for LocalDisk {
         self.write_index_file(&index_file)
     }
 
-    fn read(
+    fn read_encoded(
         &self,
-        options: &super::ReadOptions,
-        data_callback: &mut dyn FnMut(types::Bytes),
-    ) -> Result<(), Error> {
-        let mut index_file = self.index_file()?;
-        let dump = index_file.find_dump(options)?;
-        let entries = read_dir(format!("{}/{}", self.dir, dump.directory_name))?;
-
-        for entry in entries {
-            let entry = entry?;
-            let data = read(entry.path())?;
-
-            // decrypt data?
-            let data = if dump.encrypted {
-                // It should be safe to unwrap here because the dump is marked as encrypted in the dump manifest
-                // so if there is no encryption key set at the datastore level we want to panic.
-                let encryption_key = self.encryption_key.as_ref().unwrap();
-                decrypt(data, encryption_key.as_str())?
-            } else {
-                data
-            };
-
-            // decompress data?
-            let data = if dump.compressed {
-                decompress(data)?
-            } else {
-                data
-            };
-
-            data_callback(data);
+        dump: &Dump,
+        data_callback: &mut dyn FnMut(types::Bytes) -> Result<(), ReplibyteError>,
+    ) -> Result<(), ReplibyteError> {
+        let mut paths = read_dir(format!("{}/{}", self.dir, dump.directory_name))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        // the parts are read in order, `10.dump` comes after `9.dump`
+        paths.sort_by_key(|path| dump_part_number(path.to_string_lossy().as_ref()));
+
+        for path in paths {
+            let _ = data_callback(read(path)?)?;
         }
 
         Ok(())
     }
 
-    fn compression_enabled(&self) -> bool {
-        self.enable_compression
+    fn compression(&self) -> Option<Compression> {
+        self.compression
     }
 
-    fn set_compression(&mut self, enable: bool) {
-        if !enable {
+    fn set_compression(&mut self, compression: Option<Compression>) {
+        if compression.is_none() {
             info!("disable datastore compression");
         }
 
-        self.enable_compression = enable;
+        self.compression = compression;
     }
 
-    fn encryption_key(&self) -> &Option<String> {
-        &self.encryption_key
+    fn encryption_keys(&self) -> &EncryptionKeys {
+        &self.encryption_keys
     }
 
-    fn set_encryption_key(&mut self, key: String) {
-        info!("set datastore encryption_key");
-        self.encryption_key = Some(key)
+    fn set_encryption_keys(&mut self, keys: EncryptionKeys) {
+        info!("set datastore encryption_keys");
+        self.encryption_keys = keys;
     }
 
     fn set_dump_name(&mut self, name: String) {
         self.dump_name = name
     }
 
-    fn delete_by_name(&self, name: String) -> Result<(), Error> {
-        let mut index_file = self.index_file()?;
-
+    fn delete_dump_files(&self, name: &str) -> Result<(), ReplibyteError> {
         let dump_dir_path = format!("{}/{}", self.dir, name);
         remove_dir_all(&dump_dir_path).map_err(|err| {
             error!("error while removing the dump directory: {}", dump_dir_path);
             err
         })?;
 
-        // update the index_file.
-        index_file.dumps.retain(|b| b.directory_name != name);
+        Ok(())
+    }
 
-        self.write_index_file(&index_file)
+    fn write_chunk(&self, hash: &str, data: types::Bytes) -> Result<(), ReplibyteError> {
+        let chunks_dir_path = format!("{}/{}", self.dir, CHUNKS_DIR);
+        let chunk_path = format!("{}/{}", self.dir, chunk_key(hash));
+
+        // create the chunks directory if needed
+        DirBuilder::new()
+            .recursive(true)
+            .create(&chunks_dir_path)
+            .map_err(|err| {
+                error!(
+                    "error while creating the chunks directory: {}",
+                    chunks_dir_path
+                );
+                err
+            })?;
+
+        debug!("writing chunk at: {}", chunk_path);
+        write(&chunk_path, data).map_err(|err| {
+            error!("error while writing chunk at: {}", chunk_path);
+            ReplibyteError::from(err)
+        })
+    }
+
+    fn read_chunk(&self, hash: &str) -> Result<types::Bytes, ReplibyteError> {
+        Ok(read(format!("{}/{}", self.dir, chunk_key(hash)))?)
+    }
+
+    fn delete_chunk(&self, hash: &str) -> Result<(), ReplibyteError> {
+        Ok(remove_file(format!("{}/{}", self.dir, chunk_key(hash)))?)
     }
 }
 
@@ -254,6 +286,7 @@ impl Datastore for LocalDisk {
 mod tests {
     use std::{fs::OpenOptions};
     use std::path::Path;
+    use std::sync::Arc;
 
     use chrono::{Duration, Utc};
     use serde_json::json;
@@ -262,7 +295,8 @@ mod tests {
     use crate::{
         cli::DumpDeleteArgs,
         connector::Connector,
-        datastore::{Datastore, Dump, ReadOptions, INDEX_FILE_NAME},
+        datastore::{Datastore, Dump, EncryptionKey, EncryptionKeys, ReadOptions, INDEX_FILE_NAME},
+        kms::tests::LocalKms,
         migration::{
             rename_backups_to_dumps::RenameBackupsToDump,
             update_version_number::UpdateVersionNumber, Migrator,
@@ -330,6 +364,166 @@ mod tests {
         assert_eq!(dump_content, b"hello world".to_vec())
     }
 
+    #[test]
+    fn test_write_and_read_parts_in_order() {
+        let dir = tempdir().expect("cannot create tempdir");
+        // the datastore directory is created if needed
+        let mut local_disk = LocalDisk::new(format!("{}/replibyte", dir.path().to_str().unwrap()));
+        let _ = local_disk.init().expect("local_disk init failed");
+        local_disk.set_encryption_keys("secret".to_string().into());
+
+        for file_part in 1..=12 {
+            assert!(local_disk
+                .write(file_part, format!("{};", file_part).into_bytes())
+                .is_ok());
+        }
+
+        let mut dump_content: Vec<u8> = vec![];
+        assert!(local_disk
+            .read(&ReadOptions::Latest, &mut |mut bytes| {
+                dump_content.append(&mut bytes);
+            })
+            .is_ok());
+        assert_eq!(
+            String::from_utf8(dump_content).unwrap(),
+            "1;2;3;4;5;6;7;8;9;10;11;12;"
+        );
+    }
+
+    #[test]
+    fn test_key_rotation_and_rewrap() {
+        let dir = tempdir().expect("cannot create tempdir");
+        let keys = |ids: &[&str]| {
+            EncryptionKeys::new(
+                ids.iter()
+                    .map(|id| EncryptionKey {
+                        id: Some(id.to_string()),
+                        key: format!("secret {}", id).into_bytes(),
+                        encrypted_data_key: None,
+                    })
+                    .collect(),
+            )
+        };
+
+        let mut local_disk = LocalDisk::new(dir.path().to_str().unwrap().to_string());
+        let _ = local_disk.init().expect("local_disk init failed");
+        local_disk.set_encryption_keys(keys(&["key-1"]));
+        local_disk.set_dump_name("dump-1".to_string());
+
+        for file_part in 1..=12 {
+            assert!(local_disk
+                .write(file_part, format!("{};", file_part).into_bytes())
+                .is_ok());
+        }
+
+        let read_dump = |local_disk: &LocalDisk| {
+            let mut dump_content: Vec<u8> = vec![];
+            local_disk
+                .read(&ReadOptions::Latest, &mut |mut bytes| {
+                    dump_content.append(&mut bytes);
+                })
+                .map(|_| String::from_utf8(dump_content).unwrap())
+        };
+
+        // the dumps encrypted with the previous key are still readable after a rotation
+        local_disk.set_encryption_keys(keys(&["key-1", "key-2"]));
+        assert_eq!(
+            read_dump(&local_disk).unwrap(),
+            "1;2;3;4;5;6;7;8;9;10;11;12;"
+        );
+
+        let size = local_disk.index_file().unwrap().dumps[0].size;
+        assert!(local_disk.rewrap("dump-1").is_ok());
+
+        let index_file = local_disk.index_file().unwrap();
+        assert_eq!(index_file.dumps.len(), 1);
+        assert_eq!(index_file.dumps[0].size, size);
+        assert_eq!(
+            index_file.dumps[0].encryption_key_id,
+            Some("key-2".to_string())
+        );
+
+        // the previous key is not needed anymore
+        local_disk.set_encryption_keys(keys(&["key-2"]));
+        assert_eq!(
+            read_dump(&local_disk).unwrap(),
+            "1;2;3;4;5;6;7;8;9;10;11;12;"
+        );
+
+        local_disk.set_encryption_keys(keys(&["key-1"]));
+        assert!(read_dump(&local_disk).is_err());
+    }
+
+    #[test]
+    fn test_envelope_encryption_and_rewrap() {
+        let dir = tempdir().expect("cannot create tempdir");
+        let kms_keys = |key_id: &str| {
+            let mut encryption_keys = EncryptionKeys::new(vec![EncryptionKey {
+                id: Some("key-1".to_string()),
+                key: b"secret key-1".to_vec(),
+                encrypted_data_key: None,
+            }])
+            .with_kms(Arc::new(LocalKms {
+                key_id: key_id.to_string(),
+            }));
+            let _ = encryption_keys.generate_data_key().unwrap();
+            encryption_keys
+        };
+
+        let mut local_disk = LocalDisk::new(dir.path().to_str().unwrap().to_string());
+        let _ = local_disk.init().expect("local_disk init failed");
+        local_disk.set_encryption_keys("secret key-1".to_string().into());
+        local_disk.set_dump_name("dump-1".to_string());
+
+        for file_part in 1..=3 {
+            assert!(local_disk
+                .write(file_part, format!("{};", file_part).into_bytes())
+                .is_ok());
+        }
+
+        let read_dump = |local_disk: &LocalDisk| {
+            let mut dump_content: Vec<u8> = vec![];
+            local_disk
+                .read(&ReadOptions::Latest, &mut |mut bytes| {
+                    dump_content.append(&mut bytes);
+                })
+                .map(|_| String::from_utf8(dump_content).unwrap())
+        };
+
+        // the parts are re-encrypted with a data key generated by the KMS
+        local_disk.set_encryption_keys(kms_keys("kms-1"));
+        assert!(local_disk.rewrap("dump-1").is_ok());
+
+        let dump = local_disk.index_file().unwrap().dumps.remove(0);
+        assert_eq!(dump.encryption_key_id, Some("kms-1".to_string()));
+        assert_eq!(dump.encrypted_data_key.unwrap().key_id, "kms-1");
+
+        local_disk.set_encryption_keys(EncryptionKeys::default().with_kms(Arc::new(LocalKms {
+            key_id: "kms-1".to_string(),
+        })));
+        assert_eq!(read_dump(&local_disk).unwrap(), "1;2;3;");
+
+        // only the data key is re-encrypted with the new KMS key, the parts are left untouched
+        let part_path = dir.path().join("dump-1").join("1.dump");
+        let part = std::fs::read(&part_path).unwrap();
+
+        local_disk.set_encryption_keys(kms_keys("kms-2"));
+        assert!(local_disk.rewrap("dump-1").is_ok());
+        assert_eq!(std::fs::read(&part_path).unwrap(), part);
+
+        let dump = local_disk.index_file().unwrap().dumps.remove(0);
+        assert_eq!(dump.encryption_key_id, Some("kms-2".to_string()));
+        assert_eq!(dump.encrypted_data_key.unwrap().key_id, "kms-2");
+
+        local_disk.set_encryption_keys(EncryptionKeys::default().with_kms(Arc::new(LocalKms {
+            key_id: "kms-2".to_string(),
+        })));
+        assert_eq!(read_dump(&local_disk).unwrap(), "1;2;3;");
+
+        local_disk.set_encryption_keys("secret key-1".to_string().into());
+        assert!(read_dump(&local_disk).is_err());
+    }
+
     #[test]
     fn test_index_file() {
         let dir = tempdir().expect("cannot create tempdir");
@@ -347,7 +541,18 @@ mod tests {
             size: 0,
             created_at: epoch_millis(),
             compressed: true,
+            compression: None,
             encrypted: false,
+            encryption_key_id: None,
+            encrypted_data_key: None,
+            chunks: None,
+            parts: None,
+            started_at: None,
+            base: None,
+            uncompressed_size: None,
+            source_type: None,
+            metadata: None,
+            checksums: None,
         });
 
         assert!(local_disk.write_index_file(&index_file).is_ok());
@@ -395,7 +600,8 @@ mod tests {
             .delete(&DumpDeleteArgs {
                 dump: Some("dump-1".to_string()),
                 older_than: None,
-                keep_last: None
+                keep_last: None,
+                force: false,
             })
             .is_ok());
         assert_eq!(local_disk.index_file().unwrap().dumps.len(), 1);
@@ -406,7 +612,8 @@ mod tests {
             .delete(&DumpDeleteArgs {
                 dump: Some("dump-2".to_string()),
                 older_than: None,
-                keep_last: None
+                keep_last: None,
+                force: false,
             })
             .is_ok());
         assert_eq!(local_disk.index_file().unwrap().dumps.len(), 0);
@@ -453,6 +660,7 @@ mod tests {
                 dump: None,
                 older_than: None,
                 keep_last: Some(2),
+                force: false,
             })
             .is_ok());
         assert_eq!(local_disk.index_file().unwrap().dumps.len(), 2);
@@ -466,6 +674,7 @@ mod tests {
                 dump: None,
                 older_than: None,
                 keep_last: Some(1),
+                force: false,
             })
             .is_ok());
         assert_eq!(local_disk.index_file().unwrap().dumps.len(), 1);
@@ -475,6 +684,56 @@ mod tests {
         assert!(Path::new(&format!("{}/dump-3", dir.path().to_str().unwrap())).exists());
     }
 
+    #[test]
+    fn test_delete_dumps() {
+        let dir = tempdir().expect("cannot create tempdir");
+        let mut local_disk = LocalDisk::new(dir.path().to_str().unwrap().to_string());
+        let _ = local_disk.init().expect("local_disk init failed");
+
+        for name in ["dump-1", "dump-2", "dump-3"] {
+            local_disk.set_dump_name(name.to_string());
+            assert!(local_disk.write(1, b"hello world".to_vec()).is_ok());
+        }
+
+        assert!(local_disk
+            .delete_dumps(&["dump-1".to_string(), "dump-3".to_string()])
+            .is_ok());
+
+        let index_file = local_disk.index_file().unwrap();
+        assert_eq!(index_file.dumps.len(), 1);
+        assert_eq!(index_file.dumps[0].directory_name, "dump-2");
+        assert!(!Path::new(&format!("{}/dump-1", dir.path().to_str().unwrap())).exists());
+        assert!(Path::new(&format!("{}/dump-2", dir.path().to_str().unwrap())).exists());
+        assert!(!Path::new(&format!("{}/dump-3", dir.path().to_str().unwrap())).exists());
+    }
+
+    #[test]
+    fn test_verify() {
+        let dir = tempdir().expect("cannot create tempdir");
+        let mut local_disk = LocalDisk::new(dir.path().to_str().unwrap().to_string());
+        let _ = local_disk.init().expect("local_disk init failed");
+
+        local_disk.set_dump_name("dump-1".to_string());
+        assert!(local_disk.write(1, b"hello world".to_vec()).is_ok());
+        assert!(local_disk.write(2, b"hello again".to_vec()).is_ok());
+        assert_eq!(local_disk.verify("dump-1").unwrap(), 2);
+
+        // the rewritten parts have the same checksums
+        local_disk.set_encryption_keys(EncryptionKeys::from("secret".to_string()));
+        assert!(local_disk.rewrap("dump-1").is_ok());
+        assert_eq!(local_disk.verify("dump-1").unwrap(), 2);
+
+        let part = format!("{}/dump-1/2.dump", dir.path().to_str().unwrap());
+        let mut data = std::fs::read(part.as_str()).unwrap();
+        let last = data.len() - 1;
+        data[last] ^= 1;
+        std::fs::write(part.as_str(), data).unwrap();
+        assert!(local_disk.verify("dump-1").is_err());
+
+        let _ = std::fs::remove_file(part.as_str()).unwrap();
+        assert!(local_disk.verify("dump-1").is_err());
+    }
+
     #[test]
     fn test_delete_older_than() {
         let dir = tempdir().expect("cannot create tempdir");
@@ -515,6 +774,7 @@ mod tests {
                 dump: None,
                 older_than: Some("6d".to_string()),
                 keep_last: None,
+                force: false,
             })
             .is_ok());
         assert_eq!(local_disk.index_file().unwrap().dumps.len(), 3);
@@ -528,6 +788,7 @@ mod tests {
                 dump: None,
                 older_than: Some("4d".to_string()),
                 keep_last: None,
+                force: false,
             })
             .is_ok());
         assert_eq!(local_disk.index_file().unwrap().dumps.len(), 2);
@@ -541,6 +802,7 @@ mod tests {
                 dump: None,
                 older_than: Some("1d".to_string()),
                 keep_last: None,
+                force: false,
             })
             .is_ok());
         assert_eq!(local_disk.index_file().unwrap().dumps.len(), 1);
@@ -554,6 +816,7 @@ mod tests {
                 dump: None,
                 older_than: Some("0d".to_string()),
                 keep_last: None,
+                force: false,
             })
             .is_ok());
         assert_eq!(local_disk.index_file().unwrap().dumps.len(), 0);
@@ -626,7 +889,18 @@ mod tests {
                 size: 62279,
                 created_at: 1234,
                 compressed: true,
-                encrypted: false
+                compression: None,
+                encrypted: false,
+                encryption_key_id: None,
+                encrypted_data_key: None,
+                chunks: None,
+                parts: None,
+                started_at: None,
+                base: None,
+                uncompressed_size: None,
+                source_type: None,
+                metadata: None,
+                checksums: None,
             })
         );
         assert_eq!(
@@ -636,7 +910,18 @@ mod tests {
                 size: 62283,
                 created_at: 5678,
                 compressed: true,
-                encrypted: false
+                compression: None,
+                encrypted: false,
+                encryption_key_id: None,
+                encrypted_data_key: None,
+                chunks: None,
+                parts: None,
+                started_at: None,
+                base: None,
+                uncompressed_size: None,
+                source_type: None,
+                metadata: None,
+                checksums: None,
             })
         );
     }
@@ -2,43 +2,264 @@ use aes_gcm::aead::{Aead, NewAead};
 use aes_gcm::{Aes256Gcm, Key, Nonce};
 use chrono::{Duration, Utc};
 use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::{Error, ErrorKind, Read, Write};
+use std::sync::{Arc, Mutex};
+use std::thread;
 
-use flate2::read::ZlibDecoder;
-use flate2::write::ZlibEncoder;
-use flate2::Compression;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flate2::write::{GzEncoder, ZlibEncoder};
+use ring::digest::{digest, SHA256};
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
 
 use crate::cli::DumpDeleteArgs;
+use crate::config::RetentionConfig;
 use crate::connector::Connector;
+use crate::errors::ReplibyteError;
+use crate::kms::Kms;
+use crate::runtime;
 use crate::types::Bytes;
 use crate::utils::get_replibyte_version;
 
+pub mod azure;
+pub mod dedup;
+pub mod gcs;
+pub mod http;
 pub mod local_disk;
+pub mod replicated;
 pub mod s3;
+pub mod sftp;
+pub mod throttled;
 
 const INDEX_FILE_NAME: &str = "metadata.json";
+/// directory of the chunks of the deduplicated dumps
+const CHUNKS_DIR: &str = "chunks";
+/// number of parts decrypted and decompressed concurrently, while the next ones are downloaded
+const DECODERS: usize = 2;
+pub const MILLIS_PER_DAY: u128 = 24 * 60 * 60 * 1000;
 
 pub trait Datastore: Connector + Send + Sync {
     /// Getting Index file with all the dumps information
-    fn index_file(&self) -> Result<IndexFile, Error>;
-    fn raw_index_file(&self) -> Result<Value, Error>;
-    fn write_index_file(&self, index_file: &IndexFile) -> Result<(), Error>;
-    fn write_raw_index_file(&self, raw_index_file: &Value) -> Result<(), Error>;
-    fn write(&self, file_part: u16, data: Bytes) -> Result<(), Error>;
+    fn index_file(&self) -> Result<IndexFile, ReplibyteError>;
+    fn raw_index_file(&self) -> Result<Value, ReplibyteError>;
+    fn write_index_file(&self, index_file: &IndexFile) -> Result<(), ReplibyteError>;
+    fn write_raw_index_file(&self, raw_index_file: &Value) -> Result<(), ReplibyteError>;
+    /// return the encoder of the parts of the dump, it is owned so that several parts can be encoded concurrently
+    fn encoder(&self) -> Encoder {
+        Encoder::new(
+            self.compression(),
+            self.encryption_keys().current().cloned(),
+        )
+    }
+    /// write a part encoded by the `encoder`, the parts are written in order
+    fn write_encoded(&self, file_part: u16, part: EncodedPart) -> Result<(), ReplibyteError>;
+    fn write(&self, file_part: u16, data: Bytes) -> Result<(), ReplibyteError> {
+        self.write_encoded(file_part, self.encoder().encode(data)?)
+    }
+    /// read the parts of a dump as they are stored, compressed and encrypted, in order
+    fn read_encoded(
+        &self,
+        dump: &Dump,
+        data_callback: &mut dyn FnMut(Bytes) -> Result<(), ReplibyteError>,
+    ) -> Result<(), ReplibyteError>;
+    /// read the parts of a dump, the next parts are downloaded while the previous ones are decrypted and decompressed
     fn read(
         &self,
         options: &ReadOptions,
         data_callback: &mut dyn FnMut(Bytes),
-    ) -> Result<(), Error>;
-    fn compression_enabled(&self) -> bool;
-    fn set_compression(&mut self, enable: bool);
-    fn encryption_key(&self) -> &Option<String>;
-    fn set_encryption_key(&mut self, key: String);
+    ) -> Result<(), ReplibyteError> {
+        let dump = self.index_file()?.find_dump(options)?.clone();
+        let encryption_keys = self.encryption_keys().clone();
+        let encoded_dump = dump.clone();
+
+        read_concurrently(
+            |encoded_data_callback| self.read_encoded(&encoded_dump, encoded_data_callback),
+            move |data| decode(data, &dump, &encryption_keys),
+            data_callback,
+        )
+    }
+    /// read a dump, an incremental dump is read after the dumps it is based on
+    fn read_with_bases(
+        &self,
+        options: &ReadOptions,
+        data_callback: &mut dyn FnMut(Bytes),
+    ) -> Result<(), ReplibyteError> {
+        for name in self.index_file()?.dump_chain(options)? {
+            let _ = self.read(&ReadOptions::Dump { name }, data_callback)?;
+        }
+
+        Ok(())
+    }
+
+    fn compression(&self) -> Option<Compression>;
+    fn set_compression(&mut self, compression: Option<Compression>);
+    fn encryption_keys(&self) -> &EncryptionKeys;
+    fn set_encryption_keys(&mut self, keys: EncryptionKeys);
     fn set_dump_name(&mut self, name: String);
-    fn delete_by_name(&self, name: String) -> Result<(), Error>;
+    /// delete the parts of a dump, the index file is not updated
+    fn delete_dump_files(&self, name: &str) -> Result<(), ReplibyteError>;
+    fn delete_by_name(&self, name: String) -> Result<(), ReplibyteError> {
+        let mut index_file = self.index_file()?;
+
+        let _ = self.delete_dump_files(name.as_str())?;
+
+        index_file.dumps.retain(|b| b.directory_name != name);
+
+        self.write_index_file(&index_file)
+    }
+    /// delete several dumps with a single write of the index file,
+    /// their parts are deleted once the index file does not reference them anymore
+    fn delete_dumps(&self, names: &[String]) -> Result<(), ReplibyteError> {
+        let mut index_file = self.index_file()?;
+        index_file
+            .dumps
+            .retain(|dump| !names.contains(&dump.directory_name));
+        let _ = self.write_index_file(&index_file)?;
+
+        for name in names {
+            let _ = self.delete_dump_files(name.as_str())?;
+        }
+
+        Ok(())
+    }
+    /// read a dump and compare its parts with their checksums, return the number of verified parts
+    fn verify(&self, name: &str) -> Result<usize, ReplibyteError> {
+        let options = ReadOptions::Dump {
+            name: name.to_string(),
+        };
+
+        let checksums = match &self.index_file()?.find_dump(&options)?.checksums {
+            Some(checksums) => checksums.clone(),
+            None => {
+                return Err(ReplibyteError::Datastore(format!(
+                "the dump '{}' has no checksums, it was created by an older version of Replibyte",
+                name
+            )))
+            }
+        };
+
+        let mut parts = 0;
+        let mut corrupted_parts = vec![];
+
+        let _ = self
+            .read(&options, &mut |data| {
+                if checksums.get(parts) != Some(&checksum(data.as_slice())) {
+                    corrupted_parts.push(parts + 1);
+                }
+
+                parts += 1;
+            })
+            .map_err(|err| {
+                ReplibyteError::Datastore(format!("the dump '{}' is corrupted: {}", name, err))
+            })?;
 
-    fn delete(&self, args: &DumpDeleteArgs) -> Result<(), Error> {
+        if !corrupted_parts.is_empty() {
+            return Err(ReplibyteError::Datastore(format!(
+                "the dump '{}' is corrupted: the parts {:?} do not match their checksums",
+                name, corrupted_parts
+            )));
+        }
+
+        if parts != checksums.len() {
+            return Err(ReplibyteError::Datastore(format!(
+                "the dump '{}' is corrupted: {} parts were read, {} were written",
+                name,
+                parts,
+                checksums.len()
+            )));
+        }
+
+        Ok(parts)
+    }
+    /// write a chunk of a deduplicated dump, already compressed and encrypted, it is shared by the dumps
+    fn write_chunk(&self, hash: &str, data: Bytes) -> Result<(), ReplibyteError>;
+    fn read_chunk(&self, hash: &str) -> Result<Bytes, ReplibyteError>;
+    fn delete_chunk(&self, hash: &str) -> Result<(), ReplibyteError>;
+
+    /// re-encrypt a dump with the current encryption key, its parts are rewritten in place
+    /// unless only its data key has to be re-encrypted with the current KMS key
+    fn rewrap(&mut self, name: &str) -> Result<(), ReplibyteError> {
+        let options = ReadOptions::Dump {
+            name: name.to_string(),
+        };
+
+        let current = self.encryption_keys().current().cloned();
+        let mut index_file = self.index_file()?;
+        let dump = index_file
+            .dumps
+            .iter_mut()
+            .find(|dump| dump.directory_name.as_str() == name)
+            .ok_or_else(|| {
+                ReplibyteError::Datastore(format!("Can't find dump with name '{}'", name))
+            })?;
+        let (size, checksums, compression) = (
+            dump.size,
+            dump.checksums.clone(),
+            dump.compression_algorithm()
+                .map(|algorithm| Compression::new(algorithm, None)),
+        );
+
+        match (dump.encrypted_data_key.take(), &current) {
+            (
+                Some(encrypted_data_key),
+                Some(EncryptionKey {
+                    encrypted_data_key: Some(_),
+                    id,
+                    ..
+                }),
+            ) => {
+                dump.encrypted_data_key = Some(
+                    self.encryption_keys()
+                        .rewrap_data_key(&encrypted_data_key)?,
+                );
+                dump.encryption_key_id = id.clone();
+                return self.write_index_file(&index_file);
+            }
+            (_, Some(key)) if key.encrypted_data_key.is_some() => {
+                // the new data key is saved before the parts are rewritten,
+                // the dump stays readable if the rewrap is interrupted
+                dump.encrypted_data_key = key.encrypted_data_key.clone();
+                let _ = self.write_index_file(&index_file)?;
+            }
+            _ => {}
+        }
+
+        self.set_dump_name(name.to_string());
+        self.set_compression(compression);
+
+        let datastore: &Self = self;
+        let mut file_part = 0u16;
+        let mut result = Ok(());
+
+        let _ = datastore.read(&options, &mut |data| {
+            if result.is_ok() {
+                file_part += 1;
+                result = datastore.write(file_part, data);
+            }
+        })?;
+
+        let _ = result?;
+
+        let mut index_file = self.index_file()?;
+        if let Some(dump) = index_file
+            .dumps
+            .iter_mut()
+            .find(|dump| dump.directory_name.as_str() == name)
+        {
+            // the size of the rewritten parts has been added to the previous one
+            dump.size -= size;
+            // the data of the parts is the same, only their encryption changed
+            dump.checksums = checksums;
+            dump.encrypted = current.is_some();
+            dump.encryption_key_id = current.as_ref().and_then(|key| key.id.clone());
+            dump.encrypted_data_key = current.and_then(|key| key.encrypted_data_key);
+        }
+
+        self.write_index_file(&index_file)
+    }
+
+    fn delete(&self, args: &DumpDeleteArgs) -> Result<(), ReplibyteError> {
         if let Some(dump_name) = &args.dump {
             return self.delete_by_name(dump_name.to_string());
         }
@@ -52,16 +273,13 @@ pub trait Datastore: Connector + Send + Sync {
 
                     match older_than.parse::<i64>() {
                         Ok(days) => days,
-                        Err(err) => return Err(Error::new(
-                            ErrorKind::Other,
-                            format!("command error: {} - invalid `--older-than` format. Use `--older-than=14d`", err),
-                        )),
+                        Err(err) => return Err(ReplibyteError::Config(format!("command error: {} - invalid `--older-than` format. Use `--older-than=14d`", err))),
                     }
                 }
                 _ => {
-                    return Err(Error::new(
-                        ErrorKind::Other,
-                        "command error: invalid `--older-than` format. Use `--older-than=14d`",
+                    return Err(ReplibyteError::Config(
+                        "command error: invalid `--older-than` format. Use `--older-than=14d`"
+                            .to_string(),
                     ));
                 }
             };
@@ -73,13 +291,12 @@ pub trait Datastore: Connector + Send + Sync {
             return self.delete_keep_last(keep_last);
         }
 
-        Err(Error::new(
-            ErrorKind::Other,
-            "command error: parameters or options required",
+        Err(ReplibyteError::Config(
+            "command error: parameters or options required".to_string(),
         ))
     }
 
-    fn delete_older_than(&self, days: i64) -> Result<(), Error> {
+    fn delete_older_than(&self, days: i64) -> Result<(), ReplibyteError> {
         let index_file = self.index_file()?;
 
         let threshold_date = Utc::now() - Duration::days(days);
@@ -99,7 +316,7 @@ pub trait Datastore: Connector + Send + Sync {
         Ok(())
     }
 
-    fn delete_keep_last(&self, keep_last: usize) -> Result<(), Error> {
+    fn delete_keep_last(&self, keep_last: usize) -> Result<(), ReplibyteError> {
         let mut index_file = self.index_file()?;
 
         index_file
@@ -131,14 +348,16 @@ impl IndexFile {
         }
     }
 
-    pub fn find_dump(&mut self, options: &ReadOptions) -> Result<&Dump, Error> {
+    pub fn find_dump(&mut self, options: &ReadOptions) -> Result<&Dump, ReplibyteError> {
         match options {
             ReadOptions::Latest => {
                 self.dumps.sort_by(|a, b| a.created_at.cmp(&b.created_at));
 
                 match self.dumps.last() {
                     Some(dump) => Ok(dump),
-                    None => return Err(Error::new(ErrorKind::Other, "No dumps available.")),
+                    None => {
+                        return Err(ReplibyteError::Datastore("No dumps available.".to_string()))
+                    }
                 }
             }
             ReadOptions::Dump { name } => {
@@ -149,24 +368,411 @@ impl IndexFile {
                 {
                     Some(dump) => Ok(dump),
                     None => {
-                        return Err(Error::new(
-                            ErrorKind::Other,
-                            format!("Can't find dump with name '{}'", name),
-                        ));
+                        return Err(ReplibyteError::Datastore(format!(
+                            "Can't find dump with name '{}'",
+                            name
+                        )));
                     }
                 }
             }
         }
     }
+
+    /// return the names of the dumps to read in order to restore a dump,
+    /// starting with the full dump an incremental dump is based on
+    pub fn dump_chain(&mut self, options: &ReadOptions) -> Result<Vec<String>, ReplibyteError> {
+        let dump = self.find_dump(options)?;
+        let mut names = vec![dump.directory_name.clone()];
+        let mut base = dump.base.clone();
+
+        while let Some(name) = base {
+            let dump = self
+                .dumps
+                .iter()
+                .find(|dump| dump.directory_name == name)
+                .filter(|_| !names.contains(&name))
+                .ok_or_else(|| {
+                    ReplibyteError::Datastore(format!(
+                        "the dump '{}' is based on the dump '{}', which is missing",
+                        names.last().unwrap(),
+                        name
+                    ))
+                })?;
+
+            base = dump.base.clone();
+            names.push(name);
+        }
+
+        names.reverse();
+        Ok(names)
+    }
+
+    /// names of the dumps not kept by the retention policy, the dumps which the kept ones are based on are kept too
+    pub fn dumps_to_prune(&mut self, retention: &RetentionConfig, now: u128) -> Vec<String> {
+        self.dumps.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+        let threshold = retention
+            .keep_days
+            .map(|days| now.saturating_sub(days as u128 * MILLIS_PER_DAY));
+
+        let kept_dumps = self
+            .dumps
+            .iter()
+            .enumerate()
+            .filter(|(idx, dump)| {
+                matches!(retention.keep_last, Some(keep_last) if *idx < keep_last)
+                    || matches!(threshold, Some(threshold) if dump.created_at >= threshold)
+            })
+            .map(|(_, dump)| dump.directory_name.clone())
+            .collect::<Vec<_>>();
+
+        let mut kept = HashSet::new();
+        for name in kept_dumps {
+            let options = ReadOptions::Dump { name: name.clone() };
+
+            match self.dump_chain(&options) {
+                Ok(chain) => kept.extend(chain),
+                // a base of the dump is missing, the dumps of the chain are not known
+                Err(_) => {
+                    let _ = kept.insert(name);
+                }
+            }
+        }
+
+        self.dumps
+            .iter()
+            .filter(|dump| !kept.contains(&dump.directory_name))
+            .map(|dump| dump.directory_name.clone())
+            .collect()
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Ord, PartialOrd, Eq, PartialEq, Clone)]
 pub struct Dump {
     pub directory_name: String,
     pub size: usize,
     pub created_at: u128,
     pub compressed: bool,
+    /// algorithm the dump is compressed with, the dumps without one are compressed with zlib
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<CompressionAlgorithm>,
     pub encrypted: bool,
+    /// id of the key the dump is encrypted with, the dumps encrypted with `encryption_key` have none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption_key_id: Option<String>,
+    /// data key of the dump encrypted with a KMS key (envelope encryption)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encrypted_data_key: Option<EncryptedDataKey>,
+    /// hashes of the chunks of a deduplicated dump, in order, its data is not stored in parts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunks: Option<Vec<String>>,
+    /// number of chunks of each part of a deduplicated dump, the chunks of a part are read together
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parts: Option<Vec<usize>>,
+    /// time the dump started, the rows updated after it are in the next incremental dump
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<u128>,
+    /// name of the dump an incremental dump is based on, it is restored first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    /// size of the dump before its compression and its encryption
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uncompressed_size: Option<usize>,
+    /// type of the database the dump comes from, e.g. `postgresql`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_type: Option<String>,
+    /// what the dump contains, it is inspected without reading the dump
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<DumpMetadata>,
+    /// SHA-256 of the parts before their compression and encryption, in order
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksums: Option<Vec<String>>,
+}
+
+impl Dump {
+    /// return the algorithm the dump is compressed with, if any
+    pub fn compression_algorithm(&self) -> Option<CompressionAlgorithm> {
+        if !self.compressed {
+            return None;
+        }
+
+        Some(self.compression.unwrap_or(CompressionAlgorithm::Zlib))
+    }
+}
+
+/// Tables, subset and transformers of a dump, recorded when it is created
+#[derive(Debug, Serialize, Deserialize, Ord, PartialOrd, Eq, PartialEq, Clone, Default)]
+pub struct DumpMetadata {
+    /// rows of each table, approximate since a row is an `INSERT INTO` query or a `COPY` row
+    pub tables: BTreeMap<String, usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subset: Option<DumpSubset>,
+    #[serde(default)]
+    pub transformers: Vec<DumpTransformer>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct DumpSubset {
+    /// `database.table` the subset starts from
+    pub table: String,
+    /// e.g. `random 10%`, `limit 100` or `where created_at > now() - interval '7 days'`
+    pub strategy: String,
+    #[serde(default)]
+    pub passthrough_tables: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct DumpTransformer {
+    /// `database.table.column`, `*.*.column` for the global transformations
+    pub column: String,
+    /// id of the transformer, e.g. `email`
+    pub transformer: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Ord, PartialOrd, Eq, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionAlgorithm {
+    Zlib,
+    Gzip,
+    Zstd,
+}
+
+/// how the dump parts are compressed, the level is the default one of the algorithm if not set
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Compression {
+    pub algorithm: CompressionAlgorithm,
+    pub level: Option<i32>,
+}
+
+impl Compression {
+    pub fn new(algorithm: CompressionAlgorithm, level: Option<i32>) -> Self {
+        Self { algorithm, level }
+    }
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Compression::new(CompressionAlgorithm::Zlib, None)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct EncryptedDataKey {
+    /// id of the KMS key the data key is encrypted with
+    pub key_id: String,
+    /// encrypted data key, base64 encoded
+    pub ciphertext: String,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct EncryptionKey {
+    pub id: Option<String>,
+    pub key: Bytes,
+    /// set for the data keys generated by a KMS
+    pub encrypted_data_key: Option<EncryptedDataKey>,
+}
+
+/// keys the dumps are encrypted with, the last one encrypts the new dumps
+/// and the others are kept to decrypt the dumps encrypted before a key rotation
+#[derive(Clone, Default)]
+pub struct EncryptionKeys {
+    keys: Vec<EncryptionKey>,
+    /// KMS the data keys are encrypted with, a data key is generated for each dump
+    kms: Option<Arc<dyn Kms>>,
+    /// data keys already decrypted by the KMS, by encrypted data key
+    data_keys: Arc<Mutex<HashMap<String, Bytes>>>,
+}
+
+impl EncryptionKeys {
+    pub fn new(keys: Vec<EncryptionKey>) -> Self {
+        Self {
+            keys,
+            kms: None,
+            data_keys: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn with_kms(mut self, kms: Arc<dyn Kms>) -> Self {
+        self.kms = Some(kms);
+        self
+    }
+
+    /// return the key the new dumps are encrypted with, if any
+    pub fn current(&self) -> Option<&EncryptionKey> {
+        self.keys.last()
+    }
+
+    /// return the id of the key the new dumps are encrypted with, the KMS key if one is used
+    pub fn current_key_id(&self) -> Option<&str> {
+        match &self.kms {
+            Some(kms) => Some(kms.key_id()),
+            None => self.current().and_then(|key| key.id.as_deref()),
+        }
+    }
+
+    /// generate the data key of a new dump with the KMS, it becomes the current key
+    pub fn generate_data_key(&mut self) -> Result<(), ReplibyteError> {
+        let kms = match &self.kms {
+            Some(kms) => kms,
+            None => return Ok(()),
+        };
+
+        let (data_key, encrypted_data_key) = kms.generate_data_key()?;
+        let encrypted_data_key = EncryptedDataKey {
+            key_id: kms.key_id().to_string(),
+            ciphertext: base64::encode(encrypted_data_key),
+        };
+
+        let _ = self
+            .data_keys
+            .lock()
+            .unwrap()
+            .insert(encrypted_data_key.ciphertext.clone(), data_key.clone());
+
+        self.keys.push(EncryptionKey {
+            id: Some(encrypted_data_key.key_id.clone()),
+            key: data_key,
+            encrypted_data_key: Some(encrypted_data_key),
+        });
+
+        Ok(())
+    }
+
+    /// decrypt a data key with the KMS, it is kept to not call the KMS for each dump part
+    fn data_key(&self, encrypted_data_key: &EncryptedDataKey) -> Result<Bytes, ReplibyteError> {
+        let mut data_keys = self.data_keys.lock().unwrap();
+        if let Some(data_key) = data_keys.get(&encrypted_data_key.ciphertext) {
+            return Ok(data_key.clone());
+        }
+
+        let kms = self.kms.as_ref().ok_or_else(|| {
+            ReplibyteError::Datastore(format!(
+                    "the dump data key is encrypted with the KMS key '{}', <encryption_kms> is mandatory",
+                    encrypted_data_key.key_id
+                ))
+        })?;
+
+        let ciphertext = base64::decode(encrypted_data_key.ciphertext.as_str())
+            .map_err(|err| ReplibyteError::Datastore(format!("invalid data key: {}", err)))?;
+        let data_key =
+            kms.decrypt_data_key(encrypted_data_key.key_id.as_str(), ciphertext.as_slice())?;
+
+        let _ = data_keys.insert(encrypted_data_key.ciphertext.clone(), data_key.clone());
+        Ok(data_key)
+    }
+
+    /// re-encrypt a data key with the current KMS key
+    pub fn rewrap_data_key(
+        &self,
+        encrypted_data_key: &EncryptedDataKey,
+    ) -> Result<EncryptedDataKey, ReplibyteError> {
+        let data_key = self.data_key(encrypted_data_key)?;
+        let kms = self.kms.as_ref().ok_or_else(|| {
+            ReplibyteError::Datastore("<encryption_kms> is mandatory".to_string())
+        })?;
+
+        let ciphertext = kms.encrypt_data_key(data_key.as_slice())?;
+        let encrypted_data_key = EncryptedDataKey {
+            key_id: kms.key_id().to_string(),
+            ciphertext: base64::encode(ciphertext),
+        };
+
+        let _ = self
+            .data_keys
+            .lock()
+            .unwrap()
+            .insert(encrypted_data_key.ciphertext.clone(), data_key);
+
+        Ok(encrypted_data_key)
+    }
+
+    /// decrypt the data of a dump, with its data key or its encryption key, the other keys are tried if it fails
+    /// which lets a dump partially re-encrypted by an interrupted `dump rewrap` be read
+    pub fn decrypt(&self, encrypted_data: Bytes, dump: &Dump) -> Result<Bytes, ReplibyteError> {
+        let key_id = dump.encryption_key_id.as_deref();
+        let mut last_error = ReplibyteError::Datastore(
+            match key_id {
+                Some(key_id) => format!("the encryption key '{}' is missing", key_id),
+                None => "the encryption key is missing".to_string(),
+            }
+            .to_string(),
+        );
+
+        if let Some(encrypted_data_key) = &dump.encrypted_data_key {
+            match self
+                .data_key(encrypted_data_key)
+                .and_then(|data_key| decrypt(encrypted_data.clone(), data_key.as_slice()))
+            {
+                Ok(data) => return Ok(data),
+                Err(err) => last_error = err,
+            }
+        }
+
+        let mut keys = self.keys.iter().collect::<Vec<_>>();
+        // the key of the dump is tried first, then the most recent ones
+        keys.reverse();
+        keys.sort_by_key(|key| key.id.as_deref() != key_id);
+
+        for key in keys {
+            match decrypt(encrypted_data.clone(), key.key.as_slice()) {
+                Ok(data) => return Ok(data),
+                Err(err) => last_error = err,
+            }
+        }
+
+        Err(last_error)
+    }
+}
+
+impl From<String> for EncryptionKeys {
+    /// a single key without id, as `encryption_key`
+    fn from(key: String) -> Self {
+        EncryptionKeys::new(vec![EncryptionKey {
+            id: None,
+            key: key.into_bytes(),
+            encrypted_data_key: None,
+        }])
+    }
+}
+
+/// compresses and encrypts the parts of a dump, it is cloned to encode several parts concurrently
+#[derive(Clone, Default)]
+pub struct Encoder {
+    compression: Option<Compression>,
+    key: Option<EncryptionKey>,
+}
+
+impl Encoder {
+    pub fn new(compression: Option<Compression>, key: Option<EncryptionKey>) -> Self {
+        Self { compression, key }
+    }
+
+    /// checksum, compress and encrypt a part of a dump
+    pub fn encode(&self, data: Bytes) -> Result<EncodedPart, ReplibyteError> {
+        // the checksum of the part is compared with the part read back, decrypted and decompressed
+        let checksum = checksum(&data);
+
+        // compress data?
+        let data = match self.compression {
+            Some(compression) => compress(data, compression)?,
+            None => data,
+        };
+
+        // encrypt data?
+        let data = match &self.key {
+            Some(key) => encrypt(data, key.key.as_slice())?,
+            None => data,
+        };
+
+        Ok(EncodedPart { data, checksum })
+    }
+}
+
+/// a part of a dump ready to be written, with the checksum of its original data
+#[derive(Debug, Clone)]
+pub struct EncodedPart {
+    pub data: Bytes,
+    pub checksum: String,
 }
 
 #[derive(Serialize, Deserialize, Ord, PartialOrd, Eq, PartialEq, Clone)]
@@ -175,55 +781,185 @@ pub enum ReadOptions {
     Dump { name: String },
 }
 
-fn compress(data: Bytes) -> Result<Bytes, Error> {
-    let mut enc = ZlibEncoder::new(Vec::new(), Compression::default());
-    let _ = enc.write_all(data.as_slice());
-    enc.flush_finish()
+/// hex encoded SHA-256 of the data
+fn checksum(data: &[u8]) -> String {
+    digest(&SHA256, data)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn compress(data: Bytes, compression: Compression) -> Result<Bytes, ReplibyteError> {
+    let flate2_level = || {
+        compression
+            .level
+            .map(|level| flate2::Compression::new(level as u32))
+            .unwrap_or_default()
+    };
+
+    match compression.algorithm {
+        CompressionAlgorithm::Zlib => {
+            let mut enc = ZlibEncoder::new(Vec::new(), flate2_level());
+            let _ = enc.write_all(data.as_slice());
+            Ok(enc.flush_finish()?)
+        }
+        CompressionAlgorithm::Gzip => {
+            let mut enc = GzEncoder::new(Vec::new(), flate2_level());
+            let _ = enc.write_all(data.as_slice())?;
+            Ok(enc.finish()?)
+        }
+        CompressionAlgorithm::Zstd => Ok(zstd::encode_all(
+            data.as_slice(),
+            compression.level.unwrap_or(zstd::DEFAULT_COMPRESSION_LEVEL),
+        )?),
+    }
+}
+
+fn decompress(data: Bytes, algorithm: CompressionAlgorithm) -> Result<Bytes, ReplibyteError> {
+    match algorithm {
+        CompressionAlgorithm::Zlib => {
+            let mut dec = ZlibDecoder::new(data.as_slice());
+            let mut decoded_data = Vec::new();
+            let _ = dec.read_to_end(&mut decoded_data);
+            Ok(decoded_data)
+        }
+        CompressionAlgorithm::Gzip => {
+            let mut dec = GzDecoder::new(data.as_slice());
+            let mut decoded_data = Vec::new();
+            let _ = dec.read_to_end(&mut decoded_data)?;
+            Ok(decoded_data)
+        }
+        CompressionAlgorithm::Zstd => Ok(zstd::decode_all(data.as_slice())?),
+    }
+}
+
+/// decrypt and decompress a part of a dump
+fn decode(
+    data: Bytes,
+    dump: &Dump,
+    encryption_keys: &EncryptionKeys,
+) -> Result<Bytes, ReplibyteError> {
+    // decrypt data?
+    let data = if dump.encrypted {
+        encryption_keys.decrypt(data, dump)?
+    } else {
+        data
+    };
+
+    // decompress data?
+    match dump.compression_algorithm() {
+        Some(algorithm) => decompress(data, algorithm),
+        None => Ok(data),
+    }
+}
+
+/// read the encoded data with `read_encoded` on another thread, while the data read before is decoded on the
+/// pipeline runtime, `DECODERS` at a time. The decoded data is given to the callback in order, on the calling thread.
+fn read_concurrently<T, R, G, F>(
+    read_encoded: G,
+    decode: F,
+    data_callback: &mut dyn FnMut(R),
+) -> Result<(), ReplibyteError>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    G: FnOnce(&mut dyn FnMut(T) -> Result<(), ReplibyteError>) -> Result<(), ReplibyteError> + Send,
+    F: Fn(T) -> Result<R, ReplibyteError> + Send + Sync + 'static,
+{
+    let (tx, rx) = mpsc::channel::<T>(1);
+    let mut decoded_rx = runtime::map_concurrently(rx, DECODERS, decode);
+
+    // the datastore is borrowed by the download thread, the caller can use it again once the dump is read
+    thread::scope(|scope| {
+        let join_handle = scope.spawn(move || {
+            read_encoded(&mut |data| {
+                tx.blocking_send(data).map_err(|_| {
+                    ReplibyteError::from(Error::new(
+                        ErrorKind::Other,
+                        "the decoding of the dump stopped before the end of the dump",
+                    ))
+                })
+            })
+        });
+
+        let mut decode_result = Ok(());
+        while let Some(data) = decoded_rx.blocking_recv() {
+            match data {
+                Ok(data) => data_callback(data),
+                Err(err) => {
+                    decode_result = Err(err);
+                    break;
+                }
+            }
+        }
+
+        // the download stops once the decoded data is not received anymore
+        drop(decoded_rx);
+
+        let read_result = join_handle.join().unwrap_or_else(|_| {
+            Err(ReplibyteError::from(Error::new(
+                ErrorKind::Other,
+                "the download of the dump panicked",
+            )))
+        });
+
+        // the error of the decoding comes first, it stopped the download
+        let _ = decode_result?;
+        read_result
+    })
+}
+
+/// return the part number of a `<dump>/<part>.dump` key
+fn dump_part_number(key: &str) -> u16 {
+    key.rsplit('/')
+        .next()
+        .and_then(|file_name| file_name.strip_suffix(".dump"))
+        .and_then(|part| part.parse::<u16>().ok())
+        .unwrap_or(u16::MAX)
 }
 
-fn decompress(data: Bytes) -> Result<Bytes, Error> {
-    let mut dec = ZlibDecoder::new(data.as_slice());
-    let mut decoded_data = Vec::new();
-    let _ = dec.read_to_end(&mut decoded_data);
-    Ok(decoded_data)
+/// return the `chunks/<hash>` key of a chunk
+fn chunk_key(hash: &str) -> String {
+    format!("{}/{}", CHUNKS_DIR, hash)
 }
 
-fn get_encryption_key_with_correct_length(key: &str) -> String {
+fn get_encryption_key_with_correct_length(key: &[u8]) -> Bytes {
     if key.len() >= 32 {
-        return key[0..32].to_string();
+        return key[0..32].to_vec();
     }
 
-    let mut key_string = key.to_string();
+    let mut key_bytes = key.to_vec();
     for _ in 0..(32 - key.len()) {
-        key_string.push('x');
+        key_bytes.push(b'x');
     }
 
-    key_string
+    key_bytes
 }
 
-fn encrypt(data: Bytes, encryption_key: &str) -> Result<Bytes, Error> {
+fn encrypt(data: Bytes, encryption_key: &[u8]) -> Result<Bytes, ReplibyteError> {
     let key = get_encryption_key_with_correct_length(encryption_key);
-    let key = Key::from_slice(key.as_bytes());
+    let key = Key::from_slice(key.as_slice());
     let cipher = Aes256Gcm::new(key);
     let nonce = Nonce::from_slice(b"unique nonce");
 
     let encrypted_data = match cipher.encrypt(nonce, data.as_slice()) {
         Ok(data) => data,
-        Err(err) => return Err(Error::new(ErrorKind::Other, format!("{:?}", err))),
+        Err(err) => return Err(ReplibyteError::Datastore(format!("{:?}", err))),
     };
 
     Ok(encrypted_data)
 }
 
-fn decrypt(encrypted_data: Bytes, encryption_key: &str) -> Result<Bytes, Error> {
+fn decrypt(encrypted_data: Bytes, encryption_key: &[u8]) -> Result<Bytes, ReplibyteError> {
     let key = get_encryption_key_with_correct_length(encryption_key);
-    let key = Key::from_slice(key.as_bytes());
+    let key = Key::from_slice(key.as_slice());
     let cipher = Aes256Gcm::new(key);
     let nonce = Nonce::from_slice(b"unique nonce");
 
     let data = match cipher.decrypt(nonce, encrypted_data.as_slice()) {
         Ok(data) => data,
-        Err(err) => return Err(Error::new(ErrorKind::Other, format!("{:?}", err))),
+        Err(err) => return Err(ReplibyteError::Datastore(format!("{:?}", err))),
     };
 
     Ok(data)
@@ -231,31 +967,352 @@ fn decrypt(encrypted_data: Bytes, encryption_key: &str) -> Result<Bytes, Error>
 
 #[cfg(test)]
 mod tests {
-    use crate::datastore::{compress, decompress, decrypt, encrypt};
+    use std::sync::Arc;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    use crate::config::RetentionConfig;
+    use crate::datastore::{
+        compress, decompress, decrypt, dump_part_number, encrypt, read_concurrently, Compression,
+        CompressionAlgorithm, Dump, EncryptedDataKey, EncryptionKey, EncryptionKeys, IndexFile,
+        ReadOptions,
+    };
+    use crate::errors::ReplibyteError;
+    use crate::kms::tests::LocalKms;
+
+    fn dump(key_id: Option<&str>, encrypted_data_key: Option<EncryptedDataKey>) -> Dump {
+        Dump {
+            directory_name: "dump-1".to_string(),
+            size: 0,
+            created_at: 0,
+            compressed: false,
+            compression: None,
+            encrypted: true,
+            encryption_key_id: key_id.map(|id| id.to_string()),
+            encrypted_data_key,
+            chunks: None,
+            parts: None,
+            started_at: None,
+            base: None,
+            uncompressed_size: None,
+            source_type: None,
+            metadata: None,
+            checksums: None,
+        }
+    }
 
     #[test]
     fn test_compression() {
         let data = b"hello w0rld - this is a long sentence right?".to_vec();
-        let compressed_data = compress(data.clone()).unwrap();
+        let compressed_data = compress(data.clone(), Compression::default()).unwrap();
         assert_ne!(data, compressed_data);
-        assert_eq!(decompress(compressed_data).unwrap(), data);
+        assert_eq!(
+            decompress(compressed_data, CompressionAlgorithm::Zlib).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn compression_algorithms() {
+        let data = b"hello w0rld hello w0rld hello w0rld hello w0rld hello w0rld".to_vec();
+
+        for algorithm in [
+            CompressionAlgorithm::Zlib,
+            CompressionAlgorithm::Gzip,
+            CompressionAlgorithm::Zstd,
+        ] {
+            for level in [None, Some(1), Some(9)] {
+                let compressed_data =
+                    compress(data.clone(), Compression::new(algorithm, level)).unwrap();
+                assert!(compressed_data.len() < data.len());
+                assert_eq!(decompress(compressed_data, algorithm).unwrap(), data);
+            }
+        }
+
+        let compressed_data =
+            compress(data, Compression::new(CompressionAlgorithm::Zstd, None)).unwrap();
+        assert!(decompress(compressed_data, CompressionAlgorithm::Gzip).is_err());
+
+        // the dumps compressed before the algorithm was recorded are compressed with zlib
+        let mut dump = dump(None, None);
+        assert_eq!(dump.compression_algorithm(), None);
+        dump.compressed = true;
+        assert_eq!(
+            dump.compression_algorithm(),
+            Some(CompressionAlgorithm::Zlib)
+        );
+        dump.compression = Some(CompressionAlgorithm::Zstd);
+        assert_eq!(
+            dump.compression_algorithm(),
+            Some(CompressionAlgorithm::Zstd)
+        );
     }
 
     #[test]
     fn test_encryption_1() {
         let key = "this is my secret";
         let data = b"hello w0rld hello w0rld hello w0rld hello w0rld hello w0rld".to_vec();
-        let encrypted_data = encrypt(data.clone(), key).unwrap();
+        let encrypted_data = encrypt(data.clone(), key.as_bytes()).unwrap();
         assert_ne!(encrypted_data, data);
-        assert_eq!(decrypt(encrypted_data, key).unwrap(), data);
+        assert_eq!(decrypt(encrypted_data, key.as_bytes()).unwrap(), data);
     }
 
     #[test]
     fn test_encryption_2() {
         let key = "this is my secret very very very long and greater than 32 chars";
         let data = b"hello w0rld hello w0rld hello w0rld hello w0rld hello w0rld".to_vec();
-        let encrypted_data = encrypt(data.clone(), key).unwrap();
+        let encrypted_data = encrypt(data.clone(), key.as_bytes()).unwrap();
         assert_ne!(encrypted_data, data);
-        assert_eq!(decrypt(encrypted_data, key).unwrap(), data);
+        assert_eq!(decrypt(encrypted_data, key.as_bytes()).unwrap(), data);
+    }
+
+    #[test]
+    fn encryption_keys() {
+        let key = |id: Option<&str>, key: &str| EncryptionKey {
+            id: id.map(|id| id.to_string()),
+            key: key.as_bytes().to_vec(),
+            encrypted_data_key: None,
+        };
+
+        let encryption_keys = EncryptionKeys::new(vec![
+            key(None, "legacy secret"),
+            key(Some("2022-01"), "old secret"),
+            key(Some("2022-06"), "new secret"),
+        ]);
+        assert_eq!(
+            encryption_keys.current(),
+            Some(&key(Some("2022-06"), "new secret"))
+        );
+
+        let data = b"hello w0rld".to_vec();
+        for (key_id, secret) in [
+            (None, "legacy secret"),
+            (Some("2022-01"), "old secret"),
+            (Some("2022-06"), "new secret"),
+        ] {
+            let encrypted_data = encrypt(data.clone(), secret.as_bytes()).unwrap();
+            assert_eq!(
+                encryption_keys
+                    .decrypt(encrypted_data.clone(), &dump(key_id, None))
+                    .unwrap(),
+                data
+            );
+            // the other keys are tried when the key of the dump does not match
+            assert_eq!(
+                encryption_keys
+                    .decrypt(encrypted_data, &dump(Some("2021-01"), None))
+                    .unwrap(),
+                data
+            );
+        }
+
+        let encrypted_data = encrypt(data, b"unknown secret").unwrap();
+        assert!(encryption_keys
+            .decrypt(encrypted_data, &dump(None, None))
+            .is_err());
+        assert!(EncryptionKeys::default().current().is_none());
+    }
+
+    #[test]
+    fn envelope_encryption() {
+        let kms = |key_id: &str| {
+            Arc::new(LocalKms {
+                key_id: key_id.to_string(),
+            })
+        };
+
+        let mut encryption_keys = EncryptionKeys::new(vec![EncryptionKey {
+            id: Some("2022-01".to_string()),
+            key: b"old secret".to_vec(),
+            encrypted_data_key: None,
+        }])
+        .with_kms(kms("kms-1"));
+        assert_eq!(encryption_keys.current_key_id(), Some("kms-1"));
+
+        let _ = encryption_keys.generate_data_key().unwrap();
+        let current = encryption_keys.current().unwrap().clone();
+        let encrypted_data_key = current.encrypted_data_key.clone().unwrap();
+        assert_eq!(current.id, Some("kms-1".to_string()));
+        assert_eq!(encrypted_data_key.key_id, "kms-1");
+
+        // the data key is decrypted by the KMS
+        let data = b"hello w0rld".to_vec();
+        let encrypted_data = encrypt(data.clone(), current.key.as_slice()).unwrap();
+        let dump_1 = dump(Some("kms-1"), Some(encrypted_data_key.clone()));
+        let encryption_keys = EncryptionKeys::default().with_kms(kms("kms-1"));
+        assert_eq!(
+            encryption_keys
+                .decrypt(encrypted_data.clone(), &dump_1)
+                .unwrap(),
+            data
+        );
+        assert!(EncryptionKeys::default()
+            .decrypt(encrypted_data.clone(), &dump_1)
+            .is_err());
+
+        // only the data key is re-encrypted with the new KMS key
+        let encryption_keys = EncryptionKeys::default().with_kms(kms("kms-2"));
+        let rewrapped_data_key = encryption_keys
+            .rewrap_data_key(&encrypted_data_key)
+            .unwrap();
+        assert_eq!(rewrapped_data_key.key_id, "kms-2");
+        assert_ne!(rewrapped_data_key.ciphertext, encrypted_data_key.ciphertext);
+
+        let dump_2 = dump(Some("kms-2"), Some(rewrapped_data_key));
+        let encryption_keys = EncryptionKeys::default().with_kms(kms("kms-2"));
+        assert_eq!(
+            encryption_keys.decrypt(encrypted_data, &dump_2).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn dump_parts_order() {
+        let mut keys = vec![
+            "dump-1/10.dump".to_string(),
+            "dump-1/2.dump".to_string(),
+            "dump-1/1.dump".to_string(),
+        ];
+
+        keys.sort_by_key(|key| dump_part_number(key.as_str()));
+        assert_eq!(
+            keys,
+            vec!["dump-1/1.dump", "dump-1/2.dump", "dump-1/10.dump"]
+        );
+    }
+
+    #[test]
+    fn parts_are_decoded_in_order() {
+        let mut parts = vec![];
+        let _ = read_concurrently(
+            |data_callback| {
+                for part in 1..=20u64 {
+                    let _ = data_callback(part)?;
+                }
+
+                Ok(())
+            },
+            // the first parts are decoded last
+            |part| {
+                sleep(Duration::from_millis(40 - part * 2));
+                Ok(part * 10)
+            },
+            &mut |part| parts.push(part),
+        )
+        .unwrap();
+
+        assert_eq!(parts, (1..=20).map(|part| part * 10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn failed_decoding() {
+        let mut read_parts = 0;
+        let mut parts = vec![];
+
+        let result = read_concurrently(
+            |data_callback| {
+                for part in 1..=100 {
+                    read_parts += 1;
+                    let _ = data_callback(part)?;
+                }
+
+                Ok(())
+            },
+            |part| match part {
+                3 => Err(ReplibyteError::Datastore("corrupted part".to_string())),
+                part => Ok(part),
+            },
+            &mut |part| parts.push(part),
+        );
+
+        // the error of the decoding is returned, and the download stops
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            ReplibyteError::Datastore("corrupted part".to_string()).to_string()
+        );
+        assert_eq!(parts, vec![1, 2]);
+        assert!(read_parts < 100);
+    }
+
+    #[test]
+    fn dump_chain() {
+        let incremental = |name: &str, created_at: u128, base: &str| {
+            let mut dump = dump(None, None);
+            dump.directory_name = name.to_string();
+            dump.created_at = created_at;
+            dump.base = Some(base.to_string());
+            dump
+        };
+
+        let mut index_file = IndexFile::new();
+        index_file.dumps.push(dump(None, None));
+        index_file.dumps.push(incremental("dump-2", 2, "dump-1"));
+        index_file.dumps.push(incremental("dump-3", 3, "dump-2"));
+
+        assert_eq!(
+            index_file.dump_chain(&ReadOptions::Latest).unwrap(),
+            vec!["dump-1", "dump-2", "dump-3"]
+        );
+        assert_eq!(
+            index_file
+                .dump_chain(&ReadOptions::Dump {
+                    name: "dump-1".to_string()
+                })
+                .unwrap(),
+            vec!["dump-1"]
+        );
+
+        // the dumps an incremental dump is based on can't be missing
+        index_file
+            .dumps
+            .retain(|dump| dump.directory_name != "dump-2");
+        assert!(index_file.dump_chain(&ReadOptions::Latest).is_err());
+
+        index_file.dumps.push(incremental("dump-2", 2, "dump-3"));
+        assert!(index_file.dump_chain(&ReadOptions::Latest).is_err());
+    }
+
+    #[test]
+    fn dumps_to_prune() {
+        let day = 24 * 60 * 60 * 1000;
+        let now = 100 * day;
+
+        let dump = |name: &str, days_ago: u128, base: Option<&str>| {
+            let mut dump = dump(None, None);
+            dump.directory_name = name.to_string();
+            dump.created_at = now - days_ago * day;
+            dump.base = base.map(|base| base.to_string());
+            dump
+        };
+
+        let mut index_file = IndexFile::new();
+        index_file.dumps.push(dump("dump-1", 40, None));
+        index_file.dumps.push(dump("dump-2", 35, None));
+        index_file.dumps.push(dump("dump-3", 20, Some("dump-2")));
+        index_file.dumps.push(dump("dump-4", 10, None));
+        index_file.dumps.push(dump("dump-5", 1, None));
+
+        let retention = |keep_last, keep_days| RetentionConfig {
+            keep_last,
+            keep_days,
+        };
+
+        assert_eq!(
+            index_file.dumps_to_prune(&retention(Some(2), None), now),
+            vec!["dump-3", "dump-2", "dump-1"]
+        );
+        // dump-2 is kept, dump-3 is based on it
+        assert_eq!(
+            index_file.dumps_to_prune(&retention(None, Some(30)), now),
+            vec!["dump-1"]
+        );
+        // a dump is kept by either rule
+        assert_eq!(
+            index_file.dumps_to_prune(&retention(Some(1), Some(15)), now),
+            vec!["dump-3", "dump-2", "dump-1"]
+        );
+        assert!(index_file
+            .dumps_to_prune(&retention(Some(10), None), now)
+            .is_empty());
     }
 }
@@ -0,0 +1,347 @@
+use log::{info, warn};
+use serde_json::Value;
+
+use crate::connector::Connector;
+use crate::datastore::{
+    Compression, Datastore, Dump, EncodedPart, EncryptionKeys, IndexFile, ReadOptions,
+};
+use crate::errors::ReplibyteError;
+use crate::types::Bytes;
+use crate::utils::epoch_millis;
+
+/// Replicated writes the dumps to all its datastores, and reads them from the first reachable one.
+/// The datastores are listed by order of preference, the first one being the primary.
+pub struct Replicated {
+    datastores: Vec<Box<dyn Datastore>>,
+    /// whether each datastore has been reached when initializing
+    reachable: Vec<bool>,
+    compression: Option<Compression>,
+    encryption_keys: EncryptionKeys,
+}
+
+impl Replicated {
+    pub fn new(datastores: Vec<Box<dyn Datastore>>) -> Self {
+        let mut replicated = Replicated {
+            reachable: vec![true; datastores.len()],
+            datastores,
+            compression: Some(Compression::default()),
+            encryption_keys: EncryptionKeys::default(),
+        };
+
+        // the dump has the same name in all the datastores
+        replicated.set_dump_name(format!("dump-{}", epoch_millis()));
+        replicated
+    }
+
+    /// call `f` on the reachable datastores by order of preference, until one succeeds
+    fn first_reachable<T, F>(&self, mut f: F) -> Result<T, ReplibyteError>
+    where
+        F: FnMut(&dyn Datastore) -> Result<T, ReplibyteError>,
+    {
+        let mut last_error = ReplibyteError::Datastore("no datastore is reachable".to_string());
+
+        for (idx, datastore) in self.datastores.iter().enumerate() {
+            if !self.reachable[idx] {
+                continue;
+            }
+
+            match f(datastore.as_ref()) {
+                Ok(value) => return Ok(value),
+                Err(err) => {
+                    warn!("datastore #{}: {}, trying the next one", idx + 1, err);
+                    last_error = err;
+                }
+            }
+        }
+
+        Err(last_error)
+    }
+
+    /// call `read` on the reachable datastores by order of preference until one succeeds,
+    /// `read` records whether it gave some data, the next datastores are not tried once it did
+    fn read_first_reachable<F>(&self, mut read: F) -> Result<(), ReplibyteError>
+    where
+        F: FnMut(&dyn Datastore, &mut bool) -> Result<(), ReplibyteError>,
+    {
+        let mut last_error = ReplibyteError::Datastore("no datastore is reachable".to_string());
+
+        for (idx, datastore) in self.datastores.iter().enumerate() {
+            if !self.reachable[idx] {
+                continue;
+            }
+
+            let mut read_data = false;
+            match read(datastore.as_ref(), &mut read_data) {
+                Ok(_) => return Ok(()),
+                // the data already given can not be taken back, the next datastores are not tried
+                Err(err) if read_data => {
+                    return Err(err.with_context(format!("datastore #{}", idx + 1).as_str()))
+                }
+                Err(err) => {
+                    warn!("datastore #{}: {}, trying the next one", idx + 1, err);
+                    last_error = err;
+                }
+            }
+        }
+
+        Err(last_error)
+    }
+
+    /// call `f` on every datastore, they must all be reachable for the dumps to be replicated
+    fn all<F>(&self, mut f: F) -> Result<(), ReplibyteError>
+    where
+        F: FnMut(&dyn Datastore) -> Result<(), ReplibyteError>,
+    {
+        if let Some(idx) = self.reachable.iter().position(|reachable| !reachable) {
+            return Err(ReplibyteError::Datastore(format!(
+                "datastore #{} is unreachable, the dumps can not be replicated",
+                idx + 1
+            )));
+        }
+
+        for (idx, datastore) in self.datastores.iter().enumerate() {
+            f(datastore.as_ref())
+                .map_err(|err| err.with_context(format!("datastore #{}", idx + 1).as_str()))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Connector for Replicated {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        for (idx, datastore) in self.datastores.iter_mut().enumerate() {
+            match datastore.init() {
+                Ok(_) => self.reachable[idx] = true,
+                Err(err) => {
+                    warn!("datastore #{} is unreachable: {}", idx + 1, err);
+                    self.reachable[idx] = false;
+                }
+            }
+        }
+
+        if !self.reachable.iter().any(|reachable| *reachable) {
+            return Err(ReplibyteError::Datastore(
+                "no datastore is reachable".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Datastore for Replicated {
+    fn index_file(&self) -> Result<IndexFile, ReplibyteError> {
+        self.first_reachable(|datastore| datastore.index_file())
+    }
+
+    fn raw_index_file(&self) -> Result<Value, ReplibyteError> {
+        self.first_reachable(|datastore| datastore.raw_index_file())
+    }
+
+    fn write_index_file(&self, index_file: &IndexFile) -> Result<(), ReplibyteError> {
+        self.all(|datastore| datastore.write_index_file(index_file))
+    }
+
+    fn write_raw_index_file(&self, raw_index_file: &Value) -> Result<(), ReplibyteError> {
+        self.all(|datastore| datastore.write_raw_index_file(raw_index_file))
+    }
+
+    fn write_encoded(&self, file_part: u16, part: EncodedPart) -> Result<(), ReplibyteError> {
+        self.all(|datastore| datastore.write_encoded(file_part, part.clone()))
+    }
+
+    fn read_encoded(
+        &self,
+        dump: &Dump,
+        data_callback: &mut dyn FnMut(Bytes) -> Result<(), ReplibyteError>,
+    ) -> Result<(), ReplibyteError> {
+        self.read_first_reachable(|datastore, read_data| {
+            datastore.read_encoded(dump, &mut |data| {
+                *read_data = true;
+                data_callback(data)
+            })
+        })
+    }
+
+    fn read(
+        &self,
+        options: &ReadOptions,
+        data_callback: &mut dyn FnMut(Bytes),
+    ) -> Result<(), ReplibyteError> {
+        // each datastore decodes its parts, a dump which can not be decoded is read from the next one
+        self.read_first_reachable(|datastore, read_data| {
+            datastore.read(options, &mut |data| {
+                *read_data = true;
+                data_callback(data)
+            })
+        })
+    }
+
+    fn compression(&self) -> Option<Compression> {
+        self.compression
+    }
+
+    fn set_compression(&mut self, compression: Option<Compression>) {
+        for datastore in self.datastores.iter_mut() {
+            datastore.set_compression(compression);
+        }
+
+        self.compression = compression;
+    }
+
+    fn encryption_keys(&self) -> &EncryptionKeys {
+        &self.encryption_keys
+    }
+
+    fn set_encryption_keys(&mut self, keys: EncryptionKeys) {
+        for datastore in self.datastores.iter_mut() {
+            datastore.set_encryption_keys(keys.clone());
+        }
+
+        self.encryption_keys = keys;
+    }
+
+    fn set_dump_name(&mut self, name: String) {
+        for datastore in self.datastores.iter_mut() {
+            datastore.set_dump_name(name.clone());
+        }
+    }
+
+    fn delete_dump_files(&self, name: &str) -> Result<(), ReplibyteError> {
+        self.all(|datastore| datastore.delete_dump_files(name))
+    }
+
+    fn delete_by_name(&self, name: String) -> Result<(), ReplibyteError> {
+        info!("deleting dump '{}' from all the datastores", name);
+        self.all(|datastore| datastore.delete_by_name(name.clone()))
+    }
+
+    fn write_chunk(&self, hash: &str, data: Bytes) -> Result<(), ReplibyteError> {
+        self.all(|datastore| datastore.write_chunk(hash, data.clone()))
+    }
+
+    fn read_chunk(&self, hash: &str) -> Result<Bytes, ReplibyteError> {
+        self.first_reachable(|datastore| datastore.read_chunk(hash))
+    }
+
+    fn delete_chunk(&self, hash: &str) -> Result<(), ReplibyteError> {
+        self.all(|datastore| datastore.delete_chunk(hash))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{remove_dir_all, write};
+
+    use tempfile::tempdir;
+
+    use crate::connector::Connector;
+    use crate::datastore::local_disk::LocalDisk;
+    use crate::datastore::replicated::Replicated;
+    use crate::datastore::{Datastore, ReadOptions};
+    use crate::errors::ReplibyteError;
+
+    fn read_all(datastore: &dyn Datastore) -> Result<Vec<u8>, ReplibyteError> {
+        let mut data = vec![];
+        datastore.read(&ReadOptions::Latest, &mut |part| data.extend(part))?;
+        Ok(data)
+    }
+
+    #[test]
+    fn write_to_all_datastores() {
+        let primary_dir = tempdir().unwrap();
+        let secondary_dir = tempdir().unwrap();
+
+        let mut replicated = Replicated::new(vec![
+            Box::new(LocalDisk::new(primary_dir.path().to_str().unwrap())),
+            Box::new(LocalDisk::new(secondary_dir.path().to_str().unwrap())),
+        ]);
+        let _ = replicated.init().unwrap();
+        replicated.set_encryption_keys("encryption key".to_string().into());
+
+        let _ = replicated.write(1, b"part 1 ".to_vec()).unwrap();
+        let _ = replicated.write(2, b"part 2".to_vec()).unwrap();
+
+        for dir in [&primary_dir, &secondary_dir] {
+            let mut local_disk = LocalDisk::new(dir.path().to_str().unwrap());
+            local_disk.set_encryption_keys("encryption key".to_string().into());
+
+            let index_file = local_disk.index_file().unwrap();
+            assert_eq!(index_file.dumps.len(), 1);
+            assert!(index_file.dumps[0].encrypted);
+            assert_eq!(read_all(&local_disk).unwrap(), b"part 1 part 2");
+        }
+
+        let dump_name = replicated.index_file().unwrap().dumps[0]
+            .directory_name
+            .clone();
+        let _ = replicated.delete_by_name(dump_name).unwrap();
+
+        for dir in [&primary_dir, &secondary_dir] {
+            let local_disk = LocalDisk::new(dir.path().to_str().unwrap());
+            assert!(local_disk.index_file().unwrap().dumps.is_empty());
+        }
+    }
+
+    #[test]
+    fn read_from_the_next_datastore() {
+        let primary_dir = tempdir().unwrap();
+        let secondary_dir = tempdir().unwrap();
+
+        let mut replicated = Replicated::new(vec![
+            Box::new(LocalDisk::new(primary_dir.path().to_str().unwrap())),
+            Box::new(LocalDisk::new(secondary_dir.path().to_str().unwrap())),
+        ]);
+        let _ = replicated.init().unwrap();
+        let _ = replicated.write(1, b"dump".to_vec()).unwrap();
+
+        // the primary datastore is lost
+        remove_dir_all(primary_dir.path()).unwrap();
+        assert_eq!(read_all(&replicated).unwrap(), b"dump");
+
+        // the primary datastore is not reachable anymore, its directory can not be created
+        write(primary_dir.path(), b"").unwrap();
+
+        let mut replicated = Replicated::new(vec![
+            Box::new(LocalDisk::new(primary_dir.path().to_str().unwrap())),
+            Box::new(LocalDisk::new(secondary_dir.path().to_str().unwrap())),
+        ]);
+        let _ = replicated.init().unwrap();
+
+        assert_eq!(replicated.index_file().unwrap().dumps.len(), 1);
+        assert_eq!(read_all(&replicated).unwrap(), b"dump");
+        // the dumps can not be replicated without the primary datastore
+        assert!(replicated.write(1, b"dump".to_vec()).is_err());
+    }
+
+    #[test]
+    fn keep_the_error_category() {
+        let primary_dir = tempdir().unwrap();
+        let secondary_dir = tempdir().unwrap();
+
+        let mut replicated = Replicated::new(vec![
+            Box::new(LocalDisk::new(primary_dir.path().to_str().unwrap())),
+            Box::new(LocalDisk::new(secondary_dir.path().to_str().unwrap())),
+        ]);
+        let _ = replicated.init().unwrap();
+        replicated.set_encryption_keys("encryption key".to_string().into());
+
+        let _ = replicated.write(1, b"part 1 ".to_vec()).unwrap();
+        let _ = replicated.write(2, b"part 2".to_vec()).unwrap();
+
+        // the second part of the primary datastore can not be decrypted once the first one is read
+        let dump_name = replicated.index_file().unwrap().dumps[0]
+            .directory_name
+            .clone();
+        write(
+            primary_dir.path().join(dump_name).join("2.dump"),
+            b"corrupted",
+        )
+        .unwrap();
+
+        let err = read_all(&replicated).unwrap_err();
+        assert!(matches!(err, ReplibyteError::Datastore(_)));
+        assert!(err.to_string().starts_with("datastore #1: "));
+    }
+}
@@ -1,25 +1,32 @@
 use std::borrow::Cow;
-use std::io::{Error, ErrorKind};
+use std::fmt::Display;
+use std::future::Future;
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 
-use aws_config::profile::retry_config::ProfileFileRetryConfigProvider;
-use aws_config::profile::{ProfileFileCredentialsProvider, ProfileFileRegionProvider};
 use aws_sdk_s3::model::{
-    BucketLocationConstraint, CreateBucketConfiguration, Delete, Object, ObjectIdentifier,
+    BucketLocationConstraint, CompletedMultipartUpload, CompletedPart, CreateBucketConfiguration,
+    Delete, Object, ObjectIdentifier,
 };
 use aws_sdk_s3::types::ByteStream;
 use aws_sdk_s3::{Client, Endpoint as SdkEndpoint};
-use aws_types::region::Region;
-use aws_types::Credentials;
-use log::{error, info};
+use aws_smithy_client::hyper_ext::Adapter;
+use hyper_rustls::HttpsConnectorBuilder;
+use log::{error, info, warn};
 use serde_json::Value;
+use tokio::sync::Semaphore;
 
+use crate::aws::sdk_config;
 use crate::config::{AwsCredentials, Endpoint};
 use crate::connector::Connector;
 use crate::datastore::s3::S3Error::FailedObjectUpload;
 use crate::datastore::{
-    compress, decompress, decrypt, encrypt, Datastore, Dump, IndexFile, ReadOptions,
+    chunk_key, dump_part_number, Compression, Datastore, Dump, EncodedPart, EncryptionKeys,
+    IndexFile,
 };
+use crate::errors::ReplibyteError;
+use crate::proxy::ProxyConnector;
 use crate::runtime::block_on;
 use crate::types::Bytes;
 use crate::utils::epoch_millis;
@@ -27,6 +34,32 @@ use crate::utils::epoch_millis;
 use super::INDEX_FILE_NAME;
 
 const GOOGLE_CLOUD_STORAGE_URL: &str = "https://storage.googleapis.com";
+/// S3 rejects the parts of a multipart upload smaller than 5 MB, except the last one
+pub const MIN_PART_SIZE: usize = 5 * 1024 * 1024;
+const DEFAULT_PART_SIZE: usize = 16 * 1024 * 1024;
+const DEFAULT_CONCURRENCY: usize = 4;
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// the dump parts larger than `part_size` are uploaded in several parts, `concurrency` at a time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MultipartUpload {
+    pub part_size: usize,
+    pub concurrency: usize,
+    /// retries of a failed upload, with an exponential backoff
+    pub max_retries: u32,
+}
+
+impl Default for MultipartUpload {
+    fn default() -> Self {
+        MultipartUpload {
+            part_size: DEFAULT_PART_SIZE,
+            concurrency: DEFAULT_CONCURRENCY,
+            max_retries: DEFAULT_MAX_RETRIES,
+        }
+    }
+}
 
 pub struct S3 {
     bucket: String,
@@ -34,8 +67,9 @@ pub struct S3 {
     region: Option<String>,
     endpoint: Endpoint,
     client: Client,
-    enable_compression: bool,
-    encryption_key: Option<String>,
+    compression: Option<Compression>,
+    encryption_keys: EncryptionKeys,
+    multipart: Option<MultipartUpload>,
 }
 
 impl S3 {
@@ -45,52 +79,16 @@ impl S3 {
         profile: Option<S>,
         credentials: Option<AwsCredentials>,
         endpoint: Endpoint,
-    ) -> anyhow::Result<Self>
+        proxy: Option<String>,
+    ) -> Result<Self, ReplibyteError>
     where
         S: 'static + AsRef<str> + Into<Cow<'static, str>> + Clone,
     {
-        let mut config_loader = aws_config::from_env();
-
-        if let Some(profile) = profile {
-            config_loader = config_loader
-                .region(
-                    ProfileFileRegionProvider::builder()
-                        .profile_name(profile.as_ref())
-                        .build(),
-                )
-                .credentials_provider(
-                    ProfileFileCredentialsProvider::builder()
-                        .profile_name(profile.as_ref())
-                        .build(),
-                )
-                .retry_config(
-                    block_on(
-                        ProfileFileRetryConfigProvider::builder()
-                            .profile_name(profile.as_ref())
-                            .build()
-                            .retry_config_builder(),
-                    )?
-                    .build(),
-                )
-        }
-
-        if let Some(region) = region.clone() {
-            let region: Cow<str> = region.into();
-
-            config_loader = config_loader.region(Region::new(region))
-        }
-
-        if let Some(credentials) = credentials {
-            config_loader = config_loader.credentials_provider(Credentials::new(
-                credentials.access_key_id,
-                credentials.secret_access_key,
-                credentials.session_token,
-                None,
-                "replibyte-config",
-            ))
-        }
-
-        let sdk_config = block_on(config_loader.load());
+        let sdk_config = sdk_config(
+            region.as_ref().map(|region| region.as_ref().to_string()),
+            profile.as_ref().map(|profile| profile.as_ref().to_string()),
+            credentials,
+        )?;
 
         let s3_config_builder = aws_sdk_s3::config::Builder::from(&sdk_config);
 
@@ -104,14 +102,28 @@ impl S3 {
             },
         };
 
+        let client = match ProxyConnector::new(proxy)? {
+            Some(proxy_connector) => {
+                let connector = HttpsConnectorBuilder::new()
+                    .with_native_roots()
+                    .https_or_http()
+                    .enable_http1()
+                    .wrap_connector(proxy_connector);
+
+                Client::from_conf_conn(s3_config, Adapter::builder().build(connector))
+            }
+            None => Client::from_conf(s3_config),
+        };
+
         Ok(S3 {
             bucket: bucket.as_ref().into(),
             root_key: format!("dump-{}", epoch_millis()),
             region: region.map(|region| region.as_ref().into()),
             endpoint,
-            client: Client::from_conf(s3_config),
-            enable_compression: true,
-            encryption_key: None,
+            client,
+            compression: Some(Compression::default()),
+            encryption_keys: EncryptionKeys::default(),
+            multipart: Some(MultipartUpload::default()),
         })
     }
 
@@ -121,7 +133,8 @@ impl S3 {
         access_key: S,
         secret: S,
         endpoint: Endpoint,
-    ) -> anyhow::Result<Self>
+        proxy: Option<String>,
+    ) -> Result<Self, ReplibyteError>
     where
         S: 'static + AsRef<str> + Into<Cow<'static, str>> + Clone,
     {
@@ -132,7 +145,7 @@ impl S3 {
             Endpoint::Custom(url) => Endpoint::Custom(url),
         };
 
-        S3::aws(
+        let s3 = S3::aws(
             bucket,
             Some(region),
             None,
@@ -142,10 +155,22 @@ impl S3 {
                 session_token: None,
             }),
             endpoint,
-        )
+            proxy,
+        )?;
+
+        // the dump parts are uploaded in one request, the S3 interoperability of Cloud Storage is partial
+        Ok(S3 {
+            multipart: None,
+            ..s3
+        })
     }
 
-    fn create_index_file(&self) -> Result<IndexFile, Error> {
+    pub fn with_multipart(mut self, multipart: MultipartUpload) -> Self {
+        self.multipart = Some(multipart);
+        self
+    }
+
+    fn create_index_file(&self) -> Result<IndexFile, ReplibyteError> {
         match self.index_file() {
             Ok(index_file) => Ok(index_file),
             Err(_) => {
@@ -158,7 +183,7 @@ impl S3 {
 }
 
 impl Connector for S3 {
-    fn init(&mut self) -> Result<(), Error> {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
         match &self.endpoint {
             Endpoint::Custom(url) if url.as_str() == GOOGLE_CLOUD_STORAGE_URL => {
                 // Do not try to create bucket - the current S3 client does not supports well GCP Cloud Storage
@@ -173,20 +198,20 @@ impl Connector for S3 {
 }
 
 impl Datastore for S3 {
-    fn index_file(&self) -> Result<IndexFile, Error> {
+    fn index_file(&self) -> Result<IndexFile, ReplibyteError> {
         let object = get_object(&self.client, self.bucket.as_str(), INDEX_FILE_NAME)?;
         let index_file: IndexFile = serde_json::from_slice(object.as_slice())?;
         Ok(index_file)
     }
 
-    fn raw_index_file(&self) -> Result<Value, Error> {
+    fn raw_index_file(&self) -> Result<Value, ReplibyteError> {
         let object = get_object(&self.client, self.bucket.as_str(), INDEX_FILE_NAME)?;
         let index_file = serde_json::from_slice(object.as_slice())?;
 
         Ok(index_file)
     }
 
-    fn write_index_file(&self, index_file: &IndexFile) -> Result<(), Error> {
+    fn write_index_file(&self, index_file: &IndexFile) -> Result<(), ReplibyteError> {
         let index_file_json = serde_json::to_vec(index_file)?;
 
         create_object(
@@ -195,10 +220,10 @@ impl Datastore for S3 {
             INDEX_FILE_NAME,
             index_file_json,
         )
-        .map_err(|err| Error::from(err))
+        .map_err(|err| ReplibyteError::from(err))
     }
 
-    fn write_raw_index_file(&self, raw_index_file: &Value) -> Result<(), Error> {
+    fn write_raw_index_file(&self, raw_index_file: &Value) -> Result<(), ReplibyteError> {
         let index_file_json = serde_json::to_vec(raw_index_file)?;
 
         create_object(
@@ -207,118 +232,108 @@ impl Datastore for S3 {
             INDEX_FILE_NAME,
             index_file_json,
         )
-        .map_err(|err| Error::from(err))
+        .map_err(|err| ReplibyteError::from(err))
     }
 
-    fn write(&self, file_part: u16, data: Bytes) -> Result<(), Error> {
+    fn write_encoded(&self, file_part: u16, part: EncodedPart) -> Result<(), ReplibyteError> {
         write_objects(
             self,
             file_part,
-            data,
+            part,
             self.bucket.as_str(),
             self.root_key.as_str(),
             &self.client,
+            self.multipart.as_ref(),
         )
     }
 
-    fn read(
+    fn read_encoded(
         &self,
-        options: &ReadOptions,
-        mut data_callback: &mut dyn FnMut(Bytes),
-    ) -> Result<(), Error> {
-        let mut index_file = self.index_file()?;
-        let dump = index_file.find_dump(options)?;
-
-        for object in list_objects(
+        dump: &Dump,
+        data_callback: &mut dyn FnMut(Bytes) -> Result<(), ReplibyteError>,
+    ) -> Result<(), ReplibyteError> {
+        let mut objects = list_objects(
             &self.client,
             self.bucket.as_str(),
             Some(dump.directory_name.as_str()),
-        )? {
-            let data = get_object(&self.client, self.bucket.as_str(), object.key().unwrap())?;
+        )?;
+
+        // the parts are read in order, `10.dump` comes after `9.dump`
+        objects.sort_by_key(|object| dump_part_number(object.key().unwrap_or_default()));
 
-            // decrypt data?
-            let data = if dump.encrypted {
-                // It should be safe to unwrap here because the dump is marked as encrypted in the dump manifest
-                // so if there is no encryption key set at the datastore level we want to panic.
-                let encryption_key = self.encryption_key.as_ref().unwrap();
-                decrypt(data, encryption_key.as_str())?
-            } else {
-                data
-            };
-
-            // decompress data?
-            let data = if dump.compressed {
-                decompress(data)?
-            } else {
-                data
-            };
-
-            data_callback(data);
+        for object in objects {
+            let data = get_object(&self.client, self.bucket.as_str(), object.key().unwrap())?;
+            let _ = data_callback(data)?;
         }
 
         Ok(())
     }
 
-    fn set_encryption_key(&mut self, key: String) {
-        self.encryption_key = Some(key);
+    fn set_encryption_keys(&mut self, keys: EncryptionKeys) {
+        self.encryption_keys = keys;
     }
 
-    fn set_compression(&mut self, enable: bool) {
-        self.enable_compression = enable;
+    fn set_compression(&mut self, compression: Option<Compression>) {
+        self.compression = compression;
     }
 
     fn set_dump_name(&mut self, name: String) {
         self.root_key = name;
     }
 
-    fn compression_enabled(&self) -> bool {
-        self.enable_compression
+    fn compression(&self) -> Option<Compression> {
+        self.compression
     }
 
-    fn encryption_key(&self) -> &Option<String> {
-        &self.encryption_key
+    fn encryption_keys(&self) -> &EncryptionKeys {
+        &self.encryption_keys
     }
 
-    fn delete_by_name(&self, name: String) -> Result<(), Error> {
-        let mut index_file = self.index_file()?;
-
+    fn delete_dump_files(&self, name: &str) -> Result<(), ReplibyteError> {
         let bucket = &self.bucket;
 
-        let _ = delete_directory(&self.client, bucket, &name).map_err(|err| Error::from(err))?;
+        let _ = delete_directory(&self.client, bucket, name)
+            .map_err(|err| ReplibyteError::from(err))?;
 
-        index_file.dumps.retain(|b| b.directory_name != name);
+        Ok(())
+    }
+
+    fn write_chunk(&self, hash: &str, data: Bytes) -> Result<(), ReplibyteError> {
+        let key = chunk_key(hash);
+        create_object(&self.client, self.bucket.as_str(), key.as_str(), data)
+            .map_err(|err| ReplibyteError::from(err))
+    }
+
+    fn read_chunk(&self, hash: &str) -> Result<Bytes, ReplibyteError> {
+        let key = chunk_key(hash);
+        get_object(&self.client, self.bucket.as_str(), key.as_str())
+            .map_err(|err| ReplibyteError::from(err))
+    }
 
-        self.write_index_file(&index_file)
+    fn delete_chunk(&self, hash: &str) -> Result<(), ReplibyteError> {
+        let key = chunk_key(hash);
+        delete_object(&self.client, self.bucket.as_str(), key.as_str())
+            .map_err(|err| ReplibyteError::from(err))
     }
 }
 
 fn write_objects<B: Datastore>(
     datastore: &B,
     file_part: u16,
-    data: Bytes,
+    part: EncodedPart,
     bucket: &str,
     root_key: &str,
     client: &Client,
-) -> Result<(), Error> {
-    // compress data?
-    let data = if datastore.compression_enabled() {
-        compress(data)?
-    } else {
-        data
-    };
-
-    // encrypt data?
-    let data = match datastore.encryption_key() {
-        Some(key) => encrypt(data, key.as_str())?,
-        None => data,
-    };
+    multipart: Option<&MultipartUpload>,
+) -> Result<(), ReplibyteError> {
+    let EncodedPart { data, checksum } = part;
 
     let data_size = data.len();
     let key = format!("{}/{}.dump", root_key, file_part);
 
     info!("upload object '{}' part {} on", key.as_str(), file_part);
 
-    let _ = create_object(client, bucket, key.as_str(), data)?;
+    let _ = upload_object(client, bucket, key.as_str(), data, multipart)?;
 
     // update index file
     let mut index_file = datastore.index_file()?;
@@ -327,8 +342,27 @@ fn write_objects<B: Datastore>(
         directory_name: root_key.to_string(),
         size: 0,
         created_at: epoch_millis(),
-        compressed: datastore.compression_enabled(),
-        encrypted: datastore.encryption_key().is_some(),
+        compressed: datastore.compression().is_some(),
+        compression: datastore
+            .compression()
+            .map(|compression| compression.algorithm),
+        encrypted: datastore.encryption_keys().current().is_some(),
+        encryption_key_id: datastore
+            .encryption_keys()
+            .current()
+            .and_then(|key| key.id.clone()),
+        encrypted_data_key: datastore
+            .encryption_keys()
+            .current()
+            .and_then(|key| key.encrypted_data_key.clone()),
+        chunks: None,
+        parts: None,
+        started_at: None,
+        base: None,
+        uncompressed_size: None,
+        source_type: None,
+        metadata: None,
+        checksums: None,
     };
 
     // find or create dump
@@ -338,6 +372,8 @@ fn write_objects<B: Datastore>(
         .find(|b| b.directory_name.as_str() == root_key)
         .unwrap_or(&mut new_dump);
 
+    dump.checksums.get_or_insert_with(Vec::new).push(checksum);
+
     if dump.size == 0 {
         // it means it's a new dump.
         // We need to add it into the index_file.dumps
@@ -364,51 +400,47 @@ enum S3Error<'a> {
     FailedToDeleteDirectory { bucket: &'a str, directory: &'a str },
 }
 
-impl<'a> From<S3Error<'a>> for Error {
+impl<'a> From<S3Error<'a>> for ReplibyteError {
     fn from(err: S3Error<'a>) -> Self {
         match err {
-            S3Error::FailedToCreateBucket { bucket } => Error::new(
-                ErrorKind::Other,
-                format!("failed to create bucket '{}'", bucket),
-            ),
-            S3Error::FailedToDeleteBucket { bucket } => Error::new(
-                ErrorKind::Other,
-                format!("failed to delete bucket '{}'", bucket),
-            ),
-            S3Error::FailedToListObjects { bucket } => Error::new(
-                ErrorKind::Other,
-                format!("failed to list objects from bucket '{}'", bucket),
-            ),
+            S3Error::FailedToCreateBucket { bucket } => {
+                ReplibyteError::Datastore(format!("failed to create bucket '{}'", bucket))
+            }
+            S3Error::FailedToDeleteBucket { bucket } => {
+                ReplibyteError::Datastore(format!("failed to delete bucket '{}'", bucket))
+            }
+            S3Error::FailedToListObjects { bucket } => ReplibyteError::Datastore(format!(
+                "failed to list objects from bucket '{}'",
+                bucket
+            )),
             S3Error::ObjectDoesNotExist {
                 bucket,
                 key: object,
-            } => Error::new(
-                ErrorKind::Other,
-                format!("object '{}/{}' does not exist", bucket, object),
-            ),
+            } => {
+                ReplibyteError::Datastore(format!("object '{}/{}' does not exist", bucket, object))
+            }
             S3Error::FailedObjectDownload {
                 bucket,
                 key: object,
-            } => Error::new(
-                ErrorKind::Other,
-                format!("failed to download object '{}/{}'", bucket, object),
-            ),
+            } => ReplibyteError::Datastore(format!(
+                "failed to download object '{}/{}'",
+                bucket, object
+            )),
             FailedObjectUpload {
                 bucket,
                 key: object,
-            } => Error::new(
-                ErrorKind::Other,
-                format!("failed to upload object '{}/{}'", bucket, object),
-            ),
+            } => ReplibyteError::Datastore(format!(
+                "failed to upload object '{}/{}'",
+                bucket, object
+            )),
             S3Error::FailedToDeleteObject {
                 bucket,
                 key: object,
-            } => Error::new(
-                ErrorKind::Other,
-                format!("failed to delete object '{}/{}'", bucket, object),
-            ),
-            S3Error::FailedToDeleteDirectory { bucket, directory } => Error::new(
-                ErrorKind::Other,
+            } => ReplibyteError::Datastore(format!(
+                "failed to delete object '{}/{}'",
+                bucket, object
+            )),
+            S3Error::FailedToDeleteDirectory { bucket, directory } => ReplibyteError::Datastore(
                 format!("failed to delete directory '{}/{}'", bucket, directory),
             ),
         }
@@ -504,6 +536,206 @@ fn create_object<'a>(
     Ok(())
 }
 
+/// upload a dump part, in several parts if it is larger than the part size, the failed requests are retried
+fn upload_object<'a>(
+    client: &Client,
+    bucket: &'a str,
+    key: &'a str,
+    object: Vec<u8>,
+    multipart: Option<&MultipartUpload>,
+) -> Result<(), S3Error<'a>> {
+    let result = match multipart {
+        Some(multipart) if object.len() > multipart.part_size => {
+            block_on(multipart_upload(client, bucket, key, object, multipart))
+        }
+        _ => {
+            let max_retries =
+                multipart.map_or(DEFAULT_MAX_RETRIES, |multipart| multipart.max_retries);
+
+            block_on(with_retries(key, max_retries, || {
+                client
+                    .put_object()
+                    .bucket(bucket)
+                    .key(key)
+                    .body(ByteStream::from(object.clone()))
+                    .send()
+            }))
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+        }
+    };
+
+    if let Err(err) = result {
+        error!("{}", err);
+        return Err(S3Error::FailedObjectUpload { bucket, key });
+    }
+
+    Ok(())
+}
+
+async fn multipart_upload(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    object: Vec<u8>,
+    multipart: &MultipartUpload,
+) -> Result<(), String> {
+    let upload = client
+        .create_multipart_upload()
+        .bucket(bucket)
+        .key(key)
+        .send()
+        .await
+        .map_err(|err| err.to_string())?;
+
+    let upload_id = upload.upload_id().unwrap_or_default().to_string();
+
+    let parts = match upload_parts(client, bucket, key, upload_id.as_str(), object, multipart).await
+    {
+        Ok(parts) => parts,
+        Err(err) => {
+            // the uploaded parts are stored, and billed, until the upload is aborted
+            let _ = client
+                .abort_multipart_upload()
+                .bucket(bucket)
+                .key(key)
+                .upload_id(upload_id.as_str())
+                .send()
+                .await;
+
+            return Err(err);
+        }
+    };
+
+    let _ = client
+        .complete_multipart_upload()
+        .bucket(bucket)
+        .key(key)
+        .upload_id(upload_id.as_str())
+        .multipart_upload(
+            CompletedMultipartUpload::builder()
+                .set_parts(Some(parts))
+                .build(),
+        )
+        .send()
+        .await
+        .map_err(|err| err.to_string())?;
+
+    Ok(())
+}
+
+/// upload the parts `concurrency` at a time, return them ordered by part number
+async fn upload_parts(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    upload_id: &str,
+    object: Vec<u8>,
+    multipart: &MultipartUpload,
+) -> Result<Vec<CompletedPart>, String> {
+    let semaphore = Arc::new(Semaphore::new(multipart.concurrency));
+    let mut uploads = vec![];
+
+    for (idx, data) in object.chunks(multipart.part_size).enumerate() {
+        // a part is copied once it can be uploaded, at most `concurrency` parts are in memory twice
+        let permit = Arc::clone(&semaphore)
+            .acquire_owned()
+            .await
+            .map_err(|err| err.to_string())?;
+
+        let part_number = idx as i32 + 1;
+        let data = data.to_vec();
+        let (client, bucket, key, upload_id) = (
+            client.clone(),
+            bucket.to_string(),
+            key.to_string(),
+            upload_id.to_string(),
+        );
+        let max_retries = multipart.max_retries;
+
+        uploads.push(tokio::spawn(async move {
+            let _permit = permit;
+
+            let output = with_retries(key.as_str(), max_retries, || {
+                client
+                    .upload_part()
+                    .bucket(bucket.as_str())
+                    .key(key.as_str())
+                    .upload_id(upload_id.as_str())
+                    .part_number(part_number)
+                    .body(ByteStream::from(data.clone()))
+                    .send()
+            })
+            .await
+            .map_err(|err| err.to_string())?;
+
+            Ok::<CompletedPart, String>(
+                CompletedPart::builder()
+                    .set_e_tag(output.e_tag().map(|e_tag| e_tag.to_string()))
+                    .part_number(part_number)
+                    .build(),
+            )
+        }));
+    }
+
+    let mut parts = vec![];
+    let mut uploads = uploads.into_iter();
+
+    while let Some(upload) = uploads.next() {
+        match upload
+            .await
+            .map_err(|err| err.to_string())
+            .and_then(|part| part)
+        {
+            Ok(part) => parts.push(part),
+            Err(err) => {
+                for upload in uploads {
+                    upload.abort();
+                }
+
+                return Err(err);
+            }
+        }
+    }
+
+    Ok(parts)
+}
+
+/// send a request until it succeeds or `max_retries` retries failed
+async fn with_retries<T, E, F, R>(key: &str, max_retries: u32, mut request: F) -> Result<T, E>
+where
+    E: Display,
+    F: FnMut() -> R,
+    R: Future<Output = Result<T, E>>,
+{
+    let mut attempt = 0;
+
+    loop {
+        match request().await {
+            Ok(output) => return Ok(output),
+            Err(err) if attempt < max_retries => {
+                attempt += 1;
+                let delay = retry_delay(attempt);
+
+                warn!(
+                    "upload of '{}' failed, retry {}/{} in {:?}: {}",
+                    key, attempt, max_retries, delay, err
+                );
+
+                tokio::time::sleep(delay).await;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// exponential backoff: 500ms before the first retry, then 1s, 2s... up to 30s
+fn retry_delay(attempt: u32) -> Duration {
+    RETRY_BASE_DELAY
+        .checked_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .map_or(RETRY_MAX_DELAY, |delay| delay.min(RETRY_MAX_DELAY))
+}
+
 fn get_object<'a>(client: &Client, bucket: &'a str, key: &'a str) -> Result<Vec<u8>, S3Error<'a>> {
     let result = block_on(client.get_object().bucket(bucket).key(key).send());
 
@@ -546,8 +778,12 @@ fn list_objects<'a>(
         })
         .collect::<Vec<_>>();
 
-    // sort by key
-    objects.sort_by(|a, b| a.key.cmp(&b.key));
+    // sort by key, with the parts of a dump in order: `10.dump` comes after `9.dump`
+    objects.sort_by_key(|object| {
+        let key = object.key().unwrap_or_default();
+        let dir = key.rsplit_once('/').map(|(dir, _)| dir).unwrap_or_default();
+        (dir.to_string(), dump_part_number(key), key.to_string())
+    });
 
     Ok(objects)
 }
@@ -611,19 +847,55 @@ mod tests {
     use crate::config::{AwsCredentials, Endpoint};
     use crate::connector::Connector;
     use crate::datastore::s3::{
-        create_bucket, create_object, delete_bucket, delete_object, get_object, S3Error,
+        create_bucket, create_object, delete_bucket, delete_object, get_object, retry_delay,
+        with_retries, S3Error, S3,
     };
     use crate::datastore::{Datastore, Dump, INDEX_FILE_NAME};
     use crate::migration::rename_backups_to_dumps::RenameBackupsToDump;
     use crate::migration::update_version_number::UpdateVersionNumber;
     use crate::migration::Migrator;
+    use crate::runtime::block_on;
     use crate::utils::epoch_millis;
-    use crate::S3;
 
     const REGION: &str = "us-east-2";
     const MINIO_ENDPOINT: &str = "http://localhost:9000";
     const MINIO_CREDENTIALS: &str = "minioadmin";
 
+    #[test]
+    fn retry_delays() {
+        assert_eq!(retry_delay(1), std::time::Duration::from_millis(500));
+        assert_eq!(retry_delay(2), std::time::Duration::from_secs(1));
+        assert_eq!(retry_delay(4), std::time::Duration::from_secs(4));
+        assert_eq!(retry_delay(10), std::time::Duration::from_secs(30));
+        assert_eq!(retry_delay(100), std::time::Duration::from_secs(30));
+    }
+
+    #[test]
+    fn retried_requests() {
+        let mut attempts = 0;
+
+        let result = block_on(with_retries("dump-1/1.dump", 2, || {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                if attempt < 3 {
+                    Err("timeout")
+                } else {
+                    Ok(attempt)
+                }
+            }
+        }));
+        assert_eq!(result, Ok(3));
+
+        let mut attempts = 0;
+        let result: Result<(), &str> = block_on(with_retries("dump-1/1.dump", 1, || {
+            attempts += 1;
+            async { Err("timeout") }
+        }));
+        assert_eq!(result, Err("timeout"));
+        assert_eq!(attempts, 2);
+    }
+
     fn aws_bucket() -> String {
         format!("replibyte-test-{}", Faker.fake::<String>().to_lowercase())
     }
@@ -652,6 +924,7 @@ mod tests {
                 session_token: None,
             }),
             Endpoint::Custom(MINIO_ENDPOINT.to_string()),
+            None,
         )
         .unwrap()
     }
@@ -679,6 +952,7 @@ mod tests {
             access_key,
             secret,
             endpoint,
+            None,
         )
         .unwrap()
     }
@@ -851,7 +1125,18 @@ mod tests {
             size: 0,
             created_at: epoch_millis(),
             compressed: true,
+            compression: None,
             encrypted: false,
+            encryption_key_id: None,
+            encrypted_data_key: None,
+            chunks: None,
+            parts: None,
+            started_at: None,
+            base: None,
+            uncompressed_size: None,
+            source_type: None,
+            metadata: None,
+            checksums: None,
         });
 
         assert!(s3.write_index_file(&index_file).is_ok());
@@ -890,7 +1175,18 @@ mod tests {
             size: 0,
             created_at: epoch_millis(),
             compressed: true,
+            compression: None,
             encrypted: false,
+            encryption_key_id: None,
+            encrypted_data_key: None,
+            chunks: None,
+            parts: None,
+            started_at: None,
+            base: None,
+            uncompressed_size: None,
+            source_type: None,
+            metadata: None,
+            checksums: None,
         });
 
         index_file.dumps.push(Dump {
@@ -898,7 +1194,18 @@ mod tests {
             size: 0,
             created_at: epoch_millis(),
             compressed: true,
+            compression: None,
             encrypted: false,
+            encryption_key_id: None,
+            encrypted_data_key: None,
+            chunks: None,
+            parts: None,
+            started_at: None,
+            base: None,
+            uncompressed_size: None,
+            source_type: None,
+            metadata: None,
+            checksums: None,
         });
 
         assert!(s3.write_index_file(&index_file).is_ok());
@@ -925,6 +1232,7 @@ mod tests {
                 dump: Some("dump-1".to_string()),
                 older_than: None,
                 keep_last: None,
+                force: false,
             })
             .is_ok());
 
@@ -937,6 +1245,7 @@ mod tests {
                 dump: Some("dump-2".to_string()),
                 older_than: None,
                 keep_last: None,
+                force: false,
             })
             .is_ok());
         assert!(s3.index_file().unwrap().dumps.is_empty());
@@ -962,7 +1271,18 @@ mod tests {
             size: 0,
             created_at: (Utc::now() - Duration::days(5)).timestamp_millis() as u128,
             compressed: true,
+            compression: None,
             encrypted: false,
+            encryption_key_id: None,
+            encrypted_data_key: None,
+            chunks: None,
+            parts: None,
+            started_at: None,
+            base: None,
+            uncompressed_size: None,
+            source_type: None,
+            metadata: None,
+            checksums: None,
         });
 
         // Add a dump from now
@@ -971,7 +1291,18 @@ mod tests {
             size: 0,
             created_at: epoch_millis(),
             compressed: true,
+            compression: None,
             encrypted: false,
+            encryption_key_id: None,
+            encrypted_data_key: None,
+            chunks: None,
+            parts: None,
+            started_at: None,
+            base: None,
+            uncompressed_size: None,
+            source_type: None,
+            metadata: None,
+            checksums: None,
         });
 
         assert!(s3.write_index_file(&index_file).is_ok());
@@ -998,6 +1329,7 @@ mod tests {
                 dump: None,
                 older_than: Some("6d".to_string()),
                 keep_last: None,
+                force: false,
             })
             .is_ok());
 
@@ -1010,6 +1342,7 @@ mod tests {
                 dump: None,
                 older_than: Some("5d".to_string()),
                 keep_last: None,
+                force: false,
             })
             .is_ok());
 
@@ -1036,7 +1369,18 @@ mod tests {
             size: 0,
             created_at: (Utc::now() - Duration::days(3)).timestamp_millis() as u128,
             compressed: true,
+            compression: None,
             encrypted: false,
+            encryption_key_id: None,
+            encrypted_data_key: None,
+            chunks: None,
+            parts: None,
+            started_at: None,
+            base: None,
+            uncompressed_size: None,
+            source_type: None,
+            metadata: None,
+            checksums: None,
         });
 
         index_file.dumps.push(Dump {
@@ -1044,7 +1388,18 @@ mod tests {
             size: 0,
             created_at: (Utc::now() - Duration::days(5)).timestamp_millis() as u128,
             compressed: true,
+            compression: None,
             encrypted: false,
+            encryption_key_id: None,
+            encrypted_data_key: None,
+            chunks: None,
+            parts: None,
+            started_at: None,
+            base: None,
+            uncompressed_size: None,
+            source_type: None,
+            metadata: None,
+            checksums: None,
         });
 
         index_file.dumps.push(Dump {
@@ -1052,7 +1407,18 @@ mod tests {
             size: 0,
             created_at: epoch_millis(),
             compressed: true,
+            compression: None,
             encrypted: false,
+            encryption_key_id: None,
+            encrypted_data_key: None,
+            chunks: None,
+            parts: None,
+            started_at: None,
+            base: None,
+            uncompressed_size: None,
+            source_type: None,
+            metadata: None,
+            checksums: None,
         });
 
         assert!(s3.write_index_file(&index_file).is_ok());
@@ -1087,6 +1453,7 @@ mod tests {
                 dump: None,
                 older_than: None,
                 keep_last: Some(2),
+                force: false,
             })
             .is_ok());
 
@@ -1100,6 +1467,7 @@ mod tests {
                 dump: None,
                 older_than: None,
                 keep_last: Some(1),
+                force: false,
             })
             .is_ok());
 
@@ -1170,7 +1538,18 @@ mod tests {
                 size: 62279,
                 created_at: 1234,
                 compressed: true,
-                encrypted: false
+                compression: None,
+                encrypted: false,
+                encryption_key_id: None,
+                encrypted_data_key: None,
+                chunks: None,
+                parts: None,
+                started_at: None,
+                base: None,
+                uncompressed_size: None,
+                source_type: None,
+                metadata: None,
+                checksums: None,
             })
         );
         assert_eq!(
@@ -1180,7 +1559,18 @@ mod tests {
                 size: 62283,
                 created_at: 5678,
                 compressed: true,
-                encrypted: false
+                compression: None,
+                encrypted: false,
+                encryption_key_id: None,
+                encrypted_data_key: None,
+                chunks: None,
+                parts: None,
+                started_at: None,
+                base: None,
+                uncompressed_size: None,
+                source_type: None,
+                metadata: None,
+                checksums: None,
             })
         );
     }
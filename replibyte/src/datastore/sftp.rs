@@ -0,0 +1,433 @@
+use std::io::{Error, ErrorKind};
+
+use log::{debug, error, info};
+use russh_sftp::client::error::Error as SftpError;
+use russh_sftp::client::SftpSession;
+use russh_sftp::protocol::StatusCode;
+use serde_json::Value;
+use tokio::io::AsyncWriteExt;
+use tokio::runtime::Runtime;
+
+use crate::config::SshConfig;
+use crate::connector::Connector;
+use crate::datastore::{
+    chunk_key, dump_part_number, Compression, Datastore, Dump, EncodedPart, EncryptionKeys,
+    IndexFile, CHUNKS_DIR,
+};
+use crate::errors::ReplibyteError;
+use crate::proxy::Proxy;
+use crate::ssh::{SshConnector, SshSession};
+use crate::types::Bytes;
+use crate::utils::epoch_millis;
+
+use super::INDEX_FILE_NAME;
+
+/// Sftp stores the dumps in a directory of a remote host, over the SFTP subsystem of an SSH connection.
+pub struct Sftp {
+    dir: String,
+    dump_name: String,
+    compression: Option<Compression>,
+    encryption_keys: EncryptionKeys,
+    sftp: SftpSession,
+    // the SFTP channel is carried by the SSH session, which must be kept open along with it
+    _session: SshSession,
+    // the SSH connection is served by its own runtime, dropped last
+    runtime: Runtime,
+}
+
+impl Sftp {
+    pub fn new<S: Into<String>>(
+        dir: S,
+        ssh: &SshConfig,
+        proxy: Option<&Proxy>,
+    ) -> Result<Self, ReplibyteError> {
+        let connector = SshConnector::new(ssh, proxy)?;
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("sftp")
+            .enable_all()
+            .build()?;
+
+        let (session, sftp) = runtime.block_on(async {
+            let session = connector.connect().await?;
+            let stream = session.open_subsystem("sftp").await?;
+            let sftp = SftpSession::new(stream).await.map_err(sftp_error)?;
+            Ok::<_, ReplibyteError>((session, sftp))
+        })?;
+
+        Ok(Sftp {
+            dir: dir.into(),
+            dump_name: format!("dump-{}", epoch_millis()),
+            compression: Some(Compression::default()),
+            encryption_keys: EncryptionKeys::default(),
+            sftp,
+            _session: session,
+            runtime,
+        })
+    }
+
+    fn create_index_file(&self) -> Result<IndexFile, ReplibyteError> {
+        match self.index_file() {
+            Ok(index_file) => Ok(index_file),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                info!("creating a new index_file");
+                let index_file = IndexFile::new();
+                let _ = self.write_index_file(&index_file)?;
+                Ok(index_file)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// create the directory and its missing parents, as `mkdir -p` does
+    fn create_dir_all(&self, dir: &str) -> Result<(), ReplibyteError> {
+        self.runtime.block_on(async {
+            for dir in parent_dirs(dir) {
+                if !self
+                    .sftp
+                    .try_exists(dir.as_str())
+                    .await
+                    .map_err(sftp_error)?
+                {
+                    debug!("creating directory {}", dir);
+                    self.sftp
+                        .create_dir(dir.as_str())
+                        .await
+                        .map_err(sftp_error)?;
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    fn read_file(&self, path: &str) -> Result<Bytes, ReplibyteError> {
+        self.runtime
+            .block_on(self.sftp.read(path))
+            .map_err(sftp_error)
+    }
+
+    fn write_file(&self, path: &str, data: &[u8]) -> Result<(), ReplibyteError> {
+        self.runtime.block_on(async {
+            let mut file = self.sftp.create(path).await.map_err(sftp_error)?;
+            file.write_all(data).await?;
+            // the file is closed on shutdown, which makes sure that the data has been written
+            Ok(file.shutdown().await?)
+        })
+    }
+
+    fn list_files(&self, dir: &str) -> Result<Vec<String>, ReplibyteError> {
+        let entries = self
+            .runtime
+            .block_on(self.sftp.read_dir(dir))
+            .map_err(sftp_error)?;
+
+        Ok(entries
+            .map(|entry| format!("{}/{}", dir, entry.file_name()))
+            .collect())
+    }
+}
+
+impl Connector for Sftp {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        debug!("initializing datastore");
+
+        // create the datastore directory if needed
+        self.create_dir_all(self.dir.as_str()).map_err(|err| {
+            error!("error while creating the datastore directory: {}", self.dir);
+            err
+        })?;
+
+        self.create_index_file().map(|_| ())
+    }
+}
+
+impl Datastore for Sftp {
+    fn index_file(&self) -> Result<IndexFile, ReplibyteError> {
+        let data = self.read_file(format!("{}/{}", self.dir, INDEX_FILE_NAME).as_str())?;
+        let index_file: IndexFile = serde_json::from_slice(data.as_slice())?;
+        Ok(index_file)
+    }
+
+    fn raw_index_file(&self) -> Result<Value, ReplibyteError> {
+        let data = self.read_file(format!("{}/{}", self.dir, INDEX_FILE_NAME).as_str())?;
+        let raw_index_file = serde_json::from_slice(data.as_slice())?;
+        Ok(raw_index_file)
+    }
+
+    fn write_index_file(&self, index_file: &IndexFile) -> Result<(), ReplibyteError> {
+        let index_file_json = serde_json::to_vec(index_file)?;
+        self.write_file(
+            format!("{}/{}", self.dir, INDEX_FILE_NAME).as_str(),
+            index_file_json.as_slice(),
+        )
+    }
+
+    fn write_raw_index_file(&self, raw_index_file: &Value) -> Result<(), ReplibyteError> {
+        let index_file_json = serde_json::to_vec(raw_index_file)?;
+        self.write_file(
+            format!("{}/{}", self.dir, INDEX_FILE_NAME).as_str(),
+            index_file_json.as_slice(),
+        )
+    }
+
+    fn write_encoded(&self, file_part: u16, part: EncodedPart) -> Result<(), ReplibyteError> {
+        let EncodedPart { data, checksum } = part;
+
+        let data_size = data.len();
+        let dump_dir_path = format!("{}/{}", self.dir, self.dump_name);
+        let dump_file_path = format!("{}/{}.dump", dump_dir_path, file_part);
+
+        // create the dump directory if needed
+        self.create_dir_all(dump_dir_path.as_str()).map_err(|err| {
+            error!("error while creating the dump directory: {}", dump_dir_path);
+            err
+        })?;
+
+        debug!("writing dump at: {}", dump_file_path);
+        let _ = self
+            .write_file(dump_file_path.as_str(), data.as_slice())
+            .map_err(|err| {
+                error!("error while writing dump at: {}", dump_file_path);
+                err
+            })?;
+
+        // update index file
+        let mut index_file = self.index_file()?;
+
+        let mut new_dump = Dump {
+            directory_name: self.dump_name.to_string(),
+            size: 0,
+            created_at: epoch_millis(),
+            compressed: self.compression().is_some(),
+            compression: self.compression().map(|compression| compression.algorithm),
+            encrypted: self.encryption_keys().current().is_some(),
+            encryption_key_id: self
+                .encryption_keys()
+                .current()
+                .and_then(|key| key.id.clone()),
+            encrypted_data_key: self
+                .encryption_keys()
+                .current()
+                .and_then(|key| key.encrypted_data_key.clone()),
+            chunks: None,
+            parts: None,
+            started_at: None,
+            base: None,
+            uncompressed_size: None,
+            source_type: None,
+            metadata: None,
+            checksums: None,
+        };
+
+        // find or create dump
+        let dump = index_file
+            .dumps
+            .iter_mut()
+            .find(|b| b.directory_name.as_str() == self.dump_name)
+            .unwrap_or(&mut new_dump);
+
+        dump.checksums.get_or_insert_with(Vec::new).push(checksum);
+
+        if dump.size == 0 {
+            // it means it's a new dump.
+            // We need to add it into the index_file.dumps
+            new_dump.size = data_size;
+            index_file.dumps.push(new_dump);
+        } else {
+            // update total dump size
+            dump.size += data_size;
+        }
+
+        // save index file
+        self.write_index_file(&index_file)
+    }
+
+    fn read_encoded(
+        &self,
+        dump: &Dump,
+        data_callback: &mut dyn FnMut(Bytes) -> Result<(), ReplibyteError>,
+    ) -> Result<(), ReplibyteError> {
+        let mut paths =
+            self.list_files(format!("{}/{}", self.dir, dump.directory_name).as_str())?;
+        // the parts are read in order, `10.dump` comes after `9.dump`
+        paths.sort_by_key(|path| dump_part_number(path.as_str()));
+
+        for path in paths {
+            let _ = data_callback(self.read_file(path.as_str())?)?;
+        }
+
+        Ok(())
+    }
+
+    fn compression(&self) -> Option<Compression> {
+        self.compression
+    }
+
+    fn set_compression(&mut self, compression: Option<Compression>) {
+        if compression.is_none() {
+            info!("disable datastore compression");
+        }
+
+        self.compression = compression;
+    }
+
+    fn encryption_keys(&self) -> &EncryptionKeys {
+        &self.encryption_keys
+    }
+
+    fn set_encryption_keys(&mut self, keys: EncryptionKeys) {
+        info!("set datastore encryption_keys");
+        self.encryption_keys = keys;
+    }
+
+    fn set_dump_name(&mut self, name: String) {
+        self.dump_name = name
+    }
+
+    fn delete_dump_files(&self, name: &str) -> Result<(), ReplibyteError> {
+        let dump_dir_path = format!("{}/{}", self.dir, name);
+        let _ = self
+            .list_files(dump_dir_path.as_str())
+            .and_then(|paths| {
+                self.runtime.block_on(async {
+                    for path in paths {
+                        self.sftp
+                            .remove_file(path.as_str())
+                            .await
+                            .map_err(sftp_error)?;
+                    }
+
+                    self.sftp
+                        .remove_dir(dump_dir_path.as_str())
+                        .await
+                        .map_err(sftp_error)
+                })
+            })
+            .map_err(|err| {
+                error!("error while removing the dump directory: {}", dump_dir_path);
+                err
+            })?;
+
+        Ok(())
+    }
+
+    fn write_chunk(&self, hash: &str, data: Bytes) -> Result<(), ReplibyteError> {
+        let chunks_dir_path = format!("{}/{}", self.dir, CHUNKS_DIR);
+        let chunk_path = format!("{}/{}", self.dir, chunk_key(hash));
+
+        // create the chunks directory if needed
+        self.create_dir_all(chunks_dir_path.as_str())
+            .map_err(|err| {
+                error!(
+                    "error while creating the chunks directory: {}",
+                    chunks_dir_path
+                );
+                err
+            })?;
+
+        debug!("writing chunk at: {}", chunk_path);
+        self.write_file(chunk_path.as_str(), data.as_slice())
+    }
+
+    fn read_chunk(&self, hash: &str) -> Result<Bytes, ReplibyteError> {
+        self.read_file(format!("{}/{}", self.dir, chunk_key(hash)).as_str())
+    }
+
+    fn delete_chunk(&self, hash: &str) -> Result<(), ReplibyteError> {
+        let chunk_path = format!("{}/{}", self.dir, chunk_key(hash));
+        self.runtime
+            .block_on(self.sftp.remove_file(chunk_path.as_str()))
+            .map_err(sftp_error)
+    }
+}
+
+fn sftp_error(err: SftpError) -> ReplibyteError {
+    match err {
+        SftpError::Status(status) => {
+            let kind = match status.status_code {
+                StatusCode::NoSuchFile => ErrorKind::NotFound,
+                StatusCode::PermissionDenied => ErrorKind::PermissionDenied,
+                _ => ErrorKind::Other,
+            };
+
+            ReplibyteError::from(Error::new(
+                kind,
+                format!(
+                    "sftp error: {} {}",
+                    status.status_code, status.error_message
+                ),
+            ))
+        }
+        err => ReplibyteError::Datastore(format!("sftp error: {}", err)),
+    }
+}
+
+/// return the directory and its parents, from the outermost one
+fn parent_dirs(dir: &str) -> Vec<String> {
+    let mut dirs = vec![];
+    let mut path = if dir.starts_with('/') {
+        String::from("/")
+    } else {
+        String::new()
+    };
+
+    for name in dir.split('/').filter(|name| !name.is_empty()) {
+        if !path.is_empty() && !path.ends_with('/') {
+            path.push('/');
+        }
+
+        path.push_str(name);
+        dirs.push(path.clone());
+    }
+
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+
+    use russh_sftp::client::error::Error as SftpError;
+    use russh_sftp::protocol::{Status, StatusCode};
+
+    use crate::datastore::sftp::{parent_dirs, sftp_error};
+
+    #[test]
+    fn parent_directories() {
+        assert_eq!(
+            parent_dirs("/var/backups/replibyte"),
+            vec!["/var", "/var/backups", "/var/backups/replibyte"]
+        );
+        assert_eq!(
+            parent_dirs("backups//replibyte/"),
+            vec!["backups", "backups/replibyte"]
+        );
+        assert_eq!(parent_dirs("/"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn sftp_errors() {
+        let status = |status_code| Status {
+            id: 1,
+            status_code,
+            error_message: "error".to_string(),
+            language_tag: "en-US".to_string(),
+        };
+
+        assert_eq!(
+            sftp_error(SftpError::Status(status(StatusCode::NoSuchFile))).kind(),
+            ErrorKind::NotFound
+        );
+        assert_eq!(
+            sftp_error(SftpError::Status(status(StatusCode::PermissionDenied))).kind(),
+            ErrorKind::PermissionDenied
+        );
+        assert_eq!(
+            sftp_error(SftpError::Status(status(StatusCode::Failure))).kind(),
+            ErrorKind::Other
+        );
+        assert_eq!(sftp_error(SftpError::Timeout).kind(), ErrorKind::Other);
+    }
+}
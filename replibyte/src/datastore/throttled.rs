@@ -0,0 +1,148 @@
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::connector::Connector;
+use crate::datastore::{
+    Compression, Datastore, Dump, EncodedPart, Encoder, EncryptionKeys, IndexFile,
+};
+use crate::errors::ReplibyteError;
+use crate::throttle::Throttle;
+use crate::types::Bytes;
+
+/// Throttled limits the bandwidth of the dump parts and chunks written to and read from a datastore.
+/// The throttle can be shared by several datastores, e.g. the replicas of a dump.
+pub struct Throttled {
+    datastore: Box<dyn Datastore>,
+    throttle: Arc<Throttle>,
+}
+
+impl Throttled {
+    pub fn new(datastore: Box<dyn Datastore>, throttle: Arc<Throttle>) -> Self {
+        Throttled {
+            datastore,
+            throttle,
+        }
+    }
+}
+
+impl Connector for Throttled {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        self.datastore.init()
+    }
+}
+
+impl Datastore for Throttled {
+    fn index_file(&self) -> Result<IndexFile, ReplibyteError> {
+        self.datastore.index_file()
+    }
+
+    fn raw_index_file(&self) -> Result<Value, ReplibyteError> {
+        self.datastore.raw_index_file()
+    }
+
+    fn write_index_file(&self, index_file: &IndexFile) -> Result<(), ReplibyteError> {
+        self.datastore.write_index_file(index_file)
+    }
+
+    fn write_raw_index_file(&self, raw_index_file: &Value) -> Result<(), ReplibyteError> {
+        self.datastore.write_raw_index_file(raw_index_file)
+    }
+
+    fn encoder(&self) -> Encoder {
+        self.datastore.encoder()
+    }
+
+    fn write_encoded(&self, file_part: u16, part: EncodedPart) -> Result<(), ReplibyteError> {
+        self.throttle.consume(part.data.len());
+        self.datastore.write_encoded(file_part, part)
+    }
+
+    fn read_encoded(
+        &self,
+        dump: &Dump,
+        data_callback: &mut dyn FnMut(Bytes) -> Result<(), ReplibyteError>,
+    ) -> Result<(), ReplibyteError> {
+        self.datastore.read_encoded(dump, &mut |data| {
+            self.throttle.consume(data.len());
+            data_callback(data)
+        })
+    }
+
+    fn compression(&self) -> Option<Compression> {
+        self.datastore.compression()
+    }
+
+    fn set_compression(&mut self, compression: Option<Compression>) {
+        self.datastore.set_compression(compression)
+    }
+
+    fn encryption_keys(&self) -> &EncryptionKeys {
+        self.datastore.encryption_keys()
+    }
+
+    fn set_encryption_keys(&mut self, keys: EncryptionKeys) {
+        self.datastore.set_encryption_keys(keys)
+    }
+
+    fn set_dump_name(&mut self, name: String) {
+        self.datastore.set_dump_name(name)
+    }
+
+    fn delete_dump_files(&self, name: &str) -> Result<(), ReplibyteError> {
+        self.datastore.delete_dump_files(name)
+    }
+
+    fn write_chunk(&self, hash: &str, data: Bytes) -> Result<(), ReplibyteError> {
+        self.throttle.consume(data.len());
+        self.datastore.write_chunk(hash, data)
+    }
+
+    fn read_chunk(&self, hash: &str) -> Result<Bytes, ReplibyteError> {
+        let data = self.datastore.read_chunk(hash)?;
+        self.throttle.consume(data.len());
+        Ok(data)
+    }
+
+    fn delete_chunk(&self, hash: &str) -> Result<(), ReplibyteError> {
+        self.datastore.delete_chunk(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use tempfile::tempdir;
+
+    use crate::connector::Connector;
+    use crate::datastore::local_disk::LocalDisk;
+    use crate::datastore::throttled::Throttled;
+    use crate::datastore::{Datastore, ReadOptions};
+    use crate::throttle::Throttle;
+
+    #[test]
+    fn write_and_read() {
+        let dir = tempdir().unwrap();
+        let local_disk = LocalDisk::new(dir.path().to_str().unwrap());
+        let mut datastore = Throttled::new(Box::new(local_disk), Arc::new(Throttle::new(10_000)));
+        let _ = datastore.init().unwrap();
+        datastore.set_compression(None);
+        datastore.set_dump_name("dump-1".to_string());
+
+        let started_at = Instant::now();
+        for part in 1..=3 {
+            let _ = datastore.write(part, vec![b'a'; 1000]).unwrap();
+        }
+
+        let mut data = vec![];
+        let _ = datastore
+            .read(&ReadOptions::Latest, &mut |part| data.extend(part))
+            .unwrap();
+
+        // 6000 bytes transferred at 10000 bytes per second, the first part is not waited for
+        assert_eq!(data, vec![b'a'; 3000]);
+        assert!(started_at.elapsed() >= Duration::from_millis(500));
+    }
+}
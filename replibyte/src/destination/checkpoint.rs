@@ -0,0 +1,390 @@
+use std::collections::{HashSet, VecDeque};
+use std::fs::{create_dir_all, read, remove_file, write};
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use dump_parser::utils::{list_sql_queries_from_dump_reader, ListQueryResult};
+use serde::{Deserialize, Serialize};
+
+use crate::connector::Connector;
+use crate::destination::{is_session_query, Destination};
+use crate::errors::ReplibyteError;
+use crate::tasks::Progress;
+use crate::types::Bytes;
+
+/// Checkpoint records the chunks and the tables applied by a restore into a local file,
+/// to resume the restore from where it stopped if it is interrupted.
+pub struct Checkpoint {
+    path: PathBuf,
+    state: Mutex<CheckpointState>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct CheckpointState {
+    dump_name: String,
+    applied: HashSet<String>,
+}
+
+impl Checkpoint {
+    /// start a new checkpoint for the dump, a previous checkpoint of the same dump is discarded
+    pub fn new(dump_name: &str) -> Result<Self, ReplibyteError> {
+        Self::new_at(checkpoint_path(dump_name), dump_name)
+    }
+
+    /// load the checkpoint of an interrupted restore of the dump
+    pub fn load(dump_name: &str) -> Result<Self, ReplibyteError> {
+        Self::load_at(checkpoint_path(dump_name), dump_name)
+    }
+
+    fn new_at(path: PathBuf, dump_name: &str) -> Result<Self, ReplibyteError> {
+        let checkpoint = Checkpoint {
+            path,
+            state: Mutex::new(CheckpointState {
+                dump_name: dump_name.to_string(),
+                applied: HashSet::new(),
+            }),
+        };
+
+        let _ = checkpoint.save(&checkpoint.state.lock().unwrap())?;
+
+        Ok(checkpoint)
+    }
+
+    fn load_at(path: PathBuf, dump_name: &str) -> Result<Self, ReplibyteError> {
+        let data = read(&path).map_err(|_| {
+            ReplibyteError::Config(format!(
+                "no interrupted restore to resume for dump '{}'",
+                dump_name
+            ))
+        })?;
+
+        let state: CheckpointState = serde_json::from_reader(BufReader::new(data.as_slice()))?;
+
+        if state.dump_name != dump_name {
+            return Err(ReplibyteError::Config(format!(
+                "the checkpoint at {:?} is for dump '{}', not '{}'",
+                path, state.dump_name, dump_name
+            )));
+        }
+
+        Ok(Checkpoint {
+            path,
+            state: Mutex::new(state),
+        })
+    }
+
+    /// true if nothing has been applied yet, the destination must then be initialized
+    pub fn is_empty(&self) -> bool {
+        self.state.lock().unwrap().applied.is_empty()
+    }
+
+    pub fn is_applied(&self, key: &str) -> bool {
+        self.state.lock().unwrap().applied.contains(key)
+    }
+
+    /// record that the chunk or the table identified by the key has been applied
+    pub fn mark_applied(&self, key: &str) -> Result<(), ReplibyteError> {
+        let mut state = self.state.lock().unwrap();
+        let _ = state.applied.insert(key.to_string());
+        self.save(&state)
+    }
+
+    /// remove the checkpoint once the restore is done
+    pub fn remove(self) -> Result<(), ReplibyteError> {
+        Ok(remove_file(&self.path)?)
+    }
+
+    fn save(&self, state: &CheckpointState) -> Result<(), ReplibyteError> {
+        if let Some(dir) = self.path.parent() {
+            let _ = create_dir_all(dir)?;
+        }
+
+        Ok(write(&self.path, serde_json::to_vec(state)?)?)
+    }
+}
+
+fn checkpoint_path(dump_name: &str) -> PathBuf {
+    std::env::temp_dir()
+        .join("replibyte")
+        .join(format!("restore-{}.json", dump_name))
+}
+
+/// chunks written into the destination whose queries may not be applied yet, e.g. the ones still in the stdin pipe
+/// of the `psql` process. A chunk is marked as applied once the destination has read the next ones.
+const UNCONFIRMED_CHUNKS: usize = 2;
+
+/// CheckpointDestination streams the chunks into the wrapped destination, through a single connection, and records
+/// the applied chunks into the checkpoint on the side. The chunks already applied are skipped.
+/// A checkpoint is only recorded at the end of a chunk outside of a `COPY ... FROM stdin` block, so a resumed
+/// restore never starts in the middle of one.
+pub struct CheckpointDestination<'a, D>
+where
+    D: Destination,
+{
+    destination: &'a mut D,
+    checkpoint: &'a Checkpoint,
+}
+
+impl<'a, D> CheckpointDestination<'a, D>
+where
+    D: Destination,
+{
+    pub fn new(destination: &'a mut D, checkpoint: &'a Checkpoint) -> Self {
+        CheckpointDestination {
+            destination,
+            checkpoint,
+        }
+    }
+}
+
+impl<'a, D> Connector for CheckpointDestination<'a, D>
+where
+    D: Destination,
+{
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        // a resumed destination has already been initialized (and wiped)
+        if self.checkpoint.is_empty() {
+            self.destination.init()
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<'a, D> Destination for CheckpointDestination<'a, D>
+where
+    D: Destination,
+{
+    fn write_chunks(&self, chunks: &mut dyn Iterator<Item = Bytes>) -> Result<(), ReplibyteError> {
+        // the session queries of the skipped chunks are run before the first restored one
+        let mut session_queries: Bytes = vec![];
+        let mut copy_progress = Progress::default();
+        // keys of the chunks written into the destination, and whether they end outside of a COPY block
+        let mut unconfirmed_chunks = VecDeque::new();
+        // keys of the confirmed chunks ending in a COPY block, marked with the chunk ending it
+        let mut confirmed_keys = vec![];
+        let mut checkpoint_error = None;
+
+        let mut restored_chunks = chunks
+            .enumerate()
+            .map_while(|(idx, chunk)| {
+                let key = format!("chunk-{}", idx);
+
+                if self.checkpoint.is_applied(key.as_str()) {
+                    session_queries
+                        .extend(chunk_session_queries(chunk.as_slice(), &mut copy_progress));
+                    return Some(None);
+                }
+
+                copy_progress.count_chunk_rows(chunk.as_slice());
+                unconfirmed_chunks.push_back((key, !copy_progress.is_copy()));
+
+                while unconfirmed_chunks.len() > UNCONFIRMED_CHUNKS {
+                    let (key, ends_outside_copy) = match unconfirmed_chunks.pop_front() {
+                        Some(unconfirmed_chunk) => unconfirmed_chunk,
+                        None => break,
+                    };
+                    confirmed_keys.push(key);
+
+                    if ends_outside_copy {
+                        for key in confirmed_keys.drain(..) {
+                            if let Err(err) = self.checkpoint.mark_applied(key.as_str()) {
+                                checkpoint_error = Some(err);
+                                return None;
+                            }
+                        }
+                    }
+                }
+
+                let mut data = std::mem::take(&mut session_queries);
+                data.extend_from_slice(chunk.as_slice());
+                Some(Some(data))
+            })
+            .flatten();
+
+        let _ = self.destination.write_chunks(&mut restored_chunks)?;
+
+        match checkpoint_error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+/// the session queries of a chunk (e.g. `SET client_encoding = 'UTF8';`), outside of its COPY blocks.
+/// `copy_progress` tells if the chunk starts in a COPY block, and if the next one does.
+fn chunk_session_queries(chunk: &[u8], copy_progress: &mut Progress) -> Bytes {
+    let mut session_queries = vec![];
+    let mut queries = vec![];
+
+    let mut add_session_queries = |queries: &mut Bytes| {
+        let _ = list_sql_queries_from_dump_reader(BufReader::new(queries.as_slice()), |query| {
+            let query = query.trim_start();
+
+            if is_session_query(query) {
+                session_queries.extend_from_slice(query.as_bytes());
+                session_queries.push(b'\n');
+            }
+
+            ListQueryResult::Continue
+        });
+
+        queries.clear();
+    };
+
+    for line in chunk.split(|byte| *byte == b'\n') {
+        let is_copy = copy_progress.is_copy();
+        copy_progress.count_rows(line);
+
+        match (is_copy, copy_progress.is_copy()) {
+            (false, false) => {
+                queries.extend_from_slice(line);
+                queries.push(b'\n');
+            }
+            // the queries before a COPY block are complete
+            (false, true) => add_session_queries(&mut queries),
+            _ => {}
+        }
+    }
+
+    add_session_queries(&mut queries);
+
+    session_queries
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+
+    use crate::connector::Connector;
+    use crate::destination::checkpoint::{Checkpoint, CheckpointDestination};
+    use crate::destination::Destination;
+    use crate::errors::ReplibyteError;
+    use crate::types::Bytes;
+
+    #[derive(Default)]
+    struct RecordingDestination {
+        initialized: bool,
+        chunks: RefCell<Vec<String>>,
+        /// the chunks are streamed through a single connection
+        connections: Cell<usize>,
+    }
+
+    impl Connector for RecordingDestination {
+        fn init(&mut self) -> Result<(), ReplibyteError> {
+            self.initialized = true;
+            Ok(())
+        }
+    }
+
+    impl Destination for RecordingDestination {
+        fn write_chunks(
+            &self,
+            chunks: &mut dyn Iterator<Item = Bytes>,
+        ) -> Result<(), ReplibyteError> {
+            self.connections.set(self.connections.get() + 1);
+
+            for chunk in chunks {
+                self.chunks
+                    .borrow_mut()
+                    .push(String::from_utf8(chunk).unwrap());
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("restore-dump-1.json");
+
+        let checkpoint = Checkpoint::new_at(path.clone(), "dump-1").unwrap();
+        assert!(checkpoint.is_empty());
+        checkpoint.mark_applied("chunk-0").unwrap();
+
+        let checkpoint = Checkpoint::load_at(path.clone(), "dump-1").unwrap();
+        assert!(!checkpoint.is_empty());
+        assert!(checkpoint.is_applied("chunk-0"));
+        assert!(!checkpoint.is_applied("chunk-1"));
+
+        assert!(Checkpoint::load_at(path.clone(), "dump-2").is_err());
+
+        checkpoint.remove().unwrap();
+        assert!(Checkpoint::load_at(path, "dump-1").is_err());
+    }
+
+    #[test]
+    fn skip_applied_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let checkpoint = Checkpoint::new_at(dir.path().join("checkpoint.json"), "dump-1").unwrap();
+        checkpoint.mark_applied("chunk-0").unwrap();
+
+        let mut destination = RecordingDestination::default();
+
+        let mut checkpoint_destination = CheckpointDestination::new(&mut destination, &checkpoint);
+        checkpoint_destination.init().unwrap();
+
+        let mut chunks = vec![
+            b"SET client_encoding = 'UTF8';\nCREATE TABLE users (id integer);\n".to_vec(),
+            b"INSERT INTO users (id) VALUES (1);\n".to_vec(),
+        ]
+        .into_iter();
+
+        checkpoint_destination.write_chunks(&mut chunks).unwrap();
+
+        assert!(!destination.initialized);
+        assert_eq!(
+            destination.chunks.into_inner(),
+            vec!["SET client_encoding = 'UTF8';\nINSERT INTO users (id) VALUES (1);\n"]
+        );
+    }
+
+    #[test]
+    fn checkpoint_outside_copy_blocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let checkpoint = Checkpoint::new_at(dir.path().join("checkpoint.json"), "dump-1").unwrap();
+
+        let mut destination = RecordingDestination::default();
+        let checkpoint_destination = CheckpointDestination::new(&mut destination, &checkpoint);
+
+        let mut chunks = vec![
+            b"SET client_encoding = 'UTF8';\nCREATE TABLE users (id integer);\n".to_vec(),
+            // the COPY block is split between two chunks
+            b"COPY users (id) FROM stdin;\n1\n".to_vec(),
+            b"2\n\\.\n".to_vec(),
+            b"INSERT INTO users (id) VALUES (3);\n".to_vec(),
+            b"INSERT INTO users (id) VALUES (4);\n".to_vec(),
+            b"INSERT INTO users (id) VALUES (5);\n".to_vec(),
+        ]
+        .into_iter();
+
+        checkpoint_destination.write_chunks(&mut chunks).unwrap();
+        assert_eq!(destination.connections.get(), 1);
+        assert_eq!(destination.chunks.borrow().len(), 6);
+
+        // the last chunks may still be in the pipe of the destination when it is interrupted
+        assert!(checkpoint.is_applied("chunk-0"));
+        assert!(checkpoint.is_applied("chunk-1"));
+        assert!(checkpoint.is_applied("chunk-3"));
+        assert!(!checkpoint.is_applied("chunk-4"));
+
+        // the chunk ending in the COPY block is only marked with the chunk ending it
+        let checkpoint = Checkpoint::new_at(dir.path().join("checkpoint.json"), "dump-1").unwrap();
+        let mut destination = RecordingDestination::default();
+        let checkpoint_destination = CheckpointDestination::new(&mut destination, &checkpoint);
+
+        let mut chunks = vec![
+            b"CREATE TABLE users (id integer);\n".to_vec(),
+            b"COPY users (id) FROM stdin;\n1\n".to_vec(),
+            b"2\n\\.\n".to_vec(),
+            b"INSERT INTO users (id) VALUES (3);\n".to_vec(),
+        ]
+        .into_iter();
+
+        checkpoint_destination.write_chunks(&mut chunks).unwrap();
+        assert!(checkpoint.is_applied("chunk-0"));
+        assert!(!checkpoint.is_applied("chunk-1"));
+    }
+}
@@ -1,8 +1,9 @@
-use std::io::{Error, ErrorKind};
 use std::process::{Child, Command, Stdio};
 use std::thread;
 use std::time::Duration;
 
+use crate::errors::ReplibyteError;
+
 pub const DOCKER_BINARY_NAME: &str = "docker";
 
 pub struct Image {
@@ -13,6 +14,24 @@ pub struct Image {
 pub struct ContainerOptions {
     pub host_port: u16,
     pub container_port: u16,
+    /// name of the container, Docker generates one otherwise
+    pub name: Option<String>,
+    /// extra environment variables, as `KEY=VALUE`
+    pub env: Vec<String>,
+    /// volumes to mount, as `<host path>:<container path>`
+    pub volumes: Vec<String>,
+}
+
+impl ContainerOptions {
+    pub fn new(host_port: u16, container_port: u16) -> Self {
+        ContainerOptions {
+            host_port,
+            container_port,
+            name: None,
+            env: vec![],
+            volumes: vec![],
+        }
+    }
 }
 
 pub struct Container {
@@ -25,24 +44,8 @@ impl Container {
         options: &ContainerOptions,
         args: Vec<&str>,
         command: Option<Vec<&str>>,
-    ) -> Result<Container, Error> {
-        let port_mapping = format!("{}:{}", options.host_port, options.container_port);
-        let image_version = format!("{}:{}", image.name, image.tag);
-        let mut run_args = vec!["run", "-p", port_mapping.as_str()];
-
-        for arg in args {
-            run_args.push(arg);
-        }
-
-        run_args.push("-d");
-        run_args.push(image_version.as_str());
-
-        if let Some(command) = command {
-            for arg in command {
-                run_args.push(arg);
-            }
-        }
-
+    ) -> Result<Container, ReplibyteError> {
+        let run_args = run_args(image, options, args, command);
         let output = Command::new(DOCKER_BINARY_NAME).args(run_args).output()?;
 
         // FIX: this is a workaround to wait until the container is up
@@ -51,16 +54,24 @@ impl Container {
         match output.status.success() {
             true => match String::from_utf8(output.stdout) {
                 Ok(container_id) => Ok(Container { id: container_id }),
-                Err(err) => Err(Error::new(ErrorKind::Other, format!("{}", err))),
+                Err(err) => Err(ReplibyteError::Command(format!("{}", err))),
             },
             false => match String::from_utf8(output.stderr) {
-                Ok(stderr) => Err(Error::new(ErrorKind::Other, format!("{}", stderr))),
-                Err(err) => Err(Error::new(ErrorKind::Other, format!("{}", err))),
+                Ok(stderr) => Err(ReplibyteError::Command(format!("{}", stderr))),
+                Err(err) => Err(ReplibyteError::Command(format!("{}", err))),
             },
         }
     }
 
-    pub fn stop(&self) -> Result<(), Error> {
+    /// the name of the container if it is set, its short ID otherwise
+    pub fn display_name<'a>(&'a self, options: &'a ContainerOptions) -> &'a str {
+        match &options.name {
+            Some(name) => name.as_str(),
+            None => &self.id[..12],
+        }
+    }
+
+    pub fn stop(&self) -> Result<(), ReplibyteError> {
         let _process = Command::new(DOCKER_BINARY_NAME)
             .args(["stop", &self.id[..12]])
             .stdout(Stdio::null())
@@ -69,7 +80,7 @@ impl Container {
         Ok(())
     }
 
-    pub fn rm(&self) -> Result<(), Error> {
+    pub fn rm(&self) -> Result<(), ReplibyteError> {
         let _process = Command::new(DOCKER_BINARY_NAME)
             .args(["rm", "-f", &self.id[..12]])
             .stdout(Stdio::null())
@@ -81,17 +92,57 @@ impl Container {
         Ok(())
     }
 
-    pub fn exec(&self, cmd: &str) -> Result<Child, Error> {
-        Command::new(DOCKER_BINARY_NAME)
+    pub fn exec(&self, cmd: &str) -> Result<Child, ReplibyteError> {
+        Ok(Command::new(DOCKER_BINARY_NAME)
             .args(["exec", "-i", &self.id[..12], "/bin/bash", "-c", cmd])
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .spawn()
+            .spawn()?)
     }
 }
 
+/// arguments of `docker run`, the extra env vars come after `args` to override the default ones
+fn run_args(
+    image: &Image,
+    options: &ContainerOptions,
+    args: Vec<&str>,
+    command: Option<Vec<&str>>,
+) -> Vec<String> {
+    let mut run_args = vec![
+        "run".to_string(),
+        "-p".to_string(),
+        format!("{}:{}", options.host_port, options.container_port),
+    ];
+
+    if let Some(name) = &options.name {
+        run_args.push("--name".to_string());
+        run_args.push(name.clone());
+    }
+
+    run_args.extend(args.into_iter().map(|arg| arg.to_string()));
+
+    for env in &options.env {
+        run_args.push("-e".to_string());
+        run_args.push(env.clone());
+    }
+
+    for volume in &options.volumes {
+        run_args.push("-v".to_string());
+        run_args.push(volume.clone());
+    }
+
+    run_args.push("-d".to_string());
+    run_args.push(format!("{}:{}", image.name, image.tag));
+
+    if let Some(command) = command {
+        run_args.extend(command.into_iter().map(|arg| arg.to_string()));
+    }
+
+    run_args
+}
+
 /// checks if the `dockerd` daemon runs
-pub fn daemon_is_running() -> Result<(), Error> {
+pub fn daemon_is_running() -> Result<(), ReplibyteError> {
     let mut process = Command::new(DOCKER_BINARY_NAME)
         .args(["ps"])
         .stdout(Stdio::null())
@@ -102,25 +153,22 @@ pub fn daemon_is_running() -> Result<(), Error> {
             if exit_status.success() {
                 Ok(())
             } else {
-                Err(Error::new(
-                    ErrorKind::Other,
-                    format!(
-                        "cannot connect to the Docker daemon: exit_status {}",
-                        exit_status.to_string()
-                    ),
-                ))
+                Err(ReplibyteError::Connection(format!(
+                    "cannot connect to the Docker daemon: exit_status {}",
+                    exit_status.to_string()
+                )))
             }
         }
-        Err(err) => Err(Error::new(
-            ErrorKind::Other,
-            format!("cannot connect to the Docker daemon: {}", err),
-        )),
+        Err(err) => Err(ReplibyteError::Connection(format!(
+            "cannot connect to the Docker daemon: {}",
+            err
+        ))),
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Container, ContainerOptions, Image};
+    use super::{run_args, Container, ContainerOptions, Image};
 
     #[test]
     fn handle_containers() {
@@ -129,10 +177,7 @@ mod tests {
             tag: "13".to_string(),
         };
 
-        let options = ContainerOptions {
-            host_port: 5433,
-            container_port: 5432,
-        };
+        let options = ContainerOptions::new(5433, 5432);
 
         let args = vec![
             "-e",
@@ -147,4 +192,48 @@ mod tests {
         assert!(container.stop().is_ok());
         assert!(container.rm().is_ok());
     }
+
+    #[test]
+    fn docker_run_args() {
+        let image = Image {
+            name: "postgres".to_string(),
+            tag: "14".to_string(),
+        };
+
+        let mut options = ContainerOptions::new(5433, 5432);
+        assert_eq!(
+            run_args(&image, &options, vec!["-e", "POSTGRES_USER=root"], None),
+            vec![
+                "run",
+                "-p",
+                "5433:5432",
+                "-e",
+                "POSTGRES_USER=root",
+                "-d",
+                "postgres:14"
+            ]
+        );
+
+        options.name = Some("replibyte-dev".to_string());
+        options.env = vec!["TZ=UTC".to_string()];
+        options.volumes = vec!["/tmp/data:/data".to_string()];
+        assert_eq!(
+            run_args(&image, &options, vec![], Some(vec!["--port", "5432"])),
+            vec![
+                "run",
+                "-p",
+                "5433:5432",
+                "--name",
+                "replibyte-dev",
+                "-e",
+                "TZ=UTC",
+                "-v",
+                "/tmp/data:/data",
+                "-d",
+                "postgres:14",
+                "--port",
+                "5432"
+            ]
+        );
+    }
 }
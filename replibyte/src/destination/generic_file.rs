@@ -0,0 +1,34 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use crate::connector::Connector;
+use crate::destination::{pipe_chunks, Destination};
+use crate::errors::ReplibyteError;
+use crate::types::Bytes;
+
+/// Write the dump output to a file
+pub struct GenericFile {
+    path: PathBuf,
+}
+
+impl GenericFile {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        GenericFile {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl Connector for GenericFile {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        Ok(())
+    }
+}
+
+impl Destination for GenericFile {
+    fn write_chunks(&self, chunks: &mut dyn Iterator<Item = Bytes>) -> Result<(), ReplibyteError> {
+        let file = File::create(&self.path)?;
+        pipe_chunks(BufWriter::new(file), chunks)
+    }
+}
@@ -1,7 +1,8 @@
-use std::io::{stdout, Error, Write};
+use std::io::stdout;
 
 use crate::connector::Connector;
-use crate::destination::Destination;
+use crate::destination::{pipe_chunks, Destination};
+use crate::errors::ReplibyteError;
 use crate::types::Bytes;
 
 /// Stream dump output on stdout
@@ -20,15 +21,14 @@ impl Default for GenericStdout {
 }
 
 impl Connector for GenericStdout {
-    fn init(&mut self) -> Result<(), Error> {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
         Ok(())
     }
 }
 
 impl<'a> Destination for GenericStdout {
-    fn write(&self, data: Bytes) -> Result<(), Error> {
-        let mut stdout = stdout();
-        let _ = stdout.write_all(data.as_slice());
-        Ok(())
+    fn write_chunks(&self, chunks: &mut dyn Iterator<Item = Bytes>) -> Result<(), ReplibyteError> {
+        // e.g. the restore fails if the command the dump is piped into exits
+        pipe_chunks(stdout().lock(), chunks)
     }
 }
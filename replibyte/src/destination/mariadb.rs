@@ -0,0 +1,138 @@
+use std::process::{Command, Stdio};
+
+use crate::connector::Connector;
+use crate::destination::mysql::{
+    command_output, create_database_query, sync_auto_increments_query,
+    AUTO_INCREMENT_COLUMNS_QUERY, DISABLE_FOREIGN_KEY_CHECKS_QUERIES,
+};
+use crate::destination::{pipe_chunks, wrap_chunks, Destination};
+use crate::errors::ReplibyteError;
+use crate::types::Bytes;
+use crate::utils::{binary_exists, wait_for_command};
+
+pub struct MariaDB<'a> {
+    host: &'a str,
+    port: u16,
+    database: &'a str,
+    username: &'a str,
+    password: &'a str,
+    create_database: bool,
+    disable_foreign_key_checks: bool,
+}
+
+impl<'a> MariaDB<'a> {
+    pub fn new(
+        host: &'a str,
+        port: u16,
+        database: &'a str,
+        username: &'a str,
+        password: &'a str,
+        create_database: bool,
+        disable_foreign_key_checks: bool,
+    ) -> Self {
+        MariaDB {
+            host,
+            port,
+            database,
+            username,
+            password,
+            create_database,
+            disable_foreign_key_checks,
+        }
+    }
+
+    /// execute the query on the database and return its rows, tab separated
+    fn execute(&self, query: &str) -> Result<String, ReplibyteError> {
+        let output = Command::new("mariadb")
+            .args([
+                "-h",
+                self.host,
+                "-P",
+                self.port.to_string().as_str(),
+                "-u",
+                self.username,
+                &format!("-p{}", self.password),
+                "-N",
+                "-B",
+                "-e",
+                query,
+                self.database,
+            ])
+            .output()?;
+
+        command_output(output)
+    }
+}
+
+impl<'a> Connector for MariaDB<'a> {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        binary_exists("mariadb")?;
+
+        // test MariaDB connection, and create the database if it does not exist
+        let query = if self.create_database {
+            create_database_query(self.database)
+        } else {
+            "SELECT 1;".to_string()
+        };
+
+        let mut process = Command::new("mariadb")
+            .args([
+                "-h",
+                self.host,
+                "-P",
+                self.port.to_string().as_str(),
+                "-u",
+                self.username,
+                &format!("-p{}", self.password),
+                "-e",
+                query.as_str(),
+            ])
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        wait_for_command(&mut process)
+    }
+}
+
+impl<'a> Destination for MariaDB<'a> {
+    fn write_chunks(&self, chunks: &mut dyn Iterator<Item = Bytes>) -> Result<(), ReplibyteError> {
+        let mut process = Command::new("mariadb")
+            .args([
+                "-h",
+                self.host,
+                "-P",
+                self.port.to_string().as_str(),
+                "-u",
+                self.username,
+                &format!("-p{}", self.password),
+                self.database,
+            ])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()?;
+
+        let mut chunks = wrap_chunks(
+            chunks,
+            match self.disable_foreign_key_checks {
+                true => Some(DISABLE_FOREIGN_KEY_CHECKS_QUERIES),
+                false => None,
+            },
+        );
+
+        // if mariadb stops early, the error is returned by wait_for_command
+        let _ = pipe_chunks(process.stdin.take().unwrap(), &mut chunks);
+
+        wait_for_command(&mut process)
+    }
+
+    fn sync_sequences(&self) -> Result<(), ReplibyteError> {
+        let auto_increment_columns = self.execute(AUTO_INCREMENT_COLUMNS_QUERY)?;
+        let query = sync_auto_increments_query(auto_increment_columns.as_str());
+
+        if !query.is_empty() {
+            let _ = self.execute(query.as_str())?;
+        }
+
+        Ok(())
+    }
+}
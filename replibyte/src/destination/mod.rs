@@ -1,17 +1,248 @@
-use std::io::Error;
+use std::io::{BufReader, Write};
+
+use dump_parser::utils::{list_sql_queries_from_dump_reader, ListQueryResult};
 
 use crate::connector::Connector;
+use crate::errors::ReplibyteError;
+use crate::tasks::Progress;
 use crate::types::Bytes;
 
-mod docker;
+pub mod checkpoint;
+pub mod docker;
+pub mod generic_file;
 pub mod generic_stdout;
+pub mod mariadb;
 pub mod mongodb;
 pub mod mongodb_docker;
+pub mod mssql;
 pub mod mysql;
 pub mod mysql_docker;
 pub mod postgres;
 pub mod postgres_docker;
+pub mod postgres_native;
+pub mod sqlite;
+pub mod statement_filter;
+pub mod table_filter;
 
 pub trait Destination: Connector {
-    fn write(&self, data: Bytes) -> Result<(), Error>;
+    /// write a stream of chunks. Chunks are consumed one by one to keep the memory usage bounded,
+    /// and each chunk is made of complete queries.
+    fn write_chunks(&self, chunks: &mut dyn Iterator<Item = Bytes>) -> Result<(), ReplibyteError>;
+
+    /// set the sequences (or the auto increment counters) after the rows restored in their tables,
+    /// e.g. once a subset is restored. The destinations without sequences do nothing.
+    fn sync_sequences(&self) -> Result<(), ReplibyteError> {
+        Ok(())
+    }
+}
+
+/// pipe the chunks one by one into the writer (e.g. the stdin of a child process).
+/// The writer is dropped once all the chunks are written to signal the end of the stream.
+pub fn pipe_chunks<W: Write>(
+    mut writer: W,
+    chunks: &mut dyn Iterator<Item = Bytes>,
+) -> Result<(), ReplibyteError> {
+    for data in chunks {
+        writer.write_all(data.as_slice())?;
+    }
+
+    Ok(writer.flush()?)
+}
+
+/// the chunks between the queries of `session_queries`, run before and after them on the same connection,
+/// e.g. to disable the triggers while the chunks are restored
+pub fn wrap_chunks<'a>(
+    chunks: &'a mut dyn Iterator<Item = Bytes>,
+    session_queries: Option<(&str, &str)>,
+) -> impl Iterator<Item = Bytes> + 'a {
+    let (before, after) = match session_queries {
+        Some((before, after)) => (
+            Some(before.as_bytes().to_vec()),
+            Some(after.as_bytes().to_vec()),
+        ),
+        None => (None, None),
+    };
+
+    before.into_iter().chain(chunks).chain(after)
+}
+
+/// write into the destination only the queries matching the predicate, the chunks without any query left are skipped.
+/// Chunks must be made of complete SQL queries, a COPY block can go on in the next chunks:
+/// its rows are written as they are if its `COPY ... FROM stdin;` query is selected.
+pub fn write_filtered_chunks<D, P>(
+    destination: &D,
+    chunks: &mut dyn Iterator<Item = Bytes>,
+    is_selected: P,
+) -> Result<(), ReplibyteError>
+where
+    D: Destination + ?Sized,
+    P: Fn(&str) -> bool,
+{
+    let mut filter_error = None;
+    let mut copy_block = CopyBlock::default();
+
+    let mut filtered_chunks = chunks
+        .map_while(
+            |chunk| match filter_chunk(chunk.as_slice(), &mut copy_block, &is_selected) {
+                Ok(filtered_chunk) => Some(filtered_chunk),
+                Err(err) => {
+                    filter_error = Some(err);
+                    None
+                }
+            },
+        )
+        .filter(|filtered_chunk| !filtered_chunk.is_empty());
+
+    let _ = destination.write_chunks(&mut filtered_chunks)?;
+
+    match filter_error {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// the COPY block the next chunk starts in, if any
+#[derive(Default)]
+struct CopyBlock {
+    progress: Progress,
+    /// the rows of the current COPY block are written
+    is_selected: bool,
+}
+
+/// the queries of the chunk matching the predicate, and the rows of the selected COPY blocks
+fn filter_chunk<P>(
+    chunk: &[u8],
+    copy_block: &mut CopyBlock,
+    is_selected: &P,
+) -> Result<Bytes, ReplibyteError>
+where
+    P: Fn(&str) -> bool,
+{
+    let mut filtered_chunk = Vec::with_capacity(chunk.len());
+    let mut queries = vec![];
+
+    let filter_queries = |queries: &mut Bytes, filtered_chunk: &mut Bytes| {
+        let reader = BufReader::new(queries.as_slice());
+
+        let result = list_sql_queries_from_dump_reader(reader, |query| {
+            let query = query.trim_start();
+
+            if !query.is_empty() && is_selected(query) {
+                filtered_chunk.extend_from_slice(query.as_bytes());
+                filtered_chunk.push(b'\n');
+            }
+
+            ListQueryResult::Continue
+        });
+
+        queries.clear();
+        result
+    };
+
+    let lines = chunk.strip_suffix(b"\n").unwrap_or(chunk);
+
+    for line in lines.split(|byte| *byte == b'\n') {
+        let is_copy = copy_block.progress.is_copy();
+        copy_block.progress.count_rows(line);
+
+        match (is_copy, copy_block.progress.is_copy()) {
+            (false, false) => {
+                queries.extend_from_slice(line);
+                queries.push(b'\n');
+            }
+            (false, true) => {
+                // the queries before a COPY block are complete
+                filter_queries(&mut queries, &mut filtered_chunk)?;
+
+                let query = String::from_utf8_lossy(line);
+                copy_block.is_selected = is_selected(query.trim_start());
+
+                if copy_block.is_selected {
+                    filtered_chunk.extend_from_slice(line);
+                    filtered_chunk.push(b'\n');
+                }
+            }
+            // the rows and the end of the COPY data are not SQL, they are kept as they are
+            (true, _) => {
+                if copy_block.is_selected {
+                    filtered_chunk.extend_from_slice(line);
+                    filtered_chunk.push(b'\n');
+                }
+            }
+        }
+    }
+
+    filter_queries(&mut queries, &mut filtered_chunk)?;
+
+    Ok(filtered_chunk)
+}
+
+/// return the query inside a MySQL conditional comment: i.e. `ALTER TABLE ...` for /*!40000 ALTER TABLE ... */;
+pub fn strip_conditional_comment(query: &str) -> &str {
+    match query.strip_prefix("/*!") {
+        Some(query) => query
+            .trim_start_matches(|ch: char| ch.is_ascii_digit())
+            .trim_start(),
+        None => query,
+    }
+}
+
+/// session queries change the state of the connection, they must be replayed on each new connection
+pub fn is_session_query(query: &str) -> bool {
+    // only the beginning of the query is needed, INSERT queries can be huge
+    let prefix = query.chars().take(32).collect::<String>().to_uppercase();
+
+    prefix.starts_with("SET ")
+        || prefix.starts_with("SELECT PG_CATALOG.SET_CONFIG")
+        // MySQL conditional comments: i.e. /*!40101 SET NAMES utf8mb4 */;
+        || (prefix.starts_with("/*!") && query.to_uppercase().contains(" SET "))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::destination::{filter_chunk, pipe_chunks, wrap_chunks, CopyBlock};
+
+    #[test]
+    fn pipe_all_chunks() {
+        let mut output = vec![];
+        let mut chunks = vec![b"SELECT 1;\n".to_vec(), b"SELECT 2;\n".to_vec()].into_iter();
+
+        assert!(pipe_chunks(&mut output, &mut chunks).is_ok());
+        assert_eq!(output, b"SELECT 1;\nSELECT 2;\n".to_vec());
+    }
+
+    #[test]
+    fn wrap_all_chunks() {
+        let mut chunks = vec![b"SELECT 1;\n".to_vec()].into_iter();
+        let chunks = wrap_chunks(&mut chunks, Some(("BEGIN;\n", "COMMIT;\n"))).collect::<Vec<_>>();
+        assert_eq!(chunks.concat(), b"BEGIN;\nSELECT 1;\nCOMMIT;\n".to_vec());
+
+        let mut chunks = vec![b"SELECT 1;\n".to_vec()].into_iter();
+        assert_eq!(wrap_chunks(&mut chunks, None).count(), 1);
+    }
+
+    #[test]
+    fn filter_copy_blocks() {
+        let is_selected = |query: &str| !query.contains("public.orders");
+        let mut copy_block = CopyBlock::default();
+
+        // the COPY blocks go on in the next chunk, their rows are kept as they are
+        let chunk = b"CREATE TABLE public.users (id integer, name text);\nCOPY public.users (id, name) FROM stdin;\n1\t  alice\n";
+        assert_eq!(
+            filter_chunk(chunk, &mut copy_block, &is_selected).unwrap(),
+            chunk.to_vec()
+        );
+
+        let chunk = b"\\N\tbob\n\n\\.\nCOPY public.orders (id) FROM stdin;\n1\n";
+        assert_eq!(
+            filter_chunk(chunk, &mut copy_block, &is_selected).unwrap(),
+            b"\\N\tbob\n\n\\.\n".to_vec()
+        );
+
+        let chunk = b"2\n\\.\n  SELECT 1;\n";
+        assert_eq!(
+            filter_chunk(chunk, &mut copy_block, &is_selected).unwrap(),
+            b"SELECT 1;\n".to_vec()
+        );
+    }
 }
@@ -2,10 +2,11 @@ use crate::connector::Connector;
 use crate::destination::docker::{
     daemon_is_running, Container, ContainerOptions, Image, DOCKER_BINARY_NAME,
 };
-use crate::destination::Destination;
+use crate::destination::mongodb::without_trailing_null_terminator;
+use crate::destination::{pipe_chunks, Destination};
+use crate::errors::ReplibyteError;
 use crate::types::Bytes;
 use crate::utils::binary_exists;
-use std::io::{Error, ErrorKind, Write};
 
 const DEFAULT_MONGO_IMAGE: &str = "mongo";
 pub const DEFAULT_MONGO_IMAGE_TAG: &str = "5";
@@ -26,17 +27,14 @@ impl MongoDBDocker {
                 name: DEFAULT_MONGO_IMAGE.to_string(),
                 tag,
             },
-            options: ContainerOptions {
-                host_port: port,
-                container_port: DEFAULT_MONGO_CONTAINER_PORT,
-            },
+            options: ContainerOptions::new(port, DEFAULT_MONGO_CONTAINER_PORT),
             container: None,
         }
     }
 }
 
 impl Connector for MongoDBDocker {
-    fn init(&mut self) -> Result<(), Error> {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
         let _ = binary_exists(DOCKER_BINARY_NAME)?;
         let _ = daemon_is_running()?;
 
@@ -55,7 +53,7 @@ impl Connector for MongoDBDocker {
 }
 
 impl Destination for MongoDBDocker {
-    fn write(&self, data: Bytes) -> Result<(), Error> {
+    fn write_chunks(&self, chunks: &mut dyn Iterator<Item = Bytes>) -> Result<(), ReplibyteError> {
         let cmd = format!(
             "mongorestore --authenticationDatabase admin -u {} -p {} --archive",
             DEFAULT_MONGO_USER, DEFAULT_MONGO_PASSWORD,
@@ -64,25 +62,23 @@ impl Destination for MongoDBDocker {
         match &self.container {
             Some(container) => {
                 let mut container_exec = container.exec(&cmd)?;
-                let _ = container_exec
-                    .stdin
-                    .take()
-                    .unwrap()
-                    .write_all(&data[..data.len() - 1]); // remove trailing null terminator, or else mongorestore will fail
+                let _ = pipe_chunks(
+                    container_exec.stdin.take().unwrap(),
+                    &mut without_trailing_null_terminator(chunks),
+                );
 
                 let exit_status = container_exec.wait()?;
                 if !exit_status.success() {
-                    return Err(Error::new(
-                        ErrorKind::Other,
-                        format!("command error: {:?}", exit_status.to_string()),
-                    ));
+                    return Err(ReplibyteError::Command(format!(
+                        "command error: {:?}",
+                        exit_status.to_string()
+                    )));
                 }
 
                 Ok(())
             }
-            None => Err(Error::new(
-                ErrorKind::Other,
-                "command error: cannot retrieve container",
+            None => Err(ReplibyteError::Command(
+                "command error: cannot retrieve container".to_string(),
             )),
         }
     }
@@ -109,13 +105,13 @@ mod tests {
         let mut p = get_mongodb();
         let _ = p.init().expect("can't init mongodb");
         let bytes = decode_hex("6de299816600000010636f6e63757272656e745f636f6c6c656374696f6e7300040000000276657273696f6e0004000000302e3100027365727665725f76657273696f6e0006000000352e302e360002746f6f6c5f76657273696f6e00080000003130302e352e320000020100000264620005000000746573740002636f6c6c656374696f6e0006000000757365727300026d6574616461746100ad0000007b22696e6465786573223a5b7b2276223a7b22246e756d626572496e74223a2232227d2c226b6579223a7b225f6964223a7b22246e756d626572496e74223a2231227d7d2c226e616d65223a225f69645f227d5d2c2275756964223a223464363734323637316333613463663938316439386164373831343735333234222c22636f6c6c656374696f6e4e616d65223a227573657273222c2274797065223a22636f6c6c656374696f6e227d001073697a6500000000000274797065000b000000636f6c6c656374696f6e0000ffffffff3b0000000264620005000000746573740002636f6c6c656374696f6e000600000075736572730008454f4600001243524300000000000000000000ffffffff3b0000000264620005000000746573740002636f6c6c656374696f6e000600000075736572730008454f4600011243524300000000000000000000ffffffff00").unwrap();
-        assert!(p.write(bytes.to_vec()).is_ok());
+        assert!(p.write_chunks(&mut std::iter::once(bytes.to_vec())).is_ok());
 
         // cleanup container
         let _ = p.container.unwrap().rm();
 
         let mut p = get_invalid_mongodb();
         assert!(p.init().is_err());
-        assert!(p.write(bytes.to_vec()).is_err());
+        assert!(p.write_chunks(&mut std::iter::once(bytes.to_vec())).is_err());
     }
 }
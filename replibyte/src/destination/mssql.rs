@@ -0,0 +1,151 @@
+use std::process::{Command, Stdio};
+
+use crate::connector::Connector;
+use crate::destination::{pipe_chunks, Destination};
+use crate::errors::ReplibyteError;
+use crate::types::Bytes;
+use crate::utils::{binary_exists, wait_for_command};
+
+/// sqlcmd only executes a batch once it reads this separator (or the end of the input)
+const BATCH_SEPARATOR: &[u8] = b"\nGO\n";
+
+/// database to connect to when the destination database does not exist yet
+const MASTER_DATABASE: &str = "master";
+
+pub struct MSSQL<'a> {
+    host: &'a str,
+    port: u16,
+    database: &'a str,
+    username: &'a str,
+    password: &'a str,
+    create_database: bool,
+}
+
+impl<'a> MSSQL<'a> {
+    pub fn new(
+        host: &'a str,
+        port: u16,
+        database: &'a str,
+        username: &'a str,
+        password: &'a str,
+        create_database: bool,
+    ) -> Self {
+        MSSQL {
+            host,
+            port,
+            database,
+            username,
+            password,
+            create_database,
+        }
+    }
+
+    fn server(&self) -> String {
+        format!("{},{}", self.host, self.port)
+    }
+}
+
+impl<'a> Connector for MSSQL<'a> {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        binary_exists("sqlcmd")?;
+
+        if self.create_database {
+            let mut process = Command::new("sqlcmd")
+                .env("SQLCMDPASSWORD", self.password)
+                .args([
+                    "-S",
+                    self.server().as_str(),
+                    "-U",
+                    self.username,
+                    "-d",
+                    MASTER_DATABASE,
+                    "-b",
+                    "-r",
+                    "1", // print the errors on stderr
+                    "-Q",
+                    create_database_query(self.database).as_str(),
+                ])
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .spawn()?;
+
+            let _ = wait_for_command(&mut process)?;
+        }
+
+        // test SQL Server connection
+        let mut process = Command::new("sqlcmd")
+            .env("SQLCMDPASSWORD", self.password)
+            .args([
+                "-S",
+                self.server().as_str(),
+                "-U",
+                self.username,
+                "-d",
+                self.database,
+                "-b",
+                "-r",
+                "1", // print the errors on stderr
+                "-Q",
+                "SELECT 1;",
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        wait_for_command(&mut process)
+    }
+}
+
+impl<'a> Destination for MSSQL<'a> {
+    fn write_chunks(&self, chunks: &mut dyn Iterator<Item = Bytes>) -> Result<(), ReplibyteError> {
+        let mut process = Command::new("sqlcmd")
+            .env("SQLCMDPASSWORD", self.password)
+            .args([
+                "-S",
+                self.server().as_str(),
+                "-U",
+                self.username,
+                "-d",
+                self.database,
+                "-b", // stop at the first error
+                "-r",
+                "1", // print the errors on stderr
+            ])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // run one batch per chunk to not have sqlcmd buffering the whole dump
+        let mut batches = chunks.map(|mut chunk| {
+            chunk.extend_from_slice(BATCH_SEPARATOR);
+            chunk
+        });
+
+        // if sqlcmd stops early, the error is returned by wait_for_command
+        let _ = pipe_chunks(process.stdin.take().unwrap(), &mut batches);
+
+        wait_for_command(&mut process)
+    }
+}
+
+pub fn create_database_query(database: &str) -> String {
+    format!(
+        "IF DB_ID(N'{}') IS NULL CREATE DATABASE [{}];",
+        database.replace('\'', "''"),
+        database.replace(']', "]]")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::destination::mssql::create_database_query;
+
+    #[test]
+    fn create_database_queries() {
+        assert_eq!(
+            create_database_query("db_pr_42"),
+            "IF DB_ID(N'db_pr_42') IS NULL CREATE DATABASE [db_pr_42];"
+        );
+    }
+}
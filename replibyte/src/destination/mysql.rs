@@ -1,8 +1,9 @@
-use std::io::{Error, Write};
-use std::process::{Command, Stdio};
+use std::process::{Command, Output, Stdio};
 
+use crate::config::TlsConfig;
 use crate::connector::Connector;
-use crate::destination::Destination;
+use crate::destination::{pipe_chunks, wrap_chunks, Destination};
+use crate::errors::ReplibyteError;
 use crate::types::Bytes;
 use crate::utils::{binary_exists, wait_for_command};
 
@@ -12,6 +13,9 @@ pub struct Mysql<'a> {
     database: &'a str,
     username: &'a str,
     password: &'a str,
+    create_database: bool,
+    disable_foreign_key_checks: bool,
+    tls: &'a TlsConfig,
 }
 
 impl<'a> Mysql<'a> {
@@ -21,6 +25,9 @@ impl<'a> Mysql<'a> {
         database: &'a str,
         username: &'a str,
         password: &'a str,
+        create_database: bool,
+        disable_foreign_key_checks: bool,
+        tls: &'a TlsConfig,
     ) -> Self {
         Mysql {
             host,
@@ -28,15 +35,43 @@ impl<'a> Mysql<'a> {
             database,
             username,
             password,
+            create_database,
+            disable_foreign_key_checks,
+            tls,
         }
     }
+
+    /// execute the query on the database and return its rows, tab separated
+    fn execute(&self, query: &str) -> Result<String, ReplibyteError> {
+        let output = Command::new("mysql")
+            .args([
+                "-h",
+                self.host,
+                "-P",
+                self.port.to_string().as_str(),
+                "-u",
+                self.username,
+                &format!("-p{}", self.password),
+            ])
+            .args(self.tls.mysql_args()?)
+            .args(["-N", "-B", "-e", query, self.database])
+            .output()?;
+
+        command_output(output)
+    }
 }
 
 impl<'a> Connector for Mysql<'a> {
-    fn init(&mut self) -> Result<(), Error> {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
         let _ = binary_exists("mysql")?;
 
-        // test MySQL connection
+        // test MySQL connection, and create the database if it does not exist
+        let query = if self.create_database {
+            create_database_query(self.database)
+        } else {
+            "SELECT 1;".to_string()
+        };
+
         let mut process = Command::new("mysql")
             .args([
                 "-h",
@@ -46,9 +81,9 @@ impl<'a> Connector for Mysql<'a> {
                 "-u",
                 self.username,
                 &format!("-p{}", self.password),
-                "-e",
-                "SELECT 1;",
             ])
+            .args(self.tls.mysql_args()?)
+            .args(["-e", query.as_str()])
             .stdout(Stdio::piped())
             .spawn()?;
 
@@ -56,8 +91,61 @@ impl<'a> Connector for Mysql<'a> {
     }
 }
 
+/// the stdout of the client, or its stderr as an error
+pub fn command_output(output: Output) -> Result<String, ReplibyteError> {
+    if !output.status.success() {
+        return Err(ReplibyteError::Command(format!(
+            "command error: {}",
+            String::from_utf8_lossy(output.stderr.as_slice())
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(output.stdout.as_slice()).to_string())
+}
+
+/// the foreign keys of the rows restored while the checks are disabled are not checked afterwards
+pub const DISABLE_FOREIGN_KEY_CHECKS_QUERIES: (&str, &str) = (
+    "SET FOREIGN_KEY_CHECKS = 0;\n",
+    "SET FOREIGN_KEY_CHECKS = 1;\n",
+);
+
+/// columns with an auto increment counter, one `table<TAB>column` row per column
+pub const AUTO_INCREMENT_COLUMNS_QUERY: &str =
+    "SELECT table_name, column_name FROM information_schema.columns \
+    WHERE table_schema = DATABASE() AND extra LIKE '%auto_increment%';";
+
+/// set the auto increment counters after the greatest value of their column, with the rows of the
+/// [AUTO_INCREMENT_COLUMNS_QUERY]. `ALTER TABLE` does not take an expression, it is a prepared statement.
+pub fn sync_auto_increments_query(auto_increment_columns: &str) -> String {
+    auto_increment_columns
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(table, column)| {
+            let table = format!("`{}`", table.replace('`', "``"));
+            let column = format!("`{}`", column.replace('`', "``"));
+
+            format!(
+                "SELECT COALESCE(MAX({column}), 0) + 1 INTO @auto_increment FROM {table}; \
+                SET @query = CONCAT('ALTER TABLE {quoted_table} AUTO_INCREMENT = ', @auto_increment); \
+                PREPARE statement FROM @query; EXECUTE statement; DEALLOCATE PREPARE statement;",
+                column = column,
+                table = table,
+                quoted_table = table.replace('\\', "\\\\").replace('\'', "''"),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn create_database_query(database: &str) -> String {
+    format!(
+        "CREATE DATABASE IF NOT EXISTS `{}`;",
+        database.replace('`', "``")
+    )
+}
+
 impl<'a> Destination for Mysql<'a> {
-    fn write(&self, data: Bytes) -> Result<(), Error> {
+    fn write_chunks(&self, chunks: &mut dyn Iterator<Item = Bytes>) -> Result<(), ReplibyteError> {
         let mut process = Command::new("mysql")
             .args([
                 "-h",
@@ -68,41 +156,112 @@ impl<'a> Destination for Mysql<'a> {
                 self.username,
                 &format!("-p{}", self.password),
             ])
+            .args(self.tls.mysql_args()?)
+            .arg(self.database)
             .stdin(Stdio::piped())
             .stdout(Stdio::null())
             .spawn()?;
 
-        let _ = process.stdin.take().unwrap().write_all(data.as_slice());
+        let mut chunks = wrap_chunks(
+            chunks,
+            match self.disable_foreign_key_checks {
+                true => Some(DISABLE_FOREIGN_KEY_CHECKS_QUERIES),
+                false => None,
+            },
+        );
+
+        // if mysql stops early, the error is returned by wait_for_command
+        let _ = pipe_chunks(process.stdin.take().unwrap(), &mut chunks);
 
         wait_for_command(&mut process)
     }
+
+    fn sync_sequences(&self) -> Result<(), ReplibyteError> {
+        let auto_increment_columns = self.execute(AUTO_INCREMENT_COLUMNS_QUERY)?;
+        let query = sync_auto_increments_query(auto_increment_columns.as_str());
+
+        if !query.is_empty() {
+            let _ = self.execute(query.as_str())?;
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::config::TlsConfig;
     use crate::connector::Connector;
-    use crate::destination::mysql::Mysql;
+    use crate::destination::mysql::{create_database_query, sync_auto_increments_query, Mysql};
     use crate::destination::Destination;
 
+    static TLS_CONFIG: TlsConfig = TlsConfig {
+        ssl_mode: None,
+        ssl_cert: None,
+        ssl_key: None,
+        ssl_root_cert: None,
+        parameters: Vec::new(),
+    };
+
     fn get_mysql() -> Mysql<'static> {
-        Mysql::new("127.0.0.1", 3306, "root", "root", "password")
+        Mysql::new(
+            "127.0.0.1",
+            3306,
+            "root",
+            "root",
+            "password",
+            false,
+            false,
+            &TLS_CONFIG,
+        )
     }
 
     fn get_invalid_mysql() -> Mysql<'static> {
-        Mysql::new("127.0.0.1", 3306, "root", "root", "wrong_password")
+        Mysql::new(
+            "127.0.0.1",
+            3306,
+            "root",
+            "root",
+            "wrong_password",
+            false,
+            false,
+            &TLS_CONFIG,
+        )
     }
 
     #[test]
     fn connect() {
         let mut m = get_mysql();
         let _ = m.init().expect("can't init mysql");
-        assert!(m.write(b"SELECT 1;".to_vec()).is_ok());
+        assert!(m.write_chunks(&mut std::iter::once(b"SELECT 1;".to_vec())).is_ok());
 
         let mut m = get_invalid_mysql();
         assert!(m.init().is_err());
-        assert!(m.write(b"SELECT 1".to_vec()).is_err());
+        assert!(m.write_chunks(&mut std::iter::once(b"SELECT 1".to_vec())).is_err());
     }
 
     #[test]
     fn test_inserts() {}
+
+    #[test]
+    fn create_database_queries() {
+        assert_eq!(
+            create_database_query("db_pr_`42"),
+            "CREATE DATABASE IF NOT EXISTS `db_pr_``42`;"
+        );
+    }
+
+    #[test]
+    fn sync_auto_increments_queries() {
+        assert_eq!(sync_auto_increments_query(""), "");
+        assert_eq!(
+            sync_auto_increments_query("users\tid\nit's\tuser`id\n"),
+            "SELECT COALESCE(MAX(`id`), 0) + 1 INTO @auto_increment FROM `users`; \
+            SET @query = CONCAT('ALTER TABLE `users` AUTO_INCREMENT = ', @auto_increment); \
+            PREPARE statement FROM @query; EXECUTE statement; DEALLOCATE PREPARE statement;\n\
+            SELECT COALESCE(MAX(`user``id`), 0) + 1 INTO @auto_increment FROM `it's`; \
+            SET @query = CONCAT('ALTER TABLE `it''s` AUTO_INCREMENT = ', @auto_increment); \
+            PREPARE statement FROM @query; EXECUTE statement; DEALLOCATE PREPARE statement;"
+        );
+    }
 }
@@ -2,10 +2,10 @@ use crate::connector::Connector;
 use crate::destination::docker::{
     daemon_is_running, Container, ContainerOptions, Image, DOCKER_BINARY_NAME,
 };
-use crate::destination::Destination;
+use crate::destination::{pipe_chunks, Destination};
+use crate::errors::ReplibyteError;
 use crate::types::Bytes;
 use crate::utils::binary_exists;
-use std::io::{Error, ErrorKind, Write};
 
 const DEFAULT_MYSQL_IMAGE: &str = "mysql";
 pub const DEFAULT_MYSQL_IMAGE_TAG: &str = "8";
@@ -25,17 +25,14 @@ impl MysqlDocker {
                 name: DEFAULT_MYSQL_IMAGE.to_string(),
                 tag,
             },
-            options: ContainerOptions {
-                host_port: port,
-                container_port: DEFAULT_MYSQL_CONTAINER_PORT,
-            },
+            options: ContainerOptions::new(port, DEFAULT_MYSQL_CONTAINER_PORT),
             container: None,
         }
     }
 }
 
 impl Connector for MysqlDocker {
-    fn init(&mut self) -> Result<(), Error> {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
         let _ = binary_exists(DOCKER_BINARY_NAME)?;
         let _ = daemon_is_running()?;
 
@@ -56,30 +53,25 @@ impl Connector for MysqlDocker {
 }
 
 impl Destination for MysqlDocker {
-    fn write(&self, data: Bytes) -> Result<(), Error> {
+    fn write_chunks(&self, chunks: &mut dyn Iterator<Item = Bytes>) -> Result<(), ReplibyteError> {
         match &self.container {
             Some(container) => {
                 let mut container_exec =
                     container.exec("exec mysql -uroot -p\"$MYSQL_ROOT_PASSWORD\"")?;
-                let _ = container_exec
-                    .stdin
-                    .take()
-                    .unwrap()
-                    .write_all(data.as_slice());
+                let _ = pipe_chunks(container_exec.stdin.take().unwrap(), chunks);
 
                 let exit_status = container_exec.wait()?;
                 if !exit_status.success() {
-                    return Err(Error::new(
-                        ErrorKind::Other,
-                        format!("command error: {:?}", exit_status.to_string()),
-                    ));
+                    return Err(ReplibyteError::Command(format!(
+                        "command error: {:?}",
+                        exit_status.to_string()
+                    )));
                 }
 
                 Ok(())
             }
-            None => Err(Error::new(
-                ErrorKind::Other,
-                "command error: cannot retrieve container",
+            None => Err(ReplibyteError::Command(
+                "command error: cannot retrieve container".to_string(),
             )),
         }
     }
@@ -103,13 +95,13 @@ mod tests {
     fn connect() {
         let mut p = get_mysql();
         let _ = p.init().expect("can't init mysql");
-        assert!(p.write(b"SELECT 1".to_vec()).is_ok());
+        assert!(p.write_chunks(&mut std::iter::once(b"SELECT 1".to_vec())).is_ok());
 
         // cleanup container
         let _ = p.container.unwrap().rm();
 
         let mut p = get_invalid_mysql();
         assert!(p.init().is_err());
-        assert!(p.write(b"SELECT 1".to_vec()).is_err());
+        assert!(p.write_chunks(&mut std::iter::once(b"SELECT 1".to_vec())).is_err());
     }
 }
@@ -1,8 +1,9 @@
-use std::io::{Error, ErrorKind, Write};
 use std::process::{Command, Stdio};
 
+use crate::config::{PostgresDialect, TlsConfig, WipeStrategy};
 use crate::connector::Connector;
-use crate::destination::Destination;
+use crate::destination::{pipe_chunks, wrap_chunks, Destination};
+use crate::errors::ReplibyteError;
 use crate::types::Bytes;
 use crate::utils::{binary_exists, wait_for_command};
 
@@ -12,7 +13,13 @@ pub struct Postgres<'a> {
     database: &'a str,
     username: &'a str,
     password: &'a str,
-    wipe_database: bool,
+    wipe_strategy: WipeStrategy,
+    dialect: PostgresDialect,
+    create_database: bool,
+    database_template: Option<&'a str>,
+    schemas: &'a [String],
+    disable_triggers: bool,
+    tls: &'a TlsConfig,
 }
 
 impl<'a> Postgres<'a> {
@@ -22,7 +29,13 @@ impl<'a> Postgres<'a> {
         database: &'a str,
         username: &'a str,
         password: &'a str,
-        wipe_database: bool,
+        wipe_strategy: WipeStrategy,
+        dialect: PostgresDialect,
+        create_database: bool,
+        database_template: Option<&'a str>,
+        schemas: &'a [String],
+        disable_triggers: bool,
+        tls: &'a TlsConfig,
     ) -> Self {
         Postgres {
             host,
@@ -30,55 +43,111 @@ impl<'a> Postgres<'a> {
             database,
             username,
             password,
-            wipe_database,
+            wipe_strategy,
+            dialect,
+            create_database,
+            database_template,
+            schemas,
+            disable_triggers,
+            tls,
         }
     }
+
+    /// execute the query on the database and return its output
+    fn execute(&self, database: &str, query: &str) -> Result<String, ReplibyteError> {
+        let output = Command::new("psql")
+            .env("PGPASSWORD", self.password)
+            .envs(self.tls.postgres_env()?)
+            .args([
+                "-h",
+                self.host,
+                "-p",
+                self.port.to_string().as_str(),
+                "-d",
+                database,
+                "-U",
+                self.username,
+                "-v",
+                "ON_ERROR_STOP=1",
+                "-tA", // only print the rows, without alignment
+                "-c",
+                query,
+            ])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(ReplibyteError::Command(format!(
+                "command error: {}",
+                String::from_utf8_lossy(output.stderr.as_slice())
+            )));
+        }
+
+        Ok(String::from_utf8_lossy(output.stdout.as_slice()).to_string())
+    }
 }
 
 impl<'a> Connector for Postgres<'a> {
-    fn init(&mut self) -> Result<(), Error> {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
         let _ = binary_exists("psql")?;
 
-        if self.wipe_database {
-            let s_port = self.port.to_string();
-            let wipe_db_query = wipe_database_query(self.username);
-
-            let exit_status = Command::new("psql")
-                .env("PGPASSWORD", self.password)
-                .args([
-                    "-h",
-                    self.host,
-                    "-p",
-                    s_port.as_str(),
-                    "-d",
-                    self.database,
-                    "-U",
-                    self.username,
-                    "-c",
-                    wipe_db_query.as_str(),
-                ])
-                .stdout(Stdio::null())
-                .spawn()?
-                .wait()?;
-
-            if !exit_status.success() {
-                return Err(Error::new(
-                    ErrorKind::Other,
-                    format!("command error: {:?}", exit_status.to_string()),
-                ));
+        if self.wipe_strategy == WipeStrategy::DropDatabaseRecreate {
+            for query in recreate_database_queries(
+                self.dialect,
+                self.database,
+                self.username,
+                self.database_template,
+            )? {
+                let _ = self.execute(MAINTENANCE_DATABASE, query.as_str())?;
+            }
+        } else if self.create_database {
+            let exists_query = database_exists_query(self.database);
+            let output = self.execute(MAINTENANCE_DATABASE, exists_query.as_str())?;
+
+            // the query returns no row if the database does not exist
+            if output.trim().is_empty() {
+                let create_query =
+                    create_database_query(self.dialect, self.database, self.database_template)?;
+                let _ = self.execute(MAINTENANCE_DATABASE, create_query.as_str())?;
             }
         }
 
+        let wipe_db_query = match self.wipe_strategy {
+            WipeStrategy::DropSchema => Some(drop_schemas_query(
+                self.dialect,
+                self.username,
+                self.schemas,
+            )?),
+            WipeStrategy::TruncateTables => Some(truncate_tables_query(self.schemas)),
+            WipeStrategy::DropDatabaseRecreate | WipeStrategy::None => None,
+        };
+
+        if let Some(wipe_db_query) = wipe_db_query {
+            let _ = self.execute(self.database, wipe_db_query.as_str())?;
+        }
+
+        // psql goes on after a failing query, the restore would silently fire the triggers
+        if self.disable_triggers {
+            let _ = self
+                .execute(self.database, DISABLE_TRIGGERS_QUERIES.0)
+                .map_err(|err| {
+                    ReplibyteError::Config(format!(
+                        "disable_triggers needs a superuser, or the SET privilege on session_replication_role: {}",
+                        err
+                    ))
+                })?;
+        }
+
         Ok(())
     }
 }
 
 impl<'a> Destination for Postgres<'a> {
-    fn write(&self, data: Bytes) -> Result<(), Error> {
+    fn write_chunks(&self, chunks: &mut dyn Iterator<Item = Bytes>) -> Result<(), ReplibyteError> {
         let s_port = self.port.to_string();
 
         let mut process = Command::new("psql")
             .env("PGPASSWORD", self.password)
+            .envs(self.tls.postgres_env()?)
             .args([
                 "-h",
                 self.host,
@@ -93,47 +162,276 @@ impl<'a> Destination for Postgres<'a> {
             .stdout(Stdio::null())
             .spawn()?;
 
-        let _ = process.stdin.take().unwrap().write_all(data.as_slice());
+        let mut chunks = wrap_chunks(
+            chunks,
+            match self.disable_triggers {
+                true => Some(DISABLE_TRIGGERS_QUERIES),
+                false => None,
+            },
+        );
+
+        // if psql stops early, the error is returned by wait_for_command
+        let _ = pipe_chunks(process.stdin.take().unwrap(), &mut chunks);
 
         wait_for_command(&mut process)
     }
+
+    fn sync_sequences(&self) -> Result<(), ReplibyteError> {
+        match self.dialect {
+            PostgresDialect::Postgres => {
+                let _ = self.execute(self.database, SYNC_SEQUENCES_QUERY)?;
+                Ok(())
+            }
+            // CockroachDB has no pg_sequence catalog to find the sequences of the columns
+            PostgresDialect::CockroachDB => Ok(()),
+        }
+    }
+}
+
+/// set the sequences of the serial and identity columns to the greatest value of their column (the lowest for the
+/// descending sequences), the next value of the sequences of the empty tables is their start value
+pub const SYNC_SEQUENCES_QUERY: &str = "\
+    DO $$ DECLARE r record; BEGIN \
+    FOR r IN SELECT s.seqrelid::regclass::text AS sequence_name, s.seqstart AS start_value, \
+    CASE WHEN s.seqincrement > 0 THEN 'MAX' ELSE 'MIN' END AS aggregate, \
+    n.nspname AS schema_name, t.relname AS table_name, a.attname AS column_name \
+    FROM pg_sequence s \
+    JOIN pg_depend d ON d.classid = 'pg_class'::regclass AND d.objid = s.seqrelid \
+    AND d.refclassid = 'pg_class'::regclass AND d.deptype IN ('a', 'i') \
+    JOIN pg_class t ON t.oid = d.refobjid \
+    JOIN pg_namespace n ON n.oid = t.relnamespace \
+    JOIN pg_attribute a ON a.attrelid = t.oid AND a.attnum = d.refobjsubid LOOP \
+    EXECUTE format('SELECT setval(%L, COALESCE(%s(%I), %s), %s(%I) IS NOT NULL) FROM %I.%I', \
+    r.sequence_name, r.aggregate, r.column_name, r.start_value, r.aggregate, r.column_name, r.schema_name, r.table_name); \
+    END LOOP; END $$;\
+    ";
+
+/// the triggers, including the ones checking the foreign keys, are not fired while the session replication role is
+/// `replica`, and the rows restored meanwhile are not checked afterwards
+pub const DISABLE_TRIGGERS_QUERIES: (&str, &str) = (
+    "SET session_replication_role = replica;\n",
+    "SET session_replication_role = DEFAULT;\n",
+);
+
+/// schema wiped when no schemas are configured
+pub const DEFAULT_SCHEMA: &str = "public";
+
+/// database to connect to when the destination database does not exist yet
+pub const MAINTENANCE_DATABASE: &str = "postgres";
+
+pub fn database_exists_query(database: &str) -> String {
+    format!(
+        "SELECT 1 FROM pg_database WHERE datname = '{}';",
+        database.replace('\'', "''")
+    )
+}
+
+pub fn create_database_query(
+    dialect: PostgresDialect,
+    database: &str,
+    template: Option<&str>,
+) -> Result<String, ReplibyteError> {
+    match (dialect, template) {
+        (PostgresDialect::Postgres, Some(template)) => Ok(format!(
+            "CREATE DATABASE \"{}\" TEMPLATE \"{}\";",
+            database, template
+        )),
+        (PostgresDialect::CockroachDB, Some(_)) => Err(ReplibyteError::Config(
+            "CockroachDB does not support database templates".to_string(),
+        )),
+        (_, None) => Ok(format!("CREATE DATABASE \"{}\";", database)),
+    }
 }
 
-fn wipe_database_query(username: &str) -> String {
+pub fn drop_schemas_query(
+    dialect: PostgresDialect,
+    username: &str,
+    schemas: &[String],
+) -> Result<String, ReplibyteError> {
+    if dialect == PostgresDialect::CockroachDB {
+        return Err(ReplibyteError::Config("CockroachDB does not allow to drop the public schema, use the 'drop_database_recreate' wipe strategy instead".to_string()));
+    }
+
+    Ok(schemas
+        .iter()
+        .map(|schema| {
+            if schema == DEFAULT_SCHEMA {
+                // pg_dump does not create the public schema
+                format!(
+                    "\
+    DROP SCHEMA IF EXISTS \"{schema}\" CASCADE; \
+    CREATE SCHEMA \"{schema}\"; \
+    GRANT ALL ON SCHEMA \"{schema}\" TO \"{username}\"; \
+    GRANT ALL ON SCHEMA \"{schema}\" TO public;\
+    ",
+                    schema = schema,
+                    username = username
+                )
+            } else {
+                // the other schemas are created by the dump
+                format!("DROP SCHEMA IF EXISTS \"{}\" CASCADE;", schema)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" "))
+}
+
+pub fn truncate_tables_query(schemas: &[String]) -> String {
+    let schemas = schemas
+        .iter()
+        .map(|schema| format!("'{}'", schema.replace('\'', "''")))
+        .collect::<Vec<_>>()
+        .join(", ");
+
     format!(
         "\
-    DROP SCHEMA public CASCADE; \
-    CREATE SCHEMA public; \
-    GRANT ALL ON SCHEMA public TO \"{}\"; \
-    GRANT ALL ON SCHEMA public TO public;\
+    DO $$ DECLARE r record; BEGIN \
+    FOR r IN SELECT schemaname, tablename FROM pg_tables WHERE schemaname IN ({}) LOOP \
+    EXECUTE format('TRUNCATE TABLE %I.%I CASCADE', r.schemaname, r.tablename); \
+    END LOOP; END $$;\
     ",
-        username
+        schemas
     )
 }
 
+/// queries executed one by one on the maintenance database, a database can not be dropped while connected to it
+pub fn recreate_database_queries(
+    dialect: PostgresDialect,
+    database: &str,
+    username: &str,
+    template: Option<&str>,
+) -> Result<Vec<String>, ReplibyteError> {
+    let create_query = create_database_query(dialect, database, template)?;
+
+    Ok(match dialect {
+        PostgresDialect::Postgres => vec![
+            format!("DROP DATABASE IF EXISTS \"{}\";", database),
+            create_query,
+        ],
+        PostgresDialect::CockroachDB => vec![
+            format!("DROP DATABASE IF EXISTS \"{}\" CASCADE;", database),
+            create_query,
+            format!(
+                "GRANT ALL ON DATABASE \"{}\" TO \"{}\";",
+                database, username
+            ),
+        ],
+    })
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::config::{PostgresDialect, TlsConfig, WipeStrategy};
     use crate::connector::Connector;
-    use crate::destination::postgres::Postgres;
+    use crate::destination::postgres::{
+        create_database_query, database_exists_query, drop_schemas_query,
+        recreate_database_queries, truncate_tables_query, Postgres,
+    };
     use crate::destination::Destination;
 
+    static TLS_CONFIG: TlsConfig = TlsConfig {
+        ssl_mode: None,
+        ssl_cert: None,
+        ssl_key: None,
+        ssl_root_cert: None,
+        parameters: Vec::new(),
+    };
+
     fn get_postgres() -> Postgres<'static> {
-        Postgres::new("localhost", 5453, "root", "root", "password", true)
+        Postgres::new(
+            "localhost",
+            5453,
+            "root",
+            "root",
+            "password",
+            WipeStrategy::DropSchema,
+            PostgresDialect::Postgres,
+            false,
+            None,
+            &[],
+            false,
+            &TLS_CONFIG,
+        )
     }
 
     fn get_invalid_postgres() -> Postgres<'static> {
-        Postgres::new("localhost", 5453, "root", "root", "wrongpassword", true)
+        Postgres::new(
+            "localhost",
+            5453,
+            "root",
+            "root",
+            "wrongpassword",
+            WipeStrategy::DropSchema,
+            PostgresDialect::Postgres,
+            false,
+            None,
+            &[],
+            false,
+            &TLS_CONFIG,
+        )
     }
 
     #[test]
     fn connect() {
         let mut p = get_postgres();
         let _ = p.init().expect("can't init postgres");
-        assert!(p.write(b"SELECT 1".to_vec()).is_ok());
+        assert!(p.write_chunks(&mut std::iter::once(b"SELECT 1".to_vec())).is_ok());
 
         let mut p = get_invalid_postgres();
         assert!(p.init().is_err());
-        assert!(p.write(b"SELECT 1".to_vec()).is_err());
+        assert!(p.write_chunks(&mut std::iter::once(b"SELECT 1".to_vec())).is_err());
+    }
+
+    #[test]
+    fn wipe_database_queries() {
+        let schemas = vec!["public".to_string(), "billing".to_string()];
+
+        let query = drop_schemas_query(PostgresDialect::Postgres, "root", &schemas).unwrap();
+        assert!(query.contains("DROP SCHEMA IF EXISTS \"public\" CASCADE;"));
+        assert!(query.contains("CREATE SCHEMA \"public\";"));
+        assert!(query.contains("DROP SCHEMA IF EXISTS \"billing\" CASCADE;"));
+        assert!(!query.contains("CREATE SCHEMA \"billing\";"));
+        assert!(drop_schemas_query(PostgresDialect::CockroachDB, "root", &schemas).is_err());
+
+        let query = truncate_tables_query(&schemas);
+        assert!(query.contains("WHERE schemaname IN ('public', 'billing')"));
+        assert!(query.contains("TRUNCATE TABLE %I.%I CASCADE"));
+
+        assert_eq!(
+            recreate_database_queries(PostgresDialect::Postgres, "db", "root", None).unwrap(),
+            vec!["DROP DATABASE IF EXISTS \"db\";", "CREATE DATABASE \"db\";"]
+        );
+
+        let queries =
+            recreate_database_queries(PostgresDialect::CockroachDB, "db", "root", None).unwrap();
+        assert_eq!(queries[0], "DROP DATABASE IF EXISTS \"db\" CASCADE;");
+        assert_eq!(queries[2], "GRANT ALL ON DATABASE \"db\" TO \"root\";");
+    }
+
+    #[test]
+    fn create_database_queries() {
+        assert_eq!(
+            database_exists_query("db_pr_'42"),
+            "SELECT 1 FROM pg_database WHERE datname = 'db_pr_''42';"
+        );
+
+        assert_eq!(
+            create_database_query(PostgresDialect::Postgres, "db_pr_42", None).unwrap(),
+            "CREATE DATABASE \"db_pr_42\";"
+        );
+
+        assert_eq!(
+            create_database_query(PostgresDialect::Postgres, "db_pr_42", Some("template_db"))
+                .unwrap(),
+            "CREATE DATABASE \"db_pr_42\" TEMPLATE \"template_db\";"
+        );
+
+        assert!(create_database_query(
+            PostgresDialect::CockroachDB,
+            "db_pr_42",
+            Some("template_db")
+        )
+        .is_err());
     }
 
     #[test]
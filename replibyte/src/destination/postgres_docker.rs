@@ -2,10 +2,10 @@ use crate::connector::Connector;
 use crate::destination::docker::{
     daemon_is_running, Container, ContainerOptions, Image, DOCKER_BINARY_NAME,
 };
-use crate::destination::Destination;
+use crate::destination::{pipe_chunks, Destination};
+use crate::errors::ReplibyteError;
 use crate::types::Bytes;
 use crate::utils::binary_exists;
-use std::io::{Error, ErrorKind, Write};
 
 const DEFAULT_POSTGRES_IMAGE: &str = "postgres";
 pub const DEFAULT_POSTGRES_IMAGE_TAG: &str = "13";
@@ -27,17 +27,14 @@ impl PostgresDocker {
                 name: DEFAULT_POSTGRES_IMAGE.to_string(),
                 tag,
             },
-            options: ContainerOptions {
-                host_port: port,
-                container_port: DEFAULT_POSTGRES_CONTAINER_PORT,
-            },
+            options: ContainerOptions::new(port, DEFAULT_POSTGRES_CONTAINER_PORT),
             container: None,
         }
     }
 }
 
 impl Connector for PostgresDocker {
-    fn init(&mut self) -> Result<(), Error> {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
         let _ = binary_exists(DOCKER_BINARY_NAME)?;
         let _ = daemon_is_running()?;
 
@@ -56,7 +53,7 @@ impl Connector for PostgresDocker {
 }
 
 impl Destination for PostgresDocker {
-    fn write(&self, data: Bytes) -> Result<(), Error> {
+    fn write_chunks(&self, chunks: &mut dyn Iterator<Item = Bytes>) -> Result<(), ReplibyteError> {
         let cmd = format!(
             "PGPASSWORD={} psql --username {} {}",
             DEFAULT_POSTGRES_PASSWORD, DEFAULT_POSTGRES_USER, DEFAULT_POSTGRES_DB
@@ -65,25 +62,20 @@ impl Destination for PostgresDocker {
         match &self.container {
             Some(container) => {
                 let mut container_exec = container.exec(&cmd)?;
-                let _ = container_exec
-                    .stdin
-                    .take()
-                    .unwrap()
-                    .write_all(data.as_slice());
+                let _ = pipe_chunks(container_exec.stdin.take().unwrap(), chunks);
 
                 let exit_status = container_exec.wait()?;
                 if !exit_status.success() {
-                    return Err(Error::new(
-                        ErrorKind::Other,
-                        format!("command error: {:?}", exit_status.to_string()),
-                    ));
+                    return Err(ReplibyteError::Command(format!(
+                        "command error: {:?}",
+                        exit_status.to_string()
+                    )));
                 }
 
                 Ok(())
             }
-            None => Err(Error::new(
-                ErrorKind::Other,
-                "command error: cannot retrieve container",
+            None => Err(ReplibyteError::Command(
+                "command error: cannot retrieve container".to_string(),
             )),
         }
     }
@@ -107,13 +99,13 @@ mod tests {
     fn connect() {
         let mut p = get_postgres();
         let _ = p.init().expect("can't init postgres");
-        assert!(p.write(b"SELECT 1".to_vec()).is_ok());
+        assert!(p.write_chunks(&mut std::iter::once(b"SELECT 1".to_vec())).is_ok());
 
         // cleanup container
         let _ = p.container.unwrap().rm();
 
         let mut p = get_invalid_postgres();
         assert!(p.init().is_err());
-        assert!(p.write(b"SELECT 1".to_vec()).is_err());
+        assert!(p.write_chunks(&mut std::iter::once(b"SELECT 1".to_vec())).is_err());
     }
 }
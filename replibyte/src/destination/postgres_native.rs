@@ -0,0 +1,463 @@
+use std::pin::Pin;
+
+use futures_util::SinkExt;
+use log::error;
+use tokio_postgres::{Client, CopyInSink, NoTls, SimpleQueryMessage};
+
+use crate::config::{PostgresDialect, SslMode, TlsConfig, WipeStrategy};
+use crate::connector::Connector;
+use crate::destination::postgres::{
+    create_database_query, database_exists_query, drop_schemas_query, recreate_database_queries,
+    truncate_tables_query, DISABLE_TRIGGERS_QUERIES, MAINTENANCE_DATABASE, SYNC_SEQUENCES_QUERY,
+};
+use crate::destination::{wrap_chunks, Destination};
+use crate::errors::ReplibyteError;
+use crate::runtime::block_on;
+use crate::tasks::Progress;
+use crate::types::Bytes;
+
+/// PostgresNative is a pure Rust PostgreSQL destination. Unlike `Postgres`, it does not need the `psql` binary
+/// and talks the PostgreSQL wire protocol directly.
+pub struct PostgresNative<'a> {
+    host: &'a str,
+    port: u16,
+    database: &'a str,
+    username: &'a str,
+    password: &'a str,
+    wipe_strategy: WipeStrategy,
+    dialect: PostgresDialect,
+    create_database: bool,
+    database_template: Option<&'a str>,
+    schemas: &'a [String],
+    disable_triggers: bool,
+    tls: &'a TlsConfig,
+    client: Option<Client>,
+}
+
+impl<'a> PostgresNative<'a> {
+    pub fn new(
+        host: &'a str,
+        port: u16,
+        database: &'a str,
+        username: &'a str,
+        password: &'a str,
+        wipe_strategy: WipeStrategy,
+        dialect: PostgresDialect,
+        create_database: bool,
+        database_template: Option<&'a str>,
+        schemas: &'a [String],
+        disable_triggers: bool,
+        tls: &'a TlsConfig,
+    ) -> Self {
+        PostgresNative {
+            host,
+            port,
+            database,
+            username,
+            password,
+            wipe_strategy,
+            dialect,
+            create_database,
+            database_template,
+            schemas,
+            disable_triggers,
+            tls,
+            client: None,
+        }
+    }
+
+    fn config(&self, database: &str) -> tokio_postgres::Config {
+        let mut config = tokio_postgres::Config::new();
+        config
+            .host(self.host)
+            .port(self.port)
+            .dbname(database)
+            .user(self.username)
+            .password(self.password)
+            .application_name("replibyte");
+
+        config
+    }
+
+    fn client(&self) -> Result<&Client, ReplibyteError> {
+        self.client.as_ref().ok_or_else(|| {
+            ReplibyteError::Connection(
+                "postgres connection is not initialized, call init() first".to_string(),
+            )
+        })
+    }
+}
+
+impl<'a> Connector for PostgresNative<'a> {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        // the connections are not encrypted, TLS is only supported through `psql`
+        let requires_tls = !matches!(
+            self.tls.ssl_mode,
+            None | Some(SslMode::Disable) | Some(SslMode::Prefer)
+        );
+
+        if requires_tls
+            || self.tls.ssl_cert.is_some()
+            || self.tls.ssl_key.is_some()
+            || self.tls.ssl_root_cert.is_some()
+        {
+            return Err(ReplibyteError::Config(
+                "TLS is not supported by the native client, set 'native_client: false' to use psql"
+                    .to_string(),
+            ));
+        }
+
+        if self.wipe_strategy == WipeStrategy::DropDatabaseRecreate {
+            let client = connect(self.config(MAINTENANCE_DATABASE))?;
+
+            for query in recreate_database_queries(
+                self.dialect,
+                self.database,
+                self.username,
+                self.database_template,
+            )? {
+                block_on(client.batch_execute(query.as_str()))
+                    .map_err(|err| ReplibyteError::Connection(format!("{}", err)))?;
+            }
+        } else if self.create_database {
+            let client = connect(self.config(MAINTENANCE_DATABASE))?;
+
+            let rows = block_on(client.simple_query(database_exists_query(self.database).as_str()))
+                .map_err(|err| ReplibyteError::Connection(format!("{}", err)))?;
+
+            let exists = rows
+                .iter()
+                .any(|message| matches!(message, SimpleQueryMessage::Row(_)));
+
+            if !exists {
+                let create_query =
+                    create_database_query(self.dialect, self.database, self.database_template)?;
+                block_on(client.batch_execute(create_query.as_str()))
+                    .map_err(|err| ReplibyteError::Connection(format!("{}", err)))?;
+            }
+        }
+
+        let client = connect(self.config(self.database))?;
+
+        let wipe_db_query = match self.wipe_strategy {
+            WipeStrategy::DropSchema => Some(drop_schemas_query(
+                self.dialect,
+                self.username,
+                self.schemas,
+            )?),
+            WipeStrategy::TruncateTables => Some(truncate_tables_query(self.schemas)),
+            WipeStrategy::DropDatabaseRecreate | WipeStrategy::None => None,
+        };
+
+        if let Some(wipe_db_query) = wipe_db_query {
+            block_on(client.batch_execute(wipe_db_query.as_str()))
+                .map_err(|err| ReplibyteError::Connection(format!("{}", err)))?;
+        }
+
+        self.client = Some(client);
+
+        Ok(())
+    }
+}
+
+fn connect(config: tokio_postgres::Config) -> Result<Client, ReplibyteError> {
+    block_on(async move {
+        let (client, connection) = config.connect(NoTls).await?;
+
+        // the connection object performs the actual communication with the database
+        tokio::spawn(async move {
+            if let Err(err) = connection.await {
+                error!("postgres connection error: {}", err);
+            }
+        });
+
+        Ok::<Client, tokio_postgres::Error>(client)
+    })
+    .map_err(|err| ReplibyteError::Connection(format!("{}", err)))
+}
+
+impl<'a> Destination for PostgresNative<'a> {
+    fn write_chunks(&self, chunks: &mut dyn Iterator<Item = Bytes>) -> Result<(), ReplibyteError> {
+        let client = self.client()?;
+
+        let chunks = wrap_chunks(
+            chunks,
+            match self.disable_triggers {
+                true => Some(DISABLE_TRIGGERS_QUERIES),
+                false => None,
+            },
+        );
+
+        // the rows of the COPY blocks are streamed through the COPY sub-protocol, like psql does
+        let mut copy_progress = Progress::default();
+        let mut copy_sink: Option<Pin<Box<CopyInSink<bytes::Bytes>>>> = None;
+
+        for data in chunks {
+            for statement in chunk_statements(data.as_slice(), &mut copy_progress)? {
+                match statement {
+                    Statement::Queries(queries) => block_on(client.batch_execute(queries.as_str()))
+                        .map_err(|err| ReplibyteError::Connection(format!("{}", err)))?,
+                    Statement::CopyIn(query) => {
+                        let sink = block_on(client.copy_in(query.as_str()))
+                            .map_err(|err| ReplibyteError::Connection(format!("{}", err)))?;
+                        copy_sink = Some(Box::pin(sink));
+                    }
+                    Statement::CopyData(rows) => {
+                        let sink = copy_sink.as_mut().ok_or_else(|| {
+                            ReplibyteError::Parse(
+                                "COPY rows without a COPY ... FROM stdin; query".to_string(),
+                            )
+                        })?;
+
+                        block_on(sink.send(bytes::Bytes::from(rows)))
+                            .map_err(|err| ReplibyteError::Connection(format!("{}", err)))?;
+                    }
+                    Statement::EndOfCopy => {
+                        if let Some(mut sink) = copy_sink.take() {
+                            block_on(sink.as_mut().finish())
+                                .map_err(|err| ReplibyteError::Connection(format!("{}", err)))?;
+                        }
+                    }
+                }
+            }
+        }
+
+        // a COPY block without its end of data marker, e.g. at the end of a truncated dump
+        if let Some(mut sink) = copy_sink.take() {
+            block_on(sink.as_mut().finish())
+                .map_err(|err| ReplibyteError::Connection(format!("{}", err)))?;
+        }
+
+        Ok(())
+    }
+
+    fn sync_sequences(&self) -> Result<(), ReplibyteError> {
+        match self.dialect {
+            PostgresDialect::Postgres => {
+                block_on(self.client()?.batch_execute(SYNC_SEQUENCES_QUERY))
+                    .map_err(|err| ReplibyteError::Connection(format!("{}", err)))
+            }
+            PostgresDialect::CockroachDB => Ok(()),
+        }
+    }
+}
+
+/// a part of a chunk sent to the database
+#[derive(Debug, PartialEq, Eq)]
+enum Statement {
+    /// complete queries, sent as a single simple query
+    Queries(String),
+    /// the `COPY ... FROM stdin;` query starting a COPY block
+    CopyIn(String),
+    /// rows of the current COPY block
+    CopyData(Bytes),
+    /// the end of the current COPY block
+    EndOfCopy,
+}
+
+/// split a chunk into its queries and its COPY blocks, a COPY block can go on in the next chunks.
+/// `copy_progress` tells if the chunk starts in a COPY block, and if the next one does.
+fn chunk_statements(
+    chunk: &[u8],
+    copy_progress: &mut Progress,
+) -> Result<Vec<Statement>, ReplibyteError> {
+    let mut statements = vec![];
+    let mut queries = vec![];
+    let mut rows = vec![];
+
+    let to_string = |bytes: Bytes| {
+        String::from_utf8(bytes)
+            .map_err(|err| ReplibyteError::Parse(format!("the dump is not valid UTF-8: {}", err)))
+    };
+
+    let lines = chunk.strip_suffix(b"\n").unwrap_or(chunk);
+
+    for line in lines.split(|byte| *byte == b'\n') {
+        let is_copy = copy_progress.is_copy();
+        copy_progress.count_rows(line);
+
+        match (is_copy, copy_progress.is_copy()) {
+            (false, false) => {
+                queries.extend_from_slice(line);
+                queries.push(b'\n');
+            }
+            (false, true) => {
+                // the queries before a COPY block are complete
+                let queries = std::mem::take(&mut queries);
+                if queries.iter().any(|byte| !byte.is_ascii_whitespace()) {
+                    statements.push(Statement::Queries(to_string(queries)?));
+                }
+
+                statements.push(Statement::CopyIn(to_string(line.to_vec())?));
+            }
+            (true, true) => {
+                rows.extend_from_slice(line);
+                rows.push(b'\n');
+            }
+            (true, false) => {
+                if !rows.is_empty() {
+                    statements.push(Statement::CopyData(std::mem::take(&mut rows)));
+                }
+
+                statements.push(Statement::EndOfCopy);
+            }
+        }
+    }
+
+    if !rows.is_empty() {
+        statements.push(Statement::CopyData(rows));
+    }
+
+    if queries.iter().any(|byte| !byte.is_ascii_whitespace()) {
+        statements.push(Statement::Queries(to_string(queries)?));
+    }
+
+    Ok(statements)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{PostgresDialect, SslMode, TlsConfig, WipeStrategy};
+    use crate::connector::Connector;
+    use crate::destination::postgres::MAINTENANCE_DATABASE;
+    use crate::destination::postgres_native::{chunk_statements, PostgresNative, Statement};
+    use crate::destination::Destination;
+    use crate::runtime::block_on;
+    use crate::tasks::Progress;
+
+    static TLS_CONFIG: TlsConfig = TlsConfig {
+        ssl_mode: None,
+        ssl_cert: None,
+        ssl_key: None,
+        ssl_root_cert: None,
+        parameters: Vec::new(),
+    };
+
+    fn get_postgres() -> PostgresNative<'static> {
+        PostgresNative::new(
+            "localhost",
+            5453,
+            "root",
+            "root",
+            "password",
+            WipeStrategy::DropSchema,
+            PostgresDialect::Postgres,
+            false,
+            None,
+            &[],
+            false,
+            &TLS_CONFIG,
+        )
+    }
+
+    #[test]
+    fn write_without_init() {
+        let p = get_postgres();
+        assert!(p.write_chunks(&mut std::iter::once(b"SELECT 1".to_vec())).is_err());
+    }
+
+    #[test]
+    fn config() {
+        let config = get_postgres().config("root");
+        assert_eq!(config.get_ports(), &[5453]);
+        assert_eq!(config.get_dbname(), Some("root"));
+        assert_eq!(config.get_user(), Some("root"));
+        assert_eq!(config.get_password(), Some("password".as_bytes()));
+
+        let config = get_postgres().config(MAINTENANCE_DATABASE);
+        assert_eq!(config.get_dbname(), Some("postgres"));
+    }
+
+    #[test]
+    fn tls_requires_psql() {
+        let tls = TlsConfig {
+            ssl_mode: Some(SslMode::Require),
+            ..TlsConfig::default()
+        };
+
+        let mut p = PostgresNative::new(
+            "localhost",
+            5453,
+            "root",
+            "root",
+            "password",
+            WipeStrategy::DropSchema,
+            PostgresDialect::Postgres,
+            false,
+            None,
+            &[],
+            false,
+            &tls,
+        );
+
+        assert!(p.init().is_err());
+    }
+
+    #[test]
+    fn split_copy_blocks() {
+        let mut copy_progress = Progress::default();
+
+        let chunk = b"CREATE TABLE public.users (id integer, email text);\nCOPY public.users (id, email) FROM stdin;\n1\talice@example.com\n";
+        assert_eq!(
+            chunk_statements(chunk, &mut copy_progress).unwrap(),
+            vec![
+                Statement::Queries(
+                    "CREATE TABLE public.users (id integer, email text);\n".to_string()
+                ),
+                Statement::CopyIn("COPY public.users (id, email) FROM stdin;".to_string()),
+                Statement::CopyData(b"1\talice@example.com\n".to_vec()),
+            ]
+        );
+        assert!(copy_progress.is_copy());
+
+        // the COPY block goes on in the next chunk
+        let chunk = b"2\tbob@example.com\n\\.\nALTER TABLE public.users OWNER TO root;\n";
+        assert_eq!(
+            chunk_statements(chunk, &mut copy_progress).unwrap(),
+            vec![
+                Statement::CopyData(b"2\tbob@example.com\n".to_vec()),
+                Statement::EndOfCopy,
+                Statement::Queries("ALTER TABLE public.users OWNER TO root;\n".to_string()),
+            ]
+        );
+        assert!(!copy_progress.is_copy());
+
+        let chunk = b"SELECT 1;\n";
+        assert_eq!(
+            chunk_statements(chunk, &mut copy_progress).unwrap(),
+            vec![Statement::Queries("SELECT 1;\n".to_string())]
+        );
+    }
+
+    #[test]
+    fn write_copy_blocks() {
+        let mut p = get_postgres();
+        let _ = p.init().expect("can't init postgres");
+
+        let chunks = vec![
+            b"CREATE TABLE public.users (id integer, email text);\nCOPY public.users (id, email) FROM stdin;\n1\talice@example.com\n".to_vec(),
+            b"2\t\\N\n\\.\nINSERT INTO public.users (id, email) VALUES (3, 'carol@example.com');\n".to_vec(),
+        ];
+        p.write_chunks(&mut chunks.into_iter()).unwrap();
+
+        let rows = block_on(
+            p.client()
+                .unwrap()
+                .query("SELECT id, email FROM public.users ORDER BY id", &[]),
+        )
+        .unwrap();
+
+        let users = rows
+            .iter()
+            .map(|row| (row.get::<_, i32>(0), row.get::<_, Option<String>>(1)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            users,
+            vec![
+                (1, Some("alice@example.com".to_string())),
+                (2, None),
+                (3, Some("carol@example.com".to_string())),
+            ]
+        );
+    }
+}
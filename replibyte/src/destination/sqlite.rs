@@ -0,0 +1,96 @@
+use std::fs::remove_file;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::connector::Connector;
+use crate::destination::{pipe_chunks, Destination};
+use crate::errors::ReplibyteError;
+use crate::types::Bytes;
+use crate::utils::{binary_exists, wait_for_command};
+
+pub struct SQLite<'a> {
+    path: &'a str,
+    wipe_database: bool,
+}
+
+impl<'a> SQLite<'a> {
+    pub fn new(path: &'a str, wipe_database: bool) -> Self {
+        SQLite {
+            path,
+            wipe_database,
+        }
+    }
+}
+
+impl<'a> Connector for SQLite<'a> {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        binary_exists("sqlite3")?;
+
+        // a SQLite database is a single file, wiping it means starting from a new file
+        if self.wipe_database && Path::new(self.path).exists() {
+            remove_file(self.path)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Destination for SQLite<'a> {
+    fn write_chunks(&self, chunks: &mut dyn Iterator<Item = Bytes>) -> Result<(), ReplibyteError> {
+        let mut process = Command::new("sqlite3")
+            .args(["-bail", self.path])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // if sqlite3 stops early, the error is returned by wait_for_command
+        let _ = pipe_chunks(process.stdin.take().unwrap(), chunks);
+
+        wait_for_command(&mut process)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::write;
+
+    use tempfile::tempdir;
+
+    use crate::connector::Connector;
+    use crate::destination::sqlite::SQLite;
+    use crate::destination::Destination;
+
+    #[test]
+    fn connect() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("replibyte.db");
+
+        let mut sqlite = SQLite::new(path.to_str().unwrap(), true);
+        assert!(sqlite.init().is_ok());
+        assert!(sqlite
+            .write_chunks(&mut std::iter::once(b"SELECT 1;".to_vec()))
+            .is_ok());
+        assert!(sqlite
+            .write_chunks(&mut std::iter::once(
+                b"SELECT * FROM unknown_table;".to_vec()
+            ))
+            .is_err());
+    }
+
+    #[test]
+    fn wipe_database() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("replibyte.db");
+        let path = path.to_str().unwrap();
+        write(path, b"not empty").unwrap();
+
+        let mut sqlite = SQLite::new(path, false);
+        assert!(sqlite.init().is_ok());
+        assert!(dir.path().join("replibyte.db").exists());
+
+        let mut sqlite = SQLite::new(path, true);
+        assert!(sqlite.init().is_ok());
+        assert!(!dir.path().join("replibyte.db").exists());
+    }
+}
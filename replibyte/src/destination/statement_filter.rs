@@ -0,0 +1,288 @@
+use crate::connector::Connector;
+use crate::destination::{
+    is_session_query, strip_conditional_comment, write_filtered_chunks, Destination,
+};
+use crate::errors::ReplibyteError;
+use crate::source::postgres::is_large_object_query;
+use crate::types::Bytes;
+use crate::utils::strip_prefix_ignore_case;
+
+/// queries restoring the data of the tables
+const DATA_QUERY_PREFIXES: [&str; 6] = [
+    "INSERT INTO",
+    "COPY",                     // PostgreSQL COPY blocks, along with their rows
+    "SELECT pg_catalog.setval", // PostgreSQL sequence values
+    "LOCK TABLES",
+    "UNLOCK TABLES",
+    "SET IDENTITY_INSERT", // SQL Server explicit identity values
+];
+
+/// transaction queries, i.e. the large objects of PostgreSQL are written between `BEGIN;` and `COMMIT;`
+const TRANSACTION_QUERIES: [&str; 2] = ["BEGIN;", "COMMIT;"];
+
+/// part of the dump to restore
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RestoreMode {
+    All,
+    /// only the queries creating the database objects (e.g. `CREATE TABLE`, `CREATE INDEX`)
+    SchemaOnly,
+    /// only the queries restoring the data (e.g. `INSERT INTO`, the `COPY` blocks)
+    DataOnly,
+}
+
+impl RestoreMode {
+    pub fn new(schema_only: bool, data_only: bool) -> Self {
+        match (schema_only, data_only) {
+            (true, _) => RestoreMode::SchemaOnly,
+            (_, true) => RestoreMode::DataOnly,
+            _ => RestoreMode::All,
+        }
+    }
+}
+
+/// StatementFilterDestination only writes the schema or the data queries into the wrapped destination.
+/// Session queries (e.g. `SET ...`) and transaction queries are always written.
+pub struct StatementFilterDestination<'a, D>
+where
+    D: Destination,
+{
+    destination: &'a mut D,
+    mode: RestoreMode,
+}
+
+impl<'a, D> StatementFilterDestination<'a, D>
+where
+    D: Destination,
+{
+    pub fn new(destination: &'a mut D, mode: RestoreMode) -> Self {
+        StatementFilterDestination { destination, mode }
+    }
+
+    fn is_selected(&self, query: &str) -> bool {
+        if is_session_query(query) && !is_data_query(query) {
+            return true;
+        }
+
+        if is_transaction_query(query) {
+            return true;
+        }
+
+        match self.mode {
+            RestoreMode::All => true,
+            RestoreMode::SchemaOnly => !is_data_query(query),
+            RestoreMode::DataOnly => is_data_query(query),
+        }
+    }
+}
+
+impl<'a, D> Connector for StatementFilterDestination<'a, D>
+where
+    D: Destination,
+{
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        self.destination.init()
+    }
+}
+
+impl<'a, D> Destination for StatementFilterDestination<'a, D>
+where
+    D: Destination,
+{
+    fn write_chunks(&self, chunks: &mut dyn Iterator<Item = Bytes>) -> Result<(), ReplibyteError> {
+        if self.mode == RestoreMode::All {
+            return self.destination.write_chunks(chunks);
+        }
+
+        write_filtered_chunks(self.destination, chunks, |query| self.is_selected(query))
+    }
+}
+
+/// true if the query restores data rather than creating a database object
+pub fn is_data_query(query: &str) -> bool {
+    let query = strip_conditional_comment(query);
+
+    if DATA_QUERY_PREFIXES
+        .iter()
+        .any(|prefix| strip_prefix_ignore_case(query, prefix).is_some())
+    {
+        return true;
+    }
+
+    // PostgreSQL large objects are created and written by `pg_dump` with the data
+    if is_large_object_query(query) {
+        return true;
+    }
+
+    // MySQL: /*!40000 ALTER TABLE `users` DISABLE KEYS */;
+    strip_prefix_ignore_case(query, "ALTER TABLE").is_some() && {
+        let query = query.to_ascii_uppercase();
+        query.contains(" DISABLE KEYS") || query.contains(" ENABLE KEYS")
+    }
+}
+
+/// true if the query begins or commits a transaction
+pub fn is_transaction_query(query: &str) -> bool {
+    TRANSACTION_QUERIES
+        .iter()
+        .any(|transaction_query| query.trim().eq_ignore_ascii_case(transaction_query))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use crate::connector::Connector;
+    use crate::destination::statement_filter::{
+        is_data_query, RestoreMode, StatementFilterDestination,
+    };
+    use crate::destination::Destination;
+    use crate::errors::ReplibyteError;
+    use crate::types::Bytes;
+
+    const DUMP: &[u8] = b"SET client_encoding = 'UTF8';
+CREATE TABLE public.users (id integer);
+INSERT INTO public.users (id) VALUES (1);
+SELECT pg_catalog.setval('public.users_id_seq', 1, true);
+ALTER TABLE ONLY public.users ADD CONSTRAINT users_pkey PRIMARY KEY (id);
+SELECT pg_catalog.lo_create('16392');
+BEGIN;
+SELECT pg_catalog.lo_open('16392', 131072);
+SELECT pg_catalog.lo_close(0);
+COMMIT;
+";
+
+    /// the COPY block goes on in the second chunk
+    const COPY_DUMP: [&[u8]; 2] = [
+        b"SET client_encoding = 'UTF8';
+CREATE TABLE public.users (id integer, email text);
+COPY public.users (id, email) FROM stdin;
+1\t  alice@example.com
+",
+        b"2\t\\N
+\\.
+ALTER TABLE ONLY public.users ADD CONSTRAINT users_pkey PRIMARY KEY (id);
+",
+    ];
+
+    struct RecordingDestination {
+        chunks: RefCell<Vec<String>>,
+    }
+
+    impl Connector for RecordingDestination {
+        fn init(&mut self) -> Result<(), ReplibyteError> {
+            Ok(())
+        }
+    }
+
+    impl Destination for RecordingDestination {
+        fn write_chunks(
+            &self,
+            chunks: &mut dyn Iterator<Item = Bytes>,
+        ) -> Result<(), ReplibyteError> {
+            for chunk in chunks {
+                self.chunks
+                    .borrow_mut()
+                    .push(String::from_utf8(chunk).unwrap());
+            }
+
+            Ok(())
+        }
+    }
+
+    fn restore(mode: RestoreMode) -> String {
+        restore_chunks(mode, &[DUMP])
+    }
+
+    fn restore_chunks(mode: RestoreMode, chunks: &[&[u8]]) -> String {
+        let mut destination = RecordingDestination {
+            chunks: RefCell::new(vec![]),
+        };
+
+        let filter_destination = StatementFilterDestination::new(&mut destination, mode);
+        filter_destination
+            .write_chunks(&mut chunks.iter().map(|chunk| chunk.to_vec()))
+            .unwrap();
+
+        destination.chunks.into_inner().join("")
+    }
+
+    #[test]
+    fn data_queries() {
+        assert!(is_data_query("INSERT INTO users (id) VALUES (1);"));
+        assert!(is_data_query("LOCK TABLES `users` WRITE;"));
+        assert!(is_data_query(
+            "/*!40000 ALTER TABLE `users` DISABLE KEYS */;"
+        ));
+        assert!(is_data_query("SET IDENTITY_INSERT [dbo].[users] ON;"));
+        assert!(is_data_query("COPY public.users (id, email) FROM stdin;"));
+        assert!(is_data_query("SELECT pg_catalog.lo_create('16392');"));
+        assert!(is_data_query(
+            "SELECT pg_catalog.lowrite(0, '\\x48656c6c6f');"
+        ));
+        assert!(is_data_query("ALTER LARGE OBJECT 16392 OWNER TO postgres;"));
+        assert!(is_data_query(
+            "GRANT SELECT ON LARGE OBJECT 16392 TO reader;"
+        ));
+        assert!(!is_data_query(
+            "GRANT SELECT ON TABLE public.users TO reader;"
+        ));
+        assert!(!is_data_query("CREATE TABLE users (id integer);"));
+        assert!(!is_data_query(
+            "ALTER TABLE ONLY public.users ADD CONSTRAINT users_pkey PRIMARY KEY (id);"
+        ));
+    }
+
+    #[test]
+    fn schema_only() {
+        assert_eq!(
+            restore(RestoreMode::SchemaOnly),
+            "SET client_encoding = 'UTF8';
+CREATE TABLE public.users (id integer);
+ALTER TABLE ONLY public.users ADD CONSTRAINT users_pkey PRIMARY KEY (id);
+BEGIN;
+COMMIT;
+"
+        );
+    }
+
+    #[test]
+    fn data_only() {
+        assert_eq!(
+            restore(RestoreMode::DataOnly),
+            "SET client_encoding = 'UTF8';
+INSERT INTO public.users (id) VALUES (1);
+SELECT pg_catalog.setval('public.users_id_seq', 1, true);
+SELECT pg_catalog.lo_create('16392');
+BEGIN;
+SELECT pg_catalog.lo_open('16392', 131072);
+SELECT pg_catalog.lo_close(0);
+COMMIT;
+"
+        );
+    }
+
+    #[test]
+    fn schema_only_copy() {
+        assert_eq!(
+            restore_chunks(RestoreMode::SchemaOnly, &COPY_DUMP),
+            "SET client_encoding = 'UTF8';
+CREATE TABLE public.users (id integer, email text);
+ALTER TABLE ONLY public.users ADD CONSTRAINT users_pkey PRIMARY KEY (id);
+"
+        );
+    }
+
+    #[test]
+    fn data_only_copy() {
+        // the COPY rows are written as they are
+        assert_eq!(
+            restore_chunks(RestoreMode::DataOnly, &COPY_DUMP),
+            "SET client_encoding = 'UTF8';
+COPY public.users (id, email) FROM stdin;
+1\t  alice@example.com
+2\t\\N
+\\.
+"
+        );
+    }
+}
@@ -0,0 +1,234 @@
+use crate::connector::Connector;
+use crate::destination::{strip_conditional_comment, write_filtered_chunks, Destination};
+use crate::errors::ReplibyteError;
+use crate::types::Bytes;
+use crate::utils::{parse_identifier, strip_prefix_ignore_case};
+
+/// queries targeting a table, the table name follows the prefix
+const TABLE_QUERY_PREFIXES: [&str; 8] = [
+    "INSERT INTO",
+    "COPY", // the rows of a COPY block are written with its `COPY ... FROM stdin;` query
+    "CREATE TABLE",
+    "DROP TABLE",
+    "ALTER TABLE",
+    "TRUNCATE TABLE",
+    "LOCK TABLES",
+    "SET IDENTITY_INSERT",
+];
+
+/// TableFilterDestination only writes the queries of the selected tables into the wrapped destination.
+/// The queries which do not target a table (e.g. `SET ...`) are always written.
+/// Without selected tables, the chunks are written as they are.
+pub struct TableFilterDestination<'a, D>
+where
+    D: Destination,
+{
+    destination: &'a mut D,
+    tables: &'a [String],
+}
+
+impl<'a, D> TableFilterDestination<'a, D>
+where
+    D: Destination,
+{
+    pub fn new(destination: &'a mut D, tables: &'a [String]) -> Self {
+        TableFilterDestination {
+            destination,
+            tables,
+        }
+    }
+
+    fn is_selected(&self, query: &str) -> bool {
+        match query_table_name(query) {
+            Some(table_name) => self
+                .tables
+                .iter()
+                .any(|table| is_same_table(table_name.as_str(), table.as_str())),
+            None => true,
+        }
+    }
+}
+
+impl<'a, D> Connector for TableFilterDestination<'a, D>
+where
+    D: Destination,
+{
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        self.destination.init()
+    }
+}
+
+impl<'a, D> Destination for TableFilterDestination<'a, D>
+where
+    D: Destination,
+{
+    fn write_chunks(&self, chunks: &mut dyn Iterator<Item = Bytes>) -> Result<(), ReplibyteError> {
+        if self.tables.is_empty() {
+            return self.destination.write_chunks(chunks);
+        }
+
+        write_filtered_chunks(self.destination, chunks, |query| self.is_selected(query))
+    }
+}
+
+/// return the name of the table targeted by the query, if any
+pub fn query_table_name(query: &str) -> Option<String> {
+    let query = strip_conditional_comment(query);
+
+    let table_definition = TABLE_QUERY_PREFIXES
+        .iter()
+        .find_map(|prefix| strip_prefix_ignore_case(query, prefix))
+        .or_else(|| {
+            // CREATE [UNIQUE] INDEX <name> ON [ONLY] <table>
+            let index_definition = strip_prefix_ignore_case(query, "CREATE UNIQUE INDEX")
+                .or_else(|| strip_prefix_ignore_case(query, "CREATE INDEX"))?;
+            let idx = index_definition.to_ascii_uppercase().find(" ON ")?;
+            Some(index_definition[idx + " ON ".len()..].trim_start())
+        })?;
+
+    let table_definition = ["IF NOT EXISTS", "IF EXISTS", "ONLY"].iter().fold(
+        table_definition,
+        |table_definition, keyword| {
+            strip_prefix_ignore_case(table_definition, keyword).unwrap_or(table_definition)
+        },
+    );
+
+    parse_identifier(table_definition).map(|(table_name, _)| table_name)
+}
+
+/// a table selected without schema (e.g. `users`) matches the table in any schema (e.g. `public.users`)
+fn is_same_table(table_name: &str, selected_table: &str) -> bool {
+    table_name == selected_table
+        || (!selected_table.contains('.') && table_name.rsplit('.').next() == Some(selected_table))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use crate::connector::Connector;
+    use crate::destination::table_filter::{query_table_name, TableFilterDestination};
+    use crate::destination::Destination;
+    use crate::errors::ReplibyteError;
+    use crate::types::Bytes;
+
+    struct RecordingDestination {
+        chunks: RefCell<Vec<String>>,
+    }
+
+    impl Connector for RecordingDestination {
+        fn init(&mut self) -> Result<(), ReplibyteError> {
+            Ok(())
+        }
+    }
+
+    impl Destination for RecordingDestination {
+        fn write_chunks(
+            &self,
+            chunks: &mut dyn Iterator<Item = Bytes>,
+        ) -> Result<(), ReplibyteError> {
+            for chunk in chunks {
+                self.chunks
+                    .borrow_mut()
+                    .push(String::from_utf8(chunk).unwrap());
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn table_names() {
+        assert_eq!(
+            query_table_name("INSERT INTO public.users (id) VALUES (1);"),
+            Some("public.users".to_string())
+        );
+        assert_eq!(
+            query_table_name(
+                "ALTER TABLE ONLY public.orders ADD CONSTRAINT orders_pkey PRIMARY KEY (id);"
+            ),
+            Some("public.orders".to_string())
+        );
+        assert_eq!(
+            query_table_name("DROP TABLE IF EXISTS `users`;"),
+            Some("users".to_string())
+        );
+        assert_eq!(
+            query_table_name("/*!40000 ALTER TABLE `users` DISABLE KEYS */;"),
+            Some("users".to_string())
+        );
+        assert_eq!(
+            query_table_name("SET IDENTITY_INSERT [dbo].[users] ON;"),
+            Some("dbo.users".to_string())
+        );
+        assert_eq!(
+            query_table_name(
+                "CREATE UNIQUE INDEX users_email ON ONLY public.users USING btree (email);"
+            ),
+            Some("public.users".to_string())
+        );
+        assert_eq!(
+            query_table_name("COPY public.users (id, email) FROM stdin;"),
+            Some("public.users".to_string())
+        );
+        assert_eq!(query_table_name("SET statement_timeout = 0;"), None);
+        assert_eq!(
+            query_table_name("CREATE SEQUENCE public.users_id_seq START WITH 1;"),
+            None
+        );
+    }
+
+    #[test]
+    fn filter_tables() {
+        let mut destination = RecordingDestination {
+            chunks: RefCell::new(vec![]),
+        };
+
+        let tables = vec!["users".to_string()];
+        let filter_destination = TableFilterDestination::new(&mut destination, &tables);
+
+        let mut chunks = vec![
+            b"SET client_encoding = 'UTF8';\nCREATE TABLE public.users (id integer);\nCREATE TABLE public.orders (id integer);\n".to_vec(),
+            b"INSERT INTO public.orders (id) VALUES (1);\n".to_vec(),
+            b"INSERT INTO public.users (id) VALUES (1);\n".to_vec(),
+        ]
+        .into_iter();
+
+        filter_destination.write_chunks(&mut chunks).unwrap();
+
+        assert_eq!(
+            destination.chunks.into_inner(),
+            vec![
+                "SET client_encoding = 'UTF8';\nCREATE TABLE public.users (id integer);\n",
+                "INSERT INTO public.users (id) VALUES (1);\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn filter_copy_tables() {
+        let mut destination = RecordingDestination {
+            chunks: RefCell::new(vec![]),
+        };
+
+        let tables = vec!["users".to_string()];
+        let filter_destination = TableFilterDestination::new(&mut destination, &tables);
+
+        // the COPY block of public.orders goes on in the second chunk
+        let mut chunks = vec![
+            b"CREATE TABLE public.users (id integer);\nCREATE TABLE public.orders (id integer);\nCOPY public.orders (id) FROM stdin;\n1\n".to_vec(),
+            b"2\n\\.\nCOPY public.users (id) FROM stdin;\n1\n\\.\n".to_vec(),
+        ]
+        .into_iter();
+
+        filter_destination.write_chunks(&mut chunks).unwrap();
+
+        assert_eq!(
+            destination.chunks.into_inner(),
+            vec![
+                "CREATE TABLE public.users (id integer);\n",
+                "COPY public.users (id) FROM stdin;\n1\n\\.\n",
+            ]
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use std::io::{Error, ErrorKind};
+
+use dump_parser::errors::DumpFileError;
+
+/// Error of Replibyte, each category has its own exit code.
+///
+/// The sources, destinations, datastores and tasks return a `ReplibyteError`: the errors of the
+/// I/O operations are converted into `ReplibyteError::Io`, which has no category.
+#[derive(Debug, thiserror::Error)]
+pub enum ReplibyteError {
+    /// invalid configuration file or command line arguments
+    #[error("{0}")]
+    Config(String),
+    /// a database, a proxy, an ssh tunnel or a cloud API can't be reached
+    #[error("{0}")]
+    Connection(String),
+    /// an external command (pg_dump, mysqldump, docker...) failed
+    #[error("{0}")]
+    Command(String),
+    /// a dump can't be parsed
+    #[error("{0}")]
+    Parse(String),
+    /// a datastore operation failed
+    #[error("{0}")]
+    Datastore(String),
+    /// an encryption key or a KMS operation failed
+    #[error("{0}")]
+    Crypto(String),
+    /// an I/O operation failed
+    #[error(transparent)]
+    Io(#[from] Error),
+}
+
+impl ReplibyteError {
+    /// exit code of the CLI for the error, 1 if the error has no category
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ReplibyteError::Config(_) => 2,
+            ReplibyteError::Connection(_) => 3,
+            ReplibyteError::Command(_) => 4,
+            ReplibyteError::Parse(_) => 5,
+            ReplibyteError::Datastore(_) => 6,
+            ReplibyteError::Crypto(_) => 7,
+            ReplibyteError::Io(_) => 1,
+        }
+    }
+
+    /// the same error with its message prefixed by the context, e.g. `datastore #2: ...`
+    pub fn with_context(self, context: &str) -> Self {
+        match self {
+            ReplibyteError::Config(msg) => ReplibyteError::Config(format!("{}: {}", context, msg)),
+            ReplibyteError::Connection(msg) => {
+                ReplibyteError::Connection(format!("{}: {}", context, msg))
+            }
+            ReplibyteError::Command(msg) => {
+                ReplibyteError::Command(format!("{}: {}", context, msg))
+            }
+            ReplibyteError::Parse(msg) => ReplibyteError::Parse(format!("{}: {}", context, msg)),
+            ReplibyteError::Datastore(msg) => {
+                ReplibyteError::Datastore(format!("{}: {}", context, msg))
+            }
+            ReplibyteError::Crypto(msg) => ReplibyteError::Crypto(format!("{}: {}", context, msg)),
+            ReplibyteError::Io(err) => {
+                ReplibyteError::Io(Error::new(err.kind(), format!("{}: {}", context, err)))
+            }
+        }
+    }
+
+    /// kind of the I/O error, `ErrorKind::InvalidData` for a dump which can't be parsed,
+    /// and `ErrorKind::Other` for the other categories
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ReplibyteError::Io(err) => err.kind(),
+            ReplibyteError::Parse(_) => ErrorKind::InvalidData,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+impl From<serde_json::Error> for ReplibyteError {
+    fn from(err: serde_json::Error) -> Self {
+        ReplibyteError::Io(Error::from(err))
+    }
+}
+
+/// a malformed dump (e.g. not valid UTF-8) is a `Parse` error, the failed reads are `Io` errors
+impl From<DumpFileError> for ReplibyteError {
+    fn from(err: DumpFileError) -> Self {
+        match err {
+            DumpFileError::ReadError(err) if err.kind() == ErrorKind::InvalidData => {
+                ReplibyteError::Parse(err.to_string())
+            }
+            DumpFileError::ReadError(err) => ReplibyteError::Io(err),
+            DumpFileError::MalFormatted => {
+                ReplibyteError::Parse("the dump is malformed".to_string())
+            }
+            DumpFileError::DoesNotExist => ReplibyteError::Io(Error::new(
+                ErrorKind::NotFound,
+                "the dump file does not exist",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufReader, Error, ErrorKind};
+
+    use dump_parser::utils::{list_sql_queries_from_dump_reader, ListQueryResult};
+
+    use crate::errors::ReplibyteError;
+
+    #[test]
+    fn exit_codes() {
+        let err = ReplibyteError::Config("bad config".to_string());
+        assert_eq!(err.exit_code(), 2);
+
+        let err = ReplibyteError::Crypto("bad key".to_string());
+        assert_eq!(err.exit_code(), 7);
+
+        let err = ReplibyteError::from(Error::new(ErrorKind::Other, "no category"));
+        assert_eq!(err.to_string(), "no category");
+        assert_eq!(err.exit_code(), 1);
+    }
+
+    #[test]
+    fn context() {
+        let err =
+            ReplibyteError::Datastore("bucket not found".to_string()).with_context("datastore #2");
+        assert_eq!(err.to_string(), "datastore #2: bucket not found");
+        assert_eq!(err.exit_code(), 6);
+
+        let err = ReplibyteError::from(Error::new(ErrorKind::NotFound, "no such file"))
+            .with_context("datastore #1");
+        assert_eq!(err.to_string(), "datastore #1: no such file");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn malformed_dump() {
+        let dump = b"INSERT INTO public.users (name) VALUES ('caf\xe9');\n";
+
+        let err = ReplibyteError::from(
+            list_sql_queries_from_dump_reader(BufReader::new(&dump[..]), |_| {
+                ListQueryResult::Continue
+            })
+            .unwrap_err(),
+        );
+
+        assert!(matches!(err, ReplibyteError::Parse(_)));
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(err.exit_code(), 5);
+    }
+}
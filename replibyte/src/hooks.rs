@@ -0,0 +1,451 @@
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use log::info;
+
+use crate::config::{
+    ConnectionUri, DestinationConfig, HookAction, HookConfig, NotificationStatus, SourceConfig,
+    TlsConfig,
+};
+use crate::errors::ReplibyteError;
+use crate::proxy::{database_proxy, proxy_connection_uri};
+use crate::ssh::tunnel_connection_uri;
+use crate::utils::wait_for_command;
+
+/// database the SQL scripts of the hooks run on
+#[derive(Clone, Copy)]
+pub enum HookDatabase<'a> {
+    Source(&'a SourceConfig),
+    Destination(&'a DestinationConfig),
+}
+
+impl<'a> HookDatabase<'a> {
+    /// run the script through the SSH tunnel or the proxy of the database
+    fn run_sql(&self, script: &str, single_transaction: bool) -> Result<(), ReplibyteError> {
+        let (connection_uri, tls, ssh, proxy) = match self {
+            HookDatabase::Source(source) => (
+                source.connection_uri()?,
+                source.tls()?,
+                source.ssh.as_ref(),
+                source.proxy()?,
+            ),
+            HookDatabase::Destination(destination) => (
+                destination.connection_uri()?,
+                destination.tls()?,
+                destination.ssh.as_ref(),
+                destination.proxy()?,
+            ),
+        };
+
+        let proxy = database_proxy(proxy, ssh, &connection_uri)?;
+
+        // the tunnels are closed when they go out of scope
+        let (connection_uri, _ssh_tunnel) =
+            tunnel_connection_uri(ssh, proxy.as_ref(), connection_uri)?;
+
+        let (connection_uri, _proxy_tunnel) = match ssh {
+            // the SSH connection goes through the proxy instead
+            Some(_) => (connection_uri, None),
+            None => proxy_connection_uri(proxy.as_ref(), connection_uri)?,
+        };
+
+        run_sql(&connection_uri, &tls, script, single_transaction)
+    }
+}
+
+/// run the hooks in order, the first failing hook stops the others. The `after_*` hooks get the
+/// outcome of the command, and only run if they are enabled for it.
+pub fn run(
+    stage: &str,
+    hooks: Option<&[HookConfig]>,
+    dump_name: &str,
+    status: Option<NotificationStatus>,
+    database: Option<HookDatabase>,
+) -> Result<(), ReplibyteError> {
+    for (idx, hook) in hooks.unwrap_or(&[]).iter().enumerate() {
+        if let Some(status) = status {
+            if !hook.is_enabled(status) {
+                continue;
+            }
+        }
+
+        info!("running the {} hook #{}", stage, idx + 1);
+
+        let result = match hook.action()? {
+            HookAction::Command(command) => run_command(command, stage, dump_name, status),
+            HookAction::Sql(script) => run_database_sql(database, stage, script),
+            HookAction::SqlFile(path) => fs::read_to_string(path)
+                .map_err(|err| {
+                    ReplibyteError::Config(format!("cannot read the SQL file '{}': {}", path, err))
+                })
+                .and_then(|script| run_database_sql(database, stage, script.as_str())),
+        };
+
+        result.map_err(|err| {
+            ReplibyteError::Command(format!("{} hook #{} failed: {}", stage, idx + 1, err))
+        })?;
+    }
+
+    Ok(())
+}
+
+fn run_command(
+    command: &str,
+    stage: &str,
+    dump_name: &str,
+    status: Option<NotificationStatus>,
+) -> Result<(), ReplibyteError> {
+    let mut process = Command::new("sh");
+    let _ = process
+        .args(["-c", command])
+        .env("REPLIBYTE_HOOK", stage)
+        .env("REPLIBYTE_DUMP", dump_name);
+
+    if let Some(status) = status {
+        let _ = process.env(
+            "REPLIBYTE_STATUS",
+            match status {
+                NotificationStatus::Success => "success",
+                NotificationStatus::Failure => "failure",
+            },
+        );
+    }
+
+    // the output of the command is the one of replibyte
+    let exit_status = process.status()?;
+    if !exit_status.success() {
+        return Err(ReplibyteError::Command(format!(
+            "command error: {}",
+            exit_status
+        )));
+    }
+
+    Ok(())
+}
+
+fn run_database_sql(
+    database: Option<HookDatabase>,
+    stage: &str,
+    script: &str,
+) -> Result<(), ReplibyteError> {
+    match database {
+        Some(database) => database.run_sql(script, false),
+        None => Err(ReplibyteError::Config(format!(
+            "the SQL scripts of the {} hooks need the <{}> object in the configuration file",
+            stage,
+            match stage.ends_with("_dump") {
+                true => "source",
+                false => "destination",
+            }
+        ))),
+    }
+}
+
+/// the fixup scripts of the destination concatenated in order, they are read before the restore so
+/// that a missing file does not waste it
+pub fn fixup_script(destination: &DestinationConfig) -> Result<Option<String>, ReplibyteError> {
+    let paths = match &destination.fixup_scripts {
+        Some(paths) if !paths.is_empty() => paths,
+        _ => return Ok(None),
+    };
+
+    let mut script = String::new();
+    for path in paths {
+        let content = fs::read_to_string(path).map_err(|err| {
+            ReplibyteError::Config(format!("cannot read the fixup script '{}': {}", path, err))
+        })?;
+
+        script.push_str(content.trim_end());
+        script.push('\n');
+    }
+
+    Ok(Some(script))
+}
+
+/// run the fixup scripts on the restored destination in a single transaction, the MySQL and MariaDB
+/// statements changing the schema commit it though
+pub fn run_fixup_script(
+    destination: &DestinationConfig,
+    script: &str,
+) -> Result<(), ReplibyteError> {
+    info!("running the fixup scripts");
+
+    HookDatabase::Destination(destination)
+        .run_sql(script, true)
+        .map_err(|err| ReplibyteError::Command(format!("the fixup scripts failed: {}", err)))
+}
+
+/// run the script with the client of the database, the first failing statement stops it
+fn run_sql(
+    connection_uri: &ConnectionUri,
+    tls: &TlsConfig,
+    script: &str,
+    single_transaction: bool,
+) -> Result<(), ReplibyteError> {
+    let script = match single_transaction {
+        true => transaction_script(connection_uri, script),
+        false => script.to_string(),
+    };
+    let script = script.as_str();
+
+    let mut command = match connection_uri {
+        ConnectionUri::Postgres(host, port, username, password, database) => {
+            let mut command = Command::new("psql");
+            let _ = command
+                .env("PGPASSWORD", password)
+                .envs(tls.postgres_env()?)
+                .args([
+                    "-h",
+                    host,
+                    "-p",
+                    port.to_string().as_str(),
+                    "-d",
+                    database,
+                    "-U",
+                    username,
+                    "-v",
+                    "ON_ERROR_STOP=1",
+                ]);
+            command
+        }
+        ConnectionUri::Mysql(host, port, username, password, database)
+        | ConnectionUri::MariaDB(host, port, username, password, database) => {
+            let mut command = Command::new(match connection_uri {
+                ConnectionUri::MariaDB(..) => "mariadb",
+                _ => "mysql",
+            });
+            let _ = command
+                .args([
+                    "-h",
+                    host,
+                    "-P",
+                    port.to_string().as_str(),
+                    "-u",
+                    username,
+                    &format!("-p{}", password),
+                ])
+                .args(tls.mysql_args()?)
+                .arg(database);
+            command
+        }
+        ConnectionUri::MSSQL(host, port, username, password, database) => {
+            let mut command = Command::new("sqlcmd");
+            let _ = command.env("SQLCMDPASSWORD", password).args([
+                "-S",
+                format!("{},{}", host, port).as_str(),
+                "-U",
+                username,
+                "-d",
+                database,
+                "-b", // exit with an error code if the script fails
+                "-Q",
+                script,
+            ]);
+            command
+        }
+        ConnectionUri::SQLite(path) => {
+            let mut command = Command::new("sqlite3");
+            let _ = command.args(["-bail", path]);
+            command
+        }
+        ConnectionUri::MongoDB(_, _) => {
+            return Err(ReplibyteError::Config(
+                "the SQL hooks are not supported by MongoDB, run mongosh with a `command` hook"
+                    .to_string(),
+            ))
+        }
+    };
+
+    let mut process = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let mut stdin = process.stdin.take().unwrap();
+
+    // sqlcmd gets the script as an argument
+    if !matches!(connection_uri, ConnectionUri::MSSQL(..)) {
+        // if the client stops early, the error is returned by wait_for_command
+        let _ = stdin.write_all(script.as_bytes());
+    }
+
+    // the end of the script
+    drop(stdin);
+
+    wait_for_command(&mut process)
+}
+
+/// the script in a transaction, which is rolled back when the client stops at a failing statement
+fn transaction_script(connection_uri: &ConnectionUri, script: &str) -> String {
+    match connection_uri {
+        ConnectionUri::Mysql(..) | ConnectionUri::MariaDB(..) => {
+            format!("START TRANSACTION;\n{}\nCOMMIT;\n", script)
+        }
+        // the failing statements abort the transaction instead of the batch
+        ConnectionUri::MSSQL(..) => format!(
+            "SET XACT_ABORT ON;\nBEGIN TRANSACTION;\n{}\nCOMMIT TRANSACTION;\n",
+            script
+        ),
+        _ => format!("BEGIN;\n{}\nCOMMIT;\n", script),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use crate::config::{
+        ConnectionUri, DestinationConfig, HookConfig, NotificationStatus, SourceConfig,
+    };
+    use crate::hooks::{fixup_script, run, run_fixup_script, transaction_script, HookDatabase};
+
+    fn hooks(yaml: &str) -> Vec<HookConfig> {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn command_hooks() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("hooks.log");
+        let command = format!(
+            "command: echo $REPLIBYTE_HOOK $REPLIBYTE_DUMP $REPLIBYTE_STATUS >> {}",
+            path.display()
+        );
+
+        let before_dump = hooks(format!("- {}", command).as_str());
+        assert!(run("before_dump", Some(&before_dump), "dump-1", None, None).is_ok());
+
+        let after_dump =
+            hooks(format!("- {}\n  on: [success, failure]\n- {}", command, command).as_str());
+        assert!(run(
+            "after_dump",
+            Some(&after_dump),
+            "dump-1",
+            Some(NotificationStatus::Failure),
+            None
+        )
+        .is_ok());
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "before_dump dump-1\nafter_dump dump-1 failure\n"
+        );
+
+        let failing = hooks("- command: exit 3\n- command: echo never");
+        let err = run("before_restore", Some(&failing), "dump-1", None, None).unwrap_err();
+        assert!(err.to_string().contains("before_restore hook #1 failed"));
+
+        let invalid = hooks("- command: echo\n  sql: SELECT 1");
+        assert!(run("before_dump", Some(&invalid), "dump-1", None, None).is_err());
+
+        // the SQL scripts need a database
+        let sql = hooks("- sql: SELECT 1");
+        assert!(run("after_restore", Some(&sql), "dump-1", None, None).is_err());
+    }
+
+    #[test]
+    fn sql_hooks() {
+        if std::process::Command::new("sqlite3")
+            .arg("-version")
+            .output()
+            .is_err()
+        {
+            return;
+        }
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("replibyte.db");
+        let source: SourceConfig =
+            serde_yaml::from_str(format!("connection_uri: sqlite://{}", path.display()).as_str())
+                .unwrap();
+
+        let sql = hooks(
+            "- sql: CREATE TABLE users (id INTEGER); INSERT INTO users VALUES (1);\n\
+             - sql: SELECT * FROM missing_table;\n",
+        );
+        let err = run(
+            "before_dump",
+            Some(&sql),
+            "dump-1",
+            None,
+            Some(HookDatabase::Source(&source)),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("before_dump hook #2 failed"));
+
+        let output = std::process::Command::new("sqlite3")
+            .args([path.to_str().unwrap(), "SELECT count(*) FROM users"])
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "1");
+    }
+
+    #[test]
+    fn fixup_scripts() {
+        assert_eq!(
+            transaction_script(
+                &ConnectionUri::SQLite("/tmp/replibyte.db".to_string()),
+                "DELETE FROM users;"
+            ),
+            "BEGIN;\nDELETE FROM users;\nCOMMIT;\n"
+        );
+
+        if std::process::Command::new("sqlite3")
+            .arg("-version")
+            .output()
+            .is_err()
+        {
+            return;
+        }
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("replibyte.db");
+        let reset_passwords = dir.path().join("reset_passwords.sql");
+        let disable_webhooks = dir.path().join("disable_webhooks.sql");
+        fs::write(&reset_passwords, "UPDATE users SET password = 'dev';\n").unwrap();
+        fs::write(&disable_webhooks, "DELETE FROM webhooks;\n").unwrap();
+
+        std::process::Command::new("sqlite3")
+            .args([
+                path.to_str().unwrap(),
+                "CREATE TABLE users (password TEXT); INSERT INTO users VALUES ('s3cret');",
+            ])
+            .output()
+            .unwrap();
+
+        let destination: DestinationConfig = serde_yaml::from_str(
+            format!(
+                "connection_uri: sqlite://{}\nfixup_scripts: [{}, {}]",
+                path.display(),
+                reset_passwords.display(),
+                disable_webhooks.display()
+            )
+            .as_str(),
+        )
+        .unwrap();
+
+        let script = fixup_script(&destination).unwrap().unwrap();
+        assert_eq!(
+            script,
+            "UPDATE users SET password = 'dev';\nDELETE FROM webhooks;\n"
+        );
+
+        // the webhooks table does not exist, the passwords are not reset either
+        assert!(run_fixup_script(&destination, script.as_str()).is_err());
+        let password = || {
+            let output = std::process::Command::new("sqlite3")
+                .args([path.to_str().unwrap(), "SELECT password FROM users"])
+                .output()
+                .unwrap();
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        assert_eq!(password(), "s3cret");
+
+        assert!(run_fixup_script(&destination, "UPDATE users SET password = 'dev';").is_ok());
+        assert_eq!(password(), "dev");
+    }
+}
@@ -0,0 +1,99 @@
+use std::str::FromStr;
+
+use aws_sdk_kms::types::Blob;
+use aws_sdk_kms::{Client, Endpoint as SdkEndpoint};
+
+use crate::aws::sdk_config;
+use crate::config::{AwsCredentials, Endpoint};
+use crate::errors::ReplibyteError;
+use crate::kms::Kms;
+use crate::runtime::block_on;
+use crate::types::Bytes;
+
+/// AWS KMS, the key id can be a key id, a key ARN or an alias
+pub struct AwsKms {
+    key_id: String,
+    client: Client,
+}
+
+impl AwsKms {
+    pub fn new(
+        key_id: String,
+        region: Option<String>,
+        profile: Option<String>,
+        credentials: Option<AwsCredentials>,
+        endpoint: Endpoint,
+    ) -> Result<Self, ReplibyteError> {
+        let sdk_config = sdk_config(region, profile, credentials)?;
+        let kms_config_builder = aws_sdk_kms::config::Builder::from(&sdk_config);
+
+        let kms_config = match &endpoint {
+            Endpoint::Default => kms_config_builder.build(),
+            Endpoint::Custom(url) => match http::Uri::from_str(url.as_str()) {
+                Ok(uri) => kms_config_builder
+                    .endpoint_resolver(SdkEndpoint::immutable(uri))
+                    .build(),
+                Err(_) => kms_config_builder.build(),
+            },
+        };
+
+        Ok(AwsKms {
+            key_id,
+            client: Client::from_conf(kms_config),
+        })
+    }
+}
+
+impl Kms for AwsKms {
+    fn key_id(&self) -> &str {
+        self.key_id.as_str()
+    }
+
+    fn encrypt_data_key(&self, data_key: &[u8]) -> Result<Bytes, ReplibyteError> {
+        let output = block_on(
+            self.client
+                .encrypt()
+                .key_id(self.key_id.as_str())
+                .plaintext(Blob::new(data_key))
+                .send(),
+        )
+        .map_err(|err| {
+            ReplibyteError::Crypto(format!(
+                "failed to encrypt the data key with AWS KMS key '{}': {}",
+                self.key_id, err
+            ))
+        })?;
+
+        output
+            .ciphertext_blob()
+            .map(|blob| blob.as_ref().to_vec())
+            .ok_or_else(|| {
+                ReplibyteError::Crypto("AWS KMS returned no encrypted data key".to_string())
+            })
+    }
+
+    fn decrypt_data_key(
+        &self,
+        key_id: &str,
+        encrypted_data_key: &[u8],
+    ) -> Result<Bytes, ReplibyteError> {
+        let output = block_on(
+            self.client
+                .decrypt()
+                .key_id(key_id)
+                .ciphertext_blob(Blob::new(encrypted_data_key))
+                .send(),
+        )
+        .map_err(|err| {
+            ReplibyteError::Crypto(format!(
+                "failed to decrypt the data key with AWS KMS key '{}': {}",
+                key_id, err
+            ))
+        })?;
+
+        output
+            .plaintext()
+            .map(|blob| blob.as_ref().to_vec())
+            .ok_or_else(|| ReplibyteError::Crypto("AWS KMS returned no data key".to_string()))
+    }
+}
@@ -0,0 +1,152 @@
+use std::io::Error;
+use std::time::Duration;
+
+use reqwest::blocking::Client;
+use reqwest::header::CONTENT_TYPE;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::config::Endpoint;
+use crate::errors::ReplibyteError;
+use crate::gcp::{check_response, Credentials};
+use crate::kms::Kms;
+use crate::types::Bytes;
+
+const CLOUD_KMS_API_URL: &str = "https://cloudkms.googleapis.com";
+const CLOUD_KMS_SCOPE: &str = "https://www.googleapis.com/auth/cloudkms";
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Deserialize)]
+struct EncryptResponse {
+    ciphertext: String,
+}
+
+#[derive(Deserialize)]
+struct DecryptResponse {
+    plaintext: String,
+}
+
+/// Google Cloud KMS, the key name is `projects/<project>/locations/<location>/keyRings/<key ring>/cryptoKeys/<key>`
+pub struct GcpKms {
+    key_name: String,
+    endpoint: String,
+    client: Client,
+    credentials: Credentials,
+}
+
+impl GcpKms {
+    /// the service account key file is `GOOGLE_APPLICATION_CREDENTIALS` by default,
+    /// the workload identity is used if none is set
+    pub fn new(
+        key_name: String,
+        service_account_key: Option<String>,
+        endpoint: Endpoint,
+    ) -> Result<Self, ReplibyteError> {
+        let client = Client::builder()
+            .timeout(TIMEOUT)
+            .build()
+            .map_err(|err| ReplibyteError::Crypto(err.to_string()))?;
+
+        let endpoint = match endpoint {
+            Endpoint::Default => CLOUD_KMS_API_URL.to_string(),
+            Endpoint::Custom(url) => url.trim_end_matches('/').to_string(),
+        };
+
+        Ok(GcpKms {
+            key_name,
+            endpoint,
+            client,
+            credentials: Credentials::new(service_account_key, CLOUD_KMS_SCOPE)?,
+        })
+    }
+
+    fn key_url(&self, key_name: &str, method: &str) -> String {
+        format!("{}/v1/{}:{}", self.endpoint, key_name, method)
+    }
+
+    fn call(
+        &self,
+        key_name: &str,
+        method: &str,
+        body: serde_json::Value,
+    ) -> Result<Bytes, ReplibyteError> {
+        let response = check_response(
+            self.client
+                .post(self.key_url(key_name, method))
+                .bearer_auth(self.credentials.access_token(&self.client)?)
+                .header(CONTENT_TYPE, "application/json")
+                .body(body.to_string())
+                .send(),
+        )
+        .map_err(|err| {
+            Error::new(
+                err.kind(),
+                format!(
+                    "failed to {} the data key with Google Cloud KMS key '{}': {}",
+                    method, key_name, err
+                ),
+            )
+        })?;
+
+        response
+            .bytes()
+            .map(|data| data.to_vec())
+            .map_err(|err| ReplibyteError::Crypto(err.to_string()))
+    }
+}
+
+impl Kms for GcpKms {
+    fn key_id(&self) -> &str {
+        self.key_name.as_str()
+    }
+
+    fn encrypt_data_key(&self, data_key: &[u8]) -> Result<Bytes, ReplibyteError> {
+        let response = self.call(
+            self.key_name.as_str(),
+            "encrypt",
+            json!({ "plaintext": base64::encode(data_key) }),
+        )?;
+
+        let response: EncryptResponse = serde_json::from_slice(response.as_slice())?;
+        base64::decode(response.ciphertext).map_err(|err| ReplibyteError::Crypto(err.to_string()))
+    }
+
+    fn decrypt_data_key(
+        &self,
+        key_id: &str,
+        encrypted_data_key: &[u8],
+    ) -> Result<Bytes, ReplibyteError> {
+        let response = self.call(
+            key_id,
+            "decrypt",
+            json!({ "ciphertext": base64::encode(encrypted_data_key) }),
+        )?;
+
+        let response: DecryptResponse = serde_json::from_slice(response.as_slice())?;
+        base64::decode(response.plaintext).map_err(|err| ReplibyteError::Crypto(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Endpoint;
+    use crate::kms::gcp::GcpKms;
+
+    #[test]
+    fn key_urls() {
+        let kms = GcpKms::new(
+            "projects/replibyte/locations/global/keyRings/dumps/cryptoKeys/current".to_string(),
+            None,
+            Endpoint::Custom("http://localhost:8080/".to_string()),
+        )
+        .unwrap();
+
+        assert_eq!(
+            kms.key_url(
+                "projects/replibyte/locations/global/keyRings/dumps/cryptoKeys/previous",
+                "decrypt"
+            ),
+            "http://localhost:8080/v1/projects/replibyte/locations/global/keyRings/dumps/cryptoKeys/previous:decrypt"
+        );
+    }
+}
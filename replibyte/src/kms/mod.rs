@@ -0,0 +1,86 @@
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::errors::ReplibyteError;
+use crate::types::Bytes;
+
+pub mod aws;
+pub mod gcp;
+
+/// the dumps are encrypted with AES-256, the data keys are 256 bits long
+const DATA_KEY_LENGTH: usize = 32;
+
+/// Key Management Service encrypting the data keys the dumps are encrypted with (envelope encryption)
+pub trait Kms: Send + Sync {
+    /// id of the key encrypting the new data keys
+    fn key_id(&self) -> &str;
+    fn encrypt_data_key(&self, data_key: &[u8]) -> Result<Bytes, ReplibyteError>;
+    /// decrypt a data key encrypted with the key of the given id, which may not be the current one
+    fn decrypt_data_key(
+        &self,
+        key_id: &str,
+        encrypted_data_key: &[u8],
+    ) -> Result<Bytes, ReplibyteError>;
+
+    /// generate a random data key, returned along with its encrypted form
+    fn generate_data_key(&self) -> Result<(Bytes, Bytes), ReplibyteError> {
+        let mut data_key = vec![0u8; DATA_KEY_LENGTH];
+        SystemRandom::new()
+            .fill(data_key.as_mut_slice())
+            .map_err(|_| ReplibyteError::Crypto("failed to generate a data key".to_string()))?;
+
+        let encrypted_data_key = self.encrypt_data_key(data_key.as_slice())?;
+        Ok((data_key, encrypted_data_key))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::errors::ReplibyteError;
+    use crate::kms::Kms;
+    use crate::types::Bytes;
+
+    /// KMS "encrypting" the data keys by prefixing them with the key id, for the tests only
+    pub struct LocalKms {
+        pub key_id: String,
+    }
+
+    impl Kms for LocalKms {
+        fn key_id(&self) -> &str {
+            self.key_id.as_str()
+        }
+
+        fn encrypt_data_key(&self, data_key: &[u8]) -> Result<Bytes, ReplibyteError> {
+            Ok([format!("{}:", self.key_id).as_bytes(), data_key].concat())
+        }
+
+        fn decrypt_data_key(
+            &self,
+            key_id: &str,
+            encrypted_data_key: &[u8],
+        ) -> Result<Bytes, ReplibyteError> {
+            encrypted_data_key
+                .strip_prefix(format!("{}:", key_id).as_bytes())
+                .map(|data_key| data_key.to_vec())
+                .ok_or_else(|| ReplibyteError::Crypto("invalid data key".to_string()))
+        }
+    }
+
+    #[test]
+    fn generate_data_key() {
+        let kms = LocalKms {
+            key_id: "key-1".to_string(),
+        };
+
+        let (data_key, encrypted_data_key) = kms.generate_data_key().unwrap();
+        assert_eq!(data_key.len(), 32);
+        assert_ne!(kms.generate_data_key().unwrap().0, data_key);
+        assert_eq!(
+            kms.decrypt_data_key("key-1", encrypted_data_key.as_slice())
+                .unwrap(),
+            data_key
+        );
+        assert!(kms
+            .decrypt_data_key("key-2", encrypted_data_key.as_slice())
+            .is_err());
+    }
+}
@@ -0,0 +1,303 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use crate::errors::ReplibyteError;
+use crate::utils::binary_exists;
+
+pub const KUBECTL_BINARY_NAME: &str = "kubectl";
+pub const DEFAULT_IMAGE: &str = "ghcr.io/qovery/replibyte";
+
+/// path of the configuration file in the container of the Job
+const CONFIG_PATH: &str = "/etc/replibyte/replibyte.yaml";
+/// the status of a Job is updated a bit after the end of its pod
+const STATUS_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// `kubectl` with the context and the namespace to use, the current ones otherwise
+pub struct Kubectl {
+    context: Option<String>,
+    namespace: Option<String>,
+}
+
+impl Kubectl {
+    pub fn new(context: Option<String>, namespace: Option<String>) -> Result<Self, ReplibyteError> {
+        let _ = binary_exists(KUBECTL_BINARY_NAME)?;
+
+        Ok(Kubectl { context, namespace })
+    }
+
+    fn command(&self) -> Command {
+        let mut command = Command::new(KUBECTL_BINARY_NAME);
+
+        if let Some(context) = &self.context {
+            let _ = command.args(["--context", context.as_str()]);
+        }
+
+        if let Some(namespace) = &self.namespace {
+            let _ = command.args(["--namespace", namespace.as_str()]);
+        }
+
+        command
+    }
+
+    /// create or update the resources of the manifest
+    pub fn apply(&self, manifest: &Value) -> Result<(), ReplibyteError> {
+        let mut process = self
+            .command()
+            .args(["apply", "-f", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        process
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(manifest.to_string().as_bytes())?;
+
+        let output = process.wait_with_output()?;
+        if !output.status.success() {
+            return Err(ReplibyteError::Command(format!(
+                "kubectl apply failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// stream the logs of the pod of the Job until it ends, the pod is waited for up to `timeout`
+    pub fn follow_logs(&self, job: &str, timeout: Duration) -> Result<(), ReplibyteError> {
+        let status = self
+            .command()
+            .args([
+                "logs",
+                "--follow",
+                format!("--pod-running-timeout={}s", timeout.as_secs()).as_str(),
+                format!("job/{}", job).as_str(),
+            ])
+            .status()?;
+
+        if !status.success() {
+            return Err(ReplibyteError::Command(format!(
+                "unable to read the logs of the job '{}'",
+                job
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn job_status(&self, job: &str) -> Result<JobStatus, ReplibyteError> {
+        let output = self
+            .command()
+            .args([
+                "get",
+                "job",
+                job,
+                "--output",
+                "jsonpath={.status.succeeded}/{.status.failed}",
+            ])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(ReplibyteError::Command(format!(
+                "unable to get the status of the job '{}': {}",
+                job,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(parse_job_status(
+            String::from_utf8_lossy(&output.stdout).as_ref(),
+        ))
+    }
+
+    /// the status of the Job once it is finished, an error if it is still running after `STATUS_TIMEOUT`
+    pub fn wait_for_job(&self, job: &str) -> Result<JobStatus, ReplibyteError> {
+        let started_at = Instant::now();
+
+        loop {
+            let status = self.job_status(job)?;
+            if status != JobStatus::Running {
+                return Ok(status);
+            }
+
+            if started_at.elapsed() > STATUS_TIMEOUT {
+                return Err(ReplibyteError::Command(format!(
+                    "the job '{}' is still running, check it with `kubectl describe job {}`",
+                    job, job
+                )));
+            }
+
+            thread::sleep(Duration::from_secs(2));
+        }
+    }
+
+    /// delete a resource, e.g. `delete("job", "replibyte-restore-1652280000000")`
+    pub fn delete(&self, kind: &str, name: &str) -> Result<(), ReplibyteError> {
+        let output = self
+            .command()
+            .args(["delete", kind, name, "--ignore-not-found"])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(ReplibyteError::Command(format!(
+                "unable to delete the {} '{}': {}",
+                kind,
+                name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// `1/` once the Job succeeded, `/1` once it failed
+fn parse_job_status(status: &str) -> JobStatus {
+    let (succeeded, failed) = status.trim().split_once('/').unwrap_or(("", ""));
+    let is_positive = |count: &str| matches!(count.parse::<u32>(), Ok(count) if count > 0);
+
+    if is_positive(succeeded) {
+        JobStatus::Succeeded
+    } else if is_positive(failed) {
+        JobStatus::Failed
+    } else {
+        JobStatus::Running
+    }
+}
+
+/// Secret holding the configuration file and Job running Replibyte with it, both named `name`
+pub fn job_manifest(
+    name: &str,
+    image: &str,
+    config: &str,
+    args: Vec<String>,
+    env_from_secrets: &[String],
+) -> Value {
+    let labels = json!({
+        "app.kubernetes.io/name": "replibyte",
+        "app.kubernetes.io/instance": name,
+    });
+
+    let mut container_args = vec![
+        "--config".to_string(),
+        CONFIG_PATH.to_string(),
+        "--quiet".to_string(),
+    ];
+    container_args.extend(args);
+
+    let env_from = env_from_secrets
+        .iter()
+        .map(|secret| json!({"secretRef": {"name": secret}}))
+        .collect::<Vec<_>>();
+
+    json!({
+        "apiVersion": "v1",
+        "kind": "List",
+        "items": [
+            {
+                "apiVersion": "v1",
+                "kind": "Secret",
+                "metadata": {"name": name, "labels": labels},
+                "stringData": {"replibyte.yaml": config},
+            },
+            {
+                "apiVersion": "batch/v1",
+                "kind": "Job",
+                "metadata": {"name": name, "labels": labels},
+                "spec": {
+                    // a failed restore is not retried, the destination may be half restored
+                    "backoffLimit": 0,
+                    "template": {
+                        "metadata": {"labels": labels},
+                        "spec": {
+                            "restartPolicy": "Never",
+                            "containers": [{
+                                "name": "replibyte",
+                                "image": image,
+                                "args": container_args,
+                                "envFrom": env_from,
+                                "volumeMounts": [{
+                                    "name": "config",
+                                    "mountPath": "/etc/replibyte",
+                                    "readOnly": true,
+                                }],
+                            }],
+                            "volumes": [{
+                                "name": "config",
+                                "secret": {"secretName": name},
+                            }],
+                        },
+                    },
+                },
+            },
+        ],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::kubernetes::{job_manifest, parse_job_status, JobStatus};
+
+    #[test]
+    fn job_statuses() {
+        assert_eq!(parse_job_status("/"), JobStatus::Running);
+        assert_eq!(parse_job_status("1/"), JobStatus::Succeeded);
+        assert_eq!(parse_job_status("/1"), JobStatus::Failed);
+        assert_eq!(parse_job_status(""), JobStatus::Running);
+    }
+
+    #[test]
+    fn restore_job_manifest() {
+        let manifest = job_manifest(
+            "replibyte-restore-1652280000000",
+            "ghcr.io/qovery/replibyte:0.9.2",
+            "datastore: {}",
+            vec!["dump".to_string(), "restore".to_string()],
+            &["replibyte-env".to_string()],
+        );
+
+        let secret = &manifest["items"][0];
+        assert_eq!(secret["kind"], "Secret");
+        assert_eq!(secret["stringData"]["replibyte.yaml"], "datastore: {}");
+
+        let job = &manifest["items"][1];
+        assert_eq!(job["kind"], "Job");
+        assert_eq!(job["spec"]["backoffLimit"], 0);
+
+        let container = &job["spec"]["template"]["spec"]["containers"][0];
+        assert_eq!(
+            container["args"],
+            json!([
+                "--config",
+                "/etc/replibyte/replibyte.yaml",
+                "--quiet",
+                "dump",
+                "restore"
+            ])
+        );
+        assert_eq!(
+            container["envFrom"],
+            json!([{"secretRef": {"name": "replibyte-env"}}])
+        );
+        assert_eq!(
+            job["spec"]["template"]["spec"]["volumes"][0]["secret"]["secretName"],
+            "replibyte-restore-1652280000000"
+        );
+    }
+}
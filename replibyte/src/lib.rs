@@ -0,0 +1,54 @@
+//! Replibyte as a library: the sources, transformers, datastores and destinations of the `replibyte` binary,
+//! to dump, anonymize and restore databases from other Rust tools without invoking the CLI.
+//!
+//! ```no_run
+//! use replibyte_core::datastore::local_disk::LocalDisk;
+//! use replibyte_core::datastore::ReadOptions;
+//! use replibyte_core::destination::generic_file::GenericFile;
+//! use replibyte_core::source::sqlite::SQLite;
+//! use replibyte_core::transformer::email::EmailTransformer;
+//! use replibyte_core::transformer::Transformer;
+//! use replibyte_core::Pipeline;
+//!
+//! let datastore = LocalDisk::new("/tmp/replibyte");
+//! let transformers: Vec<Box<dyn Transformer>> =
+//!     vec![Box::new(EmailTransformer::new("main", "users", "email"))];
+//!
+//! let mut pipeline = Pipeline::new(Box::new(datastore))?.transformers(transformers);
+//! let dump = pipeline.dump(SQLite::new("/tmp/production.db"), |_progress| {})?;
+//!
+//! let mut destination = GenericFile::new("/tmp/anonymized.sql");
+//! pipeline.restore(&mut destination, ReadOptions::Dump { name: dump }, |_progress| {})?;
+//! # Ok::<(), replibyte_core::errors::ReplibyteError>(())
+//! ```
+
+pub mod aws;
+pub mod cli;
+pub mod config;
+pub mod connector;
+pub mod credentials;
+pub mod datastore;
+pub mod destination;
+pub mod errors;
+pub mod gcp;
+pub mod kms;
+pub mod logging;
+pub mod migration;
+pub mod pipeline;
+pub mod proxy;
+pub mod runtime;
+pub mod secrets;
+pub mod seed;
+pub mod source;
+pub mod ssh;
+pub mod tasks;
+pub mod throttle;
+pub mod transformer;
+pub mod types;
+pub mod utils;
+
+pub use crate::datastore::Datastore;
+pub use crate::destination::Destination;
+pub use crate::pipeline::Pipeline;
+pub use crate::source::{Source, SourceOptions};
+pub use crate::transformer::Transformer;
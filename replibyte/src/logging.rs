@@ -0,0 +1,137 @@
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use chrono::{SecondsFormat, Utc};
+use log::{info, Record};
+use serde_json::{json, Map, Value};
+
+/// target of the structured events, e.g. `RUST_LOG=replibyte::event=info` only logs the events
+pub const EVENT_TARGET: &str = "replibyte::event";
+
+static JSON_FORMAT: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl LogFormat {
+    pub fn parse(format: &str) -> Option<Self> {
+        match format {
+            "text" => Some(LogFormat::Text),
+            "json" => Some(LogFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// initialize the logger, the levels are read from `level` or `RUST_LOG` (e.g. `info` or `replibyte::event=info`)
+pub fn init(format: LogFormat, level: Option<&str>) {
+    let mut builder = env_logger::Builder::from_default_env();
+
+    if let Some(level) = level {
+        let _ = builder.parse_filters(level);
+    }
+
+    if format == LogFormat::Json {
+        JSON_FORMAT.store(true, Ordering::SeqCst);
+        let _ = builder.format(|buf, record| writeln!(buf, "{}", json_line(record)));
+    }
+
+    builder.init();
+}
+
+/// log a structured event, e.g. `event("chunk_uploaded", json!({"part": 1, "bytes": 1024}))`
+pub fn event(name: &str, fields: Value) {
+    if JSON_FORMAT.load(Ordering::SeqCst) {
+        info!(target: EVENT_TARGET, "{}", json_event(name, fields));
+    } else {
+        info!(target: EVENT_TARGET, "{}", text_event(name, &fields));
+    }
+}
+
+fn json_event(name: &str, fields: Value) -> Value {
+    let mut event = Map::new();
+    let _ = event.insert("event".to_string(), Value::String(name.to_string()));
+
+    if let Value::Object(fields) = fields {
+        event.extend(fields);
+    }
+
+    Value::Object(event)
+}
+
+/// `dump_started dump=dump-1652280000000`
+fn text_event(name: &str, fields: &Value) -> String {
+    let mut event = name.to_string();
+
+    if let Value::Object(fields) = fields {
+        for (key, value) in fields {
+            let value = match value {
+                Value::String(value) => value.clone(),
+                value => value.to_string(),
+            };
+
+            event.push_str(format!(" {}={}", key, value).as_str());
+        }
+    }
+
+    event
+}
+
+fn json_line(record: &Record) -> Value {
+    let mut line = Map::new();
+    let _ = line.insert(
+        "timestamp".to_string(),
+        Value::String(Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)),
+    );
+    let _ = line.insert(
+        "level".to_string(),
+        Value::String(record.level().to_string()),
+    );
+    let _ = line.insert(
+        "target".to_string(),
+        Value::String(record.target().to_string()),
+    );
+
+    let message = record.args().to_string();
+
+    // the fields of the events are serialized in their message
+    match serde_json::from_str::<Value>(message.as_str()) {
+        Ok(Value::Object(fields)) if record.target() == EVENT_TARGET => line.extend(fields),
+        _ => {
+            let _ = line.insert("message".to_string(), json!(message));
+        }
+    }
+
+    Value::Object(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::logging::{json_event, text_event, LogFormat};
+
+    #[test]
+    fn parse_log_format() {
+        assert_eq!(LogFormat::parse("text"), Some(LogFormat::Text));
+        assert_eq!(LogFormat::parse("json"), Some(LogFormat::Json));
+        assert_eq!(LogFormat::parse("xml"), None);
+    }
+
+    #[test]
+    fn format_events() {
+        let fields = json!({"table": "public.users", "rows": 42});
+
+        assert_eq!(
+            text_event("table_transformed", &fields),
+            "table_transformed table=public.users rows=42"
+        );
+        assert_eq!(
+            json_event("table_transformed", fields),
+            json!({"event": "table_transformed", "table": "public.users", "rows": 42})
+        );
+    }
+}
@@ -1,11 +1,13 @@
 #[macro_use]
 extern crate prettytable;
 
-use std::fs::File;
-use std::sync::mpsc;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
 use std::sync::mpsc::Receiver;
+use std::sync::{mpsc, Arc};
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::{env, thread};
 
 use clap::Parser;
@@ -13,78 +15,138 @@ use indicatif::{ProgressBar, ProgressStyle};
 use migration::{migrations, Migrator};
 use utils::get_replibyte_version;
 
-use crate::cli::{DumpCommand, RestoreCommand, SubCommand, TransformerCommand, CLI};
-use crate::config::{Config, DatabaseSubsetConfig, DatastoreConfig};
+use crate::cli::{
+    DevCommand, DumpCommand, RestoreCommand, SeedCommand, SubCommand, TransformerCommand, CLI,
+};
+use crate::config::{load_env_file, parse_config, BandwidthConfig, Config, DatastoreConfig};
+use crate::datastore::azure::AzureBlobStorage;
+use crate::datastore::dedup::Dedup;
+use crate::datastore::gcs::GoogleCloudStorage;
+use crate::datastore::http::Http;
 use crate::datastore::local_disk::LocalDisk;
+use crate::datastore::replicated::Replicated;
 use crate::datastore::s3::S3;
+use crate::datastore::sftp::Sftp;
+use crate::datastore::throttled::Throttled;
 use crate::datastore::Datastore;
-use crate::source::{Source, SourceOptions};
-use crate::tasks::{MaxBytes, TransferredBytes};
+use crate::errors::ReplibyteError;
+use crate::logging::LogFormat;
+use crate::metrics::Metrics;
+use crate::proxy::ssh_proxy;
+use crate::tasks::Progress;
 use crate::telemetry::{ClientOptions, TelemetryClient, TELEMETRY_TOKEN};
+use crate::throttle::Throttle;
 use crate::utils::epoch_millis;
 
-mod cli;
+use replibyte_core::{
+    cli, config, credentials, datastore, destination, errors, logging, migration, proxy, seed,
+    source, ssh, tasks, throttle, transformer, types, utils,
+};
+
 mod commands;
-mod config;
-mod connector;
-mod datastore;
-mod destination;
-mod migration;
-mod runtime;
-mod source;
-mod tasks;
+mod daemon;
+mod hooks;
+mod kubernetes;
+mod metrics;
+mod notifications;
 mod telemetry;
-mod transformer;
-mod types;
-mod utils;
 
-fn show_progress_bar(rx_pb: Receiver<(TransferredBytes, MaxBytes)>) {
+fn show_progress_bar(rx_pb: Receiver<Progress>) {
     let pb = ProgressBar::new(0);
-    pb.set_style(ProgressStyle::default_spinner());
+    pb.set_style(spinner_style());
 
+    let start_time = Instant::now();
     let mut style_is_progress_bar = false;
     let mut _max_bytes = 0usize;
-    let mut last_transferred_bytes = 0usize;
+    let mut progress = Progress::default();
 
     loop {
-        let (transferred_bytes, max_bytes) = match rx_pb.try_recv() {
-            Ok(msg) => msg,
-            Err(_) => (last_transferred_bytes, _max_bytes),
-        };
+        // only the last progress is shown
+        while let Ok(last_progress) = rx_pb.try_recv() {
+            progress = last_progress;
+        }
 
         if _max_bytes == 0 && style_is_progress_bar {
             // show spinner if there is no max_bytes indicated
-            pb.set_style(ProgressStyle::default_spinner());
+            pb.set_style(spinner_style());
             style_is_progress_bar = false;
         } else if _max_bytes > 0 && !style_is_progress_bar {
             pb.set_style(ProgressStyle::default_bar()
-                .template("{spinner:.green} [{elapsed_precise}] [{wide_bar:.green/blue}] {bytes}/{total_bytes} ({eta})")
+                .template("{spinner:.green} [{elapsed_precise}] [{wide_bar:.green/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta}) {msg}")
                 .progress_chars("#>-"));
             style_is_progress_bar = true;
         }
 
-        if max_bytes != _max_bytes {
-            pb.set_length(max_bytes as u64);
-            _max_bytes = max_bytes;
+        if progress.max_bytes != _max_bytes {
+            pb.set_length(progress.max_bytes as u64);
+            _max_bytes = progress.max_bytes;
         }
 
-        last_transferred_bytes = transferred_bytes;
-        pb.set_position(transferred_bytes as u64);
+        pb.set_position(progress.transferred_bytes as u64);
+        pb.set_message(progress.message(start_time.elapsed()));
 
         sleep(Duration::from_micros(50));
     }
 }
 
+fn spinner_style() -> ProgressStyle {
+    ProgressStyle::default_spinner()
+        .template("{spinner:.green} [{elapsed_precise}] {bytes} ({bytes_per_sec}) {msg}")
+}
+
 fn main() {
     let start_exec_time = utils::epoch_millis();
 
-    env_logger::init();
-
     let env_args = env::args().collect::<Vec<String>>();
     let args = CLI::parse();
 
-    let file = File::open(args.config).expect("missing config file");
-    let config: Config = serde_yaml::from_reader(file).expect("bad config file format");
+    let log_format = LogFormat::parse(args.log_format.as_str()).unwrap_or(LogFormat::Text);
+    logging::init(log_format, args.log_level.as_deref());
+    credentials::ask_password(args.ask_password);
+
+    // the variables of the `.env` files are used by the configuration file and the database clients
+    for env_file in env_files(&args.config) {
+        if let Err(err) = load_env_file(&env_file) {
+            eprintln!("{}", err);
+            process::exit(err.exit_code());
+        }
+    }
+
+    // an invalid configuration file is one of the reported checks
+    if let SubCommand::Check(check_args) = &args.sub_commands {
+        let config = read_config(&args.config, args.profile.as_deref())
+            .and_then(|config| config.substitute_env_vars());
+        // the failed checks are printed already
+        if let Err(err) = commands::check::run(check_args, &args.config, config) {
+            process::exit(err.exit_code());
+        }
+
+        return;
+    }
+
+    let config = read_config(&args.config, args.profile.as_deref()).and_then(|config| match &args
+        .sub_commands
+    {
+        // the variables are substituted by the Job, with the environment of its pod
+        SubCommand::Dump(DumpCommand::Restore(RestoreCommand::K8s(_))) => Ok(config),
+        _ => config.substitute_env_vars(),
+    });
+
+    let mut config = match config {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(err.exit_code());
+        }
+    };
+
+    // the bandwidth of the command line overrides the one of the configuration file
+    if let Some(max_bandwidth) = &args.max_bandwidth {
+        config.bandwidth = Some(BandwidthConfig {
+            max: max_bandwidth.clone(),
+            source: config.bandwidth.and_then(|bandwidth| bandwidth.source),
+        });
+    }
 
     let sub_commands: &SubCommand = &args.sub_commands;
 
@@ -99,7 +161,15 @@ fn main() {
         let _ = telemetry_client.capture_command(&telemetry_config, sub_commands, &env_args, None);
     }
 
-    if let Err(err) = run(config, &sub_commands) {
+    let metrics_config = config.metrics.clone();
+    let metrics = Arc::new(Metrics::new(sub_commands.name()));
+
+    let result = match &metrics_config {
+        Some(metrics_config) => metrics::serve(metrics.clone(), metrics_config),
+        None => Ok(()),
+    }
+    .and_then(|_| run(config, &sub_commands, args.quiet, &metrics));
+    if let Err(err) = &result {
         eprintln!("{}", err);
     }
 
@@ -111,68 +181,214 @@ fn main() {
             Some(epoch_millis() - start_exec_time),
         );
     }
+
+    if let Some(metrics_config) = &metrics_config {
+        metrics.finish(result.is_ok());
+
+        if let Err(err) = metrics::push(&metrics, metrics_config) {
+            eprintln!("{}", err);
+        }
+    }
+
+    // the scripts and the CI jobs running replibyte see the failures, and their category
+    if let Err(err) = &result {
+        process::exit(err.exit_code());
+    }
 }
 
-fn run(config: Config, sub_commands: &SubCommand) -> anyhow::Result<()> {
-    let mut datastore: Box<dyn Datastore> = match &config.datastore {
-        DatastoreConfig::AWS(config) => Box::new(S3::aws(
-            config.bucket()?,
-            config.region()?,
-            config.profile()?,
-            config.credentials()?,
-            config.endpoint()?,
-        )?),
+/// `.env` files of the current directory and of the directory of the configuration file
+fn env_files(config_path: &Path) -> Vec<PathBuf> {
+    let mut env_files = vec![PathBuf::from(".env")];
+
+    if let Some(dir) = config_path.parent() {
+        if !dir.as_os_str().is_empty() {
+            env_files.push(dir.join(".env"));
+        }
+    }
+
+    env_files
+}
+
+fn read_config(path: &Path, profile: Option<&str>) -> Result<Config, ReplibyteError> {
+    let content = fs::read_to_string(path).map_err(|err| {
+        ReplibyteError::Config(format!("missing config file '{}': {}", path.display(), err))
+    })?;
+
+    let config = parse_config(content.as_str())?;
+
+    match profile {
+        Some(profile) => config.with_profile(profile),
+        None => Ok(config),
+    }
+}
+
+fn datastore(config: &DatastoreConfig) -> Result<Box<dyn Datastore>, ReplibyteError> {
+    let datastore: Box<dyn Datastore> = match config {
+        DatastoreConfig::AWS(config) => Box::new(
+            S3::aws(
+                config.bucket()?,
+                config.region()?,
+                config.profile()?,
+                config.credentials()?,
+                config.endpoint()?,
+                config.proxy()?,
+            )?
+            .with_multipart(config.multipart()?),
+        ),
         DatastoreConfig::GCP(config) => Box::new(S3::gcp(
             config.bucket()?,
             config.region()?,
             config.access_key()?,
             config.secret()?,
             config.endpoint()?,
+            config.proxy()?,
+        )?),
+        DatastoreConfig::GCS(config) => Box::new(GoogleCloudStorage::new(
+            config.bucket()?,
+            config.service_account_key()?,
+            config.endpoint()?,
+            config.proxy()?,
+        )?),
+        DatastoreConfig::Azure(config) => Box::new(AzureBlobStorage::new(
+            config.container()?,
+            config.connection_string()?,
+            config.account()?,
+            config.managed_identity_client_id()?,
+            config.proxy()?,
+        )?),
+        DatastoreConfig::SFTP(config) => Box::new(Sftp::new(
+            config.dir()?,
+            &config.ssh,
+            ssh_proxy(config.proxy()?, &config.ssh)?.as_ref(),
+        )?),
+        DatastoreConfig::Http(config) => Box::new(Http::new(
+            config.url()?,
+            config.index_file_path.clone(),
+            config.dump_part_path.clone(),
+            config.token()?,
+            config.proxy()?,
         )?),
         DatastoreConfig::LocalDisk(config) => Box::new(LocalDisk::new(config.dir()?)),
     };
 
-    let migrator = Migrator::new(get_replibyte_version(), &datastore, migrations());
-    let _ = migrator.migrate()?;
+    Ok(datastore)
+}
+
+fn run(
+    config: Config,
+    sub_commands: &SubCommand,
+    quiet: bool,
+    metrics: &Arc<Metrics>,
+) -> Result<(), ReplibyteError> {
+    // the scan only reads the source, it does not need a datastore
+    if let SubCommand::Scan(args) = sub_commands {
+        return commands::scan::run(args, config);
+    }
+
+    // the generated rows written to a file do not need a datastore
+    if let SubCommand::Seed(SeedCommand::Generate(args)) = sub_commands {
+        if args.output.is_some() {
+            return commands::seed::write(args, &config);
+        }
+    }
+
+    // the restore runs in the cluster, the datastores may not be reachable from here
+    if let SubCommand::Dump(DumpCommand::Restore(RestoreCommand::K8s(args))) = sub_commands {
+        return commands::dump::restore_k8s(args, config);
+    }
+
+    // the daemon runs the scheduled dumps with `run`
+    if let SubCommand::Daemon(args) = sub_commands {
+        return daemon::run(args, config, metrics);
+    }
+
+    // the bandwidth is shared by all the datastores
+    let throttle = match &config.bandwidth {
+        Some(bandwidth) => Some(Arc::new(Throttle::new(bandwidth.max()?))),
+        None => None,
+    };
+
+    let mut datastores = vec![];
+    for datastore_config in config.datastores()? {
+        let datastore = datastore(datastore_config)?;
+        let datastore: Box<dyn Datastore> = match &throttle {
+            Some(throttle) => Box::new(Throttled::new(datastore, Arc::clone(throttle))),
+            None => datastore,
+        };
+
+        let migrator = Migrator::new(get_replibyte_version(), &datastore, migrations());
+        let _ = migrator.migrate()?;
+
+        datastores.push(datastore);
+    }
+
+    let datastore: Box<dyn Datastore> = if datastores.len() == 1 {
+        datastores.remove(0)
+    } else {
+        Box::new(Replicated::new(datastores))
+    };
+
+    // the deduplicated dumps are read even if the dedup is disabled
+    let mut datastore: Box<dyn Datastore> = Box::new(Dedup::new(datastore, config.dedup()));
 
     let _ = datastore.init()?;
 
-    let (tx_pb, rx_pb) = mpsc::sync_channel::<(TransferredBytes, MaxBytes)>(1000);
+    let (tx_pb, rx_pb) = mpsc::sync_channel::<Progress>(1000);
 
-    match sub_commands {
-        // skip progress when output = true
-        SubCommand::Dump(dump_cmd) => match dump_cmd {
-            DumpCommand::Restore(cmd) => match cmd {
-                RestoreCommand::Local(args) => if args.output {},
-                RestoreCommand::Remote(args) => if args.output {},
-            },
-            _ => {
-                let _ = thread::spawn(move || show_progress_bar(rx_pb));
-            }
-        },
-        _ => {
-            let _ = thread::spawn(move || show_progress_bar(rx_pb));
+    let show_progress = match sub_commands {
+        // skip progress when the dump is restored to stdout or to a file
+        SubCommand::Dump(DumpCommand::Restore(RestoreCommand::Local(args))) => {
+            args.output.is_none()
+        }
+        SubCommand::Dump(DumpCommand::Restore(RestoreCommand::Remote(args))) => {
+            args.output.is_none()
         }
+        _ => true,
     };
 
-    let progress_callback = |bytes: TransferredBytes, max_bytes: MaxBytes| {
-        let _ = tx_pb.send((bytes, max_bytes));
+    if show_progress && !quiet {
+        let _ = thread::spawn(move || show_progress_bar(rx_pb));
+    } else {
+        // the progress is not sent if there is no receiver
+        drop(rx_pb);
+    }
+
+    let progress_callback = |progress: &Progress| {
+        metrics.set_progress(progress);
+        let _ = tx_pb.send(progress.clone());
     };
 
     match sub_commands {
         SubCommand::Dump(cmd) => match cmd {
-            DumpCommand::List => {
-                let _ = commands::dump::list(&mut datastore)?;
+            DumpCommand::List(args) => {
+                let _ = commands::dump::list(args, &mut datastore)?;
                 Ok(())
             }
             DumpCommand::Create(args) => {
-                if let Some(name) = &args.name {
-                    datastore.set_dump_name(name.to_string());
-                }
-
                 commands::dump::run(args, datastore, config, progress_callback)
             }
             DumpCommand::Delete(args) => commands::dump::delete(datastore, args),
+            DumpCommand::Rewrap(args) => commands::dump::rewrap(args, datastore, config),
+            DumpCommand::Inspect(args) => {
+                let _ = commands::dump::inspect(args, datastore.as_ref())?;
+                Ok(())
+            }
+            DumpCommand::Diff(args) => {
+                let _ = commands::dump::diff(args, datastore.as_ref())?;
+                Ok(())
+            }
+            DumpCommand::Download(args) => {
+                let _ = commands::dump::download(args, datastore.as_ref())?;
+                Ok(())
+            }
+            DumpCommand::Verify(args) => {
+                let _ = commands::dump::verify(args, datastore.as_ref())?;
+                Ok(())
+            }
+            DumpCommand::Prune(args) => commands::dump::prune(args, datastore, config),
+            DumpCommand::Import(args) => {
+                commands::dump::import(args, datastore, config, progress_callback)
+            }
             DumpCommand::Restore(restore_cmd) => match restore_cmd {
                 RestoreCommand::Local(args) => {
                     commands::dump::restore_local(args, datastore, config, progress_callback)
@@ -180,6 +396,11 @@ fn run(config: Config, sub_commands: &SubCommand) -> anyhow::Result<()> {
                 RestoreCommand::Remote(args) => {
                     commands::dump::restore_remote(args, datastore, config, progress_callback)
                 }
+                RestoreCommand::K8s(_) => {
+                    unreachable!(
+                        "the Kubernetes restore is run before the datastores are initialized"
+                    )
+                }
             },
         },
         SubCommand::Transformer(cmd) => match cmd {
@@ -188,5 +409,22 @@ fn run(config: Config, sub_commands: &SubCommand) -> anyhow::Result<()> {
                 Ok(())
             }
         },
+        SubCommand::Scan(_) => {
+            unreachable!("the scan is run before the datastores are initialized")
+        }
+        SubCommand::Daemon(_) => {
+            unreachable!("the daemon is run before the datastores are initialized")
+        }
+        SubCommand::Check(_) => {
+            unreachable!("the checks are run before the configuration file is read")
+        }
+        SubCommand::Dev(cmd) => match cmd {
+            DevCommand::Up(args) => commands::dev::up(args, datastore, config, progress_callback),
+        },
+        SubCommand::Seed(cmd) => match cmd {
+            SeedCommand::Generate(args) => {
+                commands::seed::generate(args, datastore, config, progress_callback)
+            }
+        },
     }
 }
@@ -0,0 +1,268 @@
+use std::io::{BufRead, BufReader, Error, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::error;
+use reqwest::blocking::Client;
+use reqwest::header::CONTENT_TYPE;
+
+use crate::config::MetricsConfig;
+use crate::errors::ReplibyteError;
+use crate::tasks::Progress;
+use crate::utils::epoch_millis;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_JOB: &str = "replibyte";
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Metrics of the command run by Replibyte, in the Prometheus text format
+pub struct Metrics {
+    command: String,
+    started_at: Mutex<Instant>,
+    bytes: AtomicUsize,
+    rows: AtomicUsize,
+    /// duration of the command and whether it succeeded, once it is finished
+    result: Mutex<Option<(Duration, bool)>>,
+    /// epoch time in seconds of the last success, kept across the runs of the daemon
+    last_success_at: Mutex<Option<u128>>,
+}
+
+impl Metrics {
+    pub fn new<S: Into<String>>(command: S) -> Self {
+        Metrics {
+            command: command.into(),
+            started_at: Mutex::new(Instant::now()),
+            bytes: AtomicUsize::new(0),
+            rows: AtomicUsize::new(0),
+            result: Mutex::new(None),
+            last_success_at: Mutex::new(None),
+        }
+    }
+
+    /// reset the metrics for a new run of the command, e.g. a dump scheduled by the daemon
+    pub fn restart(&self) {
+        *self.started_at.lock().unwrap() = Instant::now();
+        self.bytes.store(0, Ordering::Relaxed);
+        self.rows.store(0, Ordering::Relaxed);
+        *self.result.lock().unwrap() = None;
+    }
+
+    pub fn set_progress(&self, progress: &Progress) {
+        self.bytes
+            .store(progress.transferred_bytes, Ordering::Relaxed);
+        self.rows.store(progress.rows, Ordering::Relaxed);
+    }
+
+    pub fn finish(&self, is_success: bool) {
+        let duration = self.started_at.lock().unwrap().elapsed();
+        *self.result.lock().unwrap() = Some((duration, is_success));
+
+        if is_success {
+            *self.last_success_at.lock().unwrap() = Some(epoch_millis() / 1000);
+        }
+    }
+
+    pub fn to_prometheus(&self) -> String {
+        let (duration, is_success) = match *self.result.lock().unwrap() {
+            Some((duration, is_success)) => (duration, Some(is_success)),
+            None => (self.started_at.lock().unwrap().elapsed(), None),
+        };
+
+        let mut metrics = vec![
+            (
+                "replibyte_duration_seconds",
+                "Duration of the command.",
+                format!("{:.3}", duration.as_secs_f64()),
+            ),
+            (
+                "replibyte_bytes",
+                "Bytes dumped or restored by the command.",
+                self.bytes.load(Ordering::Relaxed).to_string(),
+            ),
+            (
+                "replibyte_rows",
+                "Rows transformed or restored by the command.",
+                self.rows.load(Ordering::Relaxed).to_string(),
+            ),
+        ];
+
+        if let Some(is_success) = is_success {
+            metrics.push((
+                "replibyte_failed",
+                "1 if the command failed, 0 otherwise.",
+                (if is_success { "0" } else { "1" }).to_string(),
+            ));
+        }
+
+        // not set before a success, so the Pushgateway keeps the time of the previous success
+        if let Some(last_success_at) = *self.last_success_at.lock().unwrap() {
+            metrics.push((
+                "replibyte_last_success_timestamp_seconds",
+                "Time of the last successful command.",
+                last_success_at.to_string(),
+            ));
+        }
+
+        metrics
+            .into_iter()
+            .map(|(name, help, value)| {
+                format!(
+                    "# HELP {name} {help}\n# TYPE {name} gauge\n{name}{{command=\"{}\"}} {value}\n",
+                    self.command
+                )
+            })
+            .collect()
+    }
+}
+
+/// serve the metrics on `http://<listen>/metrics` while the command runs
+pub fn serve(metrics: Arc<Metrics>, config: &MetricsConfig) -> Result<(), ReplibyteError> {
+    let listen = match config.listen()? {
+        Some(listen) => listen,
+        None => return Ok(()),
+    };
+
+    let listener = TcpListener::bind(listen.as_str()).map_err(|err| {
+        ReplibyteError::Config(format!(
+            "unable to serve the metrics on '{}': {}",
+            listen, err
+        ))
+    })?;
+
+    let _ = thread::spawn(move || {
+        for stream in listener.incoming() {
+            let result = stream.and_then(|stream| respond(stream, metrics.as_ref()));
+
+            if let Err(err) = result {
+                error!("metrics endpoint error: {}", err);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn respond(mut stream: TcpStream, metrics: &Metrics) -> Result<(), Error> {
+    let mut request_line = String::new();
+    let _ = BufReader::new(&stream).read_line(&mut request_line)?;
+
+    let response = match request_line.split_whitespace().nth(1) {
+        Some("/metrics") => {
+            let body = metrics.to_prometheus();
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                PROMETHEUS_CONTENT_TYPE,
+                body.len(),
+                body
+            )
+        }
+        _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+    };
+
+    stream.write_all(response.as_bytes())
+}
+
+/// push the metrics to the Pushgateway, if one is configured
+pub fn push(metrics: &Metrics, config: &MetricsConfig) -> Result<(), ReplibyteError> {
+    let pushgateway = match &config.pushgateway {
+        Some(pushgateway) => pushgateway,
+        None => return Ok(()),
+    };
+
+    let url = format!(
+        "{}/metrics/job/{}",
+        pushgateway.url()?.trim_end_matches('/'),
+        pushgateway.job.as_deref().unwrap_or(DEFAULT_JOB)
+    );
+
+    let client = Client::builder()
+        .timeout(TIMEOUT)
+        .build()
+        .map_err(|err| ReplibyteError::Connection(err.to_string()))?;
+
+    // POST only replaces the pushed metrics, e.g. the time of the last success is kept on failure
+    let response = client
+        .post(url.as_str())
+        .header(CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)
+        .body(metrics.to_prometheus())
+        .send()
+        .map_err(|err| {
+            ReplibyteError::Connection(format!("unable to push the metrics to '{}': {}", url, err))
+        })?;
+
+    if !response.status().is_success() {
+        return Err(ReplibyteError::Connection(format!(
+            "unable to push the metrics to '{}': {}",
+            url,
+            response.status()
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::sync::Arc;
+
+    use crate::config::MetricsConfig;
+    use crate::metrics::{serve, Metrics};
+    use crate::tasks::Progress;
+
+    #[test]
+    fn prometheus_metrics() {
+        let metrics = Metrics::new("dump-create");
+
+        let mut progress = Progress::new(100);
+        progress.transferred_bytes = 1024;
+        progress.rows = 42;
+        metrics.set_progress(&progress);
+
+        let running_metrics = metrics.to_prometheus();
+        assert!(running_metrics.contains(
+            "# HELP replibyte_bytes Bytes dumped or restored by the command.\n# TYPE replibyte_bytes gauge\nreplibyte_bytes{command=\"dump-create\"} 1024\n"
+        ));
+        assert!(running_metrics.contains("replibyte_rows{command=\"dump-create\"} 42\n"));
+        assert!(!running_metrics.contains("replibyte_failed"));
+
+        metrics.finish(false);
+        let failed_metrics = metrics.to_prometheus();
+        assert!(failed_metrics.contains("replibyte_failed{command=\"dump-create\"} 1\n"));
+        assert!(!failed_metrics.contains("replibyte_last_success_timestamp_seconds"));
+
+        metrics.finish(true);
+        let finished_metrics = metrics.to_prometheus();
+        assert!(finished_metrics.contains("replibyte_failed{command=\"dump-create\"} 0\n"));
+        assert!(finished_metrics
+            .contains("replibyte_last_success_timestamp_seconds{command=\"dump-create\"}"));
+
+        // the time of the last success is kept for the next runs
+        metrics.restart();
+        let restarted_metrics = metrics.to_prometheus();
+        assert!(restarted_metrics.contains("replibyte_rows{command=\"dump-create\"} 0\n"));
+        assert!(!restarted_metrics.contains("replibyte_failed"));
+        assert!(restarted_metrics
+            .contains("replibyte_last_success_timestamp_seconds{command=\"dump-create\"}"));
+    }
+
+    #[test]
+    fn serve_metrics() {
+        let config = serde_yaml::from_str::<MetricsConfig>("listen: 127.0.0.1:19898").unwrap();
+        serve(Arc::new(Metrics::new("dump-list")), &config).unwrap();
+
+        let mut stream = TcpStream::connect("127.0.0.1:19898").unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+
+        let mut response = String::new();
+        let _ = stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("replibyte_rows{command=\"dump-list\"} 0\n"));
+    }
+}
@@ -1,9 +1,9 @@
-use std::io::{Error, ErrorKind};
 use std::str::FromStr;
 
 use log::info;
 
 use crate::datastore::Datastore;
+use crate::errors::ReplibyteError;
 use crate::migration::rename_backups_to_dumps::RenameBackupsToDump;
 use crate::migration::update_version_number::UpdateVersionNumber;
 use crate::utils::get_replibyte_version;
@@ -19,7 +19,7 @@ pub struct Version {
 }
 
 impl FromStr for Version {
-    type Err = Error;
+    type Err = ReplibyteError;
 
     fn from_str(v: &str) -> Result<Self, Self::Err> {
         let numbers = v.split_terminator('.').collect::<Vec<&str>>();
@@ -37,10 +37,10 @@ impl FromStr for Version {
                     patch,
                 })
             }
-            _ => Err(Error::new(
-                ErrorKind::Other,
-                format!("migration: version number '{}' is invalid, must have 'major.minor.patch' format", v),
-            )),
+            _ => Err(ReplibyteError::Datastore(format!(
+                "migration: version number '{}' is invalid, must have 'major.minor.patch' format",
+                v
+            ))),
         }
     }
 }
@@ -49,7 +49,7 @@ pub trait Migration {
     /// minimal version for which the migration needs to be triggered.
     fn minimal_version(&self) -> Version;
     /// run the migration.
-    fn run(&self, datastore: &Box<dyn Datastore>) -> Result<(), Error>;
+    fn run(&self, datastore: &Box<dyn Datastore>) -> Result<(), ReplibyteError>;
 }
 
 // All registered migrations
@@ -80,7 +80,7 @@ impl<'a> Migrator<'a> {
     }
 
     /// run all registered migrations when the minimal version is matched.
-    pub fn migrate(&self) -> Result<(), Error> {
+    pub fn migrate(&self) -> Result<(), ReplibyteError> {
         match self.datastore.raw_index_file() {
             Ok(_) => {
                 for migration in &self.migrations {
@@ -89,12 +89,12 @@ impl<'a> Migrator<'a> {
                     }
                 }
                 Ok(())
-            },
+            }
             Err(err) => {
                 // raw_index_file returns an error when we don't have a metadata.json file, in this case we don't need to run migrations.
                 info!("migrate: skip migrate '{}'", err.to_string());
                 Ok(())
-            },
+            }
         }
     }
 
@@ -105,22 +105,20 @@ impl<'a> Migrator<'a> {
     }
 }
 
-fn parse_str_to_u8(s: &str) -> Result<u8, Error> {
+fn parse_str_to_u8(s: &str) -> Result<u8, ReplibyteError> {
     s.parse::<u8>()
-        .map_err(|err| Error::new(ErrorKind::Other, err.to_string()))
+        .map_err(|err| ReplibyteError::Datastore(err.to_string()))
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{
-        io::{Error, ErrorKind},
-        str::FromStr,
-    };
+    use std::str::FromStr;
 
     use serde_json::json;
 
     use crate::connector::Connector;
-    use crate::datastore::{Datastore, IndexFile, ReadOptions};
+    use crate::datastore::{Compression, Datastore, Dump, EncodedPart, EncryptionKeys, IndexFile};
+    use crate::errors::ReplibyteError;
 
     use super::{Migration, Migrator, Version};
 
@@ -130,9 +128,9 @@ mod tests {
             Version::from_str("0.7.2").unwrap()
         }
 
-        fn run(&self, _datastore: &Box<dyn Datastore>) -> Result<(), std::io::Error> {
+        fn run(&self, _datastore: &Box<dyn Datastore>) -> Result<(), ReplibyteError> {
             // trigger an error so we can assert against it
-            Err(Error::new(ErrorKind::Other, "should not run"))
+            Err(ReplibyteError::Datastore("should not run".to_string()))
         }
     }
 
@@ -142,56 +140,59 @@ mod tests {
     }
 
     impl Connector for InMemoryDatastore {
-        fn init(&mut self) -> Result<(), Error> {
+        fn init(&mut self) -> Result<(), ReplibyteError> {
             Ok(())
         }
     }
 
     impl Datastore for InMemoryDatastore {
-        fn index_file(&self) -> Result<IndexFile, Error> {
+        fn index_file(&self) -> Result<IndexFile, ReplibyteError> {
             Ok(IndexFile {
                 v: None,
                 dumps: vec![],
             })
         }
 
-        fn raw_index_file(&self) -> Result<serde_json::Value, Error> {
+        fn raw_index_file(&self) -> Result<serde_json::Value, ReplibyteError> {
             Ok(json!(self.index_file))
         }
 
-        fn write_index_file(&self, _index_file: &IndexFile) -> Result<(), Error> {
+        fn write_index_file(&self, _index_file: &IndexFile) -> Result<(), ReplibyteError> {
             unimplemented!()
         }
 
-        fn write_raw_index_file(&self, _raw_index_file: &serde_json::Value) -> Result<(), Error> {
+        fn write_raw_index_file(
+            &self,
+            _raw_index_file: &serde_json::Value,
+        ) -> Result<(), ReplibyteError> {
             unimplemented!()
         }
 
-        fn write(&self, _file_part: u16, _data: crate::types::Bytes) -> Result<(), Error> {
+        fn write_encoded(&self, _file_part: u16, _part: EncodedPart) -> Result<(), ReplibyteError> {
             unimplemented!()
         }
 
-        fn read(
+        fn read_encoded(
             &self,
-            _options: &ReadOptions,
-            _data_callback: &mut dyn FnMut(crate::types::Bytes),
-        ) -> Result<(), Error> {
+            _dump: &Dump,
+            _data_callback: &mut dyn FnMut(crate::types::Bytes) -> Result<(), ReplibyteError>,
+        ) -> Result<(), ReplibyteError> {
             unimplemented!()
         }
 
-        fn compression_enabled(&self) -> bool {
-            true
+        fn compression(&self) -> Option<Compression> {
+            Some(Compression::default())
         }
 
-        fn set_compression(&mut self, _enable: bool) {
+        fn set_compression(&mut self, _compression: Option<Compression>) {
             unimplemented!()
         }
 
-        fn encryption_key(&self) -> &Option<String> {
+        fn encryption_keys(&self) -> &EncryptionKeys {
             unimplemented!()
         }
 
-        fn set_encryption_key(&mut self, _key: String) {
+        fn set_encryption_keys(&mut self, _keys: EncryptionKeys) {
             unimplemented!()
         }
 
@@ -199,7 +200,23 @@ mod tests {
             unimplemented!()
         }
 
-        fn delete_by_name(&self, _name: String) -> Result<(), Error> {
+        fn delete_dump_files(&self, _name: &str) -> Result<(), ReplibyteError> {
+            unimplemented!()
+        }
+
+        fn write_chunk(
+            &self,
+            _hash: &str,
+            _data: crate::types::Bytes,
+        ) -> Result<(), ReplibyteError> {
+            unimplemented!()
+        }
+
+        fn read_chunk(&self, _hash: &str) -> Result<crate::types::Bytes, ReplibyteError> {
+            unimplemented!()
+        }
+
+        fn delete_chunk(&self, _hash: &str) -> Result<(), ReplibyteError> {
             unimplemented!()
         }
     }
@@ -1,12 +1,10 @@
-use std::{
-    io::{Error, ErrorKind},
-    str::FromStr,
-};
+use std::str::FromStr;
 
 use log::info;
 use serde_json::{json, Value};
 
 use crate::datastore::Datastore;
+use crate::errors::ReplibyteError;
 
 use super::{Migration, Version};
 
@@ -23,7 +21,7 @@ impl Migration for RenameBackupsToDump {
         Version::from_str("0.7.3").unwrap()
     }
 
-    fn run(&self, datastore: &Box<dyn Datastore>) -> Result<(), Error> {
+    fn run(&self, datastore: &Box<dyn Datastore>) -> Result<(), ReplibyteError> {
         info!("migrate: rename backups to dumps");
 
         let mut raw_index_file = datastore.raw_index_file()?;
@@ -32,7 +30,7 @@ impl Migration for RenameBackupsToDump {
     }
 }
 
-fn rename_backups_to_dumps(metadata_json: &mut Value) -> Result<(), Error> {
+fn rename_backups_to_dumps(metadata_json: &mut Value) -> Result<(), ReplibyteError> {
     match metadata_json.as_object_mut() {
         Some(metadata) => {
             // we rename the `backups` key to `dumps`
@@ -43,9 +41,8 @@ fn rename_backups_to_dumps(metadata_json: &mut Value) -> Result<(), Error> {
             }
             Ok(())
         }
-        None => Err(Error::new(
-            ErrorKind::Other,
-            "migrate: metadata.json is not an object",
+        None => Err(ReplibyteError::Datastore(
+            "migrate: metadata.json is not an object".to_string(),
         )),
     }
 }
@@ -1,12 +1,10 @@
-use std::{
-    io::{Error, ErrorKind},
-    str::FromStr,
-};
+use std::str::FromStr;
 
 use log::info;
 use serde_json::{json, Value};
 
 use crate::datastore::Datastore;
+use crate::errors::ReplibyteError;
 
 use super::{Migration, Version};
 
@@ -25,7 +23,7 @@ impl<'a> Migration for UpdateVersionNumber<'a> {
         Version::from_str("0.7.3").unwrap()
     }
 
-    fn run(&self, datastore: &Box<dyn Datastore>) -> Result<(), Error> {
+    fn run(&self, datastore: &Box<dyn Datastore>) -> Result<(), ReplibyteError> {
         info!("migrate: update version number");
 
         let mut raw_index_file = datastore.raw_index_file()?;
@@ -34,15 +32,14 @@ impl<'a> Migration for UpdateVersionNumber<'a> {
     }
 }
 
-fn update_version(metadata_json: &mut Value, version: &str) -> Result<(), Error> {
+fn update_version(metadata_json: &mut Value, version: &str) -> Result<(), ReplibyteError> {
     match metadata_json.as_object_mut() {
         Some(metadata) => {
             metadata.insert("v".to_string(), json!(version));
             Ok(())
         }
-        None => Err(Error::new(
-            ErrorKind::Other,
-            "migrate: metadata.json is not an object",
+        None => Err(ReplibyteError::Datastore(
+            "migrate: metadata.json is not an object".to_string(),
         )),
     }
 }
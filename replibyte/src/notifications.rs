@@ -0,0 +1,202 @@
+use std::time::Duration;
+
+use log::error;
+use reqwest::blocking::Client;
+use reqwest::header::CONTENT_TYPE;
+use serde_json::{json, Value};
+
+use crate::config::{NotificationConfig, NotificationFormat, NotificationStatus};
+use crate::errors::ReplibyteError;
+use crate::utils::to_human_readable_unit;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Outcome of a dump creation or restore, sent to the webhooks of the `notifications` config
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    command: String,
+    dump: String,
+    /// size of the dump, if it is known
+    size: Option<usize>,
+    duration: Duration,
+    error: Option<String>,
+}
+
+impl Notification {
+    pub fn new<C: Into<String>, D: Into<String>>(
+        command: C,
+        dump: D,
+        size: Option<usize>,
+        duration: Duration,
+        error: Option<String>,
+    ) -> Self {
+        Notification {
+            command: command.into(),
+            dump: dump.into(),
+            size,
+            duration,
+            error,
+        }
+    }
+
+    pub fn status(&self) -> NotificationStatus {
+        match self.error {
+            Some(_) => NotificationStatus::Failure,
+            None => NotificationStatus::Success,
+        }
+    }
+
+    pub fn payload(&self, format: NotificationFormat) -> Value {
+        match format {
+            NotificationFormat::Json => json!({
+                "command": self.command,
+                "status": self.status(),
+                "dump": self.dump,
+                "size": self.size,
+                "duration_ms": self.duration.as_millis() as u64,
+                "error": self.error,
+            }),
+            NotificationFormat::Slack => json!({ "text": self.text() }),
+        }
+    }
+
+    /// e.g. `replibyte dump-create succeeded for dump-1652280000000 (1 MB) in 12.3s`
+    fn text(&self) -> String {
+        let size = match self.size {
+            Some(size) => format!(" ({})", to_human_readable_unit(size)),
+            None => String::new(),
+        };
+
+        match &self.error {
+            None => format!(
+                "replibyte {} succeeded for {}{} in {:.1}s",
+                self.command,
+                self.dump,
+                size,
+                self.duration.as_secs_f64()
+            ),
+            Some(err) => format!(
+                "replibyte {} failed for {}{} after {:.1}s: {}",
+                self.command,
+                self.dump,
+                size,
+                self.duration.as_secs_f64(),
+                err
+            ),
+        }
+    }
+}
+
+/// call the webhooks enabled for the outcome of the notification, a failing webhook does not fail the command
+pub fn notify(configs: &[NotificationConfig], notification: &Notification) {
+    for config in configs {
+        if !config.is_enabled(notification.status()) {
+            continue;
+        }
+
+        if let Err(err) = send(config, notification) {
+            error!("{}", err);
+        }
+    }
+}
+
+fn send(config: &NotificationConfig, notification: &Notification) -> Result<(), ReplibyteError> {
+    let url = config.url()?;
+
+    let client = Client::builder()
+        .timeout(TIMEOUT)
+        .build()
+        .map_err(|err| ReplibyteError::Connection(err.to_string()))?;
+
+    let response = client
+        .post(url.as_str())
+        .header(CONTENT_TYPE, "application/json")
+        .body(notification.payload(config.format()).to_string())
+        .send()
+        .map_err(|err| {
+            ReplibyteError::Connection(format!(
+                "unable to send the notification to '{}': {}",
+                url, err
+            ))
+        })?;
+
+    if !response.status().is_success() {
+        return Err(ReplibyteError::Connection(format!(
+            "unable to send the notification to '{}': {}",
+            url,
+            response.status()
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use crate::config::{NotificationConfig, NotificationFormat, NotificationStatus};
+    use crate::notifications::Notification;
+
+    #[test]
+    fn notification_payloads() {
+        let notification = Notification::new(
+            "dump-create",
+            "dump-1652280000000",
+            Some(2048),
+            Duration::from_millis(12300),
+            None,
+        );
+
+        assert_eq!(notification.status(), NotificationStatus::Success);
+        assert_eq!(
+            notification.payload(NotificationFormat::Json),
+            json!({
+                "command": "dump-create",
+                "status": "success",
+                "dump": "dump-1652280000000",
+                "size": 2048,
+                "duration_ms": 12300,
+                "error": null,
+            })
+        );
+        assert_eq!(
+            notification.payload(NotificationFormat::Slack),
+            json!({"text": "replibyte dump-create succeeded for dump-1652280000000 (2 kB) in 12.3s"})
+        );
+
+        let notification = Notification::new(
+            "dump-restore-remote",
+            "latest",
+            None,
+            Duration::from_millis(1500),
+            Some("Can't find dump".to_string()),
+        );
+
+        assert_eq!(notification.status(), NotificationStatus::Failure);
+        assert_eq!(
+            notification.payload(NotificationFormat::Slack),
+            json!({"text": "replibyte dump-restore-remote failed for latest after 1.5s: Can't find dump"})
+        );
+    }
+
+    #[test]
+    fn notification_config() {
+        let config = serde_yaml::from_str::<NotificationConfig>(
+            "url: https://hooks.slack.com/services/T000/B000/XXX\nformat: slack\non: [failure]",
+        )
+        .unwrap();
+
+        assert_eq!(config.format(), NotificationFormat::Slack);
+        assert!(config.is_enabled(NotificationStatus::Failure));
+        assert!(!config.is_enabled(NotificationStatus::Success));
+
+        let config =
+            serde_yaml::from_str::<NotificationConfig>("url: https://example.com/hook").unwrap();
+
+        assert_eq!(config.format(), NotificationFormat::Json);
+        assert!(config.is_enabled(NotificationStatus::Success));
+    }
+}
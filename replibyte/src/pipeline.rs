@@ -0,0 +1,252 @@
+use std::cell::Cell;
+
+use crate::config::{DatabaseSubsetConfig, OnlyTablesConfig, RowFilterConfig, SkipConfig};
+use crate::datastore::{Compression, Datastore, EncryptionKeys, ReadOptions};
+use crate::destination::Destination;
+use crate::errors::ReplibyteError;
+use crate::source::{Source, SourceOptions};
+use crate::tasks::full_dump::FullDumpTask;
+use crate::tasks::full_restore::FullRestoreTask;
+use crate::tasks::{Progress, Task};
+use crate::transformer::Transformer;
+use crate::utils::epoch_millis;
+
+/// Dump a source in a datastore and restore the dumps in a destination, like `dump create` and `dump restore remote`
+pub struct Pipeline {
+    datastore: Box<dyn Datastore>,
+    transformers: Vec<Box<dyn Transformer>>,
+    skip: Vec<SkipConfig>,
+    database_subset: Option<DatabaseSubsetConfig>,
+    only_tables: Vec<OnlyTablesConfig>,
+    filters: Vec<RowFilterConfig>,
+    encoding: Option<String>,
+    skip_large_objects: bool,
+    skip_bytea: bool,
+    dump_name: Option<String>,
+}
+
+impl Pipeline {
+    /// initialize the datastore, the dumps are compressed with zlib and not encrypted by default
+    pub fn new(mut datastore: Box<dyn Datastore>) -> Result<Self, ReplibyteError> {
+        let _ = datastore.init()?;
+
+        Ok(Pipeline {
+            datastore,
+            transformers: vec![],
+            skip: vec![],
+            database_subset: None,
+            only_tables: vec![],
+            filters: vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
+            dump_name: None,
+        })
+    }
+
+    /// transformers of the columns, the other columns are dumped as they are
+    pub fn transformers(mut self, transformers: Vec<Box<dyn Transformer>>) -> Self {
+        self.transformers = transformers;
+        self
+    }
+
+    /// tables not dumped
+    pub fn skip(mut self, skip: Vec<SkipConfig>) -> Self {
+        self.skip = skip;
+        self
+    }
+
+    pub fn database_subset(mut self, database_subset: DatabaseSubsetConfig) -> Self {
+        self.database_subset = Some(database_subset);
+        self
+    }
+
+    /// only dump these tables
+    pub fn only_tables(mut self, only_tables: Vec<OnlyTablesConfig>) -> Self {
+        self.only_tables = only_tables;
+        self
+    }
+
+    /// only dump the rows of the tables matching these filters
+    pub fn filters(mut self, filters: Vec<RowFilterConfig>) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    /// encoding of the dump read from the source, e.g. `latin1`
+    pub fn encoding<S: Into<String>>(mut self, encoding: S) -> Self {
+        self.encoding = Some(encoding.into());
+        self
+    }
+
+    /// do not dump the PostgreSQL large objects
+    pub fn skip_large_objects(mut self, skip_large_objects: bool) -> Self {
+        self.skip_large_objects = skip_large_objects;
+        self
+    }
+
+    /// dump the PostgreSQL bytea values as NULL
+    pub fn skip_bytea(mut self, skip_bytea: bool) -> Self {
+        self.skip_bytea = skip_bytea;
+        self
+    }
+
+    /// compression of the dumps, `None` to store them uncompressed
+    pub fn compression(mut self, compression: Option<Compression>) -> Self {
+        self.datastore.set_compression(compression);
+        self
+    }
+
+    /// keys encrypting the new dumps and decrypting the dumps to restore
+    pub fn encryption_keys(mut self, encryption_keys: EncryptionKeys) -> Self {
+        self.datastore.set_encryption_keys(encryption_keys);
+        self
+    }
+
+    /// name of the next dump, `dump-<epoch millis>` otherwise
+    pub fn dump_name<S: Into<String>>(mut self, dump_name: S) -> Self {
+        self.dump_name = Some(dump_name.into());
+        self
+    }
+
+    /// dump the source in the datastore and return the name of the dump
+    pub fn dump<S, F>(
+        &mut self,
+        source: S,
+        mut progress_callback: F,
+    ) -> Result<String, ReplibyteError>
+    where
+        S: Source,
+        F: FnMut(&Progress),
+    {
+        let started_at = epoch_millis();
+        let dump_name = match self.dump_name.take() {
+            Some(dump_name) => dump_name,
+            None => format!("dump-{}", started_at),
+        };
+        self.datastore.set_dump_name(dump_name.clone());
+
+        let options = SourceOptions {
+            transformers: &self.transformers,
+            skip_config: &self.skip,
+            database_subset: &self.database_subset,
+            only_tables: &self.only_tables,
+            filters: &self.filters,
+            encoding: self.encoding.as_deref(),
+            skip_large_objects: self.skip_large_objects,
+            skip_bytea: self.skip_bytea,
+        };
+
+        let uncompressed_size = Cell::new(0);
+        let task = FullDumpTask::new(source, self.datastore.as_ref(), options);
+        let _ = task.run(|progress| {
+            uncompressed_size.set(progress.transferred_bytes);
+            progress_callback(progress)
+        })?;
+
+        let mut index_file = self.datastore.index_file()?;
+        if let Some(dump) = index_file
+            .dumps
+            .iter_mut()
+            .find(|dump| dump.directory_name == dump_name)
+        {
+            dump.started_at = Some(started_at);
+            dump.uncompressed_size = Some(uncompressed_size.get());
+        }
+        let _ = self.datastore.write_index_file(&index_file)?;
+
+        Ok(dump_name)
+    }
+
+    /// restore a dump of the datastore in the destination
+    pub fn restore<D, F>(
+        self,
+        destination: &mut D,
+        read_options: ReadOptions,
+        progress_callback: F,
+    ) -> Result<(), ReplibyteError>
+    where
+        D: Destination,
+        F: FnMut(&Progress),
+    {
+        let task = FullRestoreTask::new(destination, self.datastore, read_options, None);
+        task.run(progress_callback)
+    }
+
+    /// the datastore, e.g. to list or delete the dumps
+    pub fn datastore(&self) -> &dyn Datastore {
+        self.datastore.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use tempfile::tempdir;
+
+    use crate::connector::Connector;
+    use crate::datastore::local_disk::LocalDisk;
+    use crate::datastore::ReadOptions;
+    use crate::destination::generic_file::GenericFile;
+    use crate::errors::ReplibyteError;
+    use crate::source::{Source, SourceOptions};
+    use crate::types::{OriginalQuery, Query};
+    use crate::Pipeline;
+
+    const QUERIES: [&str; 2] = [
+        "INSERT INTO users (id, email) VALUES (1, 'alice@example.com');",
+        "INSERT INTO users (id, email) VALUES (2, 'bob@example.com');",
+    ];
+
+    struct UsersSource;
+
+    impl Connector for UsersSource {
+        fn init(&mut self) -> Result<(), ReplibyteError> {
+            Ok(())
+        }
+    }
+
+    impl Source for UsersSource {
+        fn read<F: FnMut(OriginalQuery, Query)>(
+            &self,
+            _options: SourceOptions,
+            mut query_callback: F,
+        ) -> Result<(), ReplibyteError> {
+            for query in QUERIES {
+                query_callback(
+                    Query(query.as_bytes().to_vec()),
+                    Query(query.as_bytes().to_vec()),
+                );
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn dump_and_restore() {
+        let dir = tempdir().unwrap();
+        let datastore = LocalDisk::new(dir.path().join("datastore").to_str().unwrap());
+
+        let mut pipeline = Pipeline::new(Box::new(datastore))
+            .unwrap()
+            .dump_name("dump-1");
+        assert_eq!(pipeline.dump(UsersSource, |_| {}).unwrap(), "dump-1");
+
+        let index_file = pipeline.datastore().index_file().unwrap();
+        assert_eq!(index_file.dumps.len(), 1);
+        assert!(index_file.dumps[0].uncompressed_size.unwrap() > 0);
+
+        let path = dir.path().join("dump.sql");
+        let mut destination = GenericFile::new(&path);
+        pipeline
+            .restore(&mut destination, ReadOptions::Latest, |_| {})
+            .unwrap();
+
+        assert_eq!(
+            read_to_string(path).unwrap(),
+            format!("{}\n", QUERIES.join("\n"))
+        );
+    }
+}
@@ -1,7 +1,12 @@
+use futures_util::stream::{self, StreamExt};
 use lazy_static::lazy_static;
 use std::future::Future;
-use std::sync::Mutex;
+use std::io::{Error, ErrorKind};
+use std::sync::{Arc, Mutex};
 use tokio::runtime::{Builder, Runtime};
+use tokio::sync::mpsc;
+
+use crate::errors::ReplibyteError;
 
 lazy_static! {
     static ref TOKIO_RUNTIME: Mutex<Runtime> = Mutex::new({
@@ -11,8 +16,59 @@ lazy_static! {
             .build()
             .unwrap()
     });
+    /// runs the dump and restore pipelines, the parts are encoded and decoded on its blocking threads
+    static ref PIPELINE_RUNTIME: Runtime = Builder::new_multi_thread()
+        .thread_name("tokio-pipeline")
+        .enable_all()
+        .build()
+        .unwrap();
 }
 
 pub fn block_on<F: Future>(future: F) -> F::Output {
     TOKIO_RUNTIME.lock().unwrap().block_on(future)
 }
+
+/// apply `f` to the items received, on the blocking threads of the pipeline runtime and `concurrency` items at a time,
+/// the results are sent in the order of the items. It stops at the first error, or once the results are not received anymore.
+pub fn map_concurrently<T, R, F>(
+    items: mpsc::Receiver<T>,
+    concurrency: usize,
+    f: F,
+) -> mpsc::Receiver<Result<R, ReplibyteError>>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> Result<R, ReplibyteError> + Send + Sync + 'static,
+{
+    let (tx, rx) = mpsc::channel(1);
+    let f = Arc::new(f);
+
+    PIPELINE_RUNTIME.spawn(async move {
+        let results = stream::unfold(items, |mut items| async move {
+            items.recv().await.map(|item| (item, items))
+        })
+        .map(|item| {
+            let f = Arc::clone(&f);
+            tokio::task::spawn_blocking(move || f(item))
+        })
+        .buffered(concurrency);
+        tokio::pin!(results);
+
+        while let Some(result) = results.next().await {
+            let result = result.unwrap_or_else(|err| {
+                Err(ReplibyteError::from(Error::new(
+                    ErrorKind::Other,
+                    format!("the pipeline panicked: {}", err),
+                )))
+            });
+
+            let is_err = result.is_err();
+            // the items not processed yet are dropped, their sender stops
+            if tx.send(result).await.is_err() || is_err {
+                break;
+            }
+        }
+    });
+
+    rx
+}
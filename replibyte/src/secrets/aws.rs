@@ -0,0 +1,111 @@
+use std::time::{Duration, SystemTime};
+
+use aws_sigv4::http_request::{sign, SignableRequest, SigningParams, SigningSettings};
+use aws_types::credentials::ProvideCredentials;
+use reqwest::blocking::Client;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::aws::sdk_config;
+use crate::errors::ReplibyteError;
+use crate::runtime::block_on;
+
+const SERVICE_NAME: &str = "secretsmanager";
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GetSecretValueResponse {
+    secret_string: Option<String>,
+    secret_binary: Option<String>,
+}
+
+/// read a secret of AWS Secrets Manager, with the credentials and the region of the environment.
+/// The secret id is the name or the ARN of the secret.
+pub fn read_secret(
+    secret_id: &str,
+    version_id: Option<&str>,
+    region: Option<String>,
+) -> Result<String, ReplibyteError> {
+    let error = |err: String| {
+        ReplibyteError::Connection(format!(
+            "failed to read the AWS secret '{}': {}",
+            secret_id, err
+        ))
+    };
+
+    let sdk_config = sdk_config(region, None, None).map_err(|err| error(err.to_string()))?;
+    let region = sdk_config
+        .region()
+        .map(|region| region.to_string())
+        .ok_or_else(|| error("the AWS region is missing, set the `region`".to_string()))?;
+    let credentials = match sdk_config.credentials_provider() {
+        Some(provider) => {
+            block_on(provider.provide_credentials()).map_err(|err| error(err.to_string()))?
+        }
+        None => return Err(error("the AWS credentials are missing".to_string())),
+    };
+
+    let mut body = json!({ "SecretId": secret_id });
+    if let Some(version_id) = version_id {
+        body["VersionId"] = json!(version_id);
+    }
+
+    let mut request = http::Request::builder()
+        .method("POST")
+        .uri(format!(
+            "https://{}.{}.amazonaws.com/",
+            SERVICE_NAME, region
+        ))
+        .header("content-type", "application/x-amz-json-1.1")
+        .header("x-amz-target", "secretsmanager.GetSecretValue")
+        .body(body.to_string().into_bytes())
+        .map_err(|err| error(err.to_string()))?;
+
+    let mut signing_params = SigningParams::builder()
+        .access_key(credentials.access_key_id())
+        .secret_key(credentials.secret_access_key())
+        .region(region.as_str())
+        .service_name(SERVICE_NAME)
+        .time(SystemTime::now())
+        .settings(SigningSettings::default());
+    signing_params.set_security_token(credentials.session_token());
+    let signing_params = signing_params
+        .build()
+        .map_err(|err| error(err.to_string()))?;
+
+    let (signing_instructions, _) = sign(SignableRequest::from(&request), &signing_params)
+        .map_err(|err| error(err.to_string()))?
+        .into_parts();
+    signing_instructions.apply_to_request(&mut request);
+
+    let client = Client::builder()
+        .timeout(TIMEOUT)
+        .build()
+        .map_err(|err| error(err.to_string()))?;
+
+    let response = client
+        .post(request.uri().to_string())
+        .headers(request.headers().clone())
+        .body(request.body().clone())
+        .send()
+        .map_err(|err| error(err.to_string()))?;
+
+    let status = response.status();
+    let response = response.text().map_err(|err| error(err.to_string()))?;
+    if !status.is_success() {
+        return Err(error(format!("{} {}", status, response)));
+    }
+
+    let response: GetSecretValueResponse =
+        serde_json::from_str(response.as_str()).map_err(|err| error(err.to_string()))?;
+
+    match (response.secret_string, response.secret_binary) {
+        (Some(secret), _) => Ok(secret),
+        (None, Some(secret)) => base64::decode(secret)
+            .ok()
+            .and_then(|secret| String::from_utf8(secret).ok())
+            .ok_or_else(|| error("the binary secret is not UTF-8 text".to_string())),
+        (None, None) => Err(error("the secret has no value".to_string())),
+    }
+}
@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use reqwest::blocking::Client;
+use serde::Deserialize;
+
+use crate::errors::ReplibyteError;
+use crate::gcp::{check_response, Credentials};
+
+const SECRET_MANAGER_API_URL: &str = "https://secretmanager.googleapis.com";
+const CLOUD_PLATFORM_SCOPE: &str = "https://www.googleapis.com/auth/cloud-platform";
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Deserialize)]
+struct AccessSecretVersionResponse {
+    payload: SecretPayload,
+}
+
+#[derive(Deserialize)]
+struct SecretPayload {
+    data: String,
+}
+
+/// read a secret of Google Cloud Secret Manager, with the credentials of `GOOGLE_APPLICATION_CREDENTIALS`
+/// or the workload identity. The name of the secret is `projects/<project>/secrets/<secret>`.
+pub fn read_secret(name: &str, version: Option<&str>) -> Result<String, ReplibyteError> {
+    let error = |err: String| {
+        ReplibyteError::Connection(format!(
+            "failed to read the Google Cloud secret '{}': {}",
+            name, err
+        ))
+    };
+
+    let client = Client::builder()
+        .timeout(TIMEOUT)
+        .build()
+        .map_err(|err| error(err.to_string()))?;
+    let credentials = Credentials::new(None, CLOUD_PLATFORM_SCOPE)?;
+
+    let response = check_response(
+        client
+            .get(secret_version_url(name, version))
+            .bearer_auth(credentials.access_token(&client)?)
+            .send(),
+    )
+    .map_err(|err| error(err.to_string()))?;
+
+    let response: AccessSecretVersionResponse = serde_json::from_slice(
+        response
+            .bytes()
+            .map_err(|err| error(err.to_string()))?
+            .as_ref(),
+    )?;
+
+    base64::decode(response.payload.data)
+        .ok()
+        .and_then(|secret| String::from_utf8(secret).ok())
+        .ok_or_else(|| error("the secret is not UTF-8 text".to_string()))
+}
+
+fn secret_version_url(name: &str, version: Option<&str>) -> String {
+    match name.contains("/versions/") {
+        true => format!("{}/v1/{}:access", SECRET_MANAGER_API_URL, name),
+        false => format!(
+            "{}/v1/{}/versions/{}:access",
+            SECRET_MANAGER_API_URL,
+            name,
+            version.unwrap_or("latest")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::secrets::gcp::secret_version_url;
+
+    #[test]
+    fn secret_version_urls() {
+        assert_eq!(
+            secret_version_url("projects/replibyte/secrets/db-password", None),
+            "https://secretmanager.googleapis.com/v1/projects/replibyte/secrets/db-password/versions/latest:access"
+        );
+        assert_eq!(
+            secret_version_url("projects/replibyte/secrets/db-password", Some("3")),
+            "https://secretmanager.googleapis.com/v1/projects/replibyte/secrets/db-password/versions/3:access"
+        );
+        assert_eq!(
+            secret_version_url("projects/replibyte/secrets/db-password/versions/2", None),
+            "https://secretmanager.googleapis.com/v1/projects/replibyte/secrets/db-password/versions/2:access"
+        );
+    }
+}
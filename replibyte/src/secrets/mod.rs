@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use serde_json::Value;
+
+use crate::config::{SecretConfig, SecretManagerConfig};
+use crate::errors::ReplibyteError;
+
+pub mod aws;
+pub mod gcp;
+pub mod vault;
+
+lazy_static! {
+    /// the secrets already read, the connection uris are decoded several times by a command
+    static ref SECRETS: Mutex<HashMap<SecretConfig, String>> = Mutex::new(HashMap::new());
+}
+
+/// read the secret from its secret manager, or the field of the JSON object it contains
+pub fn read_secret(config: &SecretConfig) -> Result<String, ReplibyteError> {
+    if let Some(secret) = SECRETS.lock().unwrap().get(config) {
+        return Ok(secret.clone());
+    }
+
+    let version = config.version.as_deref();
+    let secret = match config.from {
+        SecretManagerConfig::AwsSecretsManager => {
+            aws::read_secret(config.key.as_str(), version, config.region.clone())?
+        }
+        SecretManagerConfig::GcpSecretManager => gcp::read_secret(config.key.as_str(), version)?,
+        SecretManagerConfig::Vault => vault::read_secret(config.key.as_str(), version)?,
+    };
+
+    let secret = match (&config.field, config.from) {
+        (Some(field), _) => secret_field(secret.as_str(), field.as_str())?,
+        // the Vault secrets are key-value pairs
+        (None, SecretManagerConfig::Vault) => single_secret_field(secret.as_str())?,
+        (None, _) => secret,
+    };
+
+    let _ = SECRETS
+        .lock()
+        .unwrap()
+        .insert(config.clone(), secret.clone());
+
+    Ok(secret)
+}
+
+fn secret_fields(secret: &str) -> Result<serde_json::Map<String, Value>, ReplibyteError> {
+    match serde_json::from_str::<Value>(secret) {
+        Ok(Value::Object(fields)) => Ok(fields),
+        _ => Err(ReplibyteError::Config(
+            "the secret is not a JSON object, remove its `field`".to_string(),
+        )),
+    }
+}
+
+fn field_value(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// value of the field of a secret containing a JSON object, e.g. the `password` of an RDS secret
+fn secret_field(secret: &str, field: &str) -> Result<String, ReplibyteError> {
+    secret_fields(secret)?
+        .get(field)
+        .map(field_value)
+        .ok_or_else(|| ReplibyteError::Config(format!("the secret has no field '{}'", field)))
+}
+
+fn single_secret_field(secret: &str) -> Result<String, ReplibyteError> {
+    let fields = secret_fields(secret)?;
+
+    match fields.values().next() {
+        Some(value) if fields.len() == 1 => Ok(field_value(value)),
+        _ => Err(ReplibyteError::Config(format!(
+            "the secret has several fields, set the `field` to read (one of {})",
+            fields.keys().cloned().collect::<Vec<_>>().join(", ")
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::secrets::{secret_field, single_secret_field};
+
+    #[test]
+    fn secret_fields() {
+        let secret = r#"{"username": "root", "password": "p@ssword", "port": 5432}"#;
+
+        assert_eq!(secret_field(secret, "password").unwrap(), "p@ssword");
+        assert_eq!(secret_field(secret, "port").unwrap(), "5432");
+        assert!(secret_field(secret, "host").is_err());
+        assert!(secret_field("p@ssword", "password").is_err());
+
+        assert_eq!(
+            single_secret_field(r#"{"password": "p@ssword"}"#).unwrap(),
+            "p@ssword"
+        );
+        assert!(single_secret_field(secret).is_err());
+    }
+}
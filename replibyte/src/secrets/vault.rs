@@ -0,0 +1,134 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use reqwest::blocking::Client;
+use serde_json::Value;
+
+use crate::errors::ReplibyteError;
+use crate::gcp::check_response;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// read a secret of HashiCorp Vault, from `VAULT_ADDR` with `VAULT_TOKEN` (or the token of `vault login`).
+/// The path of the secret is the one of the API, e.g. `secret/data/prod/db` for the KV version 2 engine.
+/// Return the key-value pairs of the secret as a JSON object.
+pub fn read_secret(path: &str, version: Option<&str>) -> Result<String, ReplibyteError> {
+    let error = |err: String| {
+        ReplibyteError::Connection(format!(
+            "failed to read the Vault secret '{}': {}",
+            path, err
+        ))
+    };
+
+    let address = std::env::var("VAULT_ADDR").map_err(|_| {
+        ReplibyteError::Config(
+            "VAULT_ADDR is missing, set it to the address of the Vault server".to_string(),
+        )
+    })?;
+
+    let client = Client::builder()
+        .timeout(TIMEOUT)
+        .build()
+        .map_err(|err| error(err.to_string()))?;
+
+    let mut request = client
+        .get(secret_url(address.as_str(), path, version))
+        .header("X-Vault-Token", token()?);
+
+    if let Ok(namespace) = std::env::var("VAULT_NAMESPACE") {
+        request = request.header("X-Vault-Namespace", namespace);
+    }
+
+    let response = check_response(request.send()).map_err(|err| error(err.to_string()))?;
+    let response: Value = serde_json::from_slice(
+        response
+            .bytes()
+            .map_err(|err| error(err.to_string()))?
+            .as_ref(),
+    )?;
+
+    secret_data(response)
+        .map(|data| data.to_string())
+        .ok_or_else(|| error("the response has no data".to_string()))
+}
+
+fn secret_url(address: &str, path: &str, version: Option<&str>) -> String {
+    let url = format!(
+        "{}/v1/{}",
+        address.trim_end_matches('/'),
+        path.trim_start_matches('/')
+    );
+
+    match version {
+        Some(version) => format!("{}?version={}", url, version),
+        None => url,
+    }
+}
+
+/// the KV version 2 engine returns the key-value pairs in `data.data`, along with their metadata
+fn secret_data(response: Value) -> Option<Value> {
+    let data = response.get("data")?;
+
+    match (data.get("data"), data.get("metadata")) {
+        (Some(Value::Object(data)), Some(_)) => Some(Value::Object(data.clone())),
+        _ => Some(data.clone()),
+    }
+}
+
+fn token() -> Result<String, ReplibyteError> {
+    if let Ok(token) = std::env::var("VAULT_TOKEN") {
+        return Ok(token);
+    }
+
+    // the token written by `vault login`
+    let token_file = std::env::var("HOME")
+        .map(|home| PathBuf::from(home).join(".vault-token"))
+        .ok();
+
+    token_file
+        .and_then(|token_file| fs::read_to_string(token_file).ok())
+        .map(|token| token.trim().to_string())
+        .ok_or_else(|| {
+            ReplibyteError::Config(
+                "VAULT_TOKEN is missing, set it or log in with `vault login`".to_string(),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::secrets::vault::{secret_data, secret_url};
+
+    #[test]
+    fn secret_urls() {
+        assert_eq!(
+            secret_url("https://vault.internal:8200/", "secret/data/prod/db", None),
+            "https://vault.internal:8200/v1/secret/data/prod/db"
+        );
+        assert_eq!(
+            secret_url(
+                "https://vault.internal:8200",
+                "/secret/data/prod/db",
+                Some("2")
+            ),
+            "https://vault.internal:8200/v1/secret/data/prod/db?version=2"
+        );
+    }
+
+    #[test]
+    fn kv_secrets() {
+        let kv1 = json!({ "data": { "password": "p@ssword" }, "lease_duration": 3600 });
+        assert_eq!(secret_data(kv1).unwrap(), json!({ "password": "p@ssword" }));
+
+        let kv2 = json!({
+            "data": {
+                "data": { "password": "p@ssword" },
+                "metadata": { "version": 2 }
+            }
+        });
+        assert_eq!(secret_data(kv2).unwrap(), json!({ "password": "p@ssword" }));
+    }
+}
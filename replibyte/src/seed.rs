@@ -0,0 +1,1770 @@
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+use chrono::{Duration, NaiveDate};
+use fake::faker::address::raw::{BuildingNumber, CityName, CountryName, StreetName, ZipCode};
+use fake::faker::company::raw::CompanyName;
+use fake::faker::internet::raw::{SafeEmail, Username};
+use fake::faker::lorem::raw::{Sentence, Word};
+use fake::faker::name::raw::{FirstName, LastName, Name};
+use fake::faker::phone_number::raw::PhoneNumber;
+use fake::locales::EN;
+use fake::Fake;
+use log::warn;
+use rand::Rng;
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::errors::ReplibyteError;
+use crate::source::dump_file::DumpType;
+use crate::utils::strip_prefix_ignore_case;
+
+/// share of the NULL values of the nullable columns which are not keys
+const NULL_RATIO: f64 = 0.1;
+
+/// attempts to generate a row whose unique keys are not taken yet, the row is skipped afterwards
+const MAX_ROW_ATTEMPTS: usize = 10;
+
+/// first date of the generated dates and timestamps, they span 10 years
+const FIRST_DATE: (i32, u32, u32) = (2015, 1, 1);
+const DAYS: i64 = 3650;
+
+/// keywords starting a table constraint rather than a column definition, in a `CREATE TABLE` query
+const TABLE_CONSTRAINT_KEYWORDS: [&str; 11] = [
+    "CONSTRAINT",
+    "PRIMARY",
+    "UNIQUE",
+    "FOREIGN",
+    "CHECK",
+    "KEY",
+    "INDEX",
+    "FULLTEXT",
+    "SPATIAL",
+    "EXCLUDE",
+    "LIKE",
+];
+
+/// keywords ending the type of a column definition
+const COLUMN_CONSTRAINT_KEYWORDS: [&str; 17] = [
+    "NOT",
+    "NULL",
+    "DEFAULT",
+    "PRIMARY",
+    "UNIQUE",
+    "REFERENCES",
+    "CHECK",
+    "CONSTRAINT",
+    "COLLATE",
+    "GENERATED",
+    "AUTO_INCREMENT",
+    "COMMENT",
+    "ON",
+    "AS",
+    "CHARSET",
+    "VISIBLE",
+    "INVISIBLE",
+];
+
+/// token of a schema query, the identifiers keep their quotes
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Word(String),
+    Number(String),
+    String(String),
+    LParen,
+    RParen,
+    Comma,
+    Period,
+    /// `[` of the PostgreSQL array types, e.g. `text[]`
+    LBracket,
+    Other,
+}
+
+impl Part {
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Part::Word(word) if word.eq_ignore_ascii_case(keyword))
+    }
+}
+
+/// type of a column, e.g. `character varying` with the `255` argument
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ColumnType {
+    /// lowercase words of the type, e.g. `timestamp without time zone` or `public.mood`
+    pub name: String,
+    /// numbers and labels between the parentheses, e.g. `10` and `2` for `numeric(10,2)`
+    pub args: Vec<String>,
+    pub array: bool,
+}
+
+/// how the values of a column are set
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnValues {
+    /// fake values generated from the type and the name of the column
+    Fake,
+    /// identity column always generated by the database: 1, 2, 3...
+    Identity,
+    /// computed by the database from the other columns
+    Computed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableColumn {
+    /// name of the column, with its quotes if it is quoted
+    pub name: String,
+    pub column_type: ColumnType,
+    pub not_null: bool,
+    pub values: ColumnValues,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForeignKey {
+    pub columns: Vec<String>,
+    pub table: String,
+    /// the primary key of the referenced table if it is empty
+    pub referenced_columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    /// name of the table, with its schema and its quotes, e.g. `public.users`
+    pub name: String,
+    pub columns: Vec<TableColumn>,
+    /// columns of the primary key and of the unique constraints and indexes, the primary key first
+    pub unique_keys: Vec<Vec<String>>,
+    pub foreign_keys: Vec<ForeignKey>,
+}
+
+impl Table {
+    fn column_index(&self, name: &str) -> Option<usize> {
+        let key = identifier_key(name);
+        self.columns
+            .iter()
+            .position(|column| identifier_key(column.name.as_str()) == key)
+    }
+
+    fn column_indexes(&self, names: &[String]) -> Option<Vec<usize>> {
+        names.iter().map(|name| self.column_index(name)).collect()
+    }
+
+    fn set_not_null(&mut self, names: &[String]) {
+        for name in names {
+            if let Some(idx) = self.column_index(name) {
+                self.columns[idx].not_null = true;
+            }
+        }
+    }
+}
+
+/// Schema is made of the tables of the `CREATE TABLE` queries of a schema only dump, with their constraints,
+/// to generate fake rows for them
+#[derive(Debug)]
+pub struct Schema {
+    pub tables: Vec<Table>,
+    dump_type: DumpType,
+    /// labels of the PostgreSQL enum types, by type
+    enums: HashMap<String, Vec<String>>,
+    /// PostgreSQL domains, by domain
+    domains: HashMap<String, ColumnType>,
+}
+
+impl Schema {
+    /// parse the queries creating the database objects, only PostgreSQL and MySQL (or MariaDB) are supported
+    pub fn parse(queries: &[String], dump_type: DumpType) -> Result<Self, ReplibyteError> {
+        if !matches!(
+            dump_type,
+            DumpType::Postgres | DumpType::Mysql | DumpType::MariaDB
+        ) {
+            return Err(ReplibyteError::Config(format!(
+                "the rows of a {} schema cannot be generated, only PostgreSQL, MySQL and MariaDB are supported",
+                dump_type.database_type()
+            )));
+        }
+
+        let mut schema = Schema {
+            tables: vec![],
+            dump_type,
+            enums: HashMap::new(),
+            domains: HashMap::new(),
+        };
+
+        for query in queries {
+            schema.add_query(query.trim());
+        }
+
+        Ok(schema)
+    }
+
+    fn add_query(&mut self, query: &str) {
+        if strip_prefix_ignore_case(query, "CREATE").is_none()
+            && strip_prefix_ignore_case(query, "ALTER TABLE").is_none()
+        {
+            return;
+        }
+
+        let parts = match parts(query, self.dump_type) {
+            Some(parts) => parts,
+            None => {
+                warn!("cannot parse the query '{}'", query);
+                return;
+            }
+        };
+
+        let mut pos = 1;
+        if matches!(parts.first(), Some(part) if part.is_keyword("ALTER")) {
+            self.alter_table(&parts[2..]);
+            return;
+        }
+
+        // CREATE [UNLOGGED | TEMPORARY] TABLE, CREATE UNIQUE INDEX, CREATE TYPE or CREATE DOMAIN
+        while matches!(parts.get(pos), Some(part) if ["UNLOGGED", "TEMP", "TEMPORARY", "GLOBAL", "LOCAL"]
+            .iter()
+            .any(|keyword| part.is_keyword(keyword)))
+        {
+            pos += 1;
+        }
+
+        match parts.get(pos) {
+            Some(part) if part.is_keyword("TABLE") => self.create_table(&parts[pos + 1..]),
+            Some(part) if part.is_keyword("UNIQUE") => self.unique_index(&parts[pos + 1..]),
+            Some(part) if part.is_keyword("TYPE") => self.enum_type(&parts[pos + 1..]),
+            Some(part) if part.is_keyword("DOMAIN") => self.domain(&parts[pos + 1..]),
+            _ => {}
+        }
+    }
+
+    /// `[IF NOT EXISTS] <name> (<columns and constraints>)`
+    fn create_table(&mut self, parts: &[Part]) {
+        let pos = skip_keywords(parts, 0, &["IF", "NOT", "EXISTS"]);
+        let (name, pos) = match identifier(parts, pos) {
+            Some(identifier) => identifier,
+            // e.g. CREATE TABLE ... PARTITION OF
+            None => return,
+        };
+
+        let definitions = match group(parts, pos) {
+            Some((definitions, _)) => definitions,
+            // e.g. CREATE TABLE ... AS SELECT
+            None => return,
+        };
+
+        let mut table = Table {
+            name,
+            columns: vec![],
+            unique_keys: vec![],
+            foreign_keys: vec![],
+        };
+
+        for definition in split_list(definitions) {
+            match definition.first() {
+                Some(part)
+                    if TABLE_CONSTRAINT_KEYWORDS
+                        .iter()
+                        .any(|keyword| part.is_keyword(keyword)) =>
+                {
+                    add_table_constraint(&mut table, definition)
+                }
+                Some(_) => add_column(&mut table, definition),
+                None => {}
+            }
+        }
+
+        self.tables.push(table);
+    }
+
+    /// `[ONLY] [IF EXISTS] <name> <action>[, <action>...]`, the actions adding a constraint or a column,
+    /// or changing a column into an identity or a NOT NULL one
+    fn alter_table(&mut self, parts: &[Part]) {
+        let pos = skip_keywords(parts, 0, &["ONLY", "IF", "EXISTS"]);
+        let (name, pos) = match identifier(parts, pos) {
+            Some(identifier) => identifier,
+            None => return,
+        };
+
+        let table = match self.table_index(name.as_str()) {
+            Some(idx) => &mut self.tables[idx],
+            None => return,
+        };
+
+        for action in split_list(&parts[pos..]) {
+            match action {
+                [add, column, definition @ ..]
+                    if add.is_keyword("ADD") && column.is_keyword("COLUMN") =>
+                {
+                    add_column(table, definition)
+                }
+                [add, constraint @ ..] if add.is_keyword("ADD") => {
+                    add_table_constraint(table, constraint)
+                }
+                // MySQL: MODIFY [COLUMN] <column definition>
+                [modify, definition @ ..] if modify.is_keyword("MODIFY") => {
+                    let definition = match definition {
+                        [column, definition @ ..] if column.is_keyword("COLUMN") => definition,
+                        definition => definition,
+                    };
+
+                    if let Some(Part::Word(name)) = definition.first() {
+                        if let Some(idx) = table.column_index(name) {
+                            let _ = table.columns.remove(idx);
+                        }
+                    }
+                    add_column(table, definition);
+                }
+                // PostgreSQL: ALTER [COLUMN] <column> ADD GENERATED ... AS IDENTITY or SET NOT NULL
+                [alter, rest @ ..] if alter.is_keyword("ALTER") => {
+                    let rest = match rest {
+                        [column, rest @ ..] if column.is_keyword("COLUMN") => rest,
+                        rest => rest,
+                    };
+
+                    let idx = match rest.first() {
+                        Some(Part::Word(name)) => match table.column_index(name) {
+                            Some(idx) => idx,
+                            None => continue,
+                        },
+                        _ => continue,
+                    };
+
+                    if position_of(rest, &["GENERATED", "ALWAYS"]).is_some() {
+                        table.columns[idx].values = ColumnValues::Identity;
+                    } else if position_of(rest, &["SET", "NOT", "NULL"]).is_some() {
+                        table.columns[idx].not_null = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// `INDEX [CONCURRENTLY] [IF NOT EXISTS] <name> ON [ONLY] <table> [USING <method>] (<columns>)`,
+    /// the indexes on expressions are skipped
+    fn unique_index(&mut self, parts: &[Part]) {
+        let on = match parts.iter().position(|part| part.is_keyword("ON")) {
+            Some(on) => on,
+            None => return,
+        };
+
+        let pos = skip_keywords(parts, on + 1, &["ONLY"]);
+        let (name, pos) = match identifier(parts, pos) {
+            Some(identifier) => identifier,
+            None => return,
+        };
+
+        let pos = match parts.get(pos) {
+            Some(part) if part.is_keyword("USING") => pos + 2,
+            _ => pos,
+        };
+
+        let columns = match group(parts, pos) {
+            Some((columns, _)) => split_list(columns),
+            None => return,
+        };
+
+        let columns = columns
+            .into_iter()
+            .map(|column| match column {
+                [Part::Word(name)] => Some(name.clone()),
+                [Part::Word(name), Part::Word(_), ..] => Some(name.clone()), // e.g. `email DESC`
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+
+        if let (Some(idx), Some(columns)) = (self.table_index(name.as_str()), columns) {
+            self.tables[idx].unique_keys.push(columns);
+        }
+    }
+
+    /// `<name> AS ENUM ('<label>', ...)`
+    fn enum_type(&mut self, parts: &[Part]) {
+        let (name, pos) = match identifier(parts, 0) {
+            Some(identifier) => identifier,
+            None => return,
+        };
+
+        if !matches!(parts.get(pos), Some(part) if part.is_keyword("AS"))
+            || !matches!(parts.get(pos + 1), Some(part) if part.is_keyword("ENUM"))
+        {
+            return;
+        }
+
+        if let Some((labels, _)) = group(parts, pos + 2) {
+            let labels = labels
+                .iter()
+                .filter_map(|part| match part {
+                    Part::String(label) => Some(label.clone()),
+                    _ => None,
+                })
+                .collect();
+
+            let _ = self.enums.insert(identifier_key(name.as_str()), labels);
+        }
+    }
+
+    /// `<name> [AS] <type> [<constraints>]`
+    fn domain(&mut self, parts: &[Part]) {
+        let (name, pos) = match identifier(parts, 0) {
+            Some(identifier) => identifier,
+            None => return,
+        };
+
+        let pos = skip_keywords(parts, pos, &["AS"]);
+        let (column_type, _) = column_type(parts, pos);
+        let _ = self
+            .domains
+            .insert(identifier_key(name.as_str()), column_type);
+    }
+
+    /// the table with the name, or the only table with this name in any schema
+    fn table_index(&self, name: &str) -> Option<usize> {
+        let key = identifier_key(name);
+        if let Some(idx) = self
+            .tables
+            .iter()
+            .position(|table| identifier_key(table.name.as_str()) == key)
+        {
+            return Some(idx);
+        }
+
+        let last_part = |key: &str| key.rsplit('.').next().map(str::to_string);
+        let matches = self
+            .tables
+            .iter()
+            .enumerate()
+            .filter(|(_, table)| {
+                last_part(identifier_key(table.name.as_str()).as_str()) == last_part(key.as_str())
+            })
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+
+        match matches.as_slice() {
+            [idx] => Some(*idx),
+            _ => None,
+        }
+    }
+
+    /// write `rows` fake rows per table as `INSERT INTO` queries, the referenced tables first, and return the number
+    /// of rows written. The same seed generates the same rows.
+    pub fn write_rows<W: Write>(
+        &self,
+        rows: usize,
+        seed: Option<u64>,
+        writer: &mut W,
+    ) -> Result<usize, ReplibyteError> {
+        let rng = match seed {
+            Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+            None => ChaCha8Rng::from_entropy(),
+        };
+
+        let mut generator = RowGenerator {
+            schema: self,
+            rng,
+            rows: HashMap::new(),
+        };
+
+        let mut written_rows = 0;
+        for idx in self.ordered_tables() {
+            written_rows += generator.write_table_rows(idx, rows, writer)?;
+        }
+
+        Ok(written_rows)
+    }
+
+    /// indexes of the tables, a table comes after the tables it references, except the tables referencing each other
+    fn ordered_tables(&self) -> Vec<usize> {
+        let dependencies = self
+            .tables
+            .iter()
+            .enumerate()
+            .map(|(idx, table)| {
+                table
+                    .foreign_keys
+                    .iter()
+                    .filter_map(|foreign_key| self.table_index(foreign_key.table.as_str()))
+                    .filter(|dependency| *dependency != idx)
+                    .collect::<HashSet<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut ordered = vec![];
+        let mut remaining = (0..self.tables.len()).collect::<Vec<_>>();
+
+        while !remaining.is_empty() {
+            let next = remaining
+                .iter()
+                .position(|idx| {
+                    dependencies[*idx]
+                        .iter()
+                        .all(|dependency| ordered.contains(dependency))
+                })
+                // the tables reference each other, their nullable foreign keys are set to NULL
+                .unwrap_or(0);
+
+            ordered.push(remaining.remove(next));
+        }
+
+        ordered
+    }
+
+    /// kind of the values of a type, `None` if they cannot be generated
+    fn value_kind(&self, column_type: &ColumnType) -> Option<ValueKind> {
+        if column_type.array {
+            return match self.dump_type {
+                DumpType::Postgres => Some(ValueKind::Array),
+                _ => None,
+            };
+        }
+
+        let arg = |idx: usize| {
+            column_type
+                .args
+                .get(idx)
+                .and_then(|arg| arg.parse::<usize>().ok())
+        };
+
+        let kind = match column_type.name.as_str() {
+            "smallint" | "int2" | "smallserial" | "serial2" => ValueKind::Integer(i16::MAX as u64),
+            "tinyint" if arg(0) == Some(1) => ValueKind::Boolean,
+            "tinyint" => ValueKind::Integer(i8::MAX as u64),
+            "mediumint" => ValueKind::Integer(8_388_607),
+            "integer" | "int" | "int4" | "serial" | "serial4" => {
+                ValueKind::Integer(i32::MAX as u64)
+            }
+            "bigint" | "int8" | "bigserial" | "serial8" => ValueKind::Integer(i64::MAX as u64),
+            "boolean" | "bool" => ValueKind::Boolean,
+            "bit" if arg(0).unwrap_or(1) == 1 => match self.dump_type {
+                DumpType::Postgres => ValueKind::Labels(vec!["0".to_string(), "1".to_string()]),
+                _ => ValueKind::Integer(1),
+            },
+            "numeric" | "decimal" | "dec" | "fixed" => {
+                ValueKind::Decimal(arg(0).unwrap_or(10), arg(1).unwrap_or(2))
+            }
+            "money" => ValueKind::Decimal(10, 2),
+            "real" | "float" | "float4" | "float8" | "double" | "double precision" => {
+                ValueKind::Float
+            }
+            "character varying"
+            | "varchar"
+            | "character"
+            | "char"
+            | "bpchar"
+            | "nvarchar"
+            | "nchar"
+            | "national character varying"
+            | "national character" => ValueKind::Text(arg(0).unwrap_or(255)),
+            "text" | "citext" | "tinytext" | "mediumtext" | "longtext" | "name" => {
+                ValueKind::Text(usize::MAX)
+            }
+            "uuid" => ValueKind::Uuid,
+            "date" => ValueKind::Date,
+            "timestamp"
+            | "timestamp without time zone"
+            | "timestamp with time zone"
+            | "timestamptz"
+            | "datetime" => ValueKind::Timestamp,
+            "time" | "time without time zone" | "time with time zone" | "timetz" => ValueKind::Time,
+            "year" => ValueKind::Year,
+            "interval" => ValueKind::Interval,
+            "json" | "jsonb" => ValueKind::Json,
+            "bytea" | "blob" | "tinyblob" | "mediumblob" | "longblob" => {
+                ValueKind::Binary(usize::MAX)
+            }
+            "binary" | "varbinary" => ValueKind::Binary(arg(0).unwrap_or(1)),
+            "inet" => ValueKind::Inet,
+            "cidr" => ValueKind::Cidr,
+            "macaddr" => ValueKind::MacAddress,
+            "enum" | "set" => ValueKind::Labels(column_type.args.clone()),
+            name => {
+                let key = identifier_key(name);
+
+                if let Some(labels) = self.enums.get(&key) {
+                    ValueKind::Labels(labels.clone())
+                } else if let Some(column_type) = self.domains.get(&key) {
+                    return self.value_kind(column_type);
+                } else {
+                    return None;
+                }
+            }
+        };
+
+        match &kind {
+            ValueKind::Labels(labels) if labels.is_empty() => None,
+            _ => Some(kind),
+        }
+    }
+}
+
+/// kind of the generated values of a column
+#[derive(Debug, Clone, PartialEq)]
+enum ValueKind {
+    /// max value
+    Integer(u64),
+    /// precision and scale
+    Decimal(usize, usize),
+    Float,
+    Boolean,
+    /// max length
+    Text(usize),
+    Uuid,
+    Date,
+    Timestamp,
+    Time,
+    Year,
+    Interval,
+    Json,
+    /// max length
+    Binary(usize),
+    Inet,
+    Cidr,
+    MacAddress,
+    /// labels of an enum
+    Labels(Vec<String>),
+    Array,
+}
+
+/// RowGenerator writes the rows of the tables one table after the other. The rows of the referenced tables are kept,
+/// the foreign keys reference them.
+struct RowGenerator<'a> {
+    schema: &'a Schema,
+    rng: ChaCha8Rng,
+    /// SQL values of the rows of the referenced tables, by table
+    rows: HashMap<usize, Vec<Vec<Option<String>>>>,
+}
+
+impl<'a> RowGenerator<'a> {
+    fn write_table_rows<W: Write>(
+        &mut self,
+        table_idx: usize,
+        rows: usize,
+        writer: &mut W,
+    ) -> Result<usize, ReplibyteError> {
+        let table = &self.schema.tables[table_idx];
+
+        let kinds = table
+            .columns
+            .iter()
+            .map(|column| match column.values {
+                ColumnValues::Fake => {
+                    let kind = self.schema.value_kind(&column.column_type);
+                    if kind.is_none() {
+                        warn!(
+                            "the values of the column {}.{} of type '{}' cannot be generated, its default value is used",
+                            table.name, column.name, column.column_type.name
+                        );
+                    }
+                    kind
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let insert_columns = (0..table.columns.len())
+            .filter(|idx| kinds[*idx].is_some())
+            .collect::<Vec<_>>();
+
+        if insert_columns.is_empty() {
+            warn!(
+                "the table {} has no column to generate, its rows are skipped",
+                table.name
+            );
+            return Ok(0);
+        }
+
+        // the foreign keys of the referenced tables found in the schema, with the indexes of their columns
+        let foreign_keys = table
+            .foreign_keys
+            .iter()
+            .filter_map(|foreign_key| {
+                let referenced_idx = self.schema.table_index(foreign_key.table.as_str())?;
+                let referenced_table = &self.schema.tables[referenced_idx];
+                let referenced_columns = match foreign_key.referenced_columns.is_empty() {
+                    true => referenced_table.unique_keys.first()?.clone(),
+                    false => foreign_key.referenced_columns.clone(),
+                };
+
+                let columns = table.column_indexes(&foreign_key.columns)?;
+                let referenced_columns = referenced_table.column_indexes(&referenced_columns)?;
+                let is_unique = table.unique_keys.iter().any(|unique_key| {
+                    matches!(table.column_indexes(unique_key), Some(unique_key)
+                        if unique_key.iter().all(|idx| columns.contains(idx)))
+                });
+
+                Some((referenced_idx, columns, referenced_columns, is_unique))
+            })
+            .collect::<Vec<_>>();
+
+        let foreign_key_columns = foreign_keys
+            .iter()
+            .flat_map(|(_, columns, _, _)| columns.iter().copied())
+            .collect::<HashSet<_>>();
+
+        let unique_keys = table
+            .unique_keys
+            .iter()
+            .filter_map(|unique_key| table.column_indexes(unique_key))
+            .collect::<Vec<_>>();
+
+        let unique_columns = unique_keys
+            .iter()
+            .filter(|unique_key| unique_key.len() == 1)
+            .map(|unique_key| unique_key[0])
+            .collect::<HashSet<_>>();
+
+        let key_columns = unique_keys
+            .iter()
+            .flatten()
+            .copied()
+            .collect::<HashSet<_>>();
+
+        let is_referenced = self.schema.tables.iter().any(|other_table| {
+            other_table.foreign_keys.iter().any(|foreign_key| {
+                self.schema.table_index(foreign_key.table.as_str()) == Some(table_idx)
+            })
+        });
+
+        let mut unique_values = vec![HashSet::new(); unique_keys.len()];
+        let mut written_rows = vec![];
+        let mut skipped_rows = 0;
+
+        for row in 0..rows {
+            let mut generated_row = None;
+
+            'attempts: for _ in 0..MAX_ROW_ATTEMPTS {
+                let mut values = vec![None; table.columns.len()];
+
+                for (idx, column) in table.columns.iter().enumerate() {
+                    values[idx] = match (&kinds[idx], column.values) {
+                        (_, ColumnValues::Identity) => Some((written_rows.len() + 1).to_string()),
+                        (Some(_), _) if foreign_key_columns.contains(&idx) => None,
+                        (Some(_), _)
+                            if !column.not_null
+                                && !key_columns.contains(&idx)
+                                && self.rng.gen_bool(NULL_RATIO) =>
+                        {
+                            None
+                        }
+                        (Some(kind), _) => {
+                            Some(self.value(kind, column, row, unique_columns.contains(&idx)))
+                        }
+                        (None, _) => None,
+                    };
+                }
+
+                for (referenced_idx, columns, referenced_columns, is_unique) in &foreign_keys {
+                    let referenced_rows = match *referenced_idx == table_idx {
+                        true => written_rows.as_slice(),
+                        false => self
+                            .rows
+                            .get(referenced_idx)
+                            .map(|rows| rows.as_slice())
+                            .unwrap_or_default(),
+                    };
+
+                    let referenced_row = match referenced_rows.len() {
+                        0 => None,
+                        _ if *is_unique => match referenced_rows.get(row) {
+                            Some(referenced_row) => Some(referenced_row),
+                            // every referenced row is already referenced
+                            None => break 'attempts,
+                        },
+                        len => Some(&referenced_rows[self.rng.gen_range(0..len)]),
+                    };
+
+                    match referenced_row {
+                        Some(referenced_row) => {
+                            for (idx, referenced_column) in columns.iter().zip(referenced_columns) {
+                                values[*idx] = referenced_row[*referenced_column].clone();
+                            }
+                        }
+                        // the first row of a table referencing itself references itself
+                        None if *referenced_idx == table_idx
+                            && columns.iter().any(|idx| table.columns[*idx].not_null) =>
+                        {
+                            for (idx, referenced_column) in columns.iter().zip(referenced_columns) {
+                                values[*idx] = values[*referenced_column].clone();
+                            }
+                        }
+                        None if columns.iter().all(|idx| !table.columns[*idx].not_null) => {}
+                        None => {
+                            return Err(ReplibyteError::Config(format!(
+                                "the table {} references the table {} without rows with a NOT NULL foreign key, \
+                                 the tables referencing each other need a nullable foreign key",
+                                table.name, self.schema.tables[*referenced_idx].name
+                            )));
+                        }
+                    }
+                }
+
+                let keys = unique_keys
+                    .iter()
+                    .map(|unique_key| {
+                        unique_key
+                            .iter()
+                            .map(|idx| values[*idx].clone())
+                            .collect::<Option<Vec<_>>>()
+                    })
+                    .collect::<Vec<_>>();
+
+                // the NULL values are never the same
+                if keys.iter().zip(&unique_values).any(
+                    |(key, unique_values)| matches!(key, Some(key) if unique_values.contains(key)),
+                ) {
+                    continue;
+                }
+
+                for (key, unique_values) in keys.into_iter().zip(unique_values.iter_mut()) {
+                    if let Some(key) = key {
+                        let _ = unique_values.insert(key);
+                    }
+                }
+
+                generated_row = Some(values);
+                break;
+            }
+
+            let values = match generated_row {
+                Some(values) => values,
+                None => {
+                    skipped_rows += 1;
+                    continue;
+                }
+            };
+
+            writeln!(
+                writer,
+                "INSERT INTO {} ({}) VALUES ({});",
+                table.name,
+                insert_columns
+                    .iter()
+                    .map(|idx| table.columns[*idx].name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                insert_columns
+                    .iter()
+                    .map(|idx| values[*idx].as_deref().unwrap_or("NULL"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?;
+
+            written_rows.push(values);
+        }
+
+        if skipped_rows > 0 {
+            warn!(
+                "{} rows of the table {} are skipped, their unique keys or their foreign keys cannot be different",
+                skipped_rows, table.name
+            );
+        }
+
+        let count = written_rows.len();
+        if is_referenced {
+            let _ = self.rows.insert(table_idx, written_rows);
+        }
+
+        Ok(count)
+    }
+
+    /// SQL value of a column, unique values are derived from the index of the row
+    fn value(
+        &mut self,
+        kind: &ValueKind,
+        column: &TableColumn,
+        row: usize,
+        unique: bool,
+    ) -> String {
+        let rng = &mut self.rng;
+        let is_postgres = self.schema.dump_type == DumpType::Postgres;
+        let n = row + 1;
+
+        match kind {
+            ValueKind::Integer(max) => match unique {
+                true => n.to_string(),
+                false => rng.gen_range(1..=(*max).min(1000)).to_string(),
+            },
+            ValueKind::Decimal(precision, scale) => {
+                let integer_digits = precision.saturating_sub(*scale).min(6) as u32;
+                let integer = match unique {
+                    true => n as u64,
+                    false => rng.gen_range(0..10u64.pow(integer_digits)),
+                };
+
+                match scale {
+                    0 => integer.to_string(),
+                    scale => format!(
+                        "{}.{:0width$}",
+                        integer,
+                        rng.gen_range(0..10u64.pow((*scale).min(6) as u32)),
+                        width = (*scale).min(6)
+                    ),
+                }
+            }
+            ValueKind::Float => match unique {
+                true => format!("{}.{:02}", n, rng.gen_range(0..100)),
+                false => format!("{:.2}", rng.gen_range(0.0..1000.0)),
+            },
+            ValueKind::Boolean => match (is_postgres, rng.gen_bool(0.5)) {
+                (true, value) => value.to_string(),
+                (false, value) => (value as u8).to_string(),
+            },
+            ValueKind::Text(max_len) => {
+                let name = identifier_key(column.name.as_str());
+                let value = fake_text(name.as_str(), rng);
+
+                let value = match unique {
+                    true => match value.split_once('@') {
+                        Some((local, domain)) => format!("{}.{}@{}", local, n, domain),
+                        None => format!("{}-{}", value, n),
+                    },
+                    false => value,
+                };
+
+                let value = match value.chars().count() > *max_len {
+                    // the short unique values are the index of the row in base 36, e.g. for a `char(2)` code
+                    true if unique && *max_len < 6 => {
+                        let value = format!("{:0>width$}", base36(row), width = *max_len);
+                        value[value.len() - *max_len..].to_string()
+                    }
+                    // the suffix of the unique values is kept
+                    true if unique => {
+                        let suffix = n.to_string();
+                        let prefix = value
+                            .chars()
+                            .take(max_len.saturating_sub(suffix.len()))
+                            .collect::<String>();
+                        format!("{}{}", prefix, suffix)
+                    }
+                    true => value.chars().take(*max_len).collect(),
+                    false => value,
+                };
+
+                quote(value.as_str())
+            }
+            ValueKind::Uuid => {
+                let bytes = rng.gen::<[u8; 16]>();
+                let hex = bytes
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, byte)| match idx {
+                        // version 4 and variant 1
+                        6 => format!("{:02x}", (byte & 0x0f) | 0x40),
+                        8 => format!("{:02x}", (byte & 0x3f) | 0x80),
+                        _ => format!("{:02x}", byte),
+                    })
+                    .collect::<String>();
+
+                quote(
+                    format!(
+                        "{}-{}-{}-{}-{}",
+                        &hex[..8],
+                        &hex[8..12],
+                        &hex[12..16],
+                        &hex[16..20],
+                        &hex[20..]
+                    )
+                    .as_str(),
+                )
+            }
+            ValueKind::Date => {
+                let days = match unique {
+                    true => row as i64,
+                    false => rng.gen_range(0..DAYS),
+                };
+
+                quote((first_date() + Duration::days(days)).to_string().as_str())
+            }
+            ValueKind::Timestamp => {
+                let seconds = match unique {
+                    true => row as i64 * 60 + rng.gen_range(0..60),
+                    false => rng.gen_range(0..DAYS * 86_400),
+                };
+
+                let timestamp = first_date().and_hms_opt(0, 0, 0).unwrap_or_default()
+                    + Duration::seconds(seconds);
+                quote(timestamp.format("%Y-%m-%d %H:%M:%S").to_string().as_str())
+            }
+            ValueKind::Time => {
+                let seconds = match unique {
+                    true => row as u32 % 86_400,
+                    false => rng.gen_range(0..86_400),
+                };
+
+                quote(
+                    format!(
+                        "{:02}:{:02}:{:02}",
+                        seconds / 3600,
+                        seconds / 60 % 60,
+                        seconds % 60
+                    )
+                    .as_str(),
+                )
+            }
+            ValueKind::Year => match unique {
+                true => (1901 + row % 254).to_string(),
+                false => rng.gen_range(1970..2030).to_string(),
+            },
+            ValueKind::Interval => quote(format!("{} days", rng.gen_range(1..365)).as_str()),
+            ValueKind::Json => match unique {
+                true => quote(format!("{{\"id\": {}}}", n).as_str()),
+                false => quote("{}"),
+            },
+            ValueKind::Binary(max_len) => {
+                let bytes = match unique {
+                    true => n.to_be_bytes().to_vec(),
+                    false => rng.gen::<[u8; 8]>().to_vec(),
+                };
+
+                match is_postgres {
+                    true => quote(
+                        format!(
+                            "\\x{}",
+                            bytes
+                                .iter()
+                                .map(|byte| format!("{:02x}", byte))
+                                .collect::<String>()
+                        )
+                        .as_str(),
+                    ),
+                    // a MySQL binary string can be written as a string
+                    false => {
+                        let value = match unique {
+                            true => n.to_string(),
+                            false => Word(EN).fake_with_rng::<String, _>(rng),
+                        };
+                        quote(value.chars().take(*max_len).collect::<String>().as_str())
+                    }
+                }
+            }
+            ValueKind::Inet => {
+                let address = match unique {
+                    true => n as u32,
+                    false => rng.gen_range(1..0x00ff_ffff),
+                };
+
+                quote(
+                    format!(
+                        "10.{}.{}.{}",
+                        address >> 16 & 0xff,
+                        address >> 8 & 0xff,
+                        address & 0xff
+                    )
+                    .as_str(),
+                )
+            }
+            ValueKind::Cidr => {
+                let network = match unique {
+                    true => row as u32,
+                    false => rng.gen_range(0..0xffff),
+                };
+
+                quote(format!("10.{}.{}.0/24", network >> 8 & 0xff, network & 0xff).as_str())
+            }
+            ValueKind::MacAddress => {
+                let bytes = match unique {
+                    true => {
+                        let bytes = (n as u64).to_be_bytes();
+                        [2, 0, bytes[4], bytes[5], bytes[6], bytes[7]]
+                    }
+                    false => rng.gen::<[u8; 6]>(),
+                };
+
+                quote(
+                    bytes
+                        .iter()
+                        .map(|byte| format!("{:02x}", byte))
+                        .collect::<Vec<_>>()
+                        .join(":")
+                        .as_str(),
+                )
+            }
+            ValueKind::Labels(labels) => {
+                let idx = match unique {
+                    true => row % labels.len(),
+                    false => rng.gen_range(0..labels.len()),
+                };
+
+                quote(labels[idx].as_str())
+            }
+            ValueKind::Array => quote("{}"),
+        }
+    }
+}
+
+fn base36(mut n: usize) -> String {
+    let mut digits = vec![];
+    loop {
+        digits.push(std::char::from_digit((n % 36) as u32, 36).unwrap_or('0'));
+        n /= 36;
+        if n == 0 {
+            return digits.into_iter().rev().collect();
+        }
+    }
+}
+
+fn first_date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(FIRST_DATE.0, FIRST_DATE.1, FIRST_DATE.2).unwrap_or_default()
+}
+
+/// fake text matching the name of the column, e.g. an email for `email` or a city for `billing_city`
+fn fake_text(name: &str, rng: &mut ChaCha8Rng) -> String {
+    let is = |words: &[&str]| words.iter().any(|word| name.contains(word));
+
+    if is(&["email"]) {
+        SafeEmail(EN).fake_with_rng(rng)
+    } else if is(&["first_name", "firstname", "given_name"]) {
+        FirstName(EN).fake_with_rng(rng)
+    } else if is(&["last_name", "lastname", "surname", "family_name"]) {
+        LastName(EN).fake_with_rng(rng)
+    } else if is(&["username", "login", "nickname"]) {
+        Username(EN).fake_with_rng(rng)
+    } else if is(&["phone", "mobile"]) {
+        PhoneNumber(EN).fake_with_rng(rng)
+    } else if is(&["city"]) {
+        CityName(EN).fake_with_rng(rng)
+    } else if is(&["country"]) {
+        CountryName(EN).fake_with_rng(rng)
+    } else if is(&["zip", "postal", "postcode"]) {
+        ZipCode(EN).fake_with_rng(rng)
+    } else if is(&["address", "street"]) {
+        format!(
+            "{} {}",
+            BuildingNumber(EN).fake_with_rng::<String, _>(rng),
+            StreetName(EN).fake_with_rng::<String, _>(rng)
+        )
+    } else if is(&["company", "organization", "organisation"]) {
+        CompanyName(EN).fake_with_rng(rng)
+    } else if is(&["url", "website", "link"]) {
+        format!(
+            "https://example.com/{}",
+            Word(EN).fake_with_rng::<String, _>(rng)
+        )
+    } else if name.ends_with("name") {
+        Name(EN).fake_with_rng(rng)
+    } else if is(&[
+        "description",
+        "bio",
+        "comment",
+        "note",
+        "content",
+        "body",
+        "summary",
+        "message",
+    ]) {
+        Sentence(EN, 3..10).fake_with_rng(rng)
+    } else {
+        Word(EN).fake_with_rng(rng)
+    }
+}
+
+/// SQL string, the quotes are doubled
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// name of an identifier to compare it: without its quotes, and lowercase if it is not quoted
+fn identifier_key(identifier: &str) -> String {
+    identifier
+        .split('.')
+        .map(|part| {
+            match part
+                .strip_prefix(['"', '`', '['])
+                .and_then(|part| part.strip_suffix(['"', '`', ']']))
+            {
+                Some(part) => part.to_string(),
+                None => part.to_lowercase(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// tokens of a query, without the whitespaces
+fn parts(query: &str, dump_type: DumpType) -> Option<Vec<Part>> {
+    let quoted = |value: String, quote_style: Option<char>| match quote_style {
+        Some('[') => format!("[{}]", value),
+        Some(quote) => format!("{}{}{}", quote, value, quote),
+        None => value,
+    };
+
+    let parts = match dump_type {
+        DumpType::Mysql | DumpType::MariaDB => {
+            use dump_parser::mysql::{Token, Tokenizer};
+
+            // the tokenizer reads the identifiers quoted with backticks as strings
+            let mut quotes = mysql_quotes(query).into_iter();
+
+            Tokenizer::new(query)
+                .tokenize()
+                .ok()?
+                .into_iter()
+                .filter_map(|token| match token {
+                    Token::Word(word) => Some(Part::Word(quoted(word.value, word.quote_style))),
+                    Token::Number(number, _) => Some(Part::Number(number)),
+                    Token::SingleQuotedString(value) if quotes.next() == Some('`') => {
+                        Some(Part::Word(quoted(value, Some('`'))))
+                    }
+                    Token::SingleQuotedString(value) => Some(Part::String(value)),
+                    Token::NationalStringLiteral(value) => {
+                        let _ = quotes.next();
+                        Some(Part::String(value))
+                    }
+                    Token::LParen => Some(Part::LParen),
+                    Token::RParen => Some(Part::RParen),
+                    Token::Comma => Some(Part::Comma),
+                    Token::Period => Some(Part::Period),
+                    Token::LBracket => Some(Part::LBracket),
+                    Token::Whitespace(_) | Token::SemiColon | Token::EOF => None,
+                    _ => Some(Part::Other),
+                })
+                .collect()
+        }
+        _ => {
+            use dump_parser::postgres::{Token, Tokenizer};
+
+            Tokenizer::new(query)
+                .tokenize()
+                .ok()?
+                .into_iter()
+                .filter_map(|token| match token {
+                    Token::Word(word) => Some(Part::Word(quoted(word.value, word.quote_style))),
+                    Token::Number(number, _) => Some(Part::Number(number)),
+                    Token::SingleQuotedString(value)
+                    | Token::NationalStringLiteral(value)
+                    | Token::EscapedStringLiteral(value) => Some(Part::String(value)),
+                    Token::LParen => Some(Part::LParen),
+                    Token::RParen => Some(Part::RParen),
+                    Token::Comma => Some(Part::Comma),
+                    Token::Period => Some(Part::Period),
+                    Token::LBracket => Some(Part::LBracket),
+                    Token::Whitespace(_) | Token::SemiColon | Token::EOF => None,
+                    _ => Some(Part::Other),
+                })
+                .collect()
+        }
+    };
+
+    Some(parts)
+}
+
+/// opening quote of each string and quoted identifier of a MySQL query, in the order of the query
+fn mysql_quotes(query: &str) -> Vec<char> {
+    let mut quotes = vec![];
+    let mut chars = query.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\'' && ch != '`' {
+            continue;
+        }
+
+        quotes.push(ch);
+        while let Some(next) = chars.next() {
+            match next {
+                '\\' if ch == '\'' => {
+                    let _ = chars.next();
+                }
+                next if next == ch => {
+                    // a doubled quote is escaped
+                    if chars.peek() == Some(&ch) {
+                        let _ = chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    quotes
+}
+
+fn skip_keywords(parts: &[Part], mut pos: usize, keywords: &[&str]) -> usize {
+    while matches!(parts.get(pos), Some(part) if keywords.iter().any(|keyword| part.is_keyword(keyword)))
+    {
+        pos += 1;
+    }
+
+    pos
+}
+
+/// position of the keywords following each other
+fn position_of(parts: &[Part], keywords: &[&str]) -> Option<usize> {
+    parts.windows(keywords.len()).position(|window| {
+        window
+            .iter()
+            .zip(keywords)
+            .all(|(part, keyword)| part.is_keyword(keyword))
+    })
+}
+
+/// a (schema qualified) identifier, with the position following it
+fn identifier(parts: &[Part], mut pos: usize) -> Option<(String, usize)> {
+    let mut identifier = String::new();
+
+    loop {
+        match parts.get(pos)? {
+            Part::Word(word) => identifier.push_str(word),
+            _ => return None,
+        }
+
+        match parts.get(pos + 1) {
+            Some(Part::Period) => {
+                identifier.push('.');
+                pos += 2;
+            }
+            _ => return Some((identifier, pos + 1)),
+        }
+    }
+}
+
+/// the parts between the parenthesis at the position and the matching one, with the position following it
+fn group(parts: &[Part], pos: usize) -> Option<(&[Part], usize)> {
+    if parts.get(pos) != Some(&Part::LParen) {
+        return None;
+    }
+
+    let mut depth = 0;
+    for (idx, part) in parts.iter().enumerate().skip(pos) {
+        match part {
+            Part::LParen => depth += 1,
+            Part::RParen => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((&parts[pos + 1..idx], idx + 1));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// the items of a list separated by commas, outside of the parentheses
+fn split_list(parts: &[Part]) -> Vec<&[Part]> {
+    let mut items = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (idx, part) in parts.iter().enumerate() {
+        match part {
+            Part::LParen => depth += 1,
+            Part::RParen => depth -= 1,
+            Part::Comma if depth == 0 => {
+                items.push(&parts[start..idx]);
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+
+    if start < parts.len() {
+        items.push(&parts[start..]);
+    }
+
+    items
+}
+
+/// names of the columns of a list, e.g. `(id, email)`. The MySQL prefix lengths are skipped, e.g. `(name(10))`
+fn column_names(parts: &[Part]) -> Vec<String> {
+    split_list(parts)
+        .into_iter()
+        .filter_map(|column| match column.first() {
+            Some(Part::Word(name)) => Some(name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// the type of a column starting at the position, with the position following it
+fn column_type(parts: &[Part], mut pos: usize) -> (ColumnType, usize) {
+    let mut column_type = ColumnType::default();
+
+    while let Some(part) = parts.get(pos) {
+        match part {
+            Part::Word(_)
+                if COLUMN_CONSTRAINT_KEYWORDS
+                    .iter()
+                    .any(|keyword| part.is_keyword(keyword)) =>
+            {
+                break
+            }
+            // MySQL: CHARACTER SET utf8mb4
+            Part::Word(_)
+                if part.is_keyword("CHARACTER")
+                    && matches!(parts.get(pos + 1), Some(next) if next.is_keyword("SET")) =>
+            {
+                break
+            }
+            Part::Word(word) if word.eq_ignore_ascii_case("ARRAY") => column_type.array = true,
+            // MySQL: int unsigned
+            Part::Word(word)
+                if word.eq_ignore_ascii_case("UNSIGNED")
+                    || word.eq_ignore_ascii_case("ZEROFILL") => {}
+            Part::Word(word) => {
+                if !column_type.name.is_empty() && !column_type.name.ends_with('.') {
+                    column_type.name.push(' ');
+                }
+
+                match word.starts_with(['"', '`', '[']) {
+                    true => column_type.name.push_str(word),
+                    false => column_type.name.push_str(word.to_lowercase().as_str()),
+                }
+            }
+            Part::Period => column_type.name.push('.'),
+            Part::LBracket => column_type.array = true,
+            Part::LParen => {
+                if let Some((args, next_pos)) = group(parts, pos) {
+                    column_type.args = args
+                        .iter()
+                        .filter_map(|arg| match arg {
+                            Part::Number(value) | Part::String(value) => Some(value.clone()),
+                            _ => None,
+                        })
+                        .collect();
+                    pos = next_pos;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+
+        pos += 1;
+    }
+
+    (column_type, pos)
+}
+
+/// `<name> <type> [<constraints>]`
+fn add_column(table: &mut Table, parts: &[Part]) {
+    let name = match parts.first() {
+        Some(Part::Word(name)) => name.clone(),
+        _ => return,
+    };
+
+    let (column_type, mut pos) = column_type(parts, 1);
+
+    let mut column = TableColumn {
+        name: name.clone(),
+        not_null: matches!(
+            column_type.name.as_str(),
+            "serial" | "bigserial" | "smallserial" | "serial2" | "serial4" | "serial8"
+        ),
+        column_type,
+        values: ColumnValues::Fake,
+    };
+
+    while let Some(part) = parts.get(pos) {
+        if part.is_keyword("NOT")
+            && matches!(parts.get(pos + 1), Some(next) if next.is_keyword("NULL"))
+        {
+            column.not_null = true;
+        } else if part.is_keyword("PRIMARY") {
+            column.not_null = true;
+            table.unique_keys.insert(0, vec![name.clone()]);
+        } else if part.is_keyword("UNIQUE") {
+            table.unique_keys.push(vec![name.clone()]);
+        } else if part.is_keyword("REFERENCES") {
+            if let Some((referenced_table, next_pos)) = identifier(parts, pos + 1) {
+                let referenced_columns = match group(parts, next_pos) {
+                    Some((columns, _)) => column_names(columns),
+                    None => vec![],
+                };
+
+                table.foreign_keys.push(ForeignKey {
+                    columns: vec![name.clone()],
+                    table: referenced_table,
+                    referenced_columns,
+                });
+            }
+        } else if part.is_keyword("IDENTITY")
+            && position_of(&parts[..pos], &["GENERATED", "ALWAYS"]).is_some()
+        {
+            column.values = ColumnValues::Identity;
+        } else if part.is_keyword("AS") && parts.get(pos + 1) == Some(&Part::LParen) {
+            // GENERATED ALWAYS AS (<expression>) [STORED | VIRTUAL]
+            column.values = ColumnValues::Computed;
+        }
+
+        // the expressions between parentheses, e.g. of DEFAULT or CHECK, are skipped
+        pos = match group(parts, pos) {
+            Some((_, next_pos)) => next_pos,
+            None => pos + 1,
+        };
+    }
+
+    table.columns.push(column);
+}
+
+/// `[CONSTRAINT <name>] PRIMARY KEY (<columns>)`, `UNIQUE [KEY | INDEX] [<name>] (<columns>)` or
+/// `FOREIGN KEY [<name>] (<columns>) REFERENCES <table> [(<columns>)]`, the other constraints are skipped
+fn add_table_constraint(table: &mut Table, parts: &[Part]) {
+    let parts = match parts {
+        [constraint, _, parts @ ..] if constraint.is_keyword("CONSTRAINT") => parts,
+        parts => parts,
+    };
+
+    let columns = |parts: &[Part]| {
+        parts
+            .iter()
+            .position(|part| *part == Part::LParen)
+            .and_then(|pos| group(parts, pos))
+            .map(|(columns, next_pos)| (column_names(columns), next_pos))
+    };
+
+    match parts.first() {
+        Some(part) if part.is_keyword("PRIMARY") => {
+            if let Some((columns, _)) = columns(parts) {
+                table.set_not_null(&columns);
+                table.unique_keys.insert(0, columns);
+            }
+        }
+        Some(part) if part.is_keyword("UNIQUE") => {
+            if let Some((columns, _)) = columns(parts) {
+                table.unique_keys.push(columns);
+            }
+        }
+        Some(part) if part.is_keyword("FOREIGN") => {
+            let (columns, pos) = match columns(parts) {
+                Some(columns) => columns,
+                None => return,
+            };
+
+            if !matches!(parts.get(pos), Some(part) if part.is_keyword("REFERENCES")) {
+                return;
+            }
+
+            if let Some((referenced_table, next_pos)) = identifier(parts, pos + 1) {
+                let referenced_columns = match group(parts, next_pos) {
+                    Some((columns, _)) => column_names(columns),
+                    None => vec![],
+                };
+
+                table.foreign_keys.push(ForeignKey {
+                    columns,
+                    table: referenced_table,
+                    referenced_columns,
+                });
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::seed::{ColumnValues, ForeignKey, Schema};
+    use crate::source::dump_file::DumpType;
+
+    const POSTGRES_SCHEMA: [&str; 9] = [
+        "CREATE TYPE public.status AS ENUM ('active', 'banned');",
+        "CREATE TABLE public.countries (code character(2) NOT NULL, name text NOT NULL);",
+        "CREATE TABLE public.users (
+    id integer NOT NULL,
+    email character varying(255) NOT NULL,
+    first_name text,
+    status public.status DEFAULT 'active'::public.status NOT NULL,
+    country_code character(2) REFERENCES public.countries(code),
+    created_at timestamp without time zone DEFAULT now(),
+    search tsvector
+);",
+        "CREATE TABLE public.orders (
+    id bigint NOT NULL,
+    user_id integer NOT NULL,
+    total numeric(10,2),
+    tags text[],
+    total_with_tax numeric GENERATED ALWAYS AS ((total * 1.2)) STORED
+);",
+        "ALTER TABLE public.orders ALTER COLUMN id ADD GENERATED ALWAYS AS IDENTITY (SEQUENCE NAME public.orders_id_seq START WITH 1);",
+        "ALTER TABLE ONLY public.countries ADD CONSTRAINT countries_pkey PRIMARY KEY (code);",
+        "ALTER TABLE ONLY public.users ADD CONSTRAINT users_pkey PRIMARY KEY (id);",
+        "CREATE UNIQUE INDEX users_email_idx ON public.users USING btree (email);",
+        "ALTER TABLE ONLY public.orders ADD CONSTRAINT orders_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id);",
+    ];
+
+    fn postgres_schema() -> Schema {
+        let queries = POSTGRES_SCHEMA
+            .iter()
+            .map(|query| query.to_string())
+            .collect::<Vec<_>>();
+
+        Schema::parse(&queries, DumpType::Postgres).unwrap()
+    }
+
+    fn generate(schema: &Schema, rows: usize, seed: u64) -> String {
+        let mut dump = vec![];
+        let _ = schema.write_rows(rows, Some(seed), &mut dump).unwrap();
+        String::from_utf8(dump).unwrap()
+    }
+
+    /// values of the INSERT INTO queries of a table
+    fn rows<'a>(dump: &'a str, table: &str) -> Vec<Vec<&'a str>> {
+        let prefix = format!("INSERT INTO {} (", table);
+        dump.lines()
+            .filter(|line| line.starts_with(prefix.as_str()))
+            .map(|line| {
+                let values = line.split_once(") VALUES (").unwrap().1;
+                values.trim_end_matches(");").split(", ").collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parse_postgres_schema() {
+        let schema = postgres_schema();
+        assert_eq!(schema.tables.len(), 3);
+
+        let users = &schema.tables[1];
+        assert_eq!(users.name, "public.users");
+        assert_eq!(
+            users
+                .columns
+                .iter()
+                .map(|column| (column.name.as_str(), column.column_type.name.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("id", "integer"),
+                ("email", "character varying"),
+                ("first_name", "text"),
+                ("status", "public.status"),
+                ("country_code", "character"),
+                ("created_at", "timestamp without time zone"),
+                ("search", "tsvector"),
+            ]
+        );
+        assert_eq!(users.columns[1].column_type.args, vec!["255"]);
+        assert!(users.columns[1].not_null);
+        assert!(!users.columns[2].not_null);
+        assert_eq!(
+            users.unique_keys,
+            vec![vec!["id".to_string()], vec!["email".to_string()]]
+        );
+        assert_eq!(
+            users.foreign_keys,
+            vec![ForeignKey {
+                columns: vec!["country_code".to_string()],
+                table: "public.countries".to_string(),
+                referenced_columns: vec!["code".to_string()],
+            }]
+        );
+
+        let orders = &schema.tables[2];
+        assert_eq!(orders.columns[0].values, ColumnValues::Identity);
+        assert!(orders.columns[3].column_type.array);
+        assert_eq!(orders.columns[4].values, ColumnValues::Computed);
+        assert_eq!(orders.foreign_keys[0].table, "public.users");
+    }
+
+    #[test]
+    fn generate_postgres_rows() {
+        let schema = postgres_schema();
+        let dump = generate(&schema, 20, 42);
+
+        // the same seed generates the same rows
+        assert_eq!(dump, generate(&schema, 20, 42));
+
+        // the referenced tables come first
+        let countries_idx = dump.find("INSERT INTO public.countries").unwrap();
+        let users_idx = dump.find("INSERT INTO public.users").unwrap();
+        let orders_idx = dump.find("INSERT INTO public.orders").unwrap();
+        assert!(countries_idx < users_idx && users_idx < orders_idx);
+
+        let countries = rows(dump.as_str(), "public.countries");
+        let users = rows(dump.as_str(), "public.users");
+        let orders = rows(dump.as_str(), "public.orders");
+        assert_eq!((countries.len(), users.len(), orders.len()), (20, 20, 20));
+
+        // the column of an unknown type, the identity and the computed columns are left to the database
+        assert!(dump.contains(
+            "INSERT INTO public.users (id, email, first_name, status, country_code, created_at) VALUES ("
+        ));
+        assert!(dump.contains("INSERT INTO public.orders (user_id, total, tags) VALUES ("));
+
+        // unique keys
+        let codes = countries.iter().map(|row| row[0]).collect::<Vec<_>>();
+        assert!(codes.iter().all(|code| code.chars().count() <= 4));
+        assert_eq!(
+            codes.iter().collect::<std::collections::HashSet<_>>().len(),
+            20
+        );
+        let emails = users.iter().map(|row| row[1]).collect::<Vec<_>>();
+        assert!(emails.iter().all(|email| email.contains('@')));
+        assert_eq!(
+            emails
+                .iter()
+                .collect::<std::collections::HashSet<_>>()
+                .len(),
+            20
+        );
+
+        // foreign keys
+        let user_ids = users.iter().map(|row| row[0]).collect::<Vec<_>>();
+        assert!(orders.iter().all(|row| user_ids.contains(&row[0])));
+        assert!(users
+            .iter()
+            .all(|row| row[4] == "NULL" || codes.contains(&row[4])));
+
+        // NOT NULL columns and types
+        assert!(users.iter().all(|row| row[1] != "NULL" && row[3] != "NULL"));
+        assert!(users
+            .iter()
+            .all(|row| row[3] == "'active'" || row[3] == "'banned'"));
+        assert!(orders
+            .iter()
+            .all(|row| row[2] == "'{}'" || row[2] == "NULL"));
+    }
+
+    #[test]
+    fn generate_mysql_rows() {
+        let queries = vec![
+            "CREATE TABLE `users` (
+  `id` int unsigned NOT NULL AUTO_INCREMENT,
+  `username` varchar(16) CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci NOT NULL,
+  `role` enum('admin','user') NOT NULL DEFAULT 'user',
+  `active` tinyint(1) NOT NULL DEFAULT '1',
+  `manager_id` int unsigned DEFAULT NULL,
+  PRIMARY KEY (`id`),
+  UNIQUE KEY `username` (`username`),
+  CONSTRAINT `users_ibfk_1` FOREIGN KEY (`manager_id`) REFERENCES `users` (`id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;"
+                .to_string(),
+            "CREATE TABLE `profiles` (
+  `user_id` int unsigned NOT NULL,
+  `bio` text,
+  PRIMARY KEY (`user_id`),
+  CONSTRAINT `profiles_ibfk_1` FOREIGN KEY (`user_id`) REFERENCES `users` (`id`)
+) ENGINE=InnoDB;"
+                .to_string(),
+        ];
+
+        let schema = Schema::parse(&queries, DumpType::Mysql).unwrap();
+        let users = &schema.tables[0];
+        assert_eq!(users.columns[1].column_type.name, "varchar");
+        assert_eq!(users.columns[2].column_type.args, vec!["admin", "user"]);
+        assert_eq!(users.foreign_keys[0].table, "`users`");
+
+        let dump = generate(&schema, 10, 7);
+        let users = rows(dump.as_str(), "`users`");
+        let profiles = rows(dump.as_str(), "`profiles`");
+        assert_eq!(users.len(), 10);
+
+        // a profile per user at most
+        assert_eq!(profiles.len(), 10);
+        let user_ids = users.iter().map(|row| row[0]).collect::<Vec<_>>();
+        assert_eq!(
+            profiles.iter().map(|row| row[0]).collect::<Vec<_>>(),
+            user_ids
+        );
+
+        assert!(users.iter().all(|row| row[1].chars().count() <= 18));
+        assert!(users.iter().all(|row| row[3] == "0" || row[3] == "1"));
+        // the manager is a previous user, or none for the first one
+        assert_eq!(users[0][4], "NULL");
+        assert!(users[1..]
+            .iter()
+            .all(|row| row[4] == "NULL" || user_ids.contains(&row[4])));
+    }
+
+    #[test]
+    fn unsupported_schemas() {
+        assert!(Schema::parse(&[], DumpType::MongoDB).is_err());
+        assert!(Schema::parse(&[], DumpType::SQLite).is_err());
+    }
+}
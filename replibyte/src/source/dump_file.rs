@@ -0,0 +1,184 @@
+use std::fs::File;
+use std::io::{stdin, BufRead, BufReader, Error, Read};
+use std::path::{Path, PathBuf};
+
+use flate2::read::MultiGzDecoder;
+
+use crate::connector::Connector;
+use crate::errors::ReplibyteError;
+use crate::source::{
+    mariadb_stdin, mongodb_stdin, mssql_stdin, mysql_stdin, postgres_stdin, sqlite_stdin,
+};
+use crate::types::{OriginalQuery, Query};
+use crate::Source;
+use crate::SourceOptions;
+
+pub const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// type of the database a dump is made from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DumpType {
+    Postgres,
+    Mysql,
+    MariaDB,
+    MongoDB,
+    MSSQL,
+    SQLite,
+}
+
+impl DumpType {
+    /// `postgresql` (or `postgres`), `mysql`, `mariadb`, `mongodb`, `mssql` or `sqlite`
+    pub fn new(database_type: &str) -> Result<Self, ReplibyteError> {
+        match database_type {
+            "postgres" | "postgresql" => Ok(DumpType::Postgres),
+            "mysql" => Ok(DumpType::Mysql),
+            "mariadb" => Ok(DumpType::MariaDB),
+            "mongodb" => Ok(DumpType::MongoDB),
+            "mssql" => Ok(DumpType::MSSQL),
+            "sqlite" => Ok(DumpType::SQLite),
+            _ => Err(ReplibyteError::Config(format!(
+                "source type '{}' not recognized",
+                database_type
+            ))),
+        }
+    }
+
+    /// the type recorded in the index file, e.g. `postgresql`
+    pub fn database_type(&self) -> &'static str {
+        match self {
+            DumpType::Postgres => "postgresql",
+            DumpType::Mysql => "mysql",
+            DumpType::MariaDB => "mariadb",
+            DumpType::MongoDB => "mongodb",
+            DumpType::MSSQL => "mssql",
+            DumpType::SQLite => "sqlite",
+        }
+    }
+}
+
+/// Source dump file, e.g. a backup made with `pg_dump` or `mysqldump`, or STDIN for `-`.
+/// The dumps compressed with gzip or zstd are decompressed.
+pub struct DumpFile {
+    path: PathBuf,
+    dump_type: DumpType,
+}
+
+impl DumpFile {
+    pub fn new<P: Into<PathBuf>>(path: P, dump_type: DumpType) -> Self {
+        DumpFile {
+            path: path.into(),
+            dump_type,
+        }
+    }
+}
+
+impl Connector for DumpFile {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        if self.path.as_os_str() != "-" && !self.path.is_file() {
+            return Err(ReplibyteError::Config(format!(
+                "the dump file '{}' does not exist",
+                self.path.display()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl Source for DumpFile {
+    fn read<F: FnMut(OriginalQuery, Query)>(
+        &self,
+        options: SourceOptions,
+        query_callback: F,
+    ) -> Result<(), ReplibyteError> {
+        let reader = open_dump_file(&self.path)?;
+
+        match self.dump_type {
+            DumpType::Postgres => postgres_stdin::read_dump(reader, options, query_callback),
+            DumpType::Mysql => mysql_stdin::read_dump(reader, options, query_callback),
+            DumpType::MariaDB => mariadb_stdin::read_dump(reader, options, query_callback),
+            DumpType::MongoDB => mongodb_stdin::read_dump(reader, options, query_callback),
+            DumpType::MSSQL => mssql_stdin::read_dump(reader, options, query_callback),
+            DumpType::SQLite => sqlite_stdin::read_dump(reader, options, query_callback),
+        }
+    }
+}
+
+fn open_dump_file(path: &Path) -> Result<Box<dyn Read>, ReplibyteError> {
+    if path.as_os_str() == "-" {
+        return decompressed_reader(stdin());
+    }
+
+    let file = File::open(path).map_err(|err| {
+        Error::new(
+            err.kind(),
+            format!("cannot open the dump file '{}': {}", path.display(), err),
+        )
+    })?;
+
+    decompressed_reader(file)
+}
+
+/// the reader of the dump, decompressed if its first bytes are the magic number of gzip or zstd
+fn decompressed_reader<R: Read + 'static>(reader: R) -> Result<Box<dyn Read>, ReplibyteError> {
+    let mut reader = BufReader::new(reader);
+    let magic = reader.fill_buf()?;
+
+    if magic.starts_with(&GZIP_MAGIC) {
+        // `pigz` and the concatenated files write several gzip members
+        return Ok(Box::new(MultiGzDecoder::new(reader)));
+    }
+
+    if magic.starts_with(&ZSTD_MAGIC) {
+        return Ok(Box::new(zstd::stream::read::Decoder::with_buffer(reader)?));
+    }
+
+    Ok(Box::new(reader))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use flate2::write::GzEncoder;
+
+    use crate::source::dump_file::{decompressed_reader, DumpType};
+
+    const DUMP: &str = "INSERT INTO public.users (id) VALUES (1);\n";
+
+    fn read(data: Vec<u8>) -> String {
+        let mut dump = String::new();
+        let _ = decompressed_reader(std::io::Cursor::new(data))
+            .unwrap()
+            .read_to_string(&mut dump)
+            .unwrap();
+
+        dump
+    }
+
+    #[test]
+    fn compressed_dumps() {
+        assert_eq!(read(DUMP.as_bytes().to_vec()), DUMP);
+
+        let mut encoder = GzEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(DUMP.as_bytes()).unwrap();
+        assert_eq!(read(encoder.finish().unwrap()), DUMP);
+
+        let zstd = zstd::stream::encode_all(DUMP.as_bytes(), 0).unwrap();
+        assert_eq!(read(zstd), DUMP);
+
+        assert_eq!(read(vec![]), "");
+    }
+
+    #[test]
+    fn dump_types() {
+        assert_eq!(DumpType::new("postgres").unwrap(), DumpType::Postgres);
+        assert_eq!(
+            DumpType::new("postgresql").unwrap().database_type(),
+            "postgresql"
+        );
+        assert_eq!(DumpType::new("mssql").unwrap(), DumpType::MSSQL);
+        assert!(DumpType::new("oracle").is_err());
+    }
+}
@@ -0,0 +1,552 @@
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read};
+
+use crate::errors::ReplibyteError;
+
+use encoding_rs::{Decoder, DecoderResult, Encoding};
+
+/// Dialect of the dump, the same encoding names are different encodings in PostgreSQL and MySQL (e.g. `latin1`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dialect {
+    Postgres,
+    Mysql,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Charset {
+    /// UTF-8, or bytes the database does not interpret (e.g. `SQL_ASCII`): the dump is read as it is
+    Utf8,
+    /// ISO-8859-1 - `LATIN1` in PostgreSQL, the `latin1` of MySQL is windows-1252
+    Latin1,
+    Encoding(&'static Encoding),
+}
+
+impl Charset {
+    /// the charset of an encoding name, as named by the database (e.g. `WIN1251`, `cp1251`) or by its label (e.g. `windows-1251`)
+    fn for_name(name: &str, dialect: Dialect) -> Option<Charset> {
+        let normalized_name = name.to_lowercase().replace(['-', '_'], "");
+
+        let label = match normalized_name.as_str() {
+            "utf8" | "utf8mb3" | "utf8mb4" | "unicode" | "sqlascii" | "ascii" | "binary" => {
+                return Some(Charset::Utf8)
+            }
+            "latin1" | "iso88591" if dialect == Dialect::Postgres => return Some(Charset::Latin1),
+            "latin1" => "windows-1252",
+            "latin2" => "iso-8859-2",
+            "latin3" => "iso-8859-3",
+            "latin4" => "iso-8859-4",
+            "latin5" => "iso-8859-9",
+            "latin6" => "iso-8859-10",
+            "latin7" => "iso-8859-13",
+            "latin8" => "iso-8859-14",
+            "latin9" => "iso-8859-15",
+            "latin10" => "iso-8859-16",
+            "greek" => "iso-8859-7",
+            "hebrew" => "iso-8859-8",
+            "win866" | "cp866" | "alt" => "ibm866",
+            "win874" | "tis620" => "windows-874",
+            "win1250" | "cp1250" => "windows-1250",
+            "win1251" | "cp1251" | "win" => "windows-1251",
+            "win1252" | "cp1252" => "windows-1252",
+            "win1253" => "windows-1253",
+            "win1254" => "windows-1254",
+            "win1255" => "windows-1255",
+            "win1256" | "cp1256" => "windows-1256",
+            "win1257" | "cp1257" => "windows-1257",
+            "win1258" => "windows-1258",
+            "koi8" | "koi8r" => "koi8-r",
+            "koi8u" => "koi8-u",
+            "sjis" | "cp932" | "shiftjis" => "shift_jis",
+            "eucjp" | "ujis" | "eucjpms" | "eucjis2004" => "euc-jp",
+            "euckr" | "uhc" => "euc-kr",
+            "euccn" | "gb2312" | "gbk" => "gbk",
+            "big5" => "big5",
+            "gb18030" => "gb18030",
+            _ => name,
+        };
+
+        match Encoding::for_label(label.as_bytes()) {
+            // the dump is read line by line, the end of a line must be a `\n` byte (it is not in UTF-16)
+            Some(encoding) if !encoding.is_ascii_compatible() => None,
+            Some(encoding) if encoding == encoding_rs::UTF_8 => Some(Charset::Utf8),
+            Some(encoding) => Some(Charset::Encoding(encoding)),
+            None => None,
+        }
+    }
+}
+
+/// Read a dump from a database in a legacy encoding (e.g. latin1, cp1251) as UTF-8, for the transformers.
+///
+/// The encoding is the one configured, otherwise the one declared by the dump
+/// (`SET client_encoding = '...';` for PostgreSQL, `SET NAMES ...` for MySQL).
+/// The declarations are replaced by UTF-8 once the dump is transcoded, so the dump is restored as UTF-8
+/// and the database converts it back to its own encoding.
+pub struct Utf8Reader<R> {
+    reader: BufReader<R>,
+    dialect: Dialect,
+    /// true if the encoding is configured, the declarations of the dump are ignored
+    is_configured: bool,
+    charset: Charset,
+    decoder: Option<Decoder>,
+    line: Vec<u8>,
+    decoded_line: Vec<u8>,
+    position: usize,
+    is_eof: bool,
+}
+
+impl<R: Read> Utf8Reader<R> {
+    pub fn new(
+        reader: R,
+        encoding: Option<&str>,
+        dialect: Dialect,
+    ) -> Result<Self, ReplibyteError> {
+        let charset = match encoding {
+            Some(name) => Charset::for_name(name, dialect).ok_or_else(|| {
+                ReplibyteError::Config(format!("unsupported <source.encoding> '{}'", name))
+            })?,
+            None => Charset::Utf8,
+        };
+
+        let mut utf8_reader = Utf8Reader {
+            reader: BufReader::new(reader),
+            dialect,
+            is_configured: encoding.is_some(),
+            charset: Charset::Utf8,
+            decoder: None,
+            line: Vec::new(),
+            decoded_line: Vec::new(),
+            position: 0,
+            is_eof: false,
+        };
+        utf8_reader.set_charset(charset);
+
+        Ok(utf8_reader)
+    }
+
+    fn set_charset(&mut self, charset: Charset) {
+        self.charset = charset;
+        self.decoder = match charset {
+            Charset::Encoding(encoding) => Some(encoding.new_decoder_without_bom_handling()),
+            _ => None,
+        };
+    }
+
+    /// read the next line of the dump, decoded in UTF-8
+    fn read_line(&mut self) -> Result<(), Error> {
+        self.line.clear();
+        self.decoded_line.clear();
+        self.position = 0;
+
+        let total_bytes = self.reader.read_until(b'\n', &mut self.line)?;
+        self.is_eof = total_bytes == 0;
+
+        if self.charset == Charset::Utf8 {
+            self.decoded_line.extend_from_slice(self.line.as_slice());
+            self.replace_encoding_declaration();
+            return Ok(());
+        }
+
+        // the binary literals are bytes, not text in the encoding of the dump
+        let line = std::mem::take(&mut self.line);
+        let binary_literals = match self.dialect {
+            Dialect::Mysql => mysql_binary_literals(line.as_slice()),
+            // the bytea values of PostgreSQL are escaped in ASCII
+            Dialect::Postgres => vec![],
+        };
+
+        let mut decoded_line = String::new();
+        let mut start = 0;
+
+        for literal in binary_literals {
+            self.decode(&line[start..literal.start], false, &mut decoded_line)?;
+            write_binary_literal(&line, &literal, &mut decoded_line);
+            start = literal.end;
+        }
+
+        self.decode(&line[start..], self.is_eof, &mut decoded_line)?;
+        self.line = line;
+
+        self.decoded_line.extend_from_slice(decoded_line.as_bytes());
+        self.replace_encoding_declaration();
+
+        Ok(())
+    }
+
+    /// decode the text of a line in UTF-8, `last` is true at the end of the dump
+    fn decode(&mut self, bytes: &[u8], last: bool, decoded: &mut String) -> Result<(), Error> {
+        match self.charset {
+            // the UTF-8 lines are read as they are
+            Charset::Utf8 => {}
+            Charset::Latin1 => {
+                // the bytes of ISO-8859-1 are the first 256 unicode code points
+                decoded.extend(bytes.iter().map(|byte| *byte as char));
+            }
+            Charset::Encoding(encoding) => {
+                // unwrap is safe: the decoder is set with the encoding
+                let decoder = self.decoder.as_mut().unwrap();
+                decoded.reserve(
+                    decoder
+                        .max_utf8_buffer_length_without_replacement(bytes.len())
+                        .unwrap_or(bytes.len() * 3),
+                );
+
+                let (result, _) =
+                    decoder.decode_to_string_without_replacement(bytes, decoded, last);
+
+                if let DecoderResult::Malformed(_, _) = result {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("the dump is not encoded in {}", encoding.name()),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// use the encoding declared by the dump, and declare UTF-8 instead once the dump is transcoded
+    fn replace_encoding_declaration(&mut self) {
+        let line = match std::str::from_utf8(self.decoded_line.as_slice()) {
+            Ok(line) => line,
+            Err(_) => return,
+        };
+
+        let (declaration, utf8_name) = match self.dialect {
+            Dialect::Postgres => (postgres_encoding_declaration(line), "UTF8"),
+            Dialect::Mysql => (mysql_encoding_declaration(line), "utf8mb4"),
+        };
+
+        let (start, end) = match declaration {
+            Some(declaration) => declaration,
+            None => return,
+        };
+
+        if !self.is_configured {
+            if let Some(charset) = Charset::for_name(&line[start..end], self.dialect) {
+                if charset != self.charset {
+                    self.set_charset(charset);
+                }
+            }
+        }
+
+        if self.charset != Charset::Utf8 {
+            let _ = self.decoded_line.splice(start..end, utf8_name.bytes());
+        }
+    }
+}
+
+impl<R: Read> Read for Utf8Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        while self.position >= self.decoded_line.len() {
+            if self.is_eof {
+                return Ok(0);
+            }
+
+            self.read_line()?;
+        }
+
+        let total_bytes = buf.len().min(self.decoded_line.len() - self.position);
+        buf[..total_bytes]
+            .copy_from_slice(&self.decoded_line[self.position..self.position + total_bytes]);
+        self.position += total_bytes;
+
+        Ok(total_bytes)
+    }
+}
+
+/// a binary literal of a line, `_binary '...'` or `X'...'`
+struct BinaryLiteral {
+    start: usize,
+    end: usize,
+    /// position of the opening quote
+    quote: usize,
+}
+
+/// the binary literals of a MySQL dump line, the strings and the quoted identifiers are skipped
+fn mysql_binary_literals(line: &[u8]) -> Vec<BinaryLiteral> {
+    let mut literals = vec![];
+    let mut idx = 0;
+
+    while idx < line.len() {
+        match line[idx] {
+            b'\'' | b'"' | b'`' => idx = end_of_quoted(line, idx).unwrap_or(line.len()),
+            // a comment until the end of the line
+            b'-' if line[idx..].starts_with(b"-- ") => break,
+            b'_' | b'x' | b'X' if idx == 0 || !is_identifier_byte(line[idx - 1]) => {
+                let (prefix_len, is_hex) = match line[idx] {
+                    b'_' if line.len() >= idx + 7
+                        && line[idx..idx + 7].eq_ignore_ascii_case(b"_binary") =>
+                    {
+                        (7, false)
+                    }
+                    b'x' | b'X' => (1, true),
+                    _ => (0, false),
+                };
+
+                let mut quote = idx + prefix_len;
+                if !is_hex {
+                    while quote < line.len() && line[quote] == b' ' {
+                        quote += 1;
+                    }
+                }
+
+                if prefix_len == 0 || line.get(quote) != Some(&b'\'') {
+                    idx += 1;
+                    while idx < line.len() && is_identifier_byte(line[idx]) {
+                        idx += 1;
+                    }
+                    continue;
+                }
+
+                let end = match end_of_quoted(line, quote) {
+                    Some(end) => end,
+                    None => break,
+                };
+
+                if is_hex && !line[quote + 1..end - 1].iter().all(u8::is_ascii_hexdigit) {
+                    idx = end;
+                    continue;
+                }
+
+                literals.push(BinaryLiteral {
+                    start: idx,
+                    end,
+                    quote,
+                });
+                idx = end;
+            }
+            _ => idx += 1,
+        }
+    }
+
+    literals
+}
+
+fn is_identifier_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte >= 0x80
+}
+
+/// position after the closing quote of a string or of a quoted identifier starting at `start`,
+/// none if it is not closed
+fn end_of_quoted(line: &[u8], start: usize) -> Option<usize> {
+    let quote = line[start];
+    let mut idx = start + 1;
+
+    while idx < line.len() {
+        match line[idx] {
+            b'\\' if quote != b'`' => idx += 2,
+            // a doubled quote is a quote of the string
+            byte if byte == quote && line.get(idx + 1) == Some(&quote) => idx += 2,
+            byte if byte == quote => return Some(idx + 1),
+            _ => idx += 1,
+        }
+    }
+
+    None
+}
+
+/// write a binary literal as it is if it is valid UTF-8, as an hexadecimal literal of the same bytes otherwise
+fn write_binary_literal(line: &[u8], literal: &BinaryLiteral, decoded: &mut String) {
+    let bytes = &line[literal.start..literal.end];
+
+    // the hexadecimal digits are ASCII
+    match std::str::from_utf8(bytes) {
+        Ok(bytes) => decoded.push_str(bytes),
+        Err(_) => {
+            let value = mysql_unescape(&line[literal.quote + 1..literal.end - 1]);
+            decoded.push_str("_binary 0x");
+            for byte in value {
+                decoded.push_str(format!("{:02x}", byte).as_str());
+            }
+        }
+    }
+}
+
+/// the bytes of a MySQL string literal, without its escape sequences
+fn mysql_unescape(bytes: &[u8]) -> Vec<u8> {
+    let mut value = Vec::with_capacity(bytes.len());
+    let mut bytes = bytes.iter();
+
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'\\' => match bytes.next() {
+                Some(b'0') => value.push(0),
+                Some(b'b') => value.push(8),
+                Some(b'n') => value.push(b'\n'),
+                Some(b'r') => value.push(b'\r'),
+                Some(b't') => value.push(b'\t'),
+                Some(b'Z') => value.push(26),
+                // `\%` and `\_` keep their backslash
+                Some(byte @ (b'%' | b'_')) => value.extend([b'\\', *byte]),
+                Some(byte) => value.push(*byte),
+                None => {}
+            },
+            b'\'' => {
+                // `''` is a quote
+                let _ = bytes.next();
+                value.push(b'\'');
+            }
+            byte => value.push(*byte),
+        }
+    }
+
+    value
+}
+
+/// the dump read as UTF-8, see [Utf8Reader]
+pub fn utf8_reader<R: Read>(
+    reader: R,
+    encoding: Option<&str>,
+    dialect: Dialect,
+) -> Result<BufReader<Utf8Reader<R>>, ReplibyteError> {
+    Ok(BufReader::new(Utf8Reader::new(reader, encoding, dialect)?))
+}
+
+/// position of the encoding name in `SET client_encoding = 'LATIN1';`
+fn postgres_encoding_declaration(line: &str) -> Option<(usize, usize)> {
+    let prefix = "SET client_encoding = '";
+    if !line.starts_with(prefix) {
+        return None;
+    }
+
+    let start = prefix.len();
+    let end = start + line[start..].find('\'')?;
+    Some((start, end))
+}
+
+/// position of the encoding name in `/*!40101 SET NAMES latin1 */;` or `SET character_set_client = latin1`
+fn mysql_encoding_declaration(line: &str) -> Option<(usize, usize)> {
+    if !line.starts_with("/*!") && !line.starts_with("SET ") {
+        return None;
+    }
+
+    let start = ["SET NAMES ", "SET character_set_client = "]
+        .iter()
+        .find_map(|prefix| line.find(prefix).map(|position| position + prefix.len()))?;
+
+    let end = start
+        + line[start..]
+            .find(|ch: char| !ch.is_ascii_alphanumeric() && ch != '_')
+            .unwrap_or(line.len() - start);
+
+    if start == end {
+        // e.g. `SET character_set_client = @saved_cs_client`
+        return None;
+    }
+
+    Some((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use dump_parser::mysql::{get_rows_from_insert_into_query, get_tokens_from_query_str, Token};
+
+    use super::{utf8_reader, Dialect};
+
+    fn read_to_string(dump: &[u8], encoding: Option<&str>, dialect: Dialect) -> String {
+        let mut reader = utf8_reader(dump, encoding, dialect).unwrap();
+        let mut s = String::new();
+        let _ = reader.read_to_string(&mut s).unwrap();
+        s
+    }
+
+    #[test]
+    fn read_declared_postgres_encoding() {
+        let mut dump = b"SET statement_timeout = 0;\nSET client_encoding = 'LATIN1';\n".to_vec();
+        // 'Zoë' and a C1 control char, kept as it is in ISO-8859-1
+        dump.extend_from_slice(b"INSERT INTO public.users (name) VALUES ('Zo\xeb\x80');\n");
+
+        assert_eq!(
+            read_to_string(&dump, None, Dialect::Postgres),
+            "SET statement_timeout = 0;\nSET client_encoding = 'UTF8';\nINSERT INTO public.users (name) VALUES ('Zo\u{eb}\u{80}');\n"
+        );
+    }
+
+    #[test]
+    fn read_declared_mysql_encoding() {
+        let mut dump = b"/*!40101 SET NAMES latin1 */;\n".to_vec();
+        dump.extend_from_slice(b"/*!40101 SET character_set_client = latin1 */;\n");
+        dump.extend_from_slice(b"/*!40101 SET character_set_client = @saved_cs_client */;\n");
+        // the latin1 of MySQL is windows-1252: 0x80 is the euro sign
+        dump.extend_from_slice(b"INSERT INTO `users` VALUES ('Zo\xeb \x80');");
+
+        assert_eq!(
+            read_to_string(&dump, None, Dialect::Mysql),
+            "/*!40101 SET NAMES utf8mb4 */;\n/*!40101 SET character_set_client = utf8mb4 */;\n/*!40101 SET character_set_client = @saved_cs_client */;\nINSERT INTO `users` VALUES ('Zoë €');"
+        );
+    }
+
+    #[test]
+    fn keep_mysql_binary_literals() {
+        let mut dump = b"/*!40101 SET NAMES latin1 */;\n".to_vec();
+        dump.extend_from_slice(
+            b"INSERT INTO `na\xefve` VALUES ('caf\xe9 _binary \xe9',_binary 'ok',",
+        );
+        dump.extend_from_slice(b"_BINARY '\xff\xfe\x80\\0\\'',X'c3a9');\n");
+
+        let dump = read_to_string(&dump, None, Dialect::Mysql);
+        let query = dump.lines().nth(1).unwrap();
+        assert_eq!(
+            query,
+            "INSERT INTO `naïve` VALUES ('café _binary é',_binary 'ok',_binary 0xfffe800027,X'c3a9');"
+        );
+
+        // the bytes of the binary values are the ones of the dump
+        let tokens = get_tokens_from_query_str(query);
+        assert_eq!(
+            get_rows_from_insert_into_query(&tokens)[0][1..],
+            vec![
+                Token::BinaryStringLiteral("ok".to_string()),
+                Token::HexStringLiteral("fffe800027".to_string()),
+                Token::HexStringLiteral("c3a9".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn read_configured_encoding() {
+        // cp1251: 'Привет'
+        let dump = b"SET client_encoding = 'SQL_ASCII';\nINSERT INTO public.users (name) VALUES ('\xcf\xf0\xe8\xe2\xe5\xf2');\n";
+
+        assert_eq!(
+            read_to_string(dump, Some("WIN1251"), Dialect::Postgres),
+            "SET client_encoding = 'UTF8';\nINSERT INTO public.users (name) VALUES ('Привет');\n"
+        );
+        assert_eq!(
+            read_to_string(dump, Some("windows-1251"), Dialect::Mysql),
+            read_to_string(dump, Some("cp1251"), Dialect::Mysql),
+        );
+    }
+
+    #[test]
+    fn read_utf8_dump() {
+        let dump = "SET client_encoding = 'UTF8';\nINSERT INTO public.users (name) VALUES ('Zoë');";
+
+        assert_eq!(
+            read_to_string(dump.as_bytes(), None, Dialect::Postgres),
+            dump
+        );
+        assert_eq!(
+            read_to_string(dump.as_bytes(), Some("utf8"), Dialect::Postgres),
+            dump
+        );
+    }
+
+    #[test]
+    fn invalid_encoding() {
+        assert!(utf8_reader("".as_bytes(), Some("utf-16"), Dialect::Postgres).is_err());
+        assert!(utf8_reader("".as_bytes(), Some("klingon"), Dialect::Mysql).is_err());
+
+        // 0x81 is not a Shift_JIS char
+        let mut reader = utf8_reader(
+            b"SELECT '\x81';".as_slice(),
+            Some("SJIS"),
+            Dialect::Postgres,
+        )
+        .unwrap();
+        let mut s = String::new();
+        assert!(reader.read_to_string(&mut s).is_err());
+    }
+}
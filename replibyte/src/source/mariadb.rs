@@ -0,0 +1,347 @@
+use std::io::{BufRead, BufReader, Cursor, Read};
+use std::process::{Command, Stdio};
+
+use crate::config::DumpConsistency;
+use crate::connector::Connector;
+use crate::errors::ReplibyteError;
+use crate::source::encoding::{utf8_reader, Dialect};
+use crate::source::mysql;
+use crate::source::Source;
+use crate::types::{OriginalQuery, Query};
+use crate::utils::{binary_exists, wait_for_command};
+
+use super::SourceOptions;
+
+/// recent versions of mariadb-dump start the dump with this client command.
+/// It has no trailing ';', so it is not a valid SQL statement and it must be removed before parsing the dump.
+const SANDBOX_MODE_HEADER: &str = "/*M!999999\\- enable the sandbox mode */";
+
+pub struct MariaDB<'a> {
+    host: &'a str,
+    port: u16,
+    database: &'a str,
+    username: &'a str,
+    password: &'a str,
+    consistency: DumpConsistency,
+}
+
+impl<'a> MariaDB<'a> {
+    pub fn new(
+        host: &'a str,
+        port: u16,
+        database: &'a str,
+        username: &'a str,
+        password: &'a str,
+        consistency: DumpConsistency,
+    ) -> Self {
+        Self {
+            host,
+            port,
+            database,
+            username,
+            password,
+            consistency,
+        }
+    }
+}
+
+impl<'a> Connector for MariaDB<'a> {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        let _ = mysql::consistency_args(self.consistency)?;
+        let _ = binary_exists("mariadb-dump")?;
+
+        match self.consistency {
+            // the engines of the tables are checked with mariadb
+            DumpConsistency::SingleTransaction => binary_exists("mariadb"),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<'a> Source for MariaDB<'a> {
+    fn read<F: FnMut(OriginalQuery, Query)>(
+        &self,
+        options: SourceOptions,
+        query_callback: F,
+    ) -> Result<(), ReplibyteError> {
+        let s_port = self.port.to_string();
+        let password = &format!("-p{}", self.password);
+
+        let mut dump_args = vec![
+            "-h",
+            self.host,
+            "-P",
+            s_port.as_str(),
+            "-u",
+            self.username,
+            password,
+            "--add-drop-database", // add DROP DATABASE statement before each CREATE DATABASE statement
+            "--add-drop-table",    // add DROP TABLE statement before each CREATE TABLE statement
+            "--skip-extended-insert", // have a row by INSERT INTO statement
+            "--complete-insert",   // have column names in INSERT INTO rows
+            "--hex-blob", // have the binary values as hexadecimal literals, the dump is valid UTF-8
+        ];
+
+        // https://mariadb.com/kb/en/mariadb-dump/#options
+        dump_args.append(&mut mysql::consistency_args(self.consistency)?);
+        dump_args.push(self.database);
+
+        if self.consistency == DumpConsistency::SingleTransaction {
+            let connection_args = [
+                "-h",
+                self.host,
+                "-P",
+                s_port.as_str(),
+                "-u",
+                self.username,
+                password,
+            ];
+            mysql::check_transactional_engines(
+                "mariadb",
+                &connection_args,
+                self.database,
+                options,
+            )?;
+        }
+
+        let ignore_tables_args: Vec<String> = options
+            .skip_config
+            .iter()
+            .map(|cfg| format!("--ignore-table={}.{}", cfg.database, cfg.table))
+            .collect();
+        let mut ignore_tables_args: Vec<&str> =
+            ignore_tables_args.iter().map(String::as_str).collect();
+
+        dump_args.append(&mut ignore_tables_args);
+
+        let mut only_tables_args: Vec<&str> = options
+            .only_tables
+            .iter()
+            .map(|cfg| String::as_str(&cfg.table))
+            .collect();
+
+        dump_args.append(&mut only_tables_args);
+
+        let mut process = Command::new("mariadb-dump")
+            .args(dump_args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let stdout = process.stdout.take().ok_or_else(|| {
+            ReplibyteError::Command("Could not capture standard output.".to_string())
+        })?;
+
+        let reader = utf8_reader(stdout, options.encoding, Dialect::Mysql)?;
+
+        read_and_transform(reader, options, query_callback)?;
+
+        wait_for_command(&mut process)
+    }
+}
+
+/// consume reader and apply transformation on INSERT INTO queries if needed.
+/// Rows are tokenized with the MySQL tokenizer once the MariaDB specific statements are removed.
+pub fn read_and_transform<R: Read, F: FnMut(OriginalQuery, Query)>(
+    mut reader: BufReader<R>,
+    options: SourceOptions,
+    query_callback: F,
+) -> Result<(), ReplibyteError> {
+    let mut first_line = Vec::new();
+    let _ = reader.read_until(b'\n', &mut first_line)?;
+
+    if is_sandbox_mode_header(&first_line) {
+        first_line.clear();
+    }
+
+    let reader = BufReader::new(Cursor::new(first_line).chain(reader));
+
+    match &options.database_subset {
+        None => mysql::read_and_transform(reader, options, query_callback),
+        Some(subset_config) => {
+            let reader = mysql::subset(reader, subset_config)?;
+            mysql::read_and_transform(reader, options, query_callback)
+        }
+    }
+}
+
+fn is_sandbox_mode_header(line: &[u8]) -> bool {
+    line.starts_with(SANDBOX_MODE_HEADER.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use crate::config::{
+        DatabaseSubsetConfig, DatabaseSubsetConfigStrategy, DatabaseSubsetConfigStrategyRandom,
+    };
+    use crate::source::mariadb::{is_sandbox_mode_header, read_and_transform};
+    use crate::source::SourceOptions;
+    use crate::transformer::set_null::SetNullTransformer;
+    use crate::transformer::transient::TransientTransformer;
+    use crate::transformer::Transformer;
+
+    const DUMP: &str = "/*M!999999\\- enable the sandbox mode */
+-- MariaDB dump 10.19-11.4.2-MariaDB, for debian-linux-gnu (x86_64)
+
+DROP TABLE IF EXISTS `customers`;
+INSERT INTO `customers` (`first_name`, `is_valid`) VALUES ('Romaric', true);
+";
+
+    const SEQUENCE_DUMP: &str = "/*M!999999\\- enable the sandbox mode */
+-- MariaDB dump 10.19-11.4.2-MariaDB, for debian-linux-gnu (x86_64)
+
+DROP TABLE IF EXISTS `invoice_numbers`;
+CREATE SEQUENCE `invoice_numbers` start with 1 minvalue 1 maxvalue 9223372036854775806 increment by 1 cache 1000 nocycle ENGINE=InnoDB;
+SELECT SETVAL(`invoice_numbers`, 1001, 0);
+DROP TABLE IF EXISTS `invoices`;
+CREATE TABLE `invoices` (
+  `id` bigint(20) NOT NULL DEFAULT nextval(`world`.`invoice_numbers`),
+  `email` varchar(255) DEFAULT NULL,
+  PRIMARY KEY (`id`)
+) ENGINE=InnoDB;
+INSERT INTO `invoices` VALUES (1,'romaric@example.com');
+";
+
+    #[test]
+    fn test_is_sandbox_mode_header() {
+        assert!(is_sandbox_mode_header(
+            b"/*M!999999\\- enable the sandbox mode */ \n"
+        ));
+        assert!(!is_sandbox_mode_header(
+            b"-- MariaDB dump 10.19  Distrib 10.6.12-MariaDB\n"
+        ));
+    }
+
+    #[test]
+    fn remove_sandbox_mode_header() {
+        let transformers: Vec<Box<dyn Transformer>> = vec![];
+        let source_options = SourceOptions {
+            transformers: &transformers,
+            skip_config: &vec![],
+            database_subset: &None,
+            only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
+        };
+
+        let mut queries = vec![];
+        let reader = BufReader::new(DUMP.as_bytes());
+        assert!(read_and_transform(reader, source_options, |_original_query, query| {
+            queries.push(String::from_utf8(query.data().to_vec()).unwrap());
+        })
+        .is_ok());
+
+        assert!(queries.iter().all(|query| !query.contains("sandbox")));
+        assert!(queries
+            .iter()
+            .any(|query| query.starts_with("DROP TABLE IF EXISTS `customers`;")));
+    }
+
+    #[test]
+    fn transform_rows() {
+        let t1: Box<dyn Transformer> = Box::new(TransientTransformer::new(
+            "world",
+            "customers",
+            "first_name",
+        ));
+        let transformers = vec![t1];
+        let source_options = SourceOptions {
+            transformers: &transformers,
+            skip_config: &vec![],
+            database_subset: &None,
+            only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
+        };
+
+        let mut inserts = vec![];
+        let reader = BufReader::new(DUMP.as_bytes());
+        let _ = read_and_transform(reader, source_options, |_original_query, query| {
+            let query = String::from_utf8(query.data().to_vec()).unwrap();
+            if query.starts_with("INSERT INTO") {
+                inserts.push(query);
+            }
+        });
+
+        assert_eq!(
+            inserts,
+            vec!["INSERT INTO `customers` (`first_name`, `is_valid`) VALUES ('Romaric', true);"]
+        );
+    }
+
+    fn read_sequence_dump(source_options: SourceOptions) -> Vec<String> {
+        let mut queries = vec![];
+        let reader = BufReader::new(SEQUENCE_DUMP.as_bytes());
+        read_and_transform(reader, source_options, |_original_query, query| {
+            let query = String::from_utf8(query.data().to_vec()).unwrap();
+            if !query.trim().is_empty() {
+                queries.push(query);
+            }
+        })
+        .unwrap();
+
+        queries
+    }
+
+    #[test]
+    fn keep_sequences() {
+        let t1: Box<dyn Transformer> =
+            Box::new(SetNullTransformer::new("world", "invoices", "email"));
+        let transformers = vec![t1];
+        let source_options = SourceOptions {
+            transformers: &transformers,
+            skip_config: &vec![],
+            database_subset: &None,
+            only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
+        };
+
+        let queries = read_sequence_dump(source_options);
+
+        assert!(queries.contains(&"CREATE SEQUENCE `invoice_numbers` start with 1 minvalue 1 maxvalue 9223372036854775806 increment by 1 cache 1000 nocycle ENGINE=InnoDB;".to_string()));
+        assert!(queries.contains(&"SELECT SETVAL(`invoice_numbers`, 1001, 0);".to_string()));
+        // the columns are read from the CREATE TABLE, even with a nextval() default
+        assert!(queries.contains(&"INSERT INTO `invoices` VALUES (1, NULL);".to_string()));
+    }
+
+    #[test]
+    fn keep_sequences_in_subset() {
+        let transformers: Vec<Box<dyn Transformer>> = vec![];
+        let source_options = SourceOptions {
+            transformers: &transformers,
+            skip_config: &vec![],
+            database_subset: &Some(DatabaseSubsetConfig {
+                database: "world".to_string(),
+                table: "invoices".to_string(),
+                strategy: DatabaseSubsetConfigStrategy::Random(
+                    DatabaseSubsetConfigStrategyRandom { percent: 100 },
+                ),
+                passthrough_tables: None,
+                tables: None,
+                seed: None,
+                lookups: None,
+            }),
+            only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
+        };
+
+        let queries = read_sequence_dump(source_options);
+
+        assert!(queries.contains(&"CREATE SEQUENCE `invoice_numbers` start with 1 minvalue 1 maxvalue 9223372036854775806 increment by 1 cache 1000 nocycle ENGINE=InnoDB;".to_string()));
+        assert!(queries.contains(&"SELECT SETVAL(`invoice_numbers`, 1001, 0);".to_string()));
+        assert!(queries
+            .contains(&"INSERT INTO `invoices` VALUES (1, 'romaric@example.com');".to_string()));
+    }
+}
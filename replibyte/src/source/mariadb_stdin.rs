@@ -0,0 +1,39 @@
+use std::io::{stdin, Read};
+
+use crate::connector::Connector;
+use crate::errors::ReplibyteError;
+use crate::source::encoding::{utf8_reader, Dialect};
+use crate::source::mariadb::read_and_transform;
+use crate::types::{OriginalQuery, Query};
+use crate::Source;
+use crate::SourceOptions;
+
+/// Source MariaDB dump from STDIN
+#[derive(Default)]
+pub struct MariaDBStdin {}
+
+impl Connector for MariaDBStdin {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        Ok(())
+    }
+}
+
+impl Source for MariaDBStdin {
+    fn read<F: FnMut(OriginalQuery, Query)>(
+        &self,
+        options: SourceOptions,
+        query_callback: F,
+    ) -> Result<(), ReplibyteError> {
+        read_dump(stdin(), options, query_callback)
+    }
+}
+
+/// read a MariaDB dump and transform its rows
+pub fn read_dump<R: Read, F: FnMut(OriginalQuery, Query)>(
+    reader: R,
+    options: SourceOptions,
+    query_callback: F,
+) -> Result<(), ReplibyteError> {
+    let reader = utf8_reader(reader, options.encoding, Dialect::Mysql)?;
+    read_and_transform(reader, options, query_callback)
+}
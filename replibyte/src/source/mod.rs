@@ -1,28 +1,47 @@
-use std::io::Error;
-
-use crate::config::{DatabaseSubsetConfig, OnlyTablesConfig, SkipConfig};
+use crate::config::{DatabaseSubsetConfig, OnlyTablesConfig, RowFilterConfig, SkipConfig};
 use crate::connector::Connector;
+use crate::errors::ReplibyteError;
 use crate::transformer::Transformer;
 use crate::types::{OriginalQuery, Query};
 
+pub mod dump_file;
+pub mod encoding;
+pub mod mariadb;
+pub mod mariadb_stdin;
 pub mod mongodb;
 pub mod mongodb_stdin;
+pub mod mssql;
+pub mod mssql_stdin;
 pub mod mysql;
 pub mod mysql_stdin;
 pub mod postgres;
+pub mod postgres_incremental;
 pub mod postgres_stdin;
+pub mod replica;
+pub mod row_filter;
+pub mod sqlite;
+pub mod sqlite_stdin;
+pub mod throttled;
 
 pub trait Source: Connector {
     fn read<F: FnMut(OriginalQuery, Query)>(
         &self,
         options: SourceOptions,
         query_callback: F,
-    ) -> Result<(), Error>;
+    ) -> Result<(), ReplibyteError>;
 }
 
+#[derive(Clone, Copy)]
 pub struct SourceOptions<'a> {
     pub transformers: &'a Vec<Box<dyn Transformer>>,
     pub skip_config: &'a Vec<SkipConfig>,
     pub database_subset: &'a Option<DatabaseSubsetConfig>,
     pub only_tables: &'a Vec<OnlyTablesConfig>,
+    pub filters: &'a Vec<RowFilterConfig>,
+    /// encoding of the dump, see [encoding::Utf8Reader]
+    pub encoding: Option<&'a str>,
+    /// the PostgreSQL large objects are not dumped
+    pub skip_large_objects: bool,
+    /// the PostgreSQL bytea values are dumped as NULL
+    pub skip_bytea: bool,
 }
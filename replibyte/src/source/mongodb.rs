@@ -1,16 +1,27 @@
-use std::collections::{HashMap, HashSet};
-use std::io::{BufReader, Error, ErrorKind, Read};
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Read};
 use std::process::{Command, Stdio};
 
+use crate::config::DatabaseSubsetConfig;
 use crate::connector::Connector;
+use crate::errors::ReplibyteError;
+use crate::source::dump_file::GZIP_MAGIC;
+use crate::source::row_filter::RowFilters;
 use crate::source::Source;
+use crate::transformer::selector::TransformerSelector;
 use crate::transformer::Transformer;
 use crate::types::{Column, OriginalQuery, Query};
 use crate::utils::{binary_exists, wait_for_command};
 use crate::SourceOptions;
 
 use bson::{Bson, Document};
-use dump_parser::mongodb::Archive;
+use dump_parser::mongodb::{stream_archive, Archive};
+use flate2::read::MultiGzDecoder;
+use subset::mongodb::MongodbSubset;
+use subset::{PassthroughTable, SubsetOptions, SubsetTable, SubsetTableRelation};
+
+/// max size of the queries of a streamed archive, a query can be bigger if the archive has no new line byte
+const QUERY_SIZE: usize = 1024 * 1024;
 
 pub struct MongoDB<'a> {
     uri: &'a str,
@@ -24,7 +35,7 @@ impl<'a> MongoDB<'a> {
 }
 
 impl<'a> Connector for MongoDB<'a> {
-    fn init(&mut self) -> Result<(), Error> {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
         let _ = binary_exists("mongosh")?;
         let _ = binary_exists("mongodump")?;
         let _ = check_connection_status(self)?;
@@ -38,11 +49,7 @@ impl<'a> Source for MongoDB<'a> {
         &self,
         options: SourceOptions,
         query_callback: F,
-    ) -> Result<(), Error> {
-        if let Some(_database_subset) = &options.database_subset {
-            todo!("database subset not supported yet for MongoDB source")
-        }
-
+    ) -> Result<(), ReplibyteError> {
         let mut process = Command::new("mongodump")
             .args([
                 "--uri",
@@ -55,10 +62,9 @@ impl<'a> Source for MongoDB<'a> {
             .stderr(Stdio::piped())
             .spawn()?;
 
-        let stdout = process
-            .stdout
-            .take()
-            .ok_or_else(|| Error::new(ErrorKind::Other, "Could not capture standard output."))?;
+        let stdout = process.stdout.take().ok_or_else(|| {
+            ReplibyteError::Command("Could not capture standard output.".to_string())
+        })?;
 
         let reader = BufReader::new(stdout);
 
@@ -68,7 +74,7 @@ impl<'a> Source for MongoDB<'a> {
     }
 }
 
-fn check_connection_status(db: &MongoDB) -> Result<(), Error> {
+fn check_connection_status(db: &MongoDB) -> Result<(), ReplibyteError> {
     let mut echo_process = Command::new("echo")
         .arg(r#"'db.runCommand("ping").ok'"#)
         .stdout(Stdio::piped())
@@ -83,10 +89,10 @@ fn check_connection_status(db: &MongoDB) -> Result<(), Error> {
     let exit_status = mongo_process.wait()?;
 
     if !exit_status.success() {
-        return Err(Error::new(
-            ErrorKind::Other,
-            format!("command error: {:?}", exit_status.to_string()),
-        ));
+        return Err(ReplibyteError::Command(format!(
+            "command error: {:?}",
+            exit_status.to_string()
+        )));
     }
 
     Ok(())
@@ -95,27 +101,17 @@ fn check_connection_status(db: &MongoDB) -> Result<(), Error> {
 pub fn recursively_transform_bson(
     key: String,
     bson: Bson,
-    transformers: &HashMap<String, &Box<dyn Transformer + '_>>,
+    transformers: &TransformerSelector,
     wildcard_keys: &HashSet<String>,
+    row: &[Column],
 ) -> Bson {
-    let mut column;
-    match bson {
-        Bson::String(value) => {
-            column = Column::StringValue(key.clone(), value.clone());
-            column = match transformers.get(key.as_str()) {
-                Some(transformer) => transformer.transform(column), // apply transformation on the column
-                None => column,
-            };
-            Bson::String((*column.string_value().unwrap()).to_string())
-        }
-        Bson::Double(value) => {
-            column = Column::FloatNumberValue(key.clone(), value);
-            column = match transformers.get(key.as_str()) {
-                Some(transformer) => transformer.transform(column), // apply transformation on the column
-                None => column,
-            };
-            Bson::Double(*column.float_number_value().unwrap())
-        }
+    let column = match bson {
+        Bson::String(ref value) => Column::StringValue(key.clone(), value.clone()),
+        Bson::Double(value) => Column::FloatNumberValue(key.clone(), value),
+        Bson::Int32(value) => Column::NumberValue(key.clone(), value as i128),
+        Bson::Int64(value) => Column::NumberValue(key.clone(), value as i128),
+        Bson::Boolean(value) => Column::BooleanValue(key.clone(), value),
+        Bson::Null => Column::None(key.clone()),
         Bson::Array(arr) => {
             let new_arr = arr
                 .iter()
@@ -131,57 +127,59 @@ pub fn recursively_transform_bson(
                         bson.clone(),
                         transformers,
                         wildcard_keys,
+                        row,
                     )
                 })
                 .collect::<Vec<Bson>>();
-            Bson::Array(new_arr)
-        }
-        Bson::Document(nested_doc) => Bson::Document(recursively_transform_document(
-            key,
-            nested_doc,
-            transformers,
-            wildcard_keys,
-        )),
-        Bson::Null => Bson::Null,
-        Bson::Int32(value) => {
-            column = Column::NumberValue(key.clone(), value as i128);
-            column = match transformers.get(key.as_str()) {
-                Some(transformer) => transformer.transform(column), // apply transformation on the column
-                None => column,
-            };
-            Bson::Int32(column.number_value().map(|&n| n as i32).unwrap())
+            return Bson::Array(new_arr);
         }
-        Bson::Int64(value) => {
-            column = Column::NumberValue(key.clone(), value as i128);
-            column = match transformers.get(key.as_str()) {
-                Some(transformer) => transformer.transform(column), // apply transformation on the column
-                None => column,
-            };
-            Bson::Int64(column.number_value().map(|&n| n as i64).unwrap())
+        Bson::Document(nested_doc) => {
+            return Bson::Document(recursively_transform_document(
+                key,
+                nested_doc,
+                transformers,
+                wildcard_keys,
+                row,
+            ))
         }
-        // ALL OF THE NEXT TYPES ARE NOT TRANSFORMABLE (yet?)
-        Bson::ObjectId(oid) => Bson::ObjectId(oid),
-        Bson::Binary(bin) => Bson::Binary(bin),
-        Bson::RegularExpression(regex) => Bson::RegularExpression(regex),
-        Bson::Boolean(value) => Bson::Boolean(value),
-        Bson::DateTime(value) => Bson::DateTime(value),
-        Bson::Timestamp(value) => Bson::Timestamp(value),
-        Bson::MinKey => Bson::MinKey,
-        Bson::MaxKey => Bson::MaxKey,
-        Bson::JavaScriptCode(jsc) => Bson::JavaScriptCode(jsc),
-        Bson::JavaScriptCodeWithScope(jsc) => Bson::JavaScriptCodeWithScope(jsc),
-        Bson::Symbol(symbol) => Bson::Symbol(symbol),
-        Bson::Decimal128(decimal) => Bson::Decimal128(decimal),
-        Bson::Undefined => Bson::Undefined,
-        Bson::DbPointer(db_pointer) => Bson::DbPointer(db_pointer),
+        // ALL OF THE OTHER TYPES ARE NOT TRANSFORMABLE (yet?)
+        bson => return bson,
+    };
+
+    match transformers.get(key.as_str()) {
+        // apply transformation on the column
+        Some(transformer) => to_bson(
+            transformer.transform_column(key.as_str(), column, row),
+            &bson,
+        ),
+        None => bson,
+    }
+}
+
+/// the BSON value of a transformed column, a transformer can change the type of the value (e.g. to NULL)
+fn to_bson(column: Column, original_bson: &Bson) -> Bson {
+    match column {
+        Column::NumberValue(_, value) => match (original_bson, i32::try_from(value)) {
+            (Bson::Int32(_), Ok(value)) => Bson::Int32(value),
+            _ => match i64::try_from(value) {
+                Ok(value) => Bson::Int64(value),
+                Err(_) => Bson::String(value.to_string()),
+            },
+        },
+        Column::FloatNumberValue(_, value) => Bson::Double(value),
+        Column::StringValue(_, value) => Bson::String(value),
+        Column::CharValue(_, value) => Bson::String(value.to_string()),
+        Column::BooleanValue(_, value) => Bson::Boolean(value),
+        Column::None(_) => Bson::Null,
     }
 }
 
 pub fn recursively_transform_document(
     prefix: String,
     mut original_doc: Document,
-    transformers: &HashMap<String, &Box<dyn Transformer + '_>>,
+    transformers: &TransformerSelector,
     wildcard_keys: &HashSet<String>,
+    row: &[Column],
 ) -> Document {
     for (key, bson) in original_doc.clone() {
         original_doc.insert(
@@ -191,69 +189,311 @@ pub fn recursively_transform_document(
                 bson,
                 transformers,
                 wildcard_keys,
+                row,
             ),
         );
     }
     original_doc
 }
 
+/// return the values of the fields of a document, the nested fields are named with their path (e.g. `address.country`)
+fn document_columns(path: &str, doc: &Document, columns: &mut Vec<Column>) {
+    for (key, bson) in doc {
+        let name = if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", path, key)
+        };
+
+        match bson {
+            Bson::String(value) => columns.push(Column::StringValue(name, value.clone())),
+            Bson::Double(value) => columns.push(Column::FloatNumberValue(name, *value)),
+            Bson::Int32(value) => columns.push(Column::NumberValue(name, *value as i128)),
+            Bson::Int64(value) => columns.push(Column::NumberValue(name, *value as i128)),
+            Bson::Boolean(value) => columns.push(Column::BooleanValue(name, *value)),
+            Bson::Null => columns.push(Column::None(name)),
+            Bson::Document(nested_doc) => document_columns(name.as_str(), nested_doc, columns),
+            _ => {}
+        }
+    }
+}
+
 pub(crate) fn find_all_keys_with_array_wildcard_op(
     transformers: &Vec<Box<dyn Transformer + '_>>,
 ) -> HashSet<String> {
     let mut wildcard_keys = HashSet::new();
     for transformer in transformers {
-        let column_name = transformer.column_name();
-        let delim = ".$[].";
-        let mut iter = 0;
-        while let Some(idx) = column_name[iter..].find(delim) {
-            let offset = delim.len();
-            iter += idx + offset;
-            let key = column_name[..(iter - 1)].to_string();
-            wildcard_keys.insert(format!("{}.{}", transformer.database_and_table_name(), key));
-        }
-        // try to find last delim
-        let last_delim = ".$[]"; // no dot at the end
-        if let Some(_) = column_name[iter..].find(last_delim) {
-            let key = column_name.to_string();
-            wildcard_keys.insert(format!("{}.{}", transformer.database_and_table_name(), key));
+        // the keys end with their wildcard, e.g. `a.b.$[]` for `a.b.$[].c`
+        let segments = transformer.column_name().split('.').collect::<Vec<_>>();
+        for (idx, segment) in segments.iter().enumerate() {
+            if *segment == "$[]" {
+                let key = segments[..=idx].join(".");
+                wildcard_keys.insert(format!("{}.{}", transformer.database_and_table_name(), key));
+            }
         }
     }
     wildcard_keys
 }
 
-/// consume reader and apply transformation on INSERT INTO queries if needed
+/// the path of a field named with the array notation, e.g. `contacts.$[].email` for `contacts[*].email` and
+/// `contacts.0.email` for `contacts[0].email`. The other names are kept.
+fn field_path(column_name: &str) -> String {
+    if column_name.len() >= 2 && column_name.starts_with('/') && column_name.ends_with('/') {
+        // regex
+        return column_name.to_string();
+    }
+
+    let mut path = String::with_capacity(column_name.len());
+    let mut rest = column_name;
+
+    while let Some(start) = rest.find('[') {
+        let end = match rest[start..].find(']') {
+            Some(end) => start + end,
+            None => break,
+        };
+
+        let index = &rest[start + 1..end];
+        path.push_str(&rest[..start]);
+        if index == "*" {
+            path.push_str(".$[]");
+        } else if !index.is_empty() && index.chars().all(|c| c.is_ascii_digit()) {
+            path.push('.');
+            path.push_str(index);
+        } else {
+            path.push_str(&rest[start..=end]);
+        }
+
+        rest = &rest[end + 1..];
+    }
+
+    path.push_str(rest);
+    path
+}
+
+/// transformer of a field named with the array notation, selected by the path of the field
+struct FieldPathTransformer<'a> {
+    transformer: &'a dyn Transformer,
+    column_name: String,
+}
+
+impl<'a> Transformer for FieldPathTransformer<'a> {
+    fn id(&self) -> &str {
+        self.transformer.id()
+    }
+
+    fn description(&self) -> &str {
+        self.transformer.description()
+    }
+
+    fn database_name(&self) -> &str {
+        self.transformer.database_name()
+    }
+
+    fn table_name(&self) -> &str {
+        self.transformer.table_name()
+    }
+
+    fn column_name(&self) -> &str {
+        self.column_name.as_str()
+    }
+
+    fn transform(&self, column: Column) -> Column {
+        self.transformer.transform(column)
+    }
+
+    fn transform_row(&self, column: Column, row: &[Column]) -> Column {
+        self.transformer.transform_row(column, row)
+    }
+
+    fn transform_column(&self, name: &str, column: Column, row: &[Column]) -> Column {
+        self.transformer.transform_column(name, column, row)
+    }
+}
+
+/// the transformers with the paths of their fields, see `field_path`
+fn field_path_transformers<'a>(
+    transformers: &'a [Box<dyn Transformer>],
+) -> Vec<Box<dyn Transformer + 'a>> {
+    transformers
+        .iter()
+        .map(|transformer| {
+            Box::new(FieldPathTransformer {
+                transformer: transformer.as_ref(),
+                column_name: field_path(transformer.column_name()),
+            }) as Box<dyn Transformer + 'a>
+        })
+        .collect()
+}
+
+/// keep the documents of the subset in the archive, the references between the collections are the `lookups` of the config
+pub fn subset(
+    archive: &mut Archive,
+    subset_config: &DatabaseSubsetConfig,
+) -> Result<(), ReplibyteError> {
+    let database = subset_config.database.as_str();
+
+    let empty_vec = Vec::new();
+    let passthrough_tables = subset_config
+        .passthrough_tables
+        .as_ref()
+        .unwrap_or(&empty_vec)
+        .iter()
+        .map(|table| PassthroughTable::new(database, table.as_str()))
+        .collect::<HashSet<_>>();
+
+    let subset_tables = subset_config
+        .lookups
+        .iter()
+        .flatten()
+        .map(|lookup| {
+            SubsetTable::new(
+                database,
+                lookup.table.as_str(),
+                vec![SubsetTableRelation::new(
+                    database,
+                    lookup.from.as_str(),
+                    lookup.local_field.as_str(),
+                    lookup.foreign_field.as_str(),
+                )],
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let subset = MongodbSubset::new(
+        subset_tables,
+        subset_config.strategies()?,
+        SubsetOptions::new(&passthrough_tables, subset_config.seed),
+    );
+
+    let mut result = Ok(());
+    archive.alter_docs(|prefixed_collections| {
+        result = subset.subset(prefixed_collections);
+    });
+
+    Ok(result?)
+}
+
+/// consume reader and apply transformation on the documents of the archive
 pub fn read_and_transform<R: Read, F: FnMut(OriginalQuery, Query)>(
+    mut reader: BufReader<R>,
+    source_options: SourceOptions,
+    query_callback: F,
+) -> Result<(), ReplibyteError> {
+    // `mongodump --archive --gzip` compresses the whole archive
+    if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        let reader = BufReader::new(MultiGzDecoder::new(reader));
+        return read_and_transform_archive(reader, source_options, query_callback);
+    }
+
+    read_and_transform_archive(reader, source_options, query_callback)
+}
+
+fn read_and_transform_archive<R: Read, F: FnMut(OriginalQuery, Query)>(
     reader: BufReader<R>,
     source_options: SourceOptions,
     mut query_callback: F,
-) -> Result<(), Error> {
-    let transformers = source_options.transformers;
-    // create a set of wildcards to be used in the transformation
-    let wildcard_keys = find_all_keys_with_array_wildcard_op(transformers);
-    // create a map variable with Transformer by column_name
-    let mut transformer_by_db_and_table_and_column_name: HashMap<String, &Box<dyn Transformer>> =
-        HashMap::with_capacity(transformers.len());
-
-    for transformer in transformers {
-        let _ = transformer_by_db_and_table_and_column_name.insert(
-            transformer.database_and_table_and_column_name(),
-            transformer,
-        );
+) -> Result<(), ReplibyteError> {
+    // the references between the collections of a subset need the whole archive
+    if source_options.database_subset.is_some() {
+        return subset_and_transform(reader, source_options, query_callback);
     }
+
+    let transformers = field_path_transformers(source_options.transformers);
+    let wildcard_keys = find_all_keys_with_array_wildcard_op(&transformers);
+    let transformer_by_db_and_table_and_column_name = TransformerSelector::new(&transformers, true);
+    let row_filters = RowFilters::new(source_options.filters, true);
+
+    let mut original_query = vec![];
+    let mut query = vec![];
+
+    stream_archive(
+        reader,
+        |prefix, doc| {
+            // the transformers get the original values of the document to check their conditions
+            let mut row = vec![];
+            document_columns("", &doc, &mut row);
+
+            // the filtered documents are not dumped
+            if row_filters.excludes(prefix, &row) {
+                return None;
+            }
+
+            Some(recursively_transform_document(
+                prefix.to_string(), // prefix is <db_name>.<collection_name>
+                doc,
+                &transformer_by_db_and_table_and_column_name,
+                &wildcard_keys,
+                &row,
+            ))
+        },
+        |original_bytes, bytes| {
+            original_query.extend_from_slice(original_bytes);
+            query.extend_from_slice(bytes);
+
+            if query.len() < QUERY_SIZE {
+                return;
+            }
+
+            if let Some(bytes) = take_query(&mut query) {
+                query_callback(Query(std::mem::take(&mut original_query)), Query(bytes));
+            }
+        },
+    )?;
+
+    query_callback(Query(original_query), Query(query));
+    Ok(())
+}
+
+/// the bytes of the archive up to its last new line, which is removed. The queries are written followed by
+/// a new line: the archive is split on its own new lines to be written as is.
+fn take_query(query: &mut Vec<u8>) -> Option<Vec<u8>> {
+    let idx = query.iter().rposition(|byte| *byte == b'\n')?;
+    let next_query = query.split_off(idx + 1);
+    let _ = query.pop();
+
+    Some(std::mem::replace(query, next_query))
+}
+
+/// subset the archive in memory, then apply transformation on its documents
+fn subset_and_transform<R: Read, F: FnMut(OriginalQuery, Query)>(
+    reader: BufReader<R>,
+    source_options: SourceOptions,
+    mut query_callback: F,
+) -> Result<(), ReplibyteError> {
+    let transformers = field_path_transformers(source_options.transformers);
+    // create a set of wildcards to be used in the transformation
+    let wildcard_keys = find_all_keys_with_array_wildcard_op(&transformers);
+    // Transformer by db_name.collection_name.field_name
+    let transformer_by_db_and_table_and_column_name = TransformerSelector::new(&transformers, true);
+    let row_filters = RowFilters::new(source_options.filters, true);
     // init archive from reader
     let mut archive = Archive::from_reader(reader)?;
 
+    if let Some(subset_config) = source_options.database_subset {
+        let _ = subset(&mut archive, subset_config)?;
+    }
+
     let original_query = Query(archive.clone().into_bytes()?);
 
     archive.alter_docs(|prefixed_collections| {
         for (prefix, collection) in prefixed_collections.to_owned() {
             let mut new_collection = vec![];
             for doc in collection {
+                // the transformers get the original values of the document to check their conditions
+                let mut row = vec![];
+                document_columns("", &doc, &mut row);
+
+                // the filtered documents are not dumped
+                if row_filters.excludes(prefix.as_str(), &row) {
+                    continue;
+                }
+
                 let new_doc = recursively_transform_document(
                     prefix.clone(), // prefix is <db_name>.<collection_name>
                     doc,
                     &transformer_by_db_and_table_and_column_name,
                     &wildcard_keys,
+                    &row,
                 );
                 new_collection.push(new_doc);
             }
@@ -273,14 +513,30 @@ mod tests {
     use crate::transformer::random::RandomTransformer;
     use crate::Source;
     use bson::{doc, Bson};
-    use std::collections::{HashMap, HashSet};
+    use std::collections::HashSet;
     use std::vec;
 
     use crate::source::mongodb::{find_all_keys_with_array_wildcard_op, MongoDB};
+    use crate::transformer::selector::TransformerSelector;
+    use crate::transformer::set_constant::{
+        ConstantValue, SetConstantTransformer, SetConstantTransformerOptions,
+    };
+    use crate::transformer::set_null::SetNullTransformer;
     use crate::transformer::transient::TransientTransformer;
     use crate::transformer::Transformer;
 
-    use super::recursively_transform_document;
+    use super::{
+        document_columns, field_path, field_path_transformers, read_and_transform,
+        recursively_transform_document, subset, take_query,
+    };
+    use crate::config::DatabaseSubsetConfig;
+    use crate::transformer::conditional::{
+        ConditionOperator, ConditionValue, ConditionalTransformer, TransformerCondition,
+    };
+    use dump_parser::mongodb::Archive;
+    use dump_parser::utils::decode_hex;
+    use flate2::write::GzEncoder;
+    use std::io::{BufReader, Write};
 
     fn get_mongodb() -> MongoDB<'static> {
         MongoDB::new(
@@ -307,6 +563,10 @@ mod tests {
             skip_config: &vec![],
             database_subset: &None,
             only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
         };
 
         assert!(p.read(source_options, |_, _| {}).is_ok());
@@ -319,6 +579,10 @@ mod tests {
             skip_config: &vec![],
             database_subset: &None,
             only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
         };
 
         assert!(p.read(source_options, |_, _| {}).is_err());
@@ -334,6 +598,10 @@ mod tests {
             skip_config: &vec![],
             database_subset: &None,
             only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
         };
 
         p.read(source_options, |original_query, query| {
@@ -369,19 +637,15 @@ mod tests {
             ));
             t
         }));
-        // Create a HashMap with Transformer by db_name.table_name.column_name
-        let transformers = HashMap::from_iter(
-            transformers_vec
-                .iter()
-                .map(|t| t.database_and_table_and_column_name())
-                .zip(transformers_vec.iter()),
-        );
+        // Transformer by db_name.table_name.column_name
+        let transformers = TransformerSelector::new(&transformers_vec, true);
         // Recursively transform the document
         let transformed_doc = recursively_transform_document(
             "test.users".to_string(),
             doc,
             &transformers,
             &HashSet::new(),
+            &[],
         );
 
         // Assert transformed values are not equal to original values
@@ -442,22 +706,15 @@ mod tests {
         let transformers_vec = vec![t];
         // create a set of wildcards to be used in the transformation
         let wildcard_keys = find_all_keys_with_array_wildcard_op(&transformers_vec);
-        // create a map variable with Transformer by column_name
-        let mut transformers: HashMap<String, &Box<dyn Transformer>> =
-            HashMap::with_capacity(transformers_vec.len());
-
-        for transformer in transformers_vec.iter() {
-            let _ = transformers.insert(
-                transformer.database_and_table_and_column_name(),
-                transformer,
-            );
-        }
+        // Transformer by db_name.table_name.column_name
+        let transformers = TransformerSelector::new(&transformers_vec, true);
         // Recursively transform the document
         let transformed_doc = recursively_transform_document(
             "test.users".to_string(),
             doc,
             &transformers,
             &wildcard_keys,
+            &[],
         );
 
         // Assert transformed values are not equal to original values
@@ -477,4 +734,282 @@ mod tests {
         assert_ne!(inner_arr[0], Bson::Int32(3));
         assert_eq!(inner_arr[1], Bson::Int32(4));
     }
+
+    #[test]
+    fn conditional_document_transform() {
+        let doc = doc! {
+            "email": "john.doe@company.com",
+            "profile": { "is_internal": true },
+        };
+
+        let mut row = vec![];
+        document_columns("", &doc, &mut row);
+        assert_eq!(row.len(), 2);
+        assert_eq!(row[1].name(), "profile.is_internal");
+
+        let transformer: Box<dyn Transformer> = Box::new(ConditionalTransformer::new(
+            Box::new(RandomTransformer::new("test", "users", "email")),
+            vec![TransformerCondition {
+                column: "profile.is_internal".to_string(),
+                operator: ConditionOperator::Equals(ConditionValue::Boolean(false)),
+            }],
+        ));
+        let transformers_vec = vec![transformer];
+        let transformers = TransformerSelector::new(&transformers_vec, true);
+
+        // the internal users are not transformed
+        let transformed_doc = recursively_transform_document(
+            "test.users".to_string(),
+            doc.clone(),
+            &transformers,
+            &HashSet::new(),
+            &row,
+        );
+        assert_eq!(transformed_doc, doc);
+
+        let doc = doc! {
+            "email": "john.doe@company.com",
+            "profile": { "is_internal": false },
+        };
+        let mut row = vec![];
+        document_columns("", &doc, &mut row);
+
+        let transformed_doc = recursively_transform_document(
+            "test.users".to_string(),
+            doc.clone(),
+            &transformers,
+            &HashSet::new(),
+            &row,
+        );
+        assert_ne!(
+            transformed_doc.get_str("email").unwrap(),
+            "john.doe@company.com"
+        );
+    }
+
+    #[test]
+    fn typed_document_transform() {
+        let doc = doc! {
+            "email": "john.doe@company.com",
+            "age": 42,
+            "is_internal": true,
+        };
+
+        let set_null: Box<dyn Transformer> =
+            Box::new(SetNullTransformer::new("test", "users", "email"));
+        let set_constant: Box<dyn Transformer> = Box::new(SetConstantTransformer::new(
+            "test",
+            "users",
+            "is_internal",
+            SetConstantTransformerOptions {
+                value: ConstantValue::Boolean(false),
+            },
+        ));
+        let set_age: Box<dyn Transformer> = Box::new(SetConstantTransformer::new(
+            "test",
+            "users",
+            "age",
+            SetConstantTransformerOptions {
+                value: ConstantValue::Number(18),
+            },
+        ));
+        let transformers_vec = vec![set_null, set_constant, set_age];
+        let transformers = TransformerSelector::new(&transformers_vec, true);
+
+        let transformed_doc = recursively_transform_document(
+            "test.users".to_string(),
+            doc,
+            &transformers,
+            &HashSet::new(),
+            &[],
+        );
+        assert_eq!(
+            transformed_doc,
+            doc! {
+                "email": Bson::Null,
+                "age": 18,
+                "is_internal": false,
+            }
+        );
+        // the numbers keep their type
+        assert_eq!(transformed_doc.get("age"), Some(&Bson::Int32(18)));
+    }
+
+    #[test]
+    fn field_paths() {
+        assert_eq!(field_path("contacts[*].email"), "contacts.$[].email");
+        assert_eq!(field_path("contacts[0].email"), "contacts.0.email");
+        assert_eq!(field_path("matrix[*][1]"), "matrix.$[].1");
+        assert_eq!(
+            field_path("profile.address.street"),
+            "profile.address.street"
+        );
+        assert_eq!(field_path("contacts.$[].email"), "contacts.$[].email");
+        assert_eq!(field_path("contacts[first].email"), "contacts[first].email");
+        assert_eq!(field_path("/contacts[0-9]/"), "/contacts[0-9]/");
+    }
+
+    #[test]
+    fn nested_document_transform() {
+        let doc = doc! {
+            "profile": {
+                "address": {
+                    "street": "1 Main Street",
+                    "country": "FR",
+                },
+            },
+            "contacts": [
+                { "email": "john.doe@company.com", "phone_number": "123456" },
+                { "email": "jane.doe@company.com", "phone_number": "654321" },
+            ],
+            "tags": ["a", "b"],
+            "phone_numbers": ["123456", "654321"],
+        };
+
+        let set_constant = |column_name: &str| -> Box<dyn Transformer> {
+            Box::new(SetConstantTransformer::new(
+                "test",
+                "users",
+                column_name,
+                SetConstantTransformerOptions {
+                    value: ConstantValue::String("redacted".to_string()),
+                },
+            ))
+        };
+        let transformers_vec = vec![
+            set_constant("profile.address.street"),
+            set_constant("contacts[*].email"),
+            set_constant("phone_numbers[1]"),
+            set_constant("tags[*]"),
+        ];
+        let transformers_vec = field_path_transformers(&transformers_vec);
+        let wildcard_keys = find_all_keys_with_array_wildcard_op(&transformers_vec);
+        let transformers = TransformerSelector::new(&transformers_vec, true);
+
+        let transformed_doc = recursively_transform_document(
+            "test.users".to_string(),
+            doc,
+            &transformers,
+            &wildcard_keys,
+            &[],
+        );
+        assert_eq!(
+            transformed_doc,
+            doc! {
+                "profile": {
+                    "address": {
+                        "street": "redacted",
+                        "country": "FR",
+                    },
+                },
+                "contacts": [
+                    { "email": "redacted", "phone_number": "123456" },
+                    { "email": "redacted", "phone_number": "654321" },
+                ],
+                "tags": ["redacted", "redacted"],
+                "phone_numbers": ["123456", "redacted"],
+            }
+        );
+    }
+
+    #[test]
+    fn subset_archive() {
+        // a single collection "Users" in db "test2" with a single document: {name: "John", age: 42}
+        let dump_str = "6de299816600000010636f6e63757272656e745f636f6c6c656374696f6e7300040000000276657273696f6e0004000000302e3100027365727665725f76657273696f6e0006000000352e302e360002746f6f6c5f76657273696f6e00080000003130302e352e32000003010000026462000600000074657374320002636f6c6c656374696f6e0006000000557365727300026d6574616461746100ad0000007b22696e6465786573223a5b7b2276223a7b22246e756d626572496e74223a2232227d2c226b6579223a7b225f6964223a7b22246e756d626572496e74223a2231227d7d2c226e616d65223a225f69645f227d5d2c2275756964223a223732306531616132326231373435643739663139373530626162323933303837222c22636f6c6c656374696f6e4e616d65223a225573657273222c2274797065223a22636f6c6c656374696f6e227d001073697a6500000000000274797065000b000000636f6c6c656374696f6e0000ffffffff3c000000026462000600000074657374320002636f6c6c656374696f6e000600000055736572730008454f46000012435243000000000000000000002e000000075f696400623f23928e7f1feed4d5e3e1026e616d6500050000004a6f686e0010616765002a00000000ffffffff3c000000026462000600000074657374320002636f6c6c656374696f6e000600000055736572730008454f4600011243524300ff2a87dec3c86e6e00ffffffff";
+        let archive = || {
+            let hexdump = decode_hex(dump_str).unwrap();
+            Archive::from_reader(BufReader::new(hexdump.as_slice())).unwrap()
+        };
+
+        let documents = |predicate: &str| {
+            let subset_config = serde_yaml::from_str::<DatabaseSubsetConfig>(
+                format!(
+                    "database: test2
+table: Users
+strategy_name: where
+strategy_options:
+  where: {}",
+                    predicate
+                )
+                .as_str(),
+            )
+            .unwrap();
+
+            let mut archive = archive();
+            subset(&mut archive, &subset_config).unwrap();
+
+            let mut documents = 0;
+            archive.alter_docs(|prefixed_collections| {
+                documents = prefixed_collections["test2.Users"].len();
+            });
+            documents
+        };
+
+        assert_eq!(documents("age > 40"), 1);
+        assert_eq!(documents("age > 50"), 0);
+    }
+
+    #[test]
+    fn streamed_archive() {
+        // a single collection "Users" in db "test2" with a single document: {name: "John", age: 42}
+        let hexdump = decode_hex("6de299816600000010636f6e63757272656e745f636f6c6c656374696f6e7300040000000276657273696f6e0004000000302e3100027365727665725f76657273696f6e0006000000352e302e360002746f6f6c5f76657273696f6e00080000003130302e352e32000003010000026462000600000074657374320002636f6c6c656374696f6e0006000000557365727300026d6574616461746100ad0000007b22696e6465786573223a5b7b2276223a7b22246e756d626572496e74223a2232227d2c226b6579223a7b225f6964223a7b22246e756d626572496e74223a2231227d7d2c226e616d65223a225f69645f227d5d2c2275756964223a223732306531616132326231373435643739663139373530626162323933303837222c22636f6c6c656374696f6e4e616d65223a225573657273222c2274797065223a22636f6c6c656374696f6e227d001073697a6500000000000274797065000b000000636f6c6c656374696f6e0000ffffffff3c000000026462000600000074657374320002636f6c6c656374696f6e000600000055736572730008454f46000012435243000000000000000000002e000000075f696400623f23928e7f1feed4d5e3e1026e616d6500050000004a6f686e0010616765002a00000000ffffffff3c000000026462000600000074657374320002636f6c6c656374696f6e000600000055736572730008454f4600011243524300ff2a87dec3c86e6e00ffffffff").unwrap();
+
+        let set_name: Box<dyn Transformer> = Box::new(SetConstantTransformer::new(
+            "test2",
+            "Users",
+            "name",
+            SetConstantTransformerOptions {
+                value: ConstantValue::String("Jane".to_string()),
+            },
+        ));
+        let transformers = vec![set_name];
+
+        let read = |data: &[u8]| {
+            let source_options = SourceOptions {
+                transformers: &transformers,
+                skip_config: &vec![],
+                database_subset: &None,
+                only_tables: &vec![],
+                filters: &vec![],
+                encoding: None,
+                skip_large_objects: false,
+                skip_bytea: false,
+            };
+
+            // the dumps are written with a new line after each query
+            let mut dump = vec![];
+            read_and_transform(BufReader::new(data), source_options, |_, query| {
+                dump.extend_from_slice(query.data());
+                dump.push(b'\n');
+            })
+            .unwrap();
+
+            let archive = Archive::from_reader(BufReader::new(dump.as_slice())).unwrap();
+            let mut name = String::new();
+            archive.clone().alter_docs(|prefixed_collections| {
+                name = prefixed_collections["test2.Users"][0]
+                    .get_str("name")
+                    .unwrap()
+                    .to_string();
+            });
+            assert_eq!(dump[dump.len() - 1], b'\n');
+            name
+        };
+
+        assert_eq!(read(&hexdump), "Jane");
+
+        // mongodump --archive --gzip
+        let mut encoder = GzEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(&hexdump).unwrap();
+        assert_eq!(read(&encoder.finish().unwrap()), "Jane");
+    }
+
+    #[test]
+    fn split_queries() {
+        let mut query = b"ab\ncd\nef".to_vec();
+        assert_eq!(take_query(&mut query), Some(b"ab\ncd".to_vec()));
+        assert_eq!(query, b"ef".to_vec());
+        assert_eq!(take_query(&mut query), None);
+        assert_eq!(query, b"ef".to_vec());
+    }
 }
@@ -1,6 +1,7 @@
-use std::io::{stdin, BufReader, Error};
+use std::io::{stdin, BufReader, Read};
 
 use crate::connector::Connector;
+use crate::errors::ReplibyteError;
 use crate::source::mongodb::read_and_transform;
 use crate::types::{OriginalQuery, Query};
 use crate::Source;
@@ -15,7 +16,7 @@ impl Default for MongoDBStdin {
 }
 
 impl Connector for MongoDBStdin {
-    fn init(&mut self) -> Result<(), Error> {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
         Ok(())
     }
 }
@@ -25,14 +26,19 @@ impl Source for MongoDBStdin {
         &self,
         options: SourceOptions,
         query_callback: F,
-    ) -> Result<(), Error> {
-        let reader = BufReader::new(stdin());
+    ) -> Result<(), ReplibyteError> {
+        read_dump(stdin(), options, query_callback)
+    }
+}
 
-        if let Some(_database_subset) = &options.database_subset {
-            todo!("database subset not supported yet for MongoDB source")
-        }
+/// read a MongoDB archive and transform its documents
+pub fn read_dump<R: Read, F: FnMut(OriginalQuery, Query)>(
+    reader: R,
+    options: SourceOptions,
+    query_callback: F,
+) -> Result<(), ReplibyteError> {
+    let reader = BufReader::new(reader);
 
-        let _ = read_and_transform(reader, options, query_callback)?;
-        Ok(())
-    }
+    let _ = read_and_transform(reader, options, query_callback)?;
+    Ok(())
 }
@@ -0,0 +1,524 @@
+use std::collections::HashSet;
+use std::io::{BufReader, Read};
+use std::process::{Command, Stdio};
+
+use dump_parser::mssql::{
+    get_column_names_from_insert_into_query, get_column_values_from_insert_into_query,
+    get_table_name_from_create_table_query, get_table_name_from_insert_into_query,
+    get_tokens_from_query_str, TableName, Value,
+};
+use dump_parser::utils::{list_sql_queries_from_dump_reader, ListQueryResult};
+
+use crate::connector::Connector;
+use crate::errors::ReplibyteError;
+use crate::source::row_filter::RowFilters;
+use crate::source::Source;
+use crate::transformer::selector::TransformerSelector;
+use crate::types::{Column, OriginalQuery, Query};
+use crate::utils::{binary_exists, wait_for_command};
+
+use super::SourceOptions;
+
+/// default schema of SQL Server, used when a query does not specify one
+const DEFAULT_SCHEMA: &str = "dbo";
+
+/// SQL Server has no tool to dump a database into SQL statements, so this script generates one statement per line:
+/// `DROP TABLE`, `CREATE TABLE` (columns, identity and primary key) and one `INSERT INTO` per row.
+/// Foreign keys, indexes and computed columns are not dumped.
+/// `{filters}` is replaced by the conditions on the tables to dump.
+const DUMP_SCRIPT: &str = r#"SET NOCOUNT ON;
+DECLARE @schema sysname, @table sysname, @object_id int, @name nvarchar(max), @columns nvarchar(max), @values nvarchar(max), @sql nvarchar(max);
+DECLARE tables_cursor CURSOR LOCAL FAST_FORWARD FOR
+    SELECT s.name, t.name, t.object_id FROM sys.tables t INNER JOIN sys.schemas s ON s.schema_id = t.schema_id
+    WHERE t.is_ms_shipped = 0 {filters} ORDER BY s.name, t.name;
+OPEN tables_cursor;
+FETCH NEXT FROM tables_cursor INTO @schema, @table, @object_id;
+WHILE @@FETCH_STATUS = 0
+BEGIN
+    SET @name = QUOTENAME(@schema) + N'.' + QUOTENAME(@table);
+    SELECT N'DROP TABLE IF EXISTS ' + @name + N';';
+    SELECT N'CREATE TABLE ' + @name + N' (' + STRING_AGG(CAST(QUOTENAME(c.name) + N' ' + ty.name
+        + CASE
+            WHEN ty.name IN (N'varchar', N'char', N'varbinary', N'binary') THEN N'(' + IIF(c.max_length = -1, N'max', CAST(c.max_length AS nvarchar(10))) + N')'
+            WHEN ty.name IN (N'nvarchar', N'nchar') THEN N'(' + IIF(c.max_length = -1, N'max', CAST(c.max_length / 2 AS nvarchar(10))) + N')'
+            WHEN ty.name IN (N'decimal', N'numeric') THEN N'(' + CAST(c.precision AS nvarchar(10)) + N',' + CAST(c.scale AS nvarchar(10)) + N')'
+            WHEN ty.name IN (N'datetime2', N'time', N'datetimeoffset') THEN N'(' + CAST(c.scale AS nvarchar(10)) + N')'
+            ELSE N''
+        END
+        + IIF(c.is_identity = 1, N' IDENTITY(' + CAST(IDENT_SEED(@name) AS nvarchar(40)) + N',' + CAST(IDENT_INCR(@name) AS nvarchar(40)) + N')', N'')
+        + IIF(c.is_nullable = 1, N' NULL', N' NOT NULL') AS nvarchar(max)), N', ') WITHIN GROUP (ORDER BY c.column_id)
+        + ISNULL((SELECT N', PRIMARY KEY (' + STRING_AGG(QUOTENAME(pc.name), N', ') WITHIN GROUP (ORDER BY ic.key_ordinal) + N')'
+            FROM sys.indexes i
+            INNER JOIN sys.index_columns ic ON ic.object_id = i.object_id AND ic.index_id = i.index_id
+            INNER JOIN sys.columns pc ON pc.object_id = ic.object_id AND pc.column_id = ic.column_id
+            WHERE i.object_id = @object_id AND i.is_primary_key = 1), N'')
+        + N');'
+    FROM sys.columns c INNER JOIN sys.types ty ON ty.user_type_id = c.system_type_id
+    WHERE c.object_id = @object_id AND c.is_computed = 0;
+    SELECT @columns = STRING_AGG(CAST(QUOTENAME(c.name) AS nvarchar(max)), N', ') WITHIN GROUP (ORDER BY c.column_id),
+        @values = STRING_AGG(CAST(CASE
+            WHEN ty.name IN (N'float', N'real') THEN N'ISNULL(CONVERT(nvarchar(max), ' + QUOTENAME(c.name) + N', 3), N''NULL'')'
+            WHEN ty.name IN (N'bit', N'tinyint', N'smallint', N'int', N'bigint', N'decimal', N'numeric', N'money', N'smallmoney') THEN N'ISNULL(CONVERT(nvarchar(max), ' + QUOTENAME(c.name) + N'), N''NULL'')'
+            WHEN ty.name IN (N'binary', N'varbinary', N'image') THEN N'ISNULL(CONVERT(nvarchar(max), CONVERT(varbinary(max), ' + QUOTENAME(c.name) + N'), 1), N''NULL'')'
+            WHEN ty.name IN (N'date', N'time', N'datetime', N'datetime2', N'smalldatetime', N'datetimeoffset') THEN N'ISNULL(N''N'''''' + CONVERT(nvarchar(max), ' + QUOTENAME(c.name) + N', 126) + N'''''''', N''NULL'')'
+            ELSE N'ISNULL(N''N'''''' + REPLACE(CONVERT(nvarchar(max), ' + QUOTENAME(c.name) + N'), N'''''''', N'''''''''''''') + N'''''''', N''NULL'')'
+        END AS nvarchar(max)), N' + N'', '' + ') WITHIN GROUP (ORDER BY c.column_id)
+    FROM sys.columns c INNER JOIN sys.types ty ON ty.user_type_id = c.system_type_id
+    WHERE c.object_id = @object_id AND c.is_computed = 0 AND ty.name <> N'timestamp';
+    SET @sql = N'SELECT N''INSERT INTO ' + REPLACE(@name, N'''', N'''''') + N' (' + REPLACE(@columns, N'''', N'''''') + N') VALUES ('' + ' + @values + N' + N'');'' FROM ' + @name + N';';
+    IF OBJECTPROPERTY(@object_id, 'TableHasIdentity') = 1 SELECT N'SET IDENTITY_INSERT ' + @name + N' ON;';
+    EXEC sp_executesql @sql;
+    IF OBJECTPROPERTY(@object_id, 'TableHasIdentity') = 1 SELECT N'SET IDENTITY_INSERT ' + @name + N' OFF;';
+    FETCH NEXT FROM tables_cursor INTO @schema, @table, @object_id;
+END
+CLOSE tables_cursor;
+DEALLOCATE tables_cursor;"#;
+
+pub struct MSSQL<'a> {
+    host: &'a str,
+    port: u16,
+    database: &'a str,
+    username: &'a str,
+    password: &'a str,
+}
+
+impl<'a> MSSQL<'a> {
+    pub fn new(
+        host: &'a str,
+        port: u16,
+        database: &'a str,
+        username: &'a str,
+        password: &'a str,
+    ) -> Self {
+        MSSQL {
+            host,
+            port,
+            database,
+            username,
+            password,
+        }
+    }
+}
+
+impl<'a> Connector for MSSQL<'a> {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        binary_exists("sqlcmd")
+    }
+}
+
+impl<'a> Source for MSSQL<'a> {
+    fn read<F: FnMut(OriginalQuery, Query)>(
+        &self,
+        options: SourceOptions,
+        query_callback: F,
+    ) -> Result<(), ReplibyteError> {
+        let server = format!("{},{}", self.host, self.port);
+        let script = dump_script(&options);
+
+        let mut process = Command::new("sqlcmd")
+            .env("SQLCMDPASSWORD", self.password)
+            .args([
+                "-S",
+                server.as_str(),
+                "-U",
+                self.username,
+                "-d",
+                self.database,
+                "-b", // exit with an error code if the script fails
+                "-r",
+                "1", // print the errors on stderr
+                "-h",
+                "-1", // no column headers
+                "-y",
+                "0", // do not truncate the generated statements
+                "-Q",
+                script.as_str(),
+            ])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let stdout = process.stdout.take().ok_or_else(|| {
+            ReplibyteError::Command("Could not capture standard output.".to_string())
+        })?;
+
+        let reader = BufReader::new(stdout);
+
+        read_and_transform(reader, options, query_callback);
+
+        wait_for_command(&mut process)
+    }
+}
+
+/// return the dump script with the skipped and the only tables conditions
+fn dump_script(options: &SourceOptions) -> String {
+    let table_names = |tables: Vec<(&String, &String)>| {
+        tables
+            .into_iter()
+            .map(|(schema, table)| format!("{}.{}", schema, table).replace('\'', "''"))
+            .map(|table_name| format!("N'{}'", table_name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut filters = String::new();
+
+    if !options.skip_config.is_empty() {
+        filters.push_str(
+            format!(
+                "AND s.name + N'.' + t.name NOT IN ({}) ",
+                table_names(
+                    options
+                        .skip_config
+                        .iter()
+                        .map(|cfg| (&cfg.database, &cfg.table))
+                        .collect()
+                )
+            )
+            .as_str(),
+        );
+    }
+
+    if !options.only_tables.is_empty() {
+        filters.push_str(
+            format!(
+                "AND s.name + N'.' + t.name IN ({}) ",
+                table_names(
+                    options
+                        .only_tables
+                        .iter()
+                        .map(|cfg| (&cfg.database, &cfg.table))
+                        .collect()
+                )
+            )
+            .as_str(),
+        );
+    }
+
+    DUMP_SCRIPT.replace("{filters}", filters.trim_end())
+}
+
+/// consume reader and apply transformation on INSERT INTO queries if needed.
+/// The schema of a table is used as its database name.
+pub fn read_and_transform<R: Read, F: FnMut(OriginalQuery, Query)>(
+    reader: BufReader<R>,
+    options: SourceOptions,
+    mut query_callback: F,
+) {
+    // Transformer by schema_name.table_name.column_name
+    let transformer_by_db_and_table_and_column_name =
+        TransformerSelector::new(options.transformers, true);
+    let row_filters = RowFilters::new(options.filters, true);
+
+    let skip_tables = options
+        .skip_config
+        .iter()
+        .map(|skip| format!("{}.{}", skip.database, skip.table))
+        .collect::<HashSet<_>>();
+
+    match list_sql_queries_from_dump_reader(reader, |query| {
+        let tokens = get_tokens_from_query_str(query);
+
+        if let Some(table_name) = get_table_name_from_create_table_query(&tokens) {
+            if skip_tables.contains(&to_database_and_table_name(&table_name)) {
+                return ListQueryResult::Continue;
+            }
+        }
+
+        match get_table_name_from_insert_into_query(&tokens) {
+            Some(table_name) if skip_tables.contains(&to_database_and_table_name(&table_name)) => {
+                // skip the rows of this table
+            }
+            Some(table_name) => {
+                let column_names = get_column_names_from_insert_into_query(&tokens);
+
+                let transformed_query = match get_column_values_from_insert_into_query(&tokens) {
+                    Some(values) if column_names.len() == values.len() => {
+                        // the filtered rows are not dumped
+                        let row = to_row(&column_names, &values);
+                        if row_filters.excludes(&to_database_and_table_name(&table_name), &row) {
+                            return ListQueryResult::Continue;
+                        }
+
+                        transform_values(
+                            &table_name,
+                            column_names,
+                            values,
+                            &transformer_by_db_and_table_and_column_name,
+                        )
+                    }
+                    // the row can't be decoded, keep it as it is unless all the rows are filtered
+                    _ if row_filters.excludes_all(&to_database_and_table_name(&table_name)) => {
+                        return ListQueryResult::Continue;
+                    }
+                    _ => None,
+                };
+
+                match transformed_query {
+                    Some(transformed_query) => {
+                        query_callback(Query(query.as_bytes().to_vec()), transformed_query)
+                    }
+                    None => no_change_query_callback(&mut query_callback, query),
+                }
+            }
+            None => no_change_query_callback(&mut query_callback, query),
+        }
+
+        ListQueryResult::Continue
+    }) {
+        Ok(_) => {}
+        Err(err) => panic!("{:?}", err),
+    }
+}
+
+fn no_change_query_callback<F: FnMut(OriginalQuery, Query)>(query_callback: &mut F, query: &str) {
+    query_callback(
+        // there is no diff between the original and the modified one
+        Query(query.as_bytes().to_vec()),
+        Query(query.as_bytes().to_vec()),
+    );
+}
+
+fn to_database_and_table_name(table_name: &TableName) -> String {
+    format!(
+        "{}.{}",
+        table_name.schema.as_deref().unwrap_or(DEFAULT_SCHEMA),
+        table_name.value
+    )
+}
+
+/// apply the transformers on the values of a row. Return None if no transformer is defined for this table,
+/// then the original query is kept to not alter the values.
+fn transform_values(
+    table_name: &TableName,
+    column_names: Vec<String>,
+    values: Vec<Value>,
+    transformer_by_db_and_table_and_column_name: &TransformerSelector,
+) -> Option<Query> {
+    let database_and_table_name = to_database_and_table_name(table_name);
+    let transformers = column_names
+        .iter()
+        .map(|column_name| {
+            let key = format!("{}.{}", database_and_table_name, column_name);
+            let transformer = transformer_by_db_and_table_and_column_name.get(key.as_str());
+            (key, transformer)
+        })
+        .collect::<Vec<_>>();
+
+    if transformers
+        .iter()
+        .all(|(_, transformer)| transformer.is_none())
+    {
+        return None;
+    }
+
+    // the transformers get the original values of the row to check their conditions
+    let row = to_row(&column_names, &values);
+
+    let mut transformed_values = Vec::with_capacity(values.len());
+
+    for ((column_name, value), (key, transformer)) in
+        column_names.iter().zip(values).zip(transformers)
+    {
+        let value = match transformer {
+            Some(transformer) => {
+                let column = to_column(column_name, value)?;
+                // apply transformation on the column
+                to_value(transformer.transform_column(key.as_str(), column, &row))
+            }
+            None => value,
+        };
+
+        transformed_values.push(value);
+    }
+
+    Some(to_query(table_name, column_names, transformed_values))
+}
+
+fn to_row(column_names: &[String], values: &[Value]) -> Vec<Column> {
+    column_names
+        .iter()
+        .zip(values.iter())
+        .filter_map(|(column_name, value)| to_column(column_name, value.clone()))
+        .collect()
+}
+
+fn to_column(column_name: &str, value: Value) -> Option<Column> {
+    let column_name = column_name.to_string();
+
+    let column = match value {
+        Value::Number(value) if value.contains('.') || value.to_uppercase().contains('E') => {
+            Column::FloatNumberValue(column_name, value.parse::<f64>().ok()?)
+        }
+        Value::Number(value) => Column::NumberValue(column_name, value.parse::<i128>().ok()?),
+        Value::String(value) => Column::StringValue(column_name, value),
+        Value::Binary(value) => Column::StringValue(column_name, value),
+        Value::Null => Column::None(column_name),
+    };
+
+    Some(column)
+}
+
+fn to_value(column: Column) -> Value {
+    match column {
+        Column::NumberValue(_, value) => Value::Number(value.to_string()),
+        Column::FloatNumberValue(_, value) => Value::Number(value.to_string()),
+        Column::StringValue(_, value) => Value::String(value),
+        Column::CharValue(_, value) => Value::String(value.to_string()),
+        // SQL Server stores booleans as bits
+        Column::BooleanValue(_, value) => Value::Number((value as u8).to_string()),
+        Column::None(_) => Value::Null,
+    }
+}
+
+fn to_query(table_name: &TableName, column_names: Vec<String>, values: Vec<Value>) -> Query {
+    let query_string = format!(
+        "INSERT INTO {} ({}) VALUES ({});",
+        table_name.quoted(),
+        column_names
+            .iter()
+            .map(|column_name| dump_parser::mssql::quote_identifier(column_name))
+            .collect::<Vec<String>>()
+            .join(", "),
+        values
+            .iter()
+            .map(|value| match value {
+                Value::Number(value) => value.clone(),
+                Value::String(value) => format!("N'{}'", value.replace('\'', "''")),
+                Value::Binary(value) => format!("0x{}", value),
+                Value::Null => "NULL".to_string(),
+            })
+            .collect::<Vec<String>>()
+            .join(", "),
+    );
+
+    Query(query_string.into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use crate::config::{OnlyTablesConfig, SkipConfig};
+    use crate::source::mssql::{dump_script, read_and_transform};
+    use crate::source::SourceOptions;
+    use crate::transformer::redacted::{RedactedTransformer, RedactedTransformerOptions};
+    use crate::transformer::Transformer;
+
+    const DUMP: &str = r#"DROP TABLE IF EXISTS [dbo].[users];
+CREATE TABLE [dbo].[users] ([id] int IDENTITY(1,1) NOT NULL, [name] nvarchar(255) NULL, [avatar] varbinary(max) NULL, PRIMARY KEY ([id]));
+SET IDENTITY_INSERT [dbo].[users] ON;
+INSERT INTO [dbo].[users] ([id], [name], [avatar]) VALUES (1, N'Romaric', 0xCAFE);
+INSERT INTO [dbo].[users] ([id], [name], [avatar]) VALUES (2, N'Benny', NULL);
+SET IDENTITY_INSERT [dbo].[users] OFF;
+DROP TABLE IF EXISTS [audit].[logs];
+CREATE TABLE [audit].[logs] ([id] int NOT NULL, [message] nvarchar(max) NULL);
+INSERT INTO [audit].[logs] ([id], [message]) VALUES (1, N'user 1 logged in');
+"#;
+
+    fn read(transformers: Vec<Box<dyn Transformer>>, skip_config: Vec<SkipConfig>) -> Vec<String> {
+        let source_options = SourceOptions {
+            transformers: &transformers,
+            skip_config: &skip_config,
+            database_subset: &None,
+            only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
+        };
+
+        let mut queries = vec![];
+        let reader = BufReader::new(DUMP.as_bytes());
+        read_and_transform(reader, source_options, |_original_query, query| {
+            queries.push(String::from_utf8(query.data().to_vec()).unwrap());
+        });
+
+        // queries are prefixed by the new line separating them from the previous one
+        queries
+            .into_iter()
+            .map(|query| query.trim_start().to_string())
+            .filter(|query| query.starts_with("INSERT INTO") || query.starts_with("CREATE TABLE"))
+            .collect()
+    }
+
+    #[test]
+    fn keep_rows_without_transformers() {
+        let queries = read(vec![], vec![]);
+
+        assert_eq!(queries.len(), 5);
+        assert_eq!(
+            queries[1],
+            "INSERT INTO [dbo].[users] ([id], [name], [avatar]) VALUES (1, N'Romaric', 0xCAFE);"
+        );
+    }
+
+    #[test]
+    fn transform_rows() {
+        let t1: Box<dyn Transformer> = Box::new(RedactedTransformer::new(
+            "dbo",
+            "users",
+            "name",
+            RedactedTransformerOptions {
+                character: '*',
+                width: 4,
+            },
+        ));
+
+        let queries = read(vec![t1], vec![]);
+
+        assert_eq!(
+            queries[1],
+            "INSERT INTO [dbo].[users] ([id], [name], [avatar]) VALUES (1, N'Rom****', 0xCAFE);"
+        );
+        assert_eq!(
+            queries[2],
+            "INSERT INTO [dbo].[users] ([id], [name], [avatar]) VALUES (2, N'Ben****', NULL);"
+        );
+        assert_eq!(
+            queries[4],
+            "INSERT INTO [audit].[logs] ([id], [message]) VALUES (1, N'user 1 logged in');"
+        );
+    }
+
+    #[test]
+    fn skip_tables() {
+        let skip_config = vec![SkipConfig {
+            database: "audit".to_string(),
+            table: "logs".to_string(),
+        }];
+
+        let queries = read(vec![], skip_config);
+        assert_eq!(queries.len(), 3);
+        assert!(queries.iter().all(|query| !query.contains("[audit]")));
+    }
+
+    #[test]
+    fn dump_script_filters() {
+        let transformers = vec![];
+        let skip_config = vec![SkipConfig {
+            database: "dbo".to_string(),
+            table: "o'neil".to_string(),
+        }];
+        let only_tables = vec![OnlyTablesConfig {
+            database: "dbo".to_string(),
+            table: "users".to_string(),
+        }];
+
+        let script = dump_script(&SourceOptions {
+            transformers: &transformers,
+            skip_config: &skip_config,
+            database_subset: &None,
+            only_tables: &only_tables,
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
+        });
+
+        assert!(script.contains(
+            "WHERE t.is_ms_shipped = 0 AND s.name + N'.' + t.name NOT IN (N'dbo.o''neil') \
+AND s.name + N'.' + t.name IN (N'dbo.users') ORDER BY"
+        ));
+    }
+}
@@ -1,20 +1,32 @@
 use std::borrow::BorrowMut;
-use std::collections::HashMap;
-use std::io::{BufReader, Error, ErrorKind, Read};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
 use std::process::{Command, Stdio};
 
+use log::info;
+
 use dump_parser::mysql::Keyword::NoKeyword;
 use dump_parser::mysql::{
-    get_column_names_from_insert_into_query, get_column_values_from_insert_into_query,
+    escape_string, get_column_names_from_create_table_query,
+    get_column_names_from_insert_into_query, get_rows_from_insert_into_query,
     get_single_quoted_string_value_at_position, get_tokens_from_query_str,
-    match_keyword_at_position, Keyword, Token,
+    match_keyword_at_position, to_sql_value, Keyword, Token,
 };
-use dump_parser::utils::{list_sql_queries_from_dump_reader, ListQueryResult};
+use dump_parser::utils::{list_mysql_queries_from_dump_reader, ListQueryResult};
+use subset::mysql::MysqlSubset;
+use subset::{PassthroughTable, Subset, SubsetOptions};
 
+use crate::config::{
+    DatabaseSubsetConfig, DumpConsistency, OnlyTablesConfig, SkipConfig, TlsConfig,
+};
 use crate::connector::Connector;
+use crate::errors::ReplibyteError;
+use crate::source::encoding::{utf8_reader, Dialect};
+use crate::source::row_filter::RowFilters;
 use crate::source::Source;
-use crate::transformer::Transformer;
-use crate::types::{Column, InsertIntoQuery, OriginalQuery, Query};
+use crate::transformer::selector::TransformerSelector;
+use crate::types::{Column, OriginalQuery, Query};
 use crate::utils::{binary_exists, wait_for_command};
 
 use super::SourceOptions;
@@ -32,6 +44,8 @@ pub struct Mysql<'a> {
     database: &'a str,
     username: &'a str,
     password: &'a str,
+    consistency: DumpConsistency,
+    tls: &'a TlsConfig,
 }
 
 impl<'a> Mysql<'a> {
@@ -41,6 +55,8 @@ impl<'a> Mysql<'a> {
         database: &'a str,
         username: &'a str,
         password: &'a str,
+        consistency: DumpConsistency,
+        tls: &'a TlsConfig,
     ) -> Self {
         Self {
             host,
@@ -48,13 +64,22 @@ impl<'a> Mysql<'a> {
             database,
             username,
             password,
+            consistency,
+            tls,
         }
     }
 }
 
 impl<'a> Connector for Mysql<'a> {
-    fn init(&mut self) -> Result<(), Error> {
-        binary_exists("mysqldump")
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        let _ = consistency_args(self.consistency)?;
+        let _ = binary_exists("mysqldump")?;
+
+        match self.consistency {
+            // the engines of the tables are checked with mysql
+            DumpConsistency::SingleTransaction => binary_exists("mysql"),
+            _ => Ok(()),
+        }
     }
 }
 
@@ -63,7 +88,7 @@ impl<'a> Source for Mysql<'a> {
         &self,
         options: SourceOptions,
         query_callback: F,
-    ) -> Result<(), Error> {
+    ) -> Result<(), ReplibyteError> {
         let s_port = self.port.to_string();
         let password = &format!("-p{}", self.password);
 
@@ -79,11 +104,12 @@ impl<'a> Source for Mysql<'a> {
             "--add-drop-table",    // add DROP TABLE statement before each CREATE TABLE statement
             "--skip-extended-insert", // have a row by INSERT INTO statement
             "--complete-insert",   // have column names in INSERT INTO rows
-            "--single-transaction", // https://dev.mysql.com/doc/refman/8.0/en/mysqldump.html#option_mysqldump_single-transaction
-            "--quick", // reads out large tables in a way that doesn't require having enough RAM to fit the full table in memory
-            self.database,
+            "--hex-blob", // have the binary values as hexadecimal literals, the dump is valid UTF-8
         ];
 
+        dump_args.append(&mut consistency_args(self.consistency)?);
+        dump_args.push(self.database);
+
         let ignore_tables_args: Vec<String> = options
             .skip_config
             .iter()
@@ -102,68 +128,282 @@ impl<'a> Source for Mysql<'a> {
 
         dump_args.append(&mut only_tables_args);
 
+        let tls_args = self.tls.mysql_args()?;
+        let mut tls_args: Vec<&str> = tls_args.iter().map(String::as_str).collect();
+
+        if self.consistency == DumpConsistency::SingleTransaction {
+            let mut connection_args = vec![
+                "-h",
+                self.host,
+                "-P",
+                s_port.as_str(),
+                "-u",
+                self.username,
+                password,
+            ];
+            connection_args.extend(tls_args.iter());
+            check_transactional_engines("mysql", &connection_args, self.database, options)?;
+        }
+
+        dump_args.append(&mut tls_args);
+
         let mut process = Command::new("mysqldump")
             .args(dump_args)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()?;
 
-        let stdout = process
-            .stdout
-            .take()
-            .ok_or_else(|| Error::new(ErrorKind::Other, "Could not capture standard output."))?;
+        let stdout = process.stdout.take().ok_or_else(|| {
+            ReplibyteError::Command("Could not capture standard output.".to_string())
+        })?;
 
-        let reader = BufReader::new(stdout);
-
-        read_and_transform(reader, options, query_callback);
+        match &options.database_subset {
+            None => {
+                let reader = utf8_reader(stdout, options.encoding, Dialect::Mysql)?;
+                read_and_transform(reader, options, query_callback)?;
+            }
+            Some(subset_config) => {
+                let dump_reader = utf8_reader(stdout, options.encoding, Dialect::Mysql)?;
+                let reader = subset(dump_reader, subset_config)?;
+                read_and_transform(reader, options, query_callback)?;
+            }
+        };
 
         wait_for_command(&mut process)
     }
 }
 
+/// options of mysqldump and mariadb-dump making a consistent dump, the rows are read one at a time
+/// (`--quick`) instead of loading the tables in memory
+pub fn consistency_args(consistency: DumpConsistency) -> Result<Vec<&'static str>, ReplibyteError> {
+    match consistency {
+        // https://dev.mysql.com/doc/refman/8.0/en/mysqldump.html#option_mysqldump_single-transaction
+        DumpConsistency::SingleTransaction => Ok(vec!["--single-transaction", "--quick"]),
+        DumpConsistency::LockTables => Ok(vec!["--lock-tables", "--quick"]),
+        DumpConsistency::SerializableDeferrable => Err(ReplibyteError::Config(
+            "`consistency: serializable_deferrable` is only supported by PostgreSQL, use `single_transaction`".to_string(),
+        )),
+    }
+}
+
+/// only the tables of the transactional engines are consistent in a single transaction, return an
+/// error listing the dumped tables of the other engines
+pub fn check_transactional_engines(
+    client: &str,
+    connection_args: &[&str],
+    database: &str,
+    options: SourceOptions,
+) -> Result<(), ReplibyteError> {
+    let query = format!(
+        "SELECT TABLE_NAME, ENGINE FROM information_schema.TABLES \
+        WHERE TABLE_SCHEMA = '{}' AND TABLE_TYPE = 'BASE TABLE' \
+        AND ENGINE NOT IN ('InnoDB', 'RocksDB', 'TokuDB')",
+        escape_string(database)
+    );
+
+    let output = Command::new(client)
+        .args(connection_args)
+        .args(["-N", "-B", "-e", query.as_str()]) // tab-separated rows, without the column names
+        .output()?;
+
+    if !output.status.success() {
+        return Err(ReplibyteError::Command(format!(
+            "command error: {}",
+            String::from_utf8_lossy(output.stderr.as_slice()).trim()
+        )));
+    }
+
+    let tables = non_transactional_tables(
+        String::from_utf8_lossy(output.stdout.as_slice()).as_ref(),
+        database,
+        options.skip_config,
+        options.only_tables,
+    );
+
+    if tables.is_empty() {
+        return Ok(());
+    }
+
+    Err(ReplibyteError::Config(format!(
+        "the tables {} cannot be dumped in a single transaction, set `consistency: lock_tables` or skip them",
+        tables.join(", ")
+    )))
+}
+
+/// the `table\tengine` rows of the tables which are dumped
+fn non_transactional_tables(
+    output: &str,
+    database: &str,
+    skip_config: &[SkipConfig],
+    only_tables: &[OnlyTablesConfig],
+) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .filter(|(table, _)| {
+            !skip_config
+                .iter()
+                .any(|skip| skip.database == database && skip.table == *table)
+        })
+        .filter(|(table, _)| {
+            only_tables.is_empty() || only_tables.iter().any(|only| only.table == *table)
+        })
+        .map(|(table, engine)| format!("{} ({})", table, engine))
+        .collect()
+}
+
+/// The tables of a MySQL dump have no database, the `database` of the subset configuration is ignored.
+pub fn subset<R: Read>(
+    dump_reader: BufReader<R>,
+    subset_config: &DatabaseSubsetConfig,
+) -> Result<BufReader<File>, ReplibyteError> {
+    let mut named_temp_file = tempfile::NamedTempFile::new()?;
+    let temp_dump_file = named_temp_file.as_file_mut();
+    split_extended_insert_into_queries(dump_reader, temp_dump_file)?;
+
+    let strategies = subset_config.strategies()?;
+
+    let empty_vec = Vec::new();
+    let passthrough_tables = subset_config
+        .passthrough_tables
+        .as_ref()
+        .unwrap_or(&empty_vec)
+        .iter()
+        .map(|table| PassthroughTable::new(subset_config.database.as_str(), table.as_str()))
+        .collect::<HashSet<_>>();
+
+    let subset_options = SubsetOptions::new(&passthrough_tables, subset_config.seed);
+    let subset = MysqlSubset::new(named_temp_file.path(), strategies, subset_options)?;
+
+    let named_subset_file = tempfile::NamedTempFile::new()?;
+    let mut subset_file = named_subset_file.as_file();
+
+    let _ = subset.read(
+        |row| {
+            match subset_file.write(format!("{}\n", row).as_bytes()) {
+                Ok(_) => {}
+                Err(err) => {
+                    panic!("{}", err)
+                }
+            };
+        },
+        |progress| {
+            info!("Database subset completion: {}%", progress.percent());
+        },
+    )?;
+
+    Ok(BufReader::new(
+        File::open(named_subset_file.path()).unwrap(),
+    ))
+}
+
+/// Write the dump with an INSERT INTO query by row: the subset picks the rows of a table query by query.
+fn split_extended_insert_into_queries<R: Read, W: Write>(
+    dump_reader: BufReader<R>,
+    writer: &mut W,
+) -> Result<(), ReplibyteError> {
+    let mut result = Ok(());
+
+    list_mysql_queries_from_dump_reader(dump_reader, |query| {
+        if query == "\n" {
+            return ListQueryResult::Continue;
+        }
+
+        let tokens = get_tokens_from_query_str(query);
+        let rows = get_rows_from_insert_into_query(&tokens);
+
+        let queries = match get_row_type(&tokens) {
+            RowType::InsertInto { table_name } if rows.len() > 1 => {
+                let column_names = get_column_names_from_insert_into_query(&tokens);
+                rows.iter()
+                    .map(|values| {
+                        let values = values.iter().map(to_sql_value).collect::<Vec<_>>();
+                        to_query(table_name.as_str(), &column_names, &[values]).0
+                    })
+                    .collect()
+            }
+            _ => vec![query.as_bytes().to_vec()],
+        };
+
+        for query in queries {
+            if let Err(err) = writer
+                .write_all(query.as_slice())
+                .and_then(|_| writer.write_all(b"\n"))
+            {
+                result = Err(ReplibyteError::from(err));
+                return ListQueryResult::Break;
+            }
+        }
+
+        ListQueryResult::Continue
+    })?;
+
+    result
+}
+
 pub fn read_and_transform<R: Read, F: FnMut(OriginalQuery, Query)>(
     reader: BufReader<R>,
     options: SourceOptions,
     mut query_callback: F,
-) {
-    // create a map variable with Transformer by column_name
-    let mut transformer_by_db_and_table_and_column_name: HashMap<String, &Box<dyn Transformer>> =
-        HashMap::with_capacity(options.transformers.len());
-
-    for transformer in options.transformers {
-        let _ = transformer_by_db_and_table_and_column_name
-            .insert(transformer.table_and_column_name(), transformer);
-    }
-
-    match list_sql_queries_from_dump_reader(reader, |query| {
+) -> Result<(), ReplibyteError> {
+    // Transformer by table_name.column_name
+    let transformer_by_db_and_table_and_column_name =
+        TransformerSelector::new(options.transformers, false);
+    let row_filters = RowFilters::new(options.filters, false);
+    // column names by table name, for the INSERT INTO queries without column names
+    let mut column_names_by_table_name = HashMap::<String, Vec<String>>::new();
+
+    list_mysql_queries_from_dump_reader(reader, |query| {
         let tokens = get_tokens_from_query_str(query);
 
         match get_row_type(&tokens) {
             RowType::InsertInto { table_name } => {
-                let (original_columns, columns) = transform_columns(
-                    table_name.as_str(),
-                    &tokens,
-                    &transformer_by_db_and_table_and_column_name,
-                );
+                let query_column_names = get_column_names_from_insert_into_query(&tokens);
+                let column_names = if query_column_names.is_empty() {
+                    column_names_by_table_name
+                        .get(table_name.as_str())
+                        .map(|column_names| column_names.iter().map(String::as_str).collect())
+                        .unwrap_or_default()
+                } else {
+                    query_column_names.clone()
+                };
+
+                // an extended INSERT has many rows, each one is transformed and filtered
+                let mut original_rows = vec![];
+                let mut rows = vec![];
+                for values in get_rows_from_insert_into_query(&tokens) {
+                    let (original_columns, columns) = transform_columns(
+                        table_name.as_str(),
+                        &column_names,
+                        &values,
+                        &transformer_by_db_and_table_and_column_name,
+                    );
+
+                    // the filtered rows are not dumped
+                    if row_filters.excludes(table_name.as_str(), &original_columns) {
+                        continue;
+                    }
+
+                    original_rows.push(values.iter().map(to_sql_value).collect());
+                    rows.push(columns);
+                }
+
+                if rows.is_empty() {
+                    return ListQueryResult::Continue;
+                }
 
                 query_callback(
-                    to_query(
-                        None,
-                        InsertIntoQuery {
-                            table_name: table_name.to_string(),
-                            columns: original_columns,
-                        },
-                    ),
-                    to_query(
-                        None,
-                        InsertIntoQuery {
-                            table_name: table_name.to_string(),
-                            columns,
-                        },
-                    ),
+                    to_query(table_name.as_str(), &query_column_names, &original_rows),
+                    to_query(table_name.as_str(), &query_column_names, &rows),
                 )
             }
-            RowType::CreateTable { table_name: _ } => {
+            RowType::CreateTable { table_name } => {
+                let column_names = get_column_names_from_create_table_query(&tokens)
+                    .into_iter()
+                    .map(str::to_string)
+                    .collect();
+                let _ = column_names_by_table_name.insert(table_name, column_names);
+
                 no_change_query_callback(query_callback.borrow_mut(), query);
             }
             RowType::Others => {
@@ -173,10 +413,9 @@ pub fn read_and_transform<R: Read, F: FnMut(OriginalQuery, Query)>(
         }
 
         ListQueryResult::Continue
-    }) {
-        Ok(_) => {}
-        Err(err) => panic!("{:?}", err),
-    }
+    })?;
+
+    Ok(())
 }
 
 fn no_change_query_callback<F: FnMut(OriginalQuery, Query)>(query_callback: &mut F, query: &str) {
@@ -187,51 +426,43 @@ fn no_change_query_callback<F: FnMut(OriginalQuery, Query)>(query_callback: &mut
     );
 }
 
+/// return the original columns of a row, and its values once transformed.
+/// A value is written as in the dump, unless a transformer is set for its column.
 fn transform_columns(
     table_name: &str,
-    tokens: &Vec<Token>,
-    transformer_by_db_and_table_and_column_name: &HashMap<String, &Box<dyn Transformer>>,
-) -> (Vec<Column>, Vec<Column>) {
-    // find database name by filtering out all queries starting with
-    // INSERT INTO `<table>` (...)
-    // INSERT       -> position 0
-    // INTO         -> position 2
-    // <table>      -> position 4
-    // L Paren      -> position X?
-    // R Paren      -> position X?
-    let column_names = get_column_names_from_insert_into_query(&tokens);
-    let column_values = get_column_values_from_insert_into_query(&tokens);
-
+    column_names: &[&str],
+    values: &[Token],
+    transformer_by_db_and_table_and_column_name: &TransformerSelector,
+) -> (Vec<Column>, Vec<String>) {
     let mut original_columns = vec![];
-    let mut columns = vec![];
 
-    for (i, column_name) in column_names.iter().enumerate() {
-        let value_token = column_values.get(i).unwrap();
+    for (i, value_token) in values.iter().enumerate() {
+        // the values of the unknown columns are not transformed
+        let column_name = column_names.get(i).copied().unwrap_or_default();
 
         let column = match value_token {
-            Token::Number(column_value, _) => {
-                if column_value.contains(".") {
-                    Column::FloatNumberValue(
-                        column_name.to_string(),
-                        column_value.parse::<f64>().unwrap(),
-                    )
-                } else {
-                    Column::NumberValue(
-                        column_name.to_string(),
-                        column_value.parse::<i128>().unwrap(),
-                    )
+            Token::Number(column_value, is_negative) => {
+                let column_value = match is_negative {
+                    false => column_value.clone(),
+                    true => format!("-{}", column_value),
+                };
+
+                match column_value.parse::<i128>() {
+                    Ok(value) => Column::NumberValue(column_name.to_string(), value),
+                    Err(_) => match column_value.parse::<f64>() {
+                        Ok(value) => Column::FloatNumberValue(column_name.to_string(), value),
+                        Err(_) => Column::StringValue(column_name.to_string(), column_value),
+                    },
                 }
             }
             Token::Char(column_value) => {
                 Column::CharValue(column_name.to_string(), column_value.clone())
             }
-            Token::SingleQuotedString(column_value) => {
-                Column::StringValue(column_name.to_string(), column_value.clone())
-            }
-            Token::NationalStringLiteral(column_value) => {
-                Column::StringValue(column_name.to_string(), column_value.clone())
-            }
-            Token::HexStringLiteral(column_value) => {
+            Token::SingleQuotedString(column_value)
+            | Token::NationalStringLiteral(column_value)
+            | Token::HexStringLiteral(column_value)
+            | Token::BinaryStringLiteral(column_value)
+            | Token::BitStringLiteral(column_value) => {
                 Column::StringValue(column_name.to_string(), column_value.clone())
             }
             Token::Word(w)
@@ -244,22 +475,28 @@ fn transform_columns(
             _ => Column::None(column_name.to_string()),
         };
 
-        // get the right transformer for the right column name
-        let original_column = column.clone();
-
-        let table_and_column_name = format!("{}.{}", table_name, *column_name);
+        original_columns.push(column);
+    }
 
-        let column =
+    // the transformers get the original values of the row to check their conditions
+    let values = original_columns
+        .iter()
+        .zip(values)
+        .map(|(column, value_token)| {
+            // get the right transformer for the right column name
+            let table_and_column_name = format!("{}.{}", table_name, column.name());
             match transformer_by_db_and_table_and_column_name.get(table_and_column_name.as_str()) {
-                Some(transformer) => transformer.transform(column), // apply transformation on the column
-                None => column,
-            };
-
-        original_columns.push(original_column);
-        columns.push(column);
-    }
+                Some(transformer) => to_column_sql_value(&transformer.transform_column(
+                    table_and_column_name.as_str(),
+                    column.clone(),
+                    &original_columns,
+                )), // apply transformation on the column
+                None => to_sql_value(value_token),
+            }
+        })
+        .collect();
 
-    (original_columns, columns)
+    (original_columns, values)
 }
 
 fn is_insert_into_statement(tokens: &Vec<Token>) -> bool {
@@ -294,54 +531,40 @@ fn get_row_type(tokens: &Vec<Token>) -> RowType {
     row_type
 }
 
-fn to_query(database: Option<&str>, query: InsertIntoQuery) -> Query {
-    let mut column_names = Vec::with_capacity(query.columns.len());
-    let mut values = Vec::with_capacity(query.columns.len());
-
-    for column in query.columns {
-        match column {
-            Column::NumberValue(column_name, value) => {
-                column_names.push(column_name);
-                values.push(value.to_string());
-            }
-            Column::FloatNumberValue(column_name, value) => {
-                column_names.push(column_name);
-                values.push(value.to_string());
-            }
-            Column::StringValue(column_name, value) => {
-                column_names.push(column_name);
-                values.push(format!("'{}'", value));
-            }
-            Column::CharValue(column_name, value) => {
-                column_names.push(column_name);
-                values.push(format!("'{}'", value));
-            }
-            Column::BooleanValue(column_name, value) => {
-                column_names.push(column_name);
-                values.push(value.to_string());
-            }
-            Column::None(column_name) => {
-                column_names.push(column_name);
-                values.push("NULL".to_string());
-            }
-        }
+fn to_column_sql_value(column: &Column) -> String {
+    match column {
+        Column::NumberValue(_, value) => value.to_string(),
+        Column::FloatNumberValue(_, value) => value.to_string(),
+        Column::StringValue(_, value) => format!("'{}'", escape_string(value)),
+        Column::CharValue(_, value) => format!("'{}'", escape_string(value.to_string().as_str())),
+        Column::BooleanValue(_, value) => value.to_string(),
+        Column::None(_) => "NULL".to_string(),
     }
+}
 
-    let query_prefix = match database {
-        Some(_database) => panic!("database should not be present in a MySQL dump"),
-        None => "INSERT INTO".to_string(),
+/// INSERT INTO query of the rows of a table, the column names are omitted if the dump does not have them
+fn to_query(table_name: &str, column_names: &[&str], rows: &[Vec<String>]) -> Query {
+    let column_names = if column_names.is_empty() {
+        String::new()
+    } else {
+        format!(
+            " ({})",
+            column_names
+                .iter()
+                .map(|column_name| format!("`{}`", column_name))
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
     };
 
     let query_string = format!(
-        "{} `{}` ({}) VALUES ({});",
-        query_prefix,
-        query.table_name.as_str(),
-        column_names
-            .iter()
-            .map(|column_name| format!("`{}`", column_name))
+        "INSERT INTO `{}`{} VALUES {};",
+        table_name,
+        column_names,
+        rows.iter()
+            .map(|values| format!("({})", values.join(", ")))
             .collect::<Vec<String>>()
-            .join(", "),
-        values.join(", "),
+            .join(","),
     );
 
     Query(query_string.into_bytes())
@@ -349,21 +572,64 @@ fn to_query(database: Option<&str>, query: InsertIntoQuery) -> Query {
 
 #[cfg(test)]
 mod tests {
+    use std::io::{BufReader, ErrorKind};
+
+    use crate::config::{
+        DumpConsistency, OnlyTablesConfig, RowFilterConfig, SkipConfig, TlsConfig,
+    };
     use crate::connector::Connector;
     use crate::source::mysql::{is_create_table_statement, is_insert_into_statement, RowType};
     use crate::source::SourceOptions;
+    use crate::transformer::keep_first_char::KeepFirstCharTransformer;
     use crate::transformer::{transient::TransientTransformer, Transformer};
     use crate::Source;
     use dump_parser::mysql::Tokenizer;
 
-    use super::{get_row_type, Mysql};
+    use super::{
+        consistency_args, get_row_type, non_transactional_tables, read_and_transform,
+        split_extended_insert_into_queries, Mysql,
+    };
+
+    const EXTENDED_INSERT_DUMP: &str = r#"CREATE TABLE `users` (
+  `id` int NOT NULL,
+  `name` varchar(255) NOT NULL,
+  `avatar` blob,
+  PRIMARY KEY (`id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+INSERT INTO `users` VALUES (1,'O\'Reilly; --',_binary '\0\'x'),(2,'Doe',NULL),(-3,'Zoë',0xFF);
+INSERT INTO `users` VALUES (2,'Doe',NULL);
+"#;
+
+    static TLS_CONFIG: TlsConfig = TlsConfig {
+        ssl_mode: None,
+        ssl_cert: None,
+        ssl_key: None,
+        ssl_root_cert: None,
+        parameters: Vec::new(),
+    };
 
     fn get_mysql() -> Mysql<'static> {
-        Mysql::new("127.0.0.1", 3306, "world", "root", "password")
+        Mysql::new(
+            "127.0.0.1",
+            3306,
+            "world",
+            "root",
+            "password",
+            DumpConsistency::SingleTransaction,
+            &TLS_CONFIG,
+        )
     }
 
     fn get_invalid_mysql() -> Mysql<'static> {
-        Mysql::new("127.0.0.1", 3306, "world", "root", "wrong_password")
+        Mysql::new(
+            "127.0.0.1",
+            3306,
+            "world",
+            "root",
+            "wrong_password",
+            DumpConsistency::SingleTransaction,
+            &TLS_CONFIG,
+        )
     }
 
     #[test]
@@ -378,6 +644,10 @@ mod tests {
             skip_config: &vec![],
             database_subset: &None,
             only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
         };
 
         assert!(p.read(source_options, |_original_query, _query| {}).is_ok());
@@ -390,6 +660,10 @@ mod tests {
             skip_config: &vec![],
             database_subset: &None,
             only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
         };
         assert!(p
             .read(source_options, |_original_query, _query| {})
@@ -406,6 +680,10 @@ mod tests {
             skip_config: &vec![],
             database_subset: &None,
             only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
         };
         let _ = p.read(source_options, |original_query, query| {
             assert!(original_query.data().len() > 0);
@@ -413,6 +691,94 @@ mod tests {
         });
     }
 
+    #[test]
+    fn transform_extended_insert_into_rows() {
+        let t1: Box<dyn Transformer> = Box::new(KeepFirstCharTransformer::new("", "users", "name"));
+        let transformers = vec![t1];
+        let filters = serde_yaml::from_str::<Vec<RowFilterConfig>>(
+            r#"
+- database: world
+  table: users
+  exclude:
+    - column: id
+      equals: 2
+"#,
+        )
+        .unwrap();
+        let source_options = SourceOptions {
+            transformers: &transformers,
+            skip_config: &vec![],
+            database_subset: &None,
+            only_tables: &vec![],
+            filters: &filters,
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
+        };
+
+        let mut inserts = vec![];
+        let reader = BufReader::new(EXTENDED_INSERT_DUMP.as_bytes());
+        read_and_transform(reader, source_options, |original_query, query| {
+            let original_query = String::from_utf8(original_query.data().to_vec()).unwrap();
+            let query = String::from_utf8(query.data().to_vec()).unwrap();
+            if query.starts_with("INSERT INTO") {
+                inserts.push((original_query, query));
+            }
+        })
+        .unwrap();
+
+        // the row filtered out is removed, and the query with only this row is not dumped
+        assert_eq!(
+            inserts,
+            vec![(
+                r#"INSERT INTO `users` VALUES (1, 'O\'Reilly; --', _binary '\0\'x'),(-3, 'Zoë', 0xFF);"#
+                    .to_string(),
+                r#"INSERT INTO `users` VALUES (1, 'O', _binary '\0\'x'),(-3, 'Z', 0xFF);"#
+                    .to_string(),
+            )]
+        );
+    }
+
+    #[test]
+    fn raw_binary_values() {
+        let dump = b"INSERT INTO `t` VALUES (1,_binary '\xff\xfe\x80');\n";
+
+        let source_options = SourceOptions {
+            transformers: &vec![],
+            skip_config: &vec![],
+            database_subset: &None,
+            only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
+        };
+
+        let result = read_and_transform(BufReader::new(&dump[..]), source_options, |_, _| {});
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn split_extended_insert_into_rows() {
+        let mut dump = vec![];
+        split_extended_insert_into_queries(
+            BufReader::new(EXTENDED_INSERT_DUMP.as_bytes()),
+            &mut dump,
+        )
+        .unwrap();
+
+        let dump = String::from_utf8(dump).unwrap();
+        assert_eq!(
+            dump.lines().skip(6).collect::<Vec<_>>(),
+            vec![
+                r#"INSERT INTO `users` VALUES (1, 'O\'Reilly; --', _binary '\0\'x');"#,
+                "INSERT INTO `users` VALUES (2, 'Doe', NULL);",
+                "INSERT INTO `users` VALUES (-3, 'Zoë', 0xFF);",
+                "INSERT INTO `users` VALUES (2,'Doe',NULL);",
+            ]
+        );
+    }
+
     #[test]
     fn test_is_insert_into_statement() {
         let q = "INSERT INTO `customers` (`first_name`, `is_valid`) VALUES ('Romaric', true);";
@@ -491,4 +857,37 @@ CONSTRAINT `city_ibfk_1` FOREIGN KEY (`CountryCode`) REFERENCES `country` (`Code
         };
         assert_eq!(get_row_type(&tokens), expected_row_type);
     }
+
+    #[test]
+    fn consistency() {
+        assert_eq!(
+            consistency_args(DumpConsistency::SingleTransaction).unwrap(),
+            vec!["--single-transaction", "--quick"]
+        );
+        assert_eq!(
+            consistency_args(DumpConsistency::LockTables).unwrap(),
+            vec!["--lock-tables", "--quick"]
+        );
+        assert!(consistency_args(DumpConsistency::SerializableDeferrable).is_err());
+
+        let output = "logs\tMyISAM\nsessions\tMEMORY\ncache\tARCHIVE\n";
+        let skip_config = vec![SkipConfig {
+            database: "world".to_string(),
+            table: "sessions".to_string(),
+        }];
+        assert_eq!(
+            non_transactional_tables(output, "world", &skip_config, &[]),
+            vec!["logs (MyISAM)", "cache (ARCHIVE)"]
+        );
+
+        let only_tables = vec![OnlyTablesConfig {
+            database: "world".to_string(),
+            table: "cache".to_string(),
+        }];
+        assert_eq!(
+            non_transactional_tables(output, "world", &[], &only_tables),
+            vec!["cache (ARCHIVE)"]
+        );
+        assert!(non_transactional_tables("", "world", &[], &[]).is_empty());
+    }
 }
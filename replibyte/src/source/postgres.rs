@@ -2,28 +2,35 @@ use std::borrow::BorrowMut;
 use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io;
-use std::io::{BufReader, Error, ErrorKind, Read, Write};
+use std::io::{BufReader, Read, Write};
 use std::process::{Command, Stdio};
 
 use log::info;
 
 use dump_parser::postgres::Keyword::NoKeyword;
 use dump_parser::postgres::{
-    get_column_names_from_insert_into_query, get_column_values_from_insert_into_query,
-    get_tokens_from_query_str, get_word_value_at_position, match_keyword_at_position, Keyword,
-    Token,
+    get_column_names_from_copy_query, get_column_names_from_insert_into_query,
+    get_column_types_from_create_table_query, get_column_values_from_copy_row,
+    get_column_values_from_insert_into_query, get_tokens_from_query_str,
+    get_word_value_at_position, match_keyword_at_position, to_copy_row, Keyword, Token,
 };
-use dump_parser::utils::{list_sql_queries_from_dump_reader, ListQueryResult};
-use subset::postgres::{PostgresSubset, SubsetStrategy};
+use dump_parser::utils::{list_sql_queries_from_dump_reader, ListQueryResult, END_OF_COPY_DATA};
+use subset::postgres::PostgresSubset;
 use subset::{PassthroughTable, Subset, SubsetOptions};
 
-use crate::config::DatabaseSubsetConfigStrategy;
+use crate::config::{
+    ConnectionUri, DatabaseSubsetConfig, DumpConsistency, PostgresDialect, PostgresDumpFormat,
+    SkipConfig, TlsConfig,
+};
 use crate::connector::Connector;
+use crate::errors::ReplibyteError;
+use crate::source::encoding::{utf8_reader, Dialect};
+use crate::source::replica::replication_lag;
+use crate::source::row_filter::RowFilters;
 use crate::source::Source;
-use crate::transformer::Transformer;
+use crate::transformer::selector::TransformerSelector;
 use crate::types::{Column, InsertIntoQuery, OriginalQuery, Query};
-use crate::utils::{binary_exists, wait_for_command};
-use crate::DatabaseSubsetConfig;
+use crate::utils::{binary_exists, strip_prefix_ignore_case, wait_for_command};
 
 use super::SourceOptions;
 
@@ -40,15 +47,53 @@ enum RowType {
         database_name: String,
         table_name: String,
     },
+    Copy {
+        database_name: String,
+        table_name: String,
+    },
     Others,
 }
 
+/// `COPY ... FROM stdin;` query whose rows are being read
+struct CopyStatement {
+    database_name: String,
+    table_name: String,
+    column_names: Vec<String>,
+    skip: bool,
+}
+
+/// queries creating and writing the large objects, e.g. `SELECT pg_catalog.lo_create('16392');`
+const LARGE_OBJECT_QUERY_PREFIXES: [&str; 5] = [
+    "SELECT pg_catalog.lo_create",
+    "SELECT pg_catalog.lo_open",
+    "SELECT pg_catalog.lowrite",
+    "SELECT pg_catalog.lo_close",
+    "ALTER LARGE OBJECT",
+];
+
+/// pg_dump generates these statements, but CockroachDB rejects them
+const COCKROACHDB_UNSUPPORTED_STATEMENTS: [&str; 8] = [
+    "SET xmloption",
+    "SET row_security",
+    "SET default_tablespace",
+    "SET default_table_access_method",
+    "SET default_with_oids",
+    "SELECT pg_catalog.set_config",
+    "CREATE EXTENSION",
+    "COMMENT ON EXTENSION",
+];
+
 pub struct Postgres<'a> {
     host: &'a str,
     port: u16,
     database: &'a str,
     username: &'a str,
     password: &'a str,
+    dialect: PostgresDialect,
+    schemas: &'a [String],
+    dump_format: PostgresDumpFormat,
+    consistency: DumpConsistency,
+    tls: &'a TlsConfig,
 }
 
 impl<'a> Postgres<'a> {
@@ -58,6 +103,11 @@ impl<'a> Postgres<'a> {
         database: &'a str,
         username: &'a str,
         password: &'a str,
+        dialect: PostgresDialect,
+        schemas: &'a [String],
+        dump_format: PostgresDumpFormat,
+        consistency: DumpConsistency,
+        tls: &'a TlsConfig,
     ) -> Self {
         Postgres {
             host,
@@ -65,13 +115,62 @@ impl<'a> Postgres<'a> {
             database,
             username,
             password,
+            dialect,
+            schemas,
+            dump_format,
+            consistency,
+            tls,
+        }
+    }
+
+    /// a serializable transaction cannot be started on a hot standby, pg_dump would fail after connecting
+    fn check_not_in_recovery(&self) -> Result<(), ReplibyteError> {
+        let connection_uri = ConnectionUri::Postgres(
+            self.host.to_string(),
+            self.port,
+            self.username.to_string(),
+            self.password.to_string(),
+            self.database.to_string(),
+        );
+
+        match replication_lag(&connection_uri, self.tls)? {
+            Some(_) => Err(ReplibyteError::Config(
+                "`consistency: serializable_deferrable` cannot be used on a read replica, use `single_transaction`".to_string(),
+            )),
+            None => Ok(()),
         }
     }
 }
 
 impl<'a> Connector for Postgres<'a> {
-    fn init(&mut self) -> Result<(), Error> {
-        binary_exists("pg_dump")
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        let _ = binary_exists("pg_dump")?;
+
+        match self.consistency {
+            DumpConsistency::SingleTransaction => {}
+            DumpConsistency::SerializableDeferrable => {
+                if self.dialect == PostgresDialect::CockroachDB {
+                    return Err(ReplibyteError::Config(
+                        "`consistency: serializable_deferrable` is not supported by CockroachDB, its transactions are always serializable".to_string(),
+                    ));
+                }
+
+                // the hot standbys are detected with psql
+                let _ = binary_exists("psql")?;
+            }
+            DumpConsistency::LockTables => {
+                return Err(ReplibyteError::Config(
+                    "`consistency: lock_tables` is only supported by MySQL and MariaDB, pg_dump always dumps a single transaction".to_string(),
+                ));
+            }
+        }
+
+        match self.dump_format {
+            PostgresDumpFormat::Plain => Ok(()),
+            PostgresDumpFormat::Custom | PostgresDumpFormat::Directory => {
+                binary_exists("pg_restore")
+            }
+        }
     }
 }
 
@@ -79,13 +178,12 @@ impl<'a> Source for Postgres<'a> {
     fn read<F: FnMut(OriginalQuery, Query)>(
         &self,
         options: SourceOptions,
-        query_callback: F,
-    ) -> Result<(), Error> {
+        mut query_callback: F,
+    ) -> Result<(), ReplibyteError> {
         let s_port = self.port.to_string();
 
         let mut dump_args = vec![
-            "--column-inserts", // dump data as INSERT commands with column names
-            "--no-owner",       // skip restoration of object ownership
+            "--no-owner", // skip restoration of object ownership
             "-h",
             self.host,
             "-p",
@@ -106,52 +204,260 @@ impl<'a> Source for Postgres<'a> {
 
         dump_args.append(&mut only_tables_args);
 
+        let schemas_args: Vec<String> = self
+            .schemas
+            .iter()
+            .map(|schema| format!("--schema={}", schema))
+            .collect();
+        let mut schemas_args: Vec<&str> = schemas_args.iter().map(String::as_str).collect();
+
+        dump_args.append(&mut schemas_args);
+
+        if self.dialect == PostgresDialect::CockroachDB {
+            // CockroachDB has no ACL, comment, publication, subscription, security label or tablespace to dump
+            dump_args.append(&mut vec![
+                "--no-acl",
+                "--no-comments",
+                "--no-publications",
+                "--no-subscriptions",
+                "--no-security-labels",
+                "--no-tablespaces",
+            ]);
+        }
+
+        if options.skip_large_objects {
+            dump_args.push("--no-blobs");
+        }
+
+        if self.consistency == DumpConsistency::SerializableDeferrable {
+            self.check_not_in_recovery()?;
+            dump_args.push("--serializable-deferrable");
+        }
+
         dump_args.push(self.database);
 
+        let dialect = self.dialect;
+        let query_callback = |original_query: OriginalQuery, query: Query| {
+            if is_supported_statement(dialect, query.data()) {
+                query_callback(original_query, query)
+            }
+        };
+
+        match self.dump_format {
+            PostgresDumpFormat::Plain => {
+                // dump data as INSERT commands with column names
+                dump_args.insert(0, "--column-inserts");
+                self.read_plain_dump(dump_args, options, query_callback)
+            }
+            PostgresDumpFormat::Custom => {
+                self.read_archive_dump("--format=custom", dump_args, options, query_callback)
+            }
+            PostgresDumpFormat::Directory => {
+                self.read_archive_dump("--format=directory", dump_args, options, query_callback)
+            }
+        }
+    }
+}
+
+impl<'a> Postgres<'a> {
+    fn read_plain_dump<F: FnMut(OriginalQuery, Query)>(
+        &self,
+        dump_args: Vec<&str>,
+        options: SourceOptions,
+        query_callback: F,
+    ) -> Result<(), ReplibyteError> {
         // TODO: as for mysql we can exclude tables directly here so we can remove the skip_tables_map checks
         let mut process = Command::new("pg_dump")
             .env("PGPASSWORD", self.password)
+            .envs(self.tls.postgres_env()?)
             .args(dump_args)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()?;
 
-        let stdout = process
-            .stdout
-            .take()
-            .ok_or_else(|| Error::new(ErrorKind::Other, "Could not capture standard output."))?;
+        let stdout = process.stdout.take().ok_or_else(|| {
+            ReplibyteError::Command("Could not capture standard output.".to_string())
+        })?;
 
         match &options.database_subset {
             None => {
-                let reader = BufReader::new(stdout);
-                read_and_transform(reader, options, query_callback);
+                let reader = utf8_reader(stdout, options.encoding, Dialect::Postgres)?;
+                read_and_transform(reader, options, query_callback)?;
             }
             Some(subset_config) => {
-                let dump_reader = BufReader::new(stdout);
+                let dump_reader = utf8_reader(stdout, options.encoding, Dialect::Postgres)?;
                 let reader = subset(dump_reader, subset_config)?;
-                read_and_transform(reader, options, query_callback);
+                read_and_transform(reader, options, query_callback)?;
             }
         };
 
         wait_for_command(&mut process)
     }
+
+    /// pg_dump makes an archive, converted back to SQL by pg_restore
+    fn read_archive_dump<F: FnMut(OriginalQuery, Query)>(
+        &self,
+        format_arg: &str,
+        dump_args: Vec<&str>,
+        options: SourceOptions,
+        query_callback: F,
+    ) -> Result<(), ReplibyteError> {
+        if options.database_subset.is_some() {
+            return Err(ReplibyteError::Config(
+                "the database subset is only supported with the `plain` dump format".to_string(),
+            ));
+        }
+
+        let archive_dir = tempfile::tempdir()?;
+        let archive_path = archive_dir.path().join("dump");
+        let archive_path = archive_path.to_str().ok_or_else(|| {
+            ReplibyteError::Command("invalid temporary dump archive path".to_string())
+        })?;
+
+        let mut process = Command::new("pg_dump")
+            .env("PGPASSWORD", self.password)
+            .envs(self.tls.postgres_env()?)
+            .args([format_arg, "--file", archive_path])
+            .args(dump_args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        wait_for_command(&mut process)?;
+
+        // the rows of the tables not dumped are not even converted to SQL
+        let output = Command::new("pg_restore")
+            .args(["--list", archive_path])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(ReplibyteError::Command(format!(
+                "command error: {}",
+                String::from_utf8_lossy(output.stderr.as_slice())
+            )));
+        }
+
+        let row_filters = RowFilters::new(options.filters, true);
+        let toc = String::from_utf8_lossy(output.stdout.as_slice());
+        let use_list = use_list(toc.as_ref(), options.skip_config, &row_filters);
+
+        let use_list_path = archive_dir.path().join("use-list");
+        std::fs::write(&use_list_path, use_list)?;
+
+        // without a database to restore into, pg_restore writes the SQL script on its standard output
+        let mut process = Command::new("pg_restore")
+            .arg(format!("--use-list={}", use_list_path.display()))
+            .arg(archive_path)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let stdout = process.stdout.take().ok_or_else(|| {
+            ReplibyteError::Command("Could not capture standard output.".to_string())
+        })?;
+
+        let reader = utf8_reader(stdout, options.encoding, Dialect::Postgres)?;
+        read_and_transform(reader, options, query_callback)?;
+
+        wait_for_command(&mut process)
+    }
+}
+
+/// list of the entries of a `pg_restore --list` table of contents to restore:
+/// the rows of the skipped tables, or of the tables without any row dumped, are commented out
+fn use_list(toc: &str, skip_config: &[SkipConfig], row_filters: &RowFilters) -> String {
+    let mut use_list = String::with_capacity(toc.len());
+
+    for line in toc.lines() {
+        let is_excluded = match toc_table_data_entry(line) {
+            Some((database_name, table_name)) => {
+                let name = format!("{}.{}", database_name, table_name);
+                row_filters.excludes_all(name.as_str())
+                    || skip_config
+                        .iter()
+                        .any(|skip| skip.database == database_name && skip.table == table_name)
+            }
+            None => false,
+        };
+
+        if is_excluded {
+            use_list.push(';');
+        }
+
+        use_list.push_str(line);
+        use_list.push('\n');
+    }
+
+    use_list
+}
+
+/// schema and table of an entry `<id>; <table oid> <oid> TABLE DATA <schema> <table> <owner>` of a table of contents
+fn toc_table_data_entry(line: &str) -> Option<(&str, &str)> {
+    if line.starts_with(';') {
+        // comment
+        return None;
+    }
+
+    let (_, entry) = line.split_once(';')?;
+    match entry.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [_, _, "TABLE", "DATA", database_name, table_name, ..] => {
+            Some((*database_name, *table_name))
+        }
+        _ => None,
+    }
+}
+
+/// return false if the statement generated by pg_dump is rejected by the database dialect
+fn is_supported_statement(dialect: PostgresDialect, query: &[u8]) -> bool {
+    match dialect {
+        PostgresDialect::Postgres => true,
+        PostgresDialect::CockroachDB => {
+            let query = String::from_utf8_lossy(query);
+            let query = query.trim_start();
+
+            // the prefix is compared on bytes: a multi-byte char can straddle its end
+            !COCKROACHDB_UNSUPPORTED_STATEMENTS.iter().any(|statement| {
+                matches!(
+                    query.as_bytes().get(..statement.len()),
+                    Some(prefix) if prefix.eq_ignore_ascii_case(statement.as_bytes())
+                )
+            })
+        }
+    }
+}
+
+/// true if the query creates, writes, comments or grants a large object
+pub fn is_large_object_query(query: &str) -> bool {
+    let query = query.trim_start();
+
+    if LARGE_OBJECT_QUERY_PREFIXES
+        .iter()
+        .any(|prefix| strip_prefix_ignore_case(query, prefix).is_some())
+    {
+        return true;
+    }
+
+    // COMMENT ON LARGE OBJECT 16392 IS '...'; and GRANT SELECT ON LARGE OBJECT 16392 TO ...;
+    ["COMMENT", "GRANT", "REVOKE"]
+        .iter()
+        .any(|prefix| strip_prefix_ignore_case(query, prefix).is_some())
+        && query
+            .chars()
+            .take(64)
+            .collect::<String>()
+            .to_uppercase()
+            .contains(" ON LARGE OBJECT ")
 }
 
 pub fn subset<R: Read>(
     mut dump_reader: BufReader<R>,
     subset_config: &DatabaseSubsetConfig,
-) -> Result<BufReader<File>, Error> {
+) -> Result<BufReader<File>, ReplibyteError> {
     let mut named_temp_file = tempfile::NamedTempFile::new()?;
     let mut temp_dump_file = named_temp_file.as_file_mut();
     let _ = io::copy(&mut dump_reader, &mut temp_dump_file)?;
 
-    let strategy = match subset_config.strategy {
-        DatabaseSubsetConfigStrategy::Random(opt) => SubsetStrategy::RandomPercent {
-            database: subset_config.database.as_str(),
-            table: subset_config.table.as_str(),
-            percent: opt.percent,
-        },
-    };
+    let strategies = subset_config.strategies()?;
 
     let empty_vec = Vec::new();
     let passthrough_tables = subset_config
@@ -162,8 +468,8 @@ pub fn subset<R: Read>(
         .map(|table| PassthroughTable::new(subset_config.database.as_str(), table.as_str()))
         .collect::<HashSet<_>>();
 
-    let subset_options = SubsetOptions::new(&passthrough_tables);
-    let subset = PostgresSubset::new(named_temp_file.path(), strategy, subset_options)?;
+    let subset_options = SubsetOptions::new(&passthrough_tables, subset_config.seed);
+    let subset = PostgresSubset::new(named_temp_file.path(), strategies, subset_options)?;
 
     let named_subset_file = tempfile::NamedTempFile::new()?;
     let mut subset_file = named_subset_file.as_file();
@@ -192,17 +498,11 @@ pub fn read_and_transform<R: Read, F: FnMut(OriginalQuery, Query)>(
     reader: BufReader<R>,
     options: SourceOptions,
     mut query_callback: F,
-) {
-    // create a map variable with Transformer by column_name
-    let mut transformer_by_db_and_table_and_column_name: HashMap<String, &Box<dyn Transformer>> =
-        HashMap::with_capacity(options.transformers.len());
-
-    for transformer in options.transformers {
-        let _ = transformer_by_db_and_table_and_column_name.insert(
-            transformer.database_and_table_and_column_name(),
-            transformer,
-        );
-    }
+) -> Result<(), ReplibyteError> {
+    // Transformer by database_name.table_name.column_name
+    let transformer_by_db_and_table_and_column_name =
+        TransformerSelector::new(options.transformers, true);
+    let row_filters = RowFilters::new(options.filters, true);
 
     let mut skip_tables_map: HashMap<String, bool> =
         HashMap::with_capacity(options.skip_config.len());
@@ -210,7 +510,37 @@ pub fn read_and_transform<R: Read, F: FnMut(OriginalQuery, Query)>(
         let _ = skip_tables_map.insert(format!("{}.{}", skip.database, skip.table), true);
     }
 
-    match list_sql_queries_from_dump_reader(reader, |query| {
+    // bytea columns by database_name.table_name, their values are dumped as NULL
+    let mut bytea_columns_map: HashMap<String, Vec<String>> = HashMap::new();
+    let mut copy_statement: Option<CopyStatement> = None;
+
+    list_sql_queries_from_dump_reader(reader, |query| {
+        if let Some(copy) = &copy_statement {
+            if query == END_OF_COPY_DATA {
+                if !copy.skip {
+                    no_change_query_callback(query_callback.borrow_mut(), query);
+                }
+
+                copy_statement = None;
+            } else if !copy.skip {
+                let (original_columns, columns) =
+                    transform_copy_row(copy, query, &transformer_by_db_and_table_and_column_name);
+                let table = format!("{}.{}", copy.database_name, copy.table_name);
+
+                // the filtered rows are not dumped
+                if !row_filters.excludes(table.as_str(), &original_columns) {
+                    let columns = skip_bytea_columns(columns, bytea_columns_map.get(&table));
+                    query_callback(Query(query.as_bytes().to_vec()), to_copy_row_query(columns));
+                }
+            }
+
+            return ListQueryResult::Continue;
+        }
+
+        if options.skip_large_objects && is_large_object_query(query) {
+            return ListQueryResult::Continue;
+        }
+
         let tokens = get_tokens_from_query_str(query);
 
         match get_row_type(&tokens) {
@@ -226,6 +556,14 @@ pub fn read_and_transform<R: Read, F: FnMut(OriginalQuery, Query)>(
                         &transformer_by_db_and_table_and_column_name,
                     );
 
+                    // the filtered rows are not dumped
+                    let table = format!("{}.{}", database_name, table_name);
+                    if row_filters.excludes(table.as_str(), &original_columns) {
+                        return ListQueryResult::Continue;
+                    }
+
+                    let columns = skip_bytea_columns(columns, bytea_columns_map.get(&table));
+
                     query_callback(
                         to_query(
                             Some(database_name.as_str()),
@@ -248,7 +586,21 @@ pub fn read_and_transform<R: Read, F: FnMut(OriginalQuery, Query)>(
                 database_name,
                 table_name,
             } => {
-                if !skip_tables_map.contains_key(&format!("{}.{}", database_name, table_name)) {
+                let table = format!("{}.{}", database_name, table_name);
+
+                if options.skip_bytea {
+                    let bytea_columns = get_column_types_from_create_table_query(&tokens)
+                        .into_iter()
+                        .filter(|(_, column_type)| column_type == "bytea")
+                        .map(|(column_name, _)| column_name)
+                        .collect::<Vec<_>>();
+
+                    if !bytea_columns.is_empty() {
+                        let _ = bytea_columns_map.insert(table.clone(), bytea_columns);
+                    }
+                }
+
+                if !skip_tables_map.contains_key(&table) {
                     no_change_query_callback(query_callback.borrow_mut(), query);
                 }
             }
@@ -260,6 +612,24 @@ pub fn read_and_transform<R: Read, F: FnMut(OriginalQuery, Query)>(
                     no_change_query_callback(query_callback.borrow_mut(), query);
                 }
             }
+            RowType::Copy {
+                database_name,
+                table_name,
+            } => {
+                // the next lines are the rows of the table, up to the end of the COPY data
+                let skip =
+                    skip_tables_map.contains_key(&format!("{}.{}", database_name, table_name));
+                if !skip {
+                    no_change_query_callback(query_callback.borrow_mut(), query);
+                }
+
+                copy_statement = Some(CopyStatement {
+                    database_name,
+                    table_name,
+                    column_names: get_column_names_from_copy_query(&tokens),
+                    skip,
+                });
+            }
             RowType::Others => {
                 // other rows than `INSERT INTO ...` and `CREATE TABLE ...`
                 no_change_query_callback(query_callback.borrow_mut(), query);
@@ -267,9 +637,24 @@ pub fn read_and_transform<R: Read, F: FnMut(OriginalQuery, Query)>(
         }
 
         ListQueryResult::Continue
-    }) {
-        Ok(_) => {}
-        Err(err) => panic!("{:?}", err),
+    })?;
+
+    Ok(())
+}
+
+/// the values of the bytea columns are replaced by NULL
+fn skip_bytea_columns(columns: Vec<Column>, bytea_columns: Option<&Vec<String>>) -> Vec<Column> {
+    match bytea_columns {
+        Some(bytea_columns) => columns
+            .into_iter()
+            .map(
+                |column| match bytea_columns.iter().any(|name| name == column.name()) {
+                    true => Column::None(column.name().to_string()),
+                    false => column,
+                },
+            )
+            .collect(),
+        None => columns,
     }
 }
 
@@ -285,7 +670,7 @@ fn transform_columns(
     database_name: &str,
     table_name: &str,
     tokens: &Vec<Token>,
-    transformer_by_db_and_table_and_column_name: &HashMap<String, &Box<dyn Transformer>>,
+    transformer_by_db_and_table_and_column_name: &TransformerSelector,
 ) -> (Vec<Column>, Vec<Column>) {
     // find database name by filtering out all queries starting with
     // INSERT INTO <database>.<table> (...)
@@ -298,7 +683,6 @@ fn transform_columns(
     let column_values = get_column_values_from_insert_into_query(&tokens);
 
     let mut original_columns = vec![];
-    let mut columns = vec![];
 
     for (i, column_name) in column_names.iter().enumerate() {
         let value_token = column_values.get(i).unwrap();
@@ -329,6 +713,10 @@ fn transform_columns(
             Token::HexStringLiteral(column_value) => {
                 Column::StringValue(column_name.to_string(), column_value.clone())
             }
+            // written back as a standard string, its escapes are already decoded
+            Token::EscapedStringLiteral(column_value) => {
+                Column::StringValue(column_name.to_string(), column_value.clone())
+            }
             Token::Word(w)
                 if (w.value == "true" || w.value == "false")
                     && w.quote_style == None
@@ -339,25 +727,85 @@ fn transform_columns(
             _ => Column::None(column_name.to_string()),
         };
 
-        // get the right transformer for the right column name
-        let original_column = column.clone();
+        original_columns.push(column);
+    }
 
-        let db_and_table_and_column_name =
-            format!("{}.{}.{}", database_name, table_name, *column_name);
-        let column = match transformer_by_db_and_table_and_column_name
-            .get(db_and_table_and_column_name.as_str())
-        {
-            Some(transformer) => transformer.transform(column), // apply transformation on the column
-            None => column,
-        };
+    let columns = transform_original_columns(
+        database_name,
+        table_name,
+        &original_columns,
+        transformer_by_db_and_table_and_column_name,
+    );
 
-        original_columns.push(original_column);
-        columns.push(column);
-    }
+    (original_columns, columns)
+}
+
+/// the values of a COPY row are read as numbers when they are written as such, as strings otherwise
+fn transform_copy_row(
+    copy_statement: &CopyStatement,
+    row: &str,
+    transformer_by_db_and_table_and_column_name: &TransformerSelector,
+) -> (Vec<Column>, Vec<Column>) {
+    let column_values = get_column_values_from_copy_row(row);
+
+    let original_columns = copy_statement
+        .column_names
+        .iter()
+        .zip(column_values)
+        .map(|(column_name, value)| match value {
+            None => Column::None(column_name.to_string()),
+            Some(value) => match value.parse::<i128>() {
+                // e.g. a zip code 01234 is kept as it is
+                Ok(number) if number.to_string() == value => {
+                    Column::NumberValue(column_name.to_string(), number)
+                }
+                _ => match value.parse::<f64>() {
+                    Ok(number) if value.contains('.') && number.to_string() == value => {
+                        Column::FloatNumberValue(column_name.to_string(), number)
+                    }
+                    _ => Column::StringValue(column_name.to_string(), value),
+                },
+            },
+        })
+        .collect::<Vec<_>>();
+
+    let columns = transform_original_columns(
+        copy_statement.database_name.as_str(),
+        copy_statement.table_name.as_str(),
+        &original_columns,
+        transformer_by_db_and_table_and_column_name,
+    );
 
     (original_columns, columns)
 }
 
+fn transform_original_columns(
+    database_name: &str,
+    table_name: &str,
+    original_columns: &[Column],
+    transformer_by_db_and_table_and_column_name: &TransformerSelector,
+) -> Vec<Column> {
+    // the transformers get the original values of the row to check their conditions
+    original_columns
+        .iter()
+        .map(|column| {
+            // get the right transformer for the right column name
+            let db_and_table_and_column_name =
+                format!("{}.{}.{}", database_name, table_name, column.name());
+            match transformer_by_db_and_table_and_column_name
+                .get(db_and_table_and_column_name.as_str())
+            {
+                Some(transformer) => transformer.transform_column(
+                    db_and_table_and_column_name.as_str(),
+                    column.clone(),
+                    original_columns,
+                ), // apply transformation on the column
+                None => column.clone(),
+            }
+        })
+        .collect()
+}
+
 fn is_insert_into_statement(tokens: &Vec<Token>) -> bool {
     match_keyword_at_position(Keyword::Insert, &tokens, 0)
         && match_keyword_at_position(Keyword::Into, &tokens, 2)
@@ -373,6 +821,10 @@ fn is_alter_table_statement(tokens: &Vec<Token>) -> bool {
         && match_keyword_at_position(Keyword::Table, &tokens, 2)
 }
 
+fn is_copy_statement(tokens: &Vec<Token>) -> bool {
+    match_keyword_at_position(Keyword::Copy, &tokens, 0)
+}
+
 fn get_row_type(tokens: &Vec<Token>) -> RowType {
     let mut row_type = RowType::Others;
 
@@ -421,9 +873,36 @@ fn get_row_type(tokens: &Vec<Token>) -> RowType {
         }
     }
 
+    if is_copy_statement(&tokens) {
+        if let Some(database_name) = get_word_value_at_position(&tokens, 2) {
+            if let Some(table_name) = get_word_value_at_position(&tokens, 4) {
+                row_type = RowType::Copy {
+                    database_name: database_name.to_string(),
+                    table_name: table_name.to_string(),
+                };
+            }
+        }
+    }
+
     row_type
 }
 
+fn to_copy_row_query(columns: Vec<Column>) -> Query {
+    let values = columns
+        .into_iter()
+        .map(|column| match column {
+            Column::NumberValue(_, value) => Some(value.to_string()),
+            Column::FloatNumberValue(_, value) => Some(value.to_string()),
+            Column::StringValue(_, value) => Some(value),
+            Column::CharValue(_, value) => Some(value.to_string()),
+            Column::BooleanValue(_, value) => Some(value.to_string()),
+            Column::None(_) => None,
+        })
+        .collect::<Vec<_>>();
+
+    Query(to_copy_row(&values).into_bytes())
+}
+
 fn to_query(database: Option<&str>, query: InsertIntoQuery) -> Query {
     let mut column_names = Vec::with_capacity(query.columns.len());
     let mut values = Vec::with_capacity(query.columns.len());
@@ -476,27 +955,67 @@ fn to_query(database: Option<&str>, query: InsertIntoQuery) -> Query {
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
+    use std::io::{BufReader, ErrorKind};
     use std::str;
     use std::vec;
 
     use crate::config::{
         DatabaseSubsetConfig, DatabaseSubsetConfigStrategy, DatabaseSubsetConfigStrategyRandom,
-        SkipConfig,
+        DumpConsistency, PostgresDialect, PostgresDumpFormat, RowFilterConfig, SkipConfig,
+        TlsConfig,
+    };
+    use crate::source::postgres::{
+        is_large_object_query, is_supported_statement, read_and_transform, to_query, use_list,
+        Postgres,
     };
-    use crate::source::postgres::{to_query, Postgres};
+    use crate::source::row_filter::RowFilters;
     use crate::source::SourceOptions;
     use crate::transformer::random::RandomTransformer;
+    use crate::transformer::set_constant::{
+        ConstantValue, SetConstantTransformer, SetConstantTransformerOptions,
+    };
+    use crate::transformer::set_null::SetNullTransformer;
     use crate::transformer::transient::TransientTransformer;
     use crate::transformer::Transformer;
     use crate::types::{Column, InsertIntoQuery};
     use crate::Source;
 
+    static TLS_CONFIG: TlsConfig = TlsConfig {
+        ssl_mode: None,
+        ssl_cert: None,
+        ssl_key: None,
+        ssl_root_cert: None,
+        parameters: Vec::new(),
+    };
+
     fn get_postgres() -> Postgres<'static> {
-        Postgres::new("localhost", 5432, "root", "root", "password")
+        Postgres::new(
+            "localhost",
+            5432,
+            "root",
+            "root",
+            "password",
+            PostgresDialect::Postgres,
+            &[],
+            PostgresDumpFormat::Plain,
+            DumpConsistency::SingleTransaction,
+            &TLS_CONFIG,
+        )
     }
 
     fn get_invalid_postgres() -> Postgres<'static> {
-        Postgres::new("localhost", 5432, "root", "root", "wrongpassword")
+        Postgres::new(
+            "localhost",
+            5432,
+            "root",
+            "root",
+            "wrongpassword",
+            PostgresDialect::Postgres,
+            &[],
+            PostgresDumpFormat::Plain,
+            DumpConsistency::SingleTransaction,
+            &TLS_CONFIG,
+        )
     }
 
     #[test]
@@ -509,6 +1028,10 @@ mod tests {
             skip_config: &vec![],
             database_subset: &None,
             only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
         };
 
         assert!(p.read(source_options, |original_query, query| {}).is_ok());
@@ -521,6 +1044,10 @@ mod tests {
             skip_config: &vec![],
             database_subset: &None,
             only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
         };
 
         assert!(p.read(source_options, |original_query, query| {}).is_err());
@@ -536,6 +1063,10 @@ mod tests {
             skip_config: &vec![],
             database_subset: &None,
             only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
         };
 
         let _ = p.read(source_options, |original_query, query| {
@@ -544,6 +1075,41 @@ mod tests {
         });
     }
 
+    #[test]
+    fn cockroachdb_supported_statements() {
+        let queries: [&[u8]; 4] = [
+            b"SET statement_timeout = 0;",
+            b"\nSET xmloption = content;",
+            b"SELECT pg_catalog.set_config('search_path', '', false);",
+            b"CREATE EXTENSION IF NOT EXISTS pgcrypto WITH SCHEMA public;",
+        ];
+
+        assert!(queries
+            .iter()
+            .all(|query| is_supported_statement(PostgresDialect::Postgres, query)));
+
+        assert_eq!(
+            queries
+                .iter()
+                .map(|query| is_supported_statement(PostgresDialect::CockroachDB, query))
+                .collect::<Vec<_>>(),
+            vec![true, false, false, false]
+        );
+        assert!(is_supported_statement(
+            PostgresDialect::CockroachDB,
+            b"INSERT INTO public.users (id) VALUES (1);"
+        ));
+        // a multi-byte char across the length of a statement prefix
+        assert!(is_supported_statement(
+            PostgresDialect::CockroachDB,
+            "1\tMarie-Hélène\tgrenoble".as_bytes()
+        ));
+        assert!(is_supported_statement(
+            PostgresDialect::CockroachDB,
+            "42\tJosé\tCrème brûlée".as_bytes()
+        ));
+    }
+
     #[test]
     fn test_to_row() {
         let query = to_query(
@@ -662,6 +1228,10 @@ mod tests {
             skip_config: &vec![],
             database_subset: &None,
             only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
         };
 
         let _ = p.read(source_options, |original_query, query| {
@@ -682,6 +1252,232 @@ mod tests {
         });
     }
 
+    #[test]
+    fn transform_copy_rows() {
+        let dump = r#"CREATE TABLE public.users (id integer, name text, bio text, zip text);
+
+COPY public.users (id, name, bio, zip) FROM stdin;
+1	Romaric	likes ; and '	01234
+2	Benny	\N	75001
+3	Fab	line 1\nline 2	\N
+\.
+
+COPY public.audit_logs (id, message) FROM stdin;
+1	user 1 logged in
+\.
+
+SELECT pg_catalog.setval('public.users_id_seq', 3, true);
+"#;
+
+        let transformers: Vec<Box<dyn Transformer>> = vec![
+            Box::new(SetConstantTransformer::new(
+                "public",
+                "users",
+                "name",
+                SetConstantTransformerOptions {
+                    value: ConstantValue::String("John\tDoe".to_string()),
+                },
+            )),
+            Box::new(SetNullTransformer::new("public", "users", "bio")),
+        ];
+        let skip_config = vec![SkipConfig {
+            database: "public".to_string(),
+            table: "audit_logs".to_string(),
+        }];
+        let filters = serde_yaml::from_str::<Vec<RowFilterConfig>>(
+            r#"
+- database: public
+  table: users
+  exclude:
+    - column: id
+      equals: 2
+"#,
+        )
+        .unwrap();
+
+        let source_options = SourceOptions {
+            transformers: &transformers,
+            skip_config: &skip_config,
+            database_subset: &None,
+            only_tables: &vec![],
+            filters: &filters,
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
+        };
+
+        let mut original_queries = vec![];
+        let mut queries = vec![];
+        read_and_transform(
+            BufReader::new(dump.as_bytes()),
+            source_options,
+            |original_query, query| {
+                original_queries.push(String::from_utf8(original_query.0).unwrap());
+                queries.push(String::from_utf8(query.0).unwrap());
+            },
+        )
+        .unwrap();
+
+        let queries = queries
+            .into_iter()
+            .map(|query| query.trim_start().to_string())
+            .filter(|query| !query.is_empty())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            queries,
+            vec![
+                "CREATE TABLE public.users (id integer, name text, bio text, zip text);",
+                "COPY public.users (id, name, bio, zip) FROM stdin;",
+                "1\tJohn\\tDoe\t\\N\t01234",
+                "3\tJohn\\tDoe\t\\N\t\\N",
+                "\\.",
+                "SELECT pg_catalog.setval('public.users_id_seq', 3, true);",
+            ]
+        );
+
+        // the original rows are kept as they are in the dump
+        assert!(original_queries.contains(&"1\tRomaric\tlikes ; and '\t01234".to_string()));
+        assert!(original_queries.contains(&"3\tFab\tline 1\\nline 2\t\\N".to_string()));
+    }
+
+    #[test]
+    fn invalid_utf8_copy_rows() {
+        let dump = b"COPY public.users (id, name) FROM stdin;\n1\tcaf\xe9\n\\.\n";
+
+        let source_options = SourceOptions {
+            transformers: &vec![],
+            skip_config: &vec![],
+            database_subset: &None,
+            only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
+        };
+
+        let result = read_and_transform(BufReader::new(&dump[..]), source_options, |_, _| {});
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn binary_data() {
+        let dump = r#"CREATE TABLE public.files (id integer, name text, data bytea, "Thumbnail" bytea);
+
+INSERT INTO public.files (id, name, data, "Thumbnail") VALUES (1, E'it\'s\tan E''string', '\x0aff', NULL);
+
+COPY public.files (id, name, data, "Thumbnail") FROM stdin;
+2	a.bin	\\x0aff	\\x00
+\.
+
+SELECT pg_catalog.lo_create('16392');
+ALTER LARGE OBJECT 16392 OWNER TO postgres;
+BEGIN;
+SELECT pg_catalog.lo_open('16392', 131072);
+SELECT pg_catalog.lowrite(0, '\x48656c6c6f0a27205c');
+SELECT pg_catalog.lo_close(0);
+COMMIT;
+"#;
+
+        let read = |skip_binary_data: bool| {
+            let source_options = SourceOptions {
+                transformers: &vec![],
+                skip_config: &vec![],
+                database_subset: &None,
+                only_tables: &vec![],
+                filters: &vec![],
+                encoding: None,
+                skip_large_objects: skip_binary_data,
+                skip_bytea: skip_binary_data,
+            };
+
+            let mut queries = vec![];
+            read_and_transform(
+                BufReader::new(dump.as_bytes()),
+                source_options,
+                |_, query| {
+                    let query = String::from_utf8(query.0).unwrap();
+                    if !query.trim().is_empty() {
+                        queries.push(query.trim_start().to_string());
+                    }
+                },
+            )
+            .unwrap();
+
+            queries
+        };
+
+        let queries = read(false);
+        // the escape string is written as a standard string
+        assert_eq!(
+            queries[1],
+            r#"INSERT INTO public.files (id, name, data, "Thumbnail") VALUES (1, 'it''s	an E''string', '\x0aff', NULL);"#
+        );
+        assert_eq!(queries[3], "2\ta.bin\t\\\\x0aff\t\\\\x00");
+        assert!(
+            queries.contains(&"SELECT pg_catalog.lowrite(0, '\\x48656c6c6f0a27205c');".to_string())
+        );
+
+        let queries = read(true);
+        assert_eq!(
+            queries[1],
+            r#"INSERT INTO public.files (id, name, data, "Thumbnail") VALUES (1, 'it''s	an E''string', NULL, NULL);"#
+        );
+        assert_eq!(queries[3], "2\ta.bin\t\\N\t\\N");
+        assert_eq!(
+            &queries[5..],
+            &["BEGIN;".to_string(), "COMMIT;".to_string()]
+        );
+        assert!(is_large_object_query(
+            "GRANT SELECT ON LARGE OBJECT 16392 TO reader;"
+        ));
+    }
+
+    #[test]
+    fn use_list_without_skipped_rows() {
+        let toc = r#";
+; Archive created at 2022-06-01 10:00:00 UTC
+;     dbname: root
+;
+; Selected TOC Entries:
+;
+216; 1259 16386 TABLE public users root
+217; 1259 16392 TABLE public audit_logs root
+218; 1259 16398 TABLE public sessions root
+3340; 0 16386 TABLE DATA public users root
+3341; 0 16392 TABLE DATA public audit_logs root
+3342; 0 16398 TABLE DATA public sessions root
+3190; 2606 16393 CONSTRAINT public users users_pkey root
+"#;
+
+        let skip_config = vec![SkipConfig {
+            database: "public".to_string(),
+            table: "audit_logs".to_string(),
+        }];
+        let filters = vec![RowFilterConfig {
+            database: "public".to_string(),
+            table: "sessions".to_string(),
+            exclude: vec![],
+        }];
+        let row_filters = RowFilters::new(&filters, true);
+
+        let use_list = use_list(toc, &skip_config, &row_filters);
+        let lines = use_list.lines().skip(6).collect::<Vec<_>>();
+
+        assert_eq!(
+            lines,
+            vec![
+                "216; 1259 16386 TABLE public users root",
+                "217; 1259 16392 TABLE public audit_logs root",
+                "218; 1259 16398 TABLE public sessions root",
+                "3340; 0 16386 TABLE DATA public users root",
+                ";3341; 0 16392 TABLE DATA public audit_logs root",
+                ";3342; 0 16398 TABLE DATA public sessions root",
+                "3190; 2606 16393 CONSTRAINT public users users_pkey root",
+            ]
+        );
+    }
+
     #[test]
     fn skip_table() {
         let p = get_postgres();
@@ -703,6 +1499,10 @@ mod tests {
             skip_config: &skip_config,
             database_subset: &None,
             only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
         };
 
         let _ = p.read(source_options, |_original_query, query| {
@@ -752,8 +1552,15 @@ mod tests {
                     DatabaseSubsetConfigStrategyRandom { percent: 50 },
                 ),
                 passthrough_tables: None,
+                tables: None,
+                seed: None,
+                lookups: None,
             }),
             only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
         };
 
         let mut rows_percent_50 = vec![];
@@ -789,8 +1596,15 @@ mod tests {
                     DatabaseSubsetConfigStrategyRandom { percent: 30 },
                 ),
                 passthrough_tables: None,
+                tables: None,
+                seed: None,
+                lookups: None,
             }),
             only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
         };
 
         let mut rows_percent_30 = vec![];
@@ -0,0 +1,433 @@
+use std::io::BufReader;
+use std::process::{Command, Stdio};
+
+use log::info;
+
+use crate::config::{IncrementalTableConfig, TlsConfig};
+use crate::connector::Connector;
+use crate::errors::ReplibyteError;
+use crate::source::postgres::read_and_transform;
+use crate::source::{Source, SourceOptions};
+use crate::types::{OriginalQuery, Query};
+use crate::utils::{binary_exists, wait_for_command};
+
+/// the rows updated shortly before the previous dump started are dumped again, to not miss the ones
+/// committed while it started or with a database clock behind ours. They are upserted, so it is harmless.
+pub const SINCE_MARGIN_MILLIS: u128 = 60 * 1000;
+
+/// PostgresIncremental dumps the rows changed since a previous dump, found with a timestamp column of each table.
+/// The rows are dumped as upserts on the primary key of their table, to be restored on top of the previous dumps.
+/// The rows deleted since the previous dump are not dumped.
+pub struct PostgresIncremental<'a> {
+    host: &'a str,
+    port: u16,
+    database: &'a str,
+    username: &'a str,
+    password: &'a str,
+    tls: &'a TlsConfig,
+    tables: &'a [IncrementalTableConfig],
+    /// the rows updated after this time (epoch millis) are dumped
+    since: u128,
+}
+
+/// column of a table, with the category of its type (`pg_type.typcategory`)
+struct TableColumn {
+    name: String,
+    category: String,
+}
+
+impl<'a> PostgresIncremental<'a> {
+    pub fn new(
+        host: &'a str,
+        port: u16,
+        database: &'a str,
+        username: &'a str,
+        password: &'a str,
+        tls: &'a TlsConfig,
+        tables: &'a [IncrementalTableConfig],
+        since: u128,
+    ) -> Self {
+        PostgresIncremental {
+            host,
+            port,
+            database,
+            username,
+            password,
+            tls,
+            tables,
+            since,
+        }
+    }
+
+    fn psql(&self, query: &str) -> Result<Command, ReplibyteError> {
+        let mut command = Command::new("psql");
+        let _ = command
+            .env("PGPASSWORD", self.password)
+            .envs(self.tls.postgres_env()?)
+            .args([
+                "-h",
+                self.host,
+                "-p",
+                self.port.to_string().as_str(),
+                "-d",
+                self.database,
+                "-U",
+                self.username,
+                "-X", // skip the psqlrc file
+                "-v",
+                "ON_ERROR_STOP=1",
+                "-tA", // only print the rows, without alignment
+                "-F",
+                "\t",
+                "-c",
+                query,
+            ]);
+
+        Ok(command)
+    }
+
+    fn execute(&self, query: &str) -> Result<Vec<Vec<String>>, ReplibyteError> {
+        let output = self.psql(query)?.output()?;
+
+        if !output.status.success() {
+            return Err(ReplibyteError::Command(format!(
+                "command error: {}",
+                String::from_utf8_lossy(output.stderr.as_slice())
+            )));
+        }
+
+        Ok(String::from_utf8_lossy(output.stdout.as_slice())
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.split('\t').map(|value| value.to_string()).collect())
+            .collect())
+    }
+
+    fn columns(&self, table: &IncrementalTableConfig) -> Result<Vec<TableColumn>, ReplibyteError> {
+        let columns = self.execute(columns_query(table).as_str())?;
+
+        if columns.is_empty() {
+            return Err(ReplibyteError::Config(format!(
+                "table '{}.{}' has no columns",
+                table.database, table.table
+            )));
+        }
+
+        Ok(columns
+            .into_iter()
+            .map(|mut row| TableColumn {
+                category: row.pop().unwrap_or_default(),
+                name: row.pop().unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    fn primary_key(&self, table: &IncrementalTableConfig) -> Result<Vec<String>, ReplibyteError> {
+        let primary_key = self
+            .execute(primary_key_query(table).as_str())?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
+        if primary_key.is_empty() {
+            return Err(ReplibyteError::Config(format!(
+                "table '{}.{}' has no primary key, its changed rows can not be upserted",
+                table.database, table.table
+            )));
+        }
+
+        Ok(primary_key)
+    }
+}
+
+impl<'a> Connector for PostgresIncremental<'a> {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        binary_exists("psql")
+    }
+}
+
+impl<'a> Source for PostgresIncremental<'a> {
+    fn read<F: FnMut(OriginalQuery, Query)>(
+        &self,
+        options: SourceOptions,
+        mut query_callback: F,
+    ) -> Result<(), ReplibyteError> {
+        for table in self.tables {
+            info!(
+                "dumping the rows of '{}.{}' updated since {}",
+                table.database, table.table, self.since
+            );
+
+            let columns = self.columns(table)?;
+            let primary_key = self.primary_key(table)?;
+            let column_names = columns
+                .iter()
+                .map(|column| column.name.clone())
+                .collect::<Vec<_>>();
+
+            let mut process = self
+                .psql(changed_rows_query(table, &columns, self.since).as_str())?
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()?;
+
+            let stdout = process.stdout.take().ok_or_else(|| {
+                ReplibyteError::Command("Could not capture standard output.".to_string())
+            })?;
+
+            read_and_transform(BufReader::new(stdout), options, |original_query, query| {
+                query_callback(
+                    upsert(original_query, &primary_key, &column_names),
+                    upsert(query, &primary_key, &column_names),
+                )
+            })?;
+
+            let _ = wait_for_command(&mut process)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// quote an identifier if needed, as pg_dump does
+fn quote_identifier(identifier: &str) -> String {
+    let is_simple = identifier
+        .chars()
+        .next()
+        .map(|c| c.is_ascii_lowercase() || c == '_')
+        .unwrap_or(false)
+        && identifier
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+
+    if is_simple {
+        identifier.to_string()
+    } else {
+        format!("\"{}\"", identifier.replace('"', "\"\""))
+    }
+}
+
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn table_name(table: &IncrementalTableConfig) -> String {
+    format!(
+        "{}.{}",
+        quote_identifier(table.database.as_str()),
+        quote_identifier(table.table.as_str())
+    )
+}
+
+fn columns_query(table: &IncrementalTableConfig) -> String {
+    format!(
+        "SELECT a.attname, t.typcategory FROM pg_attribute a JOIN pg_type t ON t.oid = a.atttypid \
+        WHERE a.attrelid = {}::regclass AND a.attnum > 0 AND NOT a.attisdropped ORDER BY a.attnum;",
+        quote_literal(table_name(table).as_str())
+    )
+}
+
+fn primary_key_query(table: &IncrementalTableConfig) -> String {
+    format!(
+        "SELECT a.attname FROM pg_index i \
+        JOIN pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = ANY(i.indkey) \
+        WHERE i.indrelid = {}::regclass AND i.indisprimary \
+        ORDER BY array_position(i.indkey::int2[], a.attnum);",
+        quote_literal(table_name(table).as_str())
+    )
+}
+
+/// return the query printing an `INSERT INTO` statement for each row updated since the given time,
+/// the numbers and booleans are not quoted as in the statements generated by pg_dump
+fn changed_rows_query(
+    table: &IncrementalTableConfig,
+    columns: &[TableColumn],
+    since: u128,
+) -> String {
+    let column_names = columns
+        .iter()
+        .map(|column| quote_identifier(column.name.as_str()))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let values = columns
+        .iter()
+        .map(|column| {
+            let name = format!("\"{}\"", column.name.replace('"', "\"\""));
+
+            match column.category.as_str() {
+                // NaN and Infinity are quoted
+                "N" => format!(
+                    "coalesce(CASE WHEN {0}::text ~ '^[-+0-9.e]+$' THEN {0}::text ELSE quote_literal({0}) END, 'NULL')",
+                    name
+                ),
+                "B" => format!("coalesce({}::text, 'NULL')", name),
+                _ => format!("quote_nullable({})", name),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" || ', ' || ");
+
+    format!(
+        "SELECT {} || {} || ');' FROM {} WHERE \"{}\" > to_timestamp({}::double precision / 1000);",
+        quote_literal(
+            format!(
+                "INSERT INTO {} ({}) VALUES (",
+                table_name(table),
+                column_names
+            )
+            .as_str()
+        ),
+        values,
+        table_name(table),
+        table.column.replace('"', "\"\""),
+        since
+    )
+}
+
+/// turn the `INSERT INTO` statement of a changed row into an upsert, the row may have been dumped before
+fn upsert(query: Query, primary_key: &[String], columns: &[String]) -> Query {
+    let data = String::from_utf8_lossy(query.data());
+    let data = data.trim_end().trim_end_matches(';');
+
+    // the rows are separated by new lines, which are kept as they are
+    if !data.starts_with("INSERT INTO") {
+        return query;
+    }
+
+    let query = data;
+
+    let updated_columns = columns
+        .iter()
+        .filter(|column| !primary_key.contains(column))
+        .map(|column| {
+            let column = quote_identifier(column.as_str());
+            format!("{} = EXCLUDED.{}", column, column)
+        })
+        .collect::<Vec<_>>();
+
+    let primary_key = primary_key
+        .iter()
+        .map(|column| quote_identifier(column.as_str()))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let query = if updated_columns.is_empty() {
+        format!("{} ON CONFLICT ({}) DO NOTHING;", query, primary_key)
+    } else {
+        format!(
+            "{} ON CONFLICT ({}) DO UPDATE SET {};",
+            query,
+            primary_key,
+            updated_columns.join(", ")
+        )
+    };
+
+    Query(query.into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use crate::config::IncrementalTableConfig;
+    use crate::source::postgres::read_and_transform;
+    use crate::source::postgres_incremental::{
+        changed_rows_query, quote_identifier, upsert, TableColumn,
+    };
+    use crate::source::SourceOptions;
+    use crate::transformer::random::RandomTransformer;
+    use crate::transformer::Transformer;
+    use crate::types::Query;
+
+    fn orders() -> IncrementalTableConfig {
+        IncrementalTableConfig {
+            database: "public".to_string(),
+            table: "orders".to_string(),
+            column: "updated_at".to_string(),
+        }
+    }
+
+    #[test]
+    fn quote_identifiers() {
+        assert_eq!(quote_identifier("orders"), "orders");
+        assert_eq!(quote_identifier("order_2"), "order_2");
+        assert_eq!(quote_identifier("Orders"), "\"Orders\"");
+        assert_eq!(quote_identifier("2orders"), "\"2orders\"");
+        assert_eq!(quote_identifier("my \"orders\""), "\"my \"\"orders\"\"\"");
+    }
+
+    #[test]
+    fn select_changed_rows() {
+        let columns = vec![
+            TableColumn {
+                name: "id".to_string(),
+                category: "N".to_string(),
+            },
+            TableColumn {
+                name: "Status".to_string(),
+                category: "S".to_string(),
+            },
+        ];
+
+        assert_eq!(
+            changed_rows_query(&orders(), &columns, 1656633600000),
+            "SELECT 'INSERT INTO public.orders (id, \"Status\") VALUES (' || \
+            coalesce(CASE WHEN \"id\"::text ~ '^[-+0-9.e]+$' THEN \"id\"::text ELSE quote_literal(\"id\") END, 'NULL') || ', ' || \
+            quote_nullable(\"Status\") || ');' \
+            FROM public.orders WHERE \"updated_at\" > to_timestamp(1656633600000::double precision / 1000);"
+        );
+    }
+
+    #[test]
+    fn upsert_changed_rows() {
+        let query = Query(b"INSERT INTO public.orders (id, status) VALUES (1, 'paid');".to_vec());
+        let columns = vec!["id".to_string(), "status".to_string()];
+
+        assert_eq!(
+            upsert(query.clone(), &["id".to_string()], &columns).data(),
+            b"INSERT INTO public.orders (id, status) VALUES (1, 'paid') \
+            ON CONFLICT (id) DO UPDATE SET status = EXCLUDED.status;"
+        );
+
+        assert_eq!(
+            upsert(query, &columns, &columns).data(),
+            b"INSERT INTO public.orders (id, status) VALUES (1, 'paid') ON CONFLICT (id, status) DO NOTHING;"
+        );
+    }
+
+    #[test]
+    fn transform_changed_rows() {
+        let rows = b"INSERT INTO public.orders (id, status) VALUES (1, 'paid');\n";
+        let transformers = vec![
+            Box::new(RandomTransformer::new("public", "orders", "status")) as Box<dyn Transformer>,
+        ];
+        let options = SourceOptions {
+            transformers: &transformers,
+            skip_config: &vec![],
+            database_subset: &None,
+            only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
+        };
+
+        let mut queries = vec![];
+        read_and_transform(BufReader::new(&rows[..]), options, |_, query| {
+            queries.push(upsert(
+                query,
+                &["id".to_string()],
+                &["id".to_string(), "status".to_string()],
+            ))
+        })
+        .unwrap();
+
+        assert_eq!(queries.len(), 2);
+        assert_eq!(queries[1].data(), b"\n");
+        let query = String::from_utf8_lossy(queries[0].data()).to_string();
+        assert!(query.starts_with("INSERT INTO public.orders (id, status) VALUES (1, '"));
+        assert!(!query.contains("'paid'"));
+        assert!(query.ends_with(" ON CONFLICT (id) DO UPDATE SET status = EXCLUDED.status;"));
+    }
+}
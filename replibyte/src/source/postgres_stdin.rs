@@ -1,6 +1,8 @@
-use std::io::{stdin, BufReader, Error};
+use std::io::{stdin, Read};
 
 use crate::connector::Connector;
+use crate::errors::ReplibyteError;
+use crate::source::encoding::{utf8_reader, Dialect};
 use crate::source::postgres::{read_and_transform, subset};
 use crate::types::{OriginalQuery, Query};
 use crate::Source;
@@ -22,7 +24,7 @@ impl Default for PostgresStdin {
 }
 
 impl Connector for PostgresStdin {
-    fn init(&mut self) -> Result<(), Error> {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
         Ok(())
     }
 }
@@ -32,19 +34,28 @@ impl Source for PostgresStdin {
         &self,
         options: SourceOptions,
         query_callback: F,
-    ) -> Result<(), Error> {
-        match &options.database_subset {
-            None => {
-                let reader = BufReader::new(stdin());
-                read_and_transform(reader, options, query_callback);
-            }
-            Some(subset_config) => {
-                let dump_reader = BufReader::new(stdin());
-                let reader = subset(dump_reader, subset_config)?;
-                read_and_transform(reader, options, query_callback);
-            }
-        };
-
-        Ok(())
+    ) -> Result<(), ReplibyteError> {
+        read_dump(stdin(), options, query_callback)
     }
 }
+
+/// read a PostgreSQL dump and transform its rows
+pub fn read_dump<R: Read, F: FnMut(OriginalQuery, Query)>(
+    reader: R,
+    options: SourceOptions,
+    query_callback: F,
+) -> Result<(), ReplibyteError> {
+    match &options.database_subset {
+        None => {
+            let reader = utf8_reader(reader, options.encoding, Dialect::Postgres)?;
+            read_and_transform(reader, options, query_callback)?;
+        }
+        Some(subset_config) => {
+            let dump_reader = utf8_reader(reader, options.encoding, Dialect::Postgres)?;
+            let reader = subset(dump_reader, subset_config)?;
+            read_and_transform(reader, options, query_callback)?;
+        }
+    };
+
+    Ok(())
+}
@@ -0,0 +1,254 @@
+use std::process::Command;
+
+use log::{info, warn};
+
+use crate::config::{ConnectionUri, SourceConfig, TlsConfig};
+use crate::errors::ReplibyteError;
+use crate::proxy::{database_proxy, proxy_connection_uri};
+use crate::ssh::tunnel_connection_uri;
+
+pub const DEFAULT_MAX_REPLICATION_LAG_SECONDS: u64 = 60;
+
+/// an unreachable replica must not hold the dump for long
+const CONNECT_TIMEOUT_SECONDS: u64 = 10;
+
+/// seconds behind the primary, -1 if the database is not in recovery (i.e. not a replica)
+const POSTGRES_LAG_QUERY: &str = "SELECT CASE \
+    WHEN NOT pg_is_in_recovery() THEN -1 \
+    WHEN pg_last_wal_receive_lsn() = pg_last_wal_replay_lsn() THEN 0 \
+    ELSE GREATEST(COALESCE(EXTRACT(EPOCH FROM now() - pg_last_xact_replay_timestamp()), 0), 0)::bigint \
+    END";
+
+/// connection uri and TLS options of the database the dump is made from: the first healthy read
+/// replica, or the primary if there is none
+pub fn source_connection(
+    source: &SourceConfig,
+) -> Result<(ConnectionUri, TlsConfig), ReplibyteError> {
+    let read_replicas = source.read_replicas()?;
+    if read_replicas.is_empty() {
+        return Ok((source.connection_uri()?, source.tls()?));
+    }
+
+    let max_lag = source
+        .max_replication_lag_seconds
+        .unwrap_or(DEFAULT_MAX_REPLICATION_LAG_SECONDS);
+
+    match select_replica(read_replicas, max_lag, |(connection_uri, tls)| {
+        check_replica(source, connection_uri, tls)
+    }) {
+        Some(read_replica) => Ok(read_replica),
+        None => {
+            warn!("no read replica is healthy, the primary is used");
+            Ok((source.connection_uri()?, source.tls()?))
+        }
+    }
+}
+
+/// the first replica whose replication lag can be queried, a warning is logged if it lags behind
+/// more than `max_lag` seconds
+pub fn select_replica<R, F>(read_replicas: Vec<R>, max_lag: u64, check: F) -> Option<R>
+where
+    F: Fn(&R) -> Result<Option<u64>, ReplibyteError>,
+{
+    for (idx, read_replica) in read_replicas.into_iter().enumerate() {
+        match check(&read_replica) {
+            Ok(Some(lag)) if lag > max_lag => warn!(
+                "read replica #{} is {} seconds behind its primary (max_replication_lag_seconds: {})",
+                idx + 1,
+                lag,
+                max_lag
+            ),
+            Ok(Some(_)) => {}
+            Ok(None) => warn!("read replica #{} is not a replica", idx + 1),
+            Err(err) => {
+                warn!("read replica #{}: {}, trying the next one", idx + 1, err);
+                continue;
+            }
+        }
+
+        info!("read replica #{} is used", idx + 1);
+        return Some(read_replica);
+    }
+
+    None
+}
+
+/// replication lag of the read replica, through the SSH tunnel or the proxy of the source
+pub fn check_replica(
+    source: &SourceConfig,
+    connection_uri: &ConnectionUri,
+    tls: &TlsConfig,
+) -> Result<Option<u64>, ReplibyteError> {
+    let proxy = database_proxy(source.proxy()?, source.ssh.as_ref(), connection_uri)?;
+
+    // the tunnels are closed when they go out of scope
+    let (connection_uri, _ssh_tunnel) =
+        tunnel_connection_uri(source.ssh.as_ref(), proxy.as_ref(), connection_uri.clone())?;
+
+    let (connection_uri, _proxy_tunnel) = match &source.ssh {
+        // the SSH connection goes through the proxy instead
+        Some(_) => (connection_uri, None),
+        None => proxy_connection_uri(proxy.as_ref(), connection_uri)?,
+    };
+
+    replication_lag(&connection_uri, tls)
+}
+
+/// seconds the replica is behind its primary, queried from the replica. `None` if the database is
+/// not a replica.
+pub fn replication_lag(
+    connection_uri: &ConnectionUri,
+    tls: &TlsConfig,
+) -> Result<Option<u64>, ReplibyteError> {
+    match connection_uri {
+        ConnectionUri::Postgres(host, port, username, password, database) => {
+            let output = Command::new("psql")
+                .env("PGPASSWORD", password)
+                .env("PGCONNECT_TIMEOUT", CONNECT_TIMEOUT_SECONDS.to_string())
+                .envs(tls.postgres_env()?)
+                .args([
+                    "-h",
+                    host,
+                    "-p",
+                    port.to_string().as_str(),
+                    "-d",
+                    database,
+                    "-U",
+                    username,
+                    "-tA", // only print the rows, without alignment
+                    "-c",
+                    POSTGRES_LAG_QUERY,
+                ])
+                .output()?;
+
+            postgres_lag(command_output(output)?.as_str())
+        }
+        ConnectionUri::Mysql(host, port, username, password, _)
+        | ConnectionUri::MariaDB(host, port, username, password, _) => {
+            let client = match connection_uri {
+                ConnectionUri::MariaDB(..) => "mariadb",
+                _ => "mysql",
+            };
+
+            let show_status = |query: &str| -> Result<String, ReplibyteError> {
+                let output = Command::new(client)
+                    .args([
+                        "-h",
+                        host,
+                        "-P",
+                        port.to_string().as_str(),
+                        "-u",
+                        username,
+                        &format!("-p{}", password),
+                        &format!("--connect-timeout={}", CONNECT_TIMEOUT_SECONDS),
+                    ])
+                    .args(tls.mysql_args()?)
+                    .args(["-E", "-e", query]) // one `name: value` line per column
+                    .output()?;
+
+                command_output(output)
+            };
+
+            // `SHOW SLAVE STATUS` is removed by MySQL 8.4, `SHOW REPLICA STATUS` needs MySQL 8.0.22 or MariaDB 10.5.1
+            let output = match show_status("SHOW REPLICA STATUS") {
+                Ok(output) => output,
+                Err(_) => show_status("SHOW SLAVE STATUS")?,
+            };
+
+            mysql_lag(output.as_str())
+        }
+        _ => Err(ReplibyteError::Config(
+            "read replicas are only supported by PostgreSQL, MySQL and MariaDB".to_string(),
+        )),
+    }
+}
+
+fn command_output(output: std::process::Output) -> Result<String, ReplibyteError> {
+    if !output.status.success() {
+        return Err(ReplibyteError::Command(format!(
+            "command error: {}",
+            String::from_utf8_lossy(output.stderr.as_slice()).trim()
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(output.stdout.as_slice()).to_string())
+}
+
+fn postgres_lag(output: &str) -> Result<Option<u64>, ReplibyteError> {
+    match output.trim().parse::<i64>() {
+        Ok(lag) if lag < 0 => Ok(None),
+        Ok(lag) => Ok(Some(lag as u64)),
+        Err(_) => Err(ReplibyteError::Command(format!(
+            "unexpected replication lag '{}'",
+            output.trim()
+        ))),
+    }
+}
+
+/// `Seconds_Behind_Source` (or `Seconds_Behind_Master`) of the replica status, which is empty if the
+/// database is not a replica
+fn mysql_lag(output: &str) -> Result<Option<u64>, ReplibyteError> {
+    let lag = output.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        match name.trim() {
+            "Seconds_Behind_Source" | "Seconds_Behind_Master" => Some(value.trim()),
+            _ => None,
+        }
+    });
+
+    match lag {
+        None => Ok(None),
+        Some("NULL") => Err(ReplibyteError::Connection(
+            "the replication is not running".to_string(),
+        )),
+        Some(lag) => lag
+            .parse::<u64>()
+            .map(Some)
+            .map_err(|_| ReplibyteError::Command(format!("unexpected replication lag '{}'", lag))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::errors::ReplibyteError;
+    use crate::source::replica::{mysql_lag, postgres_lag, select_replica};
+
+    #[test]
+    fn replication_lags() {
+        assert_eq!(postgres_lag("0\n").unwrap(), Some(0));
+        assert_eq!(postgres_lag("42\n").unwrap(), Some(42));
+        assert_eq!(postgres_lag("-1\n").unwrap(), None);
+        assert!(postgres_lag("").is_err());
+
+        let status = "*************************** 1. row ***************************\n\
+            \x20            Replica_IO_State: Waiting for source to send event\n\
+            \x20                 Source_Host: primary\n\
+            \x20      Seconds_Behind_Source: 12\n";
+        assert_eq!(mysql_lag(status).unwrap(), Some(12));
+        assert_eq!(mysql_lag("Seconds_Behind_Master: 0\n").unwrap(), Some(0));
+        assert!(mysql_lag("Seconds_Behind_Master: NULL\n").is_err());
+        assert_eq!(mysql_lag("").unwrap(), None);
+    }
+
+    #[test]
+    fn select_replicas() {
+        let check = |replica: &&str| match *replica {
+            "down" => Err(ReplibyteError::Connection("connection refused".to_string())),
+            "lagging" => Ok(Some(3600)),
+            "primary" => Ok(None),
+            _ => Ok(Some(0)),
+        };
+
+        assert_eq!(select_replica(vec!["down", "up"], 60, check), Some("up"));
+        assert_eq!(
+            select_replica(vec!["lagging", "up"], 60, check),
+            Some("lagging")
+        );
+        assert_eq!(
+            select_replica(vec!["down", "primary"], 60, check),
+            Some("primary")
+        );
+        assert_eq!(select_replica(vec!["down", "down"], 60, check), None);
+        assert_eq!(select_replica(Vec::<&str>::new(), 60, check), None);
+    }
+}
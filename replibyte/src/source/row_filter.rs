@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use crate::config::RowFilterConfig;
+use crate::types::Column;
+
+/// Row filters found by the name of a table: `database.table`, or `table` for the sources without databases.
+/// A row is excluded from the dump when it matches all the conditions of a filter of its table.
+pub struct RowFilters<'a> {
+    by_table_name: HashMap<String, Vec<&'a RowFilterConfig>>,
+}
+
+impl<'a> RowFilters<'a> {
+    pub fn new(filters: &'a [RowFilterConfig], with_database_name: bool) -> Self {
+        let mut by_table_name: HashMap<String, Vec<&'a RowFilterConfig>> = HashMap::new();
+
+        for filter in filters {
+            let table_name = if with_database_name {
+                format!("{}.{}", filter.database, filter.table)
+            } else {
+                filter.table.clone()
+            };
+
+            by_table_name.entry(table_name).or_default().push(filter);
+        }
+
+        RowFilters { by_table_name }
+    }
+
+    /// the conditions are checked on the original values of the row
+    pub fn excludes(&self, table_name: &str, row: &[Column]) -> bool {
+        match self.by_table_name.get(table_name) {
+            Some(filters) => filters.iter().any(|filter| {
+                filter
+                    .exclude
+                    .iter()
+                    .all(|condition| condition.matches(row))
+            }),
+            None => false,
+        }
+    }
+
+    /// true if a filter of the table has no conditions, then none of its rows is dumped
+    pub fn excludes_all(&self, table_name: &str) -> bool {
+        match self.by_table_name.get(table_name) {
+            Some(filters) => filters.iter().any(|filter| filter.exclude.is_empty()),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::RowFilterConfig;
+    use crate::types::Column;
+
+    use super::RowFilters;
+
+    #[test]
+    fn exclude_rows() {
+        let filters = serde_yaml::from_str::<Vec<RowFilterConfig>>(
+            r#"
+- database: public
+  table: users
+  exclude:
+    - column: email
+      matches: "@company\\.com$"
+- database: public
+  table: users
+  exclude:
+    - column: country
+      in: [DE, FR]
+    - column: gdpr_deletion
+      equals: true
+"#,
+        )
+        .unwrap();
+        let row_filters = RowFilters::new(&filters, true);
+
+        let row = |email: &str, country: &str, gdpr_deletion: bool| {
+            vec![
+                Column::StringValue("email".to_string(), email.to_string()),
+                Column::StringValue("country".to_string(), country.to_string()),
+                Column::BooleanValue("gdpr_deletion".to_string(), gdpr_deletion),
+            ]
+        };
+
+        for (row, excluded) in [
+            (row("john@company.com", "US", false), true),
+            (row("john@gmail.com", "US", false), false),
+            (row("john@gmail.com", "FR", true), true),
+            (row("john@gmail.com", "FR", false), false),
+            (row("john@gmail.com", "US", true), false),
+        ] {
+            assert_eq!(row_filters.excludes("public.users", &row), excluded);
+        }
+
+        // the filters of the other tables don't apply
+        let row = row("john@company.com", "FR", true);
+        assert!(!row_filters.excludes("public.orders", &row));
+        assert!(!RowFilters::new(&filters, false).excludes("public.users", &row));
+        assert!(RowFilters::new(&filters, false).excludes("users", &row));
+        assert!(!row_filters.excludes_all("public.users"));
+    }
+
+    #[test]
+    fn exclude_all_rows() {
+        let filters = vec![RowFilterConfig {
+            database: "public".to_string(),
+            table: "audit_logs".to_string(),
+            exclude: vec![],
+        }];
+        let row_filters = RowFilters::new(&filters, true);
+
+        let row = vec![Column::NumberValue("id".to_string(), 1)];
+        assert!(row_filters.excludes("public.audit_logs", &row));
+        assert!(row_filters.excludes("public.audit_logs", &[]));
+        assert!(row_filters.excludes_all("public.audit_logs"));
+        assert!(!row_filters.excludes("public.users", &row));
+        assert!(!row_filters.excludes_all("public.users"));
+    }
+}
@@ -0,0 +1,462 @@
+use std::collections::HashMap;
+use std::io::{BufReader, Error, ErrorKind, Read};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use dump_parser::sqlite::{
+    get_column_names_from_create_table_query, get_column_names_from_insert_into_query,
+    get_column_values_from_insert_into_query, get_table_name_from_create_table_query,
+    get_table_name_from_insert_into_query, get_tokens_from_query_str, TableName, Value,
+};
+use dump_parser::utils::{list_sql_queries_from_dump_reader, ListQueryResult};
+
+use crate::connector::Connector;
+use crate::errors::ReplibyteError;
+use crate::source::row_filter::RowFilters;
+use crate::source::Source;
+use crate::transformer::selector::TransformerSelector;
+use crate::types::{Column, OriginalQuery, Query};
+use crate::utils::{binary_exists, wait_for_command};
+
+use super::SourceOptions;
+
+pub struct SQLite<'a> {
+    path: &'a str,
+}
+
+impl<'a> SQLite<'a> {
+    pub fn new(path: &'a str) -> Self {
+        SQLite { path }
+    }
+}
+
+impl<'a> Connector for SQLite<'a> {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        binary_exists("sqlite3")?;
+
+        if !Path::new(self.path).is_file() {
+            return Err(ReplibyteError::from(Error::new(
+                ErrorKind::NotFound,
+                format!("SQLite database '{}' does not exist", self.path),
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Source for SQLite<'a> {
+    fn read<F: FnMut(OriginalQuery, Query)>(
+        &self,
+        options: SourceOptions,
+        query_callback: F,
+    ) -> Result<(), ReplibyteError> {
+        // `.dump` accepts a table name to only dump this one
+        let dump_commands = if options.only_tables.is_empty() {
+            vec![".dump".to_string()]
+        } else {
+            options
+                .only_tables
+                .iter()
+                .map(|cfg| format!(".dump '{}'", cfg.table))
+                .collect::<Vec<_>>()
+        };
+
+        let mut process = Command::new("sqlite3")
+            .arg(self.path)
+            .args(dump_commands)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let stdout = process.stdout.take().ok_or_else(|| {
+            ReplibyteError::Command("Could not capture standard output.".to_string())
+        })?;
+
+        let reader = BufReader::new(stdout);
+
+        read_and_transform(reader, options, query_callback);
+
+        wait_for_command(&mut process)
+    }
+}
+
+/// consume reader and apply transformation on INSERT INTO queries if needed.
+/// `sqlite3 .dump` does not list the column names in the INSERT INTO queries,
+/// so they are taken from the previous CREATE TABLE queries.
+pub fn read_and_transform<R: Read, F: FnMut(OriginalQuery, Query)>(
+    reader: BufReader<R>,
+    options: SourceOptions,
+    mut query_callback: F,
+) {
+    // Transformer by table_name.column_name
+    let transformer_by_table_and_column_name =
+        TransformerSelector::new(options.transformers, false);
+    let row_filters = RowFilters::new(options.filters, false);
+
+    let mut skip_tables_map: HashMap<String, bool> =
+        HashMap::with_capacity(options.skip_config.len());
+    for skip in options.skip_config {
+        let _ = skip_tables_map.insert(skip.table.clone(), true);
+    }
+
+    let mut column_names_by_table_name: HashMap<String, Vec<String>> = HashMap::new();
+
+    match list_sql_queries_from_dump_reader(reader, |query| {
+        let tokens = get_tokens_from_query_str(query);
+
+        if let Some(table_name) = get_table_name_from_create_table_query(&tokens) {
+            let _ = column_names_by_table_name.insert(
+                table_name.value,
+                get_column_names_from_create_table_query(&tokens),
+            );
+        }
+
+        match get_table_name_from_insert_into_query(&tokens) {
+            Some(table_name) if skip_tables_map.contains_key(&table_name.value) => {
+                // skip the rows of this table
+            }
+            Some(table_name) => {
+                let column_names = get_column_names_from_insert_into_query(&tokens)
+                    .or_else(|| column_names_by_table_name.get(&table_name.value).cloned());
+
+                let values = get_column_values_from_insert_into_query(&tokens);
+
+                let transformed_query = match (column_names, values) {
+                    (Some(column_names), Some(values)) if column_names.len() == values.len() => {
+                        // the filtered rows are not dumped
+                        let row = to_row(&column_names, &values);
+                        if row_filters.excludes(table_name.value.as_str(), &row) {
+                            return ListQueryResult::Continue;
+                        }
+
+                        transform_values(
+                            &table_name,
+                            column_names,
+                            values,
+                            &transformer_by_table_and_column_name,
+                        )
+                    }
+                    // the row can't be decoded, keep it as it is unless all the rows are filtered
+                    _ if row_filters.excludes_all(table_name.value.as_str()) => {
+                        return ListQueryResult::Continue;
+                    }
+                    _ => None,
+                };
+
+                match transformed_query {
+                    Some(transformed_query) => {
+                        query_callback(Query(query.as_bytes().to_vec()), transformed_query)
+                    }
+                    None => no_change_query_callback(&mut query_callback, query),
+                }
+            }
+            None => no_change_query_callback(&mut query_callback, query),
+        }
+
+        ListQueryResult::Continue
+    }) {
+        Ok(_) => {}
+        Err(err) => panic!("{:?}", err),
+    }
+}
+
+fn no_change_query_callback<F: FnMut(OriginalQuery, Query)>(query_callback: &mut F, query: &str) {
+    query_callback(
+        // there is no diff between the original and the modified one
+        Query(query.as_bytes().to_vec()),
+        Query(query.as_bytes().to_vec()),
+    );
+}
+
+/// apply the transformers on the values of a row. Return None if no transformer is defined for this table,
+/// then the original query is kept to not alter the values.
+fn transform_values(
+    table_name: &TableName,
+    column_names: Vec<String>,
+    values: Vec<Value>,
+    transformer_by_table_and_column_name: &TransformerSelector,
+) -> Option<Query> {
+    let transformers = column_names
+        .iter()
+        .map(|column_name| {
+            let table_and_column_name = format!("{}.{}", table_name.value, column_name);
+            let transformer =
+                transformer_by_table_and_column_name.get(table_and_column_name.as_str());
+            (table_and_column_name, transformer)
+        })
+        .collect::<Vec<_>>();
+
+    if transformers
+        .iter()
+        .all(|(_, transformer)| transformer.is_none())
+    {
+        return None;
+    }
+
+    // the transformers get the original values of the row to check their conditions
+    let row = to_row(&column_names, &values);
+
+    let mut transformed_values = Vec::with_capacity(values.len());
+
+    for ((column_name, value), (name, transformer)) in
+        column_names.iter().zip(values).zip(transformers)
+    {
+        let value = match transformer {
+            Some(transformer) => {
+                let column = to_column(column_name, value)?;
+                // apply transformation on the column
+                to_value(transformer.transform_column(name.as_str(), column, &row))
+            }
+            None => value,
+        };
+
+        transformed_values.push(value);
+    }
+
+    Some(to_query(table_name, column_names, transformed_values))
+}
+
+fn to_row(column_names: &[String], values: &[Value]) -> Vec<Column> {
+    column_names
+        .iter()
+        .zip(values.iter())
+        .filter_map(|(column_name, value)| to_column(column_name, value.clone()))
+        .collect()
+}
+
+fn to_column(column_name: &str, value: Value) -> Option<Column> {
+    let column_name = column_name.to_string();
+
+    let column = match value {
+        Value::Number(value) if value.contains('.') || value.contains('e') => {
+            Column::FloatNumberValue(column_name, value.parse::<f64>().ok()?)
+        }
+        Value::Number(value) => Column::NumberValue(column_name, value.parse::<i128>().ok()?),
+        Value::String(value) => Column::StringValue(column_name, value),
+        Value::Blob(value) => Column::StringValue(column_name, value),
+        Value::Null => Column::None(column_name),
+    };
+
+    Some(column)
+}
+
+fn to_value(column: Column) -> Value {
+    match column {
+        Column::NumberValue(_, value) => Value::Number(value.to_string()),
+        Column::FloatNumberValue(_, value) => Value::Number(value.to_string()),
+        Column::StringValue(_, value) => Value::String(value),
+        Column::CharValue(_, value) => Value::String(value.to_string()),
+        // SQLite stores booleans as integers
+        Column::BooleanValue(_, value) => Value::Number((value as u8).to_string()),
+        Column::None(_) => Value::Null,
+    }
+}
+
+fn to_query(table_name: &TableName, column_names: Vec<String>, values: Vec<Value>) -> Query {
+    let query_string = format!(
+        "INSERT INTO {}({}) VALUES({});",
+        table_name.quoted(),
+        column_names
+            .iter()
+            .map(|column_name| format!("\"{}\"", column_name.replace('"', "\"\"")))
+            .collect::<Vec<String>>()
+            .join(","),
+        values
+            .iter()
+            .map(|value| match value {
+                Value::Number(value) => value.clone(),
+                Value::String(value) => format!("'{}'", value.replace('\'', "''")),
+                Value::Blob(value) => format!("X'{}'", value),
+                Value::Null => "NULL".to_string(),
+            })
+            .collect::<Vec<String>>()
+            .join(","),
+    );
+
+    Query(query_string.into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use tempfile::tempdir;
+
+    use crate::config::{RowFilterConfig, SkipConfig};
+    use crate::connector::Connector;
+    use crate::destination::sqlite::SQLite as SQLiteDestination;
+    use crate::destination::Destination;
+    use crate::source::sqlite::{read_and_transform, SQLite};
+    use crate::source::{Source, SourceOptions};
+    use crate::transformer::redacted::{RedactedTransformer, RedactedTransformerOptions};
+    use crate::transformer::Transformer;
+
+    const DUMP: &str = r#"PRAGMA foreign_keys=OFF;
+BEGIN TRANSACTION;
+CREATE TABLE users(id INTEGER PRIMARY KEY, name TEXT, avatar BLOB);
+INSERT INTO users VALUES(1,'Romaric',X'CAFE');
+INSERT INTO users VALUES(2,replace('Benny\nFab','\n',char(10)),NULL);
+CREATE TABLE "audit logs"(id INTEGER, message TEXT);
+INSERT INTO "audit logs" VALUES(1,'user 1 logged in');
+COMMIT;
+"#;
+
+    fn read(transformers: Vec<Box<dyn Transformer>>, skip_config: Vec<SkipConfig>) -> Vec<String> {
+        let source_options = SourceOptions {
+            transformers: &transformers,
+            skip_config: &skip_config,
+            database_subset: &None,
+            only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
+        };
+
+        let mut queries = vec![];
+        let reader = BufReader::new(DUMP.as_bytes());
+        read_and_transform(reader, source_options, |_original_query, query| {
+            queries.push(String::from_utf8(query.data().to_vec()).unwrap());
+        });
+
+        // queries are prefixed by the new line separating them from the previous one
+        queries
+            .into_iter()
+            .map(|query| query.trim_start().to_string())
+            .filter(|query| query.starts_with("INSERT INTO"))
+            .collect()
+    }
+
+    #[test]
+    fn list_rows() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("replibyte.db");
+        let path = path.to_str().unwrap();
+
+        let mut p = SQLite::new(path);
+        assert!(p.init().is_err());
+
+        let mut destination = SQLiteDestination::new(path, true);
+        destination.init().unwrap();
+        destination
+            .write_chunks(&mut std::iter::once(DUMP.as_bytes().to_vec()))
+            .unwrap();
+
+        let mut p = SQLite::new(path);
+        assert!(p.init().is_ok());
+
+        let transformers = vec![];
+        let source_options = SourceOptions {
+            transformers: &transformers,
+            skip_config: &vec![],
+            database_subset: &None,
+            only_tables: &vec![],
+            filters: &vec![],
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
+        };
+
+        let mut rows = 0;
+        assert!(p
+            .read(source_options, |original_query, query| {
+                assert!(original_query.data().len() > 0);
+                if String::from_utf8_lossy(query.data())
+                    .trim_start()
+                    .starts_with("INSERT INTO")
+                {
+                    rows += 1;
+                }
+            })
+            .is_ok());
+        assert_eq!(rows, 3);
+    }
+
+    #[test]
+    fn keep_rows_without_transformers() {
+        assert_eq!(
+            read(vec![], vec![]),
+            vec![
+                "INSERT INTO users VALUES(1,'Romaric',X'CAFE');",
+                r"INSERT INTO users VALUES(2,replace('Benny\nFab','\n',char(10)),NULL);",
+                r#"INSERT INTO "audit logs" VALUES(1,'user 1 logged in');"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn transform_rows() {
+        let t1: Box<dyn Transformer> = Box::new(RedactedTransformer::new(
+            "main",
+            "users",
+            "name",
+            RedactedTransformerOptions {
+                character: '*',
+                width: 4,
+            },
+        ));
+
+        assert_eq!(
+            read(vec![t1], vec![]),
+            vec![
+                r#"INSERT INTO users("id","name","avatar") VALUES(1,'Rom****',X'CAFE');"#,
+                "INSERT INTO users(\"id\",\"name\",\"avatar\") VALUES(2,'Ben****',NULL);",
+                r#"INSERT INTO "audit logs" VALUES(1,'user 1 logged in');"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn skip_tables() {
+        let skip_config = vec![SkipConfig {
+            database: "main".to_string(),
+            table: "audit logs".to_string(),
+        }];
+
+        assert_eq!(read(vec![], skip_config).len(), 2);
+    }
+
+    #[test]
+    fn filter_rows() {
+        let filters = serde_yaml::from_str::<Vec<RowFilterConfig>>(
+            r#"
+- database: main
+  table: users
+  exclude:
+    - column: name
+      matches: "^Rom"
+"#,
+        )
+        .unwrap();
+        let source_options = SourceOptions {
+            transformers: &vec![],
+            skip_config: &vec![],
+            database_subset: &None,
+            only_tables: &vec![],
+            filters: &filters,
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
+        };
+
+        let mut queries = vec![];
+        let reader = BufReader::new(DUMP.as_bytes());
+        read_and_transform(reader, source_options, |_original_query, query| {
+            queries.push(String::from_utf8(query.data().to_vec()).unwrap());
+        });
+
+        let queries = queries
+            .iter()
+            .map(|query| query.trim_start())
+            .filter(|query| query.starts_with("INSERT INTO"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            queries,
+            vec![
+                r"INSERT INTO users VALUES(2,replace('Benny\nFab','\n',char(10)),NULL);",
+                r#"INSERT INTO "audit logs" VALUES(1,'user 1 logged in');"#,
+            ]
+        );
+    }
+}
@@ -0,0 +1,40 @@
+use std::io::{stdin, BufReader, Read};
+
+use crate::connector::Connector;
+use crate::errors::ReplibyteError;
+use crate::source::sqlite::read_and_transform;
+use crate::types::{OriginalQuery, Query};
+use crate::Source;
+use crate::SourceOptions;
+
+/// Source SQLite dump from STDIN
+#[derive(Default)]
+pub struct SQLiteStdin {}
+
+impl Connector for SQLiteStdin {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        Ok(())
+    }
+}
+
+impl Source for SQLiteStdin {
+    fn read<F: FnMut(OriginalQuery, Query)>(
+        &self,
+        options: SourceOptions,
+        query_callback: F,
+    ) -> Result<(), ReplibyteError> {
+        read_dump(stdin(), options, query_callback)
+    }
+}
+
+/// read a SQLite dump and transform its rows
+pub fn read_dump<R: Read, F: FnMut(OriginalQuery, Query)>(
+    reader: R,
+    options: SourceOptions,
+    query_callback: F,
+) -> Result<(), ReplibyteError> {
+    let reader = BufReader::new(reader);
+    read_and_transform(reader, options, query_callback);
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use crate::connector::Connector;
+use crate::errors::ReplibyteError;
+use crate::source::{Source, SourceOptions};
+use crate::throttle::Throttle;
+use crate::types::{OriginalQuery, Query};
+
+/// Throttled limits the rate at which a source is read, the dump command of the database waits
+/// while its output is not read
+pub struct Throttled<S: Source> {
+    source: S,
+    throttle: Throttle,
+}
+
+impl<S: Source> Throttled<S> {
+    pub fn new(source: S, throttle: Throttle) -> Self {
+        Throttled { source, throttle }
+    }
+}
+
+impl<S: Source> Connector for Throttled<S> {
+    fn init(&mut self) -> Result<(), ReplibyteError> {
+        self.source.init()
+    }
+}
+
+impl<S: Source> Source for Throttled<S> {
+    fn read<F: FnMut(OriginalQuery, Query)>(
+        &self,
+        options: SourceOptions,
+        mut query_callback: F,
+    ) -> Result<(), ReplibyteError> {
+        self.source.read(options, |original_query, query| {
+            // the original queries are the bytes read from the database
+            self.throttle.consume(original_query.data().len());
+            query_callback(original_query, query)
+        })
+    }
+}
@@ -0,0 +1,627 @@
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use log::error;
+use russh::client::{self, Handle};
+use russh::keys::agent::client::AgentClient;
+use russh::keys::{
+    check_known_hosts, load_secret_key, PrivateKey, PrivateKeyWithHashAlg, PublicKeyOrCertificate,
+};
+use russh::ChannelStream;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+
+use crate::config::{ConnectionUri, SshConfig};
+use crate::errors::ReplibyteError;
+use crate::proxy::Proxy;
+
+/// host the forwarded port listens on
+const LOCAL_HOST: &str = "127.0.0.1";
+
+const DEFAULT_SSH_PORT: u16 = 22;
+
+/// time given to open the SSH connections to all the hosts
+const TUNNEL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// private keys tried when no private key is configured, relative to the home directory
+const DEFAULT_PRIVATE_KEYS: [&str; 3] = [".ssh/id_ed25519", ".ssh/id_ecdsa", ".ssh/id_rsa"];
+
+/// SshTunnel forwards a local port to a remote host through an SSH connection, jumping through the proxy hosts if any.
+/// The tunnel is closed when it is dropped.
+pub struct SshTunnel {
+    local_port: u16,
+    shutdown: Option<oneshot::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+#[derive(Debug, PartialEq)]
+struct SshHost {
+    user: String,
+    host: String,
+    port: u16,
+}
+
+/// SshConnector opens SSH connections to the SSH host, jumping through the proxy hosts if any.
+pub struct SshConnector {
+    hosts: Vec<SshHost>,
+    proxy: Option<Proxy>,
+    private_keys: Vec<Arc<PrivateKey>>,
+    use_agent: bool,
+}
+
+/// SshSession is an SSH connection to the SSH host, the connections to the jump hosts are kept alive with it.
+pub struct SshSession {
+    sessions: Vec<Handle<ClientHandler>>,
+}
+
+impl SshConnector {
+    pub fn new(ssh: &SshConfig, proxy: Option<&Proxy>) -> Result<Self, ReplibyteError> {
+        let hosts = ssh_hosts(ssh)?;
+
+        let use_agent = match ssh.agent {
+            Some(true) if std::env::var_os("SSH_AUTH_SOCK").is_none() => {
+                return Err(ReplibyteError::Config(
+                    "ssh-agent is not running, SSH_AUTH_SOCK is not set".to_string(),
+                ));
+            }
+            Some(agent) => agent,
+            None => std::env::var_os("SSH_AUTH_SOCK").is_some(),
+        };
+
+        // the keys are loaded beforehand to prompt for their passphrase if needed
+        let private_keys = private_keys(ssh, use_agent)?;
+
+        Ok(SshConnector {
+            hosts,
+            proxy: proxy.cloned(),
+            private_keys,
+            use_agent,
+        })
+    }
+
+    pub async fn connect(&self) -> Result<SshSession, ReplibyteError> {
+        match tokio::time::timeout(
+            TUNNEL_TIMEOUT,
+            open_sessions(
+                &self.hosts,
+                self.proxy.as_ref(),
+                &self.private_keys,
+                self.use_agent,
+            ),
+        )
+        .await
+        {
+            Ok(Ok(sessions)) => Ok(SshSession { sessions }),
+            Ok(Err(err)) => Err(err),
+            Err(_) => Err(ReplibyteError::Connection(
+                "ssh tunnel error: timed out while connecting".to_string(),
+            )),
+        }
+    }
+}
+
+impl SshSession {
+    fn handle(&self) -> &Handle<ClientHandler> {
+        // the sessions of the jump hosts carry the session of the SSH host
+        self.sessions.last().unwrap()
+    }
+
+    /// start a subsystem (e.g. `sftp`) on the SSH host and return the stream to talk to it
+    pub async fn open_subsystem(
+        &self,
+        name: &str,
+    ) -> Result<ChannelStream<client::Msg>, ReplibyteError> {
+        let channel = self
+            .handle()
+            .channel_open_session()
+            .await
+            .map_err(|err| ReplibyteError::Connection(format!("ssh error: {}", err)))?;
+
+        channel
+            .request_subsystem(true, name)
+            .await
+            .map_err(|err| ReplibyteError::Connection(format!("ssh error: {}", err)))?;
+
+        Ok(channel.into_stream())
+    }
+}
+
+impl SshTunnel {
+    pub fn open(
+        ssh: &SshConfig,
+        proxy: Option<&Proxy>,
+        remote_host: &str,
+        remote_port: u16,
+    ) -> Result<Self, ReplibyteError> {
+        let connector = SshConnector::new(ssh, proxy)?;
+
+        let remote_host = remote_host.to_string();
+        let (ready_sender, ready_receiver) = mpsc::channel();
+        let (shutdown_sender, shutdown_receiver) = oneshot::channel();
+
+        // the tunnel runs on its own runtime to keep forwarding the connections while the caller is blocked
+        let thread = std::thread::Builder::new()
+            .name("ssh-tunnel".to_string())
+            .spawn(move || {
+                let runtime = match tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                {
+                    Ok(runtime) => runtime,
+                    Err(err) => {
+                        let _ = ready_sender.send(Err(ReplibyteError::from(err)));
+                        return;
+                    }
+                };
+
+                runtime.block_on(async move {
+                    let session = match connector.connect().await {
+                        Ok(session) => session,
+                        Err(err) => {
+                            let _ = ready_sender.send(Err(err));
+                            return;
+                        }
+                    };
+
+                    let listener = match TcpListener::bind((LOCAL_HOST, 0)).await {
+                        Ok(listener) => listener,
+                        Err(err) => {
+                            let _ = ready_sender.send(Err(ReplibyteError::from(err)));
+                            return;
+                        }
+                    };
+
+                    let local_port = match listener.local_addr() {
+                        Ok(local_address) => local_address.port(),
+                        Err(err) => {
+                            let _ = ready_sender.send(Err(ReplibyteError::from(err)));
+                            return;
+                        }
+                    };
+
+                    let _ = ready_sender.send(Ok(local_port));
+
+                    let session = Arc::new(session);
+                    let mut shutdown_receiver = shutdown_receiver;
+
+                    loop {
+                        tokio::select! {
+                            _ = &mut shutdown_receiver => break,
+                            accepted = listener.accept() => match accepted {
+                                Ok((stream, _)) => {
+                                    let session = session.clone();
+                                    let remote_host = remote_host.clone();
+
+                                    tokio::spawn(async move {
+                                        if let Err(err) =
+                                            forward(session.handle(), stream, remote_host.as_str(), remote_port).await
+                                        {
+                                            error!("ssh tunnel error: {}", err);
+                                        }
+                                    });
+                                }
+                                Err(err) => {
+                                    error!("ssh tunnel error: {}", err);
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                });
+            })?;
+
+        match ready_receiver.recv() {
+            Ok(Ok(local_port)) => Ok(SshTunnel {
+                local_port,
+                shutdown: Some(shutdown_sender),
+                thread: Some(thread),
+            }),
+            Ok(Err(err)) => {
+                let _ = thread.join();
+                Err(err)
+            }
+            Err(_) => {
+                let _ = thread.join();
+                Err(ReplibyteError::Connection(
+                    "ssh tunnel error: the tunnel stopped unexpectedly".to_string(),
+                ))
+            }
+        }
+    }
+
+    pub fn local_port(&self) -> u16 {
+        self.local_port
+    }
+}
+
+impl Drop for SshTunnel {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// open a tunnel to the database if the SSH config is set, and return the connection uri going through it.
+/// The SSH connection goes through the proxy if any.
+pub fn tunnel_connection_uri(
+    ssh: Option<&SshConfig>,
+    proxy: Option<&Proxy>,
+    connection_uri: ConnectionUri,
+) -> Result<(ConnectionUri, Option<SshTunnel>), ReplibyteError> {
+    let ssh = match ssh {
+        Some(ssh) => ssh,
+        None => return Ok((connection_uri, None)),
+    };
+
+    let (host, port) = connection_uri.host_and_port().ok_or_else(|| {
+        ReplibyteError::Config(
+            "ssh tunnels are only supported by PostgreSQL, MySQL, MariaDB and SQL Server"
+                .to_string(),
+        )
+    })?;
+
+    let tunnel = SshTunnel::open(ssh, proxy, host, port)?;
+    let connection_uri = connection_uri.with_host_and_port(LOCAL_HOST, tunnel.local_port())?;
+
+    Ok((connection_uri, Some(tunnel)))
+}
+
+struct ClientHandler {
+    host: String,
+    port: u16,
+}
+
+impl client::Handler for ClientHandler {
+    type Error = russh::Error;
+
+    async fn check_server_key(
+        &mut self,
+        server_public_key: &PublicKeyOrCertificate,
+    ) -> Result<bool, Self::Error> {
+        // as with `StrictHostKeyChecking=yes`, the host key must already be in the known hosts
+        match server_public_key {
+            PublicKeyOrCertificate::PublicKey { key, .. } => {
+                Ok(check_known_hosts(self.host.as_str(), self.port, key).unwrap_or(false))
+            }
+            PublicKeyOrCertificate::Certificate(_) => Ok(false),
+        }
+    }
+}
+
+/// connect to each host through the previous one, the last session is connected to the SSH host
+async fn open_sessions(
+    hosts: &[SshHost],
+    proxy: Option<&Proxy>,
+    private_keys: &[Arc<PrivateKey>],
+    use_agent: bool,
+) -> Result<Vec<Handle<ClientHandler>>, ReplibyteError> {
+    let config = Arc::new(client::Config::default());
+    let mut sessions: Vec<Handle<ClientHandler>> = vec![];
+
+    for host in hosts {
+        let handler = ClientHandler {
+            host: host.host.clone(),
+            port: host.port,
+        };
+
+        let session = match sessions.last() {
+            None => match proxy {
+                Some(proxy) => {
+                    let stream = proxy.connect(host.host.as_str(), host.port).await?;
+                    client::connect_stream(config.clone(), stream, handler).await
+                }
+                None => {
+                    client::connect(config.clone(), (host.host.as_str(), host.port), handler).await
+                }
+            },
+            Some(previous_session) => {
+                let channel = previous_session
+                    .channel_open_direct_tcpip(host.host.as_str(), host.port as u32, LOCAL_HOST, 0)
+                    .await
+                    .map_err(|err| connection_error(host, err))?;
+
+                client::connect_stream(config.clone(), channel.into_stream(), handler).await
+            }
+        };
+
+        let mut session = session.map_err(|err| connection_error(host, err))?;
+        authenticate(&mut session, host, private_keys, use_agent).await?;
+        sessions.push(session);
+    }
+
+    Ok(sessions)
+}
+
+async fn authenticate(
+    session: &mut Handle<ClientHandler>,
+    host: &SshHost,
+    private_keys: &[Arc<PrivateKey>],
+    use_agent: bool,
+) -> Result<(), ReplibyteError> {
+    let rsa_hash = session
+        .best_supported_rsa_hash()
+        .await
+        .map_err(|err| connection_error(host, err))?
+        .flatten();
+
+    if use_agent {
+        let mut agent = AgentClient::connect_env()
+            .await
+            .map_err(|err| ReplibyteError::Connection(format!("ssh-agent error: {}", err)))?;
+
+        let identities = agent
+            .request_identities()
+            .await
+            .map_err(|err| ReplibyteError::Connection(format!("ssh-agent error: {}", err)))?;
+
+        for identity in identities {
+            let public_key = identity.public_key().into_owned();
+            let hash_alg = if public_key.algorithm().is_rsa() {
+                rsa_hash
+            } else {
+                None
+            };
+
+            let result = session
+                .authenticate_publickey_with(host.user.as_str(), public_key, hash_alg, &mut agent)
+                .await
+                .map_err(|err| ReplibyteError::Connection(format!("ssh-agent error: {}", err)))?;
+
+            if result.success() {
+                return Ok(());
+            }
+        }
+    }
+
+    for private_key in private_keys {
+        let result = session
+            .authenticate_publickey(
+                host.user.as_str(),
+                PrivateKeyWithHashAlg::new(private_key.clone(), rsa_hash),
+            )
+            .await
+            .map_err(|err| connection_error(host, err))?;
+
+        if result.success() {
+            return Ok(());
+        }
+    }
+
+    Err(ReplibyteError::Connection(format!(
+        "ssh tunnel error: unable to authenticate as {}@{}",
+        host.user, host.host
+    )))
+}
+
+async fn forward(
+    session: &Handle<ClientHandler>,
+    mut stream: TcpStream,
+    remote_host: &str,
+    remote_port: u16,
+) -> Result<(), ReplibyteError> {
+    let local_port = stream.local_addr()?.port();
+
+    let channel = session
+        .channel_open_direct_tcpip(
+            remote_host,
+            remote_port as u32,
+            LOCAL_HOST,
+            local_port as u32,
+        )
+        .await
+        .map_err(|err| ReplibyteError::Connection(format!("{}", err)))?;
+
+    let mut channel = channel.into_stream();
+    let _ = tokio::io::copy_bidirectional(&mut stream, &mut channel).await?;
+
+    Ok(())
+}
+
+fn connection_error(host: &SshHost, err: russh::Error) -> ReplibyteError {
+    match err {
+        russh::Error::UnknownKey => ReplibyteError::Connection(format!(
+                "ssh tunnel error: the host key of {host} is unknown or has changed, add it to ~/.ssh/known_hosts (e.g. `ssh-keyscan -p {port} {host} >> ~/.ssh/known_hosts`)",
+                host = host.host,
+                port = host.port
+            )),
+        err => ReplibyteError::Connection(format!("ssh tunnel error ({}:{}): {}", host.host, host.port, err)),
+    }
+}
+
+/// hosts to connect to, in order: the jump hosts then the SSH host
+fn ssh_hosts(ssh: &SshConfig) -> Result<Vec<SshHost>, ReplibyteError> {
+    let mut hosts = vec![];
+
+    for jump_host in ssh.proxy_jump.iter().flatten() {
+        hosts.push(parse_ssh_host(jump_host.as_str(), ssh.user.as_str())?);
+    }
+
+    hosts.push(SshHost {
+        user: ssh.user.clone(),
+        host: ssh.host.clone(),
+        port: ssh.port.unwrap_or(DEFAULT_SSH_PORT),
+    });
+
+    Ok(hosts)
+}
+
+/// parse a `[user@]host[:port]` destination
+fn parse_ssh_host(destination: &str, default_user: &str) -> Result<SshHost, ReplibyteError> {
+    let (user, address) = destination
+        .rsplit_once('@')
+        .unwrap_or((default_user, destination));
+
+    let (host, port) = match address.rsplit_once(':') {
+        // IPv6 address without port (e.g. `::1`)
+        Some((host, _)) if host.contains(':') && !host.starts_with('[') => (address, None),
+        Some((host, port)) => {
+            let port = port.parse::<u16>().map_err(|_| {
+                ReplibyteError::Config(format!(
+                    "invalid port in the ssh destination '{}'",
+                    destination
+                ))
+            })?;
+
+            (host, Some(port))
+        }
+        None => (address, None),
+    };
+
+    Ok(SshHost {
+        user: user.to_string(),
+        host: host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string(),
+        port: port.unwrap_or(DEFAULT_SSH_PORT),
+    })
+}
+
+fn private_keys(ssh: &SshConfig, use_agent: bool) -> Result<Vec<Arc<PrivateKey>>, ReplibyteError> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+
+    let paths = match &ssh.private_key {
+        Some(private_key) => match (private_key.strip_prefix("~/"), &home) {
+            (Some(relative_path), Some(home)) => vec![home.join(relative_path)],
+            _ => vec![PathBuf::from(private_key)],
+        },
+        // the keys of the agent are used instead of the default keys
+        None if use_agent => vec![],
+        None => match &home {
+            Some(home) => DEFAULT_PRIVATE_KEYS
+                .iter()
+                .map(|private_key| home.join(private_key))
+                .filter(|path| path.exists())
+                .collect(),
+            None => vec![],
+        },
+    };
+
+    let passphrase = ssh.passphrase()?;
+
+    paths
+        .iter()
+        .map(|path| {
+            let private_key = match load_secret_key(path, passphrase.as_deref()) {
+                Err(russh::keys::Error::KeyIsEncrypted) => {
+                    // prompt for the passphrase, as the ssh client does
+                    let passphrase = rpassword::prompt_password(format!(
+                        "Enter passphrase for key '{}': ",
+                        path.display()
+                    ))?;
+
+                    load_secret_key(path, Some(passphrase.as_str()))
+                }
+                private_key => private_key,
+            };
+
+            private_key.map(Arc::new).map_err(|err| {
+                ReplibyteError::Config(format!(
+                    "unable to load the private key '{}': {}",
+                    path.display(),
+                    err
+                ))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{ConnectionUri, SshConfig};
+    use crate::ssh::{parse_ssh_host, private_keys, ssh_hosts, tunnel_connection_uri, SshHost};
+
+    fn ssh_config() -> SshConfig {
+        SshConfig {
+            host: "db.internal".to_string(),
+            port: None,
+            user: "deploy".to_string(),
+            private_key: None,
+            passphrase: None,
+            proxy_jump: None,
+            agent: None,
+        }
+    }
+
+    fn ssh_host(user: &str, host: &str, port: u16) -> SshHost {
+        SshHost {
+            user: user.to_string(),
+            host: host.to_string(),
+            port,
+        }
+    }
+
+    #[test]
+    fn parse_destinations() {
+        assert_eq!(
+            parse_ssh_host("bastion", "deploy").unwrap(),
+            ssh_host("deploy", "bastion", 22)
+        );
+        assert_eq!(
+            parse_ssh_host("jump@bastion:2222", "deploy").unwrap(),
+            ssh_host("jump", "bastion", 2222)
+        );
+        assert_eq!(
+            parse_ssh_host("jump@[::1]:2222", "deploy").unwrap(),
+            ssh_host("jump", "::1", 2222)
+        );
+        assert_eq!(
+            parse_ssh_host("::1", "deploy").unwrap(),
+            ssh_host("deploy", "::1", 22)
+        );
+        assert!(parse_ssh_host("bastion:ssh", "deploy").is_err());
+    }
+
+    #[test]
+    fn hosts() {
+        let ssh = SshConfig {
+            port: Some(2222),
+            proxy_jump: Some(vec![
+                "jump@bastion".to_string(),
+                "internal-bastion:2200".to_string(),
+            ]),
+            ..ssh_config()
+        };
+
+        assert_eq!(
+            ssh_hosts(&ssh).unwrap(),
+            vec![
+                ssh_host("jump", "bastion", 22),
+                ssh_host("deploy", "internal-bastion", 2200),
+                ssh_host("deploy", "db.internal", 2222),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_private_key() {
+        let ssh = SshConfig {
+            private_key: Some("/does/not/exist/id_ed25519".to_string()),
+            ..ssh_config()
+        };
+
+        assert!(private_keys(&ssh, false).is_err());
+        assert!(private_keys(&ssh_config(), true).unwrap().is_empty());
+    }
+
+    #[test]
+    fn tunnel_without_ssh_config() {
+        let connection_uri = ConnectionUri::SQLite("/tmp/db.sqlite".to_string());
+
+        let (connection_uri, tunnel) = tunnel_connection_uri(None, None, connection_uri).unwrap();
+        assert_eq!(
+            connection_uri,
+            ConnectionUri::SQLite("/tmp/db.sqlite".to_string())
+        );
+        assert!(tunnel.is_none());
+
+        assert!(tunnel_connection_uri(Some(&ssh_config()), None, connection_uri).is_err());
+    }
+}
@@ -0,0 +1,340 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufReader, Error, ErrorKind, Read};
+use std::sync::mpsc;
+use std::thread;
+
+use dump_parser::utils::{
+    list_mysql_queries_from_dump_reader, list_sql_queries_from_dump_reader, ListQueryResult,
+};
+use serde::Serialize;
+
+use crate::datastore::{Datastore, ReadOptions};
+use crate::destination::is_session_query;
+use crate::destination::statement_filter::{is_data_query, is_transaction_query};
+use crate::destination::table_filter::query_table_name;
+use crate::errors::ReplibyteError;
+use crate::source::dump_file::DumpType;
+use crate::tasks::transform::ChunksReader;
+use crate::tasks::Progress;
+use crate::types::Bytes;
+
+/// DumpSummary is what is compared between two dumps: the rows of their tables and the queries creating
+/// their database objects
+#[derive(Debug, Default)]
+pub struct DumpSummary {
+    /// rows of each table, an `INSERT INTO` query or a `COPY` row is a row
+    pub tables: BTreeMap<String, usize>,
+    /// queries creating the database objects (e.g. `CREATE TABLE`), in the order of the dump
+    pub schema: Vec<String>,
+    progress: Progress,
+}
+
+impl DumpSummary {
+    /// read the dump from the datastore, after the dumps it is based on.
+    /// `dump_type` is `None` for the dumps created by an older version of Replibyte
+    pub fn read(
+        datastore: &dyn Datastore,
+        options: &ReadOptions,
+        dump_type: Option<DumpType>,
+    ) -> Result<Self, ReplibyteError> {
+        // bound to 1 to avoid eating too much memory if we download the dump faster than we read it
+        let (tx, rx) = mpsc::sync_channel::<Bytes>(1);
+
+        thread::scope(|scope| {
+            let join_handle = scope.spawn(move || {
+                datastore.read_with_bases(options, &mut |data| {
+                    let _ = tx.send(data);
+                })
+            });
+
+            let summary = DumpSummary::from_reader(ChunksReader::new(rx.into_iter()), dump_type);
+
+            let read_result = join_handle.join().unwrap_or_else(|_| {
+                Err(ReplibyteError::from(Error::new(
+                    ErrorKind::Other,
+                    "the download of the dump panicked",
+                )))
+            });
+
+            // a failed download truncates the dump, its error comes first
+            read_result.and(summary)
+        })
+    }
+
+    /// summary of a SQL dump, the MongoDB archives are not supported
+    pub fn from_reader<R: Read>(
+        reader: R,
+        dump_type: Option<DumpType>,
+    ) -> Result<Self, ReplibyteError> {
+        let mut summary = DumpSummary::default();
+        let reader = BufReader::new(reader);
+
+        let query_callback = |query: &str| {
+            summary.add_query(query);
+            ListQueryResult::Continue
+        };
+
+        let result = match dump_type {
+            Some(DumpType::Mysql) | Some(DumpType::MariaDB) => {
+                list_mysql_queries_from_dump_reader(reader, query_callback)
+            }
+            _ => list_sql_queries_from_dump_reader(reader, query_callback),
+        };
+
+        match result {
+            Ok(_) => Ok(summary),
+            Err(err) => Err(ReplibyteError::from(err)),
+        }
+    }
+
+    fn add_query(&mut self, query: &str) {
+        // the rows of a COPY block are not SQL, an empty line is a row as well
+        if self.progress.is_copy {
+            self.count_rows(query);
+            return;
+        }
+
+        let query = query.trim();
+        if query.is_empty() || is_comment(query) {
+            return;
+        }
+
+        if is_data_query(query) || is_copy_query(query) {
+            self.count_rows(query);
+            return;
+        }
+
+        if is_session_query(query) || is_transaction_query(query) {
+            return;
+        }
+
+        // the tables without rows are in the summary as well
+        if let Some(table) = query_table_name(query) {
+            let _ = self.tables.entry(table).or_insert(0);
+        }
+
+        self.schema.push(query.to_string());
+    }
+
+    fn count_rows(&mut self, query: &str) {
+        let rows = self.progress.rows;
+        self.progress.count_rows(query.as_bytes());
+
+        if let (true, Some(table)) = (self.progress.rows > rows, &self.progress.table) {
+            *self.tables.entry(table.clone()).or_insert(0) += self.progress.rows - rows;
+        }
+    }
+}
+
+/// the comments and the psql meta-commands change between two dumps, e.g. `-- Dump completed on 2022-05-01 10:00:00`
+/// or `\restrict <random key>`. The MySQL conditional comments (e.g. `/*!40101 SET NAMES utf8mb4 */;`) are queries
+fn is_comment(query: &str) -> bool {
+    query.starts_with("--")
+        || query.starts_with('\\')
+        || (query.starts_with("/*") && !query.starts_with("/*!"))
+}
+
+fn is_copy_query(query: &str) -> bool {
+    matches!(query.get(..5), Some(prefix) if prefix.eq_ignore_ascii_case("COPY "))
+}
+
+/// TableDiff compares a table of one or both dumps
+#[derive(Debug, PartialEq, Serialize)]
+pub struct TableDiff {
+    pub table: String,
+    /// rows in the first dump, `None` if the table is not in it
+    pub from_rows: Option<usize>,
+    /// rows in the second dump, `None` if the table is not in it
+    pub to_rows: Option<usize>,
+    /// the queries creating the table, its indexes or its constraints are different
+    pub schema_changed: bool,
+}
+
+/// DumpDiff lists what changed from a dump to another one
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DumpDiff {
+    /// tables of both dumps, sorted by name
+    pub tables: Vec<TableDiff>,
+    /// schema queries of the first dump which are not in the second one
+    pub removed_queries: Vec<String>,
+    /// schema queries of the second dump which are not in the first one
+    pub added_queries: Vec<String>,
+}
+
+impl DumpDiff {
+    pub fn new(from: &DumpSummary, to: &DumpSummary) -> Self {
+        let from_schema = from.schema.iter().collect::<BTreeSet<_>>();
+        let to_schema = to.schema.iter().collect::<BTreeSet<_>>();
+
+        let removed_queries = from
+            .schema
+            .iter()
+            .filter(|query| !to_schema.contains(query))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let added_queries = to
+            .schema
+            .iter()
+            .filter(|query| !from_schema.contains(query))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let changed_tables = removed_queries
+            .iter()
+            .chain(added_queries.iter())
+            .filter_map(|query| query_table_name(query))
+            .collect::<BTreeSet<_>>();
+
+        let table_names = from
+            .tables
+            .keys()
+            .chain(to.tables.keys())
+            .collect::<BTreeSet<_>>();
+
+        let tables = table_names
+            .into_iter()
+            .map(|table| TableDiff {
+                table: table.clone(),
+                from_rows: from.tables.get(table).copied(),
+                to_rows: to.tables.get(table).copied(),
+                schema_changed: changed_tables.contains(table),
+            })
+            .collect();
+
+        DumpDiff {
+            tables,
+            removed_queries,
+            added_queries,
+        }
+    }
+
+    /// true if both dumps have the same tables, rows and schema
+    pub fn is_empty(&self) -> bool {
+        self.removed_queries.is_empty()
+            && self.added_queries.is_empty()
+            && self
+                .tables
+                .iter()
+                .all(|table| table.from_rows == table.to_rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source::dump_file::DumpType;
+    use crate::tasks::diff::{DumpDiff, DumpSummary, TableDiff};
+
+    const POSTGRES_DUMP: &str = "--
+-- PostgreSQL database dump
+--
+
+\\restrict 8BPPD5o62pnyBLFHgAgeJbVntqCDV4v
+
+SET client_encoding = 'UTF8';
+SELECT pg_catalog.set_config('search_path', '', false);
+
+CREATE TABLE public.users (
+    id integer NOT NULL,
+    email text
+);
+
+CREATE TABLE public.logs (id integer);
+
+COPY public.users (id, email) FROM stdin;
+1\talice@example.com
+2\tbob@example.com
+\\.
+
+INSERT INTO public.logs (id) VALUES (1);
+SELECT pg_catalog.setval('public.users_id_seq', 2, true);
+
+ALTER TABLE ONLY public.users ADD CONSTRAINT users_pkey PRIMARY KEY (id);
+";
+
+    fn summary(dump: &str, dump_type: DumpType) -> DumpSummary {
+        DumpSummary::from_reader(dump.as_bytes(), Some(dump_type)).unwrap()
+    }
+
+    #[test]
+    fn postgres_summary() {
+        let summary = summary(POSTGRES_DUMP, DumpType::Postgres);
+
+        assert_eq!(summary.tables.len(), 2);
+        assert_eq!(summary.tables.get("public.users"), Some(&2));
+        assert_eq!(summary.tables.get("public.logs"), Some(&1));
+        assert_eq!(
+            summary.schema,
+            vec![
+                "CREATE TABLE public.users (\n    id integer NOT NULL,\n    email text\n);",
+                "CREATE TABLE public.logs (id integer);",
+                "ALTER TABLE ONLY public.users ADD CONSTRAINT users_pkey PRIMARY KEY (id);",
+            ]
+        );
+    }
+
+    #[test]
+    fn mysql_summary() {
+        let dump = "-- MySQL dump 10.13
+/*!40101 SET NAMES utf8mb4 */;
+DROP TABLE IF EXISTS `users`;
+CREATE TABLE `users` (`id` int NOT NULL, `name` varchar(255)) ENGINE=InnoDB;
+LOCK TABLES `users` WRITE;
+/*!40000 ALTER TABLE `users` DISABLE KEYS */;
+INSERT INTO `users` VALUES (1,'O\\'Reilly;'),(2,'Smith');
+/*!40000 ALTER TABLE `users` ENABLE KEYS */;
+UNLOCK TABLES;
+-- Dump completed on 2022-05-01 10:00:00
+";
+
+        let summary = summary(dump, DumpType::Mysql);
+
+        // an INSERT INTO query is counted as a row
+        assert_eq!(summary.tables.get("users"), Some(&1));
+        assert_eq!(summary.schema.len(), 2);
+    }
+
+    #[test]
+    fn diff_dumps() {
+        let from = summary(POSTGRES_DUMP, DumpType::Postgres);
+        assert!(DumpDiff::new(&from, &from).is_empty());
+
+        let to = summary(
+            POSTGRES_DUMP
+                .replace("    email text\n", "    email text,\n    name text\n")
+                .replace("CREATE TABLE public.logs (id integer);", "")
+                .replace("INSERT INTO public.logs (id) VALUES (1);", "")
+                .replace(
+                    "2\tbob@example.com\n",
+                    "2\tbob@example.com\n3\tcarol@example.com\n",
+                )
+                .as_str(),
+            DumpType::Postgres,
+        );
+
+        let diff = DumpDiff::new(&from, &to);
+        assert!(!diff.is_empty());
+        assert_eq!(
+            diff.tables,
+            vec![
+                TableDiff {
+                    table: "public.logs".to_string(),
+                    from_rows: Some(1),
+                    to_rows: None,
+                    schema_changed: true,
+                },
+                TableDiff {
+                    table: "public.users".to_string(),
+                    from_rows: Some(2),
+                    to_rows: Some(3),
+                    schema_changed: true,
+                },
+            ]
+        );
+        assert_eq!(diff.removed_queries.len(), 2);
+        assert_eq!(
+            diff.added_queries,
+            vec!["CREATE TABLE public.users (\n    id integer NOT NULL,\n    email text,\n    name text\n);"]
+        );
+    }
+}
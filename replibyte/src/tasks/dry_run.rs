@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::errors::ReplibyteError;
+use crate::source::SourceOptions;
+use crate::tasks::{Progress, Task};
+use crate::Source;
+
+lazy_static! {
+    static ref INSERT_INTO: Regex = Regex::new(r"(?i)^INSERT\s+INTO\s+([^\s(]+)").unwrap();
+}
+
+/// DryRunTask reads a *Source* and prints its first original and transformed rows of every table,
+/// nothing is written to the *Datastore*
+pub struct DryRunTask<'a, S>
+where
+    S: Source,
+{
+    source: S,
+    options: SourceOptions<'a>,
+    sample_size: usize,
+}
+
+impl<'a, S> DryRunTask<'a, S>
+where
+    S: Source,
+{
+    pub fn new(source: S, options: SourceOptions<'a>, sample_size: usize) -> Self {
+        DryRunTask {
+            source,
+            options,
+            sample_size,
+        }
+    }
+}
+
+impl<'a, S> Task for DryRunTask<'a, S>
+where
+    S: Source,
+{
+    fn run<F: FnMut(&Progress)>(mut self, _progress_callback: F) -> Result<(), ReplibyteError> {
+        // initialize the source
+        let _ = self.source.init()?;
+
+        let mut rows_by_table_name: HashMap<String, usize> = HashMap::new();
+
+        self.source.read(self.options, |original_query, query| {
+            let original_query = String::from_utf8_lossy(original_query.data());
+            let query = String::from_utf8_lossy(query.data());
+
+            // only the rows are previewed, not the other queries (e.g. CREATE TABLE)
+            let table_name = match insert_into_table_name(original_query.trim()) {
+                Some(table_name) => table_name,
+                None => return,
+            };
+
+            let rows = rows_by_table_name
+                .entry(table_name.to_string())
+                .or_default();
+            if *rows >= self.sample_size {
+                return;
+            }
+
+            if *rows == 0 {
+                println!("-- {}", table_name);
+            }
+            *rows += 1;
+
+            for line in preview(original_query.trim(), query.trim()) {
+                println!("{}", line);
+            }
+        })
+    }
+}
+
+fn insert_into_table_name(query: &str) -> Option<&str> {
+    INSERT_INTO
+        .captures(query)
+        .and_then(|captures| captures.get(1))
+        .map(|table_name| table_name.as_str())
+}
+
+/// the lines of a row, like a diff: the original row with `-` and the transformed row with `+`
+fn preview(original_query: &str, query: &str) -> Vec<String> {
+    if original_query == query {
+        return vec![format!("  {}", original_query)];
+    }
+
+    vec![format!("- {}", original_query), format!("+ {}", query)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{insert_into_table_name, preview};
+
+    #[test]
+    fn table_name_of_insert_into_queries() {
+        for (query, table_name) in [
+            (
+                "INSERT INTO public.users (id, email) VALUES (1, 'john@doe.com');",
+                Some("public.users"),
+            ),
+            ("insert into `users` VALUES (1);", Some("`users`")),
+            ("INSERT INTO \"users\"(id) VALUES (1);", Some("\"users\"")),
+            ("CREATE TABLE public.users (id integer);", None),
+        ] {
+            assert_eq!(insert_into_table_name(query), table_name);
+        }
+    }
+
+    #[test]
+    fn preview_rows() {
+        let original_query = "INSERT INTO public.users (email) VALUES ('john@doe.com');";
+        let query = "INSERT INTO public.users (email) VALUES ('tony@stark.com');";
+
+        assert_eq!(
+            preview(original_query, query),
+            vec![format!("- {}", original_query), format!("+ {}", query)]
+        );
+        assert_eq!(
+            preview(original_query, original_query),
+            vec![format!("  {}", original_query)]
+        );
+    }
+}
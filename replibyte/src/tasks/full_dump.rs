@@ -1,13 +1,21 @@
 use std::io::{Error, ErrorKind};
-use std::sync::mpsc;
 use std::thread;
 
+use serde_json::json;
+use tokio::sync::mpsc;
+
 use crate::datastore::Datastore;
+use crate::errors::ReplibyteError;
+use crate::logging;
+use crate::runtime;
 use crate::source::SourceOptions;
-use crate::tasks::{MaxBytes, Message, Task, TransferredBytes};
+use crate::tasks::{Progress, Task};
 use crate::types::{to_bytes, Queries};
 use crate::Source;
 
+/// number of parts compressed and encrypted concurrently, while the previous ones are uploaded
+const ENCODERS: usize = 2;
+
 type DataMessage = (u16, Queries);
 
 /// FullDumpTask is a wrapping struct to execute the synchronization between a *Source* and a *Datastore*
@@ -16,19 +24,22 @@ where
     S: Source,
 {
     source: S,
-    datastore: Box<dyn Datastore>,
+    datastore: &'a dyn Datastore,
     options: SourceOptions<'a>,
+    buffer_size: usize,
 }
 
 impl<'a, S> FullDumpTask<'a, S>
 where
     S: Source,
 {
-    pub fn new(source: S, datastore: Box<dyn Datastore>, options: SourceOptions<'a>) -> Self {
+    pub fn new(source: S, datastore: &'a dyn Datastore, options: SourceOptions<'a>) -> Self {
         FullDumpTask {
             source,
             datastore,
             options,
+            // buffer of 100MB in memory to use and re-use to upload data into datastore
+            buffer_size: 100 * 1024 * 1024,
         }
     }
 }
@@ -37,80 +48,249 @@ impl<'a, S> Task for FullDumpTask<'a, S>
 where
     S: Source,
 {
-    fn run<F: FnMut(TransferredBytes, MaxBytes)>(
-        mut self,
-        mut progress_callback: F,
-    ) -> Result<(), Error> {
+    fn run<F: FnMut(&Progress)>(mut self, mut progress_callback: F) -> Result<(), ReplibyteError> {
         // initialize the source
         let _ = self.source.init()?;
 
-        let (tx, rx) = mpsc::sync_channel::<Message<DataMessage>>(1);
+        let (tx, rx) = mpsc::channel::<DataMessage>(1);
+        let encoder = self.datastore.encoder();
+
+        // the parts are compressed and encrypted on the pipeline runtime, the encoded parts are received in order
+        let mut encoded_rx =
+            runtime::map_concurrently(rx, ENCODERS, move |(chunk_part, queries)| {
+                let data = to_bytes(queries);
+                let bytes = data.len();
+                encoder.encode(data).map(|part| (chunk_part, bytes, part))
+            });
         let datastore = self.datastore;
 
-        let join_handle = thread::spawn(move || -> Result<(), Error> {
-            // managing Datastore (S3) upload here
-            let datastore = datastore;
-
-            loop {
-                let result = match rx.recv() {
-                    Ok(Message::Data((chunk_part, queries))) => Ok((chunk_part, queries)),
-                    Ok(Message::EOF) => break,
-                    Err(err) => Err(Error::new(ErrorKind::Other, format!("{}", err))),
-                };
-
-                if let Ok((chunk_part, queries)) = result {
-                    let _ = match datastore.write(chunk_part, to_bytes(queries)) {
-                        Ok(_) => {}
-                        Err(err) => return Err(Error::new(ErrorKind::Other, format!("{}", err))),
-                    };
+        // the datastore is borrowed by the upload thread, the caller can use it again once the dump is done
+        thread::scope(|scope| -> Result<(), ReplibyteError> {
+            let join_handle = scope.spawn(move || -> Result<(), ReplibyteError> {
+                // managing Datastore (S3) upload here, the parts are uploaded in order
+                while let Some(message) = encoded_rx.blocking_recv() {
+                    let (chunk_part, bytes, part) = message?;
+                    let _ = datastore.write_encoded(chunk_part, part)?;
+
+                    logging::event(
+                        "chunk_uploaded",
+                        json!({"part": chunk_part, "bytes": bytes}),
+                    );
+                }
+
+                Ok(())
+            });
+
+            // false once the encoders or the upload stopped, the next queries are not sent
+            let send = |message: DataMessage| tx.blocking_send(message).is_ok();
+
+            let buffer_size = self.buffer_size;
+            let mut queries = vec![];
+            let mut consumed_buffer_size = 0usize;
+            let mut chunk_part = 0u16;
+            let mut is_sent = true;
+
+            // init progress
+            let mut progress = Progress::new(buffer_size * (chunk_part as usize + 1));
+            progress_callback(&progress);
+
+            // the table being transformed and the number of rows read before its first row
+            let mut current_table: Option<String> = None;
+            let mut current_table_first_row = 0usize;
+
+            let read_result = self.source.read(self.options, |_original_query, query| {
+                if !is_sent {
+                    return;
+                }
+
+                if consumed_buffer_size + query.data().len() > buffer_size {
+                    chunk_part += 1;
+                    consumed_buffer_size = 0;
+
+                    if !send((chunk_part, std::mem::take(&mut queries))) {
+                        is_sent = false;
+                        return;
+                    }
                 }
+
+                consumed_buffer_size += query.data().len();
+                // the queries are written with a new line, the transferred bytes are the size of the dump before its compression
+                progress.transferred_bytes += query.data().len() + 1;
+                progress.max_bytes = buffer_size * (chunk_part as usize + 1);
+
+                let rows = progress.rows;
+                progress.count_rows(query.data());
+                if progress.table != current_table {
+                    if let Some(table) = &current_table {
+                        table_transformed(table, rows - current_table_first_row);
+                    }
+
+                    current_table = progress.table.clone();
+                    current_table_first_row = rows;
+                }
+
+                progress_callback(&progress);
+                queries.push(query);
+            });
+
+            if read_result.is_ok() && is_sent {
+                if let Some(table) = &current_table {
+                    table_transformed(table, progress.rows - current_table_first_row);
+                }
+
+                progress.max_bytes = progress.transferred_bytes;
+                progress_callback(&progress);
+
+                chunk_part += 1;
+                is_sent = send((chunk_part, queries));
+            }
+
+            // the encoders stop once the parts sent are encoded, and the upload once they are uploaded
+            drop(tx);
+
+            let upload_result = join_handle.join().unwrap_or_else(|_| {
+                Err(ReplibyteError::from(Error::new(
+                    ErrorKind::Other,
+                    "the upload of the dump panicked",
+                )))
+            });
+
+            // the error of the upload comes first, it stopped the source
+            let _ = upload_result?;
+            let _ = read_result?;
+
+            if !is_sent {
+                return Err(ReplibyteError::from(Error::new(
+                    ErrorKind::Other,
+                    "the upload of the dump stopped before the end of the dump",
+                )));
             }
 
             Ok(())
-        });
-
-        // buffer of 100MB in memory to use and re-use to upload data into datastore
-        let buffer_size = 100 * 1024 * 1024;
-        let mut queries = vec![];
-        let mut consumed_buffer_size = 0usize;
-        let mut total_transferred_bytes = 0usize;
-        let mut chunk_part = 0u16;
-
-        // init progress
-        progress_callback(
-            total_transferred_bytes,
-            buffer_size * (chunk_part as usize + 1),
-        );
+        })
+    }
+}
 
-        let _ = self.source.read(self.options, |_original_query, query| {
-            if consumed_buffer_size + query.data().len() > buffer_size {
-                chunk_part += 1;
-                consumed_buffer_size = 0;
-                // TODO .clone() - look if we do not consume more mem
+fn table_transformed(table: &str, rows: usize) {
+    logging::event("table_transformed", json!({"table": table, "rows": rows}));
+}
 
-                let message = Message::Data((chunk_part, queries.clone()));
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, read_dir};
+
+    use tempfile::tempdir;
+
+    use crate::connector::Connector;
+    use crate::datastore::local_disk::LocalDisk;
+    use crate::datastore::{Datastore, EncryptionKey, EncryptionKeys, ReadOptions};
+    use crate::errors::ReplibyteError;
+    use crate::source::{Source, SourceOptions};
+    use crate::tasks::full_dump::FullDumpTask;
+    use crate::tasks::Task;
+    use crate::types::{OriginalQuery, Query};
+
+    struct UsersSource;
+
+    impl Connector for UsersSource {
+        fn init(&mut self) -> Result<(), ReplibyteError> {
+            Ok(())
+        }
+    }
 
-                let _ = tx.send(message); // FIXME catch SendError?
-                let _ = queries.clear();
+    impl Source for UsersSource {
+        fn read<F: FnMut(OriginalQuery, Query)>(
+            &self,
+            _options: SourceOptions,
+            mut query_callback: F,
+        ) -> Result<(), ReplibyteError> {
+            for query in queries() {
+                query_callback(Query(query.clone().into_bytes()), Query(query.into_bytes()));
             }
 
-            consumed_buffer_size += query.data().len();
-            total_transferred_bytes += query.data().len();
-            progress_callback(
-                total_transferred_bytes,
-                buffer_size * (chunk_part as usize + 1),
-            );
-            queries.push(query);
-        })?;
+            Ok(())
+        }
+    }
+
+    fn queries() -> Vec<String> {
+        (1..=50)
+            .map(|id| format!("INSERT INTO users (id) VALUES ({});", id))
+            .collect()
+    }
+
+    #[test]
+    fn parts_are_written_in_order() {
+        let dir = tempdir().unwrap();
+        let mut datastore = LocalDisk::new(dir.path().to_str().unwrap());
+        let _ = datastore.init().unwrap();
+        datastore.set_encryption_keys(EncryptionKeys::new(vec![EncryptionKey {
+            id: None,
+            key: b"secret".to_vec(),
+            encrypted_data_key: None,
+        }]));
+        datastore.set_dump_name("dump-1".to_string());
+
+        let (transformers, skip, subset, only_tables, filters) =
+            (vec![], vec![], None, vec![], vec![]);
+        let options = SourceOptions {
+            transformers: &transformers,
+            skip_config: &skip,
+            database_subset: &subset,
+            only_tables: &only_tables,
+            filters: &filters,
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
+        };
+
+        // about 3 queries per part
+        let mut task = FullDumpTask::new(UsersSource, &datastore, options);
+        task.buffer_size = 120;
+        let _ = task.run(|_| {}).unwrap();
+
+        assert!(datastore.verify("dump-1").unwrap() > 10);
+
+        let mut dump = vec![];
+        let _ = datastore
+            .read(&ReadOptions::Latest, &mut |data| dump.extend(data))
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(dump).unwrap(),
+            format!("{}\n", queries().join("\n"))
+        );
+    }
+    #[test]
+    fn failed_upload() {
+        let dir = tempdir().unwrap();
+        let mut datastore = LocalDisk::new(dir.path().to_str().unwrap());
+        let _ = datastore.init().unwrap();
+        datastore.set_dump_name("dump-1".to_string());
+
+        // the third part cannot be written
+        create_dir_all(dir.path().join("dump-1").join("3.dump")).unwrap();
 
-        progress_callback(total_transferred_bytes, total_transferred_bytes);
+        let (transformers, skip, subset, only_tables, filters) =
+            (vec![], vec![], None, vec![], vec![]);
+        let options = SourceOptions {
+            transformers: &transformers,
+            skip_config: &skip,
+            database_subset: &subset,
+            only_tables: &only_tables,
+            filters: &filters,
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
+        };
 
-        chunk_part += 1;
-        let _ = tx.send(Message::Data((chunk_part, queries)));
-        let _ = tx.send(Message::EOF);
-        // wait for end of upload execution
-        join_handle.join().unwrap()?;
+        let mut task = FullDumpTask::new(UsersSource, &datastore, options);
+        task.buffer_size = 120;
+        assert!(task.run(|_| {}).is_err());
 
-        Ok(())
+        let mut parts = read_dir(dir.path().join("dump-1"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        parts.sort();
+        assert_eq!(parts, vec!["1.dump", "2.dump", "3.dump"]);
     }
 }
@@ -1,10 +1,12 @@
-use std::io::Error;
+use std::io::{Error, ErrorKind};
 use std::sync::mpsc;
 use std::thread;
 
 use crate::datastore::{Datastore, ReadOptions};
 use crate::destination::Destination;
-use crate::tasks::{MaxBytes, Message, Task, TransferredBytes};
+use crate::errors::ReplibyteError;
+use crate::tasks::transform::RestoreTransform;
+use crate::tasks::{Progress, Task};
 use crate::types::Bytes;
 
 /// FullRestoreTask is a wrapping struct to execute the synchronization between a *Datastore* and a *Source*.
@@ -15,6 +17,7 @@ where
     destination: &'a mut D,
     datastore: Box<dyn Datastore>,
     read_options: ReadOptions,
+    transform: Option<RestoreTransform>,
 }
 
 impl<'a, D> FullRestoreTask<'a, D>
@@ -25,11 +28,13 @@ where
         destination: &'a mut D,
         datastore: Box<dyn Datastore>,
         read_options: ReadOptions,
+        transform: Option<RestoreTransform>,
     ) -> Self {
         FullRestoreTask {
             destination,
             datastore,
             read_options,
+            transform,
         }
     }
 }
@@ -38,57 +43,175 @@ impl<'a, D> Task for FullRestoreTask<'a, D>
 where
     D: Destination,
 {
-    fn run<F: FnMut(TransferredBytes, MaxBytes)>(
-        mut self,
-        mut progress_callback: F,
-    ) -> Result<(), Error> {
+    fn run<F: FnMut(&Progress)>(self, mut progress_callback: F) -> Result<(), ReplibyteError> {
         // initialize the destination
         let _ = self.destination.init()?;
 
         // bound to 1 to avoid eating too much memory if we download the dump faster than we ingest it
-        let (tx, rx) = mpsc::sync_channel::<Message<Bytes>>(1);
+        let (tx, rx) = mpsc::sync_channel::<Result<Bytes, ReplibyteError>>(1);
         let datastore = self.datastore;
 
         let mut index_file = datastore.index_file()?;
         let dump = index_file.find_dump(&self.read_options)?;
 
         // init progress
-        progress_callback(0, dump.size);
+        let mut progress = Progress::new(dump.size);
+        progress_callback(&progress);
 
         let read_options = self.read_options.clone();
+        let transform = self.transform;
 
         let join_handle = thread::spawn(move || {
             // managing Datastore (S3) download here
             let datastore = datastore;
             let read_options = read_options;
 
-            let _ = match datastore.read(&read_options, &mut |data| {
-                let _ = tx.send(Message::Data(data));
-            }) {
-                Ok(_) => {}
-                Err(err) => panic!("{:?}", err),
+            let mut data_callback = |data| {
+                let _ = tx.send(Ok(data));
             };
 
-            let _ = tx.send(Message::EOF);
+            let result = match &transform {
+                Some(transform) => {
+                    transform.read(datastore.as_ref(), &read_options, &mut data_callback)
+                }
+                None => datastore.read_with_bases(&read_options, &mut data_callback),
+            };
+
+            // the end of the dump is signaled by dropping the sender
+            if let Err(err) = result {
+                let _ = tx.send(Err(err));
+            }
         });
 
-        loop {
-            let data = match rx.recv() {
-                Ok(Message::Data(data)) => data,
-                Ok(Message::EOF) => break,
-                Err(err) => panic!("{:?}", err), // FIXME what should I do here?
-            };
+        let mut read_error = None;
+
+        // chunks are consumed one by one by the destination, while the next one is downloaded
+        let mut chunks = rx
+            .iter()
+            .map_while(|message| match message {
+                Ok(data) => Some(data),
+                Err(err) => {
+                    read_error = Some(err);
+                    None
+                }
+            })
+            .inspect(|data| {
+                progress.transferred_bytes += data.len();
+                progress.count_chunk_rows(data.as_slice());
+                progress_callback(&progress);
+            });
+
+        let write_result = self.destination.write_chunks(&mut chunks);
+
+        // the download thread does not wait for a destination which has stopped reading the chunks
+        drop(chunks);
 
-            progress_callback(data.len(), dump.size);
+        // wait for end of download execution
+        let join_result = join_handle.join();
 
-            let _ = self.destination.write(data)?;
+        // a failed download truncates the dump, its error comes first
+        if let Some(err) = read_error {
+            return Err(err);
         }
 
-        // wait for end of download execution
-        let _ = join_handle.join(); // FIXME catch result here
+        if join_result.is_err() {
+            return Err(ReplibyteError::from(Error::new(
+                ErrorKind::Other,
+                "the download of the dump panicked",
+            )));
+        }
+
+        write_result?;
 
-        progress_callback(dump.size, dump.size);
+        progress.transferred_bytes = dump.size;
+        progress_callback(&progress);
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use tempfile::tempdir;
+
+    use crate::connector::Connector;
+    use crate::datastore::local_disk::LocalDisk;
+    use crate::datastore::{Datastore, ReadOptions};
+    use crate::destination::Destination;
+    use crate::errors::ReplibyteError;
+    use crate::tasks::full_restore::FullRestoreTask;
+    use crate::tasks::Task;
+    use crate::types::Bytes;
+
+    #[derive(Default)]
+    struct ChunksDestination {
+        chunks: RefCell<Vec<Bytes>>,
+    }
+
+    impl Connector for ChunksDestination {
+        fn init(&mut self) -> Result<(), ReplibyteError> {
+            Ok(())
+        }
+    }
+
+    impl Destination for ChunksDestination {
+        fn write_chunks(
+            &self,
+            chunks: &mut dyn Iterator<Item = Bytes>,
+        ) -> Result<(), ReplibyteError> {
+            self.chunks.borrow_mut().extend(chunks);
+            Ok(())
+        }
+    }
+
+    fn datastore(dir: &str) -> Box<dyn Datastore> {
+        let mut datastore = LocalDisk::new(dir.to_string());
+        let _ = datastore.init().unwrap();
+        datastore.set_dump_name("dump-1".to_string());
+        Box::new(datastore)
+    }
+
+    #[test]
+    fn restore() {
+        let dir = tempdir().unwrap();
+        let dir = dir.path().to_str().unwrap();
+        let datastore = datastore(dir);
+        let _ = datastore
+            .write(1, b"INSERT INTO users VALUES (1);\n".to_vec())
+            .unwrap();
+        let _ = datastore
+            .write(2, b"INSERT INTO users VALUES (2);\n".to_vec())
+            .unwrap();
+
+        let mut destination = ChunksDestination::default();
+        let task = FullRestoreTask::new(&mut destination, datastore, ReadOptions::Latest, None);
+        assert!(task.run(|_| {}).is_ok());
+        assert_eq!(destination.chunks.borrow().len(), 2);
+    }
+
+    #[test]
+    fn failed_download() {
+        let dir = tempdir().unwrap();
+        let dir = dir.path().to_str().unwrap();
+        let datastore = datastore(dir);
+        let _ = datastore
+            .write(1, b"INSERT INTO users VALUES (1);\n".to_vec())
+            .unwrap();
+        let _ = datastore
+            .write(2, b"INSERT INTO users VALUES (2);\n".to_vec())
+            .unwrap();
+
+        // the second part cannot be read
+        std::fs::remove_file(format!("{}/dump-1/2.dump", dir)).unwrap();
+        std::fs::create_dir(format!("{}/dump-1/2.dump", dir)).unwrap();
+
+        let mut destination = ChunksDestination::default();
+        let task = FullRestoreTask::new(&mut destination, datastore, ReadOptions::Latest, None);
+
+        // the truncated dump is not reported as restored
+        assert!(task.run(|_| {}).is_err());
+        assert_eq!(destination.chunks.borrow().len(), 1);
+    }
+}
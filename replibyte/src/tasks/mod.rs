@@ -1,18 +1,184 @@
-use std::io::Error;
+use std::time::Duration;
 
+use dump_parser::utils::END_OF_COPY_DATA;
+
+use crate::errors::ReplibyteError;
+use crate::utils::{parse_identifier, strip_prefix_ignore_case};
+
+pub mod diff;
+pub mod dry_run;
 pub mod full_dump;
 pub mod full_restore;
+pub mod parallel_restore;
+pub mod transform;
 
 pub type TransferredBytes = usize;
 pub type MaxBytes = usize;
 
+/// max number of bytes of a query read to find its table
+const MAX_QUERY_PREFIX_LEN: usize = 256;
+
 pub trait Task {
-    fn run<F: FnMut(TransferredBytes, MaxBytes)>(self, progress_callback: F) -> Result<(), Error>;
+    fn run<F: FnMut(&Progress)>(self, progress_callback: F) -> Result<(), ReplibyteError>;
+}
+
+/// Progress of a dump or a restore, reported by the tasks to the progress callback
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Progress {
+    pub transferred_bytes: TransferredBytes,
+    pub max_bytes: MaxBytes,
+    /// rows read or restored so far, an `INSERT INTO` query or a `COPY` row is a row
+    pub rows: usize,
+    /// table of the last read or restored row
+    pub table: Option<String>,
+    /// the next queries are the rows of a `COPY ... FROM stdin` block
+    is_copy: bool,
 }
 
-/// inter-thread message for Source/Destination and Datastore
-#[derive(Debug, Clone)]
-enum Message<T> {
-    Data(T),
-    EOF,
+impl Progress {
+    pub fn new(max_bytes: MaxBytes) -> Self {
+        Progress {
+            max_bytes,
+            ..Default::default()
+        }
+    }
+
+    /// count the row of a query, if it is one
+    pub fn count_rows(&mut self, query: &[u8]) {
+        if self.is_copy {
+            if query == END_OF_COPY_DATA.as_bytes() {
+                self.is_copy = false;
+            } else {
+                self.rows += 1;
+            }
+
+            return;
+        }
+
+        let prefix = String::from_utf8_lossy(&query[..query.len().min(MAX_QUERY_PREFIX_LEN)]);
+        let prefix = prefix.trim_start();
+
+        if let Some((table, _)) =
+            strip_prefix_ignore_case(prefix, "INSERT INTO").and_then(parse_identifier)
+        {
+            self.rows += 1;
+            self.table = Some(table);
+        } else if let Some((table, _)) =
+            strip_prefix_ignore_case(prefix, "COPY").and_then(parse_identifier)
+        {
+            // the rows of a COPY ... FROM stdin block are the next queries, up to the end of the COPY data
+            if query
+                .trim_ascii_end()
+                .to_ascii_lowercase()
+                .ends_with(b"from stdin;")
+            {
+                self.table = Some(table);
+                self.is_copy = true;
+            }
+        }
+    }
+
+    /// count the rows of a chunk, a chunk is made of complete queries separated by new lines
+    pub fn count_chunk_rows(&mut self, chunk: &[u8]) {
+        for query in chunk.split(|byte| *byte == b'\n') {
+            if !query.is_empty() {
+                self.count_rows(query);
+            }
+        }
+    }
+
+    /// true if the next queries are the rows of a `COPY ... FROM stdin` block
+    pub fn is_copy(&self) -> bool {
+        self.is_copy
+    }
+
+    /// add the bytes and the rows of another progress, e.g. the progress of a chunk
+    pub fn add(&mut self, progress: Progress) {
+        self.transferred_bytes += progress.transferred_bytes;
+        self.rows += progress.rows;
+
+        if progress.table.is_some() {
+            self.table = progress.table;
+        }
+    }
+
+    /// rows, rows per second and current table, e.g. `1200 rows (400 rows/s) - public.users`
+    pub fn message(&self, elapsed: Duration) -> String {
+        if self.rows == 0 {
+            return String::new();
+        }
+
+        let rows_per_sec = match elapsed.as_millis() {
+            0 => self.rows,
+            millis => (self.rows as u128 * 1000 / millis) as usize,
+        };
+
+        match &self.table {
+            Some(table) => format!("{} rows ({} rows/s) - {}", self.rows, rows_per_sec, table),
+            None => format!("{} rows ({} rows/s)", self.rows, rows_per_sec),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::tasks::Progress;
+
+    #[test]
+    fn count_rows() {
+        let mut progress = Progress::new(100);
+
+        progress.count_rows(b"CREATE TABLE public.users (id integer);");
+        progress.count_rows(b"INSERT INTO public.users (id) VALUES (1);");
+        progress.count_rows(b"insert into \"public\".\"users\" (id) VALUES (2);");
+        assert_eq!(progress.rows, 2);
+        assert_eq!(progress.table, Some("public.users".to_string()));
+
+        progress.count_chunk_rows(
+            b"COPY public.orders (id, user_id) FROM stdin;\n1\t1\n2\t2\n\\.\nALTER TABLE public.orders OWNER TO root;\n",
+        );
+        assert_eq!(progress.rows, 4);
+        assert_eq!(progress.table, Some("public.orders".to_string()));
+
+        // COPY ... TO does not have rows in the dump
+        progress.count_rows(b"COPY public.orders TO '/tmp/orders.csv';");
+        progress.count_rows(b"ALTER TABLE public.orders OWNER TO root;");
+        assert_eq!(progress.rows, 4);
+    }
+
+    #[test]
+    fn add_progress() {
+        let mut progress = Progress::new(100);
+        progress.transferred_bytes = 10;
+
+        let mut chunk_progress = Progress::new(0);
+        chunk_progress.transferred_bytes = 20;
+        chunk_progress.count_chunk_rows(b"INSERT INTO users (id) VALUES (1);\n");
+        progress.add(chunk_progress);
+
+        assert_eq!(progress.transferred_bytes, 30);
+        assert_eq!(progress.max_bytes, 100);
+        assert_eq!(progress.rows, 1);
+        assert_eq!(progress.table, Some("users".to_string()));
+    }
+
+    #[test]
+    fn progress_message() {
+        let mut progress = Progress::new(100);
+        assert_eq!(progress.message(Duration::from_secs(1)), "");
+
+        progress.rows = 1200;
+        assert_eq!(
+            progress.message(Duration::from_secs(3)),
+            "1200 rows (400 rows/s)"
+        );
+
+        progress.table = Some("public.users".to_string());
+        assert_eq!(
+            progress.message(Duration::from_secs(3)),
+            "1200 rows (400 rows/s) - public.users"
+        );
+    }
 }
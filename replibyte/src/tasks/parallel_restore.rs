@@ -0,0 +1,797 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+
+use dump_parser::utils::{list_sql_queries_from_dump_reader, ListQueryResult};
+
+use crate::datastore::{Datastore, ReadOptions};
+use crate::destination::checkpoint::Checkpoint;
+use crate::destination::{is_session_query, Destination};
+use crate::errors::ReplibyteError;
+use crate::tasks::transform::RestoreTransform;
+use crate::tasks::{Progress, Task};
+use crate::types::Bytes;
+use crate::utils::{parse_identifier, strip_prefix_ignore_case};
+
+/// max size of a chunk sent to the destination, a chunk is always made of complete queries
+const CHUNK_SIZE: usize = 10 * 1024 * 1024;
+
+const SCHEMA_CHECKPOINT_KEY: &str = "schema";
+const POST_DATA_CHECKPOINT_KEY: &str = "post-data";
+
+/// ParallelRestoreTask restores a dump from a *Datastore* with several concurrent writers to a *Destination*.
+/// The dump is split into a schema stream, one stream per table and a post-data stream (e.g. foreign keys, indexes).
+/// The schema stream is restored first, then the table streams are restored concurrently
+/// (a table is restored once the tables it references with a foreign key are restored), and finally the post-data stream.
+/// With a *Checkpoint*, the streams already restored by an interrupted restore are skipped.
+pub struct ParallelRestoreTask<'a, D>
+where
+    D: Destination + Sync,
+{
+    destination: &'a mut D,
+    datastore: Box<dyn Datastore>,
+    read_options: ReadOptions,
+    concurrency: usize,
+    checkpoint: Option<&'a Checkpoint>,
+    transform: Option<RestoreTransform>,
+}
+
+impl<'a, D> ParallelRestoreTask<'a, D>
+where
+    D: Destination + Sync,
+{
+    pub fn new(
+        destination: &'a mut D,
+        datastore: Box<dyn Datastore>,
+        read_options: ReadOptions,
+        concurrency: usize,
+        checkpoint: Option<&'a Checkpoint>,
+        transform: Option<RestoreTransform>,
+    ) -> Self {
+        ParallelRestoreTask {
+            destination,
+            datastore,
+            read_options,
+            concurrency: concurrency.max(1),
+            checkpoint,
+            transform,
+        }
+    }
+}
+
+impl<'a, D> Task for ParallelRestoreTask<'a, D>
+where
+    D: Destination + Sync,
+{
+    fn run<F: FnMut(&Progress)>(self, mut progress_callback: F) -> Result<(), ReplibyteError> {
+        // initialize the destination, unless it has already been initialized by an interrupted restore
+        if self
+            .checkpoint
+            .map(|checkpoint| checkpoint.is_empty())
+            .unwrap_or(true)
+        {
+            let _ = self.destination.init()?;
+        }
+
+        let mut index_file = self.datastore.index_file()?;
+        let dump = index_file.find_dump(&self.read_options)?;
+
+        // init progress
+        let mut progress = Progress::new(dump.size);
+        progress_callback(&progress);
+
+        // the whole dump must be read to know all the tables and their foreign keys before restoring them
+        let mut splitter = DumpSplitter::default();
+        let mut split_result = Ok(());
+
+        let mut data_callback = |data: Bytes| {
+            progress.transferred_bytes += data.len();
+            progress_callback(&progress);
+
+            if split_result.is_ok() {
+                split_result = splitter.add_chunk(data.as_slice());
+            }
+        };
+
+        match &self.transform {
+            Some(transform) => transform.read(
+                self.datastore.as_ref(),
+                &self.read_options,
+                &mut data_callback,
+            )?,
+            None => self
+                .datastore
+                .read_with_bases(&self.read_options, &mut data_callback)?,
+        };
+
+        split_result?;
+
+        let split_dump = splitter.finish()?;
+        let destination: &D = self.destination;
+        let checkpoint = self.checkpoint;
+
+        // the rows are counted while they are restored, not while they are downloaded
+        let mut progress = Progress::new(split_dump.size());
+        progress_callback(&progress);
+
+        progress.transferred_bytes += split_dump.schema_size();
+        write_stream(
+            destination,
+            checkpoint,
+            SCHEMA_CHECKPOINT_KEY,
+            split_dump.schema_queries,
+        )?;
+        progress_callback(&progress);
+
+        for mut level in split_dump.table_levels {
+            if let Some(checkpoint) = checkpoint {
+                level.retain(|table_stream| {
+                    let is_applied = checkpoint.is_applied(table_stream.checkpoint_key().as_str());
+
+                    if is_applied {
+                        progress.transferred_bytes += table_stream.size;
+                    }
+
+                    !is_applied
+                });
+            }
+
+            let workers = self.concurrency.min(level.len());
+            let queue = Mutex::new(level.into_iter());
+            let has_failed = AtomicBool::new(false);
+            let (tx, rx) = mpsc::channel::<Result<Progress, ReplibyteError>>();
+
+            thread::scope(|scope| {
+                for _ in 0..workers {
+                    let tx = tx.clone();
+                    let queue = &queue;
+                    let has_failed = &has_failed;
+
+                    let _ = scope.spawn(move || {
+                        while !has_failed.load(Ordering::SeqCst) {
+                            let table_stream = match queue.lock().unwrap().next() {
+                                Some(table_stream) => table_stream,
+                                None => break,
+                            };
+
+                            let checkpoint_key = table_stream.checkpoint_key();
+                            // the rows of a COPY block can go on in the next chunks of the table
+                            let mut table_progress = Progress::new(0);
+
+                            let result = table_stream
+                                .restore(destination, |chunk| {
+                                    let rows = table_progress.rows;
+                                    table_progress.count_chunk_rows(chunk);
+
+                                    let mut chunk_progress = Progress::new(0);
+                                    chunk_progress.transferred_bytes = chunk.len();
+                                    chunk_progress.rows = table_progress.rows - rows;
+                                    chunk_progress.table = table_progress.table.clone();
+                                    let _ = tx.send(Ok(chunk_progress));
+                                })
+                                .and_then(|_| match checkpoint {
+                                    Some(checkpoint) => {
+                                        checkpoint.mark_applied(checkpoint_key.as_str())
+                                    }
+                                    None => Ok(()),
+                                });
+
+                            if let Err(err) = result {
+                                has_failed.store(true, Ordering::SeqCst);
+                                let _ = tx.send(Err(err));
+                            }
+                        }
+                    });
+                }
+
+                // the progress is reported by the current thread, the callback is not shared with the workers
+                drop(tx);
+
+                rx.iter().try_for_each(|message| {
+                    progress.add(message?);
+                    progress_callback(&progress);
+                    Ok::<(), ReplibyteError>(())
+                })
+            })?;
+        }
+
+        write_stream(
+            destination,
+            checkpoint,
+            POST_DATA_CHECKPOINT_KEY,
+            split_dump.post_data_queries,
+        )?;
+
+        progress.transferred_bytes = progress.max_bytes;
+        progress_callback(&progress);
+
+        Ok(())
+    }
+}
+
+/// write the queries into the destination if they have not been already by an interrupted restore
+fn write_stream<D: Destination>(
+    destination: &D,
+    checkpoint: Option<&Checkpoint>,
+    checkpoint_key: &str,
+    queries: Vec<Bytes>,
+) -> Result<(), ReplibyteError> {
+    match checkpoint {
+        Some(checkpoint) if checkpoint.is_applied(checkpoint_key) => Ok(()),
+        Some(checkpoint) => {
+            let _ = destination.write_chunks(&mut into_chunks(queries).into_iter())?;
+            checkpoint.mark_applied(checkpoint_key)
+        }
+        None => destination.write_chunks(&mut into_chunks(queries).into_iter()),
+    }
+}
+
+/// Dump split by DumpSplitter
+struct SplitDump {
+    /// queries to restore before the tables data
+    schema_queries: Vec<Bytes>,
+    /// tables data, tables of a level only reference tables of the previous levels
+    table_levels: Vec<Vec<TableStream>>,
+    /// queries to restore after the tables data
+    post_data_queries: Vec<Bytes>,
+}
+
+impl SplitDump {
+    fn schema_size(&self) -> usize {
+        self.schema_queries.iter().map(|query| query.len()).sum()
+    }
+
+    fn size(&self) -> usize {
+        self.schema_size()
+            + self
+                .table_levels
+                .iter()
+                .flatten()
+                .map(|table_stream| table_stream.size)
+                .sum::<usize>()
+            + self
+                .post_data_queries
+                .iter()
+                .map(|query| query.len())
+                .sum::<usize>()
+    }
+}
+
+/// DumpSplitter splits a SQL dump into independent streams:
+/// - the `INSERT INTO` queries and the `COPY ... FROM stdin` blocks go to the stream of their table,
+/// - the other queries placed after the last table data go to the post-data stream,
+/// - and all the other queries go to the schema stream.
+///
+/// Session queries (e.g. `SET ...`) are replayed at the beginning of each table stream,
+/// since each stream is restored with its own connection.
+#[derive(Default)]
+struct DumpSplitter {
+    session_queries: Vec<Bytes>,
+    schema_queries: Vec<Bytes>,
+    /// queries since the last table data, they are moved to the schema stream on the next one
+    pending_queries: Vec<Bytes>,
+    table_names: Vec<String>,
+    table_streams: HashMap<String, TableStream>,
+    /// referenced tables by table
+    dependencies: HashMap<String, HashSet<String>>,
+    /// tells if the next chunk starts in a COPY block, and its table
+    copy_progress: Progress,
+}
+
+impl DumpSplitter {
+    /// add a chunk of the dump, a chunk is made of complete queries and a COPY block can go on in the next chunks
+    fn add_chunk(&mut self, chunk: &[u8]) -> Result<(), ReplibyteError> {
+        let mut queries = vec![];
+        let lines = chunk.strip_suffix(b"\n").unwrap_or(chunk);
+
+        for line in lines.split(|byte| *byte == b'\n') {
+            let is_copy = self.copy_progress.is_copy();
+            self.copy_progress.count_rows(line);
+
+            match (is_copy, self.copy_progress.is_copy()) {
+                (false, false) => {
+                    queries.extend_from_slice(line);
+                    queries.push(b'\n');
+                }
+                (false, true) => {
+                    // the queries before a COPY block are complete
+                    self.add_queries(queries.as_slice())?;
+                    queries.clear();
+
+                    self.add_copy_line(line)?;
+                }
+                // the rows and the end of the COPY data are not SQL, they are kept as they are
+                (true, _) => self.add_copy_line(line)?,
+            }
+        }
+
+        self.add_queries(queries.as_slice())
+    }
+
+    fn add_queries(&mut self, queries: &[u8]) -> Result<(), ReplibyteError> {
+        let mut result = Ok(());
+
+        list_sql_queries_from_dump_reader(BufReader::new(queries), |query| {
+            result = self.add_query(query);
+
+            match result {
+                Ok(_) => ListQueryResult::Continue,
+                Err(_) => ListQueryResult::Break,
+            }
+        })?;
+
+        result
+    }
+
+    fn add_query(&mut self, query: &str) -> Result<(), ReplibyteError> {
+        let trimmed_query = query.trim_start();
+
+        if trimmed_query.is_empty() {
+            return Ok(());
+        }
+
+        let query_bytes = trimmed_query.as_bytes().to_vec();
+
+        if let Some(table_name) = strip_prefix_ignore_case(trimmed_query, "INSERT INTO")
+            .and_then(parse_identifier)
+            .map(|(table_name, _)| table_name)
+        {
+            return self.add_table_data(table_name, query_bytes);
+        }
+
+        if is_session_query(trimmed_query) {
+            self.session_queries.push(query_bytes.clone());
+        }
+
+        if let Some((table_name, referenced_table_names)) = parse_foreign_keys(trimmed_query) {
+            self.dependencies
+                .entry(table_name)
+                .or_insert_with(HashSet::new)
+                .extend(referenced_table_names);
+        }
+
+        self.pending_queries.push(query_bytes);
+
+        Ok(())
+    }
+
+    /// add the `COPY ... FROM stdin;` query, a row or the end of the data of the current COPY block
+    fn add_copy_line(&mut self, line: &[u8]) -> Result<(), ReplibyteError> {
+        let table_name = self.copy_progress.table.clone().ok_or_else(|| {
+            ReplibyteError::Parse("COPY rows without a COPY ... FROM stdin; query".to_string())
+        })?;
+
+        self.add_table_data(table_name, line.to_vec())
+    }
+
+    fn add_table_data(&mut self, table_name: String, data: Bytes) -> Result<(), ReplibyteError> {
+        self.schema_queries.append(&mut self.pending_queries);
+
+        if !self.table_streams.contains_key(&table_name) {
+            let table_stream = TableStream::new(table_name.as_str(), &self.session_queries)?;
+            let _ = self.table_streams.insert(table_name.clone(), table_stream);
+            self.table_names.push(table_name.clone());
+        }
+
+        self.table_streams.get_mut(&table_name).unwrap().write(data)
+    }
+
+    fn finish(mut self) -> Result<SplitDump, ReplibyteError> {
+        let mut table_levels = vec![];
+
+        for level in restore_levels(&self.table_names, &self.dependencies) {
+            let mut table_streams = vec![];
+
+            for table_name in level {
+                let mut table_stream = self.table_streams.remove(&table_name).unwrap();
+                table_stream.flush()?;
+                table_streams.push(table_stream);
+            }
+
+            table_levels.push(table_streams);
+        }
+
+        Ok(SplitDump {
+            schema_queries: self.schema_queries,
+            table_levels,
+            post_data_queries: self.pending_queries,
+        })
+    }
+}
+
+/// TableStream stores the queries of a table in a temporary file to not keep the whole dump in memory
+struct TableStream {
+    table_name: String,
+    file: File,
+    chunk_sizes: Vec<usize>,
+    current_chunk_size: usize,
+    size: usize,
+}
+
+impl TableStream {
+    fn new(table_name: &str, session_queries: &[Bytes]) -> Result<Self, ReplibyteError> {
+        let mut table_stream = TableStream {
+            table_name: table_name.to_string(),
+            file: tempfile::tempfile()?,
+            chunk_sizes: vec![],
+            current_chunk_size: 0,
+            size: 0,
+        };
+
+        for query in session_queries {
+            table_stream.write(query.clone())?;
+        }
+
+        Ok(table_stream)
+    }
+
+    fn write(&mut self, mut query: Bytes) -> Result<(), ReplibyteError> {
+        query.push(b'\n');
+
+        if self.current_chunk_size > 0 && self.current_chunk_size + query.len() > CHUNK_SIZE {
+            self.chunk_sizes.push(self.current_chunk_size);
+            self.current_chunk_size = 0;
+        }
+
+        self.file.write_all(query.as_slice())?;
+        self.current_chunk_size += query.len();
+        self.size += query.len();
+
+        Ok(())
+    }
+
+    fn checkpoint_key(&self) -> String {
+        format!("table-{}", self.table_name)
+    }
+
+    fn flush(&mut self) -> Result<(), ReplibyteError> {
+        if self.current_chunk_size > 0 {
+            self.chunk_sizes.push(self.current_chunk_size);
+            self.current_chunk_size = 0;
+        }
+
+        Ok(self.file.flush()?)
+    }
+
+    /// write the table queries into the destination, chunk by chunk
+    fn restore<D: Destination, F: FnMut(&[u8])>(
+        mut self,
+        destination: &D,
+        mut progress_callback: F,
+    ) -> Result<(), ReplibyteError> {
+        let _ = self.file.seek(SeekFrom::Start(0))?;
+
+        let mut reader = BufReader::new(self.file);
+        let mut read_error = None;
+
+        let mut chunks = self.chunk_sizes.into_iter().map_while(|chunk_size| {
+            let mut chunk = vec![0; chunk_size];
+
+            match reader.read_exact(chunk.as_mut_slice()) {
+                Ok(_) => {
+                    progress_callback(chunk.as_slice());
+                    Some(chunk)
+                }
+                Err(err) => {
+                    read_error = Some(err);
+                    None
+                }
+            }
+        });
+
+        destination.write_chunks(&mut chunks)?;
+
+        match read_error {
+            Some(err) => Err(ReplibyteError::from(err)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// group the queries into chunks of CHUNK_SIZE max
+fn into_chunks(queries: Vec<Bytes>) -> Vec<Bytes> {
+    let mut chunks: Vec<Bytes> = vec![];
+
+    for mut query in queries {
+        query.push(b'\n');
+
+        match chunks.last_mut() {
+            Some(chunk) if chunk.len() + query.len() <= CHUNK_SIZE => chunk.append(&mut query),
+            _ => chunks.push(query),
+        }
+    }
+
+    chunks
+}
+
+/// group the tables by restore level: the tables of a level only reference tables of the previous levels.
+/// Tables with circular references are restored in the last level.
+fn restore_levels(
+    table_names: &[String],
+    dependencies: &HashMap<String, HashSet<String>>,
+) -> Vec<Vec<String>> {
+    let mut levels = vec![];
+    let mut remaining_table_names = table_names.to_vec();
+
+    while !remaining_table_names.is_empty() {
+        let (level, next_remaining_table_names): (Vec<String>, Vec<String>) = remaining_table_names
+            .iter()
+            .cloned()
+            .partition(|table_name| {
+                dependencies
+                    .get(table_name)
+                    .map(|referenced_table_names| {
+                        referenced_table_names.iter().all(|referenced_table_name| {
+                            referenced_table_name == table_name
+                                || !remaining_table_names.contains(referenced_table_name)
+                        })
+                    })
+                    .unwrap_or(true)
+            });
+
+        if level.is_empty() {
+            // circular references
+            levels.push(next_remaining_table_names);
+            break;
+        }
+
+        levels.push(level);
+        remaining_table_names = next_remaining_table_names;
+    }
+
+    levels
+}
+
+/// return the table name and the referenced table names of a
+/// `CREATE TABLE` or `ALTER TABLE` query with `REFERENCES` clauses
+fn parse_foreign_keys(query: &str) -> Option<(String, Vec<String>)> {
+    let table_definition = match strip_prefix_ignore_case(query, "CREATE TABLE") {
+        Some(table_definition) => {
+            strip_prefix_ignore_case(table_definition, "IF NOT EXISTS").unwrap_or(table_definition)
+        }
+        None => {
+            let table_definition = strip_prefix_ignore_case(query, "ALTER TABLE")?;
+            let table_definition =
+                strip_prefix_ignore_case(table_definition, "IF EXISTS").unwrap_or(table_definition);
+            strip_prefix_ignore_case(table_definition, "ONLY").unwrap_or(table_definition)
+        }
+    };
+
+    let (table_name, mut rest) = parse_identifier(table_definition)?;
+    let mut referenced_table_names = vec![];
+
+    while let Some(idx) = rest.to_ascii_uppercase().find("REFERENCES") {
+        rest = &rest[idx + "REFERENCES".len()..];
+
+        if let Some((referenced_table_name, next_rest)) = parse_identifier(rest) {
+            referenced_table_names.push(referenced_table_name);
+            rest = next_rest;
+        }
+    }
+
+    if referenced_table_names.is_empty() {
+        return None;
+    }
+
+    Some((table_name, referenced_table_names))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::{HashMap, HashSet};
+
+    use crate::connector::Connector;
+    use crate::destination::Destination;
+    use crate::errors::ReplibyteError;
+    use crate::tasks::parallel_restore::{parse_foreign_keys, restore_levels, DumpSplitter};
+    use crate::types::Bytes;
+
+    const DUMP: &str = r#"SET statement_timeout = 0;
+SELECT pg_catalog.set_config('search_path', '', false);
+CREATE TABLE public.orders (id integer NOT NULL, user_id integer);
+CREATE TABLE public.users (id integer NOT NULL, name text);
+INSERT INTO public.orders (id, user_id) VALUES (1, 1);
+INSERT INTO public.users (id, name) VALUES (1, 'Romaric');
+INSERT INTO public.orders (id, user_id) VALUES (2, 1);
+SELECT pg_catalog.setval('public.users_id_seq', 1, true);
+ALTER TABLE ONLY public.orders
+    ADD CONSTRAINT orders_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id);
+"#;
+
+    struct RecordingDestination {
+        queries: RefCell<Vec<String>>,
+    }
+
+    impl Connector for RecordingDestination {
+        fn init(&mut self) -> Result<(), ReplibyteError> {
+            Ok(())
+        }
+    }
+
+    impl Destination for RecordingDestination {
+        fn write_chunks(
+            &self,
+            chunks: &mut dyn Iterator<Item = Bytes>,
+        ) -> Result<(), ReplibyteError> {
+            for chunk in chunks {
+                self.queries
+                    .borrow_mut()
+                    .push(String::from_utf8(chunk).unwrap());
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn split_dump() {
+        let mut splitter = DumpSplitter::default();
+        splitter.add_chunk(DUMP.as_bytes()).unwrap();
+        let split_dump = splitter.finish().unwrap();
+
+        assert_eq!(
+            split_dump
+                .schema_queries
+                .iter()
+                .map(|query| String::from_utf8(query.clone()).unwrap())
+                .collect::<Vec<_>>(),
+            vec![
+                "SET statement_timeout = 0;",
+                "SELECT pg_catalog.set_config('search_path', '', false);",
+                "CREATE TABLE public.orders (id integer NOT NULL, user_id integer);",
+                "CREATE TABLE public.users (id integer NOT NULL, name text);",
+            ]
+        );
+
+        assert_eq!(split_dump.post_data_queries.len(), 2);
+
+        // users are referenced by orders, so they are restored first
+        let mut levels = vec![];
+        for level in split_dump.table_levels {
+            let mut restored_levels = vec![];
+
+            for table_stream in level {
+                let destination = RecordingDestination {
+                    queries: RefCell::new(vec![]),
+                };
+                table_stream.restore(&destination, |_| {}).unwrap();
+                restored_levels.push(destination.queries.into_inner().join(""));
+            }
+
+            levels.push(restored_levels);
+        }
+
+        assert_eq!(
+            levels,
+            vec![
+                vec![
+                    "SET statement_timeout = 0;\n\
+SELECT pg_catalog.set_config('search_path', '', false);\n\
+INSERT INTO public.users (id, name) VALUES (1, 'Romaric');\n"
+                ],
+                vec![
+                    "SET statement_timeout = 0;\n\
+SELECT pg_catalog.set_config('search_path', '', false);\n\
+INSERT INTO public.orders (id, user_id) VALUES (1, 1);\n\
+INSERT INTO public.orders (id, user_id) VALUES (2, 1);\n"
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn split_copy_dump() {
+        let mut splitter = DumpSplitter::default();
+        // the COPY block of public.users goes on in the second chunk
+        splitter
+            .add_chunk(
+                b"SET statement_timeout = 0;
+CREATE TABLE public.users (name text, email text);
+CREATE TABLE public.notes (body text);
+COPY public.users (name, email) FROM stdin;
+  Romaric\tromaric@example.com
+",
+            )
+            .unwrap();
+        splitter
+            .add_chunk(
+                b"\t\\N
+\\.
+
+COPY public.notes (body) FROM stdin;
+
+  indented
+\\.
+CREATE INDEX users_email ON public.users USING btree (email);
+",
+            )
+            .unwrap();
+        let split_dump = splitter.finish().unwrap();
+
+        assert_eq!(split_dump.schema_queries.len(), 3);
+        assert_eq!(
+            split_dump.post_data_queries,
+            vec![b"CREATE INDEX users_email ON public.users USING btree (email);".to_vec()]
+        );
+
+        let mut restored_tables = vec![];
+        for table_stream in split_dump.table_levels.into_iter().flatten() {
+            let destination = RecordingDestination {
+                queries: RefCell::new(vec![]),
+            };
+            table_stream.restore(&destination, |_| {}).unwrap();
+            restored_tables.push(destination.queries.into_inner().join(""));
+        }
+
+        // the COPY rows are restored as they are, with their leading whitespace and the empty rows
+        assert_eq!(
+            restored_tables,
+            vec![
+                "SET statement_timeout = 0;\n\
+COPY public.users (name, email) FROM stdin;\n  Romaric\tromaric@example.com\n\t\\N\n\\.\n",
+                "SET statement_timeout = 0;\n\
+COPY public.notes (body) FROM stdin;\n\n  indented\n\\.\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn foreign_keys() {
+        assert_eq!(
+            parse_foreign_keys(
+                "CREATE TABLE `orders` (`id` int, `user_id` int, \
+CONSTRAINT `fk` FOREIGN KEY (`user_id`) REFERENCES `users` (`id`));"
+            ),
+            Some(("orders".to_string(), vec!["users".to_string()]))
+        );
+
+        assert_eq!(
+            parse_foreign_keys(
+                "ALTER TABLE [dbo].[orders] ADD FOREIGN KEY ([user_id]) REFERENCES [dbo].[users] ([id]);"
+            ),
+            Some(("dbo.orders".to_string(), vec!["dbo.users".to_string()]))
+        );
+
+        assert_eq!(
+            parse_foreign_keys("CREATE TABLE public.users (id integer NOT NULL);"),
+            None
+        );
+    }
+
+    #[test]
+    fn levels() {
+        let table_names = vec![
+            "orders".to_string(),
+            "users".to_string(),
+            "countries".to_string(),
+            "a".to_string(),
+            "b".to_string(),
+        ];
+
+        let mut dependencies = HashMap::new();
+        let _ = dependencies.insert(
+            "orders".to_string(),
+            HashSet::from(["users".to_string(), "orders".to_string()]),
+        );
+        let _ = dependencies.insert(
+            "users".to_string(),
+            HashSet::from(["countries".to_string(), "unknown".to_string()]),
+        );
+        let _ = dependencies.insert("a".to_string(), HashSet::from(["b".to_string()]));
+        let _ = dependencies.insert("b".to_string(), HashSet::from(["a".to_string()]));
+
+        assert_eq!(
+            restore_levels(&table_names, &dependencies),
+            vec![
+                vec!["countries".to_string()],
+                vec!["users".to_string()],
+                vec!["orders".to_string()],
+                vec!["a".to_string(), "b".to_string()],
+            ]
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use std::io::{BufReader, Error, ErrorKind, Read};
+use std::sync::mpsc;
+use std::thread;
+
+use crate::config::SourceConfig;
+use crate::datastore::{Datastore, ReadOptions};
+use crate::errors::ReplibyteError;
+use crate::source::dump_file::DumpType;
+use crate::source::{mariadb, mssql, mysql, postgres, sqlite, SourceOptions};
+use crate::types::{Bytes, OriginalQuery, Query};
+
+/// max size of a transformed chunk, a chunk is always made of complete queries
+const CHUNK_SIZE: usize = 10 * 1024 * 1024;
+
+/// RestoreTransform applies the transformers of the source to a dump while it is restored, e.g. to a dump
+/// created without them. The skipped tables and the row filters of the source are applied as well, but not its subset.
+#[derive(Clone)]
+pub struct RestoreTransform {
+    dump_type: DumpType,
+    source: SourceConfig,
+}
+
+impl RestoreTransform {
+    /// `database_type` is the type of the database the dump is made from, e.g. `postgresql`
+    pub fn new(database_type: &str, source: SourceConfig) -> Result<Self, ReplibyteError> {
+        match DumpType::new(database_type)? {
+            DumpType::MongoDB => Err(ReplibyteError::Config(format!(
+                "the transformers cannot be applied to a {} dump while it is restored",
+                database_type
+            ))),
+            dump_type => Ok(RestoreTransform { dump_type, source }),
+        }
+    }
+
+    /// read the dump from the datastore and pass its transformed chunks to the callback.
+    /// The next chunks are downloaded while the previous ones are transformed.
+    pub fn read(
+        &self,
+        datastore: &dyn Datastore,
+        options: &ReadOptions,
+        data_callback: &mut dyn FnMut(Bytes),
+    ) -> Result<(), ReplibyteError> {
+        // bound to 1 to avoid eating too much memory if we download the dump faster than we transform it
+        let (tx, rx) = mpsc::sync_channel::<Bytes>(1);
+
+        thread::scope(|scope| {
+            let join_handle = scope.spawn(move || {
+                datastore.read_with_bases(options, &mut |data| {
+                    let _ = tx.send(data);
+                })
+            });
+
+            // the download stops once the reader is dropped
+            let transform_result = self.transform(ChunksReader::new(rx.into_iter()), data_callback);
+
+            let read_result = join_handle.join().unwrap_or_else(|_| {
+                Err(ReplibyteError::from(Error::new(
+                    ErrorKind::Other,
+                    "the download of the dump panicked",
+                )))
+            });
+
+            // a failed download truncates the dump, its error comes first
+            read_result.and(transform_result)
+        })
+    }
+
+    /// transform the queries of the dump, they are passed to the callback by chunks of complete queries
+    pub fn transform<R: Read>(
+        &self,
+        reader: R,
+        data_callback: &mut dyn FnMut(Bytes),
+    ) -> Result<(), ReplibyteError> {
+        let transformers = self.source.transformers();
+        let skip_config = self.source.skip_config();
+        let filters = self.source.filters();
+        let only_tables = self.source.only_tables.clone().unwrap_or_default();
+
+        let options = SourceOptions {
+            transformers: &transformers,
+            skip_config: &skip_config,
+            database_subset: &None,
+            only_tables: &only_tables,
+            filters: &filters,
+            // the dumps are stored in UTF-8
+            encoding: None,
+            skip_large_objects: false,
+            skip_bytea: false,
+        };
+
+        let mut chunk = Vec::new();
+        let query_callback = |_original_query: OriginalQuery, query: Query| {
+            if !chunk.is_empty() && chunk.len() + query.data().len() >= CHUNK_SIZE {
+                data_callback(std::mem::take(&mut chunk));
+            }
+
+            chunk.extend_from_slice(query.data());
+            chunk.push(b'\n');
+        };
+
+        let reader = BufReader::new(reader);
+
+        match self.dump_type {
+            DumpType::Postgres => postgres::read_and_transform(reader, options, query_callback)?,
+            DumpType::Mysql => mysql::read_and_transform(reader, options, query_callback)?,
+            DumpType::MariaDB => mariadb::read_and_transform(reader, options, query_callback)?,
+            DumpType::MSSQL => mssql::read_and_transform(reader, options, query_callback),
+            DumpType::SQLite => sqlite::read_and_transform(reader, options, query_callback),
+            // rejected by `new`
+            DumpType::MongoDB => {}
+        }
+
+        if !chunk.is_empty() {
+            data_callback(chunk);
+        }
+
+        Ok(())
+    }
+}
+
+/// ChunksReader reads the chunks of a dump as a single stream, a query can span several chunks
+pub struct ChunksReader<I> {
+    chunks: I,
+    chunk: Bytes,
+    position: usize,
+}
+
+impl<I> ChunksReader<I>
+where
+    I: Iterator<Item = Bytes>,
+{
+    pub fn new(chunks: I) -> Self {
+        ChunksReader {
+            chunks,
+            chunk: vec![],
+            position: 0,
+        }
+    }
+}
+
+impl<I> Read for ChunksReader<I>
+where
+    I: Iterator<Item = Bytes>,
+{
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        while self.position == self.chunk.len() {
+            match self.chunks.next() {
+                Some(chunk) => {
+                    self.chunk = chunk;
+                    self.position = 0;
+                }
+                None => return Ok(0),
+            }
+        }
+
+        let len = buf.len().min(self.chunk.len() - self.position);
+        buf[..len].copy_from_slice(&self.chunk[self.position..self.position + len]);
+        self.position += len;
+
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use crate::config::SourceConfig;
+    use crate::tasks::transform::{ChunksReader, RestoreTransform};
+
+    const SOURCE: &str = "transformers:
+  - database: public
+    table: users
+    columns:
+      - name: email
+        transformer_name: set-constant
+        transformer_options:
+          value: redacted
+skip:
+  - database: public
+    table: logs";
+
+    #[test]
+    fn read_chunks() {
+        let chunks = vec![b"SELECT ".to_vec(), vec![], b"1;\nSELECT 2;\n".to_vec()];
+
+        let mut dump = String::new();
+        let _ = ChunksReader::new(chunks.into_iter())
+            .read_to_string(&mut dump)
+            .unwrap();
+
+        assert_eq!(dump, "SELECT 1;\nSELECT 2;\n");
+    }
+
+    #[test]
+    fn transform_postgres_dump() {
+        let source = serde_yaml::from_str::<SourceConfig>(SOURCE).unwrap();
+        let transform = RestoreTransform::new("postgresql", source).unwrap();
+
+        // the COPY block is split between the chunks of the dump
+        let chunks = vec![
+            b"CREATE TABLE public.users (id integer, email text);\nCOPY public.users (id, email) FROM stdin;\n1\talice@example.com\n".to_vec(),
+            b"2\tbob@example.com\n\\.\nINSERT INTO public.logs (id) VALUES (1);\n".to_vec(),
+        ];
+
+        let mut transformed_chunks = vec![];
+        transform
+            .transform(ChunksReader::new(chunks.into_iter()), &mut |chunk| {
+                transformed_chunks.push(String::from_utf8(chunk).unwrap())
+            })
+            .unwrap();
+
+        assert_eq!(transformed_chunks.len(), 1);
+        let dump = transformed_chunks[0].as_str();
+        assert!(dump.contains(
+            "COPY public.users (id, email) FROM stdin;\n1\tredacted\n2\tredacted\n\\.\n"
+        ));
+        assert!(!dump.contains("example.com"));
+        // the skipped tables are not restored
+        assert!(!dump.contains("public.logs"));
+    }
+
+    #[test]
+    fn unsupported_dumps() {
+        let source = serde_yaml::from_str::<SourceConfig>(SOURCE).unwrap();
+
+        assert!(RestoreTransform::new("mysql", source.clone()).is_ok());
+        assert!(RestoreTransform::new("mongodb", source).is_err());
+    }
+}
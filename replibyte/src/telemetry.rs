@@ -1,11 +1,11 @@
-use crate::config::{ConnectionUri, TransformerTypeConfig};
-use crate::{Config, DumpCommand, RestoreCommand, SubCommand, TransformerCommand};
+use crate::config::{TransformerGroupConfig, TransformerTypeConfig};
+use crate::errors::ReplibyteError;
+use crate::{Config, SubCommand};
 use chrono::{NaiveDateTime, Utc};
 use reqwest::blocking::Client as HttpClient;
 use reqwest::header::CONTENT_TYPE;
 use serde::Serialize;
 use std::collections::{HashMap, HashSet};
-use std::io::{Error, ErrorKind};
 use std::time::Duration;
 
 extern crate serde_json;
@@ -45,7 +45,7 @@ impl TelemetryClient {
         }
     }
 
-    pub fn capture(&self, event: Event) -> Result<(), Error> {
+    pub fn capture(&self, event: Event) -> Result<(), ReplibyteError> {
         let inner_event = InnerEvent::new(event, self.options.api_key.clone());
         let _res = self
             .client
@@ -53,11 +53,11 @@ impl TelemetryClient {
             .header(CONTENT_TYPE, "application/json")
             .body(serde_json::to_string(&inner_event).expect("unwrap here is safe"))
             .send()
-            .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))?;
+            .map_err(|e| ReplibyteError::Connection(e.to_string()))?;
         Ok(())
     }
 
-    pub fn capture_batch(&self, events: Vec<Event>) -> Result<(), Error> {
+    pub fn capture_batch(&self, events: Vec<Event>) -> Result<(), ReplibyteError> {
         for event in events {
             self.capture(event)?;
         }
@@ -70,30 +70,33 @@ impl TelemetryClient {
         sub_command: &SubCommand,
         args: &Vec<String>,
         execution_time_in_millis: Option<u128>,
-    ) -> Result<(), Error> {
+    ) -> Result<(), ReplibyteError> {
         let mut props = HashMap::new();
         let _ = props.insert("args".to_string(), args.join(" ").to_string());
 
         props.insert(
             "encryption_used".to_string(),
-            config.encryption_key.is_some().to_string(),
+            (config.encryption_key.is_some()
+                || config.encryption_keys.is_some()
+                || config.encryption_kms.is_some())
+            .to_string(),
         );
 
         match &config.source {
             Some(x) => {
                 props.insert(
                     "database".to_string(),
-                    match x.connection_uri()? {
-                        ConnectionUri::Postgres(_, _, _, _, _) => "postgresql",
-                        ConnectionUri::Mysql(_, _, _, _, _) => "mysql",
-                        ConnectionUri::MongoDB(_, _) => "mongodb",
-                    }
-                    .to_string(),
+                    x.connection_uri()?.database_type().to_string(),
                 );
 
                 props.insert(
                     "compression_used".to_string(),
-                    x.compression.unwrap_or(true).to_string(),
+                    x.compression()?.is_some().to_string(),
+                );
+
+                props.insert(
+                    "dedup_used".to_string(),
+                    (x.dedup == Some(true)).to_string(),
                 );
 
                 props.insert("skip_tables_used".to_string(), x.skip.is_some().to_string());
@@ -103,51 +106,68 @@ impl TelemetryClient {
                     x.database_subset.is_some().to_string(),
                 );
 
+                props.insert(
+                    "global_transformations_used".to_string(),
+                    x.global_transformations.is_some().to_string(),
+                );
+
                 let mut transformers = HashSet::new();
 
-                if let Some(transformers_config) = &x.transformers {
-                    for transformer in transformers_config {
-                        for column in &transformer.columns {
-                            transformers.insert(match column.transformer {
-                                TransformerTypeConfig::Random => "random",
-                                TransformerTypeConfig::RandomDate => "random-date",
-                                TransformerTypeConfig::FirstName => "first-name",
-                                TransformerTypeConfig::Email => "email",
-                                TransformerTypeConfig::KeepFirstChar => "keep-first-char",
-                                TransformerTypeConfig::PhoneNumber => "phone-number",
-                                TransformerTypeConfig::CreditCard => "credit-card",
-                                TransformerTypeConfig::Redacted(_) => "redacted",
-                                TransformerTypeConfig::Transient => "transient",
-                                TransformerTypeConfig::CustomWasm(_) => "custom-wasm",
-                            });
-                        }
-                    }
+                let columns = x
+                    .transformers
+                    .iter()
+                    .flatten()
+                    .flat_map(|transformer| transformer.columns.iter())
+                    .chain(x.global_transformations.iter().flatten());
+
+                for column in columns {
+                    transformers.insert(match column.transformer {
+                        TransformerTypeConfig::Random => "random",
+                        TransformerTypeConfig::RandomDate => "random-date",
+                        TransformerTypeConfig::FirstName => "first-name",
+                        TransformerTypeConfig::Email => "email",
+                        TransformerTypeConfig::KeepFirstChar => "keep-first-char",
+                        TransformerTypeConfig::PhoneNumber => "phone-number",
+                        TransformerTypeConfig::CreditCard => "credit-card",
+                        TransformerTypeConfig::Iban => "iban",
+                        TransformerTypeConfig::Ipv4(_) => "ipv4",
+                        TransformerTypeConfig::Ipv6(_) => "ipv6",
+                        TransformerTypeConfig::Mac(_) => "mac",
+                        TransformerTypeConfig::Redacted(_) => "redacted",
+                        TransformerTypeConfig::Transient => "transient",
+                        TransformerTypeConfig::SetNull => "set-null",
+                        TransformerTypeConfig::SetConstant(_) => "set-constant",
+                        TransformerTypeConfig::Faker(_) => "faker",
+                        TransformerTypeConfig::Fpe(_) => "fpe",
+                        TransformerTypeConfig::Hash(_) => "hash",
+                        TransformerTypeConfig::RegexReplace(_) => "regex-replace",
+                        TransformerTypeConfig::JsonPath(_) => "json-path",
+                        TransformerTypeConfig::XmlXpath(_) => "xml-xpath",
+                        TransformerTypeConfig::DateShift(_) => "date-shift",
+                        TransformerTypeConfig::Noise(_) => "noise",
+                        TransformerTypeConfig::Script(_) => "script",
+                        TransformerTypeConfig::Process(_) => "process",
+                        TransformerTypeConfig::CustomWasm(_) => "custom-wasm",
+                    });
+                }
 
-                    for (idx, transformer_name) in transformers.iter().enumerate() {
-                        props.insert(format!("transformer_{}", idx), transformer_name.to_string());
+                for transformer in x.transformers.iter().flatten() {
+                    for group in transformer.groups.iter().flatten() {
+                        transformers.insert(match group {
+                            TransformerGroupConfig::Person(_) => "person",
+                        });
                     }
                 }
+
+                for (idx, transformer_name) in transformers.iter().enumerate() {
+                    props.insert(format!("transformer_{}", idx), transformer_name.to_string());
+                }
             }
             None => {}
         };
 
-        let event = match sub_command {
-            SubCommand::Dump(cmd) => match cmd {
-                DumpCommand::List => "dump-list",
-                DumpCommand::Create(_) => "dump-create",
-                DumpCommand::Delete(_) => "dump-delete",
-                DumpCommand::Restore(restore_cmd) => match restore_cmd {
-                    RestoreCommand::Local(_) => "dump-restore-local",
-                    RestoreCommand::Remote(_) => "dump-restore-remote",
-                },
-            },
-            SubCommand::Transformer(cmd) => match cmd {
-                TransformerCommand::List => "transformer-list",
-            },
-        };
-
         self.capture(Event {
-            event: event.to_string(),
+            event: sub_command.name().to_string(),
             properties: Properties {
                 distinct_id: machine_uid::get().unwrap_or("unknown".to_string()),
                 props,
@@ -0,0 +1,117 @@
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::errors::ReplibyteError;
+
+/// shorter waits are added to the next ones, sleeping for each small query would slow the dump down
+const MIN_WAIT: Duration = Duration::from_millis(10);
+
+/// Throttle limits the average rate of the transfers sharing it, e.g. all the datastores of a dump
+pub struct Throttle {
+    bytes_per_second: u64,
+    /// time at which the next transfer can start
+    next: Mutex<Instant>,
+}
+
+impl Throttle {
+    pub fn new(bytes_per_second: u64) -> Self {
+        Throttle {
+            bytes_per_second: bytes_per_second.max(1),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub fn bytes_per_second(&self) -> u64 {
+        self.bytes_per_second
+    }
+
+    /// wait until `bytes` can be transferred without exceeding the bandwidth
+    pub fn consume(&self, bytes: usize) {
+        let wait = {
+            let mut next = self.next.lock().unwrap();
+            let now = Instant::now();
+
+            // the bandwidth not used while idle is not used later
+            if *next < now {
+                *next = now;
+            }
+
+            let wait = *next - now;
+            *next += Duration::from_secs_f64(bytes as f64 / self.bytes_per_second as f64);
+            wait
+        };
+
+        if wait >= MIN_WAIT {
+            thread::sleep(wait);
+        }
+    }
+}
+
+/// parse a bandwidth like `50MB/s`, `512KiB/s` or `1000000`, in bytes per second
+pub fn parse_bandwidth(bandwidth: &str) -> Result<u64, ReplibyteError> {
+    let invalid = || {
+        ReplibyteError::Config(format!(
+            "invalid bandwidth '{}', expected a value like 50MB/s",
+            bandwidth
+        ))
+    };
+
+    let value = bandwidth.trim();
+    let value = value.strip_suffix("/s").unwrap_or(value);
+    let unit_start = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(unit_start);
+
+    let multiplier = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1f64,
+        "kb" => 1000f64,
+        "mb" => 1000f64 * 1000f64,
+        "gb" => 1000f64 * 1000f64 * 1000f64,
+        "kib" => 1024f64,
+        "mib" => 1024f64 * 1024f64,
+        "gib" => 1024f64 * 1024f64 * 1024f64,
+        _ => return Err(invalid()),
+    };
+
+    let bytes_per_second = number.parse::<f64>().map_err(|_| invalid())? * multiplier;
+    if bytes_per_second < 1f64 {
+        return Err(invalid());
+    }
+
+    Ok(bytes_per_second as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::throttle::{parse_bandwidth, Throttle};
+
+    #[test]
+    fn bandwidths() {
+        assert_eq!(parse_bandwidth("50MB/s").unwrap(), 50_000_000);
+        assert_eq!(parse_bandwidth("512KiB/s").unwrap(), 512 * 1024);
+        assert_eq!(parse_bandwidth("1.5 GB/s").unwrap(), 1_500_000_000);
+        assert_eq!(parse_bandwidth("1000000").unwrap(), 1_000_000);
+        assert_eq!(parse_bandwidth("10mb").unwrap(), 10_000_000);
+        assert!(parse_bandwidth("fast").is_err());
+        assert!(parse_bandwidth("50MB/h").is_err());
+        assert!(parse_bandwidth("0MB/s").is_err());
+    }
+
+    #[test]
+    fn consume() {
+        let throttle = Throttle::new(1000);
+        let started_at = Instant::now();
+
+        // the first 100 bytes are not waited for
+        for _ in 0..4 {
+            throttle.consume(100);
+        }
+
+        assert!(started_at.elapsed() >= Duration::from_millis(300));
+        assert!(started_at.elapsed() < Duration::from_millis(1000));
+    }
+}
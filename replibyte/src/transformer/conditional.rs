@@ -0,0 +1,321 @@
+use regex::Regex;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::transformer::Transformer;
+use crate::types::Column;
+
+/// This struct is dedicated to applying a transformer only on the rows matching all the conditions.
+pub struct ConditionalTransformer {
+    transformer: Box<dyn Transformer>,
+    conditions: Vec<TransformerCondition>,
+}
+
+/// condition on the original value of a column of the row
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TransformerCondition {
+    pub column: String,
+    #[serde(flatten)]
+    pub operator: ConditionOperator,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum ConditionOperator {
+    Equals(ConditionValue),
+    NotEquals(ConditionValue),
+    In(Vec<ConditionValue>),
+    /// the text of the value contains a match of the regex
+    Matches(ConditionRegex),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum ConditionValue {
+    Null,
+    Boolean(bool),
+    Number(i64),
+    Float(f64),
+    String(String),
+}
+
+/// regex of a condition, compiled once when the configuration is read
+#[derive(Debug, Clone)]
+pub struct ConditionRegex(Regex);
+
+impl ConditionRegex {
+    pub fn new(regex: &str) -> Result<Self, regex::Error> {
+        Regex::new(regex).map(ConditionRegex)
+    }
+}
+
+impl PartialEq for ConditionRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl Serialize for ConditionRegex {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ConditionRegex {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let regex = String::deserialize(deserializer)?;
+        ConditionRegex::new(regex.as_str()).map_err(de::Error::custom)
+    }
+}
+
+impl ConditionalTransformer {
+    pub fn new(transformer: Box<dyn Transformer>, conditions: Vec<TransformerCondition>) -> Self {
+        ConditionalTransformer {
+            transformer,
+            conditions,
+        }
+    }
+}
+
+impl TransformerCondition {
+    /// a column missing from the row is equal to no value
+    pub fn matches(&self, row: &[Column]) -> bool {
+        let column = row.iter().find(|column| column.name() == self.column);
+        let equals =
+            |value: &ConditionValue| matches!(column, Some(column) if is_equal(column, value));
+
+        match &self.operator {
+            ConditionOperator::Equals(value) => equals(value),
+            ConditionOperator::NotEquals(value) => !equals(value),
+            ConditionOperator::In(values) => values.iter().any(equals),
+            ConditionOperator::Matches(ConditionRegex(regex)) => {
+                matches!(column.and_then(to_text), Some(text) if regex.is_match(text.as_str()))
+            }
+        }
+    }
+}
+
+/// the text of a value, NULL has no text
+fn to_text(column: &Column) -> Option<String> {
+    match column {
+        Column::NumberValue(_, value) => Some(value.to_string()),
+        Column::FloatNumberValue(_, value) => Some(value.to_string()),
+        Column::StringValue(_, value) => Some(value.clone()),
+        Column::CharValue(_, value) => Some(value.to_string()),
+        Column::BooleanValue(_, value) => Some(value.to_string()),
+        Column::None(_) => None,
+    }
+}
+
+fn is_equal(column: &Column, value: &ConditionValue) -> bool {
+    match (column, value) {
+        (Column::None(_), ConditionValue::Null) => true,
+        (Column::BooleanValue(_, column_value), ConditionValue::Boolean(value)) => {
+            column_value == value
+        }
+        // MySQL stores the booleans as numbers
+        (Column::NumberValue(_, column_value), ConditionValue::Boolean(value)) => {
+            *column_value == *value as i128
+        }
+        (Column::NumberValue(_, column_value), ConditionValue::Number(value)) => {
+            *column_value == *value as i128
+        }
+        (Column::NumberValue(_, column_value), ConditionValue::Float(value)) => {
+            *column_value as f64 == *value
+        }
+        (Column::FloatNumberValue(_, column_value), ConditionValue::Number(value)) => {
+            *column_value == *value as f64
+        }
+        (Column::FloatNumberValue(_, column_value), ConditionValue::Float(value)) => {
+            column_value == value
+        }
+        (Column::StringValue(_, column_value), ConditionValue::String(value)) => {
+            column_value == value
+        }
+        // the values of some dumps are all quoted
+        (Column::StringValue(_, column_value), ConditionValue::Boolean(value)) => {
+            *column_value == value.to_string()
+        }
+        (Column::StringValue(_, column_value), ConditionValue::Number(value)) => {
+            *column_value == value.to_string()
+        }
+        (Column::CharValue(_, column_value), ConditionValue::String(value)) => {
+            *value == column_value.to_string()
+        }
+        _ => false,
+    }
+}
+
+impl Transformer for ConditionalTransformer {
+    fn id(&self) -> &str {
+        self.transformer.id()
+    }
+
+    fn description(&self) -> &str {
+        self.transformer.description()
+    }
+
+    fn database_name(&self) -> &str {
+        self.transformer.database_name()
+    }
+
+    fn table_name(&self) -> &str {
+        self.transformer.table_name()
+    }
+
+    fn column_name(&self) -> &str {
+        self.transformer.column_name()
+    }
+
+    fn transform_row(&self, column: Column, row: &[Column]) -> Column {
+        if self
+            .conditions
+            .iter()
+            .all(|condition| condition.matches(row))
+        {
+            self.transformer.transform_row(column, row)
+        } else {
+            column
+        }
+    }
+
+    fn transform(&self, column: Column) -> Column {
+        self.transform_row(column, &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transformer::redacted::{RedactedTransformer, RedactedTransformerOptions};
+    use crate::{transformer::Transformer, types::Column};
+
+    use super::{
+        ConditionOperator, ConditionRegex, ConditionValue, ConditionalTransformer,
+        TransformerCondition,
+    };
+
+    #[test]
+    fn transform_when_conditions_match() {
+        let transformer = get_transformer(vec![
+            condition(
+                "is_internal",
+                ConditionOperator::Equals(ConditionValue::Boolean(false)),
+            ),
+            condition(
+                "country",
+                ConditionOperator::In(vec![
+                    ConditionValue::String("DE".to_string()),
+                    ConditionValue::String("FR".to_string()),
+                ]),
+            ),
+        ]);
+
+        let transform = |is_internal: Column, country: &str| {
+            let email =
+                Column::StringValue("email".to_string(), "john.doe@company.com".to_string());
+            let row = vec![
+                email.clone(),
+                is_internal,
+                Column::StringValue("country".to_string(), country.to_string()),
+            ];
+
+            transformer
+                .transform_row(email, &row)
+                .string_value()
+                .unwrap()
+                .to_string()
+        };
+
+        let not_internal = || Column::BooleanValue("is_internal".to_string(), false);
+        assert_eq!(transform(not_internal(), "DE"), "joh*****************");
+        assert_eq!(transform(not_internal(), "US"), "john.doe@company.com");
+        assert_eq!(
+            transform(Column::BooleanValue("is_internal".to_string(), true), "DE"),
+            "john.doe@company.com"
+        );
+        // MySQL booleans are numbers
+        assert_eq!(
+            transform(Column::NumberValue("is_internal".to_string(), 0), "FR"),
+            "joh*****************"
+        );
+        assert_eq!(
+            transform(Column::None("is_internal".to_string()), "FR"),
+            "john.doe@company.com"
+        );
+    }
+
+    #[test]
+    fn transform_when_column_is_missing() {
+        let email = Column::StringValue("email".to_string(), "john.doe@company.com".to_string());
+
+        let transformer = get_transformer(vec![condition(
+            "is_internal",
+            ConditionOperator::Equals(ConditionValue::Boolean(false)),
+        )]);
+        let transformed_column =
+            transformer.transform_row(email.clone(), std::slice::from_ref(&email));
+        assert_eq!(
+            transformed_column.string_value().unwrap(),
+            "john.doe@company.com"
+        );
+
+        let transformer = get_transformer(vec![condition(
+            "is_internal",
+            ConditionOperator::NotEquals(ConditionValue::Boolean(true)),
+        )]);
+        let transformed_column = transformer.transform_row(email.clone(), &[email]);
+        assert_eq!(
+            transformed_column.string_value().unwrap(),
+            "joh*****************"
+        );
+    }
+
+    #[test]
+    fn transform_when_regex_matches() {
+        let transformer = get_transformer(vec![condition(
+            "email",
+            ConditionOperator::Matches(ConditionRegex::new(r"@company\.com$").unwrap()),
+        )]);
+
+        let transform = |email: Column| {
+            let row = vec![email.clone()];
+            transformer.transform_row(email, &row)
+        };
+
+        let email = Column::StringValue("email".to_string(), "john.doe@company.com".to_string());
+        assert_eq!(
+            transform(email).string_value().unwrap(),
+            "joh*****************"
+        );
+
+        let email = Column::StringValue("email".to_string(), "john.doe@gmail.com".to_string());
+        assert_eq!(
+            transform(email).string_value().unwrap(),
+            "john.doe@gmail.com"
+        );
+
+        let email = Column::None("email".to_string());
+        assert!(matches!(transform(email), Column::None(_)));
+    }
+
+    fn condition(column: &str, operator: ConditionOperator) -> TransformerCondition {
+        TransformerCondition {
+            column: column.to_string(),
+            operator,
+        }
+    }
+
+    fn get_transformer(conditions: Vec<TransformerCondition>) -> ConditionalTransformer {
+        let redacted = RedactedTransformer::new(
+            "github",
+            "users",
+            "email",
+            RedactedTransformerOptions {
+                character: '*',
+                width: 17,
+            },
+        );
+
+        ConditionalTransformer::new(Box::new(redacted), conditions)
+    }
+}
@@ -3,8 +3,13 @@ use crate::types::Column;
 use fake::faker::creditcard::raw::CreditCardNumber;
 use fake::locales::EN;
 use fake::Fake;
+use rand::Rng;
+
+/// the first digits identify the issuer (e.g. Visa or Mastercard), they are kept
+const IIN_LENGTH: usize = 6;
 
 /// This struct is dedicated to replacing a credit card string.
+/// The issuer, the length and the separators of the number are kept, and its Luhn check digit is valid.
 pub struct CreditCardTransformer {
     database_name: String,
     table_name: String,
@@ -40,7 +45,7 @@ impl Transformer for CreditCardTransformer {
     }
 
     fn description(&self) -> &str {
-        "Generate a credit card number (string only)."
+        "Generate a credit card number of the same issuer with a valid Luhn check digit (string only). [4242 4242 4242 4242]->[4242 4281 0395 7713]"
     }
 
     fn database_name(&self) -> &str {
@@ -57,19 +62,79 @@ impl Transformer for CreditCardTransformer {
 
     fn transform(&self, column: Column) -> Column {
         match column {
-            Column::StringValue(column_name, _value) => {
-                Column::StringValue(column_name, CreditCardNumber(EN).fake())
+            Column::StringValue(column_name, value) => {
+                let new_value = match replace_card_number(&mut rand::thread_rng(), value.as_str()) {
+                    Some(new_value) => new_value,
+                    None => CreditCardNumber(EN).fake(),
+                };
+
+                Column::StringValue(column_name, new_value)
             }
             column => column,
         }
     }
 }
 
+/// replace the digits of a card number after its IIN, `None` if the value is not a card number
+fn replace_card_number<R: Rng>(rng: &mut R, value: &str) -> Option<String> {
+    let mut digits = value
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .collect::<Vec<_>>();
+
+    if !(12..=19).contains(&digits.len())
+        || value
+            .chars()
+            .any(|c| !(c.is_ascii_digit() || c == ' ' || c == '-'))
+    {
+        return None;
+    }
+
+    let check_digit_position = digits.len() - 1;
+    loop {
+        for digit in &mut digits[IIN_LENGTH..check_digit_position] {
+            *digit = rng.gen_range(0..10);
+        }
+        digits[check_digit_position] = luhn_check_digit(&digits[..check_digit_position]);
+
+        let mut new_digits = digits.iter();
+        let new_value = value
+            .chars()
+            .map(|c| match c.to_digit(10) {
+                // unwrap is safe: there are as many new digits as digits
+                Some(_) => char::from_digit(*new_digits.next().unwrap(), 10).unwrap(),
+                None => c,
+            })
+            .collect::<String>();
+
+        if new_value != value {
+            return Some(new_value);
+        }
+    }
+}
+
+/// the digit making the Luhn sum of the number a multiple of 10
+pub fn luhn_check_digit(digits: &[u32]) -> u32 {
+    // from the right, every other digit is doubled, starting with the one next to the check digit
+    let sum = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(position, digit)| match position % 2 {
+            0 if *digit > 4 => digit * 2 - 9,
+            0 => digit * 2,
+            _ => *digit,
+        })
+        .sum::<u32>();
+
+    (10 - sum % 10) % 10
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{transformer::Transformer, types::Column};
 
-    use super::CreditCardTransformer;
+    use super::{luhn_check_digit, CreditCardTransformer};
 
     #[test]
     fn transform_string_with_a_credit_card() {
@@ -80,6 +145,50 @@ mod tests {
 
         assert!(!transformed_value.is_empty());
         assert_ne!(transformed_value, "4242424242424242".to_string());
+        assert!(is_luhn_valid(transformed_value));
+    }
+
+    #[test]
+    fn transform_credit_card_keeps_format() {
+        let transformer = get_transformer();
+
+        for value in [
+            "4242 4242 4242 4242",
+            "5555-5555-5555-4444",
+            "378282246310005",
+        ] {
+            let column = Column::StringValue("credit_card".to_string(), value.to_string());
+            let transformed_column = transformer.transform(column);
+            let transformed_value = transformed_column.string_value().unwrap();
+
+            assert_ne!(transformed_value, value);
+            assert_eq!(transformed_value.len(), value.len());
+            assert_eq!(digits(transformed_value)[..6], digits(value)[..6]);
+            assert!(is_luhn_valid(transformed_value));
+            for (original, transformed) in value.chars().zip(transformed_value.chars()) {
+                assert_eq!(original.is_ascii_digit(), transformed.is_ascii_digit());
+            }
+        }
+    }
+
+    #[test]
+    fn luhn_check_digits() {
+        assert_eq!(luhn_check_digit(&[7, 9, 9, 2, 7, 3, 9, 8, 7, 1]), 3);
+        assert_eq!(
+            luhn_check_digit(&[4, 2, 4, 2, 4, 2, 4, 2, 4, 2, 4, 2, 4, 2, 4]),
+            2
+        );
+    }
+
+    fn is_luhn_valid(value: &str) -> bool {
+        let digits = digits(value);
+        let (check_digit, digits) = digits.split_last().unwrap();
+
+        luhn_check_digit(digits) == *check_digit
+    }
+
+    fn digits(value: &str) -> Vec<u32> {
+        value.chars().filter_map(|c| c.to_digit(10)).collect()
     }
 
     fn get_transformer() -> CreditCardTransformer {
@@ -1,22 +1,38 @@
-use std::io::{self, ErrorKind};
+use std::time::Duration;
 
+use crate::errors::ReplibyteError;
+use crate::transformer::json_path::{to_column, to_value};
 use crate::transformer::Transformer;
 use crate::types::Column;
 
+use reqwest::blocking::Client;
+use ring::digest;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use wasmer::{ImportObject, Instance, Module, Store};
-use wasmer_wasi::{Pipe, WasiEnv, WasiState};
+use wasmer_wasi::{get_wasi_version, Pipe, WasiEnv, WasiState};
 
 pub type WasmError = Box<dyn std::error::Error>;
 
+/// version of the plugin ABI, returned by the `replibyte_abi_version` function of the plugins
+pub const PLUGIN_ABI_VERSION: i32 = 1;
+const PLUGIN_TRANSFORM_FUNCTION: &str = "replibyte_transform";
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct CustomWasmTransformerOptions {
+    /// path of the wasm file, or http(s) url it is downloaded from
     pub path: String,
+    /// hex encoded SHA-256 checksum the wasm file must have
+    pub sha256: Option<String>,
 }
 struct WasmConfig {
     wasi_env: WasiEnv,
     import_object: ImportObject,
     module: Module,
+    /// instance of the modules implementing the plugin ABI, the other modules are run through stdin and stdout
+    plugin: Option<Instance>,
 }
 pub struct CustomWasmTransformer {
     database_name: String,
@@ -49,8 +65,20 @@ impl CustomWasmTransformer {
             .stdout(Box::new(output))
             .finalize()?;
 
-        // Import object related to WASI
-        let import_object = wasi_env.import_object(&module)?;
+        // Import object related to WASI, the plugins don't have to use it
+        let import_object = match get_wasi_version(&module, false) {
+            Some(_) => wasi_env.import_object(&module)?,
+            None => ImportObject::new(),
+        };
+
+        let plugin = if module
+            .exports()
+            .any(|export| export.name() == PLUGIN_TRANSFORM_FUNCTION)
+        {
+            Some(new_plugin_instance(&module, &import_object)?)
+        } else {
+            None
+        };
 
         Ok(CustomWasmTransformer {
             database_name: database_name.into(),
@@ -60,9 +88,66 @@ impl CustomWasmTransformer {
                 wasi_env,
                 import_object,
                 module,
+                plugin,
             },
         })
     }
+
+    /// call the `replibyte_transform` function of a plugin with the JSON encoded value and names,
+    /// the plugin returns the JSON encoded new value
+    fn call_wasm_plugin(&self, instance: &Instance, column: Column) -> Result<Value, WasmError> {
+        let input = json!({
+            "database": self.database_name,
+            "table": self.table_name,
+            "column": column.name(),
+            "value": to_value(column),
+        })
+        .to_string();
+
+        let memory = instance.exports.get_memory("memory")?;
+        let alloc = instance
+            .exports
+            .get_native_function::<i32, i32>("replibyte_alloc")?;
+        let transform = instance
+            .exports
+            .get_native_function::<(i32, i32), i64>(PLUGIN_TRANSFORM_FUNCTION)?;
+
+        let input_len = input.len() as i32;
+        let input_ptr = alloc.call(input_len)?;
+        let view = memory.view::<u8>();
+        let input_cells = view
+            .get(input_ptr as usize..input_ptr as usize + input.len())
+            .ok_or_else(|| {
+                ReplibyteError::Config("Wasm plugin allocated out of its memory".to_string())
+            })?;
+        for (cell, byte) in input_cells.iter().zip(input.as_bytes()) {
+            cell.set(*byte);
+        }
+
+        // the input is owned by the plugin, the output is a (pointer << 32 | length) of its memory
+        let output = transform.call(input_ptr, input_len)? as u64;
+        let (output_ptr, output_len) = ((output >> 32) as usize, (output & 0xffff_ffff) as usize);
+
+        // the memory may have grown during the call
+        let view = memory.view::<u8>();
+        let output = view
+            .get(output_ptr..output_ptr + output_len)
+            .ok_or_else(|| {
+                ReplibyteError::Config("Wasm plugin returned a value out of its memory".to_string())
+            })?
+            .iter()
+            .map(|cell| cell.get())
+            .collect::<Vec<u8>>();
+
+        if let Ok(dealloc) = instance
+            .exports
+            .get_native_function::<(i32, i32), ()>("replibyte_dealloc")
+        {
+            dealloc.call(output_ptr as i32, output_len as i32)?;
+        }
+
+        Ok(serde_json::from_slice(output.as_slice())?)
+    }
     fn call_wasm_module(&self, value: &str) -> Result<String, WasmError> {
         // Create a new wasm instance from the wasm configuration
         let instance = Instance::new(&self.wasm_config.module, &self.wasm_config.import_object)?;
@@ -71,9 +156,8 @@ impl CustomWasmTransformer {
         {
             let mut state = self.wasm_config.wasi_env.state();
             let wasi_stdin = state.fs.stdin_mut()?.as_mut().ok_or_else(|| {
-                io::Error::new(
-                    ErrorKind::Other,
-                    "Couldn't get the VirtualFile object at stdin mutably",
+                ReplibyteError::Config(
+                    "Couldn't get the VirtualFile object at stdin mutably".to_string(),
                 )
             })?;
             // Write to the stdin pipe
@@ -86,9 +170,8 @@ impl CustomWasmTransformer {
 
         let mut state = self.wasm_config.wasi_env.state();
         let wasi_stdout = state.fs.stdout_mut()?.as_mut().ok_or_else(|| {
-            io::Error::new(
-                ErrorKind::Other,
-                "Couldn't get the VirtualFile object at stdout mutably",
+            ReplibyteError::Config(
+                "Couldn't get the VirtualFile object at stdout mutably".to_string(),
             )
         })?;
         // Read from the stdout pipe
@@ -99,6 +182,67 @@ impl CustomWasmTransformer {
     }
 }
 
+fn new_plugin_instance(
+    module: &Module,
+    import_object: &ImportObject,
+) -> Result<Instance, WasmError> {
+    let instance = Instance::new(module, import_object)?;
+
+    let version = match instance
+        .exports
+        .get_native_function::<(), i32>("replibyte_abi_version")
+    {
+        Ok(abi_version) => abi_version.call()?,
+        Err(_) => PLUGIN_ABI_VERSION,
+    };
+    if version != PLUGIN_ABI_VERSION {
+        return Err(Box::new(ReplibyteError::Config(format!(
+            "Wasm plugin ABI version {} is not supported, the supported version is {}",
+            version, PLUGIN_ABI_VERSION
+        ))));
+    }
+
+    // the WASI reactors initialize their runtime once
+    if let Ok(initialize) = instance.exports.get_function("_initialize") {
+        initialize.call(&[])?;
+    }
+
+    Ok(instance)
+}
+
+/// read the wasm file of a transformer from its path or url, and check its checksum
+pub fn read_wasm_file(options: &CustomWasmTransformerOptions) -> Result<Vec<u8>, WasmError> {
+    let wasm_bytes = if options.path.starts_with("http://") || options.path.starts_with("https://")
+    {
+        let response = Client::builder()
+            .timeout(DOWNLOAD_TIMEOUT)
+            .build()?
+            .get(options.path.as_str())
+            .send()?
+            .error_for_status()?;
+        response.bytes()?.to_vec()
+    } else {
+        std::fs::read(options.path.as_str())?
+    };
+
+    if let Some(sha256) = &options.sha256 {
+        let checksum: String = digest::digest(&digest::SHA256, wasm_bytes.as_slice())
+            .as_ref()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+
+        if !checksum.eq_ignore_ascii_case(sha256.trim()) {
+            return Err(Box::new(ReplibyteError::Config(format!(
+                "the SHA-256 checksum of '{}' is {}, {} is expected",
+                options.path, checksum, sha256
+            ))));
+        }
+    }
+
+    Ok(wasm_bytes)
+}
+
 impl Default for CustomWasmTransformer {
     fn default() -> Self {
         CustomWasmTransformer {
@@ -121,6 +265,7 @@ impl Default for CustomWasmTransformer {
                     ],
                 )
                 .unwrap(),
+                plugin: None,
             },
         }
     }
@@ -148,6 +293,26 @@ impl Transformer for CustomWasmTransformer {
     }
 
     fn transform(&self, column: Column) -> Column {
+        if let Some(instance) = &self.wasm_config.plugin {
+            let column_name = column.name().to_string();
+            let is_char = matches!(column, Column::CharValue(_, _));
+
+            let value = self
+                .call_wasm_plugin(instance, column)
+                .expect("Wasm plugin call failed");
+
+            return match to_column(column_name.as_str(), value) {
+                // the chars are strings in JSON
+                Column::StringValue(column_name, value)
+                    if is_char && value.chars().count() == 1 =>
+                {
+                    // unwrap is safe: the string has one char
+                    Column::CharValue(column_name, value.chars().next().unwrap())
+                }
+                column => column,
+            };
+        }
+
         match column {
             Column::StringValue(column_name, value) => Column::StringValue(
                 column_name,
@@ -189,16 +354,107 @@ impl Transformer for CustomWasmTransformer {
 
 #[cfg(test)]
 mod tests {
+    use serde_json::{json, Value};
+
     use crate::{
-        transformer::{custom_wasm::CustomWasmTransformer, Transformer},
+        transformer::{
+            custom_wasm::{read_wasm_file, CustomWasmTransformer, CustomWasmTransformerOptions},
+            Transformer,
+        },
         types::Column,
     };
 
+    /// plugin returning its input, or the `null` stored at offset 16 when `RETURN_NULL` is 1
+    const PLUGIN_WAT: &str = r#"
+        (module
+          ;; an even number of signatures keeps the imports of wasmer aligned in the debug builds
+          (type (func))
+          (memory (export "memory") 1)
+          (data (i32.const 16) "null")
+          (global $next (mut i32) (i32.const 1024))
+          (func (export "replibyte_abi_version") (result i32) (i32.const ABI_VERSION))
+          (func (export "replibyte_alloc") (param $size i32) (result i32)
+            (local $ptr i32)
+            (local.set $ptr (global.get $next))
+            (global.set $next (i32.add (global.get $next) (local.get $size)))
+            (local.get $ptr))
+          (func (export "replibyte_transform") (param $ptr i32) (param $len i32) (result i64)
+            (if (result i64) (i32.eq (i32.const RETURN_NULL) (i32.const 1))
+              (then (i64.or (i64.shl (i64.const 16) (i64.const 32)) (i64.const 4)))
+              (else (i64.or
+                (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+                (i64.extend_i32_u (local.get $len)))))))
+    "#;
+
     fn get_wasm_transformer(path: &str) -> CustomWasmTransformer {
         let wasm_bytes = std::fs::read(path).unwrap();
         CustomWasmTransformer::new("test", "users", "number", wasm_bytes).unwrap()
     }
 
+    fn get_wasm_plugin(
+        abi_version: i32,
+        return_null: bool,
+    ) -> Result<CustomWasmTransformer, super::WasmError> {
+        let wat = PLUGIN_WAT
+            .replace("ABI_VERSION", abi_version.to_string().as_str())
+            .replace("RETURN_NULL", if return_null { "1" } else { "0" });
+
+        CustomWasmTransformer::new("test", "users", "email", wat.into_bytes())
+    }
+
+    #[test]
+    fn transform_wasm_plugin() {
+        let transformer = get_wasm_plugin(1, false).unwrap();
+
+        for (column, value) in [
+            (
+                Column::StringValue("email".to_string(), "john@doe.com".to_string()),
+                json!("john@doe.com"),
+            ),
+            (Column::NumberValue("email".to_string(), 42), json!(42)),
+            (Column::None("email".to_string()), Value::Null),
+        ] {
+            // the plugin returns its input, a JSON object
+            let transformed_column = transformer.transform(column);
+            let input: Value =
+                serde_json::from_str(transformed_column.string_value().unwrap()).unwrap();
+
+            assert_eq!(
+                input,
+                json!({"database": "test", "table": "users", "column": "email", "value": value})
+            );
+        }
+
+        let transformer = get_wasm_plugin(1, true).unwrap();
+        let column = Column::StringValue("email".to_string(), "john@doe.com".to_string());
+        assert!(matches!(transformer.transform(column), Column::None(_)));
+    }
+
+    #[test]
+    fn wasm_plugin_with_other_abi_version() {
+        assert!(get_wasm_plugin(2, false).is_err());
+    }
+
+    #[test]
+    fn read_wasm_file_with_checksum() {
+        let mut options = CustomWasmTransformerOptions {
+            path: "../examples/wasm/wasm-transformer-reverse-string.wasm".to_string(),
+            sha256: None,
+        };
+        let wasm_bytes = read_wasm_file(&options).unwrap();
+
+        let checksum: String = ring::digest::digest(&ring::digest::SHA256, wasm_bytes.as_slice())
+            .as_ref()
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect();
+        options.sha256 = Some(checksum);
+        assert_eq!(read_wasm_file(&options).unwrap(), wasm_bytes);
+
+        options.sha256 = Some("0".repeat(64));
+        assert!(read_wasm_file(&options).is_err());
+    }
+
     #[test]
     fn transform_wasm_reverse_string() {
         let transformer =
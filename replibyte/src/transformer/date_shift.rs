@@ -0,0 +1,244 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::transformer::Transformer;
+use crate::types::Column;
+
+const SEED_CONTEXT: &str = "replibyte date-shift transformer seed";
+
+const DEFAULT_MAX_DAYS: u32 = 365;
+
+/// This struct is dedicated to shifting the dates of a subject (e.g. a user) by a number of days derived from the subject.
+/// All the dates of a subject are shifted by the same number of days, so the intervals between them are kept.
+pub struct DateShiftTransformer {
+    database_name: String,
+    table_name: String,
+    column_name: String,
+    options: DateShiftTransformerOptions,
+    key: [u8; 32],
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct DateShiftTransformerOptions {
+    /// column of the row identifying the subject, e.g. `user_id`
+    pub subject_column: String,
+    /// the dates are shifted by 1 to `max_days` days, in the past or in the future (365 by default)
+    pub max_days: Option<u32>,
+    /// secret mixed into the offsets, without it the original dates can be computed back
+    pub seed: Option<String>,
+}
+
+impl DateShiftTransformer {
+    pub fn new<S>(
+        database_name: S,
+        table_name: S,
+        column_name: S,
+        options: DateShiftTransformerOptions,
+    ) -> Self
+    where
+        S: Into<String>,
+    {
+        let key = blake3::derive_key(
+            SEED_CONTEXT,
+            options.seed.as_deref().unwrap_or_default().as_bytes(),
+        );
+
+        DateShiftTransformer {
+            database_name: database_name.into(),
+            table_name: table_name.into(),
+            column_name: column_name.into(),
+            options,
+            key,
+        }
+    }
+
+    /// the number of days the dates of the subject of a row are shifted by, never 0
+    fn offset_days(&self, row: &[Column]) -> i64 {
+        let subject = row
+            .iter()
+            .find(|column| column.name() == self.options.subject_column)
+            .map(subject_value)
+            .unwrap_or_default();
+
+        let hash = blake3::keyed_hash(&self.key, subject.as_bytes());
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&hash.as_bytes()[..8]);
+        let hash = u64::from_le_bytes(bytes);
+
+        let max_days = self.options.max_days.unwrap_or(DEFAULT_MAX_DAYS).max(1) as u64;
+        let days = (hash >> 1) % max_days + 1;
+
+        if hash & 1 == 0 {
+            days as i64
+        } else {
+            -(days as i64)
+        }
+    }
+}
+
+fn subject_value(column: &Column) -> String {
+    match column {
+        Column::NumberValue(_, value) => value.to_string(),
+        Column::FloatNumberValue(_, value) => value.to_string(),
+        Column::StringValue(_, value) => value.clone(),
+        Column::CharValue(_, value) => value.to_string(),
+        Column::BooleanValue(_, value) => value.to_string(),
+        Column::None(_) => String::new(),
+    }
+}
+
+/// shift the `YYYY-MM-DD` date at the start of a value, the rest of the value (e.g. the time) is kept
+fn shift_date(value: &str, days: i64) -> Option<String> {
+    let (date, rest) = (value.get(..10)?, &value[10..]);
+    if !(rest.is_empty() || rest.starts_with(' ') || rest.starts_with('T')) {
+        return None;
+    }
+
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    let new_date = date.checked_add_signed(Duration::days(days))?;
+
+    if !(1..=9999).contains(&new_date.year()) {
+        return None;
+    }
+
+    Some(format!("{}{}", new_date.format("%Y-%m-%d"), rest))
+}
+
+impl Default for DateShiftTransformer {
+    fn default() -> Self {
+        DateShiftTransformer::new("", "", "", DateShiftTransformerOptions::default())
+    }
+}
+
+impl Transformer for DateShiftTransformer {
+    fn id(&self) -> &str {
+        "date-shift"
+    }
+
+    fn description(&self) -> &str {
+        "Shift the dates of a subject by the same random number of days (string only). [2022-05-01 10:20:30]->[2022-02-13 10:20:30]"
+    }
+
+    fn database_name(&self) -> &str {
+        self.database_name.as_str()
+    }
+
+    fn table_name(&self) -> &str {
+        self.table_name.as_str()
+    }
+
+    fn column_name(&self) -> &str {
+        self.column_name.as_str()
+    }
+
+    fn transform_row(&self, column: Column, row: &[Column]) -> Column {
+        match column {
+            Column::StringValue(column_name, value) => {
+                match shift_date(value.as_str(), self.offset_days(row)) {
+                    Some(new_value) => Column::StringValue(column_name, new_value),
+                    None => Column::StringValue(column_name, value),
+                }
+            }
+            column => column,
+        }
+    }
+
+    fn transform(&self, column: Column) -> Column {
+        self.transform_row(column, &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::{transformer::Transformer, types::Column};
+
+    use super::{DateShiftTransformer, DateShiftTransformerOptions};
+
+    #[test]
+    fn transform_date_shift_keeps_intervals() {
+        let transformer = get_transformer(Some(30));
+
+        let admitted_at = transform(&transformer, 1, "2022-05-01 10:20:30.123+02");
+        let discharged_at = transform(&transformer, 1, "2022-05-11");
+
+        assert_ne!(admitted_at, "2022-05-01 10:20:30.123+02");
+        assert!(admitted_at.ends_with(" 10:20:30.123+02"));
+        assert_eq!(days_between(&admitted_at, &discharged_at), 10);
+
+        let offset = days_between("2022-05-01", &admitted_at);
+        assert!(offset != 0 && offset.abs() <= 30);
+
+        // the same subject always gets the same offset
+        assert_eq!(
+            transform(&transformer, 1, "2022-05-01 10:20:30.123+02"),
+            admitted_at
+        );
+    }
+
+    #[test]
+    fn transform_date_shift_per_subject() {
+        let transformer = get_transformer(None);
+
+        let offsets = (1..=10)
+            .map(|user_id| {
+                days_between(
+                    "2022-05-01",
+                    &transform(&transformer, user_id, "2022-05-01"),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert!(offsets
+            .iter()
+            .all(|offset| *offset != 0 && offset.abs() <= 365));
+        assert!(offsets.iter().any(|offset| *offset != offsets[0]));
+    }
+
+    #[test]
+    fn transform_date_shift_with_other_values() {
+        let transformer = get_transformer(None);
+
+        for value in ["", "not a date", "2022-13-01", "2022-05", "2022-05-0123"] {
+            assert_eq!(transform(&transformer, 1, value), value);
+        }
+
+        let column = Column::NumberValue("created_at".to_string(), 1651400430);
+        let transformed_column = transformer.transform(column);
+        assert_eq!(transformed_column.number_value(), Some(&1651400430));
+    }
+
+    fn transform(transformer: &DateShiftTransformer, user_id: i128, value: &str) -> String {
+        let column = Column::StringValue("created_at".to_string(), value.to_string());
+        let row = vec![
+            Column::NumberValue("user_id".to_string(), user_id),
+            column.clone(),
+        ];
+
+        transformer
+            .transform_row(column, &row)
+            .string_value()
+            .unwrap()
+            .to_string()
+    }
+
+    fn days_between(from: &str, to: &str) -> i64 {
+        let date = |value: &str| NaiveDate::parse_from_str(&value[..10], "%Y-%m-%d").unwrap();
+        (date(to) - date(from)).num_days()
+    }
+
+    fn get_transformer(max_days: Option<u32>) -> DateShiftTransformer {
+        DateShiftTransformer::new(
+            "github",
+            "events",
+            "created_at",
+            DateShiftTransformerOptions {
+                subject_column: "user_id".to_string(),
+                max_days,
+                seed: Some("secret".to_string()),
+            },
+        )
+    }
+}
@@ -0,0 +1,256 @@
+use fake::faker::address::raw::{BuildingNumber, CityName, CountryName, StreetName, ZipCode};
+use fake::faker::company::raw::CompanyName;
+use fake::faker::internet::raw::{SafeEmail, Username};
+use fake::faker::name::raw::{FirstName, LastName, Name};
+use fake::faker::phone_number::raw::PhoneNumber;
+use fake::locales::EN;
+use fake::Fake;
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::transformer::iban::random_iban;
+use crate::transformer::Transformer;
+use crate::types::Column;
+
+const SEED_CONTEXT: &str = "replibyte faker transformer seed";
+
+/// This struct is dedicated to replacing a string by fake data generated from a seed derived from the original value.
+/// The same value is always replaced by the same fake value, so the joins between tables are kept.
+pub struct FakerTransformer {
+    database_name: String,
+    table_name: String,
+    column_name: String,
+    options: FakerTransformerOptions,
+    key: [u8; 32],
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct FakerTransformerOptions {
+    pub kind: FakerKind,
+    /// secret mixed into the seeds, without it the original values can be guessed from the fake ones
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FakerKind {
+    #[default]
+    FirstName,
+    LastName,
+    Name,
+    Email,
+    Username,
+    PhoneNumber,
+    Address,
+    City,
+    ZipCode,
+    Country,
+    Company,
+    Iban,
+}
+
+impl FakerTransformer {
+    pub fn new<S>(
+        database_name: S,
+        table_name: S,
+        column_name: S,
+        options: FakerTransformerOptions,
+    ) -> Self
+    where
+        S: Into<String>,
+    {
+        let key = blake3::derive_key(
+            SEED_CONTEXT,
+            options.seed.as_deref().unwrap_or_default().as_bytes(),
+        );
+
+        FakerTransformer {
+            database_name: database_name.into(),
+            table_name: table_name.into(),
+            column_name: column_name.into(),
+            options,
+            key,
+        }
+    }
+
+    fn fake(&self, value: &str) -> String {
+        let seed = blake3::keyed_hash(&self.key, value.as_bytes());
+        let rng = &mut ChaCha8Rng::from_seed(*seed.as_bytes());
+
+        match self.options.kind {
+            FakerKind::FirstName => FirstName(EN).fake_with_rng(rng),
+            FakerKind::LastName => LastName(EN).fake_with_rng(rng),
+            FakerKind::Name => Name(EN).fake_with_rng(rng),
+            FakerKind::Email => SafeEmail(EN).fake_with_rng(rng),
+            FakerKind::Username => Username(EN).fake_with_rng(rng),
+            FakerKind::PhoneNumber => PhoneNumber(EN).fake_with_rng(rng),
+            FakerKind::Address => format!(
+                "{} {}, {} {}",
+                BuildingNumber(EN).fake_with_rng::<String, _>(rng),
+                StreetName(EN).fake_with_rng::<String, _>(rng),
+                CityName(EN).fake_with_rng::<String, _>(rng),
+                ZipCode(EN).fake_with_rng::<String, _>(rng),
+            ),
+            FakerKind::City => CityName(EN).fake_with_rng(rng),
+            FakerKind::ZipCode => ZipCode(EN).fake_with_rng(rng),
+            FakerKind::Country => CountryName(EN).fake_with_rng(rng),
+            FakerKind::Company => CompanyName(EN).fake_with_rng(rng),
+            FakerKind::Iban => random_iban(rng),
+        }
+    }
+}
+
+impl Default for FakerTransformer {
+    fn default() -> Self {
+        FakerTransformer::new("", "", "", FakerTransformerOptions::default())
+    }
+}
+
+impl Transformer for FakerTransformer {
+    fn id(&self) -> &str {
+        "faker"
+    }
+
+    fn description(&self) -> &str {
+        "Generate fake data of a kind, the same value always gets the same fake data (string only). [Lucas]->[Georges]"
+    }
+
+    fn database_name(&self) -> &str {
+        self.database_name.as_str()
+    }
+
+    fn table_name(&self) -> &str {
+        self.table_name.as_str()
+    }
+
+    fn column_name(&self) -> &str {
+        self.column_name.as_str()
+    }
+
+    fn transform(&self, column: Column) -> Column {
+        match column {
+            Column::NumberValue(column_name, value) => Column::NumberValue(column_name, value),
+            Column::FloatNumberValue(column_name, value) => {
+                Column::FloatNumberValue(column_name, value)
+            }
+            Column::StringValue(column_name, value) => {
+                let new_value = if value == "" {
+                    "".to_string()
+                } else {
+                    self.fake(value.as_str())
+                };
+
+                Column::StringValue(column_name, new_value)
+            }
+            Column::CharValue(column_name, value) => Column::CharValue(column_name, value),
+            Column::BooleanValue(column_name, value) => Column::BooleanValue(column_name, value),
+            Column::None(column_name) => Column::None(column_name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{transformer::Transformer, types::Column};
+
+    use super::{FakerKind, FakerTransformer, FakerTransformerOptions};
+
+    #[test]
+    fn transform_faker_with_number_value() {
+        let expected_value = 34;
+        let transformer = get_transformer(FakerKind::FirstName, None);
+        let column = Column::NumberValue("first_name".to_string(), expected_value);
+        let transformed_column = transformer.transform(column);
+        let transformed_value = transformed_column.number_value().unwrap();
+
+        assert_eq!(transformed_value.to_owned(), expected_value)
+    }
+
+    #[test]
+    fn transform_faker_with_empty_string_value() {
+        let expected_value = "";
+        let transformer = get_transformer(FakerKind::Email, None);
+        let column = Column::StringValue("email".to_string(), expected_value.to_string());
+        let transformed_column = transformer.transform(column);
+        let transformed_value = transformed_column.string_value().unwrap();
+
+        assert_eq!(transformed_value, expected_value)
+    }
+
+    #[test]
+    fn transform_faker_is_deterministic() {
+        let transform = |transformer: &FakerTransformer, value: &str| {
+            let column = Column::StringValue("email".to_string(), value.to_string());
+            transformer
+                .transform(column)
+                .string_value()
+                .unwrap()
+                .to_string()
+        };
+
+        let transformer = get_transformer(FakerKind::Email, None);
+        let email = transform(&transformer, "lucas@company.com");
+        assert!(email.contains('@'));
+        assert_ne!(email, "lucas@company.com");
+
+        // the same value gets the same fake value in another table
+        let other_table = FakerTransformer::new(
+            "github",
+            "orders",
+            "customer_email",
+            FakerTransformerOptions {
+                kind: FakerKind::Email,
+                seed: None,
+            },
+        );
+        assert_eq!(transform(&other_table, "lucas@company.com"), email);
+        assert_ne!(transform(&transformer, "georges@company.com"), email);
+
+        let transformer = get_transformer(FakerKind::Email, Some("secret"));
+        assert_ne!(transform(&transformer, "lucas@company.com"), email);
+    }
+
+    #[test]
+    fn transform_faker_iban() {
+        let transformer = get_transformer(FakerKind::Iban, None);
+        let column = Column::StringValue(
+            "iban".to_string(),
+            "FR7630006000011234567890189".to_string(),
+        );
+        let iban = transformer
+            .transform(column)
+            .string_value()
+            .unwrap()
+            .to_string();
+
+        assert_eq!(iban.len(), 22);
+        assert!(iban.starts_with("DE"));
+
+        // ISO 13616: the country code and check digits moved to the end give 1 modulo 97
+        let rearranged = format!("{}{}", &iban[4..], &iban[..4]);
+        let remainder = rearranged.chars().fold(0u32, |remainder, c| {
+            let value = c.to_digit(36).unwrap();
+            if value < 10 {
+                (remainder * 10 + value) % 97
+            } else {
+                (remainder * 100 + value) % 97
+            }
+        });
+        assert_eq!(remainder, 1);
+    }
+
+    fn get_transformer(kind: FakerKind, seed: Option<&str>) -> FakerTransformer {
+        FakerTransformer::new(
+            "github",
+            "users",
+            "email",
+            FakerTransformerOptions {
+                kind,
+                seed: seed.map(|seed| seed.to_string()),
+            },
+        )
+    }
+}
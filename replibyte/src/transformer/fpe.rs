@@ -0,0 +1,296 @@
+use aes::Aes256;
+use fpe::ff1::{FlexibleNumeralString, FF1};
+use serde::{Deserialize, Serialize};
+
+use crate::transformer::Transformer;
+use crate::types::Column;
+
+const KEY_CONTEXT: &str = "replibyte fpe transformer key";
+
+/// the sequences of characters with fewer possible values are not encrypted (NIST SP 800-38G)
+const MIN_DOMAIN_SIZE: u128 = 1_000_000;
+
+/// the characters encrypted together, each one is replaced by a character of the same range
+const ALPHABETS: [(u8, u8); 3] = [(b'0', b'9'), (b'a', b'z'), (b'A', b'Z')];
+
+/// This struct is dedicated to encrypting a value with FF1 while keeping its format:
+/// digits stay digits, letters stay letters of the same case, the other characters and the length are kept.
+/// The value can be decrypted with the same key.
+pub struct FpeTransformer {
+    database_name: String,
+    table_name: String,
+    column_name: String,
+    digits: FF1<Aes256>,
+    letters: FF1<Aes256>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct FpeTransformerOptions {
+    pub key: String,
+}
+
+impl FpeTransformer {
+    pub fn new<S>(
+        database_name: S,
+        table_name: S,
+        column_name: S,
+        options: FpeTransformerOptions,
+    ) -> Self
+    where
+        S: Into<String>,
+    {
+        let key = blake3::derive_key(KEY_CONTEXT, options.key.as_bytes());
+
+        FpeTransformer {
+            database_name: database_name.into(),
+            table_name: table_name.into(),
+            column_name: column_name.into(),
+            // unwrap is safe: the radixes are valid
+            digits: FF1::new(&key, 10).unwrap(),
+            letters: FF1::new(&key, 26).unwrap(),
+        }
+    }
+
+    fn cipher(&self, column: Column, decrypt: bool) -> Column {
+        match column {
+            Column::NumberValue(column_name, value) => {
+                Column::NumberValue(column_name, self.cipher_number(value, decrypt))
+            }
+            Column::StringValue(column_name, value) => {
+                Column::StringValue(column_name, self.cipher_string(value.as_str(), decrypt))
+            }
+            column => column,
+        }
+    }
+
+    fn cipher_numerals(
+        &self,
+        numerals: Vec<u16>,
+        radix: u16,
+        tweak: &[u8],
+        decrypt: bool,
+    ) -> Vec<u16> {
+        let ff1 = if radix == 10 {
+            &self.digits
+        } else {
+            &self.letters
+        };
+
+        let numerals = FlexibleNumeralString::from(numerals);
+        // unwrap is safe: the numerals are lower than the radix
+        let numerals = if decrypt {
+            ff1.decrypt(tweak, &numerals).unwrap()
+        } else {
+            ff1.encrypt(tweak, &numerals).unwrap()
+        };
+
+        Vec::from(numerals)
+    }
+
+    fn cipher_string(&self, value: &str, decrypt: bool) -> String {
+        let mut bytes = value.as_bytes().to_vec();
+
+        for (first, last) in ALPHABETS {
+            let radix = (last - first + 1) as u16;
+            let positions = bytes
+                .iter()
+                .enumerate()
+                .filter(|(_, byte)| (first..=last).contains(*byte))
+                .map(|(position, _)| position)
+                .collect::<Vec<_>>();
+
+            if !is_large_enough(radix, positions.len()) {
+                continue;
+            }
+
+            let numerals = positions
+                .iter()
+                .map(|position| (bytes[*position] - first) as u16)
+                .collect::<Vec<_>>();
+
+            let numerals = self.cipher_numerals(numerals, radix, &[first], decrypt);
+
+            for (position, numeral) in positions.into_iter().zip(numerals) {
+                bytes[position] = first + numeral as u8;
+            }
+        }
+
+        // only ASCII characters are replaced by ASCII characters, the string stays valid UTF-8
+        String::from_utf8(bytes).unwrap()
+    }
+
+    fn cipher_number(&self, value: i128, decrypt: bool) -> i128 {
+        let digits = value.unsigned_abs().to_string();
+
+        // the digits of the result must fit an i128
+        if !is_large_enough(10, digits.len()) || digits.len() >= 39 {
+            return value;
+        }
+
+        let mut numerals = digits
+            .bytes()
+            .map(|b| (b - b'0') as u16)
+            .collect::<Vec<_>>();
+
+        // a leading zero would change the number of digits, the result is encrypted again until it has none (cycle walking)
+        loop {
+            numerals = self.cipher_numerals(numerals, 10, b"number", decrypt);
+            if numerals[0] != 0 {
+                break;
+            }
+        }
+
+        let number = numerals
+            .into_iter()
+            .fold(0i128, |number, numeral| number * 10 + numeral as i128);
+
+        value.signum() * number
+    }
+}
+
+/// check that a sequence has enough possible values to be encrypted
+fn is_large_enough(radix: u16, length: usize) -> bool {
+    match (radix as u128).checked_pow(length as u32) {
+        Some(domain_size) => domain_size >= MIN_DOMAIN_SIZE,
+        None => true,
+    }
+}
+
+impl Default for FpeTransformer {
+    fn default() -> Self {
+        FpeTransformer::new("", "", "", FpeTransformerOptions::default())
+    }
+}
+
+impl Transformer for FpeTransformer {
+    fn id(&self) -> &str {
+        "fpe"
+    }
+
+    fn description(&self) -> &str {
+        "Encrypt the value while keeping its format, it can be decrypted with the key. [4242 4242 4242 4242]->[9175 0283 6614 5390]"
+    }
+
+    fn database_name(&self) -> &str {
+        self.database_name.as_str()
+    }
+
+    fn table_name(&self) -> &str {
+        self.table_name.as_str()
+    }
+
+    fn column_name(&self) -> &str {
+        self.column_name.as_str()
+    }
+
+    fn transform(&self, column: Column) -> Column {
+        self.cipher(column, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{transformer::Transformer, types::Column};
+
+    use super::{FpeTransformer, FpeTransformerOptions};
+
+    #[test]
+    fn transform_fpe_with_string_value() {
+        let transformer = get_transformer("secret");
+        let column = Column::StringValue("card".to_string(), "4242 4242 4242 4242".to_string());
+        let transformed_column = transformer.transform(column.clone());
+        let transformed_value = transformed_column.string_value().unwrap().to_string();
+
+        assert_ne!(transformed_value, "4242 4242 4242 4242");
+        assert_eq!(transformed_value.len(), 19);
+        for (original, transformed) in "4242 4242 4242 4242".chars().zip(transformed_value.chars())
+        {
+            assert_eq!(original.is_ascii_digit(), transformed.is_ascii_digit());
+            if !original.is_ascii_digit() {
+                assert_eq!(original, transformed);
+            }
+        }
+
+        // the same value is always encrypted the same way, and can be decrypted with the key
+        assert_eq!(
+            transformer
+                .transform(column.clone())
+                .string_value()
+                .unwrap(),
+            transformed_value
+        );
+        assert_ne!(
+            get_transformer("other secret")
+                .transform(column)
+                .string_value()
+                .unwrap(),
+            transformed_value
+        );
+        let reversed_column = transformer.cipher(transformed_column, true);
+        assert_eq!(
+            reversed_column.string_value().unwrap(),
+            "4242 4242 4242 4242"
+        );
+    }
+
+    #[test]
+    fn transform_fpe_with_letters() {
+        let transformer = get_transformer("secret");
+        let column = Column::StringValue("id".to_string(), "AB 123456 cdefgh-é".to_string());
+        let transformed_column = transformer.transform(column);
+        let transformed_value = transformed_column.string_value().unwrap().to_string();
+
+        // too few uppercase letters to be encrypted
+        assert!(transformed_value.starts_with("AB "));
+        assert!(transformed_value.ends_with("-é"));
+        assert_ne!(&transformed_value[3..9], "123456");
+        assert!(transformed_value[3..9].chars().all(|c| c.is_ascii_digit()));
+        assert!(transformed_value[10..16]
+            .chars()
+            .all(|c| c.is_ascii_lowercase()));
+
+        assert_eq!(
+            transformer
+                .cipher(transformed_column, true)
+                .string_value()
+                .unwrap(),
+            "AB 123456 cdefgh-é"
+        );
+    }
+
+    #[test]
+    fn transform_fpe_with_number_value() {
+        let transformer = get_transformer("secret");
+
+        for value in [1234567890i128, -100000, 123] {
+            let column = Column::NumberValue("national_id".to_string(), value);
+            let transformed_column = transformer.transform(column);
+            let transformed_value = *transformed_column.number_value().unwrap();
+
+            assert_eq!(transformed_value.signum(), value.signum());
+            assert_eq!(
+                transformed_value.abs().to_string().len(),
+                value.abs().to_string().len()
+            );
+            assert_eq!(
+                *transformer
+                    .cipher(transformed_column, true)
+                    .number_value()
+                    .unwrap(),
+                value
+            );
+        }
+    }
+
+    fn get_transformer(key: &str) -> FpeTransformer {
+        FpeTransformer::new(
+            "github",
+            "users",
+            "card",
+            FpeTransformerOptions {
+                key: key.to_string(),
+            },
+        )
+    }
+}
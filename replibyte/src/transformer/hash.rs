@@ -0,0 +1,231 @@
+use ring::{digest, hmac};
+use serde::{Deserialize, Serialize};
+
+use crate::transformer::Transformer;
+use crate::types::Column;
+
+/// This struct is dedicated to replacing a value by its SHA-256 hash, or its HMAC-SHA256 with a salt.
+/// The same value is always replaced by the same hash, so the joins between tables are kept.
+pub struct HashTransformer {
+    database_name: String,
+    table_name: String,
+    column_name: String,
+    options: HashTransformerOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct HashTransformerOptions {
+    /// secret key of the HMAC, without it the original values can be guessed from their hash
+    pub salt: Option<String>,
+    #[serde(default)]
+    pub encoding: HashEncoding,
+    /// number of characters (or digits for numbers) the hash is truncated to
+    pub length: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum HashEncoding {
+    #[default]
+    Hex,
+    Base64,
+}
+
+impl HashTransformer {
+    pub fn new<S>(
+        database_name: S,
+        table_name: S,
+        column_name: S,
+        options: HashTransformerOptions,
+    ) -> Self
+    where
+        S: Into<String>,
+    {
+        HashTransformer {
+            database_name: database_name.into(),
+            table_name: table_name.into(),
+            column_name: column_name.into(),
+            options,
+        }
+    }
+
+    fn hash(&self, value: &[u8]) -> Vec<u8> {
+        match &self.options.salt {
+            Some(salt) => {
+                let key = hmac::Key::new(hmac::HMAC_SHA256, salt.as_bytes());
+                hmac::sign(&key, value).as_ref().to_vec()
+            }
+            None => digest::digest(&digest::SHA256, value).as_ref().to_vec(),
+        }
+    }
+
+    fn hash_string(&self, value: &str) -> String {
+        let hash = self.hash(value.as_bytes());
+
+        let mut hash = match self.options.encoding {
+            HashEncoding::Hex => hash.iter().map(|byte| format!("{:02x}", byte)).collect(),
+            HashEncoding::Base64 => base64::encode(hash),
+        };
+
+        if let Some(length) = self.options.length {
+            hash.truncate(length);
+        }
+
+        hash
+    }
+
+    fn hash_number(&self, value: i128) -> i128 {
+        let hash = self.hash(value.to_string().as_bytes());
+
+        // the first 63 bits of the hash, to be a positive number in any integer column
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&hash[..8]);
+        let number = (u64::from_be_bytes(bytes) >> 1) as i128;
+
+        match self.options.length {
+            Some(length) if length < 19 => number % 10i128.pow(length as u32),
+            _ => number,
+        }
+    }
+}
+
+impl Default for HashTransformer {
+    fn default() -> Self {
+        HashTransformer {
+            database_name: String::default(),
+            table_name: String::default(),
+            column_name: String::default(),
+            options: HashTransformerOptions::default(),
+        }
+    }
+}
+
+impl Transformer for HashTransformer {
+    fn id(&self) -> &str {
+        "hash"
+    }
+
+    fn description(&self) -> &str {
+        "Replace the value by its SHA-256 or HMAC-SHA256 hash (string and number only). [abc]->[ba7816bf8f01cfea...]"
+    }
+
+    fn database_name(&self) -> &str {
+        self.database_name.as_str()
+    }
+
+    fn table_name(&self) -> &str {
+        self.table_name.as_str()
+    }
+
+    fn column_name(&self) -> &str {
+        self.column_name.as_str()
+    }
+
+    fn transform(&self, column: Column) -> Column {
+        match column {
+            Column::NumberValue(column_name, value) => {
+                Column::NumberValue(column_name, self.hash_number(value))
+            }
+            Column::StringValue(column_name, value) => {
+                let new_value = if value == "" {
+                    "".to_string()
+                } else {
+                    self.hash_string(value.as_str())
+                };
+
+                Column::StringValue(column_name, new_value)
+            }
+            column => column,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{transformer::Transformer, types::Column};
+
+    use super::{HashEncoding, HashTransformer, HashTransformerOptions};
+
+    #[test]
+    fn transform_hash_with_string_value() {
+        let transformer = get_transformer(HashTransformerOptions::default());
+        let column = Column::StringValue("email".to_string(), "abc".to_string());
+        let transformed_column = transformer.transform(column);
+        let transformed_value = transformed_column.string_value().unwrap();
+
+        assert_eq!(
+            transformed_value,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn transform_hash_with_salt() {
+        let transformer = get_transformer(HashTransformerOptions {
+            salt: Some("key".to_string()),
+            encoding: HashEncoding::Hex,
+            length: None,
+        });
+        let column = Column::StringValue(
+            "email".to_string(),
+            "The quick brown fox jumps over the lazy dog".to_string(),
+        );
+        let transformed_column = transformer.transform(column);
+        let transformed_value = transformed_column.string_value().unwrap();
+
+        assert_eq!(
+            transformed_value,
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[test]
+    fn transform_hash_with_encoding_and_length() {
+        let transformer = get_transformer(HashTransformerOptions {
+            salt: None,
+            encoding: HashEncoding::Base64,
+            length: Some(10),
+        });
+        let column = Column::StringValue("email".to_string(), "abc".to_string());
+        let transformed_column = transformer.transform(column);
+        let transformed_value = transformed_column.string_value().unwrap();
+
+        assert_eq!(transformed_value, "ungWv48Bz+");
+    }
+
+    #[test]
+    fn transform_hash_with_number_value() {
+        let transformer = get_transformer(HashTransformerOptions {
+            salt: Some("key".to_string()),
+            encoding: HashEncoding::Hex,
+            length: Some(6),
+        });
+        let column = Column::NumberValue("user_id".to_string(), 42);
+        let transformed_value = *transformer
+            .transform(column.clone())
+            .number_value()
+            .unwrap();
+
+        assert!((0..1_000_000).contains(&transformed_value));
+        assert_ne!(transformed_value, 42);
+        assert_eq!(
+            *transformer.transform(column).number_value().unwrap(),
+            transformed_value
+        );
+    }
+
+    #[test]
+    fn transform_hash_with_empty_string_value() {
+        let transformer = get_transformer(HashTransformerOptions::default());
+        let column = Column::StringValue("email".to_string(), "".to_string());
+        let transformed_column = transformer.transform(column);
+        let transformed_value = transformed_column.string_value().unwrap();
+
+        assert_eq!(transformed_value, "");
+    }
+
+    fn get_transformer(options: HashTransformerOptions) -> HashTransformer {
+        HashTransformer::new("github", "users", "email", options)
+    }
+}
@@ -0,0 +1,212 @@
+use crate::transformer::Transformer;
+use crate::types::Column;
+use rand::Rng;
+
+/// This struct is dedicated to replacing an IBAN string.
+/// The country code, the length, the format (digits and letters) and the spaces are kept, and the check digits are valid.
+pub struct IbanTransformer {
+    database_name: String,
+    table_name: String,
+    column_name: String,
+}
+
+impl IbanTransformer {
+    pub fn new<S>(database_name: S, table_name: S, column_name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        IbanTransformer {
+            database_name: database_name.into(),
+            table_name: table_name.into(),
+            column_name: column_name.into(),
+        }
+    }
+}
+
+impl Default for IbanTransformer {
+    fn default() -> Self {
+        IbanTransformer {
+            database_name: String::default(),
+            table_name: String::default(),
+            column_name: String::default(),
+        }
+    }
+}
+
+impl Transformer for IbanTransformer {
+    fn id(&self) -> &str {
+        "iban"
+    }
+
+    fn description(&self) -> &str {
+        "Generate an IBAN of the same country with valid check digits (string only). [FR76 3000 6000 0112 3456 7890 189]->[FR14 8204 1937 5520 6618 3027 451]"
+    }
+
+    fn database_name(&self) -> &str {
+        self.database_name.as_str()
+    }
+
+    fn table_name(&self) -> &str {
+        self.table_name.as_str()
+    }
+
+    fn column_name(&self) -> &str {
+        self.column_name.as_str()
+    }
+
+    fn transform(&self, column: Column) -> Column {
+        let mut random = rand::thread_rng();
+
+        match column {
+            Column::StringValue(column_name, value) => {
+                let new_value = match replace_iban(&mut random, value.as_str()) {
+                    Some(new_value) => new_value,
+                    None => random_iban(&mut random),
+                };
+
+                Column::StringValue(column_name, new_value)
+            }
+            column => column,
+        }
+    }
+}
+
+/// replace the BBAN of an IBAN and compute its check digits, `None` if the value is not an IBAN
+fn replace_iban<R: Rng>(rng: &mut R, value: &str) -> Option<String> {
+    let iban = value.chars().filter(|c| *c != ' ').collect::<String>();
+
+    if !(15..=34).contains(&iban.len())
+        || !iban.is_ascii()
+        || !iban[..2].chars().all(|c| c.is_ascii_alphabetic())
+        || !iban[2..4].chars().all(|c| c.is_ascii_digit())
+        || !iban[4..].chars().all(|c| c.is_ascii_alphanumeric())
+    {
+        return None;
+    }
+
+    let country_code = iban[..2].to_ascii_uppercase();
+    loop {
+        let bban = iban[4..]
+            .chars()
+            .map(|c| match c {
+                '0'..='9' => char::from(b'0' + rng.gen_range(0..10)),
+                'a'..='z' => char::from(b'a' + rng.gen_range(0..26)),
+                _ => char::from(b'A' + rng.gen_range(0..26)),
+            })
+            .collect::<String>();
+
+        let new_iban = format!(
+            "{}{:02}{}",
+            &iban[..2],
+            check_digits(country_code.as_str(), bban.as_str()),
+            bban
+        );
+
+        if new_iban == iban {
+            continue;
+        }
+
+        // the spaces are put back at their positions
+        let mut new_characters = new_iban.chars();
+        return Some(
+            value
+                .chars()
+                .map(|c| match c {
+                    ' ' => c,
+                    // unwrap is safe: the new IBAN has as many characters as the original one
+                    _ => new_characters.next().unwrap(),
+                })
+                .collect(),
+        );
+    }
+}
+
+/// generate a german IBAN, its check digits are valid
+pub fn random_iban<R: Rng>(rng: &mut R) -> String {
+    let bban = (0..18)
+        .map(|_| char::from(b'0' + rng.gen_range(0..10)))
+        .collect::<String>();
+
+    format!("DE{:02}{}", check_digits("DE", bban.as_str()), bban)
+}
+
+/// the check digits of an IBAN (ISO 13616): they make the BBAN followed by the country code and the check digits equal to 1 modulo 97
+pub fn check_digits(country_code: &str, bban: &str) -> u32 {
+    // the letters are replaced by 2 digits, A = 10, B = 11... Z = 35
+    let remainder = format!("{}{}00", bban, country_code)
+        .chars()
+        .filter_map(|c| c.to_digit(36))
+        .fold(0u32, |remainder, value| {
+            if value < 10 {
+                (remainder * 10 + value) % 97
+            } else {
+                (remainder * 100 + value) % 97
+            }
+        });
+
+    98 - remainder
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{transformer::Transformer, types::Column};
+
+    use super::{check_digits, IbanTransformer};
+
+    #[test]
+    fn transform_iban_keeps_country_and_format() {
+        let transformer = get_transformer();
+
+        for value in [
+            "FR7630006000011234567890189",
+            "FR76 3000 6000 0112 3456 7890 189",
+            "GB29 NWBK 6016 1331 9268 19",
+            "NL91ABNA0417164300",
+        ] {
+            let column = Column::StringValue("iban".to_string(), value.to_string());
+            let transformed_column = transformer.transform(column);
+            let transformed_value = transformed_column.string_value().unwrap();
+
+            assert_ne!(transformed_value, value);
+            assert_eq!(&transformed_value[..2], &value[..2]);
+            assert!(is_iban_valid(transformed_value), "{}", transformed_value);
+            for (original, transformed) in value.chars().zip(transformed_value.chars()) {
+                assert_eq!(original.is_ascii_digit(), transformed.is_ascii_digit());
+                assert_eq!(original == ' ', transformed == ' ');
+            }
+        }
+    }
+
+    #[test]
+    fn transform_iban_with_other_values() {
+        let transformer = get_transformer();
+
+        for value in ["", "not an iban", "FR76"] {
+            let column = Column::StringValue("iban".to_string(), value.to_string());
+            let transformed_column = transformer.transform(column);
+            let transformed_value = transformed_column.string_value().unwrap();
+
+            assert!(transformed_value.starts_with("DE"));
+            assert!(is_iban_valid(transformed_value));
+        }
+
+        let column = Column::NumberValue("iban".to_string(), 42);
+        let transformed_column = transformer.transform(column);
+        assert_eq!(transformed_column.number_value(), Some(&42));
+    }
+
+    #[test]
+    fn iban_check_digits() {
+        assert_eq!(check_digits("GB", "NWBK60161331926819"), 29);
+        assert_eq!(check_digits("FR", "30006000011234567890189"), 76);
+    }
+
+    fn is_iban_valid(value: &str) -> bool {
+        let iban = value.replace(' ', "");
+        check_digits(&iban[..2], &iban[4..]) == iban[2..4].parse::<u32>().unwrap()
+    }
+
+    fn get_transformer() -> IbanTransformer {
+        IbanTransformer::new("github", "users", "iban")
+    }
+}
@@ -0,0 +1,276 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::transformer::Transformer;
+use crate::types::Column;
+
+const SEED_CONTEXT: &str = "replibyte ip address transformer seed";
+
+/// This struct is dedicated to replacing the host part of an IPv4 address, the network prefix can be kept.
+/// The same address is always replaced by the same address, so the addresses can still be counted and joined.
+pub struct Ipv4Transformer {
+    database_name: String,
+    table_name: String,
+    column_name: String,
+    prefix_length: u8,
+    key: [u8; 32],
+}
+
+/// This struct is dedicated to replacing the interface part of an IPv6 address, the network prefix can be kept.
+/// The same address is always replaced by the same address, so the addresses can still be counted and joined.
+pub struct Ipv6Transformer {
+    database_name: String,
+    table_name: String,
+    column_name: String,
+    prefix_length: u8,
+    key: [u8; 32],
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct IpAddressTransformerOptions {
+    /// number of leading bits kept, e.g. 24 keeps the /24 network of an IPv4 address (0 by default)
+    pub prefix_length: Option<u8>,
+    /// secret mixed into the new addresses, without it the original addresses can be guessed
+    pub seed: Option<String>,
+}
+
+impl Ipv4Transformer {
+    pub fn new<S>(
+        database_name: S,
+        table_name: S,
+        column_name: S,
+        options: IpAddressTransformerOptions,
+    ) -> Self
+    where
+        S: Into<String>,
+    {
+        Ipv4Transformer {
+            database_name: database_name.into(),
+            table_name: table_name.into(),
+            column_name: column_name.into(),
+            prefix_length: options.prefix_length.unwrap_or_default().min(32),
+            key: derive_key(&options),
+        }
+    }
+
+    fn replace_address(&self, address: &str) -> Option<String> {
+        let address = address.parse::<Ipv4Addr>().ok()?;
+
+        let mask = u32::MAX
+            .checked_shl(32 - self.prefix_length as u32)
+            .unwrap_or(0);
+        let bits = keyed_bits(&self.key, &address.octets()) as u32;
+        let new_address = (u32::from(address) & mask) | (bits & !mask);
+
+        Some(Ipv4Addr::from(new_address).to_string())
+    }
+}
+
+impl Ipv6Transformer {
+    pub fn new<S>(
+        database_name: S,
+        table_name: S,
+        column_name: S,
+        options: IpAddressTransformerOptions,
+    ) -> Self
+    where
+        S: Into<String>,
+    {
+        Ipv6Transformer {
+            database_name: database_name.into(),
+            table_name: table_name.into(),
+            column_name: column_name.into(),
+            prefix_length: options.prefix_length.unwrap_or_default().min(128),
+            key: derive_key(&options),
+        }
+    }
+
+    fn replace_address(&self, address: &str) -> Option<String> {
+        let address = address.parse::<Ipv6Addr>().ok()?;
+
+        let mask = u128::MAX
+            .checked_shl(128 - self.prefix_length as u32)
+            .unwrap_or(0);
+        let bits = keyed_bits(&self.key, &address.octets());
+        let new_address = (u128::from(address) & mask) | (bits & !mask);
+
+        Some(Ipv6Addr::from(new_address).to_string())
+    }
+}
+
+fn derive_key(options: &IpAddressTransformerOptions) -> [u8; 32] {
+    blake3::derive_key(
+        SEED_CONTEXT,
+        options.seed.as_deref().unwrap_or_default().as_bytes(),
+    )
+}
+
+fn keyed_bits(key: &[u8; 32], value: &[u8]) -> u128 {
+    let hash = blake3::keyed_hash(key, value);
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&hash.as_bytes()[..16]);
+
+    u128::from_be_bytes(bytes)
+}
+
+/// replace the address of a value, the network mask of the CIDR notation (e.g. `/24`) is kept
+fn transform_address<F>(column: Column, replace_address: F) -> Column
+where
+    F: Fn(&str) -> Option<String>,
+{
+    match column {
+        Column::StringValue(column_name, value) => {
+            let (address, mask) = match value.find('/') {
+                Some(position) => value.split_at(position),
+                None => (value.as_str(), ""),
+            };
+
+            match replace_address(address) {
+                Some(new_address) => Column::StringValue(column_name, new_address + mask),
+                None => Column::StringValue(column_name, value),
+            }
+        }
+        column => column,
+    }
+}
+
+impl Default for Ipv4Transformer {
+    fn default() -> Self {
+        Ipv4Transformer::new("", "", "", IpAddressTransformerOptions::default())
+    }
+}
+
+impl Default for Ipv6Transformer {
+    fn default() -> Self {
+        Ipv6Transformer::new("", "", "", IpAddressTransformerOptions::default())
+    }
+}
+
+impl Transformer for Ipv4Transformer {
+    fn id(&self) -> &str {
+        "ipv4"
+    }
+
+    fn description(&self) -> &str {
+        "Replace the host part of an IPv4 address, the network prefix can be kept (string only). [192.168.1.42]->[192.168.1.187]"
+    }
+
+    fn database_name(&self) -> &str {
+        self.database_name.as_str()
+    }
+
+    fn table_name(&self) -> &str {
+        self.table_name.as_str()
+    }
+
+    fn column_name(&self) -> &str {
+        self.column_name.as_str()
+    }
+
+    fn transform(&self, column: Column) -> Column {
+        transform_address(column, |address| self.replace_address(address))
+    }
+}
+
+impl Transformer for Ipv6Transformer {
+    fn id(&self) -> &str {
+        "ipv6"
+    }
+
+    fn description(&self) -> &str {
+        "Replace the interface part of an IPv6 address, the network prefix can be kept (string only). [2001:db8::1]->[2001:db8::8f1c:2a4e:91d:5b07]"
+    }
+
+    fn database_name(&self) -> &str {
+        self.database_name.as_str()
+    }
+
+    fn table_name(&self) -> &str {
+        self.table_name.as_str()
+    }
+
+    fn column_name(&self) -> &str {
+        self.column_name.as_str()
+    }
+
+    fn transform(&self, column: Column) -> Column {
+        transform_address(column, |address| self.replace_address(address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use crate::{transformer::Transformer, types::Column};
+
+    use super::{IpAddressTransformerOptions, Ipv4Transformer, Ipv6Transformer};
+
+    #[test]
+    fn transform_ipv4_keeps_prefix() {
+        let transformer = Ipv4Transformer::new("github", "logs", "ip", options(Some(24)));
+
+        let new_address = transform(&transformer, "192.168.1.42");
+        assert!(new_address.starts_with("192.168.1."));
+        assert!(new_address.parse::<Ipv4Addr>().is_ok());
+
+        // the same address is always replaced by the same address
+        assert_eq!(transform(&transformer, "192.168.1.42"), new_address);
+        assert_ne!(transform(&transformer, "192.168.1.43"), new_address);
+
+        // the network mask is kept
+        assert!(transform(&transformer, "10.0.0.1/8").ends_with("/8"));
+
+        let transformer = Ipv4Transformer::new("github", "logs", "ip", options(Some(32)));
+        assert_eq!(transform(&transformer, "192.168.1.42"), "192.168.1.42");
+
+        let transformer = Ipv4Transformer::new("github", "logs", "ip", options(None));
+        assert_ne!(transform(&transformer, "192.168.1.42"), new_address);
+    }
+
+    #[test]
+    fn transform_ipv6_keeps_prefix() {
+        let transformer = Ipv6Transformer::new("github", "logs", "ip", options(Some(64)));
+
+        let new_address = transform(&transformer, "2001:db8:85a3:8d3::1");
+        let segments = new_address.parse::<Ipv6Addr>().unwrap().segments();
+        assert_eq!(segments[..4], [0x2001, 0xdb8, 0x85a3, 0x8d3]);
+        assert_ne!(new_address, "2001:db8:85a3:8d3::1");
+        assert_eq!(transform(&transformer, "2001:db8:85a3:8d3::1"), new_address);
+    }
+
+    #[test]
+    fn transform_ip_with_other_values() {
+        let ipv4 = Ipv4Transformer::new("github", "logs", "ip", options(Some(24)));
+        let ipv6 = Ipv6Transformer::new("github", "logs", "ip", options(Some(64)));
+
+        for value in ["", "not an ip", "256.1.1.1", "2001:db8::1"] {
+            assert_eq!(transform(&ipv4, value), value);
+        }
+        for value in ["", "not an ip", "192.168.1.42"] {
+            assert_eq!(transform(&ipv6, value), value);
+        }
+
+        let column = Column::None("ip".to_string());
+        assert!(matches!(ipv4.transform(column), Column::None(_)));
+    }
+
+    fn transform(transformer: &dyn Transformer, value: &str) -> String {
+        let column = Column::StringValue("ip".to_string(), value.to_string());
+
+        transformer
+            .transform(column)
+            .string_value()
+            .unwrap()
+            .to_string()
+    }
+
+    fn options(prefix_length: Option<u8>) -> IpAddressTransformerOptions {
+        IpAddressTransformerOptions {
+            prefix_length,
+            seed: None,
+        }
+    }
+}
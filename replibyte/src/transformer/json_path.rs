@@ -0,0 +1,347 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Number, Value};
+
+use crate::config::TransformerTypeConfig;
+use crate::transformer::Transformer;
+use crate::types::Column;
+
+/// This struct is dedicated to transforming the values at some paths of a JSON column, e.g. `$.customer.email`.
+/// The other values of the document are kept, the values which are not valid JSON are not changed.
+pub struct JsonPathTransformer {
+    database_name: String,
+    table_name: String,
+    column_name: String,
+    paths: Vec<(Vec<PathSegment>, Box<dyn Transformer>)>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct JsonPathTransformerOptions {
+    pub paths: Vec<JsonPathConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct JsonPathConfig {
+    pub path: String,
+
+    #[serde(flatten)]
+    pub transformer: TransformerTypeConfig,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+    /// every value of an object or an array
+    Wildcard,
+}
+
+impl JsonPathTransformer {
+    pub fn new<S>(
+        database_name: S,
+        table_name: S,
+        column_name: S,
+        options: &JsonPathTransformerOptions,
+    ) -> Result<Self, String>
+    where
+        S: Into<String>,
+    {
+        let database_name = database_name.into();
+        let table_name = table_name.into();
+        let column_name = column_name.into();
+
+        let paths = options
+            .paths
+            .iter()
+            .map(|config| {
+                let path = parse_path(config.path.as_str())?;
+                let transformer = config.transformer.transformer(
+                    database_name.as_str(),
+                    table_name.as_str(),
+                    column_name.as_str(),
+                );
+                Ok((path, transformer))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(JsonPathTransformer {
+            database_name,
+            table_name,
+            column_name,
+            paths,
+        })
+    }
+}
+
+/// parse a path like `$.customer.emails[0]`, `$.items[*].name` or `$.*.email`
+fn parse_path(path: &str) -> Result<Vec<PathSegment>, String> {
+    let invalid = || format!("invalid JSON path '{}'", path);
+
+    let mut rest = path.strip_prefix('$').ok_or_else(invalid)?;
+    let mut segments = vec![];
+
+    while !rest.is_empty() {
+        if let Some(after_dot) = rest.strip_prefix('.') {
+            let end = after_dot.find(['.', '[']).unwrap_or(after_dot.len());
+            let key = &after_dot[..end];
+
+            segments.push(match key {
+                "" => return Err(invalid()),
+                "*" => PathSegment::Wildcard,
+                key => PathSegment::Key(key.to_string()),
+            });
+
+            rest = &after_dot[end..];
+        } else if let Some(after_bracket) = rest.strip_prefix('[') {
+            let end = after_bracket.find(']').ok_or_else(invalid)?;
+            let index = &after_bracket[..end];
+
+            segments.push(match index {
+                "*" => PathSegment::Wildcard,
+                index => PathSegment::Index(index.parse::<usize>().map_err(|_| invalid())?),
+            });
+
+            rest = &after_bracket[end + 1..];
+        } else {
+            return Err(invalid());
+        }
+    }
+
+    Ok(segments)
+}
+
+fn transform_value(
+    value: &mut Value,
+    path: &[PathSegment],
+    transformer: &dyn Transformer,
+    column_name: &str,
+    row: &[Column],
+) {
+    let (segment, rest) = match path.split_first() {
+        Some(split) => split,
+        None => {
+            let column = to_column(column_name, value.take());
+            *value = to_value(transformer.transform_row(column, row));
+            return;
+        }
+    };
+
+    let transform = |value: &mut Value| transform_value(value, rest, transformer, column_name, row);
+
+    match (segment, value) {
+        (PathSegment::Key(key), Value::Object(object)) => {
+            if let Some(value) = object.get_mut(key) {
+                transform(value);
+            }
+        }
+        (PathSegment::Index(index), Value::Array(array)) => {
+            if let Some(value) = array.get_mut(*index) {
+                transform(value);
+            }
+        }
+        (PathSegment::Wildcard, Value::Object(object)) => object.values_mut().for_each(transform),
+        (PathSegment::Wildcard, Value::Array(array)) => array.iter_mut().for_each(transform),
+        // the path does not exist in this document
+        _ => {}
+    }
+}
+
+/// convert a JSON value to a column, the objects and arrays are kept as JSON strings
+pub fn to_column(column_name: &str, value: Value) -> Column {
+    let column_name = column_name.to_string();
+
+    match value {
+        Value::Null => Column::None(column_name),
+        Value::Bool(value) => Column::BooleanValue(column_name, value),
+        Value::Number(number) => match (number.as_i64(), number.as_u64()) {
+            (Some(value), _) => Column::NumberValue(column_name, value as i128),
+            (_, Some(value)) => Column::NumberValue(column_name, value as i128),
+            // unwrap is safe: a number which is not an integer is a float
+            _ => Column::FloatNumberValue(column_name, number.as_f64().unwrap()),
+        },
+        Value::String(value) => Column::StringValue(column_name, value),
+        // the objects and arrays are only reached through the segments of the path
+        value => Column::StringValue(column_name, value.to_string()),
+    }
+}
+
+/// convert a column to a JSON value, the integers out of the JSON range are strings
+pub fn to_value(column: Column) -> Value {
+    match column {
+        Column::NumberValue(_, value) => match i64::try_from(value) {
+            Ok(value) => Value::from(value),
+            Err(_) => match u64::try_from(value) {
+                Ok(value) => Value::from(value),
+                Err(_) => Value::String(value.to_string()),
+            },
+        },
+        Column::FloatNumberValue(_, value) => match Number::from_f64(value) {
+            Some(number) => Value::Number(number),
+            None => Value::Null,
+        },
+        Column::StringValue(_, value) => Value::String(value),
+        Column::CharValue(_, value) => Value::String(value.to_string()),
+        Column::BooleanValue(_, value) => Value::Bool(value),
+        Column::None(_) => Value::Null,
+    }
+}
+
+impl Default for JsonPathTransformer {
+    fn default() -> Self {
+        JsonPathTransformer {
+            database_name: String::default(),
+            table_name: String::default(),
+            column_name: String::default(),
+            paths: vec![],
+        }
+    }
+}
+
+impl Transformer for JsonPathTransformer {
+    fn id(&self) -> &str {
+        "json-path"
+    }
+
+    fn description(&self) -> &str {
+        "Apply transformers to the values at some paths of a JSON document (string only). [{\"email\": \"lucas@company.com\"}]->[{\"email\": \"georges@example.org\"}]"
+    }
+
+    fn database_name(&self) -> &str {
+        self.database_name.as_str()
+    }
+
+    fn table_name(&self) -> &str {
+        self.table_name.as_str()
+    }
+
+    fn column_name(&self) -> &str {
+        self.column_name.as_str()
+    }
+
+    fn transform_row(&self, column: Column, row: &[Column]) -> Column {
+        match column {
+            Column::StringValue(column_name, value) => {
+                let mut document = match serde_json::from_str::<Value>(value.as_str()) {
+                    Ok(document) => document,
+                    Err(_) => return Column::StringValue(column_name, value),
+                };
+
+                for (path, transformer) in &self.paths {
+                    transform_value(
+                        &mut document,
+                        path,
+                        transformer.as_ref(),
+                        column_name.as_str(),
+                        row,
+                    );
+                }
+
+                Column::StringValue(column_name, document.to_string())
+            }
+            column => column,
+        }
+    }
+
+    fn transform(&self, column: Column) -> Column {
+        self.transform_row(column, &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::TransformerTypeConfig;
+    use crate::transformer::redacted::RedactedTransformerOptions;
+    use crate::{transformer::Transformer, types::Column};
+
+    use super::{
+        parse_path, JsonPathConfig, JsonPathTransformer, JsonPathTransformerOptions, PathSegment,
+    };
+
+    #[test]
+    fn parse_json_paths() {
+        assert_eq!(
+            parse_path("$.customer.emails[0]").unwrap(),
+            vec![
+                PathSegment::Key("customer".to_string()),
+                PathSegment::Key("emails".to_string()),
+                PathSegment::Index(0),
+            ]
+        );
+        assert_eq!(
+            parse_path("$.items[*].*").unwrap(),
+            vec![
+                PathSegment::Key("items".to_string()),
+                PathSegment::Wildcard,
+                PathSegment::Wildcard,
+            ]
+        );
+        assert_eq!(parse_path("$").unwrap(), vec![]);
+        assert!(parse_path("customer.email").is_err());
+        assert!(parse_path("$..email").is_err());
+        assert!(parse_path("$.items[a]").is_err());
+        assert!(parse_path("$.items[0").is_err());
+    }
+
+    #[test]
+    fn transform_json_paths() {
+        let transformer = get_transformer(&["$.customer.email", "$.items[*].card", "$.missing"]);
+        let column = Column::StringValue(
+            "data".to_string(),
+            r#"{"id":42,"customer":{"name":"Lucas","email":"lucas@company.com"},"items":[{"card":"4242424242424242"},{"card":"5555555555554444","qty":1}]}"#.to_string(),
+        );
+        let transformed_column = transformer.transform(column);
+        let transformed_value = transformed_column.string_value().unwrap();
+
+        // the order of the keys is kept
+        assert_eq!(
+            transformed_value,
+            r#"{"id":42,"customer":{"name":"Lucas","email":"luc*************"},"items":[{"card":"424*************"},{"card":"555*************","qty":1}]}"#
+        );
+    }
+
+    #[test]
+    fn transform_json_path_with_other_values() {
+        let transformer = get_transformer(&["$.email"]);
+
+        for value in ["not json", r#"["lucas@company.com"]"#, r#"{"email":null}"#] {
+            let column = Column::StringValue("data".to_string(), value.to_string());
+            let transformed_column = transformer.transform(column);
+            assert_eq!(transformed_column.string_value().unwrap(), value);
+        }
+
+        let column = Column::None("data".to_string());
+        assert!(matches!(transformer.transform(column), Column::None(_)));
+    }
+
+    #[test]
+    fn invalid_json_path() {
+        let options = JsonPathTransformerOptions {
+            paths: vec![JsonPathConfig {
+                path: "email".to_string(),
+                transformer: TransformerTypeConfig::Transient,
+            }],
+        };
+
+        assert!(JsonPathTransformer::new("github", "users", "data", &options).is_err());
+    }
+
+    fn get_transformer(paths: &[&str]) -> JsonPathTransformer {
+        let options = JsonPathTransformerOptions {
+            paths: paths
+                .iter()
+                .map(|path| JsonPathConfig {
+                    path: path.to_string(),
+                    transformer: TransformerTypeConfig::Redacted(Some(
+                        RedactedTransformerOptions {
+                            character: '*',
+                            width: 13,
+                        },
+                    )),
+                })
+                .collect(),
+        };
+
+        JsonPathTransformer::new("github", "users", "data", &options).unwrap()
+    }
+}
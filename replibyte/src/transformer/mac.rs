@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+
+use crate::transformer::Transformer;
+use crate::types::Column;
+
+const SEED_CONTEXT: &str = "replibyte mac transformer seed";
+
+/// This struct is dedicated to replacing a MAC address, its first bits (e.g. the 24 bits of the vendor) can be kept.
+/// The same address is always replaced by the same address, and its separators and case are kept.
+pub struct MacTransformer {
+    database_name: String,
+    table_name: String,
+    column_name: String,
+    prefix_length: u8,
+    key: [u8; 32],
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct MacTransformerOptions {
+    /// number of leading bits kept, e.g. 24 keeps the vendor (OUI) of the address (0 by default)
+    pub prefix_length: Option<u8>,
+    /// secret mixed into the new addresses, without it the original addresses can be guessed
+    pub seed: Option<String>,
+}
+
+impl MacTransformer {
+    pub fn new<S>(
+        database_name: S,
+        table_name: S,
+        column_name: S,
+        options: MacTransformerOptions,
+    ) -> Self
+    where
+        S: Into<String>,
+    {
+        let key = blake3::derive_key(
+            SEED_CONTEXT,
+            options.seed.as_deref().unwrap_or_default().as_bytes(),
+        );
+
+        MacTransformer {
+            database_name: database_name.into(),
+            table_name: table_name.into(),
+            column_name: column_name.into(),
+            prefix_length: options.prefix_length.unwrap_or_default().min(48),
+            key,
+        }
+    }
+
+    /// replace the 12 hexadecimal digits of an address like `00:1A:2B:3C:4D:5E`, `00-1a-2b-3c-4d-5e` or `001a.2b3c.4d5e`
+    fn replace_address(&self, value: &str) -> Option<String> {
+        if value
+            .chars()
+            .any(|c| !(c.is_ascii_hexdigit() || ":-.".contains(c)))
+        {
+            return None;
+        }
+
+        let digits = value
+            .chars()
+            .filter(|c| c.is_ascii_hexdigit())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        if digits.len() != 12 {
+            return None;
+        }
+
+        // unwrap is safe: the 12 digits are hexadecimal
+        let address = u64::from_str_radix(digits.as_str(), 16).unwrap();
+
+        let hash = blake3::keyed_hash(&self.key, digits.as_bytes());
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&hash.as_bytes()[..8]);
+        let bits = u64::from_be_bytes(bytes) >> 16;
+
+        let mask = (u64::MAX << (48 - self.prefix_length as u32)) & 0xffff_ffff_ffff;
+        let new_digits = format!("{:012x}", (address & mask) | (bits & !mask));
+
+        let mut new_digits = new_digits.chars();
+        Some(
+            value
+                .chars()
+                .map(|c| {
+                    if !c.is_ascii_hexdigit() {
+                        return c;
+                    }
+
+                    // unwrap is safe: there are as many new digits as digits
+                    let new_digit = new_digits.next().unwrap();
+                    if c.is_ascii_uppercase() {
+                        new_digit.to_ascii_uppercase()
+                    } else {
+                        new_digit
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Default for MacTransformer {
+    fn default() -> Self {
+        MacTransformer::new("", "", "", MacTransformerOptions::default())
+    }
+}
+
+impl Transformer for MacTransformer {
+    fn id(&self) -> &str {
+        "mac"
+    }
+
+    fn description(&self) -> &str {
+        "Replace a MAC address, the vendor prefix can be kept (string only). [00:1A:2B:3C:4D:5E]->[00:1A:2B:9F:07:C3]"
+    }
+
+    fn database_name(&self) -> &str {
+        self.database_name.as_str()
+    }
+
+    fn table_name(&self) -> &str {
+        self.table_name.as_str()
+    }
+
+    fn column_name(&self) -> &str {
+        self.column_name.as_str()
+    }
+
+    fn transform(&self, column: Column) -> Column {
+        match column {
+            Column::StringValue(column_name, value) => match self.replace_address(value.as_str()) {
+                Some(new_value) => Column::StringValue(column_name, new_value),
+                None => Column::StringValue(column_name, value),
+            },
+            column => column,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{transformer::Transformer, types::Column};
+
+    use super::{MacTransformer, MacTransformerOptions};
+
+    #[test]
+    fn transform_mac_keeps_vendor_and_format() {
+        let transformer = get_transformer(Some(24));
+
+        for (value, vendor) in [
+            ("00:1A:2B:3C:4D:5E", "00:1A:2B:"),
+            ("00-1a-2b-3c-4d-5e", "00-1a-2b-"),
+            ("001a.2b3c.4d5e", "001a.2b"),
+        ] {
+            let new_value = transform(&transformer, value);
+
+            assert_ne!(new_value, value);
+            assert!(new_value.starts_with(vendor), "{}", new_value);
+            assert_eq!(new_value.len(), value.len());
+            for (original, transformed) in value.chars().zip(new_value.chars()) {
+                assert_eq!(
+                    original.is_ascii_hexdigit(),
+                    transformed.is_ascii_hexdigit()
+                );
+                assert!(!(original.is_ascii_lowercase() && transformed.is_ascii_uppercase()));
+            }
+        }
+
+        // the same address is always replaced by the same address, whatever its format
+        assert_eq!(
+            transform(&transformer, "00:1A:2B:3C:4D:5E").to_ascii_lowercase(),
+            transform(&transformer, "00-1a-2b-3c-4d-5e").replace('-', ":")
+        );
+    }
+
+    #[test]
+    fn transform_mac_with_other_values() {
+        let transformer = get_transformer(None);
+
+        for value in ["", "not a mac", "00:1A:2B:3C:4D", "00:1A:2B:3C:4D:5G"] {
+            assert_eq!(transform(&transformer, value), value);
+        }
+
+        assert_ne!(
+            transform(&transformer, "00:1A:2B:3C:4D:5E")[..9],
+            *"00:1A:2B:"
+        );
+    }
+
+    fn transform(transformer: &MacTransformer, value: &str) -> String {
+        let column = Column::StringValue("mac".to_string(), value.to_string());
+
+        transformer
+            .transform(column)
+            .string_value()
+            .unwrap()
+            .to_string()
+    }
+
+    fn get_transformer(prefix_length: Option<u8>) -> MacTransformer {
+        MacTransformer::new(
+            "github",
+            "devices",
+            "mac",
+            MacTransformerOptions {
+                prefix_length,
+                seed: None,
+            },
+        )
+    }
+}
@@ -1,22 +1,56 @@
 use crate::transformer::credit_card::CreditCardTransformer;
 use crate::transformer::custom_wasm::CustomWasmTransformer;
+use crate::transformer::date_shift::DateShiftTransformer;
 use crate::transformer::email::EmailTransformer;
+use crate::transformer::faker::FakerTransformer;
 use crate::transformer::first_name::FirstNameTransformer;
+use crate::transformer::fpe::FpeTransformer;
+use crate::transformer::hash::HashTransformer;
+use crate::transformer::iban::IbanTransformer;
+use crate::transformer::ip_address::{Ipv4Transformer, Ipv6Transformer};
+use crate::transformer::json_path::JsonPathTransformer;
 use crate::transformer::keep_first_char::KeepFirstCharTransformer;
+use crate::transformer::mac::MacTransformer;
+use crate::transformer::noise::NoiseTransformer;
+use crate::transformer::person::PersonTransformer;
 use crate::transformer::phone_number::PhoneNumberTransformer;
+use crate::transformer::process::ProcessTransformer;
 use crate::transformer::random::RandomTransformer;
 use crate::transformer::redacted::RedactedTransformer;
+use crate::transformer::regex_replace::RegexReplaceTransformer;
+use crate::transformer::script::ScriptTransformer;
+use crate::transformer::set_constant::SetConstantTransformer;
+use crate::transformer::set_null::SetNullTransformer;
 use crate::transformer::transient::TransientTransformer;
+use crate::transformer::xml_xpath::XmlXPathTransformer;
 use crate::types::Column;
 
+pub mod conditional;
 pub mod credit_card;
+pub mod date_shift;
 pub mod email;
+pub mod faker;
 pub mod first_name;
+pub mod fpe;
+pub mod hash;
+pub mod iban;
+pub mod ip_address;
+pub mod json_path;
 pub mod keep_first_char;
+pub mod mac;
+pub mod noise;
+pub mod person;
 pub mod phone_number;
+pub mod process;
 pub mod random;
 pub mod redacted;
+pub mod regex_replace;
+pub mod script;
+pub mod selector;
+pub mod set_constant;
+pub mod set_null;
 pub mod transient;
+pub mod xml_xpath;
 
 // FIXME: CI release build is broken because of feature flag
 //#[cfg(feature = "wasm")]
@@ -30,8 +64,25 @@ pub fn transformers() -> Vec<Box<dyn Transformer>> {
         Box::new(RandomTransformer::default()),
         Box::new(KeepFirstCharTransformer::default()),
         Box::new(TransientTransformer::default()),
+        Box::new(SetNullTransformer::default()),
+        Box::new(SetConstantTransformer::default()),
         Box::new(CreditCardTransformer::default()),
+        Box::new(IbanTransformer::default()),
+        Box::new(Ipv4Transformer::default()),
+        Box::new(Ipv6Transformer::default()),
+        Box::new(MacTransformer::default()),
         Box::new(RedactedTransformer::default()),
+        Box::new(FakerTransformer::default()),
+        Box::new(FpeTransformer::default()),
+        Box::new(HashTransformer::default()),
+        Box::new(RegexReplaceTransformer::default()),
+        Box::new(JsonPathTransformer::default()),
+        Box::new(XmlXPathTransformer::default()),
+        Box::new(DateShiftTransformer::default()),
+        Box::new(NoiseTransformer::default()),
+        Box::new(PersonTransformer::default()),
+        Box::new(ScriptTransformer::default()),
+        Box::new(ProcessTransformer::default()),
         Box::new(CustomWasmTransformer::default()),
     ]
 }
@@ -65,4 +116,14 @@ pub trait Transformer {
     }
 
     fn transform(&self, column: Column) -> Column;
+
+    /// transform a column of a row, the row holds the original values of all its columns
+    fn transform_row(&self, column: Column, _row: &[Column]) -> Column {
+        self.transform(column)
+    }
+
+    /// transform a column of a row, the name is the one the transformer was selected with (e.g. `public.users.email`)
+    fn transform_column(&self, _name: &str, column: Column, row: &[Column]) -> Column {
+        self.transform_row(column, row)
+    }
 }
@@ -0,0 +1,234 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::transformer::Transformer;
+use crate::types::Column;
+
+/// This struct is dedicated to adding random noise to numbers, e.g. salaries or amounts.
+/// The noise follows a Laplace or a Gaussian distribution, or is a percentage of the value.
+pub struct NoiseTransformer {
+    database_name: String,
+    table_name: String,
+    column_name: String,
+    options: NoiseTransformerOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct NoiseTransformerOptions {
+    #[serde(default)]
+    pub distribution: NoiseDistribution,
+    /// scale of the Laplace distribution, standard deviation of the Gaussian one, or maximum percentage
+    pub scale: f64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// number of digits after the decimal point, a negative precision rounds to tens, hundreds...
+    pub precision: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NoiseDistribution {
+    #[default]
+    Laplace,
+    Gaussian,
+    Percentage,
+}
+
+impl NoiseTransformer {
+    pub fn new<S>(
+        database_name: S,
+        table_name: S,
+        column_name: S,
+        options: NoiseTransformerOptions,
+    ) -> Self
+    where
+        S: Into<String>,
+    {
+        NoiseTransformer {
+            database_name: database_name.into(),
+            table_name: table_name.into(),
+            column_name: column_name.into(),
+            options,
+        }
+    }
+
+    fn add_noise<R: Rng>(&self, rng: &mut R, value: f64) -> f64 {
+        let scale = self.options.scale.abs();
+
+        let mut new_value = match self.options.distribution {
+            NoiseDistribution::Laplace => {
+                // inverse of the cumulative distribution function
+                let u = rng.gen_range(-0.5..0.5f64);
+                value - scale * u.signum() * (1.0 - 2.0 * u.abs()).ln()
+            }
+            NoiseDistribution::Gaussian => {
+                // Box-Muller transform
+                let u1 = 1.0 - rng.gen::<f64>();
+                let u2 = rng.gen::<f64>();
+                value + scale * (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+            }
+            NoiseDistribution::Percentage => {
+                let percentage = rng.gen_range(-scale..=scale);
+                value * (1.0 + percentage / 100.0)
+            }
+        };
+
+        if let Some(precision) = self.options.precision {
+            let factor = 10f64.powi(precision);
+            new_value = (new_value * factor).round() / factor;
+        }
+
+        if let Some(min) = self.options.min {
+            new_value = new_value.max(min);
+        }
+
+        if let Some(max) = self.options.max {
+            new_value = new_value.min(max);
+        }
+
+        new_value
+    }
+}
+
+impl Default for NoiseTransformer {
+    fn default() -> Self {
+        NoiseTransformer {
+            database_name: String::default(),
+            table_name: String::default(),
+            column_name: String::default(),
+            options: NoiseTransformerOptions::default(),
+        }
+    }
+}
+
+impl Transformer for NoiseTransformer {
+    fn id(&self) -> &str {
+        "noise"
+    }
+
+    fn description(&self) -> &str {
+        "Add Laplace, Gaussian or percentage noise to the value (number only). [52000]->[53817]"
+    }
+
+    fn database_name(&self) -> &str {
+        self.database_name.as_str()
+    }
+
+    fn table_name(&self) -> &str {
+        self.table_name.as_str()
+    }
+
+    fn column_name(&self) -> &str {
+        self.column_name.as_str()
+    }
+
+    fn transform(&self, column: Column) -> Column {
+        let mut random = rand::thread_rng();
+
+        match column {
+            Column::NumberValue(column_name, value) => {
+                let new_value = self.add_noise(&mut random, value as f64).round();
+                Column::NumberValue(column_name, new_value as i128)
+            }
+            Column::FloatNumberValue(column_name, value) => {
+                Column::FloatNumberValue(column_name, self.add_noise(&mut random, value))
+            }
+            column => column,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use crate::{transformer::Transformer, types::Column};
+
+    use super::{NoiseDistribution, NoiseTransformer, NoiseTransformerOptions};
+
+    #[test]
+    fn add_noise_distributions() {
+        for distribution in [
+            NoiseDistribution::Laplace,
+            NoiseDistribution::Gaussian,
+            NoiseDistribution::Percentage,
+        ] {
+            let transformer = get_transformer(distribution, 10.0, None, None, None);
+            let rng = &mut ChaCha8Rng::seed_from_u64(42);
+
+            let values = (0..10_000)
+                .map(|_| transformer.add_noise(rng, 1000.0))
+                .collect::<Vec<_>>();
+
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            assert!((mean - 1000.0).abs() < 1.0, "{:?} {}", distribution, mean);
+            assert!(values.iter().any(|value| *value != values[0]));
+
+            if distribution == NoiseDistribution::Percentage {
+                assert!(values.iter().all(|value| (900.0..=1100.0).contains(value)));
+            }
+        }
+    }
+
+    #[test]
+    fn add_noise_with_clamp_and_precision() {
+        let transformer = get_transformer(
+            NoiseDistribution::Gaussian,
+            5000.0,
+            Some(0.0),
+            Some(60000.0),
+            Some(-2),
+        );
+        let rng = &mut ChaCha8Rng::seed_from_u64(42);
+
+        for _ in 0..1000 {
+            let value = transformer.add_noise(rng, 55000.0);
+            assert!((0.0..=60000.0).contains(&value));
+            assert_eq!(value % 100.0, 0.0);
+        }
+
+        let transformer = get_transformer(NoiseDistribution::Laplace, 1.0, None, None, Some(2));
+        let value = transformer.add_noise(rng, 10.0);
+        assert_eq!((value * 100.0).round(), value * 100.0);
+    }
+
+    #[test]
+    fn transform_noise() {
+        let transformer = get_transformer(NoiseDistribution::Percentage, 10.0, None, None, None);
+
+        let column = Column::NumberValue("salary".to_string(), 50000);
+        let transformed_value = *transformer.transform(column).number_value().unwrap();
+        assert!((45000..=55000).contains(&transformed_value));
+
+        let column = Column::FloatNumberValue("amount".to_string(), 12.5);
+        let transformed_value = *transformer.transform(column).float_number_value().unwrap();
+        assert!((11.25..=13.75).contains(&transformed_value));
+
+        let column = Column::StringValue("salary".to_string(), "50000".to_string());
+        let transformed_column = transformer.transform(column);
+        assert_eq!(transformed_column.string_value().unwrap(), "50000");
+    }
+
+    fn get_transformer(
+        distribution: NoiseDistribution,
+        scale: f64,
+        min: Option<f64>,
+        max: Option<f64>,
+        precision: Option<i32>,
+    ) -> NoiseTransformer {
+        NoiseTransformer::new(
+            "github",
+            "employees",
+            "salary",
+            NoiseTransformerOptions {
+                distribution,
+                scale,
+                min,
+                max,
+                precision,
+            },
+        )
+    }
+}
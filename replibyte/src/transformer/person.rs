@@ -0,0 +1,309 @@
+use fake::faker::name::raw::{FirstName, LastName};
+use fake::locales::EN;
+use fake::Fake;
+use rand::Rng;
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::transformer::Transformer;
+use crate::types::Column;
+
+const SEED_CONTEXT: &str = "replibyte person transformer seed";
+
+const EMAIL_DOMAINS: [&str; 3] = ["example.com", "example.org", "example.net"];
+
+/// This struct is dedicated to replacing a column of a group by a field of a fake person.
+/// The person is generated from the original values of all the columns of the group,
+/// so the columns of a row get consistent values: the email matches the first and last names.
+pub struct PersonTransformer {
+    database_name: String,
+    table_name: String,
+    column_name: String,
+    field: PersonField,
+    group_columns: Vec<String>,
+    key: [u8; 32],
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct PersonTransformerOptions {
+    /// secret mixed into the seeds, without it the original values can be guessed from the fake ones
+    pub seed: Option<String>,
+    pub columns: Vec<PersonColumn>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PersonColumn {
+    pub name: String,
+    pub field: PersonField,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PersonField {
+    #[default]
+    FirstName,
+    LastName,
+    Name,
+    Email,
+    Username,
+}
+
+struct Person {
+    first_name: String,
+    last_name: String,
+    username: String,
+    email_domain: &'static str,
+}
+
+impl PersonTransformer {
+    pub fn new<S>(
+        database_name: S,
+        table_name: S,
+        column_name: S,
+        field: PersonField,
+        options: &PersonTransformerOptions,
+    ) -> Self
+    where
+        S: Into<String>,
+    {
+        let key = blake3::derive_key(
+            SEED_CONTEXT,
+            options.seed.as_deref().unwrap_or_default().as_bytes(),
+        );
+
+        PersonTransformer {
+            database_name: database_name.into(),
+            table_name: table_name.into(),
+            column_name: column_name.into(),
+            field,
+            group_columns: options
+                .columns
+                .iter()
+                .map(|column| column.name.clone())
+                .collect(),
+            key,
+        }
+    }
+
+    /// the person of a row, all the columns of the group generate the same one
+    fn person(&self, row: &[Column]) -> Person {
+        let mut hasher = blake3::Hasher::new_keyed(&self.key);
+        for group_column in &self.group_columns {
+            let value = row
+                .iter()
+                .find(|column| column.name() == group_column)
+                .and_then(|column| column.string_value())
+                .unwrap_or_default();
+
+            // the length prefix keeps ("ab", "c") and ("a", "bc") apart
+            hasher.update(&(value.len() as u64).to_le_bytes());
+            hasher.update(value.as_bytes());
+        }
+
+        let rng = &mut ChaCha8Rng::from_seed(*hasher.finalize().as_bytes());
+        let first_name: String = FirstName(EN).fake_with_rng(rng);
+        let last_name: String = LastName(EN).fake_with_rng(rng);
+        let username = format!(
+            "{}.{}{}",
+            slug(first_name.as_str()),
+            slug(last_name.as_str()),
+            rng.gen_range(10..100)
+        );
+        let email_domain = EMAIL_DOMAINS[rng.gen_range(0..EMAIL_DOMAINS.len())];
+
+        Person {
+            first_name,
+            last_name,
+            username,
+            email_domain,
+        }
+    }
+
+    fn field(&self, row: &[Column]) -> String {
+        let person = self.person(row);
+
+        match self.field {
+            PersonField::FirstName => person.first_name,
+            PersonField::LastName => person.last_name,
+            PersonField::Name => format!("{} {}", person.first_name, person.last_name),
+            PersonField::Email => format!("{}@{}", person.username, person.email_domain),
+            PersonField::Username => person.username,
+        }
+    }
+}
+
+/// lowercase ASCII letters and digits only, e.g. [O'Conner]->[oconner]
+fn slug(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+impl Default for PersonTransformer {
+    fn default() -> Self {
+        PersonTransformer::new(
+            "",
+            "",
+            "",
+            PersonField::default(),
+            &PersonTransformerOptions::default(),
+        )
+    }
+}
+
+impl Transformer for PersonTransformer {
+    fn id(&self) -> &str {
+        "person"
+    }
+
+    fn description(&self) -> &str {
+        "Generate a fake person for a group of columns, the email matches the names (string only). [Lucas, lucas@company.com]->[Georges, georges.smith42@example.org]"
+    }
+
+    fn database_name(&self) -> &str {
+        self.database_name.as_str()
+    }
+
+    fn table_name(&self) -> &str {
+        self.table_name.as_str()
+    }
+
+    fn column_name(&self) -> &str {
+        self.column_name.as_str()
+    }
+
+    fn transform_row(&self, column: Column, row: &[Column]) -> Column {
+        match column {
+            Column::StringValue(column_name, value) => {
+                let new_value = if value == "" {
+                    "".to_string()
+                } else {
+                    self.field(row)
+                };
+
+                Column::StringValue(column_name, new_value)
+            }
+            column => column,
+        }
+    }
+
+    fn transform(&self, column: Column) -> Column {
+        let row = [column.clone()];
+        self.transform_row(column, &row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{transformer::Transformer, types::Column};
+
+    use super::{slug, PersonColumn, PersonField, PersonTransformer, PersonTransformerOptions};
+
+    #[test]
+    fn transform_person_columns_are_consistent() {
+        let row = get_row("Lucas", "Martin", "lucas.martin@company.com");
+        let first_name = transform(PersonField::FirstName, "first_name", &row, None);
+        let last_name = transform(PersonField::LastName, "last_name", &row, None);
+        let email = transform(PersonField::Email, "email", &row, None);
+
+        assert_ne!(first_name, "Lucas");
+        assert!(email.starts_with(
+            format!("{}.{}", slug(first_name.as_str()), slug(last_name.as_str())).as_str()
+        ));
+        assert!(email.contains("@example."));
+        assert_eq!(
+            transform(PersonField::Name, "first_name", &row, None),
+            format!("{} {}", first_name, last_name)
+        );
+
+        // the same person is always replaced by the same fake person
+        assert_eq!(
+            transform(PersonField::Email, "email", &row, None),
+            email.as_str()
+        );
+
+        let other_row = get_row("Lucas", "Martin", "lucas@other.com");
+        assert_ne!(
+            transform(PersonField::Email, "email", &other_row, None),
+            email
+        );
+        assert_ne!(
+            transform(PersonField::Email, "email", &row, Some("secret")),
+            email
+        );
+    }
+
+    #[test]
+    fn transform_person_with_empty_and_null_values() {
+        let row = vec![
+            Column::StringValue("first_name".to_string(), "".to_string()),
+            Column::None("last_name".to_string()),
+        ];
+        let transformer = get_transformer(PersonField::FirstName, "first_name", None);
+
+        let transformed_column = transformer.transform_row(row[0].clone(), &row);
+        assert_eq!(transformed_column.string_value().unwrap(), "");
+
+        let transformed_column = transformer.transform_row(row[1].clone(), &row);
+        assert!(matches!(transformed_column, Column::None(_)));
+    }
+
+    fn transform(
+        field: PersonField,
+        column_name: &str,
+        row: &[Column],
+        seed: Option<&str>,
+    ) -> String {
+        let column = row
+            .iter()
+            .find(|column| column.name() == column_name)
+            .unwrap()
+            .clone();
+
+        get_transformer(field, column_name, seed)
+            .transform_row(column, row)
+            .string_value()
+            .unwrap()
+            .to_string()
+    }
+
+    fn get_row(first_name: &str, last_name: &str, email: &str) -> Vec<Column> {
+        vec![
+            Column::NumberValue("id".to_string(), 1),
+            Column::StringValue("first_name".to_string(), first_name.to_string()),
+            Column::StringValue("last_name".to_string(), last_name.to_string()),
+            Column::StringValue("email".to_string(), email.to_string()),
+        ]
+    }
+
+    fn get_transformer(
+        field: PersonField,
+        column_name: &str,
+        seed: Option<&str>,
+    ) -> PersonTransformer {
+        let options = PersonTransformerOptions {
+            seed: seed.map(|seed| seed.to_string()),
+            columns: vec![
+                PersonColumn {
+                    name: "first_name".to_string(),
+                    field: PersonField::FirstName,
+                },
+                PersonColumn {
+                    name: "last_name".to_string(),
+                    field: PersonField::LastName,
+                },
+                PersonColumn {
+                    name: "email".to_string(),
+                    field: PersonField::Email,
+                },
+            ],
+        };
+
+        PersonTransformer::new("github", "users", column_name, field, &options)
+    }
+}
@@ -3,8 +3,17 @@ use crate::types::Column;
 use fake::faker::phone_number::raw::PhoneNumber;
 use fake::locales::EN;
 use fake::Fake;
+use rand::Rng;
 
-/// This struct is dedicated to replacing a string by an email address.
+/// the country calling codes of 2 digits, the others have 1 (`1` and `7`) or 3 digits
+const TWO_DIGITS_COUNTRY_CODES: [&str; 44] = [
+    "20", "27", "30", "31", "32", "33", "34", "36", "39", "40", "41", "43", "44", "45", "46", "47",
+    "48", "49", "51", "52", "53", "54", "55", "56", "57", "58", "60", "61", "62", "63", "64", "65",
+    "66", "81", "82", "84", "86", "90", "91", "92", "93", "94", "95", "98",
+];
+
+/// This struct is dedicated to replacing a phone number string.
+/// The country code, the first digit of the national number and the separators are kept.
 pub struct PhoneNumberTransformer {
     database_name: String,
     table_name: String,
@@ -40,7 +49,7 @@ impl Transformer for PhoneNumberTransformer {
     }
 
     fn description(&self) -> &str {
-        "Generate a phone number (string only)."
+        "Generate a phone number of the same country and format (string only). [+33 6 12 34 56 78]->[+33 6 80 27 41 95]"
     }
 
     fn database_name(&self) -> &str {
@@ -57,14 +66,90 @@ impl Transformer for PhoneNumberTransformer {
 
     fn transform(&self, column: Column) -> Column {
         match column {
-            Column::StringValue(column_name, _) => {
-                Column::StringValue(column_name, PhoneNumber(EN).fake())
+            Column::StringValue(column_name, value) => {
+                let new_value = match replace_phone_number(&mut rand::thread_rng(), value.as_str())
+                {
+                    Some(new_value) => new_value,
+                    None => PhoneNumber(EN).fake(),
+                };
+
+                Column::StringValue(column_name, new_value)
             }
             column => column,
         }
     }
 }
 
+/// replace the digits of a phone number after its country code and the first digit of the national number,
+/// `None` if the value is not a phone number
+fn replace_phone_number<R: Rng>(rng: &mut R, value: &str) -> Option<String> {
+    let digits = value
+        .chars()
+        .filter(|c| c.is_ascii_digit())
+        .collect::<String>();
+
+    if digits.len() < 6
+        || value
+            .chars()
+            .any(|c| !(c.is_ascii_digit() || " +-.()/".contains(c)))
+    {
+        return None;
+    }
+
+    let kept_digits = if value.trim_start().starts_with('+') {
+        country_code_length(digits.as_str()) + 1
+    } else if let Some(international_number) = digits.strip_prefix("00") {
+        // 00 is the international call prefix of most countries
+        2 + country_code_length(international_number) + 1
+    } else if digits.starts_with('0') {
+        // the trunk prefix and the first digit of the national number, e.g. 06 for a french mobile
+        2
+    } else {
+        // e.g. the area code of a north american number
+        3
+    };
+
+    if kept_digits >= digits.len() {
+        return None;
+    }
+
+    loop {
+        let mut position = 0;
+        let new_value = value
+            .chars()
+            .map(|c| {
+                if !c.is_ascii_digit() {
+                    return c;
+                }
+
+                position += 1;
+                if position <= kept_digits {
+                    c
+                } else {
+                    char::from(b'0' + rng.gen_range(0..10))
+                }
+            })
+            .collect::<String>();
+
+        if new_value != value {
+            return Some(new_value);
+        }
+    }
+}
+
+fn country_code_length(international_number: &str) -> usize {
+    if international_number.starts_with('1') || international_number.starts_with('7') {
+        1
+    } else if TWO_DIGITS_COUNTRY_CODES
+        .iter()
+        .any(|code| international_number.starts_with(code))
+    {
+        2
+    } else {
+        3
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{transformer::Transformer, types::Column};
@@ -82,6 +167,31 @@ mod tests {
         assert_ne!(transformed_value, "+123456789".to_string());
     }
 
+    #[test]
+    fn transform_phone_number_keeps_country_and_format() {
+        let transformer = get_transformer();
+
+        for (value, kept) in [
+            ("+33 6 12 34 56 78", "+33 6 "),
+            ("0033 6 12 34 56 78", "0033 6 "),
+            ("06.12.34.56.78", "06."),
+            ("+1 (415) 555-0132", "+1 (4"),
+            ("(415) 555-0132", "(415) "),
+            ("+352 621 123 456", "+352 6"),
+        ] {
+            let column = Column::StringValue("phone_number".to_string(), value.to_string());
+            let transformed_column = transformer.transform(column);
+            let transformed_value = transformed_column.string_value().unwrap();
+
+            assert_ne!(transformed_value, value);
+            assert!(transformed_value.starts_with(kept), "{}", transformed_value);
+            assert_eq!(transformed_value.len(), value.len());
+            for (original, transformed) in value.chars().zip(transformed_value.chars()) {
+                assert_eq!(original.is_ascii_digit(), transformed.is_ascii_digit());
+            }
+        }
+    }
+
     fn get_transformer() -> PhoneNumberTransformer {
         PhoneNumberTransformer::new("github", "users", "phone_number")
     }
@@ -0,0 +1,244 @@
+use std::io::{self, BufRead, BufReader, BufWriter, ErrorKind, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::transformer::json_path::{to_column, to_value};
+use crate::transformer::Transformer;
+use crate::types::Column;
+
+/// This struct is dedicated to transforming the values with an executable, e.g. an existing Python script.
+/// The executable is started once, it reads a JSON line per value from stdin and writes the new value to stdout.
+pub struct ProcessTransformer {
+    database_name: String,
+    table_name: String,
+    column_name: String,
+    options: ProcessTransformerOptions,
+    process: Mutex<Option<Process>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ProcessTransformerOptions {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+struct Process {
+    child: Child,
+    stdin: BufWriter<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl ProcessTransformer {
+    pub fn new<S>(
+        database_name: S,
+        table_name: S,
+        column_name: S,
+        options: ProcessTransformerOptions,
+    ) -> Self
+    where
+        S: Into<String>,
+    {
+        ProcessTransformer {
+            database_name: database_name.into(),
+            table_name: table_name.into(),
+            column_name: column_name.into(),
+            options,
+            process: Mutex::new(None),
+        }
+    }
+
+    fn spawn(&self) -> Result<Process, io::Error> {
+        let mut child = Command::new(self.options.command.as_str())
+            .args(self.options.args.as_slice())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        // unwrap is safe: stdin and stdout are piped
+        let stdin = BufWriter::new(child.stdin.take().unwrap());
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+
+        Ok(Process {
+            child,
+            stdin,
+            stdout,
+        })
+    }
+
+    /// send the JSON line of a value to the process, and read the JSON line of its new value
+    fn call_process(&self, column: Column) -> Result<Value, io::Error> {
+        let request = json!({
+            "database": self.database_name,
+            "table": self.table_name,
+            "column": column.name(),
+            "value": to_value(column),
+        });
+
+        // the process is started with the first value
+        let mut process = self.process.lock().unwrap();
+        if process.is_none() {
+            *process = Some(self.spawn()?);
+        }
+        // unwrap is safe: the process is started above
+        let process = process.as_mut().unwrap();
+
+        writeln!(process.stdin, "{}", request)?;
+        process.stdin.flush()?;
+
+        let mut response = String::new();
+        if process.stdout.read_line(&mut response)? == 0 {
+            return Err(io::Error::new(
+                ErrorKind::UnexpectedEof,
+                "the process exited before writing the value",
+            ));
+        }
+
+        serde_json::from_str(response.trim_end())
+            .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))
+    }
+}
+
+impl Drop for ProcessTransformer {
+    fn drop(&mut self) {
+        if let Ok(process) = self.process.get_mut() {
+            if let Some(mut process) = process.take() {
+                // closing stdin tells the process there are no more values
+                drop(process.stdin);
+                let _ = process.child.wait();
+            }
+        }
+    }
+}
+
+impl Default for ProcessTransformer {
+    fn default() -> Self {
+        ProcessTransformer::new("", "", "", ProcessTransformerOptions::default())
+    }
+}
+
+impl Transformer for ProcessTransformer {
+    fn id(&self) -> &str {
+        "process"
+    }
+
+    fn description(&self) -> &str {
+        "Replace the value by the output of an executable, which gets a JSON line per value on stdin."
+    }
+
+    fn database_name(&self) -> &str {
+        self.database_name.as_str()
+    }
+
+    fn table_name(&self) -> &str {
+        self.table_name.as_str()
+    }
+
+    fn column_name(&self) -> &str {
+        self.column_name.as_str()
+    }
+
+    fn transform(&self, column: Column) -> Column {
+        let column_name = column.name().to_string();
+        let is_char = matches!(column, Column::CharValue(_, _));
+
+        let value = match self.call_process(column) {
+            Ok(value) => value,
+            Err(err) => panic!(
+                "The process transformer of column '{}' failed: {}",
+                self.database_and_table_and_column_name(),
+                err
+            ),
+        };
+
+        match to_column(column_name.as_str(), value) {
+            // the chars are strings in JSON
+            Column::StringValue(column_name, value) if is_char && value.chars().count() == 1 => {
+                // unwrap is safe: the string has one char
+                Column::CharValue(column_name, value.chars().next().unwrap())
+            }
+            column => column,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::{transformer::Transformer, types::Column};
+
+    use super::{ProcessTransformer, ProcessTransformerOptions};
+
+    #[test]
+    fn transform_process() {
+        // cat writes the requests back
+        let transformer = get_transformer("cat", &[]);
+
+        for (column, value) in [
+            (
+                Column::StringValue("email".to_string(), "john@doe.com".to_string()),
+                json!("john@doe.com"),
+            ),
+            (
+                Column::FloatNumberValue("email".to_string(), 1.5),
+                json!(1.5),
+            ),
+            (Column::BooleanValue("email".to_string(), true), json!(true)),
+        ] {
+            let transformed_column = transformer.transform(column);
+            let request: Value =
+                serde_json::from_str(transformed_column.string_value().unwrap()).unwrap();
+
+            assert_eq!(
+                request,
+                json!({"database": "github", "table": "users", "column": "email", "value": value})
+            );
+        }
+    }
+
+    #[test]
+    fn transform_process_with_typed_values() {
+        let transformer = get_transformer(
+            "sh",
+            &[
+                "-c",
+                "while read -r line; do echo 42; echo null; echo '\"x\"'; done",
+            ],
+        );
+
+        let column = Column::StringValue("email".to_string(), "john@doe.com".to_string());
+        assert_eq!(transformer.transform(column).number_value(), Some(&42));
+
+        let column = Column::StringValue("email".to_string(), "john@doe.com".to_string());
+        assert!(matches!(transformer.transform(column), Column::None(_)));
+
+        let column = Column::CharValue("email".to_string(), 'j');
+        assert_eq!(transformer.transform(column).char_value(), Some(&'x'));
+    }
+
+    #[test]
+    #[should_panic]
+    fn transform_process_exiting() {
+        let transformer = get_transformer("true", &[]);
+
+        let column = Column::StringValue("email".to_string(), "john@doe.com".to_string());
+        let _ = transformer.transform(column);
+    }
+
+    fn get_transformer(command: &str, args: &[&str]) -> ProcessTransformer {
+        ProcessTransformer::new(
+            "github",
+            "users",
+            "email",
+            ProcessTransformerOptions {
+                command: command.to_string(),
+                args: args.iter().map(|arg| arg.to_string()).collect(),
+            },
+        )
+    }
+}
@@ -12,6 +12,7 @@ pub struct RedactedTransformer {
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(deny_unknown_fields)]
 pub struct RedactedTransformerOptions {
     pub character: char,
     pub width: u8,
@@ -0,0 +1,165 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::transformer::Transformer;
+use crate::types::Column;
+
+/// This struct is dedicated to replacing the parts of a string matching patterns.
+/// The rules are applied in order, the replacements can use the capture groups (`$1`, `${name}`).
+pub struct RegexReplaceTransformer {
+    database_name: String,
+    table_name: String,
+    column_name: String,
+    rules: Vec<(Regex, String)>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RegexReplaceTransformerOptions {
+    pub rules: Vec<RegexReplaceRule>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RegexReplaceRule {
+    pub pattern: String,
+    pub replacement: String,
+}
+
+impl RegexReplaceTransformer {
+    pub fn new<S>(
+        database_name: S,
+        table_name: S,
+        column_name: S,
+        options: &RegexReplaceTransformerOptions,
+    ) -> Result<Self, regex::Error>
+    where
+        S: Into<String>,
+    {
+        let rules = options
+            .rules
+            .iter()
+            .map(|rule| Ok((Regex::new(rule.pattern.as_str())?, rule.replacement.clone())))
+            .collect::<Result<Vec<_>, regex::Error>>()?;
+
+        Ok(RegexReplaceTransformer {
+            database_name: database_name.into(),
+            table_name: table_name.into(),
+            column_name: column_name.into(),
+            rules,
+        })
+    }
+}
+
+impl Default for RegexReplaceTransformer {
+    fn default() -> Self {
+        RegexReplaceTransformer {
+            database_name: String::default(),
+            table_name: String::default(),
+            column_name: String::default(),
+            rules: vec![],
+        }
+    }
+}
+
+impl Transformer for RegexReplaceTransformer {
+    fn id(&self) -> &str {
+        "regex-replace"
+    }
+
+    fn description(&self) -> &str {
+        "Replace the parts of the value matching patterns (string only). [john.doe@company.com]->[xxx@company.com]"
+    }
+
+    fn database_name(&self) -> &str {
+        self.database_name.as_str()
+    }
+
+    fn table_name(&self) -> &str {
+        self.table_name.as_str()
+    }
+
+    fn column_name(&self) -> &str {
+        self.column_name.as_str()
+    }
+
+    fn transform(&self, column: Column) -> Column {
+        match column {
+            Column::StringValue(column_name, value) => {
+                let new_value = self
+                    .rules
+                    .iter()
+                    .fold(value, |value, (regex, replacement)| {
+                        regex
+                            .replace_all(value.as_str(), replacement.as_str())
+                            .to_string()
+                    });
+
+                Column::StringValue(column_name, new_value)
+            }
+            column => column,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{transformer::Transformer, types::Column};
+
+    use super::{RegexReplaceRule, RegexReplaceTransformer, RegexReplaceTransformerOptions};
+
+    #[test]
+    fn transform_regex_replace_with_capture_groups() {
+        let transformer = get_transformer(&[(r"^[^@]+@(?P<domain>.+)$", "xxx@${domain}")]).unwrap();
+        let column = Column::StringValue("email".to_string(), "john.doe@company.com".to_string());
+        let transformed_column = transformer.transform(column);
+        let transformed_value = transformed_column.string_value().unwrap();
+
+        assert_eq!(transformed_value, "xxx@company.com");
+    }
+
+    #[test]
+    fn transform_regex_replace_with_multiple_rules() {
+        let transformer = get_transformer(&[(r"\d", "0"), (r"0{4}$", "1234")]).unwrap();
+        let column = Column::StringValue("phone".to_string(), "+33 6 12 34 56 78".to_string());
+        let transformed_column = transformer.transform(column);
+        let transformed_value = transformed_column.string_value().unwrap();
+
+        assert_eq!(transformed_value, "+00 0 00 00 00 00");
+
+        let column = Column::StringValue("phone".to_string(), "0612345678".to_string());
+        let transformed_column = transformer.transform(column);
+        let transformed_value = transformed_column.string_value().unwrap();
+
+        assert_eq!(transformed_value, "0000001234");
+    }
+
+    #[test]
+    fn transform_regex_replace_with_number_value() {
+        let transformer = get_transformer(&[(r"\d", "0")]).unwrap();
+        let column = Column::NumberValue("phone".to_string(), 42);
+        let transformed_column = transformer.transform(column);
+        let transformed_value = transformed_column.number_value().unwrap();
+
+        assert_eq!(transformed_value.to_owned(), 42);
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        assert!(get_transformer(&[(r"(", "")]).is_err());
+    }
+
+    fn get_transformer(rules: &[(&str, &str)]) -> Result<RegexReplaceTransformer, regex::Error> {
+        let options = RegexReplaceTransformerOptions {
+            rules: rules
+                .iter()
+                .map(|(pattern, replacement)| RegexReplaceRule {
+                    pattern: pattern.to_string(),
+                    replacement: replacement.to_string(),
+                })
+                .collect(),
+        };
+
+        RegexReplaceTransformer::new("github", "users", "email", &options)
+    }
+}
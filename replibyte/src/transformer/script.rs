@@ -0,0 +1,232 @@
+use rhai::{Dynamic, Engine, Map, Scope, AST};
+use serde::{Deserialize, Serialize};
+
+use crate::transformer::Transformer;
+use crate::types::Column;
+
+/// maximum number of operations of a script run, it stops the scripts which never end
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// This struct is dedicated to running a Rhai script (https://rhai.rs) from the configuration.
+/// The script gets the `value`, `column`, `table`, `database` and `row` variables, its result is the new value.
+pub struct ScriptTransformer {
+    database_name: String,
+    table_name: String,
+    column_name: String,
+    engine: Engine,
+    ast: AST,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ScriptTransformerOptions {
+    pub script: String,
+}
+
+impl ScriptTransformer {
+    pub fn new<S>(
+        database_name: S,
+        table_name: S,
+        column_name: S,
+        options: &ScriptTransformerOptions,
+    ) -> Result<Self, String>
+    where
+        S: Into<String>,
+    {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+
+        let ast = engine
+            .compile(options.script.as_str())
+            .map_err(|err| err.to_string())?;
+
+        Ok(ScriptTransformer {
+            database_name: database_name.into(),
+            table_name: table_name.into(),
+            column_name: column_name.into(),
+            engine,
+            ast,
+        })
+    }
+
+    fn run(&self, column: &Column, row: &[Column]) -> Result<Dynamic, String> {
+        let row = row
+            .iter()
+            .map(|column| (column.name().into(), to_dynamic(column)))
+            .collect::<Map>();
+
+        let mut scope = Scope::new();
+        scope.push("value", to_dynamic(column));
+        scope.push("column", column.name().to_string());
+        scope.push("table", self.table_name.clone());
+        scope.push("database", self.database_name.clone());
+        scope.push("row", row);
+
+        self.engine
+            .eval_ast_with_scope::<Dynamic>(&mut scope, &self.ast)
+            .map_err(|err| err.to_string())
+    }
+}
+
+fn to_dynamic(column: &Column) -> Dynamic {
+    match column {
+        Column::NumberValue(_, value) => match i64::try_from(*value) {
+            Ok(value) => Dynamic::from(value),
+            // the integers of the scripts are 64 bits
+            Err(_) => Dynamic::from(value.to_string()),
+        },
+        Column::FloatNumberValue(_, value) => Dynamic::from(*value),
+        Column::StringValue(_, value) => Dynamic::from(value.clone()),
+        Column::CharValue(_, value) => Dynamic::from(*value),
+        Column::BooleanValue(_, value) => Dynamic::from(*value),
+        Column::None(_) => Dynamic::UNIT,
+    }
+}
+
+fn to_column(column_name: String, value: Dynamic) -> Column {
+    if value.is_unit() {
+        Column::None(column_name)
+    } else if let Ok(value) = value.as_int() {
+        Column::NumberValue(column_name, value as i128)
+    } else if let Ok(value) = value.as_float() {
+        Column::FloatNumberValue(column_name, value)
+    } else if let Ok(value) = value.as_bool() {
+        Column::BooleanValue(column_name, value)
+    } else if let Ok(value) = value.as_char() {
+        Column::CharValue(column_name, value)
+    } else {
+        Column::StringValue(column_name, value.to_string())
+    }
+}
+
+impl Default for ScriptTransformer {
+    fn default() -> Self {
+        ScriptTransformer {
+            database_name: String::default(),
+            table_name: String::default(),
+            column_name: String::default(),
+            engine: Engine::new(),
+            ast: AST::empty(),
+        }
+    }
+}
+
+impl Transformer for ScriptTransformer {
+    fn id(&self) -> &str {
+        "script"
+    }
+
+    fn description(&self) -> &str {
+        "Replace the value by the result of a Rhai script. [john.doe@company.com]->[j***@company.com]"
+    }
+
+    fn database_name(&self) -> &str {
+        self.database_name.as_str()
+    }
+
+    fn table_name(&self) -> &str {
+        self.table_name.as_str()
+    }
+
+    fn column_name(&self) -> &str {
+        self.column_name.as_str()
+    }
+
+    fn transform_row(&self, column: Column, row: &[Column]) -> Column {
+        match self.run(&column, row) {
+            Ok(value) => to_column(column.name().to_string(), value),
+            Err(err) => panic!(
+                "The script transformer of column '{}' failed: {}",
+                self.database_and_table_and_column_name(),
+                err
+            ),
+        }
+    }
+
+    fn transform(&self, column: Column) -> Column {
+        self.transform_row(column, &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{transformer::Transformer, types::Column};
+
+    use super::{ScriptTransformer, ScriptTransformerOptions};
+
+    #[test]
+    fn transform_script() {
+        let transformer = get_transformer(
+            r#"
+            let at = value.index_of("@");
+            if at < 0 { return value; }
+            value.sub_string(0, 1) + "***" + value.sub_string(at)
+            "#,
+        );
+
+        let column = Column::StringValue("email".to_string(), "john.doe@company.com".to_string());
+        let transformed_column = transformer.transform(column);
+        assert_eq!(
+            transformed_column.string_value().unwrap(),
+            "j***@company.com"
+        );
+
+        let column = Column::StringValue("email".to_string(), "john".to_string());
+        let transformed_column = transformer.transform(column);
+        assert_eq!(transformed_column.string_value().unwrap(), "john");
+    }
+
+    #[test]
+    fn transform_script_with_row_and_types() {
+        let transformer = get_transformer(
+            r#"
+            if row.country == "FR" && table == "users" { value * 2 } else { () }
+            "#,
+        );
+
+        let column = Column::NumberValue("age".to_string(), 21);
+        let row = vec![
+            Column::StringValue("country".to_string(), "FR".to_string()),
+            column.clone(),
+        ];
+        let transformed_column = transformer.transform_row(column, &row);
+        assert_eq!(transformed_column.number_value(), Some(&42));
+
+        let column = Column::NumberValue("age".to_string(), 21);
+        let row = vec![
+            Column::StringValue("country".to_string(), "US".to_string()),
+            column.clone(),
+        ];
+        let transformed_column = transformer.transform_row(column, &row);
+        assert!(matches!(transformed_column, Column::None(_)));
+
+        let transformer = get_transformer("value == ()");
+        let transformed_column = transformer.transform(Column::None("age".to_string()));
+        assert_eq!(transformed_column.boolean_value(), Some(&true));
+    }
+
+    #[test]
+    fn invalid_script() {
+        let options = ScriptTransformerOptions {
+            script: "let = 1".to_string(),
+        };
+        assert!(ScriptTransformer::new("github", "users", "email", &options).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn transform_script_never_ending() {
+        let transformer = get_transformer("loop {}");
+
+        let column = Column::StringValue("email".to_string(), "john.doe@company.com".to_string());
+        let _ = transformer.transform(column);
+    }
+
+    fn get_transformer(script: &str) -> ScriptTransformer {
+        let options = ScriptTransformerOptions {
+            script: script.to_string(),
+        };
+
+        ScriptTransformer::new("github", "users", "email", &options).unwrap()
+    }
+}
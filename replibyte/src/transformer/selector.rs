@@ -0,0 +1,186 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use crate::transformer::Transformer;
+
+/// Transformers found by the name of a column: `database.table.column`, or `table.column` for the sources without databases.
+/// The names of the transformers can be patterns, with `*` and `?` wildcards (e.g. `*_audit`) or a regex between slashes
+/// (e.g. `/.*email.*/`). A transformer with the exact name of a column is used first, then the first matching pattern.
+pub struct TransformerSelector<'a> {
+    by_name: HashMap<String, &'a dyn Transformer>,
+    patterns: Vec<(Regex, &'a dyn Transformer)>,
+    /// transformers found with the patterns by name, to match them once per column
+    matched: RefCell<HashMap<String, Option<&'a dyn Transformer>>>,
+}
+
+impl<'a> TransformerSelector<'a> {
+    pub fn new(transformers: &'a [Box<dyn Transformer + 'a>], with_database_name: bool) -> Self {
+        let mut by_name = HashMap::with_capacity(transformers.len());
+        let mut patterns = vec![];
+
+        for transformer in transformers {
+            let names = if with_database_name {
+                vec![
+                    transformer.database_name(),
+                    transformer.table_name(),
+                    transformer.column_name(),
+                ]
+            } else {
+                vec![transformer.table_name(), transformer.column_name()]
+            };
+
+            if names.iter().any(|name| is_pattern(name)) {
+                let pattern = names
+                    .iter()
+                    .map(|name| to_regex(name))
+                    .collect::<Vec<_>>()
+                    .join(r"\.");
+
+                match Regex::new(format!("^{}$", pattern).as_str()) {
+                    Ok(regex) => patterns.push((regex, transformer.as_ref())),
+                    // the user probably provided an invalid regex
+                    Err(err) => panic!(
+                        "Invalid pattern in the transformer of column '{}': {}",
+                        transformer.database_and_table_and_column_name(),
+                        err
+                    ),
+                }
+            } else {
+                let _ = by_name.insert(names.join("."), transformer.as_ref());
+            }
+        }
+
+        TransformerSelector {
+            by_name,
+            patterns,
+            matched: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&'a dyn Transformer> {
+        if let Some(transformer) = self.by_name.get(name) {
+            return Some(*transformer);
+        }
+
+        if self.patterns.is_empty() {
+            return None;
+        }
+
+        if let Some(transformer) = self.matched.borrow().get(name) {
+            return *transformer;
+        }
+
+        let transformer = self
+            .patterns
+            .iter()
+            .find(|(regex, _)| regex.is_match(name))
+            .map(|(_, transformer)| *transformer);
+
+        let _ = self
+            .matched
+            .borrow_mut()
+            .insert(name.to_string(), transformer);
+
+        transformer
+    }
+}
+
+fn is_regex(name: &str) -> bool {
+    name.len() >= 2 && name.starts_with('/') && name.ends_with('/')
+}
+
+fn is_pattern(name: &str) -> bool {
+    is_regex(name) || name.contains('*') || name.contains('?')
+}
+
+/// the regex of a name, the wildcards don't match the dots separating the names
+fn to_regex(name: &str) -> String {
+    if is_regex(name) {
+        return format!("(?:{})", &name[1..name.len() - 1]);
+    }
+
+    name.chars()
+        .map(|c| match c {
+            '*' => "[^.]*".to_string(),
+            '?' => "[^.]".to_string(),
+            c => regex::escape(c.to_string().as_str()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transformer::transient::TransientTransformer;
+    use crate::transformer::Transformer;
+
+    use super::TransformerSelector;
+
+    #[test]
+    fn select_transformers() {
+        let transformers: Vec<Box<dyn Transformer>> = vec![
+            transformer("public", "users", "email"),
+            transformer("public", "*_audit", "/.*email.*/"),
+            transformer("public", "users", "/(first|last)_name/"),
+            transformer("*", "orders", "*"),
+        ];
+        let selector = TransformerSelector::new(&transformers, true);
+
+        for (name, index) in [
+            ("public.users.email", Some(0)),
+            ("public.users_audit.email", Some(1)),
+            ("public.orders_audit.billing_email_address", Some(1)),
+            ("public.users.first_name", Some(2)),
+            ("public.users.last_name", Some(2)),
+            ("public.users.middle_name", None),
+            ("public.users.first_name_2", None),
+            ("public.audit.email", None),
+            ("public.users_audit.phone", None),
+            ("sales.orders.total", Some(3)),
+            ("sales.orders.customer.name", None),
+        ] {
+            // twice, the second time from the matched transformers
+            for _ in 0..2 {
+                let selected = selector.get(name).map(|transformer| {
+                    transformers
+                        .iter()
+                        .position(|t| std::ptr::eq(t.as_ref(), transformer))
+                        .unwrap()
+                });
+                assert_eq!(selected, index, "{}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn select_transformers_without_database_name() {
+        let transformers: Vec<Box<dyn Transformer>> =
+            vec![transformer("public", "users?", "/.*email.*/")];
+        let selector = TransformerSelector::new(&transformers, false);
+
+        assert!(selector.get("users1.work_email").is_some());
+        assert!(selector.get("users.work_email").is_none());
+        assert!(selector.get("public.users1.work_email").is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn select_transformers_with_invalid_regex() {
+        let transformers: Vec<Box<dyn Transformer>> =
+            vec![transformer("public", "users", "/(email/")];
+        let _ = TransformerSelector::new(&transformers, true);
+    }
+
+    fn transformer(
+        database_name: &str,
+        table_name: &str,
+        column_name: &str,
+    ) -> Box<dyn Transformer> {
+        Box::new(TransientTransformer::new(
+            database_name,
+            table_name,
+            column_name,
+        ))
+    }
+}
@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+
+use crate::transformer::Transformer;
+use crate::types::Column;
+
+/// This struct is dedicated to replacing any value by a constant.
+/// The type of the constant is kept, so it is written as a number, a boolean, a quoted string or NULL in the dump.
+pub struct SetConstantTransformer {
+    database_name: String,
+    table_name: String,
+    column_name: String,
+    options: SetConstantTransformerOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct SetConstantTransformerOptions {
+    pub value: ConstantValue,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+#[serde(untagged)]
+pub enum ConstantValue {
+    #[default]
+    Null,
+    Boolean(bool),
+    Number(i64),
+    Float(f64),
+    String(String),
+}
+
+impl SetConstantTransformer {
+    pub fn new<S>(
+        database_name: S,
+        table_name: S,
+        column_name: S,
+        options: SetConstantTransformerOptions,
+    ) -> Self
+    where
+        S: Into<String>,
+    {
+        SetConstantTransformer {
+            database_name: database_name.into(),
+            table_name: table_name.into(),
+            column_name: column_name.into(),
+            options,
+        }
+    }
+}
+
+impl Default for SetConstantTransformer {
+    fn default() -> Self {
+        SetConstantTransformer {
+            database_name: String::default(),
+            table_name: String::default(),
+            column_name: String::default(),
+            options: SetConstantTransformerOptions::default(),
+        }
+    }
+}
+
+impl Transformer for SetConstantTransformer {
+    fn id(&self) -> &str {
+        "set-constant"
+    }
+
+    fn description(&self) -> &str {
+        "Replace the value by a constant string, number, boolean or NULL. [john.doe@company.com]->[redacted@example.com]"
+    }
+
+    fn database_name(&self) -> &str {
+        self.database_name.as_str()
+    }
+
+    fn table_name(&self) -> &str {
+        self.table_name.as_str()
+    }
+
+    fn column_name(&self) -> &str {
+        self.column_name.as_str()
+    }
+
+    fn transform(&self, column: Column) -> Column {
+        let column_name = column.name().to_string();
+
+        match &self.options.value {
+            ConstantValue::Null => Column::None(column_name),
+            ConstantValue::Boolean(value) => Column::BooleanValue(column_name, *value),
+            ConstantValue::Number(value) => Column::NumberValue(column_name, *value as i128),
+            ConstantValue::Float(value) => Column::FloatNumberValue(column_name, *value),
+            ConstantValue::String(value) => Column::StringValue(column_name, value.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{transformer::Transformer, types::Column};
+
+    use super::{ConstantValue, SetConstantTransformer, SetConstantTransformerOptions};
+
+    #[test]
+    fn transform_set_constant() {
+        let column =
+            || Column::StringValue("email".to_string(), "john.doe@company.com".to_string());
+
+        let transformed_column =
+            get_transformer(ConstantValue::String("redacted@example.com".to_string()))
+                .transform(column());
+        assert_eq!(
+            transformed_column.string_value().unwrap(),
+            "redacted@example.com"
+        );
+
+        let transformed_column = get_transformer(ConstantValue::Number(0)).transform(column());
+        assert_eq!(transformed_column.number_value(), Some(&0));
+
+        let transformed_column = get_transformer(ConstantValue::Float(0.5)).transform(column());
+        assert_eq!(transformed_column.float_number_value(), Some(&0.5));
+
+        let transformed_column = get_transformer(ConstantValue::Boolean(false)).transform(column());
+        assert!(matches!(transformed_column, Column::BooleanValue(_, false)));
+
+        let transformed_column = get_transformer(ConstantValue::Null).transform(column());
+        assert!(matches!(transformed_column, Column::None(name) if name == "email"));
+    }
+
+    fn get_transformer(value: ConstantValue) -> SetConstantTransformer {
+        SetConstantTransformer::new(
+            "github",
+            "users",
+            "email",
+            SetConstantTransformerOptions { value },
+        )
+    }
+}
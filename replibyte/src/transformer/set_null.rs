@@ -0,0 +1,80 @@
+use crate::transformer::Transformer;
+use crate::types::Column;
+
+/// This transformer replaces any value by NULL.
+pub struct SetNullTransformer {
+    database_name: String,
+    table_name: String,
+    column_name: String,
+}
+
+impl Default for SetNullTransformer {
+    fn default() -> Self {
+        SetNullTransformer {
+            database_name: String::default(),
+            table_name: String::default(),
+            column_name: String::default(),
+        }
+    }
+}
+
+impl SetNullTransformer {
+    pub fn new<S>(database_name: S, table_name: S, column_name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        SetNullTransformer {
+            table_name: table_name.into(),
+            column_name: column_name.into(),
+            database_name: database_name.into(),
+        }
+    }
+}
+
+impl Transformer for SetNullTransformer {
+    fn id(&self) -> &str {
+        "set-null"
+    }
+
+    fn description(&self) -> &str {
+        "Replace the value by NULL. [john.doe@company.com]->[NULL]"
+    }
+
+    fn database_name(&self) -> &str {
+        self.database_name.as_str()
+    }
+
+    fn table_name(&self) -> &str {
+        self.table_name.as_str()
+    }
+
+    fn column_name(&self) -> &str {
+        self.column_name.as_str()
+    }
+
+    fn transform(&self, column: Column) -> Column {
+        Column::None(column.name().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{transformer::Transformer, types::Column};
+
+    use super::SetNullTransformer;
+
+    #[test]
+    fn transform_set_null() {
+        let transformer = SetNullTransformer::new("github", "users", "email");
+
+        for column in [
+            Column::StringValue("email".to_string(), "john.doe@company.com".to_string()),
+            Column::NumberValue("email".to_string(), 42),
+            Column::BooleanValue("email".to_string(), true),
+            Column::None("email".to_string()),
+        ] {
+            let transformed_column = transformer.transform(column);
+            assert!(matches!(transformed_column, Column::None(name) if name == "email"));
+        }
+    }
+}
@@ -0,0 +1,512 @@
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+use xmlparser::{ElementEnd, Token, Tokenizer};
+
+use crate::config::TransformerTypeConfig;
+use crate::transformer::Transformer;
+use crate::types::Column;
+
+/// This struct is dedicated to transforming the texts and attributes at some XPath expressions of a XML column,
+/// e.g. `/order/customer/email` or `//card/@number`.
+/// Only the matching values are rewritten, the rest of the document is kept as is.
+pub struct XmlXPathTransformer {
+    database_name: String,
+    table_name: String,
+    column_name: String,
+    paths: Vec<(XPath, Box<dyn Transformer>)>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct XmlXPathTransformerOptions {
+    pub paths: Vec<XmlXPathConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct XmlXPathConfig {
+    pub xpath: String,
+
+    #[serde(flatten)]
+    pub transformer: TransformerTypeConfig,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+struct XPath {
+    steps: Vec<XPathStep>,
+    /// the attribute of the last element, or its text when there is none
+    attribute: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+struct XPathStep {
+    /// `//`, the element can be at any depth below the previous one
+    descendant: bool,
+    /// the name of the element, `None` for `*`
+    name: Option<String>,
+}
+
+impl XmlXPathTransformer {
+    pub fn new<S>(
+        database_name: S,
+        table_name: S,
+        column_name: S,
+        options: &XmlXPathTransformerOptions,
+    ) -> Result<Self, String>
+    where
+        S: Into<String>,
+    {
+        let database_name = database_name.into();
+        let table_name = table_name.into();
+        let column_name = column_name.into();
+
+        let paths = options
+            .paths
+            .iter()
+            .map(|config| {
+                let xpath = parse_xpath(config.xpath.as_str())?;
+                let transformer = config.transformer.transformer(
+                    database_name.as_str(),
+                    table_name.as_str(),
+                    column_name.as_str(),
+                );
+                Ok((xpath, transformer))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(XmlXPathTransformer {
+            database_name,
+            table_name,
+            column_name,
+            paths,
+        })
+    }
+
+    /// the replacements of the matching texts and attribute values, `None` if the document is not valid XML
+    fn replacements(
+        &self,
+        document: &str,
+        column_name: &str,
+        row: &[Column],
+    ) -> Option<Vec<(Range<usize>, String)>> {
+        let mut replacements = vec![];
+        let mut elements: Vec<(&str, &str)> = vec![];
+
+        for token in Tokenizer::from(document) {
+            match token.ok()? {
+                Token::ElementStart { prefix, local, .. } => {
+                    elements.push((prefix.as_str(), local.as_str()))
+                }
+                Token::ElementEnd {
+                    end: ElementEnd::Empty,
+                    ..
+                } => {
+                    let _ = elements.pop();
+                }
+                Token::ElementEnd {
+                    end: ElementEnd::Close(prefix, local),
+                    ..
+                } => {
+                    // the tokenizer does not check that the elements are closed in order
+                    let element = elements.pop();
+                    if element != Some((prefix.as_str(), local.as_str())) {
+                        return None;
+                    }
+                }
+                Token::Attribute {
+                    prefix,
+                    local,
+                    value,
+                    ..
+                } => {
+                    let path = self.paths.iter().find(|(xpath, _)| {
+                        is_name_matching(
+                            xpath.attribute.as_deref(),
+                            prefix.as_str(),
+                            local.as_str(),
+                        ) && is_matching(&xpath.steps, &elements)
+                    });
+
+                    if let Some((_, transformer)) = path {
+                        let new_value = transform_value(
+                            transformer.as_ref(),
+                            unescape(value.as_str()),
+                            column_name,
+                            row,
+                        );
+                        replacements.push((value.range(), escape(new_value.as_str())));
+                    }
+                }
+                Token::Text { text } if !text.as_str().trim().is_empty() => {
+                    if let Some(transformer) = self.text_transformer(&elements) {
+                        let new_value =
+                            transform_value(transformer, unescape(text.as_str()), column_name, row);
+                        replacements.push((text.range(), escape(new_value.as_str())));
+                    }
+                }
+                Token::Cdata { text, span } => {
+                    // the new value is written as a text, it could contain `]]>`
+                    if let Some(transformer) = self.text_transformer(&elements) {
+                        let new_value =
+                            transform_value(transformer, text.to_string(), column_name, row);
+                        replacements.push((span.range(), escape(new_value.as_str())));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if !elements.is_empty() {
+            return None;
+        }
+
+        Some(replacements)
+    }
+
+    fn text_transformer(&self, elements: &[(&str, &str)]) -> Option<&dyn Transformer> {
+        self.paths
+            .iter()
+            .find(|(xpath, _)| xpath.attribute.is_none() && is_matching(&xpath.steps, elements))
+            .map(|(_, transformer)| transformer.as_ref())
+    }
+}
+
+fn transform_value(
+    transformer: &dyn Transformer,
+    value: String,
+    column_name: &str,
+    row: &[Column],
+) -> String {
+    let column = Column::StringValue(column_name.to_string(), value);
+
+    match transformer.transform_row(column, row) {
+        Column::StringValue(_, value) => value,
+        Column::NumberValue(_, value) => value.to_string(),
+        Column::FloatNumberValue(_, value) => value.to_string(),
+        Column::CharValue(_, value) => value.to_string(),
+        Column::BooleanValue(_, value) => value.to_string(),
+        Column::None(_) => "".to_string(),
+    }
+}
+
+/// parse a XPath expression like `/order/customer/email`, `//customer/*/text()` or `//card/@number`
+fn parse_xpath(xpath: &str) -> Result<XPath, String> {
+    let invalid = || format!("invalid XPath expression '{}'", xpath);
+
+    let mut rest = xpath;
+    let mut steps = vec![];
+    let mut attribute = None;
+
+    while !rest.is_empty() {
+        if attribute.is_some() {
+            // the attribute is the last step
+            return Err(invalid());
+        }
+
+        let (descendant, after_slash) = match rest.strip_prefix("//") {
+            Some(after_slash) => (true, after_slash),
+            None => (false, rest.strip_prefix('/').ok_or_else(invalid)?),
+        };
+
+        let end = after_slash.find('/').unwrap_or(after_slash.len());
+        let name = &after_slash[..end];
+        rest = &after_slash[end..];
+
+        match name {
+            "" => return Err(invalid()),
+            "text()" if rest.is_empty() && !descendant => {}
+            "*" => steps.push(XPathStep {
+                descendant,
+                name: None,
+            }),
+            name if name.starts_with('@') && name.len() > 1 => {
+                // `//@id` is the attribute of any element
+                if descendant {
+                    steps.push(XPathStep {
+                        descendant,
+                        name: None,
+                    });
+                }
+                attribute = Some(name[1..].to_string());
+            }
+            name if name
+                .chars()
+                .all(|c| c.is_alphanumeric() || "_-.:".contains(c)) =>
+            {
+                steps.push(XPathStep {
+                    descendant,
+                    name: Some(name.to_string()),
+                })
+            }
+            _ => return Err(invalid()),
+        }
+    }
+
+    if steps.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(XPath { steps, attribute })
+}
+
+/// check that the elements from the root to the current one match the steps
+fn is_matching(steps: &[XPathStep], elements: &[(&str, &str)]) -> bool {
+    let (step, next_steps) = match steps.split_first() {
+        Some(split) => split,
+        None => return elements.is_empty(),
+    };
+
+    let is_step_matching = |(prefix, local): &(&str, &str)| match &step.name {
+        Some(name) => is_name_matching(Some(name.as_str()), prefix, local),
+        None => true,
+    };
+
+    if step.descendant {
+        (0..elements.len()).any(|position| {
+            is_step_matching(&elements[position])
+                && is_matching(next_steps, &elements[position + 1..])
+        })
+    } else {
+        match elements.split_first() {
+            Some((element, next_elements)) => {
+                is_step_matching(element) && is_matching(next_steps, next_elements)
+            }
+            None => false,
+        }
+    }
+}
+
+/// a name without prefix matches the local name of an element in any namespace
+fn is_name_matching(name: Option<&str>, prefix: &str, local: &str) -> bool {
+    match name {
+        Some(name) if name.contains(':') => name == format!("{}:{}", prefix, local),
+        Some(name) => name == local,
+        None => false,
+    }
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end = match rest.find(';') {
+            Some(end) => end,
+            None => break,
+        };
+
+        let character = match &rest[1..end] {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "apos" => Some('\''),
+            "quot" => Some('"'),
+            entity => match entity.strip_prefix("#x") {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => entity
+                    .strip_prefix('#')
+                    .and_then(|dec| dec.parse::<u32>().ok()),
+            }
+            .and_then(char::from_u32),
+        };
+
+        match character {
+            Some(character) => {
+                unescaped.push(character);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    unescaped.push_str(rest);
+    unescaped
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+impl Default for XmlXPathTransformer {
+    fn default() -> Self {
+        XmlXPathTransformer {
+            database_name: String::default(),
+            table_name: String::default(),
+            column_name: String::default(),
+            paths: vec![],
+        }
+    }
+}
+
+impl Transformer for XmlXPathTransformer {
+    fn id(&self) -> &str {
+        "xml-xpath"
+    }
+
+    fn description(&self) -> &str {
+        "Apply transformers to the texts and attributes at some XPath expressions of a XML document (string only). [<email>lucas@company.com</email>]->[<email>georges@example.org</email>]"
+    }
+
+    fn database_name(&self) -> &str {
+        self.database_name.as_str()
+    }
+
+    fn table_name(&self) -> &str {
+        self.table_name.as_str()
+    }
+
+    fn column_name(&self) -> &str {
+        self.column_name.as_str()
+    }
+
+    fn transform_row(&self, column: Column, row: &[Column]) -> Column {
+        match column {
+            Column::StringValue(column_name, value) => {
+                let replacements =
+                    match self.replacements(value.as_str(), column_name.as_str(), row) {
+                        Some(replacements) => replacements,
+                        None => return Column::StringValue(column_name, value),
+                    };
+
+                let mut new_value = String::with_capacity(value.len());
+                let mut position = 0;
+                for (range, replacement) in replacements {
+                    new_value.push_str(&value[position..range.start]);
+                    new_value.push_str(replacement.as_str());
+                    position = range.end;
+                }
+                new_value.push_str(&value[position..]);
+
+                Column::StringValue(column_name, new_value)
+            }
+            column => column,
+        }
+    }
+
+    fn transform(&self, column: Column) -> Column {
+        self.transform_row(column, &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::TransformerTypeConfig;
+    use crate::transformer::redacted::RedactedTransformerOptions;
+    use crate::{transformer::Transformer, types::Column};
+
+    use super::{parse_xpath, XmlXPathConfig, XmlXPathTransformer, XmlXPathTransformerOptions};
+
+    #[test]
+    fn parse_xpath_expressions() {
+        for xpath in [
+            "/order/customer/email",
+            "/order/customer/email/text()",
+            "//email",
+            "/order//*/@id",
+            "//@id",
+            "/ns:order/ns:email",
+        ] {
+            assert!(parse_xpath(xpath).is_ok(), "{}", xpath);
+        }
+
+        for xpath in [
+            "",
+            "order/email",
+            "/order//",
+            "/order/@id/email",
+            "/order/email[1]",
+            "/@",
+            "//text()",
+        ] {
+            assert!(parse_xpath(xpath).is_err(), "{}", xpath);
+        }
+    }
+
+    #[test]
+    fn transform_xml_xpaths() {
+        let transformer = get_transformer(&["/order/customer/email", "//card/@number"]);
+        let column = Column::StringValue(
+            "data".to_string(),
+            r#"<?xml version="1.0"?>
+<order id="42">
+  <customer><name>Lucas</name><email>lucas&amp;co@company.com</email></customer>
+  <items><item><card number='4242424242424242'/></item><card number="5555555555554444"></card></items>
+  <email>support@company.com</email>
+</order>"#
+                .to_string(),
+        );
+        let transformed_column = transformer.transform(column);
+        let transformed_value = transformed_column.string_value().unwrap();
+
+        assert_eq!(
+            transformed_value,
+            r#"<?xml version="1.0"?>
+<order id="42">
+  <customer><name>Lucas</name><email>luc*****</email></customer>
+  <items><item><card number='424*****'/></item><card number="555*****"></card></items>
+  <email>support@company.com</email>
+</order>"#
+        );
+    }
+
+    #[test]
+    fn transform_xml_xpath_with_cdata_and_namespaces() {
+        let transformer = get_transformer(&["//email"]);
+        let column = Column::StringValue(
+            "data".to_string(),
+            "<a:order xmlns:a=\"urn:a\"><a:email><![CDATA[lu<cas@company.com]]></a:email></a:order>"
+                .to_string(),
+        );
+        let transformed_column = transformer.transform(column);
+
+        assert_eq!(
+            transformed_column.string_value().unwrap(),
+            "<a:order xmlns:a=\"urn:a\"><a:email>lu&lt;*****</a:email></a:order>"
+        );
+    }
+
+    #[test]
+    fn transform_xml_xpath_with_other_values() {
+        let transformer = get_transformer(&["//email"]);
+
+        for value in ["not xml", "<email>lucas@company.com", ""] {
+            let column = Column::StringValue("data".to_string(), value.to_string());
+            let transformed_column = transformer.transform(column);
+            assert_eq!(transformed_column.string_value().unwrap(), value);
+        }
+
+        let column = Column::None("data".to_string());
+        assert!(matches!(transformer.transform(column), Column::None(_)));
+    }
+
+    fn get_transformer(xpaths: &[&str]) -> XmlXPathTransformer {
+        let options = XmlXPathTransformerOptions {
+            paths: xpaths
+                .iter()
+                .map(|xpath| XmlXPathConfig {
+                    xpath: xpath.to_string(),
+                    transformer: TransformerTypeConfig::Redacted(Some(
+                        RedactedTransformerOptions {
+                            character: '*',
+                            width: 5,
+                        },
+                    )),
+                })
+                .collect(),
+        };
+
+        XmlXPathTransformer::new("github", "users", "data", &options).unwrap()
+    }
+}
@@ -1,9 +1,11 @@
 use prettytable::{format, Table};
-use std::io::{Error, ErrorKind, Read};
+use std::io::{stdin, stdout, Read, Write};
 use std::process::Child;
 use std::time::{SystemTime, UNIX_EPOCH};
 use which::which;
 
+use crate::errors::ReplibyteError;
+
 pub fn epoch_millis() -> u128 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -20,6 +22,26 @@ pub fn table() -> Table {
     table
 }
 
+/// parse a number of days like `14d`
+pub fn parse_days(value: &str) -> Option<u64> {
+    value.strip_suffix('d')?.parse::<u64>().ok()
+}
+
+/// ask a yes or no question on the terminal, no is the default answer
+pub fn confirm(question: &str) -> Result<bool, ReplibyteError> {
+    print!("{} [y/N] ", question);
+    let _ = stdout().flush()?;
+
+    let mut answer = String::new();
+    let _ = stdin().read_line(&mut answer)?;
+
+    Ok(is_yes(answer.as_str()))
+}
+
+fn is_yes(answer: &str) -> bool {
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
 /// converts Bytes into Bytes, KB, MB, GB or TB
 pub fn to_human_readable_unit(bytes: usize) -> String {
     match bytes {
@@ -33,50 +55,133 @@ pub fn to_human_readable_unit(bytes: usize) -> String {
 }
 
 /// check for binary presence in PATH
-pub fn binary_exists(binary_name: &str) -> Result<(), Error> {
+pub fn binary_exists(binary_name: &str) -> Result<(), ReplibyteError> {
     let _ = which(binary_name).map_err(|_| {
-        Error::new(
-            ErrorKind::Other,
-            format!("cannot find '{}' binary in path", binary_name),
-        )
+        ReplibyteError::Command(format!("cannot find '{}' binary in path", binary_name))
     })?;
 
     Ok(())
 }
 
 // wait for the end of a process and handle errors
-pub fn wait_for_command(process: &mut Child) -> Result<(), Error> {
+pub fn wait_for_command(process: &mut Child) -> Result<(), ReplibyteError> {
     match process.wait() {
         Ok(exit_status) => {
             if !exit_status.success() {
                 if let Some(stderr) = process.stderr.take().as_mut() {
                     let mut buffer = String::new();
                     let error = match stderr.read_to_string(&mut buffer) {
-                        Ok(_) => Error::new(ErrorKind::Other, format!("{}", buffer)),
-                        Err(err) => Error::new(ErrorKind::Other, format!("{}", err)),
+                        Ok(_) => ReplibyteError::Command(format!("{}", buffer)),
+                        Err(err) => ReplibyteError::Command(format!("{}", err)),
                     };
 
-                    return Err(Error::new(
-                        ErrorKind::Other,
-                        format!("command error: {}", error),
-                    ));
+                    return Err(ReplibyteError::Command(format!("command error: {}", error)));
                 }
 
-                return Err(Error::new(
-                    ErrorKind::Other,
-                    format!("command error: {}", exit_status.to_string()),
-                ));
+                return Err(ReplibyteError::Command(format!(
+                    "command error: {}",
+                    exit_status.to_string()
+                )));
             }
 
             Ok(())
         }
-        Err(err) => Err(Error::new(
-            ErrorKind::Other,
-            format!("command error: {}", err),
-        )),
+        Err(err) => Err(ReplibyteError::Command(format!("command error: {}", err))),
     }
 }
 
 pub fn get_replibyte_version() -> &'static str {
     env!("CARGO_PKG_VERSION")
 }
+
+/// strip a keywords prefix and the following whitespaces
+pub fn strip_prefix_ignore_case<'a>(value: &'a str, prefix: &str) -> Option<&'a str> {
+    match value.get(..prefix.len()) {
+        Some(start) if start.eq_ignore_ascii_case(prefix) => {
+            let rest = &value[prefix.len()..];
+
+            // the prefix must be a whole word, i.e. `ONLY` is not a prefix of `only_users`
+            match rest.chars().next() {
+                Some(ch) if ch.is_alphanumeric() || ch == '_' || ch == '$' => None,
+                _ => Some(rest.trim_start()),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// parse a (schema qualified) identifier quoted with "", `` or [] and return it without the quotes,
+/// with the rest of the value
+pub fn parse_identifier(value: &str) -> Option<(String, &str)> {
+    let mut parts = vec![];
+    let mut rest = value.trim_start();
+
+    loop {
+        let (part, next_rest) = match rest.chars().next()? {
+            quote @ ('"' | '`' | '[') => {
+                let closing_quote = if quote == '[' { ']' } else { quote };
+                let end_idx = rest[1..].find(closing_quote)? + 1;
+                (&rest[1..end_idx], &rest[end_idx + 1..])
+            }
+            _ => {
+                let end_idx = rest
+                    .find(|ch: char| !(ch.is_alphanumeric() || ch == '_' || ch == '$'))
+                    .unwrap_or(rest.len());
+                (&rest[..end_idx], &rest[end_idx..])
+            }
+        };
+
+        if part.is_empty() {
+            return None;
+        }
+
+        parts.push(part);
+
+        match next_rest.strip_prefix('.') {
+            Some(next_rest) => rest = next_rest,
+            None => return Some((parts.join("."), next_rest)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::{is_yes, parse_days, parse_identifier, strip_prefix_ignore_case};
+
+    #[test]
+    fn identifiers() {
+        assert_eq!(
+            parse_identifier("public.\"user accounts\" (id)"),
+            Some(("public.user accounts".to_string(), " (id)"))
+        );
+        assert_eq!(parse_identifier("(id)"), None);
+    }
+
+    #[test]
+    fn strip_prefixes() {
+        assert_eq!(
+            strip_prefix_ignore_case("alter table  users", "ALTER TABLE"),
+            Some("users")
+        );
+        assert_eq!(strip_prefix_ignore_case("only_users", "ONLY"), None);
+        assert_eq!(strip_prefix_ignore_case("users", "ALTER TABLE"), None);
+    }
+
+    #[test]
+    fn days() {
+        assert_eq!(parse_days("7d"), Some(7));
+        assert_eq!(parse_days("0d"), Some(0));
+        assert_eq!(parse_days("7"), None);
+        assert_eq!(parse_days("-7d"), None);
+        assert_eq!(parse_days("d"), None);
+    }
+
+    #[test]
+    fn answers() {
+        assert!(is_yes("y\n"));
+        assert!(is_yes(" YES "));
+        assert!(!is_yes("\n"));
+        assert!(!is_yes("no"));
+        assert!(!is_yes(""));
+    }
+}
@@ -1,12 +1,18 @@
 use std::collections::HashSet;
 use std::io::Error;
 
-mod dedup;
+pub mod mongodb;
+pub mod mysql;
 pub mod postgres;
+mod predicate;
+mod related_rows;
 mod utils;
 
 pub type Bytes = Vec<u8>;
 
+type Database = String;
+type Table = String;
+
 pub trait Subset {
     fn read<F: FnMut(String), P: FnMut(Progress)>(&self, data: F, progress: P)
         -> Result<(), Error>;
@@ -29,6 +35,69 @@ impl Progress {
     }
 }
 
+/// How the rows a subset starts from are picked in a table, the rows related to them are added to the subset
+pub enum SubsetStrategy<'a> {
+    RandomPercent {
+        database: &'a str,
+        table: &'a str,
+        percent: u8,
+    },
+    /// at most `limit` rows, spread over the table
+    Limit {
+        database: &'a str,
+        table: &'a str,
+        limit: usize,
+    },
+    /// the rows matching a `where` predicate, see `Predicate`
+    Predicate {
+        database: &'a str,
+        table: &'a str,
+        predicate: &'a str,
+    },
+}
+
+impl<'a> SubsetStrategy<'a> {
+    pub fn random(database: &'a str, table: &'a str, percent: u8) -> Self {
+        SubsetStrategy::RandomPercent {
+            database,
+            table,
+            percent,
+        }
+    }
+
+    pub fn limit(database: &'a str, table: &'a str, limit: usize) -> Self {
+        SubsetStrategy::Limit {
+            database,
+            table,
+            limit,
+        }
+    }
+
+    pub fn predicate(database: &'a str, table: &'a str, predicate: &'a str) -> Self {
+        SubsetStrategy::Predicate {
+            database,
+            table,
+            predicate,
+        }
+    }
+
+    pub fn database(&self) -> &'a str {
+        match *self {
+            SubsetStrategy::RandomPercent { database, .. } => database,
+            SubsetStrategy::Limit { database, .. } => database,
+            SubsetStrategy::Predicate { database, .. } => database,
+        }
+    }
+
+    pub fn table(&self) -> &'a str {
+        match *self {
+            SubsetStrategy::RandomPercent { table, .. } => table,
+            SubsetStrategy::Limit { table, .. } => table,
+            SubsetStrategy::Predicate { table, .. } => table,
+        }
+    }
+}
+
 #[derive(Debug, Hash, Eq, PartialEq)]
 pub struct PassthroughTable<'a> {
     pub database: &'a str,
@@ -46,11 +115,17 @@ impl<'a> PassthroughTable<'a> {
 
 pub struct SubsetOptions<'a> {
     pub passthrough_tables: &'a HashSet<PassthroughTable<'a>>,
+    /// pick the rows of the `RandomPercent` and `Limit` strategies randomly, the same rows for the same seed.
+    /// Without a seed, the rows are picked at regular intervals.
+    pub seed: Option<u64>,
 }
 
 impl<'a> SubsetOptions<'a> {
-    pub fn new(passthrough_tables: &'a HashSet<PassthroughTable<'a>>) -> Self {
-        SubsetOptions { passthrough_tables }
+    pub fn new(passthrough_tables: &'a HashSet<PassthroughTable<'a>>, seed: Option<u64>) -> Self {
+        SubsetOptions {
+            passthrough_tables,
+            seed,
+        }
     }
 }
 
@@ -0,0 +1,394 @@
+use crate::predicate::Predicate;
+use crate::related_rows::{RelatedRows, RowIndex, TableRows};
+use crate::{utils, Database, PassthroughTable, SubsetOptions, SubsetStrategy, SubsetTable, Table};
+use bson::{Bson, Document};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashMap;
+use std::io::Error;
+
+/// Subset of the collections of a mongodump archive, by `<database>.<collection>`.
+/// The collections have no foreign keys, the references between them are declared like `$lookup` stages:
+/// a field of the documents of a collection (`localField`) referencing a field of the documents of another collection (`foreignField`).
+pub struct MongodbSubset<'a> {
+    subset_table_by_database_and_table_name: HashMap<(Database, Table), SubsetTable>,
+    subset_strategies: Vec<SubsetStrategy<'a>>,
+    subset_options: SubsetOptions<'a>,
+}
+
+impl<'a> MongodbSubset<'a> {
+    pub fn new(
+        subset_tables: Vec<SubsetTable>,
+        subset_strategies: Vec<SubsetStrategy<'a>>,
+        subset_options: SubsetOptions<'a>,
+    ) -> Self {
+        let mut subset_table_by_database_and_table_name =
+            HashMap::<(Database, Table), SubsetTable>::new();
+
+        // a collection can have several references
+        for subset_table in subset_tables {
+            subset_table_by_database_and_table_name
+                .entry((subset_table.database.clone(), subset_table.table.clone()))
+                .or_insert_with(|| {
+                    SubsetTable::new(
+                        subset_table.database.as_str(),
+                        subset_table.table.as_str(),
+                        vec![],
+                    )
+                })
+                .relations
+                .extend(subset_table.relations);
+        }
+
+        MongodbSubset {
+            subset_table_by_database_and_table_name,
+            subset_strategies,
+            subset_options,
+        }
+    }
+
+    /// Keep the documents of the subset in the collections, see `PostgresSubset` for the algorithm.
+    /// The documents of the passthrough collections are all kept.
+    pub fn subset(&self, collections: &mut HashMap<String, Vec<Document>>) -> Result<(), Error> {
+        // the documents are indexed one after the other, in the collections sorted by name
+        let mut prefixes = collections.keys().cloned().collect::<Vec<_>>();
+        prefixes.sort();
+
+        let mut first_row_index_by_prefix = HashMap::<String, RowIndex>::new();
+        let mut row_idx = 0usize;
+        for prefix in prefixes {
+            let total_documents = collections[&prefix].len();
+            let _ = first_row_index_by_prefix.insert(prefix, row_idx);
+            row_idx += total_documents;
+        }
+
+        let reference_rows = self.reference_rows(collections, &first_row_index_by_prefix)?;
+
+        let mut related_rows = RelatedRows::new(
+            &self.subset_table_by_database_and_table_name,
+            |database, table| {
+                let prefix = format!("{}.{}", database, table);
+
+                match (
+                    collections.get(&prefix),
+                    first_row_index_by_prefix.get(&prefix),
+                ) {
+                    (Some(documents), Some(first_row_idx)) => {
+                        Ok(table_rows(documents, *first_row_idx))
+                    }
+                    // the collection has no documents
+                    _ => Ok(TableRows::new(vec![])),
+                }
+            },
+        );
+
+        for (database, table, row) in reference_rows {
+            let _ = related_rows.visit(database, table, row)?;
+        }
+
+        let rows = related_rows.rows();
+
+        for (prefix, documents) in collections.iter_mut() {
+            let is_passthrough_collection = match prefix.split_once('.') {
+                Some((database, collection)) => self
+                    .subset_options
+                    .passthrough_tables
+                    .contains(&PassthroughTable::new(database, collection)),
+                None => false,
+            };
+
+            if is_passthrough_collection {
+                continue;
+            }
+
+            let mut row_idx = first_row_index_by_prefix[prefix];
+            documents.retain(|_| {
+                let is_in_subset = rows.contains(&row_idx);
+                row_idx += 1;
+                is_in_subset
+            });
+        }
+
+        Ok(())
+    }
+
+    /// documents picked by the subset strategies, with their database and collection
+    fn reference_rows(
+        &self,
+        collections: &HashMap<String, Vec<Document>>,
+        first_row_index_by_prefix: &HashMap<String, RowIndex>,
+    ) -> Result<Vec<(&'a str, &'a str, RowIndex)>, Error> {
+        let mut reference_rows = vec![];
+        // a single generator for all the strategies, so the same seed always picks the same documents
+        let mut rng = self.subset_options.seed.map(ChaCha8Rng::seed_from_u64);
+
+        for subset_strategy in &self.subset_strategies {
+            let (database, table) = (subset_strategy.database(), subset_strategy.table());
+            let prefix = format!("{}.{}", database, table);
+
+            let (documents, first_row_idx) = match (
+                collections.get(&prefix),
+                first_row_index_by_prefix.get(&prefix),
+            ) {
+                (Some(documents), Some(first_row_idx)) => (documents, *first_row_idx),
+                // the collection has no documents
+                _ => continue,
+            };
+
+            let rows = match *subset_strategy {
+                SubsetStrategy::RandomPercent { percent, .. } => {
+                    percent_of_positions(percent, documents.len(), rng.as_mut())
+                        .into_iter()
+                        .map(|position| first_row_idx + position)
+                        .collect::<Vec<_>>()
+                }
+                SubsetStrategy::Limit { limit, .. } => {
+                    limit_of_positions(limit, documents.len(), rng.as_mut())
+                        .into_iter()
+                        .map(|position| first_row_idx + position)
+                        .collect::<Vec<_>>()
+                }
+                SubsetStrategy::Predicate { predicate, .. } => {
+                    let predicate = Predicate::parse(predicate)?;
+                    table_rows(documents, first_row_idx).rows_matching(&predicate)
+                }
+            };
+
+            reference_rows.extend(rows.into_iter().map(|row| (database, table, row)));
+        }
+
+        Ok(reference_rows)
+    }
+}
+
+/// positions of around `percent`% of `total` documents, at regular intervals or random with a generator
+fn percent_of_positions(percent: u8, total: usize, rng: Option<&mut ChaCha8Rng>) -> Vec<usize> {
+    if percent == 0 || total == 0 {
+        return vec![];
+    }
+
+    let percent = if percent > 100 { 100 } else { percent };
+
+    let total_rows_to_pick = total as f32 * percent as f32 / 100.0;
+
+    if let Some(rng) = rng {
+        let mut positions =
+            utils::random_positions(rng, total, total_rows_to_pick.round() as usize)
+                .into_iter()
+                .collect::<Vec<_>>();
+        positions.sort_unstable();
+        return positions;
+    }
+
+    let modulo = (total as f32 / total_rows_to_pick) as usize;
+
+    (1..=total)
+        .filter(|counter| counter % modulo == 0)
+        .map(|counter| counter - 1)
+        .collect()
+}
+
+/// positions of at most `limit` of `total` documents, at regular intervals or random with a generator
+fn limit_of_positions(limit: usize, total: usize, rng: Option<&mut ChaCha8Rng>) -> Vec<usize> {
+    if limit == 0 || total == 0 {
+        return vec![];
+    }
+
+    let limit = limit.min(total);
+
+    if let Some(rng) = rng {
+        let mut positions = utils::random_positions(rng, total, limit)
+            .into_iter()
+            .collect::<Vec<_>>();
+        positions.sort_unstable();
+        return positions;
+    }
+
+    (0..total)
+        .filter(|counter| (counter + 1) * limit / total > counter * limit / total)
+        .collect()
+}
+
+/// the values of the fields of the documents of a collection, the nested fields are named with their path (e.g. `address.country`)
+fn table_rows(documents: &[Document], first_row_idx: RowIndex) -> TableRows {
+    let mut columns = vec![];
+    let mut column_idx_by_name = HashMap::<String, usize>::new();
+    let mut rows = Vec::with_capacity(documents.len());
+
+    for document in documents {
+        let mut fields = vec![];
+        document_values("", document, &mut fields);
+
+        let mut values = vec![];
+        for (name, value) in fields {
+            let column_idx = *column_idx_by_name.entry(name.clone()).or_insert_with(|| {
+                columns.push(name);
+                columns.len() - 1
+            });
+
+            if values.len() <= column_idx {
+                values.resize(column_idx + 1, None);
+            }
+
+            values[column_idx] = value;
+        }
+
+        rows.push(values);
+    }
+
+    let mut table_rows = TableRows::new(columns);
+    for (position, values) in rows.into_iter().enumerate() {
+        table_rows.insert(first_row_idx + position, values);
+    }
+
+    table_rows
+}
+
+fn document_values(path: &str, document: &Document, values: &mut Vec<(String, Option<String>)>) {
+    for (key, bson) in document {
+        let name = if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", path, key)
+        };
+
+        match bson {
+            Bson::Document(nested_document) => {
+                document_values(name.as_str(), nested_document, values)
+            }
+            // the elements of the arrays are not referenced
+            Bson::Array(_) => {}
+            Bson::Null | Bson::Undefined => values.push((name, None)),
+            Bson::String(value) => values.push((name, Some(value.clone()))),
+            Bson::ObjectId(id) => values.push((name, Some(id.to_hex()))),
+            Bson::DateTime(time) => values.push((name, Some(time.to_rfc3339_string()))),
+            bson => values.push((name, Some(bson.to_string()))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mongodb::MongodbSubset;
+    use crate::{
+        PassthroughTable, SubsetOptions, SubsetStrategy, SubsetTable, SubsetTableRelation,
+    };
+    use bson::{doc, oid::ObjectId, Document};
+    use std::collections::{HashMap, HashSet};
+
+    fn collections() -> (HashMap<String, Vec<Document>>, Vec<ObjectId>) {
+        let user_ids = (0..4).map(|_| ObjectId::new()).collect::<Vec<_>>();
+
+        let mut collections = HashMap::new();
+        let _ = collections.insert(
+            "test.users".to_string(),
+            user_ids
+                .iter()
+                .enumerate()
+                .map(|(idx, id)| doc! { "_id": *id, "name": format!("user {}", idx), "address": { "country": if idx % 2 == 0 { "FR" } else { "US" } } })
+                .collect::<Vec<_>>(),
+        );
+        let _ = collections.insert(
+            "test.orders".to_string(),
+            vec![
+                doc! { "_id": 1, "user_id": user_ids[1], "country": "US" },
+                doc! { "_id": 2, "user_id": user_ids[3], "country": "US" },
+                doc! { "_id": 3, "user_id": user_ids[3], "country": "FR" },
+                doc! { "_id": 4, "user_id": user_ids[0], "country": "FR" },
+            ],
+        );
+        let _ = collections.insert(
+            "test.countries".to_string(),
+            vec![doc! { "code": "FR" }, doc! { "code": "US" }],
+        );
+
+        (collections, user_ids)
+    }
+
+    fn ids(collections: &HashMap<String, Vec<Document>>, prefix: &str) -> Vec<String> {
+        collections[prefix]
+            .iter()
+            .map(|document| document.get("_id").unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn check_mongodb_subset() {
+        let (mut collections, user_ids) = collections();
+
+        let s = HashSet::new();
+        let subset = MongodbSubset::new(
+            vec![SubsetTable::new(
+                "test",
+                "orders",
+                vec![SubsetTableRelation::new("test", "users", "user_id", "_id")],
+            )],
+            vec![SubsetStrategy::random("test", "users", 50)],
+            SubsetOptions::new(&s, None),
+        );
+
+        subset.subset(&mut collections).unwrap();
+
+        // users 1 and 3 are picked, with their orders
+        assert_eq!(
+            ids(&collections, "test.users"),
+            vec![user_ids[1].to_string(), user_ids[3].to_string()]
+                .into_iter()
+                .map(|id| format!("ObjectId(\"{}\")", id))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(ids(&collections, "test.orders"), vec!["1", "2", "3"]);
+        // the collections without reference from the picked documents are empty
+        assert!(collections["test.countries"].is_empty());
+    }
+
+    #[test]
+    fn check_mongodb_subset_strategies() {
+        let (mut collections, _) = collections();
+
+        let mut s = HashSet::new();
+        s.insert(PassthroughTable::new("test", "countries"));
+        let subset = MongodbSubset::new(
+            vec![
+                SubsetTable::new(
+                    "test",
+                    "orders",
+                    vec![SubsetTableRelation::new("test", "users", "user_id", "_id")],
+                ),
+                SubsetTable::new(
+                    "test",
+                    "users",
+                    vec![SubsetTableRelation::new(
+                        "test",
+                        "countries",
+                        "address.country",
+                        "code",
+                    )],
+                ),
+            ],
+            vec![
+                SubsetStrategy::predicate("test", "orders", "country = 'FR'"),
+                SubsetStrategy::limit("test", "users", 1),
+            ],
+            SubsetOptions::new(&s, Some(42)),
+        );
+
+        subset.subset(&mut collections).unwrap();
+
+        // the orders 3 and 4, their users, and the orders of the picked user
+        let orders = ids(&collections, "test.orders");
+        assert!(orders.contains(&"3".to_string()));
+        assert!(orders.contains(&"4".to_string()));
+        assert!(collections["test.users"].len() >= 2);
+        assert_eq!(collections["test.countries"].len(), 2);
+
+        let mut invalid_collections = HashMap::new();
+        let subset = MongodbSubset::new(
+            vec![],
+            vec![SubsetStrategy::predicate("test", "orders", "country")],
+            SubsetOptions::new(&s, None),
+        );
+        let _ = invalid_collections.insert("test.orders".to_string(), vec![doc! { "_id": 1 }]);
+        assert!(subset.subset(&mut invalid_collections).is_err());
+    }
+}
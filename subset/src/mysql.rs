@@ -0,0 +1,706 @@
+use crate::predicate::Predicate;
+use crate::related_rows::{RelatedRows, RowIndex, TableRows};
+use crate::{
+    utils, Database, PassthroughTable, Progress, Subset, SubsetOptions, SubsetStrategy,
+    SubsetTable, SubsetTableRelation, Table,
+};
+use dump_parser::mysql::{
+    get_column_names_from_insert_into_query, get_column_values_from_insert_into_query,
+    get_single_quoted_string_value_at_position, get_tokens_from_query_str,
+    match_keyword_at_position, Keyword, Token,
+};
+use dump_parser::utils::{list_mysql_queries_from_dump_reader, ListQueryResult};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufReader, Error, ErrorKind, Read};
+use std::path::Path;
+
+#[derive(Debug)]
+struct ForeignKey {
+    from_property: String,
+    to_table: String,
+    to_property: String,
+}
+
+struct TableStats {
+    table: String,
+    columns: Vec<String>,
+    total_rows: usize,
+    first_insert_into_row_index: usize,
+    last_insert_into_row_index: usize,
+}
+
+/// Subset of a dump of a single database made by mysqldump, with a `INSERT INTO` query by row.
+/// The tables do not have a database in the dump, they all belong to the database of the subset strategies.
+pub struct MysqlSubset<'a> {
+    subset_table_by_database_and_table_name: HashMap<(Database, Table), SubsetTable>,
+    dump: &'a Path,
+    database: &'a str,
+    subset_strategies: Vec<SubsetStrategy<'a>>,
+    subset_options: SubsetOptions<'a>,
+}
+
+impl<'a> MysqlSubset<'a> {
+    pub fn new(
+        dump: &'a Path,
+        subset_strategies: Vec<SubsetStrategy<'a>>,
+        subset_options: SubsetOptions<'a>,
+    ) -> Result<Self, Error> {
+        let database = match subset_strategies.first() {
+            Some(subset_strategy) => subset_strategy.database(),
+            None => {
+                return Err(Error::new(
+                    ErrorKind::Other,
+                    "a subset needs at least one strategy",
+                ))
+            }
+        };
+
+        Ok(MysqlSubset {
+            subset_table_by_database_and_table_name: get_subset_table_by_database_and_table_name(
+                database,
+                BufReader::new(File::open(dump)?),
+            )?,
+            dump,
+            database,
+            subset_strategies,
+            subset_options,
+        })
+    }
+
+    fn dump_reader(&self) -> BufReader<File> {
+        BufReader::new(File::open(self.dump).unwrap())
+    }
+
+    /// rows picked by the subset strategies, with their table
+    fn reference_rows(
+        &self,
+        table_stats: &HashMap<Table, TableStats>,
+    ) -> Result<Vec<(&'a str, RowIndex)>, Error> {
+        let mut reference_rows = vec![];
+        // a single generator for all the strategies, so the same seed always picks the same rows
+        let mut rng = self.subset_options.seed.map(ChaCha8Rng::seed_from_u64);
+
+        for subset_strategy in &self.subset_strategies {
+            let table = subset_strategy.table();
+
+            let table_stats = match table_stats.get(table) {
+                Some(table_stats) => table_stats,
+                // the table has no rows
+                None => continue,
+            };
+
+            let rows = match *subset_strategy {
+                SubsetStrategy::RandomPercent { percent, .. } => list_percent_of_insert_into_rows(
+                    percent,
+                    table_stats,
+                    self.dump_reader(),
+                    rng.as_mut(),
+                )?,
+                SubsetStrategy::Limit { limit, .. } => list_limit_of_insert_into_rows(
+                    limit,
+                    table_stats,
+                    self.dump_reader(),
+                    rng.as_mut(),
+                )?,
+                SubsetStrategy::Predicate { predicate, .. } => {
+                    let predicate = Predicate::parse(predicate)?;
+                    table_rows(self.dump_reader(), table_stats)?.rows_matching(&predicate)
+                }
+            };
+
+            reference_rows.extend(rows.into_iter().map(|row| (table, row)));
+        }
+
+        Ok(reference_rows)
+    }
+}
+
+impl<'a> Subset for MysqlSubset<'a> {
+    /// Return every subset rows, see `PostgresSubset` for the algorithm.
+    /// The queries of mysqldump are grouped by table (`CREATE TABLE ...` then `INSERT INTO ...`),
+    /// so the dump is sent as it is, without the `INSERT INTO` rows which are not in the subset.
+    fn read<F: FnMut(String), P: FnMut(Progress)>(
+        &self,
+        mut data: F,
+        mut progress: P,
+    ) -> Result<(), Error> {
+        let table_stats = table_stats_by_table_name(self.dump_reader())?;
+        let rows = self.reference_rows(&table_stats)?;
+
+        let total_rows = table_stats
+            .values()
+            .fold(0usize, |acc, y| acc + y.total_rows);
+
+        let total_rows_to_process = rows.len();
+        let mut processed_rows = 0usize;
+
+        progress(Progress {
+            total_rows,
+            total_rows_to_process,
+            processed_rows,
+            last_process_time: 0,
+        });
+
+        let mut related_rows =
+            RelatedRows::new(&self.subset_table_by_database_and_table_name, |_, table| {
+                match table_stats.get(table) {
+                    Some(table_stats) => table_rows(self.dump_reader(), table_stats),
+                    // the table has no rows
+                    None => Ok(TableRows::new(vec![])),
+                }
+            });
+
+        // visit INSERT INTO rows
+        for (table, row) in rows {
+            let start_time = utils::epoch_millis();
+            let _ = related_rows.visit(self.database, table, row)?;
+
+            processed_rows += 1;
+
+            progress(Progress {
+                total_rows,
+                total_rows_to_process,
+                processed_rows,
+                last_process_time: utils::epoch_millis() - start_time,
+            });
+        }
+
+        let rows = related_rows.rows();
+
+        let mut query_idx = 0usize;
+        let _ = list_mysql_queries_from_dump_reader(self.dump_reader(), |query| {
+            let tokens = get_tokens_from_query_str(query);
+
+            let is_in_subset = match get_insert_into_table_name(&tokens) {
+                // copy all rows from passthrough tables
+                Some(table) => {
+                    rows.contains(&query_idx)
+                        || self
+                            .subset_options
+                            .passthrough_tables
+                            .contains(&PassthroughTable::new(self.database, table))
+                }
+                None => true,
+            };
+
+            if is_in_subset {
+                data(query.to_string());
+            }
+
+            query_idx += 1;
+            ListQueryResult::Continue
+        })?;
+
+        Ok(())
+    }
+}
+
+fn list_percent_of_insert_into_rows<R: Read>(
+    percent: u8,
+    table_stats: &TableStats,
+    dump_reader: BufReader<R>,
+    rng: Option<&mut ChaCha8Rng>,
+) -> Result<Vec<RowIndex>, Error> {
+    let mut insert_into_rows = vec![];
+
+    if percent == 0 || table_stats.total_rows == 0 {
+        return Ok(insert_into_rows);
+    }
+
+    let percent = if percent > 100 { 100 } else { percent };
+
+    let total_rows_to_pick = table_stats.total_rows as f32 * percent as f32 / 100.0;
+
+    if let Some(rng) = rng {
+        let positions = utils::random_positions(
+            rng,
+            table_stats.total_rows,
+            total_rows_to_pick.round() as usize,
+        );
+
+        return list_insert_into_rows_at_positions(&positions, table_stats, dump_reader);
+    }
+
+    let modulo = (table_stats.total_rows as f32 / total_rows_to_pick) as usize;
+
+    let mut counter = 1usize;
+    let _ = list_insert_into_rows(dump_reader, table_stats, |row_idx, _| {
+        if counter % modulo == 0 {
+            insert_into_rows.push(row_idx);
+        }
+
+        counter += 1;
+    })?;
+
+    Ok(insert_into_rows)
+}
+
+fn list_limit_of_insert_into_rows<R: Read>(
+    limit: usize,
+    table_stats: &TableStats,
+    dump_reader: BufReader<R>,
+    rng: Option<&mut ChaCha8Rng>,
+) -> Result<Vec<RowIndex>, Error> {
+    let mut insert_into_rows = vec![];
+
+    if limit == 0 || table_stats.total_rows == 0 {
+        return Ok(insert_into_rows);
+    }
+
+    let limit = limit.min(table_stats.total_rows);
+
+    if let Some(rng) = rng {
+        let positions = utils::random_positions(rng, table_stats.total_rows, limit);
+        return list_insert_into_rows_at_positions(&positions, table_stats, dump_reader);
+    }
+
+    // pick a row each time the number of rows to pick (counter * limit / total_rows) grows
+    let mut counter = 0usize;
+    let _ = list_insert_into_rows(dump_reader, table_stats, |row_idx, _| {
+        if (counter + 1) * limit / table_stats.total_rows > counter * limit / table_stats.total_rows
+        {
+            insert_into_rows.push(row_idx);
+        }
+
+        counter += 1;
+    })?;
+
+    Ok(insert_into_rows)
+}
+
+/// rows at some positions among the rows of a table, the first row is at position 0
+fn list_insert_into_rows_at_positions<R: Read>(
+    positions: &HashSet<usize>,
+    table_stats: &TableStats,
+    dump_reader: BufReader<R>,
+) -> Result<Vec<RowIndex>, Error> {
+    let mut insert_into_rows = vec![];
+
+    let mut counter = 0usize;
+    let _ = list_insert_into_rows(dump_reader, table_stats, |row_idx, _| {
+        if positions.contains(&counter) {
+            insert_into_rows.push(row_idx);
+        }
+
+        counter += 1;
+    })?;
+
+    Ok(insert_into_rows)
+}
+
+fn list_insert_into_rows<R: Read, F: FnMut(RowIndex, &Vec<Token>)>(
+    dump_reader: BufReader<R>,
+    table_stats: &TableStats,
+    mut rows: F,
+) -> Result<(), Error> {
+    let mut query_idx = 0usize;
+    let _ = list_mysql_queries_from_dump_reader(dump_reader, |query| {
+        if query_idx > table_stats.last_insert_into_row_index {
+            // early break to avoid parsing the dump while we have already parsed all the table rows
+            return ListQueryResult::Break;
+        }
+
+        if query_idx >= table_stats.first_insert_into_row_index {
+            let tokens = get_tokens_from_query_str(query);
+
+            if get_insert_into_table_name(&tokens) == Some(table_stats.table.as_str()) {
+                rows(query_idx, &tokens);
+            }
+        }
+
+        query_idx += 1;
+        ListQueryResult::Continue
+    })?;
+
+    Ok(())
+}
+
+/// load the values of all the rows of a table
+fn table_rows<R: Read>(
+    dump_reader: BufReader<R>,
+    table_stats: &TableStats,
+) -> Result<TableRows, Error> {
+    let mut table_rows = TableRows::new(table_stats.columns.clone());
+
+    let _ = list_insert_into_rows(dump_reader, table_stats, |row_idx, tokens| {
+        let values = get_column_values_from_insert_into_query(tokens)
+            .into_iter()
+            .map(|token| match token {
+                Token::Word(word) if word.keyword == Keyword::Null => None,
+                Token::Word(word) => Some(word.value.clone()),
+                Token::SingleQuotedString(value) => Some(value.clone()),
+                Token::Number(value, false) => Some(value.clone()),
+                Token::Number(value, true) => Some(format!("-{}", value)),
+                // other literals, only compared with each other
+                token => Some(format!("{:?}", token)),
+            })
+            .collect::<Vec<_>>();
+
+        table_rows.insert(row_idx, values);
+    })?;
+
+    Ok(table_rows)
+}
+
+fn table_stats_by_table_name<R: Read>(
+    dump_reader: BufReader<R>,
+) -> Result<HashMap<Table, TableStats>, Error> {
+    let mut table_stats_by_table_name = HashMap::<Table, TableStats>::new();
+
+    let mut query_idx = 0usize;
+    let _ = list_mysql_queries_from_dump_reader(dump_reader, |query| {
+        let tokens = get_tokens_from_query_str(query);
+
+        if let Some(table) = get_insert_into_table_name(&tokens) {
+            let table_stats = table_stats_by_table_name
+                .entry(table.to_string())
+                .or_insert_with(|| TableStats {
+                    table: table.to_string(),
+                    // I assume that the INSERT INTO row has all the column set
+                    columns: get_column_names_from_insert_into_query(&tokens)
+                        .iter()
+                        .map(|name| name.to_string())
+                        .collect(),
+                    total_rows: 0,
+                    first_insert_into_row_index: query_idx,
+                    last_insert_into_row_index: query_idx,
+                });
+
+            table_stats.last_insert_into_row_index = query_idx;
+            table_stats.total_rows += 1;
+        }
+
+        query_idx += 1;
+        ListQueryResult::Continue
+    })?;
+
+    Ok(table_stats_by_table_name)
+}
+
+fn get_subset_table_by_database_and_table_name<R: Read>(
+    database: &str,
+    dump_reader: BufReader<R>,
+) -> Result<HashMap<(Database, Table), SubsetTable>, Error> {
+    let mut subset_table_by_database_and_table_name =
+        HashMap::<(Database, Table), SubsetTable>::new();
+
+    list_mysql_queries_from_dump_reader(dump_reader, |query| {
+        let tokens = get_tokens_from_query_str(query);
+
+        if let Some(table) = get_create_table_name(&tokens) {
+            // the foreign keys are in the CREATE TABLE query
+            let relations = get_create_table_foreign_keys(&tokens)
+                .into_iter()
+                .map(|fk| {
+                    SubsetTableRelation::new(
+                        database.to_string(),
+                        fk.to_table,
+                        fk.from_property,
+                        fk.to_property,
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            // add table into index
+            let _ = subset_table_by_database_and_table_name.insert(
+                (database.to_string(), table.to_string()),
+                SubsetTable::new(database.to_string(), table.to_string(), relations),
+            );
+        }
+
+        ListQueryResult::Continue
+    })?;
+
+    Ok(subset_table_by_database_and_table_name)
+}
+
+fn get_create_table_name(tokens: &Vec<Token>) -> Option<&str> {
+    if match_keyword_at_position(Keyword::Create, tokens, 0)
+        && match_keyword_at_position(Keyword::Table, tokens, 2)
+    {
+        return get_single_quoted_string_value_at_position(tokens, 4);
+    }
+
+    None
+}
+
+fn get_insert_into_table_name(tokens: &Vec<Token>) -> Option<&str> {
+    if match_keyword_at_position(Keyword::Insert, tokens, 0)
+        && match_keyword_at_position(Keyword::Into, tokens, 2)
+    {
+        return get_single_quoted_string_value_at_position(tokens, 4);
+    }
+
+    None
+}
+
+/// the foreign keys of a single column of a `CREATE TABLE` query,
+/// e.g. CONSTRAINT `city_ibfk_1` FOREIGN KEY (`CountryCode`) REFERENCES `country` (`Code`)
+fn get_create_table_foreign_keys(tokens: &Vec<Token>) -> Vec<ForeignKey> {
+    if get_create_table_name(tokens).is_none() {
+        return vec![];
+    }
+
+    let tokens = tokens
+        .iter()
+        .filter(|token| !matches!(token, Token::Whitespace(_)))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut foreign_keys = vec![];
+
+    for (idx, _) in tokens.iter().enumerate().filter(|(idx, _)| {
+        match_keyword_at_position(Keyword::Foreign, &tokens, *idx)
+            && match_keyword_at_position(Keyword::Key, &tokens, idx + 1)
+    }) {
+        let from_property = match (tokens.get(idx + 2), tokens.get(idx + 4)) {
+            (Some(Token::LParen), Some(Token::RParen)) => {
+                get_single_quoted_string_value_at_position(&tokens, idx + 3)
+            }
+            // the foreign keys of several columns are not supported
+            _ => None,
+        };
+
+        if !match_keyword_at_position(Keyword::References, &tokens, idx + 5) {
+            continue;
+        }
+
+        let to_property = match (tokens.get(idx + 7), tokens.get(idx + 9)) {
+            (Some(Token::LParen), Some(Token::RParen)) => {
+                get_single_quoted_string_value_at_position(&tokens, idx + 8)
+            }
+            _ => None,
+        };
+
+        let to_table = get_single_quoted_string_value_at_position(&tokens, idx + 6);
+
+        if let (Some(from_property), Some(to_table), Some(to_property)) =
+            (from_property, to_table, to_property)
+        {
+            foreign_keys.push(ForeignKey {
+                from_property: from_property.to_string(),
+                to_table: to_table.to_string(),
+                to_property: to_property.to_string(),
+            });
+        }
+    }
+
+    foreign_keys
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mysql::{get_create_table_foreign_keys, MysqlSubset};
+    use crate::{PassthroughTable, Subset, SubsetOptions, SubsetStrategy};
+    use dump_parser::mysql::get_tokens_from_query_str;
+    use std::collections::HashSet;
+    use std::io::Write;
+
+    const DUMP: &str = r#"/*!40014 SET @OLD_FOREIGN_KEY_CHECKS=@@FOREIGN_KEY_CHECKS, FOREIGN_KEY_CHECKS=0 */;
+DROP TABLE IF EXISTS `countries`;
+CREATE TABLE `countries` (
+  `code` char(2) NOT NULL,
+  `name` varchar(255) NOT NULL,
+  PRIMARY KEY (`code`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+LOCK TABLES `countries` WRITE;
+INSERT INTO `countries` (`code`, `name`) VALUES ('FR','France');
+INSERT INTO `countries` (`code`, `name`) VALUES ('US','United States');
+INSERT INTO `countries` (`code`, `name`) VALUES ('DE','Germany');
+UNLOCK TABLES;
+DROP TABLE IF EXISTS `users`;
+CREATE TABLE `users` (
+  `id` int NOT NULL,
+  `country_code` char(2) DEFAULT NULL,
+  PRIMARY KEY (`id`),
+  CONSTRAINT `users_ibfk_1` FOREIGN KEY (`country_code`) REFERENCES `countries` (`code`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+LOCK TABLES `users` WRITE;
+INSERT INTO `users` (`id`, `country_code`) VALUES (1,'FR');
+INSERT INTO `users` (`id`, `country_code`) VALUES (2,'US');
+INSERT INTO `users` (`id`, `country_code`) VALUES (3,NULL);
+INSERT INTO `users` (`id`, `country_code`) VALUES (4,'FR');
+UNLOCK TABLES;
+DROP TABLE IF EXISTS `orders`;
+CREATE TABLE `orders` (
+  `id` int NOT NULL,
+  `user_id` int NOT NULL,
+  PRIMARY KEY (`id`),
+  CONSTRAINT `orders_ibfk_1` FOREIGN KEY (`user_id`) REFERENCES `users` (`id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+LOCK TABLES `orders` WRITE;
+INSERT INTO `orders` (`id`, `user_id`) VALUES (1,2);
+INSERT INTO `orders` (`id`, `user_id`) VALUES (2,4);
+INSERT INTO `orders` (`id`, `user_id`) VALUES (3,4);
+INSERT INTO `orders` (`id`, `user_id`) VALUES (4,1);
+UNLOCK TABLES;
+"#;
+
+    #[test]
+    fn check_foreign_keys() {
+        let tokens = get_tokens_from_query_str(
+            "CREATE TABLE `city` (
+  `ID` int NOT NULL AUTO_INCREMENT,
+  `CountryCode` char(3) NOT NULL DEFAULT '',
+  PRIMARY KEY (`ID`),
+  KEY `CountryCode` (`CountryCode`),
+  CONSTRAINT `city_ibfk_1` FOREIGN KEY (`CountryCode`) REFERENCES `country` (`Code`)
+) ENGINE=InnoDB AUTO_INCREMENT=4080 DEFAULT CHARSET=latin1;",
+        );
+
+        let foreign_keys = get_create_table_foreign_keys(&tokens);
+        assert_eq!(foreign_keys.len(), 1);
+        assert_eq!(foreign_keys[0].from_property, "CountryCode");
+        assert_eq!(foreign_keys[0].to_table, "country");
+        assert_eq!(foreign_keys[0].to_property, "Code");
+
+        let tokens = get_tokens_from_query_str("CREATE TABLE `country` (`Code` char(3));");
+        assert!(get_create_table_foreign_keys(&tokens).is_empty());
+    }
+
+    #[test]
+    fn check_mysql_subset() {
+        let mut dump = tempfile::NamedTempFile::new().unwrap();
+        dump.write_all(DUMP.as_bytes()).unwrap();
+
+        let s = HashSet::new();
+        let mysql_subset = MysqlSubset::new(
+            dump.path(),
+            vec![SubsetStrategy::random("world", "users", 50)],
+            SubsetOptions::new(&s, None),
+        )
+        .unwrap();
+
+        let mut rows = vec![];
+        mysql_subset
+            .read(|row| rows.push(row.trim().to_string()), |_| {})
+            .unwrap();
+
+        let insert_into_rows = rows
+            .iter()
+            .filter(|row| row.starts_with("INSERT INTO"))
+            .map(|row| row.as_str())
+            .collect::<Vec<_>>();
+
+        // users 2 and 4 are picked, with their countries and their orders
+        assert_eq!(
+            insert_into_rows,
+            vec![
+                "INSERT INTO `countries` (`code`, `name`) VALUES ('FR','France');",
+                "INSERT INTO `countries` (`code`, `name`) VALUES ('US','United States');",
+                "INSERT INTO `users` (`id`, `country_code`) VALUES (2,'US');",
+                "INSERT INTO `users` (`id`, `country_code`) VALUES (4,'FR');",
+                "INSERT INTO `orders` (`id`, `user_id`) VALUES (1,2);",
+                "INSERT INTO `orders` (`id`, `user_id`) VALUES (2,4);",
+                "INSERT INTO `orders` (`id`, `user_id`) VALUES (3,4);",
+            ]
+        );
+
+        // the schema is kept
+        assert_eq!(
+            rows.iter()
+                .filter(|row| row.starts_with("CREATE TABLE"))
+                .count(),
+            3
+        );
+
+        let mut s = HashSet::new();
+        s.insert(PassthroughTable::new("world", "countries"));
+        let mysql_subset = MysqlSubset::new(
+            dump.path(),
+            vec![SubsetStrategy::random("world", "users", 50)],
+            SubsetOptions::new(&s, None),
+        )
+        .unwrap();
+
+        let mut rows = vec![];
+        mysql_subset.read(|row| rows.push(row), |_| {}).unwrap();
+        assert_eq!(
+            rows.iter()
+                .filter(|row| row.contains("INSERT INTO `countries`"))
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn check_mysql_subset_with_seed() {
+        let mut dump = tempfile::NamedTempFile::new().unwrap();
+        dump.write_all(DUMP.as_bytes()).unwrap();
+
+        let s = HashSet::new();
+        let subset_rows = |seed: u64| {
+            let mysql_subset = MysqlSubset::new(
+                dump.path(),
+                vec![SubsetStrategy::limit("world", "orders", 2)],
+                SubsetOptions::new(&s, Some(seed)),
+            )
+            .unwrap();
+
+            let mut rows = vec![];
+            mysql_subset.read(|row| rows.push(row), |_| {}).unwrap();
+            rows
+        };
+
+        // the same seed picks the same rows
+        let rows = subset_rows(42);
+        assert_eq!(
+            rows.iter()
+                .filter(|row| row.contains("INSERT INTO `orders`"))
+                .count(),
+            2
+        );
+        assert_eq!(rows, subset_rows(42));
+    }
+
+    #[test]
+    fn check_mysql_subset_strategies() {
+        let mut dump = tempfile::NamedTempFile::new().unwrap();
+        dump.write_all(DUMP.as_bytes()).unwrap();
+
+        let s = HashSet::new();
+        let mysql_subset = MysqlSubset::new(
+            dump.path(),
+            vec![
+                SubsetStrategy::limit("world", "countries", 1),
+                SubsetStrategy::predicate("world", "orders", "`user_id` = 1"),
+            ],
+            SubsetOptions::new(&s, None),
+        )
+        .unwrap();
+
+        let mut rows = vec![];
+        mysql_subset
+            .read(|row| rows.push(row.trim().to_string()), |_| {})
+            .unwrap();
+
+        let insert_into_rows = rows
+            .iter()
+            .filter(|row| row.starts_with("INSERT INTO"))
+            .map(|row| row.as_str())
+            .collect::<Vec<_>>();
+
+        // a country without users, and the order of user 1 with user 1 and its country
+        assert_eq!(
+            insert_into_rows,
+            vec![
+                "INSERT INTO `countries` (`code`, `name`) VALUES ('FR','France');",
+                "INSERT INTO `countries` (`code`, `name`) VALUES ('DE','Germany');",
+                "INSERT INTO `users` (`id`, `country_code`) VALUES (1,'FR');",
+                "INSERT INTO `orders` (`id`, `user_id`) VALUES (4,1);",
+            ]
+        );
+
+        let mysql_subset = MysqlSubset::new(
+            dump.path(),
+            vec![SubsetStrategy::predicate("world", "orders", "user_id ==")],
+            SubsetOptions::new(&s, None),
+        )
+        .unwrap();
+
+        assert!(mysql_subset.read(|_| {}, |_| {}).is_err());
+    }
+}
@@ -1,23 +1,22 @@
-use crate::dedup::does_line_exist_and_set;
-use crate::postgres::SubsetStrategy::RandomPercent;
+use crate::predicate::Predicate;
+use crate::related_rows::{RelatedRows, RowIndex, TableRows};
 use crate::{
-    utils, PassthroughTable, Progress, Subset, SubsetOptions, SubsetTable, SubsetTableRelation,
+    utils, Database, PassthroughTable, Progress, Subset, SubsetOptions, SubsetStrategy,
+    SubsetTable, SubsetTableRelation, Table,
 };
 use dump_parser::postgres::{
-    get_column_names_from_insert_into_query, get_column_values_str_from_insert_into_query,
-    get_tokens_from_query_str, get_word_value_at_position, match_keyword_at_position,
-    trim_pre_whitespaces, Keyword, Token,
+    get_column_names_from_insert_into_query, get_column_values_from_insert_into_query,
+    get_tokens_from_query_str, get_word_value_at_position, match_keyword_at_position, Keyword,
+    Token,
 };
 use dump_parser::utils::{list_sql_queries_from_dump_reader, ListQueryResult};
-use std::collections::HashMap;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::{BufReader, Error, ErrorKind, Read};
-use std::ops::Index;
+use std::io::{BufReader, Error, Read};
 use std::path::Path;
 
-type Database = String;
-type Table = String;
-
 #[derive(Debug)]
 struct ForeignKey {
     from_database: String,
@@ -37,35 +36,17 @@ struct TableStats {
     last_insert_into_row_index: usize,
 }
 
-pub enum SubsetStrategy<'a> {
-    RandomPercent {
-        database: &'a str,
-        table: &'a str,
-        percent: u8,
-    },
-}
-
-impl<'a> SubsetStrategy<'a> {
-    pub fn random(database: &'a str, table: &'a str, percent: u8) -> Self {
-        RandomPercent {
-            database,
-            table,
-            percent,
-        }
-    }
-}
-
 pub struct PostgresSubset<'a> {
     subset_table_by_database_and_table_name: HashMap<(Database, Table), SubsetTable>,
     dump: &'a Path,
-    subset_strategy: SubsetStrategy<'a>,
+    subset_strategies: Vec<SubsetStrategy<'a>>,
     subset_options: SubsetOptions<'a>,
 }
 
 impl<'a> PostgresSubset<'a> {
     pub fn new(
         dump: &'a Path,
-        subset_strategy: SubsetStrategy<'a>,
+        subset_strategies: Vec<SubsetStrategy<'a>>,
         subset_options: SubsetOptions<'a>,
     ) -> Result<Self, Error> {
         Ok(PostgresSubset {
@@ -73,7 +54,7 @@ impl<'a> PostgresSubset<'a> {
                 BufReader::new(File::open(dump).unwrap()),
             )?,
             dump,
-            subset_strategy,
+            subset_strategies,
             subset_options,
         })
     }
@@ -82,141 +63,74 @@ impl<'a> PostgresSubset<'a> {
         BufReader::new(File::open(self.dump).unwrap())
     }
 
+    /// rows picked by the subset strategies, with their database and table
     fn reference_rows(
         &self,
         table_stats: &HashMap<(Database, Table), TableStats>,
-    ) -> Result<Vec<String>, Error> {
-        match self.subset_strategy {
-            SubsetStrategy::RandomPercent {
-                database,
-                table,
-                percent,
-            } => Ok(list_percent_of_insert_into_rows(
-                percent,
-                table_stats
-                    .get(&(database.to_string(), table.to_string()))
-                    .unwrap(),
-                self.dump_reader(),
-            )?),
-        }
-    }
-
-    fn visits<F: FnMut(String)>(
-        &self,
-        row: String,
-        table_stats: &HashMap<(Database, Table), TableStats>,
-        data: &mut F,
-    ) -> Result<(), Error> {
-        data(format!("{}\n", row));
-
-        // tokenize `INSERT INTO ...` row
-        let row_tokens = get_tokens_from_query_str(row.as_str());
-
-        // find the database and table names from this row
-        let (row_database, row_table) =
-            get_insert_into_database_and_table_name(&row_tokens).unwrap();
-
-        if self.subset_options.passthrough_tables.is_empty()
-            || !self
-                .subset_options
-                .passthrough_tables
-                .contains(&PassthroughTable::new(
-                    row_database.as_str(),
-                    row_table.as_str(),
-                ))
-        {
-            // only insert if the row is not from passthrough tables list
-            // otherwise we'll have duplicated rows
-            data(format!("{}\n", row));
-        }
-
-        // find the subset table from this row
-        let row_subset_table = self
-            .subset_table_by_database_and_table_name
-            .get(&(row_database.to_string(), row_table.to_string()))
-            .unwrap();
-
-        let row_column_names = get_column_names_from_insert_into_query(&row_tokens);
-        let row_column_values = get_column_values_str_from_insert_into_query(&row_tokens);
-
-        for row_relation in &row_subset_table.relations {
-            let column = row_relation.from_property.as_str();
-            // find the value from the current row for the relation column
-            let column_idx = row_column_names.iter().position(|x| *x == column).unwrap(); // FIXME unwrap
-            let value = row_column_values.get(column_idx).unwrap();
-
-            let database_and_table_tuple =
-                (row_relation.database.clone(), row_relation.table.clone());
-
-            // find the table stats for this row
-            let row_relation_table_stats = table_stats.get(&database_and_table_tuple).unwrap();
+    ) -> Result<Vec<(&'a str, &'a str, RowIndex)>, Error> {
+        let mut reference_rows = vec![];
+        // a single generator for all the strategies, so the same seed always picks the same rows
+        let mut rng = self.subset_options.seed.map(ChaCha8Rng::seed_from_u64);
+
+        for subset_strategy in &self.subset_strategies {
+            let (database, table) = (subset_strategy.database(), subset_strategy.table());
+
+            let table_stats = match table_stats.get(&(database.to_string(), table.to_string())) {
+                Some(table_stats) => table_stats,
+                // the table has no rows
+                None => continue,
+            };
 
-            // TODO break acyclic graph
-            let row_clb = |row: &str| match self.visits(row.to_string(), table_stats, data) {
-                Ok(_) => {}
-                Err(err) => {
-                    panic!("{}", err);
+            let rows = match *subset_strategy {
+                SubsetStrategy::RandomPercent { percent, .. } => list_percent_of_insert_into_rows(
+                    percent,
+                    table_stats,
+                    self.dump_reader(),
+                    rng.as_mut(),
+                )?,
+                SubsetStrategy::Limit { limit, .. } => list_limit_of_insert_into_rows(
+                    limit,
+                    table_stats,
+                    self.dump_reader(),
+                    rng.as_mut(),
+                )?,
+                SubsetStrategy::Predicate { predicate, .. } => {
+                    let predicate = Predicate::parse(predicate)?;
+                    table_rows(self.dump_reader(), table_stats)?.rows_matching(&predicate)
                 }
             };
 
-            let _ = filter_insert_into_rows(
-                row_relation.to_property.as_str(),
-                value.as_str(),
-                self.dump_reader(),
-                row_relation_table_stats,
-                row_clb,
-            )?;
+            reference_rows.extend(rows.into_iter().map(|row| (database, table, row)));
         }
 
-        Ok(())
+        Ok(reference_rows)
+    }
+
+    fn is_passthrough_table(&self, database: &str, table: &str) -> bool {
+        self.subset_options
+            .passthrough_tables
+            .contains(&PassthroughTable::new(database, table))
     }
 }
 
 impl<'a> Subset for PostgresSubset<'a> {
     /// Return every subset rows
     /// Algorithm used:
-    /// 1. find the reference table and take the X rows from this table with the appropriate SubsetStrategy
-    /// 2. iterate over each row and their relations (0 to N relations)
-    /// 3. for each rows from each relations, filter on the id from the parent related row id. (equivalent `SELECT * FROM table_1 INNER JOIN ... WHERE table_1.id = 'xxx';`
-    /// 4. do it recursively for table_1.relations[*].relations[*]... but the algo stops when reaching the end or reach a cyclic ref.
+    /// 1. find the reference tables and take the X rows from each of them with their SubsetStrategy
+    /// 2. visit each row and their relations in both directions (0 to N relations):
+    ///    the rows they reference (equivalent `SELECT * FROM table_2 WHERE table_2.id = table_1.table_2_id`)
+    ///    and the rows referencing them (equivalent `SELECT * FROM table_3 WHERE table_3.table_1_id = table_1.id`)
+    /// 3. do it for the related rows as well, until all the related rows are visited. (see `RelatedRows`)
+    /// 4. send the visited rows and all the rows of the passthrough tables, in the order of the dump.
     ///
     /// Notes:
     /// a. the algo must visits all the tables, even the one that has no relations.
     fn read<F: FnMut(String), P: FnMut(Progress)>(
         &self,
-        mut data: F,
-        mut progress: P,
+        data: F,
+        progress: P,
     ) -> Result<(), Error> {
-        let temp_dir = tempfile::tempdir()?;
-
-        let _ = read(
-            self,
-            |line| {
-                if line.contains("INSERT INTO") {
-                    // Dedup INSERT INTO queries
-                    // check if the line has not already been sent
-                    match does_line_exist_and_set(
-                        temp_dir.path(),
-                        &get_insert_into_md5_hash(line.as_str()),
-                        line.as_str(),
-                    ) {
-                        Ok(does_line_exist) => {
-                            if !does_line_exist {
-                                data(line);
-                            }
-                        }
-                        Err(err) => {
-                            panic!("{}", err);
-                        }
-                    }
-                } else {
-                    data(line);
-                }
-            },
-            progress,
-        )?;
-
-        Ok(())
+        read(self, data, progress)
     }
 }
 
@@ -229,7 +143,7 @@ fn read<F: FnMut(String), P: FnMut(Progress)>(
     let rows = postgres_subset.reference_rows(&table_stats)?;
 
     // send schema header
-    let table_stats_values = table_stats.values().collect::<Vec<_>>();
+    let mut table_stats_values = table_stats.values().collect::<Vec<_>>();
     let _ = dump_header(
         postgres_subset.dump_reader(),
         last_header_row_idx(&table_stats_values),
@@ -252,10 +166,19 @@ fn read<F: FnMut(String), P: FnMut(Progress)>(
         last_process_time: 0,
     });
 
-    // send INSERT INTO rows
-    for row in rows {
+    let mut related_rows = RelatedRows::new(
+        &postgres_subset.subset_table_by_database_and_table_name,
+        |database, table| match table_stats.get(&(database.to_string(), table.to_string())) {
+            Some(table_stats) => table_rows(postgres_subset.dump_reader(), table_stats),
+            // the table has no rows
+            None => Ok(TableRows::new(vec![])),
+        },
+    );
+
+    // visit INSERT INTO rows
+    for (database, table, row) in rows {
         let start_time = utils::epoch_millis();
-        let _ = postgres_subset.visits(row, &table_stats, &mut data)?;
+        let _ = related_rows.visit(database, table, row)?;
 
         processed_rows += 1;
 
@@ -267,16 +190,25 @@ fn read<F: FnMut(String), P: FnMut(Progress)>(
         });
     }
 
-    for passthrough_table in postgres_subset.subset_options.passthrough_tables {
+    let rows = related_rows.rows();
+
+    // send INSERT INTO rows
+    table_stats_values.sort_by_key(|table_stats| table_stats.first_insert_into_row_index);
+    for table_stats in &table_stats_values {
         // copy all rows from passthrough tables
-        for table_stats in &table_stats_values {
-            if table_stats.database.as_str() == passthrough_table.database
-                && table_stats.table.as_str() == passthrough_table.table
-            {
-                let _ = list_insert_into_rows(postgres_subset.dump_reader(), table_stats, |row| {
-                    data(row.to_string());
-                })?;
-            }
+        let is_passthrough_table = postgres_subset
+            .is_passthrough_table(table_stats.database.as_str(), table_stats.table.as_str());
+
+        if table_stats.total_rows > 0 {
+            let _ = list_insert_into_rows(
+                postgres_subset.dump_reader(),
+                table_stats,
+                |row_idx, _, row| {
+                    if is_passthrough_table || rows.contains(&row_idx) {
+                        data(row.to_string());
+                    }
+                },
+            )?;
         }
     }
 
@@ -292,21 +224,12 @@ fn read<F: FnMut(String), P: FnMut(Progress)>(
     Ok(())
 }
 
-fn get_insert_into_md5_hash(query: &str) -> String {
-    let tokens = get_tokens_from_query_str(query);
-    let tokens = trim_pre_whitespaces(tokens);
-    let database = get_word_value_at_position(&tokens, 4).unwrap();
-    let table = get_word_value_at_position(&tokens, 6).unwrap();
-    let key = format!("{}-{}", database, table);
-    let digest = md5::compute(key.as_bytes());
-    format!("{:x}", digest)
-}
-
 fn list_percent_of_insert_into_rows<R: Read>(
     percent: u8,
     table_stats: &TableStats,
     dump_reader: BufReader<R>,
-) -> Result<Vec<String>, Error> {
+    rng: Option<&mut ChaCha8Rng>,
+) -> Result<Vec<RowIndex>, Error> {
     let mut insert_into_rows = vec![];
 
     if percent == 0 || table_stats.total_rows == 0 {
@@ -316,12 +239,23 @@ fn list_percent_of_insert_into_rows<R: Read>(
     let percent = if percent > 100 { 100 } else { percent };
 
     let total_rows_to_pick = table_stats.total_rows as f32 * percent as f32 / 100.0;
+
+    if let Some(rng) = rng {
+        let positions = utils::random_positions(
+            rng,
+            table_stats.total_rows,
+            total_rows_to_pick.round() as usize,
+        );
+
+        return list_insert_into_rows_at_positions(&positions, table_stats, dump_reader);
+    }
+
     let modulo = (table_stats.total_rows as f32 / total_rows_to_pick) as usize;
 
     let mut counter = 1usize;
-    let _ = list_insert_into_rows(dump_reader, table_stats, |rows| {
+    let _ = list_insert_into_rows(dump_reader, table_stats, |row_idx, _, _| {
         if counter % modulo == 0 {
-            insert_into_rows.push(rows.to_string());
+            insert_into_rows.push(row_idx);
         }
 
         counter += 1;
@@ -330,67 +264,64 @@ fn list_percent_of_insert_into_rows<R: Read>(
     Ok(insert_into_rows)
 }
 
-fn list_insert_into_rows<R: Read, F: FnMut(&str)>(
-    dump_reader: BufReader<R>,
+fn list_limit_of_insert_into_rows<R: Read>(
+    limit: usize,
     table_stats: &TableStats,
-    mut rows: F,
-) -> Result<(), Error> {
-    let mut query_idx = 0usize;
-    let _ = list_sql_queries_from_dump_reader(dump_reader, |query| {
-        let mut query_res = ListQueryResult::Continue;
+    dump_reader: BufReader<R>,
+    rng: Option<&mut ChaCha8Rng>,
+) -> Result<Vec<RowIndex>, Error> {
+    let mut insert_into_rows = vec![];
 
-        // optimization to avoid tokenizing unnecessary queries -- it's a 13x optim (benched)
-        if query_idx >= table_stats.first_insert_into_row_index
-            && query_idx <= table_stats.last_insert_into_row_index
-        {
-            let tokens = get_tokens_from_query_str(query);
-            let tokens = trim_tokens(&tokens, Keyword::Insert);
+    if limit == 0 || table_stats.total_rows == 0 {
+        return Ok(insert_into_rows);
+    }
 
-            if match_keyword_at_position(Keyword::Insert, &tokens, 0)
-                && match_keyword_at_position(Keyword::Into, &tokens, 2)
-                && get_word_value_at_position(&tokens, 4) == Some(table_stats.database.as_str())
-                && get_word_value_at_position(&tokens, 6) == Some(table_stats.table.as_str())
-            {
-                rows(query.as_ref());
-            }
+    let limit = limit.min(table_stats.total_rows);
+
+    if let Some(rng) = rng {
+        let positions = utils::random_positions(rng, table_stats.total_rows, limit);
+        return list_insert_into_rows_at_positions(&positions, table_stats, dump_reader);
+    }
+
+    // pick a row each time the number of rows to pick (counter * limit / total_rows) grows
+    let mut counter = 0usize;
+    let _ = list_insert_into_rows(dump_reader, table_stats, |row_idx, _, _| {
+        if (counter + 1) * limit / table_stats.total_rows > counter * limit / table_stats.total_rows
+        {
+            insert_into_rows.push(row_idx);
         }
 
-        if query_idx > table_stats.last_insert_into_row_index {
-            // early break to avoid parsing the dump while we have already parsed all the table rows
-            query_res = ListQueryResult::Break;
+        counter += 1;
+    })?;
+
+    Ok(insert_into_rows)
+}
+
+/// rows at some positions among the rows of a table, the first row is at position 0
+fn list_insert_into_rows_at_positions<R: Read>(
+    positions: &HashSet<usize>,
+    table_stats: &TableStats,
+    dump_reader: BufReader<R>,
+) -> Result<Vec<RowIndex>, Error> {
+    let mut insert_into_rows = vec![];
+
+    let mut counter = 0usize;
+    let _ = list_insert_into_rows(dump_reader, table_stats, |row_idx, _, _| {
+        if positions.contains(&counter) {
+            insert_into_rows.push(row_idx);
         }
 
-        query_idx += 1;
-        query_res
+        counter += 1;
     })?;
 
-    Ok(())
+    Ok(insert_into_rows)
 }
 
-fn filter_insert_into_rows<R: Read, F: FnMut(&str)>(
-    column: &str,
-    value: &str,
+fn list_insert_into_rows<R: Read, F: FnMut(RowIndex, &Vec<Token>, &str)>(
     dump_reader: BufReader<R>,
     table_stats: &TableStats,
     mut rows: F,
 ) -> Result<(), Error> {
-    let column_idx = match table_stats
-        .columns
-        .iter()
-        .position(|r| r.as_str() == column)
-    {
-        Some(idx) => idx,
-        None => {
-            return Err(Error::new(
-                ErrorKind::Other,
-                format!(
-                    "table {} does not contain column {}",
-                    table_stats.table, column
-                ),
-            ));
-        }
-    };
-
     let mut query_idx = 0usize;
     let _ = list_sql_queries_from_dump_reader(dump_reader, |query| {
         let mut query_res = ListQueryResult::Continue;
@@ -407,11 +338,7 @@ fn filter_insert_into_rows<R: Read, F: FnMut(&str)>(
                 && get_word_value_at_position(&tokens, 4) == Some(table_stats.database.as_str())
                 && get_word_value_at_position(&tokens, 6) == Some(table_stats.table.as_str())
             {
-                let column_values = get_column_values_str_from_insert_into_query(&tokens);
-
-                if *column_values.index(column_idx) == value {
-                    rows(query)
-                }
+                rows(query_idx, &tokens, query.as_ref());
             }
         }
 
@@ -427,6 +354,33 @@ fn filter_insert_into_rows<R: Read, F: FnMut(&str)>(
     Ok(())
 }
 
+/// load the values of all the rows of a table
+fn table_rows<R: Read>(
+    dump_reader: BufReader<R>,
+    table_stats: &TableStats,
+) -> Result<TableRows, Error> {
+    let mut table_rows = TableRows::new(table_stats.columns.clone());
+
+    let _ = list_insert_into_rows(dump_reader, table_stats, |row_idx, tokens, _| {
+        let values = get_column_values_from_insert_into_query(tokens)
+            .into_iter()
+            .map(|token| match token {
+                Token::Word(word) if word.keyword == Keyword::Null => None,
+                Token::Word(word) => Some(word.value.clone()),
+                Token::SingleQuotedString(value) => Some(value.clone()),
+                Token::Number(value, false) => Some(value.clone()),
+                Token::Number(value, true) => Some(format!("-{}", value)),
+                // other literals, only compared with each other
+                token => Some(format!("{:?}", token)),
+            })
+            .collect::<Vec<_>>();
+
+        table_rows.insert(row_idx, values);
+    })?;
+
+    Ok(table_rows)
+}
+
 /// return the last row index from dump header (with generated table stats)
 fn last_header_row_idx(table_stats_values: &Vec<&TableStats>) -> usize {
     table_stats_values
@@ -639,26 +593,6 @@ fn get_create_table_database_and_table_name(tokens: &Vec<Token>) -> Option<(Data
     None
 }
 
-fn get_insert_into_database_and_table_name(tokens: &Vec<Token>) -> Option<(Database, Table)> {
-    let tokens = trim_tokens(&tokens, Keyword::Insert);
-
-    if tokens.is_empty() {
-        return None;
-    }
-
-    if match_keyword_at_position(Keyword::Insert, &tokens, 0)
-        && match_keyword_at_position(Keyword::Into, &tokens, 2)
-    {
-        if let Some(database) = get_word_value_at_position(&tokens, 4) {
-            if let Some(table) = get_word_value_at_position(&tokens, 6) {
-                return Some((database.to_string(), table.to_string()));
-            }
-        }
-    }
-
-    None
-}
-
 fn get_alter_table_foreign_key(tokens: &Vec<Token>) -> Option<ForeignKey> {
     let tokens = trim_tokens(&tokens, Keyword::Alter);
 
@@ -736,14 +670,17 @@ fn get_alter_table_foreign_key(tokens: &Vec<Token>) -> Option<ForeignKey> {
 #[cfg(test)]
 mod tests {
     use crate::postgres::{
-        dump_footer, dump_header, filter_insert_into_rows, first_footer_row_idx,
-        get_alter_table_foreign_key, get_create_table_database_and_table_name,
-        get_subset_table_by_database_and_table_name, last_header_row_idx,
-        list_percent_of_insert_into_rows, table_stats_by_database_and_table_name, PostgresSubset,
-        SubsetStrategy,
+        dump_footer, dump_header, first_footer_row_idx, get_alter_table_foreign_key,
+        get_create_table_database_and_table_name, get_subset_table_by_database_and_table_name,
+        last_header_row_idx, list_limit_of_insert_into_rows, list_percent_of_insert_into_rows,
+        table_rows, table_stats_by_database_and_table_name, PostgresSubset, SubsetStrategy,
     };
     use crate::{PassthroughTable, Subset, SubsetOptions};
-    use dump_parser::postgres::Tokenizer;
+    use dump_parser::postgres::{
+        get_column_values_str_from_insert_into_query, get_tokens_from_query_str, Tokenizer,
+    };
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
     use std::collections::HashSet;
     use std::fs::File;
     use std::io::BufReader;
@@ -845,31 +782,62 @@ ALTER TABLE ONLY public.territories
             .get(&("public".to_string(), "order_details".to_string()))
             .unwrap();
 
-        let rows = list_percent_of_insert_into_rows(5, first_table_stats, dump_reader()).unwrap();
+        let rows =
+            list_percent_of_insert_into_rows(5, first_table_stats, dump_reader(), None).unwrap();
+
+        assert!(rows.len() < first_table_stats.total_rows);
 
-        assert!(rows.len() < first_table_stats.total_rows)
+        // the same rows are picked with the same seed
+        let seeded_rows = |seed: u64| {
+            list_percent_of_insert_into_rows(
+                5,
+                first_table_stats,
+                dump_reader(),
+                Some(&mut ChaCha8Rng::seed_from_u64(seed)),
+            )
+            .unwrap()
+        };
+
+        let rows = seeded_rows(42);
+        assert_eq!(
+            rows.len(),
+            (first_table_stats.total_rows as f32 * 0.05).round() as usize
+        );
+        assert_eq!(rows, seeded_rows(42));
+        assert_ne!(rows, seeded_rows(43));
     }
 
     #[test]
-    fn check_filter_insert_into_rows() {
+    fn check_limit_of_rows() {
         let table_stats = table_stats_by_database_and_table_name(dump_reader()).unwrap();
         let first_table_stats = table_stats
             .get(&("public".to_string(), "order_details".to_string()))
             .unwrap();
 
-        let mut found_rows = vec![];
-        filter_insert_into_rows(
-            "product_id",
-            "11",
-            dump_reader(),
+        let rows =
+            list_limit_of_insert_into_rows(100, first_table_stats, dump_reader(), None).unwrap();
+        assert_eq!(rows.len(), 100);
+
+        let rows = list_limit_of_insert_into_rows(
+            100,
             first_table_stats,
-            |row| {
-                found_rows.push(row.to_string());
-            },
+            dump_reader(),
+            Some(&mut ChaCha8Rng::seed_from_u64(42)),
         )
         .unwrap();
+        assert_eq!(rows.len(), 100);
+    }
+
+    #[test]
+    fn check_table_rows() {
+        let table_stats = table_stats_by_database_and_table_name(dump_reader()).unwrap();
+        let first_table_stats = table_stats
+            .get(&("public".to_string(), "order_details".to_string()))
+            .unwrap();
 
-        assert_eq!(found_rows.len(), 38)
+        let mut table_rows = table_rows(dump_reader(), first_table_stats).unwrap();
+
+        assert_eq!(table_rows.rows_with_value("product_id", "11").len(), 38)
     }
 
     #[test]
@@ -922,8 +890,8 @@ ALTER TABLE ONLY public.territories
 
         let postgres_subset = PostgresSubset::new(
             path.as_path(),
-            SubsetStrategy::random("public", "orders", 50),
-            SubsetOptions::new(&s),
+            vec![SubsetStrategy::random("public", "orders", 50)],
+            SubsetOptions::new(&s, None),
         )
         .unwrap();
 
@@ -972,5 +940,54 @@ ALTER TABLE ONLY public.territories
                 .count(),
             51
         );
+        // the order details of the orders are in the subset, with their products
+        let values = |table: &str, column_idx: usize| {
+            rows.iter()
+                .filter(|x| x.contains(format!("INSERT INTO public.{} ", table).as_str()))
+                .map(|x| {
+                    let tokens = get_tokens_from_query_str(x.as_str());
+                    get_column_values_str_from_insert_into_query(&tokens)[column_idx].clone()
+                })
+                .collect::<HashSet<_>>()
+        };
+
+        let order_ids = values("orders", 0);
+        assert_eq!(order_ids.len(), 415);
+        assert_eq!(values("order_details", 0), order_ids);
+        assert_eq!(values("order_details", 1), values("products", 0));
+    }
+
+    #[test]
+    fn check_postgres_subset_strategies() {
+        let path = dump_path();
+        let s = HashSet::new();
+
+        let postgres_subset = PostgresSubset::new(
+            path.as_path(),
+            vec![
+                SubsetStrategy::limit("public", "orders", 10),
+                SubsetStrategy::predicate("public", "orders", "order_date >= '1998-05-01'"),
+            ],
+            SubsetOptions::new(&s, None),
+        )
+        .unwrap();
+
+        let mut rows = vec![];
+        postgres_subset.read(|row| rows.push(row), |_| {}).unwrap();
+
+        let orders = rows
+            .iter()
+            .filter(|x| x.contains("INSERT INTO public.orders "))
+            .collect::<Vec<_>>();
+
+        // 14 orders since May 1998, and 10 orders spread over the table - the last one is since May 1998
+        assert_eq!(orders.len(), 23);
+        assert_eq!(
+            orders.iter().filter(|x| x.contains("'1998-05-0")).count(),
+            14
+        );
+        assert!(rows
+            .iter()
+            .any(|x| x.contains("INSERT INTO public.order_details ")));
     }
 }
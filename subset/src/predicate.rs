@@ -0,0 +1,433 @@
+use std::cmp::Ordering;
+use std::io::{Error, ErrorKind};
+use std::iter::Peekable;
+use std::vec::IntoIter;
+
+use chrono::{Duration, Months, NaiveDate, NaiveDateTime, Utc};
+use dump_parser::postgres::{Token, Tokenizer};
+
+/// `where` predicate of a subset strategy, checked on the values of the rows of the dump,
+/// e.g. `created_at > now() - interval '30 days' AND country = 'FR'`.
+///
+/// The conditions are joined with `AND`, each of them compares a column with:
+/// - a number, a string, `TRUE` or `FALSE`
+/// - `now()`, `current_timestamp` or `current_date`, plus or minus intervals (`interval '30 days'` or `INTERVAL 30 DAY`)
+/// - `IS NULL` and `IS NOT NULL`
+///
+/// A condition on a `NULL` value is false, except `IS NULL`.
+#[derive(Debug, PartialEq)]
+pub struct Predicate {
+    conditions: Vec<Condition>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Condition {
+    column: String,
+    operator: Operator,
+    value: Value,
+}
+
+#[derive(Debug, PartialEq)]
+enum Operator {
+    Eq,
+    Neq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    IsNull,
+    IsNotNull,
+}
+
+#[derive(Debug, PartialEq)]
+enum Value {
+    Null,
+    Boolean(bool),
+    Number(f64),
+    Text(String),
+    Time(NaiveDateTime),
+}
+
+impl Predicate {
+    pub fn parse(predicate: &str) -> Result<Self, Error> {
+        let error = |message: String| {
+            Error::new(
+                ErrorKind::Other,
+                format!("invalid predicate '{}': {}", predicate, message),
+            )
+        };
+
+        // MySQL identifiers are quoted with backticks
+        let tokens = Tokenizer::new(predicate.replace('`', "\"").as_str())
+            .tokenize()
+            .map_err(|err| error(err.message))?
+            .into_iter()
+            .filter(|token| !matches!(token, Token::Whitespace(_) | Token::EOF))
+            .collect::<Vec<_>>();
+
+        let mut tokens = tokens.into_iter().peekable();
+        let mut conditions = vec![];
+
+        loop {
+            conditions.push(parse_condition(&mut tokens).map_err(error)?);
+
+            match tokens.next() {
+                None => break,
+                Some(Token::SemiColon) if tokens.peek().is_none() => break,
+                Some(token) if is_word(&token, "AND") => continue,
+                Some(token) => return Err(error(format!("unexpected {:?}", token))),
+            }
+        }
+
+        Ok(Predicate { conditions })
+    }
+
+    /// true if the values of a row match all the conditions, `value` returns the value of a column
+    pub fn matches<'v, F: Fn(&str) -> Option<&'v str>>(&self, value: F) -> bool {
+        self.conditions
+            .iter()
+            .all(|condition| condition.matches(value(condition.column.as_str())))
+    }
+}
+
+impl Condition {
+    fn matches(&self, value: Option<&str>) -> bool {
+        let value = match (&self.operator, value) {
+            (Operator::IsNull, value) => return value.is_none(),
+            (Operator::IsNotNull, value) => return value.is_some(),
+            (_, None) => return false,
+            (_, Some(value)) => value,
+        };
+
+        let ordering = match compare(value, &self.value) {
+            Some(ordering) => ordering,
+            // values of different types
+            None => return false,
+        };
+
+        match self.operator {
+            Operator::Eq => ordering == Ordering::Equal,
+            Operator::Neq => ordering != Ordering::Equal,
+            Operator::Lt => ordering == Ordering::Less,
+            Operator::LtEq => ordering != Ordering::Greater,
+            Operator::Gt => ordering == Ordering::Greater,
+            Operator::GtEq => ordering != Ordering::Less,
+            Operator::IsNull | Operator::IsNotNull => unreachable!(),
+        }
+    }
+}
+
+/// compare the value of a column with the value of a condition
+fn compare(value: &str, other: &Value) -> Option<Ordering> {
+    match other {
+        Value::Null => None,
+        Value::Boolean(other) => parse_boolean(value).map(|value| value.cmp(other)),
+        Value::Number(other) => value.parse::<f64>().ok()?.partial_cmp(other),
+        Value::Time(other) => parse_time(value).map(|value| value.cmp(other)),
+        Value::Text(other) => match (parse_time(value), parse_time(other)) {
+            (Some(value), Some(other)) => Some(value.cmp(&other)),
+            _ => Some(value.cmp(other.as_str())),
+        },
+    }
+}
+
+fn parse_boolean(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "t" | "1" => Some(true),
+        "false" | "f" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// parse a date or a timestamp, in UTC if it has a time zone
+fn parse_time(value: &str) -> Option<NaiveDateTime> {
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(time.naive_utc());
+    }
+
+    if let Ok(time) = chrono::DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f%#z") {
+        return Some(time.naive_utc());
+    }
+
+    for format in ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"] {
+        if let Ok(time) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(time);
+        }
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+}
+
+fn is_word(token: &Token, value: &str) -> bool {
+    match token {
+        Token::Word(word) => word.quote_style.is_none() && word.value.eq_ignore_ascii_case(value),
+        _ => false,
+    }
+}
+
+fn parse_condition(tokens: &mut Peekable<IntoIter<Token>>) -> Result<Condition, String> {
+    let mut column = match tokens.next() {
+        Some(Token::Word(word)) => word.value,
+        token => return Err(format!("a column is expected, got {}", display(token))),
+    };
+
+    // the table of the column is not needed, e.g. `orders.created_at`
+    while tokens.peek() == Some(&Token::Period) {
+        let _ = tokens.next();
+        column = match tokens.next() {
+            Some(Token::Word(word)) => word.value,
+            token => return Err(format!("a column is expected, got {}", display(token))),
+        };
+    }
+
+    let operator = match tokens.next() {
+        Some(Token::Eq) | Some(Token::DoubleEq) => Operator::Eq,
+        Some(Token::Neq) => Operator::Neq,
+        Some(Token::Lt) => Operator::Lt,
+        Some(Token::LtEq) => Operator::LtEq,
+        Some(Token::Gt) => Operator::Gt,
+        Some(Token::GtEq) => Operator::GtEq,
+        Some(token) if is_word(&token, "IS") => {
+            let operator = match tokens.peek() {
+                Some(token) if is_word(token, "NOT") => {
+                    let _ = tokens.next();
+                    Operator::IsNotNull
+                }
+                _ => Operator::IsNull,
+            };
+
+            return match tokens.next() {
+                Some(token) if is_word(&token, "NULL") => Ok(Condition {
+                    column,
+                    operator,
+                    value: Value::Null,
+                }),
+                token => Err(format!("NULL is expected, got {}", display(token))),
+            };
+        }
+        token => return Err(format!("an operator is expected, got {}", display(token))),
+    };
+
+    Ok(Condition {
+        column,
+        operator,
+        value: parse_value(tokens)?,
+    })
+}
+
+fn parse_value(tokens: &mut Peekable<IntoIter<Token>>) -> Result<Value, String> {
+    let token = tokens.next();
+
+    let time = match token {
+        Some(Token::Number(number, _)) => return parse_number(number.as_str(), false),
+        Some(Token::Minus) => {
+            return match tokens.next() {
+                Some(Token::Number(number, _)) => parse_number(number.as_str(), true),
+                token => Err(format!("a number is expected, got {}", display(token))),
+            }
+        }
+        Some(Token::SingleQuotedString(value)) => return Ok(Value::Text(value)),
+        Some(ref token) if is_word(token, "TRUE") => return Ok(Value::Boolean(true)),
+        Some(ref token) if is_word(token, "FALSE") => return Ok(Value::Boolean(false)),
+        Some(ref token) if is_word(token, "NULL") => {
+            return Err("NULL is only compared with IS NULL or IS NOT NULL".to_string())
+        }
+        Some(ref token) if is_word(token, "NOW") || is_word(token, "CURRENT_TIMESTAMP") => {
+            if tokens.peek() == Some(&Token::LParen) {
+                let _ = tokens.next();
+                match tokens.next() {
+                    Some(Token::RParen) => {}
+                    token => return Err(format!("')' is expected, got {}", display(token))),
+                }
+            }
+
+            Utc::now().naive_utc()
+        }
+        Some(ref token) if is_word(token, "CURRENT_DATE") => Utc::now()
+            .date_naive()
+            .and_hms_opt(0, 0, 0)
+            .ok_or_else(|| "invalid current date".to_string())?,
+        token => return Err(format!("a value is expected, got {}", display(token))),
+    };
+
+    parse_intervals(tokens, time).map(Value::Time)
+}
+
+fn parse_number(number: &str, negative: bool) -> Result<Value, String> {
+    let number = number
+        .parse::<f64>()
+        .map_err(|_| format!("invalid number {}", number))?;
+
+    Ok(Value::Number(if negative { -number } else { number }))
+}
+
+/// add or subtract the intervals following a time, e.g. `- interval '1 month 2 days'`
+fn parse_intervals(
+    tokens: &mut Peekable<IntoIter<Token>>,
+    mut time: NaiveDateTime,
+) -> Result<NaiveDateTime, String> {
+    while let Some(Token::Plus) | Some(Token::Minus) = tokens.peek() {
+        let negative = tokens.next() == Some(Token::Minus);
+
+        match tokens.next() {
+            Some(token) if is_word(&token, "INTERVAL") => {}
+            token => return Err(format!("INTERVAL is expected, got {}", display(token))),
+        }
+
+        let interval = match tokens.next() {
+            // PostgreSQL: interval '30 days'
+            Some(Token::SingleQuotedString(interval)) => interval,
+            // MySQL: INTERVAL 30 DAY
+            Some(Token::Number(number, _)) => match tokens.next() {
+                Some(Token::Word(unit)) => format!("{} {}", number, unit.value),
+                token => return Err(format!("a unit is expected, got {}", display(token))),
+            },
+            token => return Err(format!("an interval is expected, got {}", display(token))),
+        };
+
+        let parts = interval.split_whitespace().collect::<Vec<_>>();
+        if parts.is_empty() || parts.len() % 2 != 0 {
+            return Err(format!("invalid interval '{}'", interval));
+        }
+
+        for part in parts.chunks(2) {
+            let amount = part[0]
+                .parse::<u32>()
+                .map_err(|_| format!("invalid interval '{}'", interval))?;
+
+            time = add_interval(time, amount, part[1], negative)
+                .ok_or_else(|| format!("invalid interval '{}'", interval))?;
+        }
+    }
+
+    Ok(time)
+}
+
+fn add_interval(
+    time: NaiveDateTime,
+    amount: u32,
+    unit: &str,
+    negative: bool,
+) -> Option<NaiveDateTime> {
+    let unit = unit.to_lowercase();
+    let unit = unit.strip_suffix('s').unwrap_or(unit.as_str());
+
+    let duration = match unit {
+        "second" => Duration::seconds(amount as i64),
+        "minute" => Duration::minutes(amount as i64),
+        "hour" => Duration::hours(amount as i64),
+        "day" => Duration::days(amount as i64),
+        "week" => Duration::weeks(amount as i64),
+        "month" | "year" => {
+            let months = Months::new(if unit == "year" { amount * 12 } else { amount });
+            return if negative {
+                time.checked_sub_months(months)
+            } else {
+                time.checked_add_months(months)
+            };
+        }
+        _ => return None,
+    };
+
+    if negative {
+        time.checked_sub_signed(duration)
+    } else {
+        time.checked_add_signed(duration)
+    }
+}
+
+fn display(token: Option<Token>) -> String {
+    match token {
+        Some(token) => format!("{:?}", token),
+        None => "the end of the predicate".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::Predicate;
+
+    fn matches(predicate: &str, row: &[(&str, Option<&str>)]) -> bool {
+        Predicate::parse(predicate).unwrap().matches(|column| {
+            row.iter()
+                .find(|(name, _)| *name == column)
+                .and_then(|(_, value)| *value)
+        })
+    }
+
+    #[test]
+    fn compare_values() {
+        let row = [
+            ("id", Some("42")),
+            ("country", Some("FR")),
+            ("active", Some("t")),
+            ("deleted_at", None),
+            ("created_at", Some("2022-05-01 10:00:00+00")),
+        ];
+
+        for (predicate, expected) in [
+            ("id = 42", true),
+            ("id > 41.5 AND id <= 42", true),
+            ("id != 42", false),
+            ("id > -1", true),
+            ("country = 'FR'", true),
+            ("\"country\" <> 'FR'", false),
+            ("`country` = 'FR' and id >= 43", false),
+            ("orders.country = 'FR'", true),
+            ("active = TRUE", true),
+            ("active = false", false),
+            ("deleted_at IS NULL", true),
+            ("deleted_at IS NOT NULL", false),
+            ("deleted_at = 1", false),
+            ("deleted_at != 1", false),
+            ("created_at IS NOT NULL", true),
+            ("created_at > '2022-04-30'", true),
+            ("created_at < '2022-05-01T09:00:00Z'", false),
+            ("created_at > now() - interval '30 days'", false),
+            ("created_at < NOW() - INTERVAL 1 YEAR", true),
+            ("created_at > CURRENT_DATE - INTERVAL 10 YEAR", true),
+            // the column does not exist, its value is NULL
+            ("updated_at > 1", false),
+            ("updated_at IS NULL;", true),
+        ] {
+            assert_eq!(matches(predicate, &row), expected, "{}", predicate);
+        }
+    }
+
+    #[test]
+    fn compare_recent_times() {
+        let recent = (Utc::now().naive_utc() - Duration::days(2))
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string();
+        let row = [("created_at", Some(recent.as_str()))];
+
+        assert!(matches("created_at > now() - interval '1 week'", &row));
+        assert!(matches(
+            "created_at > current_timestamp - interval '1 day 12 hours' - interval '13 hours'",
+            &row
+        ));
+        assert!(!matches("created_at > now() - interval '1 day'", &row));
+        assert!(matches("created_at < now() + interval '1 month'", &row));
+    }
+
+    #[test]
+    fn invalid_predicates() {
+        for predicate in [
+            "",
+            "id",
+            "id = ",
+            "id = NULL",
+            "id IS 1",
+            "id = 1 OR id = 2",
+            "created_at > now() - '1 day'",
+            "created_at > now() - interval '1 fortnight'",
+            "created_at > now() - interval 'a day'",
+        ] {
+            assert!(Predicate::parse(predicate).is_err(), "{}", predicate);
+        }
+    }
+}
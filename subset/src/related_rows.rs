@@ -0,0 +1,318 @@
+use std::collections::{HashMap, HashSet};
+use std::io::Error;
+
+use crate::predicate::Predicate;
+use crate::{Database, SubsetTable, SubsetTableRelation, Table};
+
+/// Query index of a row, its position in the dump
+pub type RowIndex = usize;
+
+/// Values of the rows of a table, by the query index of the rows. A `NULL` value is `None`.
+pub struct TableRows {
+    columns: Vec<String>,
+    values: HashMap<RowIndex, Vec<Option<String>>>,
+    /// row indexes by value, by column index - built the first time a column is looked up
+    indexes: HashMap<usize, HashMap<String, Vec<RowIndex>>>,
+}
+
+impl TableRows {
+    pub fn new(columns: Vec<String>) -> Self {
+        TableRows {
+            columns: columns.iter().map(|column| unquote(column)).collect(),
+            values: HashMap::new(),
+            indexes: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, row: RowIndex, values: Vec<Option<String>>) {
+        let _ = self.values.insert(row, values);
+    }
+
+    fn column_index(&self, column: &str) -> Option<usize> {
+        let column = unquote(column);
+        self.columns.iter().position(|name| *name == column)
+    }
+
+    /// value of a column of a row, None if the value is NULL or the column does not exist
+    fn value(&self, row: RowIndex, column: &str) -> Option<&String> {
+        let column_idx = self.column_index(column)?;
+        self.values.get(&row)?.get(column_idx)?.as_ref()
+    }
+
+    /// indexes of the rows with this value in a column
+    pub fn rows_with_value(&mut self, column: &str, value: &str) -> Vec<RowIndex> {
+        let column_idx = match self.column_index(column) {
+            Some(column_idx) => column_idx,
+            None => return vec![],
+        };
+
+        let values = &self.values;
+        let index = self.indexes.entry(column_idx).or_insert_with(|| {
+            let mut index = HashMap::<String, Vec<RowIndex>>::new();
+            for (row, row_values) in values {
+                if let Some(Some(value)) = row_values.get(column_idx) {
+                    index.entry(value.clone()).or_default().push(*row);
+                }
+            }
+
+            index
+        });
+
+        index.get(value).cloned().unwrap_or_default()
+    }
+
+    /// indexes of the rows matching a predicate, in the order of the dump
+    pub fn rows_matching(&self, predicate: &Predicate) -> Vec<RowIndex> {
+        let mut rows = self
+            .values
+            .keys()
+            .filter(|row| {
+                predicate.matches(|column| self.value(**row, column).map(|value| value.as_str()))
+            })
+            .copied()
+            .collect::<Vec<_>>();
+
+        rows.sort_unstable();
+        rows
+    }
+}
+
+fn unquote(name: &str) -> String {
+    name.trim_matches(|c| c == '"' || c == '`').to_string()
+}
+
+/// Walk the foreign keys from the rows picked by a subset strategy, so the subset has no broken foreign key:
+/// - the rows referenced by a row are visited, and the rows they reference, etc.
+/// - the rows referencing a picked row are visited as well, with the rows referencing them, etc.
+///   but not the rows referencing a row only visited because it is referenced,
+///   otherwise the whole database would be visited through the lookup tables.
+///
+/// The rows of a table are loaded in memory the first time the table is visited.
+pub struct RelatedRows<'a, L>
+where
+    L: FnMut(&str, &str) -> Result<TableRows, Error>,
+{
+    subset_tables: &'a HashMap<(Database, Table), SubsetTable>,
+    /// the relations of the tables referencing a table, from the referenced table
+    referencing_relations: HashMap<(Database, Table), Vec<SubsetTableRelation>>,
+    load_table_rows: L,
+    tables: HashMap<(Database, Table), TableRows>,
+    /// rows visited with the rows they reference
+    referenced_rows: HashSet<(Database, Table, RowIndex)>,
+    /// rows visited with the rows they reference and the rows referencing them
+    referencing_rows: HashSet<(Database, Table, RowIndex)>,
+}
+
+impl<'a, L> RelatedRows<'a, L>
+where
+    L: FnMut(&str, &str) -> Result<TableRows, Error>,
+{
+    pub fn new(
+        subset_tables: &'a HashMap<(Database, Table), SubsetTable>,
+        load_table_rows: L,
+    ) -> Self {
+        let mut referencing_relations =
+            HashMap::<(Database, Table), Vec<SubsetTableRelation>>::new();
+
+        for subset_table in subset_tables.values() {
+            for relation in &subset_table.relations {
+                referencing_relations
+                    .entry((relation.database.clone(), relation.table.clone()))
+                    .or_default()
+                    .push(SubsetTableRelation::new(
+                        subset_table.database.as_str(),
+                        subset_table.table.as_str(),
+                        relation.to_property.as_str(),
+                        relation.from_property.as_str(),
+                    ));
+            }
+        }
+
+        RelatedRows {
+            subset_tables,
+            referencing_relations,
+            load_table_rows,
+            tables: HashMap::new(),
+            referenced_rows: HashSet::new(),
+            referencing_rows: HashSet::new(),
+        }
+    }
+
+    /// visit a row picked by the subset strategy, and all its related rows
+    pub fn visit(&mut self, database: &str, table: &str, row: RowIndex) -> Result<(), Error> {
+        // (database, table, row, visit the rows referencing it)
+        let mut rows = vec![(database.to_string(), table.to_string(), row, true)];
+
+        // no recursion: the graph of the relations can be deep and cyclic
+        while let Some((database, table, row, with_referencing_rows)) = rows.pop() {
+            let key = (database, table, row);
+
+            if with_referencing_rows {
+                if !self.referencing_rows.insert(key.clone()) {
+                    continue;
+                }
+            } else if self.referencing_rows.contains(&key)
+                || !self.referenced_rows.insert(key.clone())
+            {
+                continue;
+            }
+
+            let (database, table, row) = key;
+            let table_key = (database, table);
+
+            let mut relations = vec![];
+            if let Some(subset_table) = self.subset_tables.get(&table_key) {
+                for relation in &subset_table.relations {
+                    relations.push((relation.clone(), false));
+                }
+            }
+
+            if with_referencing_rows {
+                for relation in self
+                    .referencing_relations
+                    .get(&table_key)
+                    .into_iter()
+                    .flatten()
+                {
+                    relations.push((relation.clone(), true));
+                }
+            }
+
+            for (relation, with_referencing_rows) in relations {
+                let value = match self
+                    .table_rows(&table_key.0, &table_key.1)?
+                    .value(row, relation.from_property.as_str())
+                {
+                    Some(value) => value.clone(),
+                    // NULL does not reference any row
+                    None => continue,
+                };
+
+                let related_rows = self
+                    .table_rows(&relation.database, &relation.table)?
+                    .rows_with_value(relation.to_property.as_str(), value.as_str());
+
+                for related_row in related_rows {
+                    rows.push((
+                        relation.database.clone(),
+                        relation.table.clone(),
+                        related_row,
+                        with_referencing_rows,
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// query indexes of all the visited rows
+    pub fn rows(&self) -> HashSet<RowIndex> {
+        self.referenced_rows
+            .iter()
+            .chain(self.referencing_rows.iter())
+            .map(|(_, _, row)| *row)
+            .collect()
+    }
+
+    fn table_rows(&mut self, database: &str, table: &str) -> Result<&mut TableRows, Error> {
+        let key = (database.to_string(), table.to_string());
+
+        if !self.tables.contains_key(&key) {
+            let table_rows = (self.load_table_rows)(database, table)?;
+            let _ = self.tables.insert(key.clone(), table_rows);
+        }
+
+        // unwrap is safe: the table rows are loaded above
+        Ok(self.tables.get_mut(&key).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{SubsetTable, SubsetTableRelation};
+
+    use super::{RelatedRows, TableRows};
+
+    fn value(value: &str) -> Option<String> {
+        Some(value.to_string())
+    }
+
+    #[test]
+    fn visit_related_rows() {
+        // orders reference users and products, users reference themselves (referrer)
+        let mut subset_tables = HashMap::new();
+        for subset_table in [
+            SubsetTable::new(
+                "public",
+                "users",
+                vec![SubsetTableRelation::new(
+                    "public",
+                    "users",
+                    "referrer_id",
+                    "id",
+                )],
+            ),
+            SubsetTable::new(
+                "public",
+                "orders",
+                vec![
+                    SubsetTableRelation::new("public", "users", "user_id", "id"),
+                    SubsetTableRelation::new("public", "products", "product_id", "id"),
+                ],
+            ),
+            SubsetTable::new("public", "products", vec![]),
+        ] {
+            let _ = subset_tables.insert(
+                (subset_table.database.clone(), subset_table.table.clone()),
+                subset_table,
+            );
+        }
+
+        let mut related_rows = RelatedRows::new(&subset_tables, |_, table| {
+            let mut table_rows;
+            match table {
+                "users" => {
+                    table_rows = TableRows::new(vec!["id".to_string(), "referrer_id".to_string()]);
+                    table_rows.insert(1, vec![value("1"), None]);
+                    table_rows.insert(2, vec![value("2"), value("1")]);
+                    table_rows.insert(3, vec![value("3"), value("3")]);
+                    table_rows.insert(4, vec![value("4"), None]);
+                }
+                "orders" => {
+                    table_rows = TableRows::new(vec![
+                        "\"id\"".to_string(),
+                        "\"user_id\"".to_string(),
+                        "\"product_id\"".to_string(),
+                    ]);
+                    table_rows.insert(10, vec![value("1"), value("2"), value("1")]);
+                    table_rows.insert(11, vec![value("2"), value("2"), value("1")]);
+                    table_rows.insert(12, vec![value("3"), value("4"), value("1")]);
+                    table_rows.insert(13, vec![value("4"), value("3"), value("2")]);
+                }
+                _ => {
+                    table_rows = TableRows::new(vec!["id".to_string()]);
+                    table_rows.insert(20, vec![value("1")]);
+                    table_rows.insert(21, vec![value("2")]);
+                    table_rows.insert(22, vec![value("3")]);
+                }
+            }
+
+            Ok(table_rows)
+        });
+
+        // user 2, its orders, their product, and the referrer of user 2 but not the orders of the referrer
+        related_rows.visit("public", "users", 2).unwrap();
+        let mut rows = related_rows.rows().into_iter().collect::<Vec<_>>();
+        rows.sort();
+        assert_eq!(rows, vec![1, 2, 10, 11, 20]);
+
+        // user 3 references itself
+        related_rows.visit("public", "users", 3).unwrap();
+        let mut rows = related_rows.rows().into_iter().collect::<Vec<_>>();
+        rows.sort();
+        assert_eq!(rows, vec![1, 2, 3, 10, 11, 13, 20, 21]);
+    }
+}
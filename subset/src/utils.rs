@@ -1,8 +1,19 @@
+use std::collections::HashSet;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use rand::seq::index;
+use rand_chacha::ChaCha8Rng;
+
 pub fn epoch_millis() -> u128 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
         .as_millis()
 }
+
+/// `count` random positions between 0 and `total` (excluded)
+pub fn random_positions(rng: &mut ChaCha8Rng, total: usize, count: usize) -> HashSet<usize> {
+    index::sample(rng, total, count.min(total))
+        .into_iter()
+        .collect()
+}